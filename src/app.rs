@@ -6,15 +6,20 @@ use gpui_component::WindowExt;
 use gpui_component::button::{Button, ButtonVariants};
 use gpui_component::input::{Input, InputEvent, InputState};
 use gpui_component::notification::{Notification, NotificationType};
+use gpui_component::spinner::Spinner;
 use gpui_component::switch::Switch;
+use gpui_component::tooltip::Tooltip;
 use gpui_component::{ActiveTheme, Disableable, Sizable, h_flex, v_flex};
-use log::{debug, error, info};
+use log::{debug, error, info, warn};
 use rust_i18n::t;
 use parking_lot::Mutex;
+use std::cell::RefCell;
 use std::collections::HashSet;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
 use std::sync::Arc;
-use std::time::SystemTime;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant, SystemTime};
 
 /// Settings page tabs
 #[derive(Debug, Clone, Copy, PartialEq, Default)]
@@ -24,16 +29,94 @@ pub enum SettingsPage {
     Conversion,
     Indexing,
     Hotkey,
+    Cleanup,
+    Diagnostics,
+    Advanced,
     About,
 }
 
+impl SettingsPage {
+    /// Sidebar order
+    const ALL: [SettingsPage; 8] = [
+        SettingsPage::General,
+        SettingsPage::Conversion,
+        SettingsPage::Indexing,
+        SettingsPage::Hotkey,
+        SettingsPage::Cleanup,
+        SettingsPage::Diagnostics,
+        SettingsPage::Advanced,
+        SettingsPage::About,
+    ];
+
+    /// Element id for the sidebar tab
+    fn id(&self) -> &'static str {
+        match self {
+            SettingsPage::General => "tab-general",
+            SettingsPage::Conversion => "tab-conversion",
+            SettingsPage::Indexing => "tab-indexing",
+            SettingsPage::Hotkey => "tab-hotkey",
+            SettingsPage::Cleanup => "tab-cleanup",
+            SettingsPage::Diagnostics => "tab-diagnostics",
+            SettingsPage::Advanced => "tab-advanced",
+            SettingsPage::About => "tab-about",
+        }
+    }
+
+    fn title(&self) -> String {
+        match self {
+            SettingsPage::General => t!("settings.tabs.general").to_string(),
+            SettingsPage::Conversion => t!("settings.tabs.conversion").to_string(),
+            SettingsPage::Indexing => t!("settings.tabs.indexing").to_string(),
+            SettingsPage::Hotkey => t!("settings.tabs.hotkey").to_string(),
+            SettingsPage::Cleanup => t!("settings.tabs.cleanup").to_string(),
+            SettingsPage::Diagnostics => t!("settings.tabs.diagnostics").to_string(),
+            SettingsPage::Advanced => t!("settings.tabs.advanced").to_string(),
+            SettingsPage::About => t!("settings.tabs.about").to_string(),
+        }
+    }
+
+    /// Whether the sidebar lists this page
+    fn is_visible(&self, settings: &crate::settings::Settings) -> bool {
+        match self {
+            SettingsPage::Advanced => settings.show_advanced_settings,
+            _ => true,
+        }
+    }
+}
+
+use crate::auto_index::PendingIndex;
 use crate::clipboard;
+use crate::content_date;
 use crate::convert;
+use crate::convert_pool;
+use crate::file_ops::{self, BlockReason, FileOp};
+use crate::i18n_helpers::plural_key;
+use crate::index_schedule;
+use crate::indexer::IndexScope;
+use crate::metadata_strip::{self, FileOutcome, StripReport};
 use crate::organizer;
-use crate::settings::ConversionFormat;
-use crate::thumbnail::ThumbnailCache;
-use crate::ui::gallery;
-use crate::{AppMessage, AppState, set_latest_screenshot};
+use crate::perf;
+use crate::pipeline::{self, Transition};
+use crate::removals;
+use crate::screenshot_list::ScreenshotList;
+use crate::settings::{
+    BadgeContent, ConversionFormat, EscapeAction, GalleryGrouping, IndexSchedule, LayoutSlot, ModelPrewarm,
+    SortMode,
+};
+use crate::shortcuts::{Binding, ShortcutAction, ShortcutRegistry};
+use crate::thumbnail::{PreviewStrip, PreviewStrips, ThumbnailCache};
+use crate::naming;
+use crate::number_format::{format_count, format_file_size};
+use crate::ui::click::ClickTracker;
+use crate::ui::modal::{focus_ring, ring_color, ModalHost, ModalId, ModalKey, ModalSpec};
+use crate::ui::progress::{render_current_file, CurrentFile};
+use crate::ui::settings_focus::{Control, ControlInput, ControlKind, SettingsFocus, SettingsKey};
+use crate::ui::keynav::{self, GridMove};
+use crate::ui::text;
+use crate::ui::{gallery, ContextMenuCommand, Orientation};
+use crate::usage;
+use crate::watcher::WatcherCommand;
+use crate::{AppMessage, AppState, get_latest_screenshots, set_latest_screenshots};
 use fastembed;
 
 /// App version
@@ -49,6 +132,166 @@ static PREWARMED_TEXT_MODEL: parking_lot::Mutex<Option<Arc<Mutex<fastembed::Text
 static PREWARMED_VISION_MODEL: parking_lot::Mutex<Option<Arc<Mutex<fastembed::ImageEmbedding>>>> =
     parking_lot::Mutex::new(None);
 
+/// Set once both prewarmed models are in place, so render code can check
+/// model status without locking the model mutexes
+static MODELS_LOADED: AtomicBool = AtomicBool::new(false);
+
+/// Directory fastembed keeps downloaded models in
+fn model_cache_dir() -> PathBuf {
+    crate::settings::Settings::config_path()
+        .and_then(|p| p.parent().map(|d| d.to_path_buf()))
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join(".fastembed_cache")
+}
+
+/// Refresh MODELS_LOADED after a model was stored or dropped
+fn update_models_loaded() {
+    let loaded = PREWARMED_VISION_MODEL.lock().is_some() && PREWARMED_TEXT_MODEL.lock().is_some();
+    MODELS_LOADED.store(loaded, Ordering::Release);
+}
+
+/// Shared loader for the vision model. Reuses the resident model; otherwise
+/// loads it and keeps it resident unless `prewarm` is `Never`. Blocking.
+fn load_vision_model(prewarm: ModelPrewarm) -> anyhow::Result<Arc<Mutex<fastembed::ImageEmbedding>>> {
+    if let Some(model) = PREWARMED_VISION_MODEL.lock().clone() {
+        return Ok(model);
+    }
+
+    info!("Loading vision embedding model...");
+    let model = fastembed::ImageEmbedding::try_new(
+        fastembed::ImageInitOptions::new(fastembed::ImageEmbeddingModel::NomicEmbedVisionV15)
+            .with_cache_dir(model_cache_dir())
+            .with_show_download_progress(false),
+    )?;
+    let model = Arc::new(Mutex::new(model));
+
+    if prewarm != ModelPrewarm::Never {
+        *PREWARMED_VISION_MODEL.lock() = Some(model.clone());
+        update_models_loaded();
+        info!("Vision model resident and ready for indexing");
+    }
+    Ok(model)
+}
+
+/// Shared loader for the text model (see `load_vision_model`)
+fn load_text_model(prewarm: ModelPrewarm) -> anyhow::Result<Arc<Mutex<fastembed::TextEmbedding>>> {
+    if let Some(model) = PREWARMED_TEXT_MODEL.lock().clone() {
+        return Ok(model);
+    }
+
+    info!("Loading text embedding model...");
+    let model = fastembed::TextEmbedding::try_new(
+        fastembed::InitOptions::new(fastembed::EmbeddingModel::NomicEmbedTextV15)
+            .with_cache_dir(model_cache_dir())
+            .with_show_download_progress(false),
+    )?;
+    let model = Arc::new(Mutex::new(model));
+
+    if prewarm != ModelPrewarm::Never {
+        *PREWARMED_TEXT_MODEL.lock() = Some(model.clone());
+        update_models_loaded();
+        info!("Text model resident and ready for search");
+    }
+    Ok(model)
+}
+
+/// Start indexing the files in `scope`, loading models through the shared loader first
+fn start_indexing_with_models(
+    config: crate::indexer::IndexConfig,
+    tx: crossbeam_channel::Sender<AppMessage>,
+    scope: IndexScope,
+    cx: &App,
+) {
+    let prewarm = cx.global::<AppState>().settings.lock().model_prewarm;
+    std::thread::spawn(move || {
+        // On failure the indexer falls back to loading its own copies
+        let vision_model = load_vision_model(prewarm)
+            .inspect_err(|e| error!("Failed to load vision model: {}", e))
+            .ok();
+        let text_model = load_text_model(prewarm)
+            .inspect_err(|e| error!("Failed to load text model: {}", e))
+            .ok();
+        crate::indexer::start_indexing(config, tx, scope, vision_model, text_model);
+    });
+}
+
+/// Index a few new files on their own, loading the vision model through the shared loader first
+fn index_files_with_model(
+    files: Vec<PathBuf>,
+    config: crate::indexer::IndexConfig,
+    tx: crossbeam_channel::Sender<AppMessage>,
+    cx: &App,
+) {
+    let prewarm = cx.global::<AppState>().settings.lock().model_prewarm;
+    std::thread::spawn(move || match load_vision_model(prewarm) {
+        Ok(vision_model) => crate::indexer::index_files(files, config, vision_model, tx),
+        Err(e) => {
+            error!("Failed to load vision model: {}", e);
+            let _ = tx.send(AppMessage::FilesIndexed(0));
+        }
+    });
+}
+
+/// Start (or restart) the local API. Generates the token on first use; search
+/// reads the indexing settings per request so later changes apply.
+fn start_api_server(settings: &Arc<Mutex<crate::settings::Settings>>) -> anyhow::Result<u16> {
+    let (port, token, screenshot_dir) = {
+        let mut s = settings.lock();
+        if s.api_server_token.is_empty() {
+            s.api_server_token = crate::server::generate_token()?;
+            let _ = s.save();
+        }
+        (s.api_server_port, s.api_server_token.clone(), s.screenshot_directory.clone())
+    };
+
+    let settings = Arc::clone(settings);
+    let search: crate::server::SearchFn = Box::new(move |query, limit| {
+        let settings = settings.lock().clone();
+        let matches = search_index(&settings, query, limit)?;
+        Ok(matches.into_iter().map(|(path, _)| path).collect())
+    });
+
+    crate::server::start(
+        port,
+        crate::server::ApiContext {
+            screenshot_dir,
+            token,
+            search,
+        },
+    )
+}
+
+/// The search index next to settings.json, for the configured folder and CPU
+/// mode (None without a config directory)
+pub(crate) fn index_config(settings: &crate::settings::Settings) -> Option<crate::indexer::IndexConfig> {
+    let db_path = crate::settings::Settings::config_path()?.parent()?.join("vector_index.db");
+    Some(crate::indexer::IndexConfig {
+        db_path,
+        cpu_mode: if settings.indexing_cpu_mode == "fast" {
+            crate::indexer::CpuMode::Fast
+        } else {
+            crate::indexer::CpuMode::Normal
+        },
+        screenshot_dir: settings.screenshot_directory.clone(),
+    })
+}
+
+/// Run a text query against the search index outside the UI (local API,
+/// command line); matches are `(path, cosine distance)`, most relevant first
+pub(crate) fn search_index(
+    settings: &crate::settings::Settings,
+    query: &str,
+    limit: usize,
+) -> anyhow::Result<Vec<(PathBuf, f32)>> {
+    if !(settings.indexing_enabled && settings.models_downloaded) {
+        anyhow::bail!("Search indexing is not enabled");
+    }
+    let config = index_config(settings).ok_or_else(|| anyhow::anyhow!("No config directory"))?;
+    let min_similarity = settings.search_min_similarity as f32 / 100.0;
+    let text_model = load_text_model(settings.model_prewarm)?;
+    crate::indexer::search_images_blocking(query.to_string(), config, text_model, limit, min_similarity)
+}
+
 /// Start native window drag using Windows API
 #[cfg(windows)]
 fn start_window_drag(_window: &mut Window) {
@@ -78,8 +321,18 @@ fn start_window_drag(_window: &mut Window) {
 }
 
 /// Open Windows folder picker dialog
-#[cfg(windows)]
 pub fn pick_folder() -> Option<PathBuf> {
+    show_open_dialog(true)
+}
+
+/// Open Windows file picker dialog
+pub fn pick_file() -> Option<PathBuf> {
+    show_open_dialog(false)
+}
+
+/// Show the common open dialog for a folder or a single file
+#[cfg(windows)]
+fn show_open_dialog(pick_folders: bool) -> Option<PathBuf> {
     use std::ffi::OsString;
     use std::os::windows::ffi::OsStringExt;
     use windows::Win32::System::Com::{
@@ -98,9 +351,11 @@ pub fn pick_folder() -> Option<PathBuf> {
             CoCreateInstance(&FileOpenDialog, None, CLSCTX_INPROC_SERVER).ok()?;
 
         // Set options to pick folders
-        let mut options = dialog.GetOptions().ok()?;
-        options |= FOS_PICKFOLDERS;
-        dialog.SetOptions(options).ok()?;
+        if pick_folders {
+            let mut options = dialog.GetOptions().ok()?;
+            options |= FOS_PICKFOLDERS;
+            dialog.SetOptions(options).ok()?;
+        }
 
         // Show dialog
         if dialog.Show(None).is_err() {
@@ -125,7 +380,7 @@ pub fn pick_folder() -> Option<PathBuf> {
 }
 
 #[cfg(not(windows))]
-pub fn pick_folder() -> Option<PathBuf> {
+fn show_open_dialog(_pick_folders: bool) -> Option<PathBuf> {
     None
 }
 
@@ -369,6 +624,108 @@ fn set_window_opacity(_window: &mut Window, _opacity: f32) {
 /// Number of items to load per page
 const PAGE_SIZE: usize = 50;
 
+/// Screenshots probed for dimensions between redraws while the orientation filter is on
+const DIMENSION_PROBE_CHUNK: usize = 100;
+
+/// Grid thumbnail lookups kept in memory (the thumbnails themselves are on disk)
+const GRID_THUMBNAIL_ENTRIES: usize = 5000;
+
+/// Newest screenshots published for tray drag (fallbacks if the newest is deleted)
+const LATEST_CANDIDATES: usize = 5;
+
+/// Minimum time between automatic infinite scroll loads
+const LOAD_MORE_DEBOUNCE: Duration = Duration::from_millis(150);
+
+/// How often the time-based previews on the General page are refreshed
+const PREVIEW_REFRESH: Duration = Duration::from_secs(1);
+
+/// Columns of file name in a notification before it's shortened
+const NOTIFICATION_NAME_WIDTH: usize = 40;
+
+/// Columns of file name in the "Similar to" search chip before it's shortened
+const SIMILAR_CHIP_NAME_WIDTH: usize = 32;
+
+/// Most screenshots "Select Similar" adds at once
+const SIMILAR_SELECT_CAP: usize = 50;
+
+/// Files listed in the timestamp fix preview before "and N more"
+const TIMESTAMP_PREVIEW_ROWS: usize = 10;
+
+/// Files listed in the metadata strip report before "and N more"
+const STRIP_REPORT_ROWS: usize = 10;
+
+/// How long a first Shift+Delete waits for the second press that confirms it
+const PERMANENT_DELETE_WINDOW: Duration = Duration::from_secs(5);
+
+/// How often an idle tray tooltip is refreshed so its "last capture" age stays current
+const TRAY_TOOLTIP_REFRESH: Duration = Duration::from_secs(60);
+
+/// How long a search result stays highlighted in the full gallery after the search is cleared
+const FLASH_DURATION: Duration = Duration::from_millis(1200);
+
+/// Display strings for the General page, rebuilt only when their inputs
+/// change (previews also once per second, since they show the current time)
+#[derive(Default)]
+struct GeneralPageStrings {
+    language: String,
+    organizer_format: String,
+    file_name_pattern: String,
+    previews_at: Option<Instant>,
+
+    organizer_preview: String,
+    file_name_preview: String,
+
+    language_title: String,
+    language_label: String,
+    language_desc: String,
+    screenshot_dir_title: String,
+    browse_label: String,
+    organizer_title: String,
+    organizer_enable_label: String,
+    organizer_enable_desc: String,
+}
+
+impl GeneralPageStrings {
+    fn refresh(&mut self, settings: &crate::settings::Settings) {
+        let language = crate::i18n_helpers::current_language();
+        let language_changed = language != self.language;
+        if language_changed {
+            self.language_title = t!("settings.general.language.title").to_string();
+            self.language_label = t!("settings.general.language.label").to_string();
+            self.language_desc = t!("settings.general.language.desc").to_string();
+            self.screenshot_dir_title = t!("settings.general.screenshot_dir.title").to_string();
+            self.browse_label = t!("common.button.browse").to_string();
+            self.organizer_title = t!("settings.general.organizer.title").to_string();
+            self.organizer_enable_label = t!("settings.general.organizer.enable_label").to_string();
+            self.organizer_enable_desc = t!("settings.general.organizer.enable_desc").to_string();
+            self.language = language;
+        }
+
+        let stale = self
+            .previews_at
+            .is_none_or(|at| at.elapsed() >= PREVIEW_REFRESH);
+        if language_changed
+            || stale
+            || self.organizer_format != settings.organizer_format
+            || self.file_name_pattern != settings.file_name_pattern
+        {
+            self.organizer_format = settings.organizer_format.clone();
+            self.file_name_pattern = settings.file_name_pattern.clone();
+            self.organizer_preview = t!(
+                "settings.general.organizer.format_preview",
+                preview = organizer::format_preview(&self.organizer_format)
+            )
+            .to_string();
+            self.file_name_preview = t!(
+                "settings.general.file_names.preview",
+                preview = naming::preview(&self.file_name_pattern, "png")
+            )
+            .to_string();
+            self.previews_at = Some(Instant::now());
+        }
+    }
+}
+
 /// Screenshot metadata
 #[derive(Debug, Clone)]
 pub struct ScreenshotInfo {
@@ -376,16 +733,24 @@ pub struct ScreenshotInfo {
     #[allow(dead_code)]
     pub filename: String,
     pub modified: SystemTime,
+    /// When it was taken (file name date when enabled, else `modified`)
+    pub captured: SystemTime,
     pub file_size: u64,
     /// File extension (uppercase, e.g., "PNG", "WEBP", "JPEG")
     pub extension: String,
 }
 
 impl ScreenshotInfo {
+    /// [`Self::from_path`] for callers holding a borrowed path
+    pub fn from_path_ref(path: &Path) -> Option<Self> {
+        Self::from_path(path.to_path_buf())
+    }
+
     pub fn from_path(path: PathBuf) -> Option<Self> {
-        let metadata = std::fs::metadata(&path).ok()?;
+        let metadata = std::fs::metadata(crate::paths::long_path(&path)).ok()?;
         let filename = path.file_name()?.to_string_lossy().to_string();
         let modified = metadata.modified().ok()?;
+        let captured = crate::filename_date::capture_time(&path, modified);
         let file_size = metadata.len();
         let extension = path
             .extension()
@@ -397,26 +762,223 @@ impl ScreenshotInfo {
             path,
             filename,
             modified,
+            captured,
             file_size,
             extension,
         })
     }
 }
 
-/// Format file size in human readable format (using IEC binary units)
-pub fn format_file_size(bytes: u64) -> String {
-    const KIB: u64 = 1024;
-    const MIB: u64 = KIB * 1024;
+/// Screenshots the cleanup page offers, `(path, modified, file_size)` largest
+/// first; recomputed only when the page is opened or the age limit, the
+/// screenshots or their usage change
+#[derive(Default)]
+struct CleanupCandidates {
+    /// Age limit in days, usage revision and screenshot count they were
+    /// computed for
+    inputs: Option<(u32, u64, usize)>,
+    items: Vec<(PathBuf, SystemTime, u64)>,
+}
+
+impl CleanupCandidates {
+    fn refresh(&mut self, days: u32, screenshots: &[ScreenshotInfo]) {
+        let inputs = (days, usage::revision(), screenshots.len());
+        if self.inputs == Some(inputs) {
+            return;
+        }
+        let used = usage::used_paths();
+        self.items = usage::unused_candidates(
+            screenshots.iter().map(|s| (&s.path, s.modified, s.file_size)),
+            Duration::from_secs(days as u64 * 24 * 60 * 60),
+            SystemTime::now(),
+            |path| used.contains(path),
+        );
+        self.inputs = Some(inputs);
+    }
+
+    /// Recompute on the next refresh
+    fn invalidate(&mut self) {
+        self.inputs = None;
+    }
+}
+
+/// Per-path view state that has to follow a file when the pipeline converts
+/// or moves it, so a selection made a moment earlier isn't silently dropped
+struct PathState<'a> {
+    selected: &'a mut HashSet<PathBuf>,
+    cleanup_selected: &'a mut HashSet<PathBuf>,
+    last_selected: &'a mut Option<PathBuf>,
+    focused: &'a mut Option<PathBuf>,
+    details_path: &'a mut Option<PathBuf>,
+    preview: &'a mut Option<PathBuf>,
+}
 
-    if bytes >= MIB {
-        format!("{:.1} MiB", bytes as f64 / MIB as f64)
-    } else if bytes >= KIB {
-        format!("{} KiB", bytes / KIB)
-    } else {
-        format!("{} B", bytes)
+impl PathState<'_> {
+    fn follow_move(&mut self, from: &PathBuf, to: &PathBuf) {
+        for set in [&mut *self.selected, &mut *self.cleanup_selected] {
+            if set.remove(from) {
+                set.insert(to.clone());
+            }
+        }
+        for slot in [
+            &mut *self.last_selected,
+            &mut *self.focused,
+            &mut *self.details_path,
+            &mut *self.preview,
+        ] {
+            if slot.as_ref() == Some(from) {
+                *slot = Some(to.clone());
+            }
+        }
+    }
+}
+
+/// Selection over the context menu threshold, waiting on the user
+enum ContextMenuPrompt {
+    /// Asking whether to open the shell menu anyway
+    Confirm(Vec<PathBuf>),
+    /// Chose the app's own actions instead of the shell menu
+    AppMenu(Vec<PathBuf>),
+}
+
+/// Whether `count` files are too many to hand the shell menu without asking
+/// (a threshold of 0 never asks)
+fn needs_context_menu_confirm(count: usize, threshold: usize) -> bool {
+    threshold > 0 && count > threshold
+}
+
+/// Human-readable label for a timeline step
+fn timeline_event_label(kind: &crate::timeline::EventKind) -> String {
+    use crate::timeline::EventKind;
+
+    let name = |path: &PathBuf| {
+        path.file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_default()
+    };
+
+    match kind {
+        EventKind::Detected => t!("gallery.details.event.detected").to_string(),
+        EventKind::Converted(to) => t!("gallery.details.event.converted", name = name(to)).to_string(),
+        EventKind::ConvertFailed(reason) => {
+            t!("gallery.details.event.convert_failed", reason = reason).to_string()
+        }
+        EventKind::Organized(to) => t!(
+            "gallery.details.event.organized",
+            folder = to.parent().map(|p| p.to_string_lossy().to_string()).unwrap_or_default()
+        )
+        .to_string(),
+        EventKind::OrganizeFailed(reason) => {
+            t!("gallery.details.event.organize_failed", reason = reason).to_string()
+        }
+        EventKind::Renamed(to) => t!("gallery.details.event.renamed", name = name(to)).to_string(),
+        EventKind::Indexed => t!("gallery.details.event.indexed").to_string(),
+        EventKind::IndexFailed(reason) => {
+            t!("gallery.details.event.index_failed", reason = reason).to_string()
+        }
+        EventKind::IndexSkipped(width, height) => {
+            t!("gallery.details.event.index_skipped", width = width, height = height).to_string()
+        }
+        EventKind::ThumbnailGenerated => t!("gallery.details.event.thumbnail_generated").to_string(),
+        EventKind::DecodeFailed(reason) => {
+            t!("gallery.details.event.decode_failed", reason = reason).to_string()
+        }
+    }
+}
+
+/// Format how long ago something happened ("just now", "5m ago", ...)
+pub fn format_relative_time(at: SystemTime, now: SystemTime) -> String {
+    let secs = now.duration_since(at).map(|d| d.as_secs()).unwrap_or(0);
+    match secs {
+        0..=9 => t!("gallery.details.ago.just_now").to_string(),
+        10..=59 => t!("gallery.details.ago.seconds", count = secs).to_string(),
+        60..=3599 => t!("gallery.details.ago.minutes", count = secs / 60).to_string(),
+        3600..=86399 => t!("gallery.details.ago.hours", count = secs / 3600).to_string(),
+        _ => t!("gallery.details.ago.days", count = secs / 86400).to_string(),
+    }
+}
+
+/// Localized message for an invalid organizer folder format
+fn folder_format_error_message(error: &naming::PatternError) -> String {
+    match error {
+        naming::PatternError::IllegalChar(c) => {
+            t!("settings.general.organizer.format_error.illegal_char", char = c).to_string()
+        }
+        naming::PatternError::InvalidName(name) => {
+            t!("settings.general.organizer.format_error.invalid_name", name = name).to_string()
+        }
+        _ => t!("settings.general.organizer.format_error.empty").to_string(),
+    }
+}
+
+/// Localized message for an invalid file name pattern
+fn pattern_error_message(error: &naming::PatternError) -> String {
+    match error {
+        naming::PatternError::Empty => t!("settings.general.file_names.error.empty").to_string(),
+        naming::PatternError::IllegalChar(c) => {
+            t!("settings.general.file_names.error.illegal_char", char = c).to_string()
+        }
+        naming::PatternError::UnknownToken(token) => {
+            t!("settings.general.file_names.error.unknown_token", token = token).to_string()
+        }
+        naming::PatternError::Unclosed => t!("settings.general.file_names.error.unclosed").to_string(),
+        // Only folder formats are checked for these
+        naming::PatternError::InvalidName(_) => folder_format_error_message(error),
     }
 }
 
+/// One-line "Copy Info" summary: dimensions, size, format, capture and
+/// modified times, and the full path. Without `probe`, returns `None` when
+/// the image header hasn't been read yet rather than reading it.
+fn info_summary(path: &Path, probe: bool) -> Option<String> {
+    let metadata = std::fs::metadata(crate::paths::long_path(path)).ok();
+    let modified = metadata.as_ref().and_then(|m| m.modified().ok());
+    let dimensions = match modified {
+        Some(modified) if probe => crate::thumbnail::dimensions(path, modified),
+        Some(modified) => crate::thumbnail::cached_dimensions(path, modified)?,
+        None => None,
+    };
+    let format_time = |time: Option<SystemTime>| {
+        time.map(|t| {
+            chrono::DateTime::<chrono::Local>::from(t)
+                .format("%Y-%m-%d %H:%M:%S")
+                .to_string()
+        })
+        .unwrap_or_else(|| "-".to_string())
+    };
+    let size = format_file_size(metadata.as_ref().map(|m| m.len()).unwrap_or(0));
+    let format = path
+        .extension()
+        .map(|e| e.to_string_lossy().to_uppercase())
+        .unwrap_or_default();
+    let captured = format_time(metadata.as_ref().and_then(|m| m.created().ok()).or(modified));
+    let modified = format_time(modified);
+    let path = path.display().to_string();
+
+    Some(match dimensions {
+        Some((width, height)) => t!(
+            "app.copy_info.summary",
+            width = width,
+            height = height,
+            size = size,
+            format = format,
+            captured = captured,
+            modified = modified,
+            path = path
+        )
+        .to_string(),
+        None => t!(
+            "app.copy_info.summary_no_dimensions",
+            size = size,
+            format = format,
+            captured = captured,
+            modified = modified,
+            path = path
+        )
+        .to_string(),
+    })
+}
+
 /// Click action from gallery items
 #[derive(Debug, Clone)]
 pub enum GalleryAction {
@@ -438,20 +1000,67 @@ pub enum GalleryAction {
     ClearSelection,
 }
 
+/// What a settings control does when clicked or run from the keyboard
+type SettingsAction = Rc<dyn Fn(&mut Sukusho, ControlInput, &mut Window, &mut Context<Sukusho>)>;
+
+fn settings_action(
+    action: impl Fn(&mut Sukusho, ControlInput, &mut Window, &mut Context<Sukusho>) + 'static,
+) -> SettingsAction {
+    Rc::new(action)
+}
+
+/// Value shown between a settings stepper's - and + buttons; `width` fits
+/// its longest label
+fn stepper_value(width: f32, value: impl IntoElement, cx: &App) -> Div {
+    div()
+        .w(px(width))
+        .text_center()
+        .px_2()
+        .py_1()
+        .rounded(px(4.0))
+        .bg(cx.theme().muted)
+        .text_sm()
+        .child(value)
+}
+
 /// Main application view
 pub struct Sukusho {
-    /// All screenshot paths (sorted by modification time, newest first)
-    all_screenshots: Vec<ScreenshotInfo>,
+    /// All screenshots in the chosen sort order; past the
+    /// `max_gallery_items` cap later ones are only stubs
+    screenshots: ScreenshotList,
 
     /// Currently visible screenshots (paginated)
     visible_count: usize,
 
+    /// Gallery scroll handle (offset/size drive infinite scroll)
+    gallery_scroll: ScrollHandle,
+
+    /// When the last page was appended (debounces infinite scroll)
+    last_load_more: Option<Instant>,
+
     /// Selected screenshot paths
     selected: HashSet<PathBuf>,
 
     /// Last selected item for shift-click range selection
     last_selected: Option<PathBuf>,
 
+    /// Tile the arrow keys move from (the last one clicked or moved to)
+    focused: Option<PathBuf>,
+
+    /// Gallery scroll position from before the search, to go back to when
+    /// it's cleared with no result picked
+    pre_search_offset: Option<Point<Pixels>>,
+    /// Tile briefly highlighted where a cleared search's result landed
+    flashing: Option<PathBuf>,
+    /// New PNGs shown while auto-convert works on them (by source path); the
+    /// tile is swapped for the converted file when the pipeline finishes
+    pending_conversions: HashSet<PathBuf>,
+    /// Ends the highlight
+    flash_tick: Option<Task<()>>,
+
+    /// Gallery click/double-click/drag state
+    click_tracker: ClickTracker,
+
     /// Thumbnail cache
     thumbnail_cache: Arc<ThumbnailCache>,
 
@@ -461,12 +1070,27 @@ pub struct Sukusho {
     /// Current settings page
     settings_page: SettingsPage,
 
+    /// Keyboard focus on the settings page
+    settings_focus: SettingsFocus,
+
+    /// Settings controls drawn last frame, in tab order
+    settings_controls: RefCell<Vec<Control<SettingsAction>>>,
+
     /// Current grid columns
     grid_columns: u32,
 
     /// Current thumbnail size
     thumbnail_size: u32,
 
+    /// What gallery tile badges show
+    badge_content: BadgeContent,
+
+    /// What the gallery's group headers split screenshots by
+    grouping: GalleryGrouping,
+
+    /// Sort options shown under the header's sort chip
+    sort_menu_open: bool,
+
     /// Focus handle for keyboard events
     focus_handle: FocusHandle,
 
@@ -476,8 +1100,55 @@ pub struct Sukusho {
     /// Whether search input has focus
     search_input_focused: bool,
 
+    /// Organizer folder format input (General settings)
+    organizer_format_input: Entity<InputState>,
+
+    /// Whether the organizer format input has focus
+    organizer_format_input_focused: bool,
+
+    /// Validation error for the folder format being typed (not saved while invalid)
+    organizer_format_error: Option<naming::PatternError>,
+
+    /// File name pattern input (General settings)
+    file_name_input: Entity<InputState>,
+
+    /// Whether the file name pattern input has focus
+    file_name_input_focused: bool,
+
+    /// Validation error for the pattern being typed (not saved while invalid)
+    file_name_error: Option<naming::PatternError>,
+
+    /// Exclude patterns input (General settings), comma-separated
+    exclude_input: Entity<InputState>,
+
+    /// Whether the exclude patterns input has focus
+    exclude_input_focused: bool,
+
+    /// Editor path input (General settings)
+    editor_input: Entity<InputState>,
+
+    /// Whether the editor path input has focus
+    editor_input_focused: bool,
+
+    /// Typed editor path that doesn't exist (not saved while invalid)
+    editor_error: Option<String>,
+
+    /// Local API port input (Advanced settings)
+    api_port_input: Entity<InputState>,
+
+    /// Whether the API port input has focus
+    api_port_input_focused: bool,
+
+    /// Whether the typed port isn't one the API can use (not saved while invalid)
+    api_port_error: bool,
+
     /// Whether we're recording a new hotkey
     recording_hotkey: bool,
+    /// Fallback hotkeys last probed, and whether each was free
+    hotkey_probe: Option<Vec<(String, bool)>>,
+
+    /// Cached display strings for the General settings page
+    general_strings: GeneralPageStrings,
 
     /// Whether we're currently organizing files
     organizing: bool,
@@ -486,7 +1157,29 @@ pub struct Sukusho {
     organize_progress: (usize, usize),
 
     /// Current file being organized
-    organize_current_file: String,
+    organize_current_file: CurrentFile,
+
+    /// Set to stop a running re-organization
+    organize_cancel: Arc<AtomicBool>,
+
+    /// Organizer format change (previous, new) waiting for the user to
+    /// confirm re-organizing
+    reorganize_offer: Option<(String, String)>,
+
+    /// Asking whether to flatten the date folders
+    flatten_confirm: bool,
+
+    /// Writing a diagnostic bundle
+    creating_bundle: bool,
+
+    /// Dropped directory waiting for confirmation (path, images found inside)
+    pending_directory: Option<(PathBuf, usize)>,
+
+    /// Directory the user picked that needs an explicit go-ahead, and why
+    directory_warning: Option<(PathBuf, crate::directory_check::Concern)>,
+
+    /// The first scan stopped after this many images; there are more
+    scan_capped: Option<usize>,
 
     /// Whether we're currently converting files
     converting: bool,
@@ -494,8 +1187,26 @@ pub struct Sukusho {
     /// Conversion progress (current, total)
     convert_progress: (usize, usize),
 
+    /// Set to stop a running conversion
+    convert_cancel: Arc<AtomicBool>,
+
     /// Current file being converted
-    convert_current_file: String,
+    convert_current_file: CurrentFile,
+
+    /// Whether metadata is being stripped from the selection
+    stripping: bool,
+
+    /// Metadata stripping progress (current, total)
+    strip_progress: (usize, usize),
+
+    /// Outcome of the last strip, shown until dismissed
+    strip_report: Option<StripReport>,
+
+    /// Whether PNGs are being sampled for a savings estimate
+    estimating_savings: bool,
+
+    /// Latest savings estimate
+    savings_estimate: Option<convert::SavingsEstimate>,
 
     /// Whether we're currently downloading models
     downloading_models: bool,
@@ -512,14 +1223,38 @@ pub struct Sukusho {
     /// Indexing progress (current, total)
     index_progress: (usize, usize),
 
+    /// The running index continues a session from before a restart
+    index_resumed: bool,
+
     /// Current file being indexed
-    index_current_file: String,
+    index_current_file: CurrentFile,
+
+    /// Index export or import running
+    transferring_index: bool,
+
+    /// Why the vector DB couldn't be opened, while the recovery dialog is up
+    index_recovery: Option<String>,
 
     /// Search query
     search_query: String,
 
+    /// Show the "Run last search" chip for the restored query
+    last_search_offer: bool,
+
     /// Search results (None = show all, Some = filtered)
     search_results: Option<Vec<PathBuf>>,
+    /// Screenshot the results are similar to, instead of a text search
+    similar_to: Option<PathBuf>,
+
+    /// Only screenshots of this orientation (None = all); composes with the search
+    orientation_filter: Option<Orientation>,
+    /// Reading dimensions of loaded screenshots the filter doesn't know yet
+    dimension_probe: Option<Task<()>>,
+    /// How the current results' search ran
+    search_stats: Option<crate::indexer::SearchStats>,
+
+    /// Why the last search couldn't run (nothing indexed yet), shown under the search box
+    search_hint: Option<crate::indexer::IndexHealth>,
 
     /// Index statistics
     #[allow(dead_code)]
@@ -528,9 +1263,71 @@ pub struct Sukusho {
     /// Toast notification manager
     toast_manager: crate::ui::ToastManager,
 
+    /// Timer for the next toast fade/expiry; replacing it cancels the old one
+    toast_tick: Option<Task<()>>,
+    /// New screenshots waiting to be auto-indexed
+    auto_index: PendingIndex,
+    /// Timer for the pending auto-index batch
+    auto_index_tick: Option<Task<()>>,
+    /// New screenshots are being added to the index on their own
+    indexing_new_files: bool,
+    /// Timer for the next scheduled index run
+    index_schedule_tick: Option<Task<()>>,
+    /// When this session started; "at startup" schedules are judged from it
+    started_at: chrono::DateTime<chrono::Local>,
+
+    /// Wakes up to refresh the tray tooltip (throttled, or an aging "last capture")
+    tray_tooltip_tick: Option<Task<()>>,
+
     /// Current window opacity (0.0 = fully transparent, 1.0 = fully opaque)
     window_opacity: f32,
 
+    /// Screenshots checked on the cleanup page
+    cleanup_selected: HashSet<PathBuf>,
+    /// Screenshots listed on the cleanup page
+    cleanup_candidates: CleanupCandidates,
+
+    /// File shown in the details pane (processing timeline)
+    details_path: Option<PathBuf>,
+
+    /// Whether the details pane is collapsed to its header
+    details_collapsed: bool,
+
+    /// In-window keyboard shortcuts (key handler and help overlay)
+    shortcuts: ShortcutRegistry,
+
+    /// Whether the shortcut cheatsheet overlay is shown
+    shortcut_help_open: bool,
+
+    /// Screenshot shown full-size over the gallery
+    preview: Option<PathBuf>,
+
+    /// Large selection waiting before the shell context menu opens
+    context_menu_prompt: Option<ContextMenuPrompt>,
+
+    /// Operations stopped by a read-only file, asked about one at a time
+    read_only_prompts: Vec<FileOp>,
+
+    /// Timestamp fixes waiting for the preview dialog's go-ahead
+    timestamp_plan: Option<content_date::Plan>,
+    /// "Copy Info" is waiting for image headers to be read
+    copying_info: bool,
+    /// Shift+Delete was pressed once for this selection; a second press
+    /// within [`PERMANENT_DELETE_WINDOW`] deletes it
+    permanent_delete_armed: Option<(Instant, HashSet<PathBuf>)>,
+    /// Files we deleted and already took out of the gallery, whose watcher
+    /// Remove event is still to come
+    deleted_here: HashSet<PathBuf>,
+
+    /// Stacking, focus and keys for the dialogs above
+    modals: ModalHost,
+
+    /// In-app shortcut being re-recorded on the Hotkey page
+    recording_shortcut: Option<ShortcutAction>,
+
+    /// Last rejected recording (recorded action, action already using the keys)
+    shortcut_conflict: Option<(ShortcutAction, ShortcutAction)>,
+
     /// Track if this is the first render (to skip saving initial bounds and handle hide_window_on_start)
     first_render: bool,
     /// Track if we've already hidden the window on start (to do it only once)
@@ -541,11 +1338,26 @@ impl Sukusho {
     pub fn new(window: &mut Window, cx: &mut Context<Self>) -> Self {
         let app_state = cx.global::<AppState>();
         let settings = app_state.settings.lock().clone();
-
-        // Create search input state
+        let thumbnail_dir = crate::settings::Settings::config_path()
+            .and_then(|p| p.parent().map(|d| d.join(".thumbs")))
+            .unwrap_or_else(|| std::env::temp_dir().join("sukusho-thumbs"));
+        let thumbnail_cache = Arc::new(ThumbnailCache::new(
+            GRID_THUMBNAIL_ENTRIES,
+            thumbnail_dir,
+            app_state.message_tx.clone(),
+        ));
+
+        // Create search input state, pre-filled with the last search (not run
+        // until asked; loading the model and searching isn't free)
+        let last_search = if settings.models_downloaded {
+            settings.last_search_query.clone()
+        } else {
+            String::new()
+        };
         let search_input = cx.new(|cx| {
             InputState::new(window, cx)
                 .placeholder(&t!("app.search.placeholder").to_string())
+                .default_value(last_search.clone())
         });
 
         // Subscribe to search input events
@@ -561,10 +1373,11 @@ impl Sukusho {
                     // Use the state parameter directly (no RefCell borrow of this.search_input)
                     let text = state.read(cx).value().to_string();
                     this.search_query = text.clone();
+                    this.last_search_offer = false;
 
                     // Clear search results if query is empty
                     if text.is_empty() {
-                        this.search_results = None;
+                        this.clear_search(cx);
                     }
                     cx.notify();
                 }
@@ -572,168 +1385,313 @@ impl Sukusho {
                     // Use the state parameter directly (no RefCell borrow of this.search_input)
                     let query = state.read(cx).value().to_string();
                     if !query.is_empty() {
-                        info!("Starting search for: {}", query);
+                        this.start_search(query, cx);
+                    }
+                }
+            }
+        })
+        .detach();
 
-                        // Get message channel and config
-                        let tx = {
-                            let app_state = cx.global::<AppState>();
-                            app_state.message_tx.clone()
-                        };
-                        let config = {
-                            let app_state = cx.global::<AppState>();
-                            let settings = app_state.settings.lock();
-                            let db_path = crate::settings::Settings::config_path()
-                                .unwrap()
-                                .parent()
-                                .unwrap()
-                                .join("vector_index.db");
-                            crate::indexer::IndexConfig {
-                                db_path,
-                                cpu_mode: if settings.indexing_cpu_mode == "fast" {
-                                    crate::indexer::CpuMode::Fast
-                                } else {
-                                    crate::indexer::CpuMode::Normal
-                                },
-                                screenshot_dir: settings.screenshot_directory.clone(),
-                            }
-                        };
+        // Organizer folder format input, saved to settings whenever it's valid
+        let organizer_format_input = cx.new(|cx| {
+            InputState::new(window, cx).default_value(settings.organizer_format.clone())
+        });
 
-                        // Use prewarmed model if available, otherwise load fresh
-                        if let Some(text_model) = PREWARMED_TEXT_MODEL.lock().clone() {
-                            info!("Using prewarmed model for search");
-                            crate::indexer::search_images(
-                                query.to_string(),
-                                config,
-                                text_model,
-                                tx,
-                                100,
-                            );
-                        } else {
-                            info!("Loading model for search (not prewarmed)");
-                            // Load text model and perform search in background
-                            std::thread::spawn(move || {
-                                let cache_dir = crate::settings::Settings::config_path()
-                                    .and_then(|p| p.parent().map(|d| d.to_path_buf()))
-                                    .unwrap_or_else(|| PathBuf::from("."));
-                                let cache_dir = cache_dir.join(".fastembed_cache");
-
-                                match fastembed::TextEmbedding::try_new(
-                                    fastembed::InitOptions::new(
-                                        fastembed::EmbeddingModel::NomicEmbedTextV15,
-                                    )
-                                    .with_cache_dir(cache_dir)
-                                    .with_show_download_progress(false),
-                                ) {
-                                    Ok(model) => {
-                                        let text_model = Arc::new(Mutex::new(model));
-                                        crate::indexer::search_images(
-                                            query.to_string(),
-                                            config,
-                                            text_model,
-                                            tx,
-                                            100,
-                                        );
-                                    }
-                                    Err(e) => {
-                                        error!("Failed to load text model for search: {}", e);
-                                    }
-                                }
-                            });
+        cx.subscribe_in(&organizer_format_input, window, |this, state, event, _window, cx| {
+            match event {
+                InputEvent::Focus => {
+                    this.organizer_format_input_focused = true;
+                }
+                InputEvent::Blur => {
+                    this.organizer_format_input_focused = false;
+                }
+                InputEvent::Change => {
+                    let format = state.read(cx).value().trim().to_string();
+                    match organizer::validate_format(&format) {
+                        Ok(()) => {
+                            this.organizer_format_error = None;
+                            this.set_organizer_format(&format, cx);
+                        }
+                        Err(e) => {
+                            this.organizer_format_error = Some(e);
                         }
                     }
+                    cx.notify();
                 }
+                _ => {}
             }
         })
         .detach();
 
-        let app = Self {
-            all_screenshots: Vec::new(),
-            visible_count: PAGE_SIZE,
-            selected: HashSet::new(),
-            last_selected: None,
-            thumbnail_cache: Arc::new(ThumbnailCache::new(500)),
-            settings_open: false,
-            settings_page: SettingsPage::default(),
-            grid_columns: settings.grid_columns,
-            thumbnail_size: settings.thumbnail_size,
-            focus_handle: cx.focus_handle(),
-            search_input,
-            search_input_focused: false,
-            recording_hotkey: false,
-            organizing: false,
-            organize_progress: (0, 0),
-            organize_current_file: String::new(),
-            converting: false,
-            convert_progress: (0, 0),
-            convert_current_file: String::new(),
-            downloading_models: false,
-            model_download_progress: (0, 0),
-            models_downloaded: settings.models_downloaded,
-            indexing: false,
-            index_progress: (0, 0),
-            index_current_file: String::new(),
-            search_query: String::new(),
-            search_results: None,
-            index_stats: crate::indexer::IndexStats::default(),
-            toast_manager: crate::ui::ToastManager::new(),
-            window_opacity: settings.window_opacity,
-            first_render: true,
-            hidden_on_start: false,
-        };
-
-        // Prewarm models if indexing is enabled (creates SINGLE shared model instances)
-        if settings.indexing_enabled && settings.models_downloaded {
-            info!("Prewarming embedding models (single shared instances)...");
-            let cache_dir = crate::settings::Settings::config_path()
-                .and_then(|p| p.parent().map(|d| d.to_path_buf()))
-                .unwrap_or_else(|| PathBuf::from("."));
-            let cache_dir = cache_dir.join(".fastembed_cache");
+        // File name pattern input, saved to settings whenever it's valid
+        let file_name_input = cx.new(|cx| {
+            InputState::new(window, cx).default_value(settings.file_name_pattern.clone())
+        });
 
-            // Load models in background thread (blocking operation)
-            // The models are wrapped in Arc<Mutex<>> so they can be shared across threads
-            std::thread::spawn(move || {
-                info!("Loading vision embedding model in background...");
-                match fastembed::ImageEmbedding::try_new(
-                    fastembed::ImageInitOptions::new(
-                        fastembed::ImageEmbeddingModel::NomicEmbedVisionV15,
-                    )
-                    .with_cache_dir(cache_dir.clone())
-                    .with_show_download_progress(false),
-                ) {
-                    Ok(model) => {
-                        info!("Vision embedding model loaded successfully - setting global state");
-                        // Create a SINGLE Arc<Mutex<>> wrapped model that will be shared
-                        let vision_model = Arc::new(Mutex::new(model));
-                        // Store in global static for access from indexing function
-                        *PREWARMED_VISION_MODEL.lock() = Some(vision_model);
-                        info!("Vision model prewarmed and ready for indexing");
-                    }
-                    Err(e) => {
-                        error!("Failed to prewarm vision embedding model: {}", e);
+        cx.subscribe_in(&file_name_input, window, |this, state, event, _window, cx| {
+            match event {
+                InputEvent::Focus => {
+                    this.file_name_input_focused = true;
+                }
+                InputEvent::Blur => {
+                    this.file_name_input_focused = false;
+                }
+                InputEvent::Change => {
+                    let pattern = state.read(cx).value().to_string();
+                    match naming::validate_pattern(&pattern) {
+                        Ok(()) => {
+                            this.file_name_error = None;
+                            let app_state = cx.global::<AppState>();
+                            let mut settings = app_state.settings.lock();
+                            settings.file_name_pattern = pattern;
+                            let _ = settings.save();
+                        }
+                        Err(e) => {
+                            this.file_name_error = Some(e);
+                        }
                     }
+                    cx.notify();
                 }
+                _ => {}
+            }
+        })
+        .detach();
 
-                info!("Loading text embedding model in background...");
-                match fastembed::TextEmbedding::try_new(
-                    fastembed::InitOptions::new(fastembed::EmbeddingModel::NomicEmbedTextV15)
-                        .with_cache_dir(cache_dir)
-                        .with_show_download_progress(false),
-                ) {
-                    Ok(model) => {
-                        info!("Text embedding model loaded successfully - setting global state");
-                        // Create a SINGLE Arc<Mutex<>> wrapped model that will be shared
-                        let text_model = Arc::new(Mutex::new(model));
-                        // Store in global static for access from search function
-                        *PREWARMED_TEXT_MODEL.lock() = Some(text_model);
-                        info!("Text model prewarmed and ready for search");
-                    }
-                    Err(e) => {
-                        error!("Failed to prewarm text embedding model: {}", e);
+        // Exclude patterns input, saved to settings as typed
+        let exclude_input = cx.new(|cx| {
+            InputState::new(window, cx)
+                .placeholder("*.tmp, Recording_*.png")
+                .default_value(crate::exclude::format_list(&settings.exclude_patterns))
+        });
+
+        cx.subscribe_in(&exclude_input, window, |this, state, event, _window, cx| {
+            match event {
+                InputEvent::Focus => {
+                    this.exclude_input_focused = true;
+                }
+                InputEvent::Blur => {
+                    this.exclude_input_focused = false;
+                }
+                InputEvent::Change => {
+                    let patterns = crate::exclude::parse_list(&state.read(cx).value());
+                    crate::exclude::configure(&patterns);
+                    let app_state = cx.global::<AppState>();
+                    let mut settings = app_state.settings.lock();
+                    settings.exclude_patterns = patterns;
+                    let _ = settings.save();
+                }
+                _ => {}
+            }
+        })
+        .detach();
+
+        // Editor path input, saved to settings whenever it points at a file (or is cleared)
+        let editor_input = cx.new(|cx| {
+            InputState::new(window, cx).default_value(
+                settings
+                    .preferred_editor
+                    .as_ref()
+                    .map(|p| p.to_string_lossy().to_string())
+                    .unwrap_or_default(),
+            )
+        });
+
+        cx.subscribe_in(&editor_input, window, |this, state, event, _window, cx| {
+            match event {
+                InputEvent::Focus => {
+                    this.editor_input_focused = true;
+                }
+                InputEvent::Blur => {
+                    this.editor_input_focused = false;
+                }
+                InputEvent::Change => {
+                    let text = state.read(cx).value().trim().to_string();
+                    this.set_preferred_editor(text, cx);
+                }
+                _ => {}
+            }
+        })
+        .detach();
+
+        // Local API port input, saved whenever it's a usable port and
+        // applied when leaving the field so the API doesn't restart per digit
+        let api_port_input = cx.new(|cx| {
+            InputState::new(window, cx).default_value(settings.api_server_port.to_string())
+        });
+
+        cx.subscribe_in(&api_port_input, window, |this, state, event, _window, cx| {
+            match event {
+                InputEvent::Focus => {
+                    this.api_port_input_focused = true;
+                }
+                InputEvent::Blur => {
+                    this.api_port_input_focused = false;
+                    this.apply_api_port(cx);
+                }
+                InputEvent::PressEnter { .. } => {
+                    this.apply_api_port(cx);
+                }
+                InputEvent::Change => {
+                    let port = state
+                        .read(cx)
+                        .value()
+                        .trim()
+                        .parse::<u16>()
+                        .ok()
+                        .filter(|port| *port >= crate::server::MIN_PORT);
+                    this.api_port_error = port.is_none();
+                    if let Some(port) = port {
+                        let app_state = cx.global::<AppState>();
+                        let mut settings = app_state.settings.lock();
+                        if settings.api_server_port != port {
+                            settings.api_server_port = port;
+                            let _ = settings.save();
+                        }
                     }
+                    cx.notify();
+                }
+                _ => {}
+            }
+        })
+        .detach();
+
+        let mut app = Self {
+            screenshots: {
+                let mut screenshots = ScreenshotList::new(settings.max_gallery_items);
+                screenshots.set_sort(settings.gallery_sort, ScreenshotInfo::from_path_ref);
+                screenshots
+            },
+            visible_count: PAGE_SIZE,
+            gallery_scroll: ScrollHandle::new(),
+            last_load_more: None,
+            selected: HashSet::new(),
+            last_selected: None,
+            focused: None,
+            pre_search_offset: None,
+            flashing: None,
+            pending_conversions: HashSet::new(),
+            flash_tick: None,
+            click_tracker: ClickTracker::default(),
+            thumbnail_cache,
+            settings_open: false,
+            settings_page: SettingsPage::default(),
+            settings_focus: SettingsFocus::default(),
+            settings_controls: RefCell::new(Vec::new()),
+            grid_columns: settings.grid_columns,
+            thumbnail_size: settings.thumbnail_size,
+            badge_content: settings.badge_content,
+            grouping: settings.gallery_grouping,
+            sort_menu_open: false,
+            focus_handle: cx.focus_handle(),
+            search_input,
+            search_input_focused: false,
+            organizer_format_input,
+            organizer_format_input_focused: false,
+            organizer_format_error: None,
+            file_name_input,
+            file_name_input_focused: false,
+            exclude_input,
+            exclude_input_focused: false,
+            file_name_error: None,
+            editor_input,
+            editor_input_focused: false,
+            editor_error: None,
+            api_port_input,
+            api_port_input_focused: false,
+            api_port_error: false,
+            recording_hotkey: false,
+            hotkey_probe: None,
+            general_strings: GeneralPageStrings::default(),
+            organizing: false,
+            organize_progress: (0, 0),
+            organize_current_file: CurrentFile::default(),
+            organize_cancel: Arc::new(AtomicBool::new(false)),
+            reorganize_offer: None,
+            flatten_confirm: false,
+            creating_bundle: false,
+            pending_directory: None,
+            directory_warning: None,
+            scan_capped: None,
+            converting: false,
+            convert_progress: (0, 0),
+            convert_cancel: Arc::new(AtomicBool::new(false)),
+            convert_current_file: CurrentFile::default(),
+            stripping: false,
+            strip_progress: (0, 0),
+            strip_report: None,
+            estimating_savings: false,
+            transferring_index: false,
+            index_recovery: None,
+            savings_estimate: None,
+            downloading_models: false,
+            model_download_progress: (0, 0),
+            models_downloaded: settings.models_downloaded,
+            indexing: false,
+            index_progress: (0, 0),
+            index_resumed: false,
+            index_current_file: CurrentFile::default(),
+            last_search_offer: !last_search.is_empty(),
+            search_query: last_search,
+            search_results: None,
+            similar_to: None,
+            orientation_filter: None,
+            dimension_probe: None,
+            search_stats: None,
+            search_hint: None,
+            index_stats: crate::indexer::IndexStats::default(),
+            toast_manager: crate::ui::ToastManager::new(),
+            toast_tick: None,
+            auto_index: PendingIndex::default(),
+            auto_index_tick: None,
+            index_schedule_tick: None,
+            started_at: chrono::Local::now(),
+            indexing_new_files: false,
+            tray_tooltip_tick: None,
+            window_opacity: settings.window_opacity,
+            cleanup_selected: HashSet::new(),
+            cleanup_candidates: CleanupCandidates::default(),
+            details_path: None,
+            details_collapsed: false,
+            shortcuts: ShortcutRegistry::with_overrides(&settings.shortcut_bindings),
+            recording_shortcut: None,
+            shortcut_conflict: None,
+            shortcut_help_open: false,
+            preview: None,
+            context_menu_prompt: None,
+            read_only_prompts: Vec::new(),
+            timestamp_plan: None,
+            copying_info: false,
+            permanent_delete_armed: None,
+            deleted_here: HashSet::new(),
+            modals: ModalHost::new(cx),
+            first_render: true,
+            hidden_on_start: false,
+        };
+
+        // Prewarm models if indexing is enabled (creates SINGLE shared model instances)
+        if settings.indexing_enabled
+            && settings.models_downloaded
+            && settings.model_prewarm == ModelPrewarm::OnStartup
+        {
+            info!("Prewarming embedding models (single shared instances)...");
+            // Load models in background thread (blocking operation)
+            std::thread::spawn(move || {
+                if let Err(e) = load_vision_model(ModelPrewarm::OnStartup) {
+                    error!("Failed to prewarm vision embedding model: {}", e);
+                }
+                if let Err(e) = load_text_model(ModelPrewarm::OnStartup) {
+                    error!("Failed to prewarm text embedding model: {}", e);
                 }
             });
         }
 
+        if settings.api_server_enabled {
+            let shared_settings = Arc::clone(&cx.global::<AppState>().settings);
+            if let Err(e) = start_api_server(&shared_settings) {
+                error!("Failed to start local API: {}", e);
+            }
+        }
+
         // Set initial window opacity
         set_window_opacity(window, settings.window_opacity);
 
@@ -743,77 +1701,201 @@ impl Sukusho {
         // Apply initial theme
         apply_theme(settings.theme, window, cx);
 
+        // Recycled screenshots whose grace period ran out while we weren't running
+        Self::purge_expired_removals(cx);
+
+        app.schedule_index_run(cx);
+
         app
     }
 
-    /// Convert a keystroke to a hotkey string
-    fn keystroke_to_hotkey_string(keystroke: &Keystroke) -> Option<String> {
-        let mut parts = Vec::new();
+    /// Save a new toggle hotkey and register it, saying so if another app
+    /// already owns the combination
+    fn apply_hotkey(&mut self, hotkey_str: String, window: &mut Window, cx: &mut Context<Self>) {
+        {
+            let app_state = cx.global::<AppState>();
+            let mut settings = app_state.settings.lock();
+            settings.hotkey = hotkey_str.clone();
+            let _ = settings.save();
+        }
+        // Update the global hotkey registration
+        if let Err(error) = crate::hotkey::update_hotkey(&hotkey_str) {
+            window.push_notification(
+                Notification::new()
+                    .message(
+                        &t!("notifications.hotkey.unavailable", hotkey = hotkey_str, error = error)
+                            .to_string(),
+                    )
+                    .with_type(NotificationType::Error),
+                cx,
+            );
+        }
+        self.hotkey_probe = None;
+        cx.notify();
+    }
+
+    /// Save the editor path from settings; empty clears it, missing files are rejected
+    fn set_preferred_editor(&mut self, text: String, cx: &mut Context<Self>) {
+        let editor = (!text.is_empty()).then(|| PathBuf::from(&text));
+        if let Some(path) = &editor {
+            if let Err(e) = crate::editor::validate_editor(path) {
+                debug!("{}", e);
+                self.editor_error = Some(text);
+                cx.notify();
+                return;
+            }
+        }
 
-        if keystroke.modifiers.control {
-            parts.push("Ctrl");
+        self.editor_error = None;
+        crate::editor::set_preferred_editor(editor.clone());
+        let app_state = cx.global::<AppState>();
+        let mut settings = app_state.settings.lock();
+        if settings.preferred_editor != editor {
+            settings.preferred_editor = editor;
+            let _ = settings.save();
         }
-        if keystroke.modifiers.shift {
-            parts.push("Shift");
+        drop(settings);
+        cx.notify();
+    }
+
+    /// Move the running local API to the saved port, if it changed
+    fn apply_api_port(&mut self, cx: &mut Context<Self>) {
+        let Some(running) = crate::server::running_port() else {
+            return;
+        };
+        let shared_settings = Arc::clone(&cx.global::<AppState>().settings);
+        if shared_settings.lock().api_server_port == running {
+            return;
         }
-        if keystroke.modifiers.alt {
-            parts.push("Alt");
+        if let Err(e) = start_api_server(&shared_settings) {
+            error!("Failed to restart local API: {}", e);
         }
-        if keystroke.modifiers.platform {
-            parts.push("Win");
+        cx.notify();
+    }
+
+    /// Bind an in-app shortcut to recorded keys, rejecting keys another action uses
+    fn rebind_shortcut(&mut self, action: ShortcutAction, binding: Binding, cx: &mut Context<Self>) {
+        let keys = binding.label();
+        if let Some(other) = self.shortcuts.conflict(action, &binding) {
+            self.shortcut_conflict = Some((action, other));
+            self.recording_shortcut = None;
+            cx.notify();
+            return;
         }
 
-        // Get the key name
-        let key = keystroke.key.as_str();
+        info!("Recorded shortcut for {}: {}", action.id(), keys);
+        let app_state = cx.global::<AppState>();
+        let mut settings = app_state.settings.lock();
+        settings.shortcut_bindings.insert(action.id().to_string(), keys);
+        let _ = settings.save();
+        self.shortcuts = ShortcutRegistry::with_overrides(&settings.shortcut_bindings);
+        drop(settings);
 
-        // Skip if only modifier keys are pressed
-        let is_modifier_only = matches!(key, "control" | "shift" | "alt" | "meta" | "super" | "");
+        self.shortcut_conflict = None;
+        self.recording_shortcut = None;
+        cx.notify();
+    }
 
-        if is_modifier_only {
-            return None;
+    /// Restore an in-app shortcut's built-in keys
+    fn reset_shortcut(&mut self, action: ShortcutAction, cx: &mut Context<Self>) {
+        // Another action may have been moved onto a default key meanwhile
+        if let Some(other) = action
+            .default_bindings()
+            .iter()
+            .find_map(|binding| self.shortcuts.conflict(action, binding))
+        {
+            self.shortcut_conflict = Some((action, other));
+            cx.notify();
+            return;
         }
 
-        // Convert key to display format
-        let key_display = match key {
-            "space" => "Space",
-            "tab" => "Tab",
-            "enter" => "Enter",
-            "backspace" => "Backspace",
-            "delete" => "Delete",
-            "insert" => "Insert",
-            "home" => "Home",
-            "end" => "End",
-            "pageup" => "PageUp",
-            "pagedown" => "PageDown",
-            "up" => "Up",
-            "down" => "Down",
-            "left" => "Left",
-            "right" => "Right",
-            "`" => "`",
-            k if k.starts_with('f') && k.len() <= 3 => k, // F1-F12
-            k if k.len() == 1 => k,                       // Single char keys
-            _ => return None,                             // Unknown key
-        };
+        let app_state = cx.global::<AppState>();
+        let mut settings = app_state.settings.lock();
+        settings.shortcut_bindings.remove(action.id());
+        let _ = settings.save();
+        self.shortcuts = ShortcutRegistry::with_overrides(&settings.shortcut_bindings);
+        drop(settings);
 
-        parts.push(key_display);
+        if self.shortcut_conflict.is_some_and(|(recorded, _)| recorded == action) {
+            self.shortcut_conflict = None;
+        }
+        cx.notify();
+    }
 
-        if parts.len() < 2 {
-            // Require at least one modifier + key, or just function keys
-            let key_upper = key_display.to_uppercase();
-            if !key_upper.starts_with('F') || key_upper.len() > 3 {
-                return None;
+    /// Run an in-window shortcut from the registry
+    fn run_shortcut(&mut self, action: ShortcutAction, window: &mut Window, cx: &mut Context<Self>) {
+        match action {
+            ShortcutAction::Back => {
+                // Open dialogs take Escape themselves (see handle_modal_key)
+                if !self.selected.is_empty() {
+                    // Clear selection if items are selected
+                    self.selected.clear();
+                    self.last_selected = None;
+                    cx.notify();
+                } else if self.settings_open {
+                    // Close settings if open
+                    self.settings_open = false;
+                    cx.notify();
+                } else {
+                    let escape_action = cx.global::<AppState>().settings.lock().escape_action;
+                    match escape_action {
+                        EscapeAction::Hide => crate::tray::hide_window(),
+                        EscapeAction::Minimize => window.minimize_window(),
+                        EscapeAction::None => {}
+                    }
+                }
+            }
+            ShortcutAction::ToggleHelp => {
+                self.shortcut_help_open = !self.shortcut_help_open;
+                cx.notify();
+            }
+            ShortcutAction::Preview => {
+                if !self.settings_open && self.selected.len() == 1 {
+                    self.preview = self.selected.iter().next().cloned();
+                    cx.notify();
+                }
+            }
+            ShortcutAction::CopySelection => {
+                if !self.selected.is_empty() {
+                    let files: Vec<_> = self.selected.iter().cloned().collect();
+                    self.copy_files(&files, cx);
+                } else {
+                    info!("No files selected for clipboard copy");
+                }
+            }
+            ShortcutAction::SelectAll => self.select_all(cx),
+            ShortcutAction::SelectPage => self.select_page(cx),
+            ShortcutAction::DeleteSelection => self.delete_selection(false, cx),
+            ShortcutAction::DeleteSelectionPermanently => {
+                let armed = self.permanent_delete_armed.take().is_some_and(|(at, paths)| {
+                    at.elapsed() < PERMANENT_DELETE_WINDOW && paths == self.selected
+                });
+                if armed {
+                    self.delete_selection(true, cx);
+                } else if !self.settings_open && !self.selected.is_empty() {
+                    self.permanent_delete_armed = Some((Instant::now(), self.selected.clone()));
+                    let keys = self
+                        .shortcuts
+                        .shortcut(ShortcutAction::DeleteSelectionPermanently)
+                        .map(|s| s.keys_label())
+                        .unwrap_or_default();
+                    let message = t!(
+                        &plural_key("notifications.delete.confirm_permanent", self.selected.len()),
+                        count = format_count(self.selected.len()),
+                        keys = keys
+                    )
+                    .to_string();
+                    self.show_toast(message, cx);
+                }
+            }
+            ShortcutAction::ToggleLayout => self.toggle_window_layout(cx),
+            ShortcutAction::FocusSearch => {
+                if !self.settings_open && self.models_downloaded {
+                    let handle = self.search_input.read(cx).focus_handle(cx);
+                    window.focus(&handle);
+                }
             }
         }
-
-        Some(
-            parts
-                .join("+")
-                .to_uppercase()
-                .replace("CTRL", "Ctrl")
-                .replace("SHIFT", "Shift")
-                .replace("ALT", "Alt")
-                .replace("WIN", "Win"),
-        )
     }
 
     /// Maximum messages to process per render cycle (prevents UI blocking)
@@ -860,11 +1942,103 @@ impl Sukusho {
         for msg in messages {
             match msg {
                 AppMessage::NewScreenshot(path, should_auto_index) => {
+                    // Still coming in from the directory we just switched away from
+                    let directory = cx
+                        .global::<AppState>()
+                        .settings
+                        .lock()
+                        .screenshot_directory
+                        .clone();
+                    if !path.starts_with(&directory) {
+                        debug!("Ignoring screenshot outside {:?}: {:?}", directory, path);
+                        continue;
+                    }
                     self.add_screenshot(path, should_auto_index, cx);
                 }
+                AppMessage::ScreenshotsScanned(mut infos) => {
+                    let directory = cx
+                        .global::<AppState>()
+                        .settings
+                        .lock()
+                        .screenshot_directory
+                        .clone();
+                    infos.retain(|info| info.path.starts_with(&directory));
+                    self.add_scanned(infos, cx);
+                }
                 AppMessage::ScreenshotRemoved(path) => {
+                    // The converter deleting its source; the tile is swapped when it finishes
+                    if self.pending_conversions.contains(&path) {
+                        continue;
+                    }
                     self.remove_screenshot(&path, cx);
                 }
+                AppMessage::ScreenshotRenamed(from, to) => {
+                    let directory = cx
+                        .global::<AppState>()
+                        .settings
+                        .lock()
+                        .screenshot_directory
+                        .clone();
+                    if !to.starts_with(&directory) {
+                        debug!("Ignoring rename outside {:?}: {:?}", directory, to);
+                        continue;
+                    }
+                    self.rename_screenshot(&from, to, cx);
+                }
+                AppMessage::FilesDeleted(deleted, failed, permanent) => {
+                    for path in &deleted {
+                        self.pending_conversions.remove(path);
+                        // The watcher may have reported it first
+                        if self.screenshots.contains(path) {
+                            self.remove_screenshot(path, cx);
+                            self.deleted_here.insert(path.clone());
+                        }
+                    }
+                    let message = match (failed, permanent) {
+                        (0, false) => t!(
+                            &plural_key("notifications.delete.recycled", deleted.len()),
+                            count = format_count(deleted.len())
+                        ),
+                        (0, true) => t!(
+                            &plural_key("notifications.delete.deleted", deleted.len()),
+                            count = format_count(deleted.len())
+                        ),
+                        _ => t!(&plural_key("notifications.delete.failed", failed), count = format_count(failed)),
+                    };
+                    self.show_toast(message.to_string(), cx);
+                }
+                AppMessage::BlockedRetried(failed) => {
+                    if failed > 0 {
+                        let message = t!(&plural_key("notifications.read_only.failed", failed), count = format_count(failed));
+                        self.show_toast(message.to_string(), cx);
+                    }
+                }
+                AppMessage::PipelineTransition(transition) => {
+                    debug!("Pipeline transition: {:?}", transition);
+                    // Arrives before the matching ScreenshotRemoved, which would drop the selection
+                    if let Transition::Finished { from, to } | Transition::Moved { from, to } =
+                        &transition
+                    {
+                        if from != to {
+                            self.path_state().follow_move(from, to);
+                            self.thumbnail_cache.follow_move(from, to);
+                            usage::follow_move(from, to);
+                            self.cleanup_candidates.invalidate();
+                            cx.notify();
+                        }
+                    }
+                    match &transition {
+                        Transition::Converting(path, _) => self.start_conversion(path.clone(), cx),
+                        Transition::Finished { from, to } | Transition::Moved { from, to } => {
+                            self.finish_conversion(from, to, cx)
+                        }
+                        Transition::Removed(_) => {}
+                    }
+                    let tray_manager = Arc::clone(&cx.global::<AppState>().tray_manager);
+                    if let Some(tray) = tray_manager.lock().as_mut() {
+                        tray.update_recent(&pipeline::recent());
+                    }
+                }
                 AppMessage::ToggleWindow => {
                     info!("Toggle window requested - activating window");
                     window.activate_window();
@@ -877,95 +2051,241 @@ impl Sukusho {
                 }
                 AppMessage::OpenSettings => {
                     self.settings_open = true;
+                    self.cleanup_candidates.invalidate();
                     cx.notify();
                 }
+                AppMessage::ProcessingPaused(paused) => self.processing_paused_changed(paused, cx),
                 AppMessage::ChangeDirectory(new_dir) => {
-                    info!("Changing screenshot directory to: {:?}", new_dir);
-                    // Update settings
-                    {
+                    let (unchanged, limit, tx) = {
                         let app_state = cx.global::<AppState>();
-                        let mut settings = app_state.settings.lock();
-                        settings.screenshot_directory = new_dir;
-                        let _ = settings.save();
+                        let settings = app_state.settings.lock();
+                        (
+                            settings.screenshot_directory == new_dir,
+                            settings.large_directory_files,
+                            app_state.message_tx.clone(),
+                        )
+                    };
+                    if unchanged {
+                        debug!("Screenshot directory unchanged: {:?}", new_dir);
+                        continue;
                     }
-                    // Clear current screenshots and reload
-                    self.all_screenshots.clear();
-                    self.selected.clear();
-                    self.visible_count = PAGE_SIZE;
-                    // Note: Would need to restart watcher for new directory
-                    // For now, user needs to restart app
+                    // The sampling walk reads the disk; keep it off the UI thread
+                    std::thread::spawn(move || {
+                        let concern = crate::directory_check::check(&new_dir, limit);
+                        let _ = tx.send(AppMessage::DirectoryChecked(new_dir, concern));
+                    });
+                }
+                AppMessage::DirectoryChecked(new_dir, Some(concern)) => {
+                    warn!("Asking before switching to {:?}: {:?}", new_dir, concern);
+                    self.directory_warning = Some((new_dir, concern));
+                    self.settings_page = SettingsPage::General;
+                    cx.notify();
+                }
+                AppMessage::DirectoryChecked(new_dir, None) => self.switch_directory(new_dir, cx),
+                AppMessage::ScanCapped(shown) => {
+                    self.scan_capped = Some(shown);
+                    cx.notify();
+                }
+                AppMessage::EditorPicked(path) => {
+                    let text = path.to_string_lossy().to_string();
+                    self.editor_input.update(cx, |input, cx| {
+                        input.set_value(text.clone(), window, cx);
+                    });
+                    self.set_preferred_editor(text, cx);
+                }
+                AppMessage::DirectoryDropped(dir, count) => {
+                    info!("Directory dropped: {:?} ({} images)", dir, count);
+                    self.pending_directory = Some((dir, count));
+                    self.settings_page = SettingsPage::General;
                     cx.notify();
                 }
                 AppMessage::Quit => {
                     info!("Quit requested");
+                    crate::server::stop();
+                    usage::flush();
                     cx.quit();
                 }
                 AppMessage::RequestLatestScreenshot => {
                     // Update the latest screenshot from current state
-                    if let Some(latest) = self.all_screenshots.first() {
-                        set_latest_screenshot(Some(latest.path.clone()));
-                    }
+                    set_latest_screenshots(self.screenshots.newest(LATEST_CANDIDATES));
                 }
                 AppMessage::OrganizeStarted(total) => {
                     info!("Organization started: {} files", total);
                     self.organizing = true;
                     self.organize_progress = (0, total);
-                    self.organize_current_file = String::new();
+                    self.organize_current_file.clear();
                     cx.notify();
                 }
                 AppMessage::OrganizeProgress(current, total, file) => {
                     self.organize_progress = (current, total);
-                    self.organize_current_file = file;
+                    self.organize_current_file.advance(file);
+                    cx.notify();
+                }
+                AppMessage::OrganizeFileFailed(path, error) => {
+                    self.organize_current_file.fail(path, error);
                     cx.notify();
                 }
                 AppMessage::OrganizeCompleted => {
                     info!("Organization completed");
                     self.organizing = false;
                     self.organize_progress = (0, 0);
-                    self.organize_current_file = String::new();
+                    self.organize_current_file.clear();
+                    cx.notify();
+                }
+                AppMessage::SelectionOrganized(summary) => {
+                    self.organizing = false;
+                    self.organize_progress = (0, 0);
+                    self.organize_current_file.clear();
+                    let mut message = if summary.canceled {
+                        t!("notifications.organize_selected.canceled", organized = format_count(summary.organized)).to_string()
+                    } else {
+                        t!("notifications.organize_selected.done", organized = format_count(summary.organized)).to_string()
+                    };
+                    if summary.skipped > 0 {
+                        message.push_str(&t!("notifications.organize_selected.skipped", count = format_count(summary.skipped)));
+                    }
+                    if summary.failed > 0 {
+                        message.push_str(&t!("notifications.organize_selected.failed", count = format_count(summary.failed)));
+                    }
+                    self.show_toast(message, cx);
                     cx.notify();
                 }
                 AppMessage::ConvertStarted(total) => {
                     info!("Conversion started: {} files", total);
                     self.converting = true;
                     self.convert_progress = (0, total);
-                    self.convert_current_file = String::new();
+                    self.convert_current_file.clear();
                     cx.notify();
                 }
                 AppMessage::ConvertProgress(current, total, file) => {
                     self.convert_progress = (current, total);
-                    self.convert_current_file = file;
+                    self.convert_current_file.advance(file);
+                    cx.notify();
+                }
+                AppMessage::ConvertFileFailed(path, error) => {
+                    self.convert_current_file.fail(path, error);
                     cx.notify();
                 }
-                AppMessage::ConvertCompleted => {
+                AppMessage::ConvertCompleted(summary) => {
                     info!("Conversion completed");
                     self.converting = false;
                     self.convert_progress = (0, 0);
-                    self.convert_current_file = String::new();
+                    self.convert_current_file.clear();
+                    let mut message = if summary.canceled {
+                        t!("notifications.convert_selected.canceled", converted = format_count(summary.converted)).to_string()
+                    } else {
+                        t!(
+                            "notifications.convert_selected.done",
+                            converted = format_count(summary.converted),
+                            skipped = format_count(summary.skipped)
+                        )
+                        .to_string()
+                    };
+                    if summary.saved_bytes > 0 {
+                        message.push_str(&t!(
+                            "notifications.convert_selected.saved",
+                            size = format_file_size(summary.saved_bytes as u64)
+                        ));
+                    }
+                    if summary.failed > 0 {
+                        message.push_str(&t!("notifications.convert_selected.failed", count = format_count(summary.failed)));
+                    }
+                    if summary.in_use > 0 {
+                        message.push_str(&t!("notifications.convert_selected.in_use", count = format_count(summary.in_use)));
+                    }
+                    self.show_toast(message, cx);
                     cx.notify();
                 }
-                AppMessage::ModelDownloadProgress(current, total, model) => {
-                    info!("Model download progress: {}/{} ({})", current, total, model);
-                    self.downloading_models = true;
-                    self.model_download_progress = (current, total);
+                AppMessage::StripStarted(total) => {
+                    info!("Metadata stripping started: {} files", total);
+                    self.stripping = true;
+                    self.strip_progress = (0, total);
                     cx.notify();
                 }
-                AppMessage::ModelDownloadCompleted => {
-                    info!("Model download completed");
-                    self.downloading_models = false;
-                    self.models_downloaded = true;
-
-                    // Text model will be loaded on-demand when search is triggered
-
-                    // Save to settings
-                    {
-                        let app_state = cx.global::<AppState>();
-                        let mut settings = app_state.settings.lock();
-                        settings.models_downloaded = true;
-                        let _ = settings.save();
+                AppMessage::StripProgress(current, total, _file) => {
+                    self.strip_progress = (current, total);
+                    cx.notify();
+                }
+                AppMessage::StripCompleted(report) => {
+                    info!("Metadata stripping completed");
+                    self.stripping = false;
+                    self.strip_progress = (0, 0);
+                    if report.stripped() == 0 && report.failed() == 0 {
+                        self.show_toast(t!("notifications.strip_metadata.none_found").to_string(), cx);
+                    } else {
+                        self.strip_report = Some(report);
                     }
-
-                    // Show notification
+                    cx.notify();
+                }
+                AppMessage::SavingsEstimated(estimate) => {
+                    self.estimating_savings = false;
+                    self.savings_estimate = Some(estimate);
+                    cx.notify();
+                }
+                AppMessage::TimestampPlan(plan) => {
+                    if plan.fixes.is_empty() {
+                        let message = if plan.unchanged > 0 {
+                            t!("notifications.timestamps.all_correct").to_string()
+                        } else {
+                            t!("notifications.timestamps.no_dates").to_string()
+                        };
+                        self.show_toast(message, cx);
+                    } else {
+                        self.timestamp_plan = Some(plan);
+                    }
+                    cx.notify();
+                }
+                AppMessage::InfoSummaries(lines) => {
+                    self.copying_info = false;
+                    self.write_info(lines, cx);
+                }
+                AppMessage::ScreenshotRedated(path) => {
+                    // Same file, new place in the gallery; selection and index rows stay
+                    if self.screenshots.remove(&path) {
+                        if let Some(info) = ScreenshotInfo::from_path(path) {
+                            self.screenshots.insert(info);
+                        }
+                        set_latest_screenshots(self.screenshots.newest(LATEST_CANDIDATES));
+                        cx.notify();
+                    }
+                }
+                AppMessage::TimestampsFixed(fixed, failed) => {
+                    let message = if failed == 0 {
+                        t!(&plural_key("notifications.timestamps.fixed", fixed), count = format_count(fixed)).to_string()
+                    } else {
+                        t!(
+                            &plural_key("notifications.timestamps.fixed_with_failures", fixed),
+                            count = format_count(fixed),
+                            failed = format_count(failed)
+                        )
+                        .to_string()
+                    };
+                    self.show_toast(message, cx);
+                    cx.notify();
+                }
+                AppMessage::ModelDownloadProgress(current, total, model) => {
+                    info!("Model download progress: {}/{} ({})", current, total, model);
+                    self.downloading_models = true;
+                    self.model_download_progress = (current, total);
+                    cx.notify();
+                }
+                AppMessage::ModelDownloadCompleted => {
+                    info!("Model download completed");
+                    self.downloading_models = false;
+                    self.models_downloaded = true;
+
+                    // Text model will be loaded on-demand when search is triggered
+
+                    // Save to settings
+                    {
+                        let app_state = cx.global::<AppState>();
+                        let mut settings = app_state.settings.lock();
+                        settings.models_downloaded = true;
+                        let _ = settings.save();
+                    }
+                    self.schedule_index_run(cx);
+
+                    // Show notification
                     window.push_notification(
                         Notification::new()
                             .message(&t!("notifications.models.download_success").to_string())
@@ -1001,69 +2321,141 @@ impl Sukusho {
                     info!("Indexing started: {} files", total);
                     self.indexing = true;
                     self.index_progress = (0, total);
-                    self.index_current_file = String::new();
+                    self.index_resumed = false;
+                    self.index_current_file.clear();
+                    cx.notify();
+                }
+                AppMessage::IndexResumed(completed, total) => {
+                    info!("Indexing resumed: {}/{} files already done", completed, total);
+                    self.indexing = true;
+                    self.index_progress = (completed, total);
+                    self.index_resumed = true;
+                    self.index_current_file.clear();
                     cx.notify();
                 }
                 AppMessage::IndexProgress(current, total, file) => {
                     self.index_progress = (current, total);
-                    self.index_current_file = file;
+                    self.index_current_file.advance(file);
+                    cx.notify();
+                }
+                AppMessage::IndexFileFailed(path, error) => {
+                    self.index_current_file.fail(path, error);
                     cx.notify();
                 }
-                AppMessage::IndexCompleted(newly_indexed_count) => {
+                AppMessage::FilesIndexed(newly_indexed_count) => {
+                    debug!("{} new screenshots indexed", newly_indexed_count);
+                    self.indexing_new_files = false;
+                    if newly_indexed_count > 0 {
+                        Self::refresh_indexed_count(cx);
+                    }
+                    let held = self.auto_index.take_held();
+                    self.index_new_files(held, cx);
+                }
+                AppMessage::IndexCompleted(newly_indexed_count, refreshed_count) => {
                     info!(
-                        "Indexing completed: {} new images indexed",
-                        newly_indexed_count
+                        "Indexing completed: {} new images indexed, {} changed ones refreshed",
+                        newly_indexed_count, refreshed_count
                     );
+                    if refreshed_count > 0 {
+                        self.show_toast(
+                            t!(
+                                &plural_key("notifications.indexing.refreshed", refreshed_count),
+                                count = format_count(refreshed_count)
+                            )
+                            .to_string(),
+                            cx,
+                        );
+                    }
                     self.indexing = false;
                     self.index_progress = (0, 0);
-                    self.index_current_file = String::new();
-
-                    // Query database for actual total indexed count
-                    let (screenshot_dir, cpu_mode) = {
-                        let app_state = cx.global::<AppState>();
-                        let settings = app_state.settings.lock();
-                        (
-                            settings.screenshot_directory.clone(),
-                            settings.indexing_cpu_mode.clone(),
-                        )
-                    };
-
-                    let db_path = crate::settings::Settings::config_path()
-                        .unwrap()
-                        .parent()
-                        .unwrap()
-                        .join("vector_index.db");
+                    self.index_current_file.clear();
+                    self.search_hint = None;
+                    Self::refresh_indexed_count(cx);
 
-                    // Get total count from database in background
-                    let settings_arc = {
+                    // Any finished run counts for the schedule
+                    {
                         let app_state = cx.global::<AppState>();
-                        Arc::clone(&app_state.settings)
-                    };
-
-                    std::thread::spawn(move || {
-                        let config = crate::indexer::IndexConfig {
-                            db_path,
-                            cpu_mode: if cpu_mode == "fast" {
-                                crate::indexer::CpuMode::Fast
-                            } else {
-                                crate::indexer::CpuMode::Normal
-                            },
-                            screenshot_dir,
-                        };
+                        let mut settings = app_state.settings.lock();
+                        settings.last_index_run = Some(chrono::Local::now());
+                        let _ = settings.save();
+                    }
+                    self.schedule_index_run(cx);
 
-                        if let Ok(total_count) = crate::indexer::get_indexed_count(&config) {
-                            let mut settings = settings_arc.lock();
-                            settings.last_indexed_count = total_count;
-                            let _ = settings.save();
-                            info!("Total indexed count updated: {}", total_count);
-                        }
-                    });
+                    // New screenshots that came in during the run
+                    let held = self.auto_index.take_held();
+                    self.index_new_files(held, cx);
 
                     cx.notify();
                 }
+                AppMessage::IndexExportPicked(destination) => {
+                    if let Some(config) = self.index_transfer_config(cx) {
+                        info!("Exporting the index to {:?}", destination);
+                        self.transferring_index = true;
+                        let tx = cx.global::<AppState>().message_tx.clone();
+                        crate::index_transfer::export(config, destination, tx);
+                    }
+                    cx.notify();
+                }
+                AppMessage::IndexImportPicked(archive) => {
+                    if let Some(config) = self.index_transfer_config(cx) {
+                        info!("Importing the index from {:?}", archive);
+                        self.transferring_index = true;
+                        let tx = cx.global::<AppState>().message_tx.clone();
+                        crate::index_transfer::import(config, archive, tx);
+                    }
+                    cx.notify();
+                }
+                AppMessage::IndexExported(folder) => {
+                    self.transferring_index = false;
+                    let held = self.auto_index.take_held();
+                    self.index_new_files(held, cx);
+                    let name = folder
+                        .file_name()
+                        .map(|n| n.to_string_lossy().to_string())
+                        .unwrap_or_default();
+                    self.show_toast(t!("notifications.index_transfer.exported", name = name).to_string(), cx);
+                    cx.notify();
+                }
+                AppMessage::IndexImported(rows) => {
+                    self.transferring_index = false;
+                    // New screenshots that came in during the import
+                    let held = self.auto_index.take_held();
+                    self.index_new_files(held, cx);
+                    self.search_hint = None;
+                    Self::refresh_indexed_count(cx);
+                    let message =
+                        t!(&plural_key("notifications.index_transfer.imported", rows), count = format_count(rows)).to_string();
+                    self.show_toast(message, cx);
+                    cx.notify();
+                }
+                AppMessage::IndexUnavailable(error) => {
+                    log::warn!("Vector DB unavailable: {}", error);
+                    self.indexing = false;
+                    self.index_progress = (0, 0);
+                    self.index_current_file.clear();
+                    self.search_hint = Some(crate::indexer::IndexHealth::Unavailable);
+                    // Nothing is moved or deleted until the user picks an option
+                    self.index_recovery = Some(error);
+                    cx.notify();
+                }
+                AppMessage::IndexTransferFailed(message) => {
+                    self.transferring_index = false;
+                    let held = self.auto_index.take_held();
+                    self.index_new_files(held, cx);
+                    window.push_notification(
+                        Notification::new()
+                            .message(&message)
+                            .with_type(NotificationType::Error),
+                        cx,
+                    );
+                    cx.notify();
+                }
                 AppMessage::IndexFailed(error) => {
                     error!("Indexing failed: {}", error);
                     self.indexing = false;
+                    if self.next_scheduled_index(cx).is_some() {
+                        self.schedule_index_run_in(index_schedule::FAILED_RETRY_DELAY, cx);
+                    }
 
                     // Show error notification
                     window.push_notification(
@@ -1080,1206 +2472,6574 @@ impl Sukusho {
                     self.search_query = query.clone();
 
                     if query.is_empty() {
-                        // Clear search
-                        self.search_results = None;
-                        cx.notify();
-                    } else if let Some(text_model) = PREWARMED_TEXT_MODEL.lock().clone() {
-                        // Spawn search in background
-                        let app_state = cx.global::<AppState>();
-                        let message_tx = app_state.message_tx.clone();
-                        let settings = app_state.settings.lock();
-                        let screenshot_dir = settings.screenshot_directory.clone();
-                        let config_path = crate::settings::Settings::config_path()
-                            .unwrap()
-                            .parent()
-                            .unwrap()
-                            .join("vector_index.db");
-
-                        let config = crate::indexer::IndexConfig {
-                            db_path: config_path,
-                            cpu_mode: if settings.indexing_cpu_mode == "fast" {
-                                crate::indexer::CpuMode::Fast
-                            } else {
-                                crate::indexer::CpuMode::Normal
-                            },
-                            screenshot_dir,
-                        };
-
-                        crate::indexer::search_images(query, config, text_model, message_tx, 100);
+                        self.clear_search(cx);
+                    } else {
+                        self.start_search(query, cx);
+                    }
+                }
+                AppMessage::SearchIndexHealth(health) => {
+                    self.search_hint = match health {
+                        crate::indexer::IndexHealth::Ready(_) => None,
+                        _ => Some(health),
+                    };
+                    cx.notify();
+                }
+                AppMessage::SearchResults(matches, stats) => {
+                    info!("Search results: {} images", matches.len());
+                    let paths: Vec<PathBuf> = matches.into_iter().map(|(path, _)| path).collect();
+                    self.screenshots
+                        .hydrate_matching(&paths, ScreenshotInfo::from_path_ref);
+                    // A search that ran but found nothing close enough still
+                    // filters the gallery, down to the "no good matches" note
+                    self.search_results = if paths.is_empty() && stats.is_none() {
+                        None
+                    } else {
+                        Some(paths)
+                    };
+                    if self.search_results.is_none() && self.similar_to.take().is_some() {
+                        self.show_toast(t!("notifications.similar.search_failed").to_string(), cx);
                     }
+                    self.search_stats = stats;
+                    cx.notify();
+                }
+                AppMessage::SimilarFound(source, matches) => {
+                    let Some(mut matches) = matches else {
+                        self.show_toast(t!("notifications.similar.not_indexed").to_string(), cx);
+                        continue;
+                    };
+                    let capped = matches.len() > SIMILAR_SELECT_CAP;
+                    matches.truncate(SIMILAR_SELECT_CAP);
+                    debug!("Similar to {:?}: {:?}", source, matches);
+
+                    let paths: Vec<PathBuf> = matches.into_iter().map(|(path, _)| path).collect();
+                    self.screenshots
+                        .hydrate_matching(&paths, ScreenshotInfo::from_path_ref);
+                    self.selected.insert(source.clone());
+                    let added = paths
+                        .into_iter()
+                        .filter(|path| self.screenshots.contains(path))
+                        .filter(|path| self.selected.insert(path.clone()))
+                        .count();
+                    self.last_selected = Some(source);
+
+                    let message = if added == 0 {
+                        t!("notifications.similar.none").to_string()
+                    } else if capped {
+                        t!(&plural_key("notifications.similar.capped", added), count = format_count(added)).to_string()
+                    } else {
+                        t!(&plural_key("notifications.similar.added", added), count = format_count(added)).to_string()
+                    };
+                    self.show_toast(message, cx);
+                    cx.notify();
                 }
-                AppMessage::SearchResults(paths) => {
-                    info!("Search results: {} images", paths.len());
-                    self.search_results = if paths.is_empty() { None } else { Some(paths) };
+                AppMessage::ThumbnailReady(_) => cx.notify(),
+                AppMessage::DiagnosticBundleCreated(result) => {
+                    self.creating_bundle = false;
+                    match result {
+                        Ok(path) => {
+                            info!("Wrote diagnostic bundle {:?}", path);
+                            let folder_button = t!("notifications.diagnostics.open_folder").to_string();
+                            window.push_notification(
+                                Notification::new()
+                                    .message(
+                                        &t!("notifications.diagnostics.created", path = path.display())
+                                            .to_string(),
+                                    )
+                                    .with_type(NotificationType::Success)
+                                    .action(move |_, _, _| {
+                                        let path = path.clone();
+                                        Button::new("open-bundle-folder")
+                                            .small()
+                                            .label(folder_button.clone())
+                                            .on_click(move |_, _, _| crate::ui::progress::reveal_in_explorer(&path))
+                                    }),
+                                cx,
+                            );
+                        }
+                        Err(e) => {
+                            error!("Failed to write diagnostic bundle: {}", e);
+                            window.push_notification(
+                                Notification::new()
+                                    .message(&t!("notifications.diagnostics.failed", error = e).to_string())
+                                    .with_type(NotificationType::Error),
+                                cx,
+                            );
+                        }
+                    }
                     cx.notify();
                 }
                 AppMessage::CopiedToClipboard(count) => {
                     info!("Showing clipboard toast for {} items", count);
                     // Show toast notification
-                    let message = if count == 1 {
-                        t!("notifications.copied_to_clipboard.one").to_string()
+                    let message = t!(&plural_key("notifications.copied_to_clipboard", count), count = count)
+                        .to_string();
+                    self.show_toast(message, cx);
+                    let sound_feedback = cx.global::<AppState>().settings.lock().sound_feedback;
+                    crate::feedback::play_success(sound_feedback);
+                    cx.notify();
+                }
+                AppMessage::FileBlocked(op, BlockReason::ReadOnly) => {
+                    // One prompt per file, even if it's hit again while waiting
+                    if !self.read_only_prompts.contains(&op) {
+                        self.read_only_prompts.push(op);
+                    }
+                    cx.notify();
+                }
+                AppMessage::FileBlocked(op, BlockReason::Locked { owners }) => {
+                    let name = op
+                        .path()
+                        .file_name()
+                        .map(|n| text::middle_ellipsize(&n.to_string_lossy(), NOTIFICATION_NAME_WIDTH))
+                        .unwrap_or_default();
+                    let message = if owners.is_empty() {
+                        t!("notifications.file_locked.unknown", name = name)
                     } else {
-                        t!("notifications.copied_to_clipboard.other", count = count).to_string()
+                        t!("notifications.file_locked.by", name = name, owners = owners.join(", "))
                     };
-                    self.toast_manager.show(message);
+                    window.push_notification(
+                        Notification::new()
+                            .message(&message.to_string())
+                            .with_type(NotificationType::Error),
+                        cx,
+                    );
+                }
+                AppMessage::HotkeyUnavailable(hotkey, error) => {
+                    window.push_notification(
+                        Notification::new()
+                            .message(
+                                &t!("notifications.hotkey.unavailable", hotkey = hotkey, error = error)
+                                    .to_string(),
+                            )
+                            .with_type(NotificationType::Error),
+                        cx,
+                    );
+                    cx.notify();
+                }
+                AppMessage::Ipc(crate::ipc::Command::Search { query }) => {
+                    crate::tray::show_window();
+                    window.activate_window();
+                    self.settings_open = false;
+                    if self.models_downloaded {
+                        // Same path as typing the query and pressing Enter
+                        self.search_input.update(cx, |input, cx| {
+                            input.set_value(query.clone(), window, cx);
+                        });
+                        self.search_query = query.clone();
+                        self.start_search(query, cx);
+                    } else {
+                        log::warn!("Ignoring search-ui query, search isn't set up: {}", query);
+                    }
+                    cx.notify();
+                }
+                AppMessage::ShellRecovery(trigger) => {
+                    info!("Restoring hotkeys and tray icon after {:?}", trigger);
+                    let mut errors = Vec::new();
+                    if let Err(e) = crate::hotkey::reregister_all() {
+                        errors.push(e);
+                    }
+                    let tray_manager = Arc::clone(&cx.global::<AppState>().tray_manager);
+                    if let Some(tray) = tray_manager.lock().as_mut() {
+                        if let Err(e) = tray.rebuild_icon() {
+                            errors.push(e.to_string());
+                        }
+                    }
+
+                    let error = (!errors.is_empty()).then(|| errors.join("; "));
+                    match &error {
+                        None => info!("Recovered from {:?}", trigger),
+                        Some(e) => log::warn!("Recovery after {:?} failed: {}", trigger, e),
+                    }
+                    crate::shell_recovery::record(trigger, error);
                     cx.notify();
                 }
             }
         }
 
+        self.update_tray_tooltip(cx);
+
         // If there are more messages, schedule another render to process them
         if has_more {
             cx.notify();
         }
     }
 
-    /// Add a new screenshot
-    fn add_screenshot(&mut self, path: PathBuf, should_auto_index: bool, cx: &mut Context<Self>) {
-        if self.all_screenshots.iter().any(|s| s.path == path) {
-            return;
-        }
-
-        // Check if we should auto-convert
-        let (auto_convert, format, quality, message_tx) = {
-            let app_state = cx.global::<AppState>();
-            let settings = app_state.settings.lock();
-            (
-                settings.auto_convert_webp,
-                settings.conversion_format,
-                settings.webp_quality,
-                app_state.message_tx.clone(),
-            )
+    /// Summarize the library and running jobs in the tray tooltip, for a
+    /// glance while the window is hidden
+    fn update_tray_tooltip(&mut self, cx: &mut Context<Self>) {
+        let running = |on: bool, progress: (usize, usize)| on.then_some(progress);
+        let status = crate::tray::TrayStatus {
+            screenshots: self.screenshots.len(),
+            indexing: running(self.indexing, self.index_progress),
+            converting: running(self.converting, self.convert_progress),
+            organizing: running(self.organizing, self.organize_progress),
+            last_capture: self.screenshots.newest_loaded().map(|s| s.captured),
+            paused: crate::watcher::is_paused(),
+        };
+        let text = crate::tray::status_tooltip(&status, SystemTime::now());
+
+        let tray_manager = Arc::clone(&cx.global::<AppState>().tray_manager);
+        let retry = tray_manager
+            .lock()
+            .as_mut()
+            .and_then(|tray| tray.set_tooltip(&text, Instant::now()));
+
+        // A throttled change replaces any pending refresh; while idle nothing
+        // new may arrive, but "last capture" keeps aging
+        let delay = match retry {
+            Some(delay) => delay,
+            None if !status.is_busy() && self.tray_tooltip_tick.is_none() => TRAY_TOOLTIP_REFRESH,
+            None => return,
         };
+        self.tray_tooltip_tick = Some(cx.spawn(async move |this, cx| {
+            cx.background_executor().timer(delay).await;
+            let _ = this.update(cx, |this, cx| {
+                this.tray_tooltip_tick = None;
+                this.update_tray_tooltip(cx);
+            });
+        }));
+    }
 
-        // If auto-convert is enabled and this is a PNG, convert it
-        if auto_convert && convert::is_convertible(&path) {
-            info!("Auto-converting new screenshot to {:?}: {:?}", format, path);
-            let path_clone = path.clone();
-            std::thread::spawn(move || {
-                // Small delay to ensure the file is fully written
-                std::thread::sleep(std::time::Duration::from_millis(500));
-
-                match convert::convert_image(&path_clone, format, quality) {
-                    Ok(output_path) => {
-                        info!("{:?} conversion successful: {:?}", format, output_path);
-                        // Notify about the new file (the remove is handled in convert)
-                        // The watcher will pick up the new file automatically
-                        // We send a remove for the old path since convert deleted it
-                        let _ = message_tx.send(AppMessage::ScreenshotRemoved(path_clone));
-                        let _ = message_tx
-                            .send(AppMessage::NewScreenshot(output_path, should_auto_index));
-                    }
-                    Err(e) => {
-                        log::error!("Failed to convert to {:?}: {}", format, e);
-                        // Still add the original PNG if conversion failed
-                        let _ = message_tx
-                            .send(AppMessage::NewScreenshot(path_clone, should_auto_index));
-                    }
+    /// Add a new screenshot. Converting and organizing happened in the
+    /// watcher's pipeline already; this only lists the file.
+    fn add_screenshot(&mut self, path: PathBuf, should_auto_index: bool, cx: &mut Context<Self>) {
+        if self.screenshots.contains(&path) {
+            // Swapped in already when its conversion finished, but still new
+            if should_auto_index {
+                self.auto_index.add(path, Instant::now());
+                // Held while paused, indexed once processing resumes
+                if !crate::watcher::is_paused() {
+                    self.schedule_auto_index(cx);
                 }
-            });
-            // Don't add the PNG yet - wait for conversion
+            }
             return;
         }
+        self.deleted_here.remove(&path);
+        // Back from the Recycle Bin in time: its metadata was kept
+        removals::restored(&path);
 
         if let Some(info) = ScreenshotInfo::from_path(path.clone()) {
-            let insert_pos = self
-                .all_screenshots
-                .iter()
-                .position(|s| s.modified < info.modified)
-                .unwrap_or(self.all_screenshots.len());
+            let Some(insert_pos) = self.screenshots.insert(info) else {
+                return;
+            };
+            self.trim_screenshots();
 
-            // If inserted at position 0, this is the newest screenshot
-            if insert_pos == 0 {
-                set_latest_screenshot(Some(info.path.clone()));
+            // Newest screenshots changed (only position tells when sorted by date)
+            if insert_pos < LATEST_CANDIDATES || self.screenshots.sort() != SortMode::Newest {
+                set_latest_screenshots(self.screenshots.newest(LATEST_CANDIDATES));
             }
-
-            self.all_screenshots.insert(insert_pos, info);
+            self.probe_dimensions(cx);
             cx.notify();
 
-            // Auto-index the new screenshot if indexing is enabled and this is a truly new screenshot
+            // Auto-index a truly new screenshot once it has stuck around for a bit
             if should_auto_index {
-                let (
-                    indexing_enabled,
-                    models_downloaded,
-                    screenshot_dir,
-                    indexing_cpu_mode,
-                    indexing,
-                ) = {
-                    let app_state = cx.global::<AppState>();
-                    let settings = app_state.settings.lock();
-                    (
-                        settings.indexing_enabled,
-                        settings.models_downloaded,
-                        settings.screenshot_directory.clone(),
-                        settings.indexing_cpu_mode.clone(),
-                        self.indexing,
-                    )
-                };
-
-                if indexing_enabled && models_downloaded && !indexing {
-                    info!("Auto-indexing new screenshot: {:?}", path);
-                    let tx = {
-                        let app_state = cx.global::<AppState>();
-                        app_state.message_tx.clone()
-                    };
-                    let db_path = crate::settings::Settings::config_path()
-                        .unwrap()
-                        .parent()
-                        .unwrap()
-                        .join("vector_index.db");
-                    let config = crate::indexer::IndexConfig {
-                        db_path,
-                        cpu_mode: if indexing_cpu_mode == "fast" {
-                            crate::indexer::CpuMode::Fast
-                        } else {
-                            crate::indexer::CpuMode::Normal
-                        },
-                        screenshot_dir,
-                    };
-                    // Get prewarmed models for instant indexing (no loading needed)
-                    let vision_model = PREWARMED_VISION_MODEL.lock().clone();
-                    let text_model = PREWARMED_TEXT_MODEL.lock().clone();
-                    // Index only new files (force_all = false) with prewarmed models
-                    crate::indexer::start_indexing(config, tx, false, vision_model, text_model);
+                self.auto_index.add(path, Instant::now());
+                if !crate::watcher::is_paused() {
+                    self.schedule_auto_index(cx);
                 }
             }
         }
     }
 
-    /// Remove a screenshot
-    fn remove_screenshot(&mut self, path: &PathBuf, cx: &mut Context<Self>) {
-        self.all_screenshots.retain(|s| s.path != *path);
-        self.selected.remove(path);
-        self.thumbnail_cache.invalidate(path);
-
-        // Cleanup vector DB if indexing is enabled
-        let (indexing_enabled, screenshot_dir, indexing_cpu_mode) = {
-            let app_state = cx.global::<AppState>();
-            let settings = app_state.settings.lock();
-            (
-                settings.indexing_enabled,
-                settings.screenshot_directory.clone(),
-                settings.indexing_cpu_mode.clone(),
-            )
-        };
+    /// Resume processing from the header badge; the watcher handles what it
+    /// held on its next poll
+    fn resume_processing(&mut self, cx: &mut Context<Self>) {
+        crate::watcher::set_paused(false);
+        info!("Processing resumed from the header");
+        self.processing_paused_changed(false, cx);
+    }
 
-        if indexing_enabled {
-            let db_path = crate::settings::Settings::config_path()
-                .unwrap()
-                .parent()
-                .unwrap()
-                .join("vector_index.db");
-            let config = crate::indexer::IndexConfig {
-                db_path,
-                cpu_mode: if indexing_cpu_mode == "fast" {
-                    crate::indexer::CpuMode::Fast
-                } else {
-                    crate::indexer::CpuMode::Normal
-                },
-                screenshot_dir,
-            };
-            // Remove from vector DB in background
-            crate::indexer::remove_from_index(path.clone(), config);
+    /// Bring the tray check mark, tooltip and auto-index in line with a
+    /// pause or resume
+    fn processing_paused_changed(&mut self, paused: bool, cx: &mut Context<Self>) {
+        let tray_manager = Arc::clone(&cx.global::<AppState>().tray_manager);
+        if let Some(tray) = tray_manager.lock().as_mut() {
+            tray.set_paused(paused);
+        }
+        if !paused {
+            self.schedule_auto_index(cx);
         }
+        self.update_tray_tooltip(cx);
+        cx.notify();
+    }
 
+    /// Show a PNG auto-convert just picked up right away, marked as converting
+    fn start_conversion(&mut self, path: PathBuf, cx: &mut Context<Self>) {
+        let directory = cx.global::<AppState>().settings.lock().screenshot_directory.clone();
+        if !path.starts_with(&directory) {
+            return;
+        }
+        self.pending_conversions.insert(path.clone());
+        self.add_screenshot(path, false, cx);
         cx.notify();
     }
 
-    /// Handle gallery actions
-    pub fn handle_action(&mut self, action: GalleryAction, cx: &mut Context<Self>) {
-        match action {
-            GalleryAction::Select { path, modifiers } => {
-                self.handle_select(path, modifiers, cx);
-            }
-            GalleryAction::Open(path) => {
-                self.open_file(&path);
-            }
-            GalleryAction::ContextMenu { paths, position } => {
-                self.show_context_menu(&paths, position, cx);
-            }
-            GalleryAction::StartDrag(paths) => {
-                self.start_drag(&paths);
-            }
-            GalleryAction::LoadMore => {
-                self.load_more(cx);
-            }
-            GalleryAction::ClearSelection => {
-                if !self.selected.is_empty() {
-                    self.selected.clear();
-                    self.last_selected = None;
-                    cx.notify();
-                }
-            }
+    /// Swap a converting tile for the file the pipeline ended up with. When
+    /// conversion failed, `to` is the PNG (or where it was organized to); if
+    /// it was deleted meanwhile, the tile goes.
+    fn finish_conversion(&mut self, from: &PathBuf, to: &PathBuf, cx: &mut Context<Self>) {
+        if !self.pending_conversions.remove(from) {
+            return;
         }
+        if from != to {
+            self.remove_screenshot(from, cx);
+            self.add_screenshot(to.clone(), false, cx);
+        } else if !crate::paths::long_path(from).exists() {
+            self.remove_screenshot(from, cx);
+        }
+        cx.notify();
     }
 
-    /// Handle selection with modifiers
-    fn handle_select(&mut self, path: PathBuf, modifiers: Modifiers, cx: &mut Context<Self>) {
-        if modifiers.control {
-            // Ctrl+click: toggle selection
-            if self.selected.contains(&path) {
-                self.selected.remove(&path);
-            } else {
-                self.selected.insert(path.clone());
-            }
-            self.last_selected = Some(path);
-        } else if modifiers.shift {
-            // Shift+click: range selection
-            if let Some(last) = &self.last_selected {
-                let last_idx = self.all_screenshots.iter().position(|s| &s.path == last);
-                let current_idx = self.all_screenshots.iter().position(|s| s.path == path);
+    /// Wake up when the pending auto-index batch is due; replacing the task
+    /// cancels the old one, so each new screenshot pushes the batch back
+    fn schedule_auto_index(&mut self, cx: &mut Context<Self>) {
+        let Some(delay) = self.auto_index.next_due(Instant::now()) else {
+            self.auto_index_tick = None;
+            return;
+        };
+        self.auto_index_tick = Some(cx.spawn(async move |this, cx| {
+            cx.background_executor().timer(delay).await;
+            let _ = this.update(cx, |this, cx| this.run_auto_index(cx));
+        }));
+    }
 
-                if let (Some(start), Some(end)) = (last_idx, current_idx) {
-                    let (start, end) = if start <= end {
-                        (start, end)
-                    } else {
-                        (end, start)
-                    };
-                    for i in start..=end {
-                        if i < self.all_screenshots.len() {
-                            self.selected.insert(self.all_screenshots[i].path.clone());
-                        }
-                    }
-                }
-            } else {
-                self.selected.clear();
-                self.selected.insert(path.clone());
-                self.last_selected = Some(path);
-            }
-        } else {
-            // Normal click: single selection
-            self.selected.clear();
-            self.selected.insert(path.clone());
-            self.last_selected = Some(path);
+    /// Index the new screenshots that are still there
+    fn run_auto_index(&mut self, cx: &mut Context<Self>) {
+        self.auto_index_tick = None;
+        if crate::watcher::is_paused() {
+            return;
         }
-        cx.notify();
+        let due = self.auto_index.take_due(Instant::now());
+        self.index_new_files(due, cx);
     }
 
-    /// Open file with default application
-    fn open_file(&self, path: &PathBuf) {
-        info!("Opening file: {:?}", path);
-        #[cfg(windows)]
+    /// When the scheduled index run is next due, if indexing is set up for one
+    fn next_scheduled_index(&self, cx: &App) -> Option<chrono::DateTime<chrono::Local>> {
+        let settings = cx.global::<AppState>().settings.lock();
+        // Like auto-index, never with models that aren't kept resident
+        if !settings.indexing_enabled
+            || !settings.models_downloaded
+            || settings.model_prewarm == ModelPrewarm::Never
         {
-            use std::os::windows::process::CommandExt;
-            const CREATE_NO_WINDOW: u32 = 0x08000000;
-            let _ = std::process::Command::new("cmd")
-                .args(["/C", "start", "", &path.to_string_lossy()])
-                .creation_flags(CREATE_NO_WINDOW)
-                .spawn();
+            return None;
         }
-        #[cfg(not(windows))]
-        {
-            let _ = open::that(path);
+        index_schedule::next_run(
+            settings.index_schedule,
+            settings.index_schedule_hours,
+            settings.last_index_run,
+            self.started_at,
+        )
+    }
+
+    /// Wake up when the scheduled index run is due (call again after the
+    /// schedule or the indexing settings change)
+    fn schedule_index_run(&mut self, cx: &mut Context<Self>) {
+        let Some(due) = self.next_scheduled_index(cx) else {
+            self.index_schedule_tick = None;
+            return;
+        };
+        let delay = (due - chrono::Local::now()).to_std().unwrap_or(Duration::ZERO);
+        debug!("Next scheduled index run at {}", due.format("%Y-%m-%d %H:%M"));
+        self.schedule_index_run_in(delay, cx);
+    }
+
+    /// Try the scheduled run after `delay`; replacing the task cancels the old one
+    fn schedule_index_run_in(&mut self, delay: Duration, cx: &mut Context<Self>) {
+        self.index_schedule_tick = Some(cx.spawn(async move |this, cx| {
+            cx.background_executor().timer(delay).await;
+            let _ = this.update(cx, |this, cx| this.run_scheduled_index(cx));
+        }));
+    }
+
+    /// Start the scheduled run, unless the PC is on battery or in use
+    fn run_scheduled_index(&mut self, cx: &mut Context<Self>) {
+        self.index_schedule_tick = None;
+        if self.next_scheduled_index(cx).is_none() {
+            return;
+        }
+        // A run already going counts as this one once it finishes
+        if self.indexing {
+            return;
+        }
+        // An import replaces the DB directory under the indexer
+        if self.indexing_new_files || self.downloading_models || self.transferring_index {
+            self.schedule_index_run_in(index_schedule::RETRY_DELAY, cx);
+            return;
+        }
+        if let Some(reason) = index_schedule::deferral() {
+            debug!("Scheduled index run put off: {:?}", reason);
+            self.schedule_index_run_in(index_schedule::RETRY_DELAY, cx);
+            return;
         }
+        info!("Starting the scheduled index run");
+        Self::start_incremental_index(cx);
     }
 
-    /// Show Windows context menu for files
-    fn show_context_menu(
-        &self,
-        paths: &[PathBuf],
-        _position: Point<Pixels>,
-        _cx: &mut Context<Self>,
-    ) {
-        info!("Context menu for {} files", paths.len());
-        #[cfg(windows)]
+    /// Index the files not in the index yet, as the Index button does
+    fn start_incremental_index(cx: &mut Context<Self>) {
+        let tx = {
+            let app_state = cx.global::<AppState>();
+            app_state.message_tx.clone()
+        };
+        let Some(config) = index_config(&cx.global::<AppState>().settings.lock()) else {
+            return;
+        };
+        start_indexing_with_models(config, tx, IndexScope::New, cx);
+    }
+
+    fn set_index_schedule(&mut self, schedule: IndexSchedule, hours: u32, cx: &mut Context<Self>) {
         {
-            // Context menu MUST run on UI thread (same thread that owns the window)
-            // This will block the UI while the menu is open, but that's expected behavior
-            crate::ui::show_shell_context_menu(paths);
+            let app_state = cx.global::<AppState>();
+            let mut settings = app_state.settings.lock();
+            settings.index_schedule = schedule;
+            settings.index_schedule_hours = hours;
+            let _ = settings.save();
         }
+        self.schedule_index_run(cx);
+        cx.notify();
     }
 
-    /// Start native drag operation
-    fn start_drag(&self, paths: &[PathBuf]) {
-        if paths.is_empty() {
+    /// Add `files` to the index on their own, or hold them until the indexer is free
+    fn index_new_files(&mut self, files: Vec<PathBuf>, cx: &mut Context<Self>) {
+        let files: Vec<PathBuf> = files
+            .into_iter()
+            .filter(|path| crate::paths::long_path(path).exists())
+            .collect();
+        if files.is_empty() {
             return;
         }
-        info!("Starting drag with {} files", paths.len());
-        // Note: This is called from the drag handler,
-        // actual drag is initiated there
+
+        let (indexing_enabled, models_downloaded, config, model_prewarm) = {
+            let app_state = cx.global::<AppState>();
+            let settings = app_state.settings.lock();
+            (
+                settings.indexing_enabled,
+                settings.models_downloaded,
+                index_config(&settings),
+                settings.model_prewarm,
+            )
+        };
+
+        // With models never kept resident, only index on request
+        if !indexing_enabled || !models_downloaded || model_prewarm == ModelPrewarm::Never {
+            return;
+        }
+        if self.indexing || self.indexing_new_files || self.transferring_index {
+            debug!("Indexer busy; holding {} new screenshots", files.len());
+            self.auto_index.hold(files);
+            return;
+        }
+        let Some(config) = config else {
+            return;
+        };
+
+        info!("Auto-indexing {} new screenshots", files.len());
+        self.indexing_new_files = true;
+        let tx = {
+            let app_state = cx.global::<AppState>();
+            app_state.message_tx.clone()
+        };
+        // Just these files; the vision model comes from the shared loader
+        index_files_with_model(files, config, tx, cx);
     }
 
-    /// Load more items for infinite scroll
-    fn load_more(&mut self, cx: &mut Context<Self>) {
-        let new_count = (self.visible_count + PAGE_SIZE).min(self.all_screenshots.len());
-        if new_count > self.visible_count {
-            self.visible_count = new_count;
-            cx.notify();
+    /// Index location for an export or import, unless the index is busy
+    fn index_transfer_config(&mut self, cx: &mut Context<Self>) -> Option<crate::indexer::IndexConfig> {
+        if self.indexing || self.indexing_new_files || self.transferring_index {
+            self.show_toast(t!("notifications.index_transfer.busy").to_string(), cx);
+            return None;
         }
+        index_config(&cx.global::<AppState>().settings.lock())
     }
 
-    /// Get currently visible screenshots
-    fn visible_screenshots(&self) -> &[ScreenshotInfo] {
-        let end = self.visible_count.min(self.all_screenshots.len());
-        &self.all_screenshots[..end]
+    /// Move the unopenable vector DB aside as a backup and index from scratch
+    fn start_new_index(&mut self, cx: &mut Context<Self>) {
+        let Some(config) = self.index_transfer_config(cx) else {
+            return;
+        };
+        self.index_recovery = None;
+        match crate::indexer::quarantine(&config.db_path) {
+            Ok(backup) => {
+                let name = backup
+                    .file_name()
+                    .map(|n| n.to_string_lossy().to_string())
+                    .unwrap_or_default();
+                self.show_toast(t!("notifications.index_recovery.moved", name = name).to_string(), cx);
+                self.search_hint = None;
+
+                let tx = cx.global::<AppState>().message_tx.clone();
+                start_indexing_with_models(config, tx, IndexScope::New, cx);
+            }
+            Err(e) => {
+                error!("Failed to set the vector DB aside: {:#}", e);
+                self.show_toast(
+                    t!("notifications.index_recovery.failed", error = format!("{:#}", e)).to_string(),
+                    cx,
+                );
+            }
+        }
     }
 
-    /// Check if there are more items to load
-    fn has_more(&self) -> bool {
-        self.visible_count < self.all_screenshots.len()
+    /// Store the number of indexed screenshots, read from the database in the background
+    fn refresh_indexed_count(cx: &mut Context<Self>) {
+        // Query database for actual total indexed count
+        let Some(config) = index_config(&cx.global::<AppState>().settings.lock()) else {
+            return;
+        };
+
+        // Get total count from database in background
+        let settings_arc = {
+            let app_state = cx.global::<AppState>();
+            Arc::clone(&app_state.settings)
+        };
+
+        std::thread::spawn(move || {
+            if let Ok(total_count) = crate::indexer::get_indexed_count(&config) {
+                let mut settings = settings_arc.lock();
+                settings.last_indexed_count = total_count;
+                let _ = settings.save();
+                info!("Total indexed count updated: {}", total_count);
+            }
+        });
     }
 
-    /// Get selected paths for context menu
-    pub fn get_selected_paths(&self) -> Vec<PathBuf> {
-        self.selected.iter().cloned().collect()
+    /// View state keyed by path (see `PathState`)
+    fn path_state(&mut self) -> PathState<'_> {
+        PathState {
+            selected: &mut self.selected,
+            cleanup_selected: &mut self.cleanup_selected,
+            last_selected: &mut self.last_selected,
+            focused: &mut self.focused,
+            details_path: &mut self.details_path,
+            preview: &mut self.preview,
+        }
     }
 
-    /// Check if a path is selected
-    pub fn is_path_selected(&self, path: &PathBuf) -> bool {
-        self.selected.contains(path)
+    /// Remove a screenshot
+    fn remove_screenshot(&mut self, path: &PathBuf, cx: &mut Context<Self>) {
+        // Our own delete already took it out; this is the watcher catching up
+        if self.deleted_here.remove(path) {
+            return;
+        }
+        self.screenshots.remove(path);
+
+        // Don't leave the tray drag pointing at a deleted file
+        if get_latest_screenshots().contains(path) {
+            set_latest_screenshots(self.screenshots.newest(LATEST_CANDIDATES));
+        }
+        self.selected.remove(path);
+        self.cleanup_selected.remove(path);
+        if self.preview.as_ref() == Some(path) {
+            self.preview = None;
+        }
+        self.thumbnail_cache.invalidate(path);
+        if self.auto_index.remove(path) {
+            debug!("{:?} removed before it was auto-indexed", path);
+        }
+
+        // A recycled file keeps its index rows and last-used time until its
+        // grace period runs out; anything else is cleaned up now
+        if !removals::is_pending(path) {
+            Self::forget_removed(std::slice::from_ref(path), cx);
+        }
+        Self::purge_expired_removals(cx);
+
+        cx.notify();
     }
 
-    /// Check if any items are selected
-    pub fn has_selection(&self) -> bool {
-        !self.selected.is_empty()
+    /// A screenshot renamed or moved within the folder outside the pipeline
+    /// (Explorer, another instance). It keeps its selection, thumbnail,
+    /// last-used time and index row, and isn't treated as new: no
+    /// conversion or organizing.
+    fn rename_screenshot(&mut self, from: &PathBuf, to: PathBuf, cx: &mut Context<Self>) {
+        if *from == to {
+            return;
+        }
+        self.path_state().follow_move(from, &to);
+        self.thumbnail_cache.follow_move(from, &to);
+        usage::follow_move(from, &to);
+        self.cleanup_candidates.invalidate();
+        // Still waiting to be auto-indexed: under its new name
+        let auto_index = self.auto_index.remove(from);
+
+        self.screenshots.remove(from);
+        self.add_screenshot(to.clone(), auto_index, cx);
+        if get_latest_screenshots().contains(from) {
+            set_latest_screenshots(self.screenshots.newest(LATEST_CANDIDATES));
+        }
+
+        if let Some(config) = Self::index_rows_config(cx) {
+            crate::indexer::rename_in_index(vec![(from.clone(), to)], config);
+        }
+        cx.notify();
     }
-}
 
-impl Render for Sukusho {
-    fn render(&mut self, window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
-        // Process any pending messages
-        self.process_messages(window, cx);
+    /// Drop search index rows and last-used times of screenshots that are gone
+    fn forget_removed(paths: &[PathBuf], cx: &mut Context<Self>) {
+        usage::forget(paths);
 
-        // Save window size if changed (position is always centered, no need to save)
-        // window.bounds() returns GPUI logical pixels (already DPI-scaled by GPUI)
-        let bounds = window.bounds();
-        let current_width: f32 = bounds.size.width.into();
-        let current_height: f32 = bounds.size.height.into();
+        // Cleanup vector DB if indexing is enabled
+        if let Some(config) = Self::index_rows_config(cx) {
+            // Remove from vector DB in background
+            for path in paths {
+                crate::indexer::remove_from_index(path.clone(), config.clone());
+            }
+        }
+    }
 
-        let app_state = cx.global::<AppState>();
-        let settings = app_state.settings.lock();
-        let saved_width = settings.window_width;
-        let saved_height = settings.window_height;
-        drop(settings);
+    /// Clean up recycled screenshots that weren't restored in time
+    fn purge_expired_removals(cx: &mut Context<Self>) {
+        let expired = removals::take_expired();
+        if !expired.is_empty() {
+            info!("Grace period over for {} recycled screenshots, dropping their metadata", expired.len());
+            Self::forget_removed(&expired, cx);
+        }
+    }
 
-        // Skip saving on first render (window is still being initialized)
-        if self.first_render {
-            self.first_render = false;
-            debug!("First render - skipping window size save. Current: {}x{} (GPUI logical pixels)",
-                   current_width, current_height);
-            // Update in-memory settings
+    /// Run a search, loading the text model through the shared loader if it
+    /// isn't resident yet
+    fn start_search(&mut self, query: String, cx: &mut Context<Self>) {
+        info!("Starting search for: {}", query);
+        self.last_search_offer = false;
+        self.similar_to = None;
+        // From the full gallery (not one set of results to the next)
+        if self.search_results.is_none() {
+            self.pre_search_offset = Some(self.gallery_scroll.offset());
+        }
+
+        let (tx, config, prewarm, min_similarity) = {
+            let app_state = cx.global::<AppState>();
             let mut settings = app_state.settings.lock();
-            settings.window_width = current_width;
-            settings.window_height = current_height;
-            drop(settings);
+            if settings.last_search_query != query {
+                settings.last_search_query = query.clone();
+                let _ = settings.save();
+            }
+            (
+                app_state.message_tx.clone(),
+                index_config(&settings),
+                settings.model_prewarm,
+                settings.search_min_similarity as f32 / 100.0,
+            )
+        };
+        let Some(config) = config else {
+            error!("No config directory to search the index in");
+            return;
+        };
 
-            // Hide window after first render if hide_window_on_start is set
-            if app_state.hide_window_on_start && !self.hidden_on_start {
-                self.hidden_on_start = true;
-                info!("First render complete - hiding window (hide_window_on_start = true)");
-                crate::tray::hide_window();
+        if let Some(text_model) = PREWARMED_TEXT_MODEL.lock().clone() {
+            info!("Using resident model for search");
+            crate::indexer::search_images(query, config, text_model, tx, 100, min_similarity);
+            return;
+        }
+
+        // First use (or models aren't kept resident): load in the background
+        self.show_toast(t!("notifications.models.loading").to_string(), cx);
+        cx.notify();
+        std::thread::spawn(move || match load_text_model(prewarm) {
+            Ok(text_model) => {
+                crate::indexer::search_images(query, config, text_model, tx, 100, min_similarity)
             }
-        } else {
-            // Only save if size actually changed (avoid constant writes)
-            let size_changed = (current_width - saved_width).abs() > 1.0 || (current_height - saved_height).abs() > 1.0;
+            Err(e) => error!("Failed to load text model for search: {}", e),
+        });
+    }
 
-            if size_changed {
-                debug!("Window size changed: {}x{} -> {}x{}", saved_width, saved_height, current_width, current_height);
-                let mut settings = app_state.settings.lock();
-                settings.window_width = current_width;
-                settings.window_height = current_height;
-                match settings.save() {
-                    Ok(_) => debug!("Window size saved successfully"),
-                    Err(e) => log::warn!("Failed to save window size: {}", e),
-                }
+    /// Watch `new_dir` from now on and reload the gallery from it
+    fn switch_directory(&mut self, new_dir: PathBuf, cx: &mut Context<Self>) {
+        let app_state = cx.global::<AppState>();
+        if app_state.settings.lock().screenshot_directory == new_dir {
+            debug!("Screenshot directory unchanged: {:?}", new_dir);
+            return;
+        }
+        info!("Changing screenshot directory to: {:?}", new_dir);
+        // Update settings
+        {
+            let mut settings = app_state.settings.lock();
+            settings.screenshot_directory = new_dir.clone();
+            let _ = settings.save();
+        }
+        // The watcher rescans the new directory, repopulating the gallery
+        let _ = app_state
+            .watcher_tx
+            .send(WatcherCommand::ChangeDirectory(new_dir));
+        // Clear current screenshots and reload
+        self.screenshots.clear();
+        self.selected.clear();
+        self.pending_conversions.clear();
+        self.visible_count = PAGE_SIZE;
+        self.scan_capped = None;
+        self.directory_warning = None;
+        set_latest_screenshots(Vec::new());
+        // The local API only serves files from the current directory
+        if crate::server::running_port().is_some() {
+            let shared_settings = Arc::clone(&cx.global::<AppState>().settings);
+            if let Err(e) = start_api_server(&shared_settings) {
+                error!("Failed to restart local API: {}", e);
             }
         }
+        cx.notify();
+    }
 
-        let total_count = self.all_screenshots.len();
-        let visible_count = self.visible_screenshots().len();
-        let selected_count = self.selected.len();
-        let settings_open = self.settings_open;
-        let has_more = self.has_more();
+    /// Handle paths dropped from Explorer. While settings are open, a single
+    /// directory is offered as the new screenshot directory.
+    fn handle_external_drop(
+        &mut self,
+        paths: &ExternalPaths,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        if !self.settings_open {
+            return;
+        }
 
-        v_flex()
-            .id("main-container")
-            .size_full()
-            // Use theme background color
-            .bg(cx.theme().background)
-            .track_focus(&self.focus_handle)
-            // Keyboard shortcuts
-            .on_key_down(cx.listener(|this, event: &KeyDownEvent, window, cx| {
-                // Skip handling if search input has focus
-                if this.search_input_focused {
-                    return;
-                }
+        let dir = match paths.paths() {
+            [path] if path.is_dir() => path.clone(),
+            _ => {
+                window.push_notification(
+                    Notification::new()
+                        .message(&t!("settings.general.screenshot_dir.drop_invalid").to_string())
+                        .with_type(NotificationType::Error),
+                    cx,
+                );
+                return;
+            }
+        };
 
-                // Handle hotkey recording
-                if this.recording_hotkey {
-                    // ESC cancels recording
-                    if event.keystroke.key.as_str() == "escape" {
-                        this.recording_hotkey = false;
-                        cx.notify();
-                        return;
-                    }
+        // Counting can take a while on big folders
+        let tx = cx.global::<AppState>().message_tx.clone();
+        std::thread::spawn(move || {
+            let count = crate::watcher::ScreenshotWatcher::count_images(&dir);
+            let _ = tx.send(AppMessage::DirectoryDropped(dir, count));
+        });
+    }
 
-                    // Try to convert keystroke to hotkey string
-                    if let Some(hotkey_str) = Self::keystroke_to_hotkey_string(&event.keystroke) {
-                        info!("Recorded hotkey: {}", hotkey_str);
-                        // Save the new hotkey and re-register it
-                        {
-                            let app_state = cx.global::<AppState>();
-                            let mut settings = app_state.settings.lock();
-                            settings.hotkey = hotkey_str.clone();
-                            let _ = settings.save();
-                        }
-                        // Update the global hotkey registration
-                        crate::hotkey::update_hotkey(&hotkey_str);
-                        this.recording_hotkey = false;
-                        cx.notify();
-                    }
-                    return;
-                }
+    /// Save a new organizer folder format. If the organizer is on, offer to
+    /// move existing date folders into the new scheme.
+    fn set_organizer_format(&mut self, format: &str, cx: &mut Context<Self>) {
+        let (previous, changed_while_enabled) = {
+            let app_state = cx.global::<AppState>();
+            let mut settings = app_state.settings.lock();
+            let changed = settings.organizer_format != format;
+            let previous = std::mem::replace(&mut settings.organizer_format, format.to_string());
+            let _ = settings.save();
+            (previous, changed && settings.organizer_enabled)
+        };
+
+        if changed_while_enabled && !self.organizing {
+            // Folders still carry the format from before the first unconfirmed change
+            let previous = match self.reorganize_offer.take() {
+                Some((pending, _)) => pending,
+                None => previous,
+            };
+            if previous != format {
+                self.reorganize_offer = Some((previous, format.to_string()));
+            }
+        }
+        cx.notify();
+    }
+
+    /// Pick one of the preset folder formats, showing it in the format input
+    fn pick_organizer_format(&mut self, format: &str, window: &mut Window, cx: &mut Context<Self>) {
+        self.organizer_format_error = None;
+        self.organizer_format_input.update(cx, |input, cx| {
+            input.set_value(format.to_string(), window, cx);
+        });
+        self.set_organizer_format(format, cx);
+    }
+
+    /// Turn file-name dates on or off. Gallery groups follow right away; with
+    /// the organizer on, offer to re-sort the existing date folders.
+    fn set_date_from_file_name(&mut self, enabled: bool, cx: &mut Context<Self>) {
+        let (organizer_enabled, format) = {
+            let app_state = cx.global::<AppState>();
+            let mut settings = app_state.settings.lock();
+            settings.date_from_file_name = enabled;
+            let _ = settings.save();
+            crate::filename_date::configure(enabled, &settings.file_name_date_patterns);
+            (settings.organizer_enabled, settings.organizer_format.clone())
+        };
+
+        for info in self.screenshots.loaded_mut() {
+            info.captured = crate::filename_date::capture_time(&info.path, info.modified);
+        }
+        if organizer_enabled && !self.organizing {
+            self.reorganize_offer = Some((format.clone(), format));
+        }
+        cx.notify();
+    }
+
+    /// Convert the selected PNGs to the configured format; anything else is
+    /// skipped
+    fn convert_selected(&mut self, cx: &mut Context<Self>) {
+        if self.settings_open || self.selected.is_empty() {
+            return;
+        }
+        if self.converting {
+            self.show_toast(t!("notifications.convert_selected.busy").to_string(), cx);
+            return;
+        }
+
+        let files: Vec<PathBuf> = self.selected.iter().cloned().collect();
+        let (options, index_config, tx) = Self::conversion_job(cx);
+
+        info!("Converting {} selected files to {:?}", files.len(), options.format);
+        self.convert_cancel = Arc::new(AtomicBool::new(false));
+        // Show the progress bar right away; ConvertStarted fills in the total
+        self.converting = true;
+        convert::convert_files(files, options, index_config, self.convert_cancel.clone(), tx);
+        cx.notify();
+    }
+
+    /// Convert every PNG in the screenshot directory ("Convert existing PNGs")
+    fn convert_library(&mut self, cx: &mut Context<Self>) {
+        if self.converting {
+            self.show_toast(t!("notifications.convert_selected.busy").to_string(), cx);
+            return;
+        }
+
+        let base_dir = cx.global::<AppState>().settings.lock().screenshot_directory.clone();
+        let (options, index_config, tx) = Self::conversion_job(cx);
+
+        info!("Converting existing PNGs in {:?} to {:?}", base_dir, options.format);
+        self.convert_cancel = Arc::new(AtomicBool::new(false));
+        // Show the progress bar right away; ConvertStarted fills in the total
+        self.converting = true;
+        convert::convert_library(base_dir, options, index_config, self.convert_cancel.clone(), tx);
+        cx.notify();
+    }
+
+    /// Options and index for a batch conversion. Index rows follow the
+    /// converted files when indexing is on.
+    fn conversion_job(
+        cx: &App,
+    ) -> (convert::ConvertOptions, Option<crate::indexer::IndexConfig>, crossbeam_channel::Sender<AppMessage>) {
+        let index_config = Self::index_rows_config(cx);
+        let app_state = cx.global::<AppState>();
+        let settings = app_state.settings.lock();
+        (
+            convert::ConvertOptions::from_settings(&settings),
+            index_config,
+            app_state.message_tx.clone(),
+        )
+    }
+
+    /// Index to keep rows in step with moved files, when indexing is on
+    fn index_rows_config(cx: &App) -> Option<crate::indexer::IndexConfig> {
+        let settings = cx.global::<AppState>().settings.lock();
+        if !settings.indexing_enabled {
+            return None;
+        }
+        index_config(&settings)
+    }
+
+    /// Strip EXIF, XMP and location data from the selected files
+    fn strip_selected(&mut self, cx: &mut Context<Self>) {
+        if self.settings_open || self.selected.is_empty() {
+            return;
+        }
+        if self.stripping {
+            self.show_toast(t!("notifications.strip_metadata.busy").to_string(), cx);
+            return;
+        }
+
+        let files: Vec<PathBuf> = self.selected.iter().cloned().collect();
+        let tx = cx.global::<AppState>().message_tx.clone();
+        info!("Stripping metadata from {} selected files", files.len());
+        // Dim the button right away; StripStarted fills in the total
+        self.stripping = true;
+        metadata_strip::strip_files(files, tx);
+        cx.notify();
+    }
+
+    /// Re-organize existing date folders into the offered format
+    fn start_reorganize(&mut self, cx: &mut Context<Self>) {
+        let Some((previous_format, format)) = self.reorganize_offer.take() else {
+            return;
+        };
+        if self.organizing {
+            return;
+        }
+
+        let (base_dir, tx) = {
+            let app_state = cx.global::<AppState>();
+            let settings = app_state.settings.lock();
+            (settings.screenshot_directory.clone(), app_state.message_tx.clone())
+        };
+
+        self.organize_cancel = Arc::new(AtomicBool::new(false));
+        // Show the progress bar right away; OrganizeStarted fills in the total
+        self.organizing = true;
+        organizer::reorganize_existing_files(base_dir, previous_format, format, self.organize_cancel.clone(), tx);
+        cx.notify();
+    }
+
+    /// Undo organizing: turn the organizer off and move everything in date
+    /// folders back into the screenshot folder
+    fn start_flatten(&mut self, cx: &mut Context<Self>) {
+        self.flatten_confirm = false;
+        if self.organizing {
+            return;
+        }
+
+        let (base_dir, format, tx) = {
+            let app_state = cx.global::<AppState>();
+            let mut settings = app_state.settings.lock();
+            // Or the watcher would organize new screenshots right back
+            settings.organizer_enabled = false;
+            let _ = settings.save();
+            (
+                settings.screenshot_directory.clone(),
+                settings.organizer_format.clone(),
+                app_state.message_tx.clone(),
+            )
+        };
+
+        self.reorganize_offer = None;
+        self.organize_cancel = Arc::new(AtomicBool::new(false));
+        // Show the progress bar right away; OrganizeStarted fills in the total
+        self.organizing = true;
+        organizer::flatten_organized_files(base_dir, format, self.organize_cancel.clone(), tx);
+        cx.notify();
+    }
+
+    /// Move `paths` into the configured date folders, even with the
+    /// organizer off; ones already in a subfolder are skipped
+    fn organize_selected(&mut self, paths: Vec<PathBuf>, cx: &mut Context<Self>) {
+        if paths.is_empty() {
+            return;
+        }
+        if self.organizing {
+            self.show_toast(t!("notifications.organize_selected.busy").to_string(), cx);
+            return;
+        }
+
+        let (base_dir, format, tx) = {
+            let app_state = cx.global::<AppState>();
+            let settings = app_state.settings.lock();
+            (
+                settings.screenshot_directory.clone(),
+                settings.organizer_format.clone(),
+                app_state.message_tx.clone(),
+            )
+        };
+
+        self.organize_cancel = Arc::new(AtomicBool::new(false));
+        // Show the progress bar right away; OrganizeStarted fills in the total
+        self.organizing = true;
+        organizer::organize_selected_files(paths, base_dir, format, self.organize_cancel.clone(), tx);
+        cx.notify();
+    }
+
+    /// Zip up settings, the log and diagnostics for a bug report, on a
+    /// background thread; `DiagnosticBundleCreated` reports where it went
+    fn create_diagnostic_bundle(&mut self, cx: &mut Context<Self>) {
+        if self.creating_bundle {
+            return;
+        }
+        let (settings, tx) = {
+            let app_state = cx.global::<AppState>();
+            let settings = app_state.settings.lock().clone();
+            (settings, app_state.message_tx.clone())
+        };
+        let db_path = settings
+            .indexing_enabled
+            .then(|| index_config(&settings))
+            .flatten()
+            .map(|config| config.db_path);
+
+        self.creating_bundle = true;
+        cx.notify();
+        std::thread::spawn(move || {
+            let result = crate::diagnostics::create_bundle(&settings, APP_VERSION, db_path.as_deref())
+                .map_err(|e| e.to_string());
+            let _ = tx.send(AppMessage::DiagnosticBundleCreated(result));
+        });
+    }
+
+    /// Handle gallery actions
+    pub fn handle_action(&mut self, action: GalleryAction, cx: &mut Context<Self>) {
+        match action {
+            GalleryAction::Select { path, modifiers } => {
+                self.handle_select(path, modifiers, cx);
+            }
+            GalleryAction::Open(path) => {
+                self.open_file(&path);
+            }
+            GalleryAction::ContextMenu { paths, position } => {
+                self.show_context_menu(&paths, position, cx);
+            }
+            GalleryAction::StartDrag(paths) => {
+                self.start_drag(&paths);
+            }
+            GalleryAction::LoadMore => {
+                self.load_more(cx);
+            }
+            GalleryAction::ClearSelection => {
+                if !self.selected.is_empty() {
+                    self.selected.clear();
+                    self.last_selected = None;
+                    cx.notify();
+                }
+            }
+        }
+    }
+
+    /// Handle selection with modifiers
+    fn handle_select(&mut self, path: PathBuf, modifiers: Modifiers, cx: &mut Context<Self>) {
+        self.focused = Some(path.clone());
+        if modifiers.control {
+            // Ctrl+click: toggle selection
+            if self.selected.contains(&path) {
+                self.selected.remove(&path);
+            } else {
+                self.selected.insert(path.clone());
+            }
+            self.last_selected = Some(path);
+        } else if modifiers.shift {
+            // Shift+click: range selection
+            if let Some(last) = &self.last_selected {
+                let loaded = self.screenshots.loaded();
+                let last_idx = loaded.iter().position(|s| &s.path == last);
+                let current_idx = loaded.iter().position(|s| s.path == path);
+
+                if let (Some(start), Some(end)) = (last_idx, current_idx) {
+                    let (start, end) = if start <= end {
+                        (start, end)
+                    } else {
+                        (end, start)
+                    };
+                    for info in &loaded[start..=end] {
+                        self.selected.insert(info.path.clone());
+                    }
+                }
+            } else {
+                self.selected.clear();
+                self.selected.insert(path.clone());
+                self.last_selected = Some(path);
+            }
+        } else {
+            // Normal click: single selection
+            self.selected.clear();
+            self.selected.insert(path.clone());
+            self.last_selected = Some(path);
+        }
+        cx.notify();
+    }
+
+    /// Open file with default application
+    fn open_file(&self, path: &PathBuf) {
+        info!("Opening file: {:?}", path);
+        usage::mark_used(std::slice::from_ref(path));
+        #[cfg(windows)]
+        {
+            use std::os::windows::process::CommandExt;
+            const CREATE_NO_WINDOW: u32 = 0x08000000;
+            let _ = std::process::Command::new("cmd")
+                .args(["/C", "start", "", &path.to_string_lossy()])
+                .creation_flags(CREATE_NO_WINDOW)
+                .spawn();
+        }
+        #[cfg(not(windows))]
+        {
+            let _ = open::that(path);
+        }
+    }
+
+    /// Show Windows context menu for files
+    fn show_context_menu(
+        &mut self,
+        paths: &[PathBuf],
+        _position: Point<Pixels>,
+        cx: &mut Context<Self>,
+    ) {
+        // Shell extensions would enumerate missing files too
+        let paths: Vec<PathBuf> = paths
+            .iter()
+            .filter(|p| crate::paths::long_path(p).exists())
+            .cloned()
+            .collect();
+        if paths.is_empty() {
+            return;
+        }
+
+        // Shell extensions can take seconds on a big selection, and the menu
+        // blocks the UI thread while they do, so ask first
+        let threshold = cx.global::<AppState>().settings.lock().context_menu_confirm_threshold;
+        if needs_context_menu_confirm(paths.len(), threshold) {
+            info!("Context menu for {} files (over {}), asking first", paths.len(), threshold);
+            self.context_menu_prompt = Some(ContextMenuPrompt::Confirm(paths));
+            cx.notify();
+            return;
+        }
+        self.open_shell_context_menu(&paths, cx);
+    }
+
+    fn open_shell_context_menu(&mut self, paths: &[PathBuf], cx: &mut Context<Self>) {
+        info!("Context menu for {} files", paths.len());
+        // Context menu MUST run on UI thread (same thread that owns the window)
+        // This will block the UI while the menu is open, but that's expected behavior
+        let command = crate::ui::show_shell_context_menu(paths);
+        self.run_context_menu_command(command, paths, cx);
+    }
+
+    fn run_context_menu_command(
+        &mut self,
+        command: Option<ContextMenuCommand>,
+        paths: &[PathBuf],
+        cx: &mut Context<Self>,
+    ) {
+        match command {
+            Some(ContextMenuCommand::Details) => {
+                self.details_path = paths.first().cloned();
+                self.details_collapsed = false;
+                cx.notify();
+            }
+            Some(ContextMenuCommand::Edit) => {
+                if let Some(path) = paths.first() {
+                    match crate::editor::open_in_editor(path) {
+                        Ok(()) => usage::mark_used(std::slice::from_ref(path)),
+                        Err(e) => error!("Failed to open {:?} in editor: {}", path, e),
+                    }
+                }
+            }
+            Some(ContextMenuCommand::SelectSimilar) => {
+                if let Some(path) = paths.first() {
+                    self.select_similar(path.clone(), cx);
+                }
+            }
+            Some(ContextMenuCommand::FindSimilar) => {
+                if let Some(path) = paths.first() {
+                    self.find_similar(path.clone(), cx);
+                }
+            }
+            Some(ContextMenuCommand::FixTimestamps) => self.plan_timestamp_fix(paths.to_vec(), cx),
+            Some(ContextMenuCommand::CopyInfo) => self.copy_info(paths.to_vec(), cx),
+            Some(ContextMenuCommand::Organize) => self.organize_selected(paths.to_vec(), cx),
+            Some(ContextMenuCommand::Preview) => {
+                if let Some(path) = paths.first() {
+                    self.handle_select(path.clone(), Modifiers::default(), cx);
+                    self.preview = Some(path.clone());
+                }
+            }
+            None => {}
+        }
+    }
+
+    /// Switch to the other saved window layout
+    fn toggle_window_layout(&mut self, cx: &mut Context<Self>) {
+        let next = {
+            let settings = cx.global::<AppState>().settings.lock();
+            settings
+                .next_layout()
+                .and_then(|slot| Some((slot, settings.layout(slot)?)))
+        };
+        let Some((slot, layout)) = next else {
+            self.show_toast(t!("notifications.layout.none").to_string(), cx);
+            return;
+        };
+        if !crate::tray::apply_window_layout(layout) {
+            self.show_toast(t!("notifications.layout.failed").to_string(), cx);
+            return;
+        }
+        {
+            let mut settings = cx.global::<AppState>().settings.lock();
+            settings.active_layout = Some(slot);
+            let _ = settings.save();
+        }
+        crate::tray::set_active_layout(Some(layout));
+        info!("Switched to window layout {}", slot.label());
+        cx.notify();
+    }
+
+    /// Remember the window's position and size as a layout, and make it the active one
+    fn save_window_layout(&mut self, slot: LayoutSlot, cx: &mut Context<Self>) {
+        let Some(layout) = crate::tray::current_window_layout() else {
+            self.show_toast(t!("notifications.layout.failed").to_string(), cx);
+            return;
+        };
+        {
+            let mut settings = cx.global::<AppState>().settings.lock();
+            settings.set_layout(slot, layout);
+            settings.active_layout = Some(slot);
+            let _ = settings.save();
+        }
+        crate::tray::set_active_layout(Some(layout));
+        info!("Saved window layout {}: {:?}", slot.label(), layout);
+        self.show_toast(t!("notifications.layout.saved", name = slot.label()).to_string(), cx);
+    }
+
+    /// Convert a sample of the library's PNGs in memory to show what
+    /// auto-convert would save
+    fn estimate_savings(&mut self, cx: &mut Context<Self>) {
+        if self.estimating_savings {
+            return;
+        }
+        let (directory, options, tx) = {
+            let app_state = cx.global::<AppState>();
+            let settings = app_state.settings.lock();
+            (
+                settings.screenshot_directory.clone(),
+                convert::ConvertOptions::from_settings(&settings),
+                app_state.message_tx.clone(),
+            )
+        };
+        self.estimating_savings = true;
+        std::thread::spawn(move || {
+            let pngs: Vec<PathBuf> = crate::watcher::ScreenshotWatcher::collect_images(&directory)
+                .into_iter()
+                .filter(|path| convert::is_convertible(path))
+                .collect();
+            let estimate = convert::estimate_savings(&pngs, options);
+            let _ = tx.send(AppMessage::SavingsEstimated(estimate));
+        });
+        cx.notify();
+    }
+
+    /// Read the content dates of `paths` and open the preview dialog for the
+    /// ones whose modified time is off
+    fn plan_timestamp_fix(&mut self, paths: Vec<PathBuf>, cx: &mut Context<Self>) {
+        let (patterns, tx) = {
+            let app_state = cx.global::<AppState>();
+            let settings = app_state.settings.lock();
+            (
+                crate::filename_date::compile(&settings.file_name_date_patterns),
+                app_state.message_tx.clone(),
+            )
+        };
+        info!("Reading content dates of {} files", paths.len());
+        std::thread::spawn(move || {
+            let plan = content_date::plan(&paths, &patterns);
+            let _ = tx.send(AppMessage::TimestampPlan(plan));
+        });
+    }
+
+    /// Copy a one-line summary of each file. Dimensions come from the cached
+    /// header probe; if some files weren't probed yet, they're read on a
+    /// background thread while the button shows a spinner.
+    fn copy_info(&mut self, paths: Vec<PathBuf>, cx: &mut Context<Self>) {
+        if paths.is_empty() || self.copying_info {
+            return;
+        }
+        let cached: Option<Vec<String>> = paths.iter().map(|p| info_summary(p, false)).collect();
+        if let Some(lines) = cached {
+            self.write_info(lines, cx);
+            return;
+        }
+
+        self.copying_info = true;
+        cx.notify();
+        let tx = cx.global::<AppState>().message_tx.clone();
+        std::thread::spawn(move || {
+            let lines = paths.iter().filter_map(|p| info_summary(p, true)).collect();
+            let _ = tx.send(AppMessage::InfoSummaries(lines));
+        });
+    }
+
+    fn write_info(&mut self, lines: Vec<String>, cx: &mut Context<Self>) {
+        if !lines.is_empty() {
+            cx.write_to_clipboard(ClipboardItem::new_string(lines.join("\n")));
+            let message = t!(&plural_key("notifications.info_copied", lines.len()), count = lines.len())
+                .to_string();
+            self.show_toast(message, cx);
+        }
+        cx.notify();
+    }
+
+    /// Delete the selected screenshots, to the Recycle Bin unless `permanent`.
+    /// They leave the gallery as soon as the delete is done; the watcher's
+    /// Remove events for them arrive later and are ignored.
+    fn delete_selection(&mut self, permanent: bool, cx: &mut Context<Self>) {
+        if self.settings_open || self.selected.is_empty() {
+            return;
+        }
+        let files: Vec<PathBuf> = self.selected.iter().cloned().collect();
+        info!(
+            "Deleting {} selected files ({})",
+            files.len(),
+            if permanent { "permanently" } else { "to Recycle Bin" }
+        );
+        let tx = cx.global::<AppState>().message_tx.clone();
+        std::thread::spawn(move || {
+            let deleted = if permanent {
+                files
+                    .iter()
+                    .filter(|path| match crate::file_ops::remove_file(path) {
+                        Ok(()) => true,
+                        Err(e) => {
+                            error!("Failed to delete {:?}: {}", path, e);
+                            false
+                        }
+                    })
+                    .cloned()
+                    .collect()
+            } else {
+                crate::recycle::recycle_files(&files)
+            };
+            let failed = files.len() - deleted.len();
+            let _ = tx.send(AppMessage::FilesDeleted(deleted, failed, permanent));
+        });
+    }
+
+    /// Write the previewed content dates as modified times
+    fn apply_timestamp_fix(&mut self, cx: &mut Context<Self>) {
+        let Some(plan) = self.timestamp_plan.take() else {
+            return;
+        };
+        let tx = cx.global::<AppState>().message_tx.clone();
+        std::thread::spawn(move || {
+            let mut fixed = 0;
+            let mut failed = 0;
+            for fix in &plan.fixes {
+                match content_date::apply(fix) {
+                    Ok(()) => {
+                        fixed += 1;
+                        let _ = tx.send(AppMessage::ScreenshotRedated(fix.path.clone()));
+                    }
+                    Err(e) => {
+                        error!("{:#}", e);
+                        failed += 1;
+                    }
+                }
+            }
+            info!("Fixed {} timestamps ({} failed)", fixed, failed);
+            let _ = tx.send(AppMessage::TimestampsFixed(fixed, failed));
+        });
+    }
+
+    /// Look up screenshots that look like `path`; they're added to the
+    /// selection when [`AppMessage::SimilarFound`] comes back
+    fn select_similar(&mut self, path: PathBuf, cx: &mut Context<Self>) {
+        let (tx, config, threshold) = {
+            let app_state = cx.global::<AppState>();
+            let settings = app_state.settings.lock();
+            (
+                app_state.message_tx.clone(),
+                index_config(&settings),
+                settings.similar_threshold,
+            )
+        };
+        let Some(config) = config else {
+            return;
+        };
+        info!("Looking for screenshots similar to {:?} (at least {}%)", path, threshold);
+        // One over the cap, to tell whether matches were left out
+        crate::indexer::find_similar(
+            path,
+            config,
+            threshold as f32 / 100.0,
+            SIMILAR_SELECT_CAP + 1,
+            tx,
+        );
+    }
+
+    /// Show the screenshots that look most like `path` as search results,
+    /// indexing it first if needed; answered with [`AppMessage::SearchResults`]
+    fn find_similar(&mut self, path: PathBuf, cx: &mut Context<Self>) {
+        let (models_downloaded, prewarm) = {
+            let settings = cx.global::<AppState>().settings.lock();
+            (settings.models_downloaded, settings.model_prewarm)
+        };
+        let Some(config) = Self::index_rows_config(cx).filter(|_| models_downloaded) else {
+            self.show_toast(t!("notifications.similar.indexing_off").to_string(), cx);
+            return;
+        };
+        info!("Finding screenshots similar to {:?}", path);
+        self.last_search_offer = false;
+        // From the full gallery (not one set of results to the next)
+        if self.search_results.is_none() {
+            self.pre_search_offset = Some(self.gallery_scroll.offset());
+        }
+        self.similar_to = Some(path.clone());
+        let tx = cx.global::<AppState>().message_tx.clone();
+
+        if let Some(vision_model) = PREWARMED_VISION_MODEL.lock().clone() {
+            crate::indexer::search_similar(path, config, vision_model, tx, 100);
+            cx.notify();
+            return;
+        }
+
+        // First use (or models aren't kept resident): load in the background
+        self.show_toast(t!("notifications.models.loading").to_string(), cx);
+        cx.notify();
+        std::thread::spawn(move || match load_vision_model(prewarm) {
+            Ok(vision_model) => crate::indexer::search_similar(path, config, vision_model, tx, 100),
+            Err(e) => {
+                error!("Failed to load vision model for a similar search: {}", e);
+                let _ = tx.send(AppMessage::SearchResults(Vec::new(), None));
+            }
+        });
+    }
+
+    /// Show a toast. Repaints are scheduled for its fade and expiry, so it
+    /// leaves on time even when nothing else redraws the window.
+    fn show_toast(&mut self, message: String, cx: &mut Context<Self>) {
+        self.toast_manager.show(message);
+        self.schedule_toast_tick(cx);
+    }
+
+    fn schedule_toast_tick(&mut self, cx: &mut Context<Self>) {
+        let Some(delay) = self.toast_manager.next_tick() else {
+            self.toast_tick = None;
+            return;
+        };
+        self.toast_tick = Some(cx.spawn(async move |this, cx| {
+            cx.background_executor().timer(delay).await;
+            let _ = this.update(cx, |this, cx| {
+                this.toast_manager.update();
+                this.schedule_toast_tick(cx);
+                cx.notify();
+            });
+        }));
+    }
+
+    /// Copy files to the clipboard and toast the count (or that it failed)
+    fn copy_files(&mut self, files: &[PathBuf], cx: &mut Context<Self>) {
+        let count = files.len();
+        info!("Attempting to copy {} files to clipboard", count);
+        if clipboard::copy_files_to_clipboard(files) {
+            info!("Successfully copied {} files to clipboard", count);
+            usage::mark_used(files);
+            // Send message to show notification (will be handled in process_messages)
+            let app_state = cx.global::<AppState>();
+            let _ = app_state.message_tx.send(AppMessage::CopiedToClipboard(count));
+        } else {
+            error!("Failed to copy files to clipboard");
+            self.show_toast(t!("notifications.copy_failed").to_string(), cx);
+            cx.notify();
+        }
+    }
+
+    /// Answer the oldest read-only prompt. `remember` turns on "Handle
+    /// read-only files" and applies to everything still waiting.
+    fn answer_read_only_prompt(&mut self, make_writable: bool, remember: bool, cx: &mut Context<Self>) {
+        if self.read_only_prompts.is_empty() {
+            return;
+        }
+        let ops: Vec<FileOp> = if remember {
+            {
+                let app_state = cx.global::<AppState>();
+                let mut settings = app_state.settings.lock();
+                settings.handle_read_only_files = true;
+                let _ = settings.save();
+            }
+            file_ops::set_clear_read_only(true);
+            self.read_only_prompts.drain(..).collect()
+        } else {
+            vec![self.read_only_prompts.remove(0)]
+        };
+
+        if make_writable {
+            // Retries wait out locked files, so keep them off the UI thread
+            let tx = cx.global::<AppState>().message_tx.clone();
+            std::thread::spawn(move || {
+                let mut failed = 0;
+                for op in ops {
+                    match file_ops::retry(&op, true) {
+                        // A delete shows up through the watcher; a move was the organizer's
+                        Ok(()) => {
+                            if let FileOp::Rename { from, to } = op {
+                                crate::timeline::record_moved(
+                                    &from,
+                                    &to,
+                                    crate::timeline::EventKind::Organized(to.clone()),
+                                );
+                                organizer::report_moved(&from, &to, &tx);
+                            }
+                        }
+                        Err(e) => {
+                            error!("Retrying {:?} failed: {}", op, e);
+                            failed += 1;
+                        }
+                    }
+                }
+                let _ = tx.send(AppMessage::BlockedRetried(failed));
+            });
+        } else {
+            // Skipped: the rewritten copy waiting to replace the file isn't needed
+            for op in ops {
+                if let FileOp::Replace { from, .. } = op {
+                    let _ = std::fs::remove_file(crate::paths::long_path(&from));
+                }
+            }
+        }
+        cx.notify();
+    }
+
+    /// Start native drag operation
+    fn start_drag(&self, paths: &[PathBuf]) {
+        if paths.is_empty() {
+            return;
+        }
+        info!("Starting drag with {} files", paths.len());
+        // Note: This is called from the drag handler,
+        // actual drag is initiated there
+    }
+
+    /// Load more items for infinite scroll
+    fn load_more(&mut self, cx: &mut Context<Self>) {
+        let target = (self.visible_count + PAGE_SIZE).min(self.screenshots.len());
+        self.screenshots.hydrate(target, ScreenshotInfo::from_path_ref);
+        let new_count = target.min(self.screenshots.loaded().len());
+        if new_count > self.visible_count {
+            self.visible_count = new_count;
+            self.last_load_more = Some(Instant::now());
+            self.probe_dimensions(cx);
+            cx.notify();
+        }
+    }
+
+    /// Add a batch from a folder scan with one sort
+    fn add_scanned(&mut self, mut infos: Vec<ScreenshotInfo>, cx: &mut Context<Self>) {
+        infos.retain(|info| !self.screenshots.contains(&info.path));
+        for info in &infos {
+            self.deleted_here.remove(&info.path);
+            removals::restored(&info.path);
+        }
+        if self.screenshots.extend(infos) == 0 {
+            return;
+        }
+        self.trim_screenshots();
+        set_latest_screenshots(self.screenshots.newest(LATEST_CANDIDATES));
+        self.probe_dimensions(cx);
+        cx.notify();
+    }
+
+    /// Unload screenshots past the cap that aren't on screen. Not while
+    /// searching: matches past the cap were loaded to be shown.
+    fn trim_screenshots(&mut self) {
+        if self.search_results.is_none() {
+            self.screenshots.trim(self.visible_count);
+        }
+    }
+
+    /// Check if a page is still being appended (shows the spinner)
+    fn is_loading_more(&self) -> bool {
+        self.last_load_more
+            .is_some_and(|at| at.elapsed() < LOAD_MORE_DEBOUNCE)
+    }
+
+    /// Load the next page when the bottom of the gallery comes within one
+    /// viewport height. Works for wheel, trackpad flings and scrollbar drags
+    /// since it only looks at the scroll offset.
+    fn check_infinite_scroll(&mut self, cx: &mut Context<Self>) {
+        if self.settings_open || !self.has_more() {
+            return;
+        }
+
+        // Wait for the appended page to lay out before measuring again
+        if self.is_loading_more() {
+            cx.notify();
+            return;
+        }
+
+        let viewport = self.gallery_scroll.bounds().size.height;
+        if viewport <= px(0.0) {
+            return;
+        }
+
+        // offset.y runs from 0 (top) to -max_offset (bottom)
+        let remaining = self.gallery_scroll.max_offset().height + self.gallery_scroll.offset().y;
+        if remaining <= viewport {
+            self.load_more(cx);
+        }
+    }
+
+    /// Move the keyboard focus through the gallery grid, selecting the tile
+    /// it lands on (or extending the selection to it with Shift)
+    fn move_focus(&mut self, direction: GridMove, extend: bool, cx: &mut Context<Self>) {
+        let groups = self.display_groups(cx);
+        let group_sizes: Vec<usize> = groups.iter().map(Vec::len).collect();
+        let order: Vec<&PathBuf> = groups.iter().flatten().collect();
+
+        let bounds = self.gallery_scroll.bounds();
+        let width: f32 = bounds.size.width.into();
+        let columns = keynav::columns_for_width(width, self.thumbnail_size);
+        let from = self
+            .focused
+            .as_ref()
+            .and_then(|focused| order.iter().position(|p| *p == focused));
+        let Some(to) = keynav::step(&group_sizes, columns, from, direction) else {
+            return;
+        };
+        let path = order[to].clone();
+
+        let modifiers = Modifiers {
+            shift: extend,
+            ..Default::default()
+        };
+        self.handle_select(path, modifiers, cx);
+
+        // Scroll just far enough to show the whole tile
+        let top = keynav::tile_top(&group_sizes, columns, self.thumbnail_size, self.has_group_headers(), to);
+        let bottom = top + self.thumbnail_size as f32;
+        let viewport: f32 = bounds.size.height.into();
+        let offset: f32 = self.gallery_scroll.offset().y.into();
+        let scrolled = -offset;
+        let target = if top < scrolled {
+            top
+        } else if bottom > scrolled + viewport {
+            bottom - viewport + keynav::TILE_GAP
+        } else {
+            return;
+        };
+        let max: f32 = self.gallery_scroll.max_offset().height.into();
+        self.gallery_scroll.set_offset(point(px(0.0), px(-target.clamp(0.0, max))));
+    }
+
+    /// Leave search results for the full gallery without losing the place:
+    /// the result last selected (or focused) stays selected and is scrolled
+    /// to and flashed; with none, the gallery goes back to where it was
+    /// before the search
+    fn clear_search(&mut self, cx: &mut Context<Self>) {
+        self.search_hint = None;
+        self.similar_to = None;
+        let Some(results) = self.search_results.take() else {
+            return;
+        };
+        let before = self.pre_search_offset.take();
+        let target = self
+            .last_selected
+            .clone()
+            .filter(|path| self.selected.contains(path))
+            .or_else(|| self.focused.clone())
+            .filter(|path| results.contains(path));
+
+        match target.filter(|path| self.scroll_to_tile(path, cx)) {
+            Some(path) => {
+                self.selected.insert(path.clone());
+                self.last_selected = Some(path.clone());
+                self.focused = Some(path.clone());
+                self.flash(path, cx);
+            }
+            None => {
+                if let Some(offset) = before {
+                    self.gallery_scroll.set_offset(offset);
+                }
+            }
+        }
+        cx.notify();
+    }
+
+    /// Scroll the gallery to put the tile for `path` in the middle of the
+    /// view; false if it isn't shown
+    fn scroll_to_tile(&self, path: &Path, cx: &App) -> bool {
+        let groups = self.display_groups(cx);
+        let group_sizes: Vec<usize> = groups.iter().map(Vec::len).collect();
+        let Some(index) = groups.iter().flatten().position(|p| p == path) else {
+            return false;
+        };
+
+        let bounds = self.gallery_scroll.bounds();
+        let width: f32 = bounds.size.width.into();
+        let columns = keynav::columns_for_width(width, self.thumbnail_size);
+        let top = keynav::tile_top(&group_sizes, columns, self.thumbnail_size, self.has_group_headers(), index);
+        let viewport: f32 = bounds.size.height.into();
+        let target = top - (viewport - self.thumbnail_size as f32) / 2.0;
+        // The full gallery is laid out on the next frame, which clamps an
+        // offset past its end
+        self.gallery_scroll.set_offset(point(px(0.0), px(-target.max(0.0))));
+        true
+    }
+
+    /// Highlight a tile for a moment
+    fn flash(&mut self, path: PathBuf, cx: &mut Context<Self>) {
+        self.flashing = Some(path);
+        self.flash_tick = Some(cx.spawn(async move |this, cx| {
+            cx.background_executor().timer(FLASH_DURATION).await;
+            let _ = this.update(cx, |this, cx| {
+                this.flashing = None;
+                this.flash_tick = None;
+                cx.notify();
+            });
+        }));
+    }
+
+    /// Gallery tiles as shown: loaded, passing the search, in date or folder
+    /// groups (or one group) in the current sort order; search results are
+    /// one group in relevance order
+    fn display_groups(&self, cx: &App) -> Vec<Vec<PathBuf>> {
+        let visible: Vec<ScreenshotInfo> = self
+            .visible_screenshots()
+            .iter()
+            .filter(|s| self.matches_filters(s))
+            .cloned()
+            .collect();
+        let root = cx.global::<AppState>().settings.lock().screenshot_directory.clone();
+        crate::ui::display_groups(
+            &visible,
+            self.search_results.as_deref(),
+            self.screenshots.sort(),
+            self.grouping,
+            &root,
+        )
+    }
+
+    /// Whether the gallery shows group headers (never for search results)
+    fn has_group_headers(&self) -> bool {
+        self.search_results.is_none() && self.grouping.has_headers(self.screenshots.sort())
+    }
+
+    /// Show the next (or previous) screenshot in gallery order in the
+    /// preview, selecting it so the gallery is there when the preview closes
+    fn step_preview(&mut self, forward: bool, cx: &mut Context<Self>) {
+        let Some(current) = self.preview.clone() else {
+            return;
+        };
+        let mut order: Vec<PathBuf> = self.display_groups(cx).into_iter().flatten().collect();
+        let Some(index) = order.iter().position(|p| *p == current) else {
+            return;
+        };
+        // Past the end of the loaded pages, load the next one
+        if forward && index + 1 == order.len() && self.has_more() {
+            self.load_more(cx);
+            order = self.display_groups(cx).into_iter().flatten().collect();
+        }
+        let next = if forward { Some(index + 1) } else { index.checked_sub(1) };
+        if let Some(path) = next.and_then(|next| order.get(next)).cloned() {
+            self.handle_select(path.clone(), Modifiers::default(), cx);
+            self.preview = Some(path);
+        }
+    }
+
+    /// Check if a screenshot passes the active search filter
+    fn matches_search(&self, path: &PathBuf) -> bool {
+        self.search_results
+            .as_ref()
+            .is_none_or(|results| results.contains(path))
+    }
+
+    /// Check if a screenshot has the filtered orientation. Ones whose
+    /// dimensions aren't known yet are left out until they're probed.
+    fn matches_orientation(&self, info: &ScreenshotInfo) -> bool {
+        self.orientation_filter
+            .is_none_or(|wanted| Orientation::cached(info) == Some(wanted))
+    }
+
+    /// Check if a screenshot passes the search and the orientation filter
+    fn matches_filters(&self, info: &ScreenshotInfo) -> bool {
+        self.matches_search(&info.path) && self.matches_orientation(info)
+    }
+
+    /// Number of screenshots in the current (filtered) set, loaded or not
+    fn filtered_count(&self) -> usize {
+        if self.search_results.is_none() && self.orientation_filter.is_none() {
+            return self.screenshots.len();
+        }
+        // Stubs matching the search were loaded with the results; a stub's
+        // orientation is never known
+        self.screenshots
+            .loaded()
+            .iter()
+            .filter(|s| self.matches_filters(s))
+            .count()
+    }
+
+    /// Show only screenshots of one orientation, or all again
+    fn set_orientation_filter(&mut self, orientation: Option<Orientation>, cx: &mut Context<Self>) {
+        info!("Orientation filter: {:?}", orientation);
+        self.orientation_filter = orientation;
+        self.gallery_scroll.set_offset(point(px(0.0), px(0.0)));
+        self.probe_dimensions(cx);
+        cx.notify();
+    }
+
+    /// Read the dimensions of loaded screenshots the orientation filter
+    /// doesn't know yet, off the UI thread, redrawing as they come in.
+    /// Until then the filter skips them instead of waiting.
+    fn probe_dimensions(&mut self, cx: &mut Context<Self>) {
+        if self.orientation_filter.is_none() || self.dimension_probe.is_some() {
+            return;
+        }
+        let unknown: Vec<(PathBuf, SystemTime)> = self
+            .visible_screenshots()
+            .iter()
+            .filter(|s| crate::thumbnail::cached_dimensions(&s.path, s.modified).is_none())
+            .map(|s| (s.path.clone(), s.modified))
+            .collect();
+        if unknown.is_empty() {
+            return;
+        }
+        debug!("Probing dimensions of {} screenshots", unknown.len());
+        self.dimension_probe = Some(cx.spawn(async move |this, cx| {
+            for chunk in unknown.chunks(DIMENSION_PROBE_CHUNK) {
+                let chunk = chunk.to_vec();
+                cx.background_executor()
+                    .spawn(async move {
+                        for (path, modified) in chunk {
+                            crate::thumbnail::dimensions(&path, modified);
+                        }
+                    })
+                    .await;
+                if this.update(cx, |_, cx| cx.notify()).is_err() {
+                    return;
+                }
+            }
+            let _ = this.update(cx, |this, cx| {
+                this.dimension_probe = None;
+                // Pages loaded meanwhile
+                this.probe_dimensions(cx);
+            });
+        }));
+    }
+
+    /// Select every screenshot in the current set regardless of pagination,
+    /// loading pages as needed so the whole selection is on screen
+    fn select_all(&mut self, cx: &mut Context<Self>) {
+        self.selected = self
+            .screenshots
+            .loaded()
+            .iter()
+            .filter(|s| self.matches_filters(s))
+            .map(|s| s.path.clone())
+            .collect();
+        // Without a filter that's everything, stubs included. Their paths are
+        // enough, so they stay unloaded; search matches were loaded already
+        // and a stub's orientation isn't known.
+        if self.search_results.is_none() && self.orientation_filter.is_none() {
+            self.selected
+                .extend(self.screenshots.stub_paths().map(Path::to_path_buf));
+        }
+
+        if let Some(last) = self
+            .screenshots
+            .loaded()
+            .iter()
+            .rposition(|s| self.selected.contains(&s.path))
+        {
+            self.visible_count = self.visible_count.max(last + 1);
+        }
+        info!("Selected all {} screenshots", self.selected.len());
+        cx.notify();
+    }
+
+    /// Select only the screenshots on the loaded page(s)
+    fn select_page(&mut self, cx: &mut Context<Self>) {
+        self.selected = self
+            .visible_screenshots()
+            .iter()
+            .filter(|s| self.matches_filters(s))
+            .map(|s| s.path.clone())
+            .collect();
+        info!("Selected {} screenshots on the loaded page", self.selected.len());
+        cx.notify();
+    }
+
+    /// Get currently visible screenshots
+    fn visible_screenshots(&self) -> &[ScreenshotInfo] {
+        let loaded = self.screenshots.loaded();
+        &loaded[..self.visible_count.min(loaded.len())]
+    }
+
+    /// Check if there are more items to load
+    fn has_more(&self) -> bool {
+        self.visible_count < self.screenshots.len()
+    }
+
+    /// Get selected paths for context menu
+    /// Gallery pointer state (used by the gallery tiles)
+    pub fn click_tracker(&mut self) -> &mut ClickTracker {
+        &mut self.click_tracker
+    }
+
+    pub fn get_selected_paths(&self) -> Vec<PathBuf> {
+        self.selected.iter().cloned().collect()
+    }
+
+    /// Check if a path is selected
+    pub fn is_path_selected(&self, path: &PathBuf) -> bool {
+        self.selected.contains(path)
+    }
+
+    /// Check if any items are selected
+    pub fn has_selection(&self) -> bool {
+        !self.selected.is_empty()
+    }
+}
+
+impl Render for Sukusho {
+    fn render(&mut self, window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        perf::begin_frame();
+
+        // Process any pending messages
+        perf::time(perf::Phase::Messages, || self.process_messages(window, cx));
+
+        // Matches past the cap are let go once the search is cleared
+        self.trim_screenshots();
+
+        // Dialogs opened or closed by those messages (or by clicks) take or give back focus
+        let modal_specs = self.modal_specs();
+        self.modals.sync(&modal_specs, window, cx);
+        let viewport = window.viewport_size();
+
+        // Save window size if changed (position is always centered, no need to save)
+        // window.bounds() returns GPUI logical pixels (already DPI-scaled by GPUI)
+        let bounds = window.bounds();
+        let current_width: f32 = bounds.size.width.into();
+        let current_height: f32 = bounds.size.height.into();
+
+        let app_state = cx.global::<AppState>();
+        let settings = app_state.settings.lock();
+        let saved_width = settings.window_width;
+        let saved_height = settings.window_height;
+        drop(settings);
+
+        // Skip saving on first render (window is still being initialized)
+        if self.first_render {
+            self.first_render = false;
+            debug!("First render - skipping window size save. Current: {}x{} (GPUI logical pixels)",
+                   current_width, current_height);
+            // Update in-memory settings
+            let mut settings = app_state.settings.lock();
+            settings.window_width = current_width;
+            settings.window_height = current_height;
+            drop(settings);
+
+            // Hide window after first render if hide_window_on_start is set
+            if app_state.hide_window_on_start && !self.hidden_on_start {
+                self.hidden_on_start = true;
+                info!("First render complete - hiding window (hide_window_on_start = true)");
+                crate::tray::hide_window();
+            }
+        } else {
+            // Only save if size actually changed (avoid constant writes)
+            let size_changed = (current_width - saved_width).abs() > 1.0 || (current_height - saved_height).abs() > 1.0;
+
+            if size_changed {
+                debug!("Window size changed: {}x{} -> {}x{}", saved_width, saved_height, current_width, current_height);
+                let mut settings = app_state.settings.lock();
+                settings.window_width = current_width;
+                settings.window_height = current_height;
+                match settings.save() {
+                    Ok(_) => debug!("Window size saved successfully"),
+                    Err(e) => log::warn!("Failed to save window size: {}", e),
+                }
+            }
+        }
+
+        self.check_infinite_scroll(cx);
+
+        // Stubs included: the true total from the scan
+        let total_count = self.screenshots.len();
+        let visible_count = self.visible_screenshots().len();
+        let selected_count = self.selected.len();
+        let filtered_count = self.filtered_count();
+        let settings_open = self.settings_open;
+        if !settings_open {
+            self.settings_focus.clear();
+        }
+        let loading_more = self.has_more() && self.is_loading_more();
+
+        let root = v_flex()
+            .id("main-container")
+            .size_full()
+            // Use theme background color
+            .bg(cx.theme().background)
+            .track_focus(&self.focus_handle)
+            // Folders dropped from Explorer (settings only)
+            .on_drop(cx.listener(|this, paths: &ExternalPaths, window, cx| {
+                this.handle_external_drop(paths, window, cx);
+            }))
+            // Keyboard shortcuts
+            .on_key_down(cx.listener(|this, event: &KeyDownEvent, window, cx| {
+                // An open dialog gets every key; nothing reaches the gallery
+                if this.modals.stack().top().is_some() {
+                    this.handle_modal_key(event, cx);
+                    cx.stop_propagation();
+                    return;
+                }
+
+                // Skip handling if a text input has focus
+                if this.search_input_focused
+                    || this.organizer_format_input_focused
+                    || this.file_name_input_focused
+                    || this.exclude_input_focused
+                    || this.editor_input_focused
+                    || this.api_port_input_focused
+                {
+                    return;
+                }
+
+                // Handle hotkey recording
+                if this.recording_hotkey {
+                    // ESC cancels recording
+                    if event.keystroke.key.as_str() == "escape" {
+                        this.recording_hotkey = false;
+                        cx.notify();
+                        return;
+                    }
+
+                    // Try to convert keystroke to hotkey string
+                    if let Some(hotkey_str) = crate::hotkey::keystroke_to_hotkey_string(&event.keystroke) {
+                        info!("Recorded hotkey: {}", hotkey_str);
+                        this.apply_hotkey(hotkey_str, window, cx);
+                        this.recording_hotkey = false;
+                        cx.notify();
+                    }
+                    return;
+                }
+
+                // Handle in-app shortcut recording. Unlike the global hotkey,
+                // keys without modifiers (Esc and Space included) are fine; the
+                // Cancel button stops recording.
+                if let Some(action) = this.recording_shortcut {
+                    if let Some(binding) = Binding::from_keystroke(&event.keystroke) {
+                        this.rebind_shortcut(action, binding, cx);
+                    }
+                    return;
+                }
+
+                // Tab through settings controls; Space, Enter and arrows run them
+                if this.settings_open && this.handle_settings_key(event, window, cx) {
+                    return;
+                }
+
+                if let Some(action) = this.shortcuts.action_for(&event.keystroke) {
+                    this.run_shortcut(action, window, cx);
+                    return;
+                }
+
+                // Gallery keyboard navigation
+                let modifiers = &event.keystroke.modifiers;
+                if this.settings_open || modifiers.control || modifiers.alt || modifiers.platform {
+                    return;
+                }
+                if let Some(direction) = GridMove::from_key(&event.keystroke.key) {
+                    this.move_focus(direction, modifiers.shift, cx);
+                } else if event.keystroke.key == "enter" {
+                    if let Some(path) = this.focused.clone().filter(|p| this.selected.contains(p)) {
+                        this.open_file(&path);
+                    }
+                }
+            }))
+            // Header bar with window controls - enhanced styling
+            .child(
+                div()
+                    .id("header-container")
+                    .w_full()
+                    .border_b_1()
+                    .border_color(cx.theme().border)
+                    // Use theme muted background for header
+                    .bg(cx.theme().popover)
+                    .child(
+                        h_flex()
+                            .w_full()
+                            .px_4()
+                            .py_3()
+                            .gap_3()
+                            .items_center()
+                            // Title area - draggable using native Windows API
+                            .child(
+                                h_flex()
+                                    .id("header-drag-area")
+                                    .flex_1()
+                                    .gap_3()
+                                    .items_center()
+                                    .h(px(28.0))
+                                    .cursor(CursorStyle::Arrow)
+                                    // Use native Windows drag on mouse down
+                                    .on_mouse_down(MouseButton::Left, |_, window, _cx| {
+                                        start_window_drag(window);
+                                    })
+                                    // App icon/logo placeholder
+                                    .child(
+                                        div()
+                                            .w(px(24.0))
+                                            .h(px(24.0))
+                                            .rounded(px(6.0))
+                                            .bg(cx.theme().primary)
+                                            .flex()
+                                            .items_center()
+                                            .justify_center()
+                                            .text_color(cx.theme().primary_foreground)
+                                            .text_xs()
+                                            .font_weight(FontWeight::BOLD)
+                                            .child("📷"),
+                                    )
+                                    .child(
+                                        div()
+                                            .text_lg()
+                                            .font_weight(FontWeight::BOLD)
+                                            .text_color(cx.theme().foreground)
+                                            .child(t!("app.header.title").to_string()),
+                                    )
+                                    .child(
+                                        div()
+                                            .px_2()
+                                            .py_1()
+                                            .rounded(px(12.0))
+                                            .bg(cx.theme().muted)
+                                            .text_xs()
+                                            .text_color(cx.theme().muted_foreground)
+                                            .child(t!("app.header.counter", visible = format_count(visible_count), total = format_count(total_count)).to_string()),
+                                    )
+                                    .child(self.render_sort_selector(cx))
+                                    .child(self.render_orientation_filter(cx))
+                                    .when(crate::watcher::is_paused(), |this| {
+                                        this.child(
+                                            div()
+                                                .id("paused-badge")
+                                                .px_2()
+                                                .py_1()
+                                                .rounded(px(12.0))
+                                                .border_1()
+                                                .border_color(cx.theme().warning)
+                                                .text_xs()
+                                                .font_weight(FontWeight::MEDIUM)
+                                                .text_color(cx.theme().warning)
+                                                .cursor_pointer()
+                                                .on_click(cx.listener(|this, _, _, cx| {
+                                                    this.resume_processing(cx);
+                                                }))
+                                                .child(t!("app.header.paused").to_string())
+                                                .tooltip(|window, cx| {
+                                                    Tooltip::new(t!("app.header.paused_tooltip").to_string())
+                                                        .build(window, cx)
+                                                }),
+                                        )
+                                    })
+                                    .when(selected_count > 0, |this| {
+                                        this.child(
+                                            div()
+                                                .id("selected-count")
+                                                .px_2()
+                                                .py_1()
+                                                .rounded(px(12.0))
+                                                .bg(cx.theme().primary)
+                                                .text_xs()
+                                                .font_weight(FontWeight::MEDIUM)
+                                                .text_color(cx.theme().primary_foreground)
+                                                .child(t!("app.header.selected", count = format_count(selected_count), total = format_count(filtered_count)).to_string())
+                                                .tooltip(|window, cx| {
+                                                    Tooltip::new(t!("app.header.select_shortcuts").to_string())
+                                                        .build(window, cx)
+                                                }),
+                                        )
+                                    }),
+                            )
+                            // Convert the selected PNGs
+                            .when(selected_count > 0 && !settings_open, |this| {
+                                let format = cx.global::<AppState>().settings.lock().conversion_format;
+                                this.child(
+                                    div()
+                                        .id("convert-selected-btn")
+                                        .w(px(32.0))
+                                        .h(px(32.0))
+                                        .rounded(px(8.0))
+                                        .flex()
+                                        .items_center()
+                                        .justify_center()
+                                        .bg(cx.theme().muted)
+                                        .text_color(cx.theme().muted_foreground)
+                                        .when(self.converting, |s| s.opacity(0.5))
+                                        .when(!self.converting, |s| {
+                                            s.cursor_pointer()
+                                                .hover(|s| {
+                                                    s.bg(cx.theme().accent)
+                                                        .text_color(cx.theme().accent_foreground)
+                                                })
+                                                .on_click(cx.listener(|this, _, _, cx| {
+                                                    this.convert_selected(cx);
+                                                }))
+                                        })
+                                        .tooltip(move |window, cx| {
+                                            Tooltip::new(
+                                                t!(
+                                                    "app.header.convert_selected",
+                                                    format = format.display_name()
+                                                )
+                                                .to_string(),
+                                            )
+                                            .build(window, cx)
+                                        })
+                                        .child("⇄"),
+                                )
+                            })
+                            // Strip metadata from the selection
+                            .when(selected_count > 0 && !settings_open, |this| {
+                                let (current, total) = self.strip_progress;
+                                let stripping = self.stripping;
+                                this.child(
+                                    div()
+                                        .id("strip-metadata-btn")
+                                        .w(px(32.0))
+                                        .h(px(32.0))
+                                        .rounded(px(8.0))
+                                        .flex()
+                                        .items_center()
+                                        .justify_center()
+                                        .bg(cx.theme().muted)
+                                        .text_color(cx.theme().muted_foreground)
+                                        .when(stripping, |s| s.opacity(0.5))
+                                        .when(!stripping, |s| {
+                                            s.cursor_pointer()
+                                                .hover(|s| {
+                                                    s.bg(cx.theme().accent)
+                                                        .text_color(cx.theme().accent_foreground)
+                                                })
+                                                .on_click(cx.listener(|this, _, _, cx| {
+                                                    this.strip_selected(cx);
+                                                }))
+                                        })
+                                        .tooltip(move |window, cx| {
+                                            let label = if stripping {
+                                                t!("app.header.strip_metadata_progress", current = current, total = total)
+                                                    .to_string()
+                                            } else {
+                                                t!("app.header.strip_metadata").to_string()
+                                            };
+                                            Tooltip::new(label).build(window, cx)
+                                        })
+                                        .child("⌀"),
+                                )
+                            })
+                            // Organize the selection into date folders
+                            .when(selected_count > 0 && !settings_open, |this| {
+                                let organizing = self.organizing;
+                                this.child(
+                                    div()
+                                        .id("organize-selected-btn")
+                                        .w(px(32.0))
+                                        .h(px(32.0))
+                                        .rounded(px(8.0))
+                                        .flex()
+                                        .items_center()
+                                        .justify_center()
+                                        .bg(cx.theme().muted)
+                                        .text_color(cx.theme().muted_foreground)
+                                        .when(organizing, |s| s.opacity(0.5))
+                                        .when(!organizing, |s| {
+                                            s.cursor_pointer()
+                                                .hover(|s| {
+                                                    s.bg(cx.theme().accent)
+                                                        .text_color(cx.theme().accent_foreground)
+                                                })
+                                                .on_click(cx.listener(|this, _, _, cx| {
+                                                    let paths = this.selected.iter().cloned().collect();
+                                                    this.organize_selected(paths, cx);
+                                                }))
+                                        })
+                                        .tooltip(|window, cx| {
+                                            Tooltip::new(t!("app.header.organize_selected").to_string())
+                                                .build(window, cx)
+                                        })
+                                        .child("▤"),
+                                )
+                            })
+                            // Window layout toggle
+                            .child(
+                                div()
+                                    .id("layout-btn")
+                                    .w(px(32.0))
+                                    .h(px(32.0))
+                                    .rounded(px(8.0))
+                                    .cursor_pointer()
+                                    .flex()
+                                    .items_center()
+                                    .justify_center()
+                                    .bg(cx.theme().muted)
+                                    .text_color(cx.theme().muted_foreground)
+                                    .hover(|s| {
+                                        s.bg(cx.theme().accent)
+                                            .text_color(cx.theme().accent_foreground)
+                                    })
+                                    .active(|s| {
+                                        s.bg(cx.theme().primary)
+                                            .text_color(cx.theme().primary_foreground)
+                                    })
+                                    .on_click(cx.listener(|this, _, _, cx| {
+                                        this.toggle_window_layout(cx);
+                                    }))
+                                    .tooltip({
+                                        let keys = self
+                                            .shortcuts
+                                            .shortcut(ShortcutAction::ToggleLayout)
+                                            .map(|s| s.keys_label())
+                                            .unwrap_or_default();
+                                        move |window, cx| {
+                                            Tooltip::new(t!("app.header.layout_tooltip", keys = keys).to_string())
+                                                .build(window, cx)
+                                        }
+                                    })
+                                    .child("⧉"),
+                            )
+                            // Settings button (opens settings / goes back)
+                            .child(
+                                div()
+                                    .id("settings-btn")
+                                    .w(px(32.0))
+                                    .h(px(32.0))
+                                    .rounded(px(8.0))
+                                    .cursor_pointer()
+                                    .flex()
+                                    .items_center()
+                                    .justify_center()
+                                    .bg(cx.theme().muted)
+                                    .text_color(cx.theme().muted_foreground)
+                                    .hover(|s| {
+                                        s.bg(cx.theme().accent)
+                                            .text_color(cx.theme().accent_foreground)
+                                    })
+                                    .active(|s| {
+                                        s.bg(cx.theme().primary)
+                                            .text_color(cx.theme().primary_foreground)
+                                    })
+                                    .on_click(cx.listener(|this, _, _, cx| {
+                                        this.settings_open = !this.settings_open;
+                                        this.cleanup_candidates.invalidate();
+                                        cx.notify();
+                                    }))
+                                    .child(if settings_open { "←" } else { "⚙" }),
+                            )
+                            // Minimize button
+                            .child(
+                                div()
+                                    .id("minimize-btn")
+                                    .w(px(32.0))
+                                    .h(px(32.0))
+                                    .rounded(px(8.0))
+                                    .cursor_pointer()
+                                    .flex()
+                                    .items_center()
+                                    .justify_center()
+                                    .bg(cx.theme().muted)
+                                    .text_color(cx.theme().muted_foreground)
+                                    .hover(|s| {
+                                        s.bg(gpui::rgb(0xE53935)).text_color(gpui::rgb(0xFFFFFF))
+                                    })
+                                    .active(|s| s.bg(gpui::rgb(0xC62828)))
+                                    .on_click(|_, window, _cx| {
+                                        window.minimize_window();
+                                    })
+                                    .child("—"),
+                            ),
+                    ),
+            )
+            .child(
+                // Main content area
+                div()
+                    .flex_1()
+                    .w_full()
+                    .overflow_hidden()
+                    .child(if settings_open {
+                        self.render_settings(cx).into_any_element()
+                    } else {
+                        self.render_gallery(loading_more, cx).into_any_element()
+                    }),
+            )
+            // Render toast overlay at bottom center
+            .child(self.toast_manager.render())
+            // Dialogs, bottom to top
+            .children(
+                self.modals
+                    .stack()
+                    .ids()
+                    .collect::<Vec<_>>()
+                    .into_iter()
+                    .map(|id| self.render_modal(id, viewport, cx)),
+            )
+            // Timings of the previous frame while tracing
+            .when_some(perf::is_enabled().then(perf::last_frame).flatten(), |this, frame| {
+                this.child(render_perf_overlay(frame, cx))
+            });
+
+        perf::end_frame();
+        root
+    }
+}
+
+/// Preview strips of an oversized image, shrunk to fit `width` (tall
+/// images) or `height` (wide ones) and scrolled along the long edge
+fn render_preview_strips(
+    strips: Vec<PreviewStrip>,
+    width: Pixels,
+    height: Pixels,
+    fallback: impl Fn() -> AnyElement + Clone + 'static,
+) -> AnyElement {
+    // Strips are cut along the long edge, which a strip's shorter side never is
+    let tall = strips[0].height >= strips[0].width;
+    let scale = if tall {
+        (width / px(strips[0].width as f32)).min(1.0)
+    } else {
+        (height / px(strips[0].height as f32)).min(1.0)
+    };
+    let images = strips.into_iter().map(move |strip| {
+        img(strip.path)
+            .flex_none()
+            .w(px(strip.width as f32) * scale)
+            .h(px(strip.height as f32) * scale)
+            .with_loading(|| Spinner::new().into_any_element())
+            .with_fallback(fallback.clone())
+    });
+
+    let container = div().id("preview-strips").size_full();
+    if tall {
+        container
+            .overflow_y_scroll()
+            .child(v_flex().w_full().items_center().children(images))
+            .into_any_element()
+    } else {
+        container
+            .overflow_x_scroll()
+            .child(h_flex().h_full().items_center().children(images))
+            .into_any_element()
+    }
+}
+
+/// Corner readout of one frame's timings for `--perf-trace`
+fn render_perf_overlay(frame: perf::FrameTiming, cx: &App) -> impl IntoElement {
+    let ms = |d: Duration| format!("{:.1}", d.as_secs_f64() * 1000.0);
+    div()
+        .absolute()
+        .top(px(56.0))
+        .right_2()
+        .px_2()
+        .py_1()
+        .rounded(px(4.0))
+        .bg(gpui::rgba(0x000000b0))
+        .text_xs()
+        .font_family("monospace")
+        .text_color(if frame.total.as_millis() > 16 {
+            cx.theme().danger
+        } else {
+            gpui::rgb(0xFFFFFF).into()
+        })
+        .child(format!(
+            "frame {} ms | msg {} | group {} | elements {} | thumbs {}/{}",
+            ms(frame.total),
+            ms(frame.messages),
+            ms(frame.grouping),
+            ms(frame.elements),
+            frame.thumbnail_hits,
+            frame.thumbnail_hits + frame.thumbnail_misses
+        ))
+}
+
+impl Sukusho {
+    fn render_gallery(&self, loading_more: bool, cx: &mut Context<Self>) -> impl IntoElement {
+        let search_enabled = self.models_downloaded;
+        let has_search_results = self.search_results.is_some();
+
+        v_flex()
+            .size_full()
+            // Search bar (only show if models are downloaded)
+            .when(search_enabled, |el| {
+                el.child(
+                    h_flex()
+                        .w_full()
+                        .px_4()
+                        .py_3()
+                        .bg(cx.theme().background)
+                        .border_b_1()
+                        .border_color(cx.theme().border)
+                        .child(
+                            h_flex()
+                                .w_full()
+                                .px_4()
+                                .gap_2()
+                                .items_center()
+                                .child(Input::new(&self.search_input).flex_1())
+                                .when(self.last_search_offer && !has_search_results, |el| {
+                                    el.child(
+                                        Button::new("run-last-search")
+                                            .small()
+                                            .outline()
+                                            .label(&t!("app.search.run_last").to_string())
+                                            .on_click(cx.listener(|this, _, _, cx| {
+                                                let query = this.search_query.clone();
+                                                if !query.is_empty() {
+                                                    this.start_search(query, cx);
+                                                }
+                                                cx.notify();
+                                            })),
+                                    )
+                                })
+                                // "Similar to foo.png ✕" instead of Clear for a similar search
+                                .when_some(self.similar_to.clone(), |el, path| {
+                                    let name = path
+                                        .file_name()
+                                        .map(|n| n.to_string_lossy().to_string())
+                                        .unwrap_or_default();
+                                    el.child(
+                                        div()
+                                            .id("similar-chip")
+                                            .px_2()
+                                            .py_1()
+                                            .rounded(px(12.0))
+                                            .cursor_pointer()
+                                            .bg(cx.theme().muted)
+                                            .hover(|s| s.bg(cx.theme().accent).text_color(cx.theme().accent_foreground))
+                                            .text_xs()
+                                            .text_color(cx.theme().muted_foreground)
+                                            .child(format!(
+                                                "{} ✕",
+                                                t!("app.search.similar_to", name = text::middle_ellipsize(&name, SIMILAR_CHIP_NAME_WIDTH))
+                                            ))
+                                            .tooltip(|window, cx| {
+                                                Tooltip::new(t!("app.search.similar_clear").to_string()).build(window, cx)
+                                            })
+                                            .on_click(cx.listener(|this, _, _, cx| {
+                                                this.clear_search(cx);
+                                            })),
+                                    )
+                                })
+                                .when(has_search_results && self.similar_to.is_none(), |el| {
+                                    el.child(
+                                        Button::new("clear-search")
+                                            .small()
+                                            .ghost()
+                                            .label(&t!("app.search.clear_button").to_string())
+                                            .on_click(cx.listener(|this, _, window, cx| {
+                                                this.search_input.update(cx, |input, cx| {
+                                                    input.set_value("", window, cx);
+                                                });
+                                                this.search_query.clear();
+                                                this.clear_search(cx);
+                                            })),
+                                    )
+                                }),
+                        ),
+                )
+            })
+            // "128 results in 84 ms", with the breakdown on hover, or a note
+            // that nothing was close enough
+            .when_some(
+                self.search_stats.filter(|_| search_enabled && has_search_results),
+                |el, stats| {
+                    let count = self.search_results.as_ref().map_or(0, Vec::len);
+                    if count == 0 {
+                        el.child(self.render_no_matches(cx))
+                    } else {
+                        el.child(self.render_search_stats(count, stats, cx))
+                    }
+                },
+            )
+            // Hint when the search had nothing to look in
+            .when_some(self.search_hint.filter(|_| search_enabled), |el, health| {
+                el.child(self.render_search_hint(health, cx))
+            })
+            // The folder is too big to show whole
+            .when_some(self.scan_capped, |el, shown| {
+                el.child(self.render_scan_capped(shown, cx))
+            })
+            // Gallery
+            .child(gallery(
+                self.visible_screenshots()
+                    .iter()
+                    .filter(|s| self.matches_orientation(s))
+                    .cloned()
+                    .collect(),
+                self.search_results.clone(),
+                self.selected.clone(),
+                self.focused.clone(),
+                self.flashing.clone(),
+                self.pending_conversions.clone(),
+                Arc::clone(&self.thumbnail_cache),
+                self.grid_columns,
+                self.thumbnail_size,
+                self.badge_content,
+                self.screenshots.sort(),
+                self.grouping,
+                cx.global::<AppState>().settings.lock().screenshot_directory.clone(),
+                &self.gallery_scroll,
+                loading_more,
+                cx,
+            ))
+            // Processing timeline for the file picked via "Details"
+            .when_some(self.details_path.clone(), |el, path| {
+                el.child(self.render_details_pane(path, cx))
+            })
+    }
+
+    /// Chip naming the gallery order, with the other orders in a menu below it
+    fn render_sort_selector(&self, cx: &mut Context<Self>) -> impl IntoElement {
+        let current = self.screenshots.sort();
+        let by_folder = self.grouping == GalleryGrouping::Folder;
+        let label = |sort: SortMode| match sort {
+            SortMode::Newest => t!("app.header.sort.newest").to_string(),
+            SortMode::Oldest => t!("app.header.sort.oldest").to_string(),
+            SortMode::Largest => t!("app.header.sort.largest").to_string(),
+            SortMode::Name => t!("app.header.sort.name").to_string(),
+        };
+
+        v_flex()
+            .child(
+                div()
+                    .id("sort-chip")
+                    .px_2()
+                    .py_1()
+                    .rounded(px(12.0))
+                    .cursor_pointer()
+                    .bg(cx.theme().muted)
+                    .hover(|s| s.bg(cx.theme().accent).text_color(cx.theme().accent_foreground))
+                    .text_xs()
+                    .text_color(cx.theme().muted_foreground)
+                    .child(if by_folder {
+                        format!("{} · {} ▾", label(current), t!("app.header.sort.folders"))
+                    } else {
+                        format!("{} ▾", label(current))
+                    })
+                    .tooltip(|window, cx| {
+                        Tooltip::new(t!("app.header.sort.tooltip").to_string()).build(window, cx)
+                    })
+                    .on_click(cx.listener(|this, _, _, cx| {
+                        this.sort_menu_open = !this.sort_menu_open;
+                        cx.notify();
+                    })),
+            )
+            .when(self.sort_menu_open, |el| {
+                el.child(deferred(
+                    anchored().snap_to_window().child(
+                        v_flex()
+                            .id("sort-menu")
+                            .mt_1()
+                            .p_1()
+                            .min_w(px(140.0))
+                            .rounded(px(8.0))
+                            .bg(cx.theme().popover)
+                            .border_1()
+                            .border_color(cx.theme().border)
+                            .shadow_md()
+                            .on_mouse_down_out(cx.listener(|this, _, _, cx| {
+                                this.sort_menu_open = false;
+                                cx.notify();
+                            }))
+                            .children(
+                                [SortMode::Newest, SortMode::Oldest, SortMode::Largest, SortMode::Name]
+                                    .into_iter()
+                                    .enumerate()
+                                    .map(|(index, sort)| {
+                                        div()
+                                            .id(("sort-option", index))
+                                            .px_2()
+                                            .py_1()
+                                            .rounded(px(6.0))
+                                            .cursor_pointer()
+                                            .text_sm()
+                                            .text_color(cx.theme().foreground)
+                                            .when(sort == current, |s| s.font_weight(FontWeight::SEMIBOLD))
+                                            .hover(|s| s.bg(cx.theme().accent))
+                                            .child(label(sort))
+                                            .on_click(cx.listener(move |this, _, _, cx| {
+                                                this.sort_menu_open = false;
+                                                this.set_sort(sort, cx);
+                                            }))
+                                    }),
+                            )
+                            .child(div().my_1().h(px(1.0)).bg(cx.theme().border))
+                            .child(
+                                div()
+                                    .id("group-by-folder")
+                                    .px_2()
+                                    .py_1()
+                                    .rounded(px(6.0))
+                                    .cursor_pointer()
+                                    .text_sm()
+                                    .text_color(cx.theme().foreground)
+                                    .hover(|s| s.bg(cx.theme().accent))
+                                    .child(format!(
+                                        "{} {}",
+                                        if by_folder { "✓" } else { "  " },
+                                        t!("app.header.sort.group_by_folder")
+                                    ))
+                                    .on_click(cx.listener(move |this, _, _, cx| {
+                                        this.sort_menu_open = false;
+                                        let grouping = if by_folder {
+                                            GalleryGrouping::Date
+                                        } else {
+                                            GalleryGrouping::Folder
+                                        };
+                                        this.set_grouping(grouping, cx);
+                                    })),
+                            ),
+                    ),
+                ))
+            })
+    }
+
+    /// Landscape / Portrait / Square chips; clicking the active one shows all again
+    fn render_orientation_filter(&self, cx: &mut Context<Self>) -> impl IntoElement {
+        let current = self.orientation_filter;
+        h_flex()
+            .id("orientation-filter")
+            .gap_1()
+            .tooltip(|window, cx| {
+                Tooltip::new(t!("app.header.orientation.tooltip").to_string()).build(window, cx)
+            })
+            .children(Orientation::ALL.into_iter().enumerate().map(|(index, orientation)| {
+                let active = current == Some(orientation);
+                div()
+                    .id(("orientation-chip", index))
+                    .px_2()
+                    .py_1()
+                    .rounded(px(12.0))
+                    .cursor_pointer()
+                    .text_xs()
+                    .map(|s| {
+                        if active {
+                            s.bg(cx.theme().primary).text_color(cx.theme().primary_foreground)
+                        } else {
+                            s.bg(cx.theme().muted).text_color(cx.theme().muted_foreground)
+                        }
+                    })
+                    .hover(|s| s.bg(cx.theme().accent).text_color(cx.theme().accent_foreground))
+                    .child(orientation.label())
+                    .on_click(cx.listener(move |this, _, _, cx| {
+                        let orientation = if active { None } else { Some(orientation) };
+                        this.set_orientation_filter(orientation, cx);
+                    }))
+            }))
+    }
+
+    /// Re-sort the gallery and remember the order
+    fn set_sort(&mut self, sort: SortMode, cx: &mut Context<Self>) {
+        if self.screenshots.sort() != sort {
+            info!("Sorting the gallery by {:?}", sort);
+            self.screenshots.set_sort(sort, ScreenshotInfo::from_path_ref);
+            self.gallery_scroll.set_offset(point(px(0.0), px(0.0)));
+            let app_state = cx.global::<AppState>();
+            let mut settings = app_state.settings.lock();
+            settings.gallery_sort = sort;
+            let _ = settings.save();
+        }
+        cx.notify();
+    }
+
+    /// Switch the gallery's group headers and remember the choice
+    fn set_grouping(&mut self, grouping: GalleryGrouping, cx: &mut Context<Self>) {
+        if self.grouping != grouping {
+            info!("Grouping the gallery by {:?}", grouping);
+            self.grouping = grouping;
+            let app_state = cx.global::<AppState>();
+            let mut settings = app_state.settings.lock();
+            settings.gallery_grouping = grouping;
+            let _ = settings.save();
+        }
+        cx.notify();
+    }
+
+    fn render_search_stats(
+        &self,
+        count: usize,
+        stats: crate::indexer::SearchStats,
+        cx: &mut Context<Self>,
+    ) -> impl IntoElement {
+        let detail = t!(
+            "app.search.stats_detail",
+            embed = stats.embed.as_millis(),
+            query = stats.query.as_millis(),
+            rows = stats.rows,
+            method = if stats.ann {
+                t!("app.search.method_ann")
+            } else {
+                t!("app.search.method_scan")
+            }
+        )
+        .to_string();
+
+        div()
+            .id("search-stats")
+            .w_full()
+            .px_8()
+            .py_1()
+            .text_xs()
+            .text_color(cx.theme().muted_foreground)
+            .tooltip(move |window, cx| Tooltip::new(detail.clone()).build(window, cx))
+            .child(
+                t!(
+                    &plural_key("app.search.stats", count),
+                    count = count,
+                    ms = stats.total().as_millis()
+                )
+                .to_string(),
+            )
+    }
+
+    fn render_no_matches(&self, cx: &mut Context<Self>) -> impl IntoElement {
+        h_flex()
+            .w_full()
+            .px_8()
+            .py_2()
+            .gap_2()
+            .items_center()
+            .bg(cx.theme().muted)
+            .border_b_1()
+            .border_color(cx.theme().border)
+            .child(
+                div()
+                    .flex_1()
+                    .text_xs()
+                    .text_color(cx.theme().muted_foreground)
+                    .child(t!("app.search.no_matches").to_string()),
+            )
+            .child(
+                Button::new("no-matches-open-indexing")
+                    .small()
+                    .outline()
+                    .label(&t!("app.search.hint.open_indexing").to_string())
+                    .on_click(cx.listener(|this, _, _, cx| {
+                        this.settings_page = SettingsPage::Indexing;
+                        this.settings_open = true;
+                        cx.notify();
+                    })),
+            )
+    }
+
+    fn render_scan_capped(&self, shown: usize, cx: &mut Context<Self>) -> impl IntoElement {
+        h_flex()
+            .w_full()
+            .px_8()
+            .py_2()
+            .gap_2()
+            .items_center()
+            .bg(cx.theme().muted)
+            .border_b_1()
+            .border_color(cx.theme().border)
+            .child(
+                div()
+                    .flex_1()
+                    .text_xs()
+                    .text_color(cx.theme().muted_foreground)
+                    .child(
+                        t!("app.scan_capped.message", shown = format_count(shown)).to_string(),
+                    ),
+            )
+            .child(
+                Button::new("scan-capped-change-folder")
+                    .small()
+                    .outline()
+                    .label(&t!("app.scan_capped.change_folder").to_string())
+                    .on_click(cx.listener(|this, _, _, cx| {
+                        this.settings_open = true;
+                        this.settings_page = SettingsPage::General;
+                        cx.notify();
+                    })),
+            )
+    }
+
+    fn render_search_hint(
+        &self,
+        health: crate::indexer::IndexHealth,
+        cx: &mut Context<Self>,
+    ) -> impl IntoElement {
+        let indexing_enabled = cx.global::<AppState>().settings.lock().indexing_enabled;
+        let unavailable = health == crate::indexer::IndexHealth::Unavailable;
+
+        h_flex()
+            .w_full()
+            .px_8()
+            .py_2()
+            .gap_2()
+            .items_center()
+            .bg(cx.theme().muted)
+            .border_b_1()
+            .border_color(cx.theme().border)
+            .child(
+                div()
+                    .flex_1()
+                    .text_xs()
+                    .text_color(cx.theme().muted_foreground)
+                    .child(if unavailable {
+                        t!("app.search.hint.unavailable").to_string()
+                    } else if indexing_enabled {
+                        t!("app.search.hint.not_indexed").to_string()
+                    } else {
+                        t!("app.search.hint.indexing_disabled").to_string()
+                    }),
+            )
+            .when(unavailable, |el| {
+                el.child(
+                    Button::new("search-hint-recovery")
+                        .small()
+                        .outline()
+                        .label(&t!("app.search.hint.recovery").to_string())
+                        .on_click(cx.listener(|this, _, _, cx| {
+                            this.index_recovery.get_or_insert_with(String::new);
+                            cx.notify();
+                        })),
+                )
+            })
+            .when(indexing_enabled && !unavailable, |el| {
+                el.child(
+                    Button::new("search-hint-index-now")
+                        .small()
+                        .outline()
+                        .disabled(self.indexing)
+                        .label(&t!("app.search.hint.index_now").to_string())
+                        .on_click(cx.listener(|this, _, _, cx| {
+                            if this.indexing {
+                                return;
+                            }
+                            Self::start_incremental_index(cx);
+                            cx.notify();
+                        })),
+                )
+            })
+            .when(!indexing_enabled, |el| {
+                el.child(
+                    Button::new("search-hint-open-indexing")
+                        .small()
+                        .outline()
+                        .label(&t!("app.search.hint.open_indexing").to_string())
+                        .on_click(cx.listener(|this, _, _, cx| {
+                            this.settings_page = SettingsPage::Indexing;
+                            this.settings_open = true;
+                            cx.notify();
+                        })),
+                )
+            })
+    }
+
+    fn render_details_pane(&self, path: PathBuf, cx: &mut Context<Self>) -> impl IntoElement {
+        let collapsed = self.details_collapsed;
+        let events = crate::timeline::events(&path);
+        let now = SystemTime::now();
+        let file_name = path
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_default();
+        let info_path = path.clone();
+
+        v_flex()
+            .w_full()
+            .flex_shrink_0()
+            .border_t_1()
+            .border_color(cx.theme().border)
+            .bg(cx.theme().background)
+            .child(
+                h_flex()
+                    .w_full()
+                    .px_4()
+                    .py_2()
+                    .gap_2()
+                    .items_center()
+                    .child(
+                        div()
+                            .flex_1()
+                            .text_sm()
+                            .font_weight(FontWeight::SEMIBOLD)
+                            .text_color(cx.theme().foreground)
+                            .overflow_x_hidden()
+                            .child(t!("gallery.details.title", name = file_name).to_string()),
+                    )
+                    .when(self.copying_info, |el| el.child(Spinner::new().small()))
+                    .child(
+                        Button::new("details-copy-info")
+                            .ghost()
+                            .compact()
+                            .label(&t!("app.copy_info.button").to_string())
+                            .tooltip(t!("app.copy_info.tooltip").to_string())
+                            .disabled(self.copying_info)
+                            .on_click(cx.listener(move |this, _, _, cx| {
+                                this.copy_info(vec![info_path.clone()], cx);
+                            })),
+                    )
+                    .child(
+                        Button::new("details-collapse")
+                            .ghost()
+                            .compact()
+                            .label(if collapsed { "▴" } else { "▾" })
+                            .on_click(cx.listener(|this, _, _, cx| {
+                                this.details_collapsed = !this.details_collapsed;
+                                cx.notify();
+                            })),
+                    )
+                    .child(
+                        Button::new("details-close")
+                            .ghost()
+                            .compact()
+                            .label("✕")
+                            .on_click(cx.listener(|this, _, _, cx| {
+                                this.details_path = None;
+                                cx.notify();
+                            })),
+                    ),
+            )
+            .when(!collapsed, |el| {
+                el.child(
+                    v_flex()
+                        .id("details-events")
+                        .w_full()
+                        .max_h(px(160.0))
+                        .overflow_y_scroll()
+                        .px_4()
+                        .pb_3()
+                        .gap_1()
+                        .when(events.is_empty(), |el| {
+                            el.child(
+                                div()
+                                    .text_xs()
+                                    .text_color(cx.theme().muted_foreground)
+                                    .child(t!("gallery.details.empty").to_string()),
+                            )
+                        })
+                        .children(events.into_iter().rev().map(|event| {
+                            let color = if event.kind.is_error() {
+                                cx.theme().danger
+                            } else {
+                                cx.theme().foreground
+                            };
+                            h_flex()
+                                .w_full()
+                                .gap_3()
+                                .child(
+                                    div()
+                                        .w(px(80.0))
+                                        .flex_shrink_0()
+                                        .text_xs()
+                                        .text_color(cx.theme().muted_foreground)
+                                        .child(format_relative_time(event.at, now)),
+                                )
+                                .child(
+                                    div()
+                                        .flex_1()
+                                        .text_xs()
+                                        .text_color(color)
+                                        .child(timeline_event_label(&event.kind)),
+                                )
+                        })),
+                )
+            })
+    }
+
+    /// Dialogs open now, in the order they were opened when it matters
+    fn modal_specs(&self) -> Vec<ModalSpec> {
+        let mut specs = Vec::new();
+        if !self.read_only_prompts.is_empty() {
+            // Skip / Always / Make writable
+            specs.push(ModalSpec {
+                id: ModalId::ReadOnlyPrompt,
+                actions: 3,
+                default_action: 2,
+            });
+        }
+        match &self.context_menu_prompt {
+            // Use app menu / First N / Open anyway
+            Some(ContextMenuPrompt::Confirm(_)) => specs.push(ModalSpec {
+                id: ModalId::ContextMenuConfirm,
+                actions: 3,
+                default_action: 2,
+            }),
+            // Copy / Details / Edit / Select Similar / Fix Timestamp / Copy Info
+            Some(ContextMenuPrompt::AppMenu(_)) => specs.push(ModalSpec {
+                id: ModalId::ContextMenuAppMenu,
+                actions: 7,
+                default_action: 0,
+            }),
+            None => {}
+        }
+        if self.index_recovery.is_some() {
+            // Try again later / Start a new index
+            specs.push(ModalSpec {
+                id: ModalId::IndexRecovery,
+                actions: 2,
+                default_action: 0,
+            });
+        }
+        if self.strip_report.is_some() {
+            // Close
+            specs.push(ModalSpec {
+                id: ModalId::StripReport,
+                actions: 1,
+                default_action: 0,
+            });
+        }
+        if self.timestamp_plan.is_some() {
+            // Cancel / Apply
+            specs.push(ModalSpec {
+                id: ModalId::FixTimestamps,
+                actions: 2,
+                default_action: 1,
+            });
+        }
+        if self.preview.is_some() {
+            specs.push(ModalSpec {
+                id: ModalId::Preview,
+                actions: 0,
+                default_action: 0,
+            });
+        }
+        if self.shortcut_help_open {
+            specs.push(ModalSpec {
+                id: ModalId::ShortcutHelp,
+                actions: 0,
+                default_action: 0,
+            });
+        }
+        specs
+    }
+
+    /// Enter, Escape and Tab for the top dialog
+    fn handle_modal_key(&mut self, event: &KeyDownEvent, cx: &mut Context<Self>) {
+        let Some(id) = self.modals.stack().top() else {
+            return;
+        };
+        let key = event.keystroke.key.as_str();
+        let shift = event.keystroke.modifiers.shift;
+        match self.modals.stack_mut().handle_key(key, shift) {
+            ModalKey::Confirm(action) => {
+                self.run_modal_action(id, action, cx);
+                self.modals.stack_mut().reset_focus(id);
+            }
+            ModalKey::Cancel => self.cancel_modal(id, cx),
+            ModalKey::Moved => cx.notify(),
+            ModalKey::Ignored => {
+                // The help shortcut still closes its own overlay
+                if id == ModalId::ShortcutHelp
+                    && self.shortcuts.action_for(&event.keystroke) == Some(ShortcutAction::ToggleHelp)
+                {
+                    self.cancel_modal(id, cx);
+                }
+                if id == ModalId::Preview {
+                    match key {
+                        "left" | "up" => self.step_preview(false, cx),
+                        "right" | "down" => self.step_preview(true, cx),
+                        _ if self.shortcuts.action_for(&event.keystroke)
+                            == Some(ShortcutAction::Preview) =>
+                        {
+                            self.cancel_modal(id, cx)
+                        }
+                        _ => {}
+                    }
+                }
+            }
+        }
+    }
+
+    /// Run a dialog button (clicked, or Enter on the focused one)
+    fn run_modal_action(&mut self, id: ModalId, action: usize, cx: &mut Context<Self>) {
+        match (id, action) {
+            (ModalId::ReadOnlyPrompt, 0) => self.answer_read_only_prompt(false, false, cx),
+            (ModalId::ReadOnlyPrompt, 1) => self.answer_read_only_prompt(true, true, cx),
+            (ModalId::ReadOnlyPrompt, _) => self.answer_read_only_prompt(true, false, cx),
+            (ModalId::ContextMenuConfirm, action) => {
+                if let Some(ContextMenuPrompt::Confirm(mut paths)) = self.context_menu_prompt.take() {
+                    match action {
+                        0 => self.context_menu_prompt = Some(ContextMenuPrompt::AppMenu(paths)),
+                        1 => {
+                            let threshold =
+                                cx.global::<AppState>().settings.lock().context_menu_confirm_threshold;
+                            paths.truncate(threshold);
+                            self.open_shell_context_menu(&paths, cx);
+                        }
+                        _ => self.open_shell_context_menu(&paths, cx),
+                    }
+                }
+            }
+            (ModalId::ContextMenuAppMenu, action) => {
+                if let Some(ContextMenuPrompt::AppMenu(paths)) = self.context_menu_prompt.take() {
+                    match action {
+                        0 => self.copy_files(&paths, cx),
+                        1 => self.run_context_menu_command(Some(ContextMenuCommand::Details), &paths, cx),
+                        2 => self.run_context_menu_command(Some(ContextMenuCommand::Edit), &paths, cx),
+                        3 => self.run_context_menu_command(Some(ContextMenuCommand::SelectSimilar), &paths, cx),
+                        4 => self.run_context_menu_command(
+                            Some(ContextMenuCommand::FixTimestamps),
+                            &paths,
+                            cx,
+                        ),
+                        5 => self.run_context_menu_command(Some(ContextMenuCommand::CopyInfo), &paths, cx),
+                        _ => self.run_context_menu_command(Some(ContextMenuCommand::Organize), &paths, cx),
+                    }
+                }
+            }
+            (ModalId::FixTimestamps, 0) => self.timestamp_plan = None,
+            (ModalId::FixTimestamps, _) => self.apply_timestamp_fix(cx),
+            (ModalId::StripReport, _) => self.strip_report = None,
+            (ModalId::IndexRecovery, 0) => self.index_recovery = None,
+            (ModalId::IndexRecovery, _) => self.start_new_index(cx),
+            (ModalId::ShortcutHelp, _) => self.shortcut_help_open = false,
+            (ModalId::Preview, _) => self.preview = None,
+        }
+        cx.notify();
+    }
+
+    /// Escape (or a backdrop click) on a dialog
+    fn cancel_modal(&mut self, id: ModalId, cx: &mut Context<Self>) {
+        match id {
+            ModalId::ShortcutHelp => self.shortcut_help_open = false,
+            ModalId::Preview => self.preview = None,
+            ModalId::ContextMenuConfirm | ModalId::ContextMenuAppMenu => self.context_menu_prompt = None,
+            ModalId::FixTimestamps => self.timestamp_plan = None,
+            ModalId::StripReport => self.strip_report = None,
+            ModalId::IndexRecovery => self.index_recovery = None,
+            // Same as Skip
+            ModalId::ReadOnlyPrompt => self.answer_read_only_prompt(false, false, cx),
+        }
+        cx.notify();
+    }
+
+    /// Dialog `id` on its backdrop
+    fn render_modal(&self, id: ModalId, viewport: Size<Pixels>, cx: &mut Context<Self>) -> AnyElement {
+        let card = match id {
+            ModalId::ShortcutHelp => self.render_shortcut_help(cx).into_any_element(),
+            ModalId::Preview => match &self.preview {
+                Some(path) => self.render_preview(path, viewport, cx).into_any_element(),
+                None => return div().into_any_element(),
+            },
+            ModalId::ContextMenuConfirm | ModalId::ContextMenuAppMenu => match &self.context_menu_prompt {
+                Some(prompt) => self.render_context_menu_prompt(prompt, cx).into_any_element(),
+                None => return div().into_any_element(),
+            },
+            ModalId::ReadOnlyPrompt => match self.read_only_prompts.first() {
+                Some(op) => self.render_read_only_prompt(op, cx).into_any_element(),
+                None => return div().into_any_element(),
+            },
+            ModalId::FixTimestamps => match &self.timestamp_plan {
+                Some(plan) => self.render_fix_timestamps(plan, cx).into_any_element(),
+                None => return div().into_any_element(),
+            },
+            ModalId::StripReport => match &self.strip_report {
+                Some(report) => self.render_strip_report(report, cx).into_any_element(),
+                None => return div().into_any_element(),
+            },
+            ModalId::IndexRecovery => match &self.index_recovery {
+                Some(error) => self.render_index_recovery(error, cx).into_any_element(),
+                None => return div().into_any_element(),
+            },
+        };
+
+        self.modals
+            .backdrop(id)
+            .when(id.cancel_on_backdrop(), |el| {
+                el.on_click(cx.listener(move |this, _, _, cx| {
+                    this.cancel_modal(id, cx);
+                }))
+            })
+            .child(
+                div()
+                    // Clicks inside the card don't reach the backdrop
+                    .on_mouse_down(MouseButton::Left, |_, _, cx| cx.stop_propagation())
+                    .child(card),
+            )
+            .into_any_element()
+    }
+
+    /// Dialog button with its focus ring, running `action` of dialog `id` on click
+    fn modal_button(&self, id: ModalId, action: usize, button: Button, cx: &mut Context<Self>) -> Div {
+        let focused = self.modals.stack().is_focused(id, action);
+        focus_ring(
+            focused,
+            button.on_click(cx.listener(move |this, _, _, cx| {
+                this.run_modal_action(id, action, cx);
+            })),
+            cx,
+        )
+    }
+
+    /// List a settings control for Tab order and key input; returns whether
+    /// it has keyboard focus
+    fn register_setting(&self, id: ElementId, kind: ControlKind, action: SettingsAction) -> bool {
+        let focused = self.settings_focus.is_focused(&id);
+        self.settings_controls
+            .borrow_mut()
+            .push(Control { id, kind, action });
+        focused
+    }
+
+    /// Settings button with its focus ring; `style` sets the label and look.
+    /// A disabled button is skipped by Tab.
+    fn settings_button(
+        &self,
+        id: impl Into<ElementId>,
+        enabled: bool,
+        style: impl FnOnce(Button) -> Button,
+        on_press: impl Fn(&mut Self, &mut Window, &mut Context<Self>) + 'static,
+        cx: &Context<Self>,
+    ) -> Div {
+        let id = id.into();
+        let on_press = settings_action(move |this, _, window, cx| on_press(this, window, cx));
+        let focused = enabled && self.register_setting(id.clone(), ControlKind::Button, on_press.clone());
+        focus_ring(
+            focused,
+            style(Button::new(id))
+                .disabled(!enabled)
+                .on_click(cx.listener(move |this, _, window, cx| {
+                    on_press(this, ControlInput::Activate, window, cx);
+                })),
+            cx,
+        )
+    }
+
+    /// Settings switch with its focus ring; `on_toggle` gets the new state
+    fn settings_switch(
+        &self,
+        id: &'static str,
+        checked: bool,
+        enabled: bool,
+        on_toggle: impl Fn(&mut Self, bool, &mut Window, &mut Context<Self>) + 'static,
+        cx: &Context<Self>,
+    ) -> Div {
+        let on_toggle = Rc::new(on_toggle);
+        let focused = enabled
+            && self.register_setting(
+                id.into(),
+                ControlKind::Toggle,
+                settings_action({
+                    let on_toggle = on_toggle.clone();
+                    move |this, _, window, cx| on_toggle(this, !checked, window, cx)
+                }),
+            );
+        focus_ring(
+            focused,
+            Switch::new(id)
+                .checked(checked)
+                .disabled(!enabled)
+                .on_click(cx.listener(move |this, checked: &bool, window, cx| {
+                    on_toggle(this, *checked, window, cx);
+                })),
+            cx,
+        )
+    }
+
+    /// "-" value "+" row with one focus ring; arrow keys step it too.
+    /// `on_step` gets -1 or +1.
+    fn settings_stepper(
+        &self,
+        id: &'static str,
+        enabled: bool,
+        value: impl IntoElement,
+        on_step: impl Fn(&mut Self, i32, &mut Window, &mut Context<Self>) + 'static,
+        cx: &Context<Self>,
+    ) -> Div {
+        let on_step = settings_action(move |this, input, window, cx| {
+            if let ControlInput::Step(step) = input {
+                on_step(this, step, window, cx);
+            }
+        });
+        let focused = enabled && self.register_setting(id.into(), ControlKind::Stepper, on_step.clone());
+        let step_button = |suffix: &str, label: &'static str, step: i32| {
+            let on_step = on_step.clone();
+            Button::new(ElementId::Name(format!("{id}-{suffix}").into()))
+                .ghost()
+                .compact()
+                .label(label)
+                .disabled(!enabled)
+                .on_click(cx.listener(move |this, _, window, cx| {
+                    on_step(this, ControlInput::Step(step), window, cx);
+                }))
+        };
+        let minus = step_button("minus", "-", -1);
+        let plus = step_button("plus", "+", 1);
+        focus_ring(
+            focused,
+            h_flex()
+                .gap_2()
+                .items_center()
+                .child(minus)
+                .child(value)
+                .child(plus),
+            cx,
+        )
+    }
+
+    /// Tab, Shift+Tab, Space, Enter and arrows on a settings page; returns
+    /// whether the key was used
+    fn handle_settings_key(&mut self, event: &KeyDownEvent, window: &mut Window, cx: &mut Context<Self>) -> bool {
+        let modifiers = &event.keystroke.modifiers;
+        if modifiers.control || modifiers.alt || modifiers.platform {
+            return false;
+        }
+        let controls = self.settings_controls.borrow();
+        match self
+            .settings_focus
+            .handle_key(&controls, &event.keystroke.key, modifiers.shift)
+        {
+            SettingsKey::Ignored => false,
+            SettingsKey::Moved => {
+                cx.notify();
+                true
+            }
+            SettingsKey::Input(index, input) => {
+                let action = controls[index].action.clone();
+                drop(controls);
+                action(self, input, window, cx);
+                cx.notify();
+                true
+            }
+        }
+    }
+
+    /// Cheatsheet of the registered shortcuts; any click dismisses it
+    fn render_shortcut_help(&self, cx: &mut Context<Self>) -> impl IntoElement {
+        v_flex()
+            .w(px(420.0))
+            .p_5()
+            .gap_4()
+            .rounded(px(12.0))
+            .bg(cx.theme().popover)
+            .border_1()
+            .border_color(cx.theme().border)
+            .child(
+                div()
+                    .text_lg()
+                    .font_weight(FontWeight::BOLD)
+                    .text_color(cx.theme().foreground)
+                    .child(t!("shortcuts.title").to_string()),
+            )
+            .children(self.shortcuts.grouped().into_iter().map(|(group, shortcuts)| {
+                v_flex()
+                    .gap_1()
+                    .child(
+                        div()
+                            .text_xs()
+                            .font_weight(FontWeight::SEMIBOLD)
+                            .text_color(cx.theme().muted_foreground)
+                            .child(group.display_name()),
+                    )
+                    .children(shortcuts.into_iter().map(|shortcut| {
+                        h_flex()
+                            .w_full()
+                            .gap_3()
+                            .items_center()
+                            .child(
+                                div()
+                                    .flex_1()
+                                    .text_sm()
+                                    .text_color(cx.theme().foreground)
+                                    .child(shortcut.action.description()),
+                            )
+                            .child(
+                                div()
+                                    .px_2()
+                                    .py_0p5()
+                                    .rounded(px(4.0))
+                                    .bg(cx.theme().muted)
+                                    .text_xs()
+                                    .font_weight(FontWeight::MEDIUM)
+                                    .text_color(cx.theme().foreground)
+                                    .child(shortcut.keys_label()),
+                            )
+                    }))
+            }))
+            .child(
+                div()
+                    .text_xs()
+                    .text_color(cx.theme().muted_foreground)
+                    .child(t!("shortcuts.dismiss_hint").to_string()),
+            )
+    }
+
+    /// Full-size preview card filling most of the window. gpui decodes the
+    /// image on its background executor, so a spinner shows until it's ready.
+    /// Oversized images show a capped copy instead, scrolled along its long
+    /// edge.
+    fn render_preview(&self, path: &Path, viewport: Size<Pixels>, cx: &mut Context<Self>) -> impl IntoElement {
+        let info = self.screenshots.loaded().iter().find(|s| s.path == path);
+        let name = path
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_default();
+        let mut details = Vec::new();
+        let mut strips = None;
+        if let Some(info) = info {
+            if let Some((width, height)) = crate::thumbnail::dimensions(path, info.modified) {
+                details.push(t!("app.preview.dimensions", width = width, height = height).to_string());
+                if crate::thumbnail::is_oversized(width, height) {
+                    details.push(t!("app.preview.reduced").to_string());
+                    strips = Some(self.thumbnail_cache.preview_strips(path, info.modified));
+                }
+            }
+            details.push(format_file_size(info.file_size));
+            let captured = chrono::DateTime::<chrono::Local>::from(info.captured)
+                .format("%Y-%m-%d %H:%M:%S")
+                .to_string();
+            details.push(t!("app.preview.captured", time = captured).to_string());
+        }
+        // Screenshot folder › subfolders, to tell where an organized file sits
+        let breadcrumb = {
+            let settings = cx.global::<AppState>().settings.lock();
+            settings.preview_show_folder.then(|| settings.screenshot_directory.clone())
+        }
+        .map(|root| {
+            let folder = path.parent().unwrap_or(&root);
+            let root_name = root
+                .file_name()
+                .map(|n| n.to_string_lossy().to_string())
+                .unwrap_or_else(|| root.display().to_string());
+            std::iter::once(root_name)
+                .chain(
+                    folder
+                        .strip_prefix(&root)
+                        .unwrap_or(folder)
+                        .components()
+                        .map(|c| c.as_os_str().to_string_lossy().to_string()),
+                )
+                .collect::<Vec<_>>()
+                .join(" › ")
+        });
+        let muted = cx.theme().muted_foreground;
+        let cannot_load = move || {
+            div()
+                .text_sm()
+                .text_color(muted)
+                .child(t!("app.preview.cannot_load").to_string())
+                .into_any_element()
+        };
+        let image = match strips {
+            None => img(path.to_path_buf())
+                .max_w_full()
+                .max_h_full()
+                .object_fit(ObjectFit::Contain)
+                .with_loading(|| Spinner::new().into_any_element())
+                .with_fallback(cannot_load)
+                .into_any_element(),
+            Some(PreviewStrips::Pending) => Spinner::new().into_any_element(),
+            Some(PreviewStrips::Failed) => cannot_load(),
+            // Card size less its padding and the lines under the image
+            Some(PreviewStrips::Ready(strips)) => render_preview_strips(
+                strips,
+                viewport.width - px(88.0),
+                viewport.height - if breadcrumb.is_some() { px(152.0) } else { px(124.0) },
+                cannot_load,
+            ),
+        };
+
+        v_flex()
+            .w(viewport.width - px(64.0))
+            .h(viewport.height - px(64.0))
+            .p_3()
+            .gap_3()
+            .rounded(px(12.0))
+            .bg(cx.theme().popover)
+            .border_1()
+            .border_color(cx.theme().border)
+            .child(
+                div()
+                    .flex_1()
+                    .min_h_0()
+                    .w_full()
+                    .flex()
+                    .items_center()
+                    .justify_center()
+                    .child(image),
+            )
+            .when_some(breadcrumb, |el, breadcrumb| {
+                el.child(
+                    div()
+                        .text_xs()
+                        .text_color(muted)
+                        .truncate()
+                        .child(breadcrumb),
+                )
+            })
+            .child(
+                h_flex()
+                    .w_full()
+                    .gap_3()
+                    .items_center()
+                    .child(
+                        div()
+                            .text_sm()
+                            .font_weight(FontWeight::SEMIBOLD)
+                            .text_color(cx.theme().foreground)
+                            .child(name),
+                    )
+                    .child(
+                        div()
+                            .flex_1()
+                            .text_xs()
+                            .text_color(muted)
+                            .child(details.join(" · ")),
+                    )
+                    .child(
+                        div()
+                            .text_xs()
+                            .text_color(muted)
+                            .child(t!("app.preview.hint").to_string()),
+                    ),
+            )
+    }
+
+    fn render_read_only_prompt(&self, op: &FileOp, cx: &mut Context<Self>) -> impl IntoElement {
+        let name = op
+            .path()
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_default();
+        let desc = match op {
+            FileOp::Remove(_) => t!("app.read_only_prompt.remove_desc", name = name),
+            FileOp::Rename { .. } => t!("app.read_only_prompt.rename_desc", name = name),
+            FileOp::Replace { .. } => t!("app.read_only_prompt.replace_desc", name = name),
+        };
+        let waiting = self.read_only_prompts.len() - 1;
+        let id = ModalId::ReadOnlyPrompt;
+
+        v_flex()
+            .w(px(420.0))
+            .p_5()
+            .gap_4()
+            .rounded(px(12.0))
+            .bg(cx.theme().popover)
+            .border_1()
+            .border_color(cx.theme().border)
+            .child(
+                div()
+                    .text_lg()
+                    .font_weight(FontWeight::BOLD)
+                    .text_color(cx.theme().foreground)
+                    .child(t!("app.read_only_prompt.title").to_string()),
+            )
+            .child(
+                div()
+                    .text_sm()
+                    .text_color(cx.theme().muted_foreground)
+                    .child(desc.to_string()),
+            )
+            .when(waiting > 0, |el| {
+                el.child(
+                    div()
+                        .text_xs()
+                        .text_color(cx.theme().muted_foreground)
+                        .child(t!("app.read_only_prompt.more", count = waiting).to_string()),
+                )
+            })
+            .child(
+                h_flex()
+                    .w_full()
+                    .gap_2()
+                    .justify_end()
+                    .child(self.modal_button(
+                        id,
+                        0,
+                        Button::new("read-only-skip")
+                            .small()
+                            .ghost()
+                            .label(&t!("app.read_only_prompt.skip").to_string()),
+                        cx,
+                    ))
+                    .child(self.modal_button(
+                        id,
+                        1,
+                        Button::new("read-only-always")
+                            .small()
+                            .outline()
+                            .label(&t!("app.read_only_prompt.always").to_string()),
+                        cx,
+                    ))
+                    .child(self.modal_button(
+                        id,
+                        2,
+                        Button::new("read-only-make-writable")
+                            .small()
+                            .primary()
+                            .label(&t!("app.read_only_prompt.make_writable").to_string()),
+                        cx,
+                    )),
+            )
+    }
+
+    fn render_context_menu_prompt(
+        &self,
+        prompt: &ContextMenuPrompt,
+        cx: &mut Context<Self>,
+    ) -> impl IntoElement {
+        let threshold = cx.global::<AppState>().settings.lock().context_menu_confirm_threshold;
+        let card = v_flex()
+            .w(px(420.0))
+            .p_5()
+            .gap_4()
+            .rounded(px(12.0))
+            .bg(cx.theme().popover)
+            .border_1()
+            .border_color(cx.theme().border);
+
+        match prompt {
+            ContextMenuPrompt::Confirm(paths) => {
+                let id = ModalId::ContextMenuConfirm;
+                card.child(
+                    div()
+                        .text_lg()
+                        .font_weight(FontWeight::BOLD)
+                        .text_color(cx.theme().foreground)
+                        .child(t!("gallery.context_menu.confirm_title").to_string()),
+                )
+                .child(
+                    div()
+                        .text_sm()
+                        .text_color(cx.theme().muted_foreground)
+                        .child(
+                            t!(
+                                &plural_key("gallery.context_menu.confirm_desc", paths.len()),
+                                count = paths.len()
+                            )
+                            .to_string(),
+                        ),
+                )
+                .child(
+                    h_flex()
+                        .w_full()
+                        .gap_2()
+                        .justify_end()
+                        .child(self.modal_button(
+                            id,
+                            0,
+                            Button::new("context-menu-app")
+                                .small()
+                                .ghost()
+                                .label(&t!("gallery.context_menu.use_app_menu").to_string()),
+                            cx,
+                        ))
+                        .child(self.modal_button(
+                            id,
+                            1,
+                            Button::new("context-menu-first")
+                                .small()
+                                .outline()
+                                .label(&t!("gallery.context_menu.first_n", count = threshold).to_string()),
+                            cx,
+                        ))
+                        .child(self.modal_button(
+                            id,
+                            2,
+                            Button::new("context-menu-proceed")
+                                .small()
+                                .primary()
+                                .label(&t!("gallery.context_menu.proceed").to_string()),
+                            cx,
+                        )),
+                )
+            }
+            ContextMenuPrompt::AppMenu(paths) => {
+                let id = ModalId::ContextMenuAppMenu;
+                let edit_label = match crate::editor::preferred_editor() {
+                    Some(editor) => t!(
+                        "gallery.context_menu.edit_in",
+                        editor = crate::editor::editor_name(&editor)
+                    )
+                    .to_string(),
+                    None => t!("gallery.context_menu.edit").to_string(),
+                };
+                card.child(
+                    div()
+                        .text_lg()
+                        .font_weight(FontWeight::BOLD)
+                        .text_color(cx.theme().foreground)
+                        .child(
+                            t!(
+                                &plural_key("gallery.context_menu.app_menu_title", paths.len()),
+                                count = paths.len()
+                            )
+                            .to_string(),
+                        ),
+                )
+                .child(
+                    v_flex()
+                        .w_full()
+                        .gap_1()
+                        .child(self.modal_button(
+                            id,
+                            0,
+                            Button::new("context-menu-copy")
+                                .w_full()
+                                .ghost()
+                                .label(&t!("gallery.context_menu.copy").to_string()),
+                            cx,
+                        ))
+                        .child(self.modal_button(
+                            id,
+                            1,
+                            Button::new("context-menu-details")
+                                .w_full()
+                                .ghost()
+                                .label(&t!("gallery.context_menu.details").to_string()),
+                            cx,
+                        ))
+                        .child(self.modal_button(
+                            id,
+                            2,
+                            Button::new("context-menu-edit")
+                                .w_full()
+                                .ghost()
+                                .label(&edit_label),
+                            cx,
+                        ))
+                        .child(self.modal_button(
+                            id,
+                            3,
+                            Button::new("context-menu-select-similar")
+                                .w_full()
+                                .ghost()
+                                .label(&t!("gallery.context_menu.select_similar").to_string()),
+                            cx,
+                        ))
+                        .child(self.modal_button(
+                            id,
+                            4,
+                            Button::new("context-menu-fix-timestamps")
+                                .w_full()
+                                .ghost()
+                                .label(&t!("gallery.context_menu.fix_timestamps").to_string()),
+                            cx,
+                        ))
+                        .child(self.modal_button(
+                            id,
+                            5,
+                            Button::new("context-menu-copy-info")
+                                .w_full()
+                                .ghost()
+                                .label(&t!("gallery.context_menu.copy_info").to_string()),
+                            cx,
+                        ))
+                        .child(self.modal_button(
+                            id,
+                            6,
+                            Button::new("context-menu-organize")
+                                .w_full()
+                                .ghost()
+                                .label(&t!("gallery.context_menu.organize").to_string()),
+                            cx,
+                        )),
+                )
+            }
+        }
+    }
+
+    /// Old and new modified times of the files a timestamp fix would change
+    fn render_fix_timestamps(
+        &self,
+        plan: &content_date::Plan,
+        cx: &mut Context<Self>,
+    ) -> impl IntoElement {
+        let format_time = |time: SystemTime| {
+            chrono::DateTime::<chrono::Local>::from(time)
+                .format("%Y-%m-%d %H:%M")
+                .to_string()
+        };
+        let hidden = plan.fixes.len().saturating_sub(TIMESTAMP_PREVIEW_ROWS);
+        let skipped = plan.no_date + plan.unchanged;
+        let id = ModalId::FixTimestamps;
+
+        v_flex()
+            .w(px(420.0))
+            .p_5()
+            .gap_4()
+            .rounded(px(12.0))
+            .bg(cx.theme().popover)
+            .border_1()
+            .border_color(cx.theme().border)
+            .child(
+                div()
+                    .text_lg()
+                    .font_weight(FontWeight::BOLD)
+                    .text_color(cx.theme().foreground)
+                    .child(
+                        t!(
+                            &plural_key("app.fix_timestamps.title", plan.fixes.len()),
+                            count = plan.fixes.len()
+                        )
+                        .to_string(),
+                    ),
+            )
+            .child(
+                v_flex()
+                    .w_full()
+                    .gap_2()
+                    .children(plan.fixes.iter().take(TIMESTAMP_PREVIEW_ROWS).map(|fix| {
+                        let name = fix
+                            .path
+                            .file_name()
+                            .map(|n| text::middle_ellipsize(&n.to_string_lossy(), NOTIFICATION_NAME_WIDTH))
+                            .unwrap_or_default();
+                        v_flex()
+                            .child(div().text_sm().text_color(cx.theme().foreground).child(name))
+                            .child(
+                                div().text_xs().text_color(cx.theme().muted_foreground).child(
+                                    t!(
+                                        "app.fix_timestamps.change",
+                                        from = format_time(fix.from),
+                                        to = format_time(fix.to),
+                                        source = fix.source.display_name()
+                                    )
+                                    .to_string(),
+                                ),
+                            )
+                    })),
+            )
+            .when(hidden > 0, |el| {
+                el.child(
+                    div()
+                        .text_xs()
+                        .text_color(cx.theme().muted_foreground)
+                        .child(t!("app.fix_timestamps.more", count = hidden).to_string()),
+                )
+            })
+            .when(skipped > 0, |el| {
+                el.child(
+                    div()
+                        .text_xs()
+                        .text_color(cx.theme().muted_foreground)
+                        .child(
+                            t!(
+                                &plural_key("app.fix_timestamps.skipped", skipped),
+                                count = skipped
+                            )
+                            .to_string(),
+                        ),
+                )
+            })
+            .child(
+                h_flex()
+                    .w_full()
+                    .gap_2()
+                    .justify_end()
+                    .child(self.modal_button(
+                        id,
+                        0,
+                        Button::new("fix-timestamps-cancel")
+                            .small()
+                            .ghost()
+                            .label(&t!("app.fix_timestamps.cancel").to_string()),
+                        cx,
+                    ))
+                    .child(self.modal_button(
+                        id,
+                        1,
+                        Button::new("fix-timestamps-apply")
+                            .small()
+                            .primary()
+                            .label(&t!("app.fix_timestamps.apply").to_string()),
+                        cx,
+                    )),
+            )
+    }
+
+    fn render_strip_report(&self, report: &StripReport, cx: &mut Context<Self>) -> impl IntoElement {
+        // Failures first, then files that had something removed
+        let mut rows: Vec<&(PathBuf, FileOutcome)> = report
+            .files
+            .iter()
+            .filter(|(_, outcome)| matches!(outcome, FileOutcome::Failed(_)))
+            .chain(
+                report
+                    .files
+                    .iter()
+                    .filter(|(_, outcome)| matches!(outcome, FileOutcome::Stripped { .. })),
+            )
+            .collect();
+        let hidden = rows.len().saturating_sub(STRIP_REPORT_ROWS);
+        rows.truncate(STRIP_REPORT_ROWS);
+        let stripped = report.stripped();
+        let had_gps = report.had_gps();
+        let unchanged = report.unchanged();
+        let id = ModalId::StripReport;
+
+        v_flex()
+            .w(px(420.0))
+            .p_5()
+            .gap_4()
+            .rounded(px(12.0))
+            .bg(cx.theme().popover)
+            .border_1()
+            .border_color(cx.theme().border)
+            .child(
+                div()
+                    .text_lg()
+                    .font_weight(FontWeight::BOLD)
+                    .text_color(cx.theme().foreground)
+                    .child(
+                        t!(
+                            &plural_key("app.strip_metadata.title", stripped),
+                            count = stripped
+                        )
+                        .to_string(),
+                    ),
+            )
+            .when(had_gps > 0, |el| {
+                el.child(
+                    div()
+                        .text_sm()
+                        .text_color(cx.theme().foreground)
+                        .child(
+                            t!(
+                                &plural_key("app.strip_metadata.had_gps", had_gps),
+                                count = had_gps
+                            )
+                            .to_string(),
+                        ),
+                )
+            })
+            .child(
+                v_flex().w_full().gap_2().children(rows.into_iter().map(|(path, outcome)| {
+                    let name = path
+                        .file_name()
+                        .map(|n| text::middle_ellipsize(&n.to_string_lossy(), NOTIFICATION_NAME_WIDTH))
+                        .unwrap_or_default();
+                    let (status, failed) = match outcome {
+                        FileOutcome::Failed(error) => {
+                            (t!("app.strip_metadata.failed", error = error).to_string(), true)
+                        }
+                        FileOutcome::Stripped { had_gps: true } => {
+                            (t!("app.strip_metadata.stripped_gps").to_string(), false)
+                        }
+                        _ => (t!("app.strip_metadata.stripped").to_string(), false),
+                    };
+                    v_flex()
+                        .child(div().text_sm().text_color(cx.theme().foreground).child(name))
+                        .child(
+                            div()
+                                .text_xs()
+                                .text_color(if failed {
+                                    cx.theme().danger
+                                } else {
+                                    cx.theme().muted_foreground
+                                })
+                                .child(status),
+                        )
+                })),
+            )
+            .when(hidden > 0, |el| {
+                el.child(
+                    div()
+                        .text_xs()
+                        .text_color(cx.theme().muted_foreground)
+                        .child(t!("app.strip_metadata.more", count = hidden).to_string()),
+                )
+            })
+            .when(unchanged > 0, |el| {
+                el.child(
+                    div()
+                        .text_xs()
+                        .text_color(cx.theme().muted_foreground)
+                        .child(
+                            t!(
+                                &plural_key("app.strip_metadata.unchanged", unchanged),
+                                count = unchanged
+                            )
+                            .to_string(),
+                        ),
+                )
+            })
+            .child(
+                h_flex().w_full().justify_end().child(self.modal_button(
+                    id,
+                    0,
+                    Button::new("strip-report-close")
+                        .small()
+                        .primary()
+                        .label(&t!("common.button.close").to_string()),
+                    cx,
+                )),
+            )
+    }
+
+    fn render_index_recovery(&self, error: &str, cx: &mut Context<Self>) -> impl IntoElement {
+        let id = ModalId::IndexRecovery;
+
+        v_flex()
+            .w(px(420.0))
+            .p_5()
+            .gap_4()
+            .rounded(px(12.0))
+            .bg(cx.theme().popover)
+            .border_1()
+            .border_color(cx.theme().border)
+            .child(
+                div()
+                    .text_lg()
+                    .font_weight(FontWeight::BOLD)
+                    .text_color(cx.theme().foreground)
+                    .child(t!("app.index_recovery.title").to_string()),
+            )
+            .child(
+                div()
+                    .text_sm()
+                    .text_color(cx.theme().foreground)
+                    .child(t!("app.index_recovery.message").to_string()),
+            )
+            .when(!error.is_empty(), |el| {
+                el.child(
+                    div()
+                        .text_xs()
+                        .text_color(cx.theme().muted_foreground)
+                        .child(error.to_string()),
+                )
+            })
+            .child(
+                h_flex()
+                    .w_full()
+                    .gap_2()
+                    .justify_end()
+                    .child(self.modal_button(
+                        id,
+                        0,
+                        Button::new("index-recovery-later")
+                            .small()
+                            .ghost()
+                            .label(&t!("app.index_recovery.later").to_string()),
+                        cx,
+                    ))
+                    .child(self.modal_button(
+                        id,
+                        1,
+                        Button::new("index-recovery-rebuild")
+                            .small()
+                            .primary()
+                            .label(&t!("app.index_recovery.rebuild").to_string()),
+                        cx,
+                    )),
+            )
+    }
+
+    fn render_settings(&mut self, cx: &mut Context<Self>) -> impl IntoElement {
+        let app_state = cx.global::<AppState>();
+        let settings = app_state.settings.lock().clone();
+        let current_page = self.settings_page;
+        self.settings_controls.get_mut().clear();
+
+        if current_page == SettingsPage::General {
+            self.general_strings.refresh(&settings);
+        }
+        if current_page == SettingsPage::Cleanup {
+            self.cleanup_candidates
+                .refresh(settings.cleanup_unused_days, self.screenshots.loaded());
+        }
+
+        let pages: Vec<SettingsPage> = SettingsPage::ALL
+            .into_iter()
+            .filter(|page| page.is_visible(&settings))
+            .collect();
+
+        h_flex()
+            .size_full()
+            // Sidebar
+            .child(
+                v_flex()
+                    .w(px(160.0))
+                    .min_w(px(160.0))
+                    .max_w(px(160.0))
+                    .h_full()
+                    .py_2()
+                    .px_2()
+                    .overflow_hidden()
+                    .border_r_1()
+                    .border_color(cx.theme().border)
+                    .bg(cx.theme().background)
+                    .children(
+                        pages
+                            .into_iter()
+                            .map(|page| self.render_settings_tab(page, current_page, cx)),
+                    ),
+            )
+            // Content area
+            .child(
+                div()
+                    .id("settings-content")
+                    .flex_1()
+                    .h_full()
+                    .overflow_scroll()
+                    .p_4()
+                    .child(match current_page {
+                        SettingsPage::General => self
+                            .render_general_settings(&settings, cx)
+                            .into_any_element(),
+                        SettingsPage::Conversion => self
+                            .render_conversion_settings(&settings, cx)
+                            .into_any_element(),
+                        SettingsPage::Indexing => self
+                            .render_indexing_settings(&settings, cx)
+                            .into_any_element(),
+                        SettingsPage::Hotkey => self
+                            .render_hotkey_settings(&settings, cx)
+                            .into_any_element(),
+                        SettingsPage::Cleanup => self
+                            .render_cleanup_settings(&settings, cx)
+                            .into_any_element(),
+                        SettingsPage::Diagnostics => self
+                            .render_diagnostics_settings(cx)
+                            .into_any_element(),
+                        SettingsPage::Advanced => self
+                            .render_advanced_settings(&settings, cx)
+                            .into_any_element(),
+                        SettingsPage::About => self.render_about_settings(cx).into_any_element(),
+                    }),
+            )
+    }
+
+    /// Switch the settings view to `page`; the cleanup page lists
+    /// candidates afresh each time it's opened
+    fn show_settings_page(&mut self, page: SettingsPage, cx: &mut Context<Self>) {
+        if page == SettingsPage::Cleanup {
+            self.cleanup_candidates.invalidate();
+        }
+        self.settings_page = page;
+        cx.notify();
+    }
+
+    fn render_settings_tab(
+        &self,
+        page: SettingsPage,
+        current: SettingsPage,
+        cx: &mut Context<Self>,
+    ) -> impl IntoElement {
+        let is_active = page == current;
+        let focused = self.register_setting(
+            page.id().into(),
+            ControlKind::Button,
+            settings_action(move |this, _, _, cx| {
+                this.show_settings_page(page, cx);
+            }),
+        );
+        div()
+            .id(page.id())
+            .w_full()
+            .px_3()
+            .py_2()
+            .cursor_pointer()
+            .text_sm()
+            .rounded(px(6.0))
+            .mb_1()
+            .border_2()
+            .border_color(ring_color(focused, cx))
+            .when(is_active, |s| {
+                s.bg(cx.theme().primary)
+                    .text_color(cx.theme().primary_foreground)
+                    .font_weight(FontWeight::MEDIUM)
+            })
+            .when(!is_active, |s| {
+                s.text_color(cx.theme().foreground)
+                    .hover(|s| s.bg(cx.theme().muted))
+            })
+            .on_click(cx.listener(move |this, _, _, cx| {
+                this.show_settings_page(page, cx);
+            }))
+            .child(page.title())
+    }
+
+    fn render_setting_row(
+        &self,
+        label: &str,
+        description: Option<&str>,
+        control: impl IntoElement,
+        cx: &Context<Self>,
+    ) -> impl IntoElement {
+        v_flex()
+            .w_full()
+            .gap_1()
+            .mb_4()
+            .child(
+                h_flex()
+                    .w_full()
+                    .justify_between()
+                    .items_center()
+                    .child(
+                        div()
+                            .text_sm()
+                            .font_weight(FontWeight::MEDIUM)
+                            .text_color(cx.theme().foreground)
+                            .child(label.to_string()),
+                    )
+                    .child(control),
+            )
+            .when_some(description, |s, desc| {
+                s.child(
+                    div()
+                        .text_xs()
+                        .text_color(cx.theme().muted_foreground)
+                        .child(desc.to_string()),
+                )
+            })
+    }
+
+    fn render_section_header(&self, title: &str, cx: &Context<Self>) -> impl IntoElement {
+        div()
+            .text_base()
+            .font_weight(FontWeight::SEMIBOLD)
+            .text_color(cx.theme().foreground)
+            .mb_3()
+            .child(title.to_string())
+    }
+
+    fn render_general_settings(
+        &self,
+        settings: &crate::settings::Settings,
+        cx: &mut Context<Self>,
+    ) -> impl IntoElement {
+        let screenshot_dir = settings.screenshot_directory.to_string_lossy().to_string();
+        let screenshot_dir_path = settings.screenshot_directory.clone();
+        let thumbnail_size = self.thumbnail_size;
+        let badge_content = self.badge_content;
+        let organizer_enabled = settings.organizer_enabled;
+        let organizer_format = settings.organizer_format.clone();
+        let organizing = self.organizing;
+        let organize_progress = self.organize_progress;
+
+        // Cached strings (see GeneralPageStrings::refresh)
+        let strings = &self.general_strings;
+        let format_preview = strings.organizer_preview.clone();
+        let file_name_preview = strings.file_name_preview.clone();
+        let language_title = strings.language_title.clone();
+        let language_label = strings.language_label.clone();
+        let language_desc = strings.language_desc.clone();
+        let screenshot_dir_title = strings.screenshot_dir_title.clone();
+        let browse_label = strings.browse_label.clone();
+        let organizer_title = strings.organizer_title.clone();
+        let organizer_enable_label = strings.organizer_enable_label.clone();
+        let organizer_enable_desc = strings.organizer_enable_desc.clone();
+
+        // Get current language
+        let current_lang = crate::i18n_helpers::current_language();
 
-                match event.keystroke.key.as_str() {
-                    // ESC - clear selection, close settings, or minimize window
-                    "escape" => {
-                        if this.recording_hotkey {
-                            this.recording_hotkey = false;
+        v_flex()
+            .w_full()
+            .gap_2()
+            // Startup
+            .child(self.render_section_header(&t!("settings.general.startup.title").to_string(), cx))
+            .child(
+                self.render_setting_row(
+                    &t!("settings.general.startup.run_on_startup_label").to_string(),
+                    Some(&t!("settings.general.startup.run_on_startup_desc").to_string()),
+                    self.settings_switch(
+                        "run-on-startup",
+                        settings.run_on_startup,
+                        true,
+                        |_this, checked, _, cx| {
+                            {
+                                let app_state = cx.global::<AppState>();
+                                let mut settings = app_state.settings.lock();
+                                settings.run_on_startup = checked;
+                                let _ = settings.save();
+                            }
+                            // Register/unregister from Windows startup
+                            if checked {
+                                register_startup();
+                            } else {
+                                unregister_startup();
+                            }
                             cx.notify();
-                        } else if !this.selected.is_empty() {
-                            // Clear selection if items are selected
-                            this.selected.clear();
-                            this.last_selected = None;
+                        },
+                        cx,
+                    ),
+                    cx,
+                ),
+            )
+            .child(
+                self.render_setting_row(
+                    &t!("settings.general.startup.hide_window_on_start_label").to_string(),
+                    Some(&t!("settings.general.startup.hide_window_on_start_desc").to_string()),
+                    self.settings_switch(
+                        "hide-on-start",
+                        settings.hide_window_on_start,
+                        true,
+                        |_this, checked, _, cx| {
+                            {
+                                let app_state = cx.global::<AppState>();
+                                let mut settings = app_state.settings.lock();
+                                settings.hide_window_on_start = checked;
+                                let _ = settings.save();
+                            }
                             cx.notify();
-                        } else if this.settings_open {
-                            // Close settings if open
-                            this.settings_open = false;
+                        },
+                        cx,
+                    ),
+                    cx,
+                ),
+            )
+            // Feedback
+            .child(self.render_section_header(&t!("settings.general.feedback.title").to_string(), cx))
+            .child(
+                self.render_setting_row(
+                    &t!("settings.general.feedback.sound_label").to_string(),
+                    Some(&t!("settings.general.feedback.sound_desc").to_string()),
+                    self.settings_switch(
+                        "sound-feedback",
+                        settings.sound_feedback,
+                        true,
+                        |_this, checked, _, cx| {
+                            {
+                                let app_state = cx.global::<AppState>();
+                                let mut settings = app_state.settings.lock();
+                                settings.sound_feedback = checked;
+                                let _ = settings.save();
+                            }
                             cx.notify();
-                        } else {
-                            // Minimize window
-                            window.minimize_window();
-                        }
+                        },
+                        cx,
+                    ),
+                    cx,
+                ),
+            )
+            // Language
+            .child(self.render_section_header(&language_title, cx))
+            .child(
+                self.render_setting_row(
+                    &language_label,
+                    Some(&language_desc),
+                    h_flex()
+                        .gap_1()
+                        .child(
+                            self.settings_button(
+                                "lang-en",
+                                true,
+                                |b| {
+                                    b.small()
+                                        .when(current_lang == "en", |b| b.primary())
+                                        .when(current_lang != "en", |b| b.outline())
+                                        .label("English")
+                                },
+                                |_this, _, cx| {
+                                    crate::i18n_helpers::change_language("en");
+                                    {
+                                        let app_state = cx.global::<AppState>();
+                                        let mut settings = app_state.settings.lock();
+                                        settings.language = Some("en".to_string());
+                                        let _ = settings.save();
+                                    }
+                                    cx.notify();
+                                },
+                                cx,
+                            )
+                        )
+                        .child(
+                            self.settings_button(
+                                "lang-ko",
+                                true,
+                                |b| {
+                                    b.small()
+                                        .when(current_lang == "ko", |b| b.primary())
+                                        .when(current_lang != "ko", |b| b.outline())
+                                        .label("한국어")
+                                },
+                                |_this, _, cx| {
+                                    crate::i18n_helpers::change_language("ko");
+                                    {
+                                        let app_state = cx.global::<AppState>();
+                                        let mut settings = app_state.settings.lock();
+                                        settings.language = Some("ko".to_string());
+                                        let _ = settings.save();
+                                    }
+                                    cx.notify();
+                                },
+                                cx,
+                            )
+                        )
+                        .child(
+                            self.settings_button(
+                                "lang-ja",
+                                true,
+                                |b| {
+                                    b.small()
+                                        .when(current_lang == "ja", |b| b.primary())
+                                        .when(current_lang != "ja", |b| b.outline())
+                                        .label("日本語")
+                                },
+                                |_this, _, cx| {
+                                    crate::i18n_helpers::change_language("ja");
+                                    {
+                                        let app_state = cx.global::<AppState>();
+                                        let mut settings = app_state.settings.lock();
+                                        settings.language = Some("ja".to_string());
+                                        let _ = settings.save();
+                                    }
+                                    cx.notify();
+                                },
+                                cx,
+                            ),
+                        ),
+                    cx,
+                )
+            )
+            // Theme
+            .child(self.render_section_header(&t!("settings.general.appearance.theme_label").to_string(), cx))
+            .child(
+                self.render_setting_row(
+                    &t!("settings.general.appearance.theme_label").to_string(),
+                    Some(&t!("settings.general.appearance.theme_desc").to_string()),
+                    h_flex()
+                        .gap_1()
+                        .child(
+                            self.settings_button(
+                                "theme-dark",
+                                true,
+                                |b| {
+                                    b.small()
+                                        .when(settings.theme == crate::settings::ThemeMode::Dark, |b| b.primary())
+                                        .when(settings.theme != crate::settings::ThemeMode::Dark, |b| b.outline())
+                                        .label(t!("settings.general.appearance.theme_dark").to_string())
+                                },
+                                |_this, window, cx| {
+                                    {
+                                        let app_state = cx.global::<AppState>();
+                                        let mut settings = app_state.settings.lock();
+                                        settings.theme = crate::settings::ThemeMode::Dark;
+                                        let _ = settings.save();
+                                    }
+                                    apply_theme(crate::settings::ThemeMode::Dark, window, cx);
+                                    cx.notify();
+                                },
+                                cx,
+                            )
+                        )
+                        .child(
+                            self.settings_button(
+                                "theme-light",
+                                true,
+                                |b| {
+                                    b.small()
+                                        .when(settings.theme == crate::settings::ThemeMode::Light, |b| b.primary())
+                                        .when(settings.theme != crate::settings::ThemeMode::Light, |b| b.outline())
+                                        .label(t!("settings.general.appearance.theme_light").to_string())
+                                },
+                                |_this, window, cx| {
+                                    {
+                                        let app_state = cx.global::<AppState>();
+                                        let mut settings = app_state.settings.lock();
+                                        settings.theme = crate::settings::ThemeMode::Light;
+                                        let _ = settings.save();
+                                    }
+                                    apply_theme(crate::settings::ThemeMode::Light, window, cx);
+                                    cx.notify();
+                                },
+                                cx,
+                            )
+                        )
+                        .child(
+                            self.settings_button(
+                                "theme-system",
+                                true,
+                                |b| {
+                                    b.small()
+                                        .when(settings.theme == crate::settings::ThemeMode::System, |b| b.primary())
+                                        .when(settings.theme != crate::settings::ThemeMode::System, |b| b.outline())
+                                        .label(t!("settings.general.appearance.theme_system").to_string())
+                                },
+                                |_this, window, cx| {
+                                    {
+                                        let app_state = cx.global::<AppState>();
+                                        let mut settings = app_state.settings.lock();
+                                        settings.theme = crate::settings::ThemeMode::System;
+                                        let _ = settings.save();
+                                    }
+                                    apply_theme(crate::settings::ThemeMode::System, window, cx);
+                                    cx.notify();
+                                },
+                                cx,
+                            ),
+                        ),
+                    cx,
+                )
+            )
+            // Screenshot Directory
+            .child(self.render_section_header(&screenshot_dir_title, cx))
+            .child(
+                h_flex()
+                    .w_full()
+                    .gap_2()
+                    .items_center()
+                    .mb_4()
+                    .child(
+                        div()
+                            .id("screenshot-dir-drop")
+                            .flex_1()
+                            .px_3()
+                            .py_2()
+                            .rounded(px(6.0))
+                            .border_1()
+                            .border_color(cx.theme().muted)
+                            .bg(cx.theme().muted)
+                            .text_sm()
+                            .text_color(cx.theme().muted_foreground)
+                            .overflow_x_hidden()
+                            .drag_over::<ExternalPaths>(|style, _, _, cx| {
+                                style.border_color(cx.theme().primary)
+                            })
+                            .child(screenshot_dir),
+                    )
+                    .child(
+                        self.settings_button(
+                            "browse-dir",
+                            true,
+                            |b| {
+                                b.label(&browse_label)
+                                    .small()
+                                    .outline()
+                            },
+                            |_this, _, cx| {
+                                let tx = {
+                                    let app_state = cx.global::<AppState>();
+                                    app_state.message_tx.clone()
+                                };
+                                std::thread::spawn(move || {
+                                    if let Some(path) = pick_folder() {
+                                        let _ = tx.send(AppMessage::ChangeDirectory(path));
+                                    }
+                                });
+                            },
+                            cx,
+                        ),
+                    ),
+            )
+            // Confirm a dropped directory before switching
+            .when_some(self.pending_directory.clone(), |el, (dir, count)| {
+                el.child(
+                    h_flex()
+                        .w_full()
+                        .gap_2()
+                        .p_3()
+                        .mb_4()
+                        .rounded(px(6.0))
+                        .bg(cx.theme().muted)
+                        .items_center()
+                        .child(
+                            div()
+                                .flex_1()
+                                .text_xs()
+                                .text_color(cx.theme().foreground)
+                                .child(
+                                    t!(
+                                        &plural_key("settings.general.screenshot_dir.drop_confirm", count),
+                                        path = dir.to_string_lossy(),
+                                        count = count
+                                    )
+                                    .to_string(),
+                                ),
+                        )
+                        .child(
+                            self.settings_button(
+                                "drop-dir-cancel",
+                                true,
+                                |b| {
+                                    b.small()
+                                        .ghost()
+                                        .label(&t!("common.button.cancel").to_string())
+                                },
+                                |this, _, cx| {
+                                    this.pending_directory = None;
+                                    cx.notify();
+                                },
+                                cx,
+                            ),
+                        )
+                        .child(
+                            self.settings_button(
+                                "drop-dir-confirm",
+                                true,
+                                |b| {
+                                    b.small()
+                                        .primary()
+                                        .label(&t!("settings.general.screenshot_dir.drop_switch").to_string())
+                                },
+                                |this, _, cx| {
+                                    if let Some((dir, _)) = this.pending_directory.take() {
+                                        let app_state = cx.global::<AppState>();
+                                        let _ = app_state.message_tx.send(AppMessage::ChangeDirectory(dir));
+                                    }
+                                    cx.notify();
+                                },
+                                cx,
+                            ),
+                        ),
+                )
+            })
+            // A drive root, system folder or huge folder needs a go-ahead
+            .when_some(self.directory_warning.clone(), |el, (dir, concern)| {
+                let path = dir.to_string_lossy().to_string();
+                let message = match concern {
+                    crate::directory_check::Concern::DriveRoot => {
+                        t!("settings.general.screenshot_dir.warning.drive_root", path = path)
                     }
-                    // Ctrl+C - copy selected files to clipboard
-                    "c" if event.keystroke.modifiers.control => {
-                        if !this.selected.is_empty() {
-                            let files: Vec<_> = this.selected.iter().cloned().collect();
-                            let count = files.len();
-                            info!("Attempting to copy {} files to clipboard", count);
-                            if clipboard::copy_files_to_clipboard(&files) {
-                                info!("Successfully copied {} files to clipboard", count);
-                                // Send message to show notification (will be handled in process_messages)
-                                let app_state = cx.global::<AppState>();
-                                let _ = app_state.message_tx.send(AppMessage::CopiedToClipboard(count));
-                            } else {
-                                error!("Failed to copy files to clipboard");
+                    crate::directory_check::Concern::SystemFolder => {
+                        t!("settings.general.screenshot_dir.warning.system_folder", path = path)
+                    }
+                    crate::directory_check::Concern::LargeFolder { estimate } => t!(
+                        &plural_key("settings.general.screenshot_dir.warning.large_folder", estimate),
+                        path = path,
+                        count = format_count(estimate)
+                    ),
+                };
+                el.child(
+                    v_flex()
+                        .w_full()
+                        .gap_2()
+                        .p_3()
+                        .mb_4()
+                        .rounded(px(6.0))
+                        .border_1()
+                        .border_color(cx.theme().danger)
+                        .bg(cx.theme().danger.opacity(0.1))
+                        .child(
+                            div()
+                                .text_sm()
+                                .font_weight(FontWeight::BOLD)
+                                .text_color(cx.theme().danger)
+                                .child(t!("settings.general.screenshot_dir.warning.title").to_string()),
+                        )
+                        .child(
+                            div()
+                                .text_xs()
+                                .text_color(cx.theme().foreground)
+                                .child(message.to_string()),
+                        )
+                        .child(
+                            h_flex()
+                                .w_full()
+                                .gap_2()
+                                .justify_end()
+                                .child(
+                                    self.settings_button(
+                                        "dir-warning-cancel",
+                                        true,
+                                        |b| {
+                                            b.small()
+                                                .primary()
+                                                .label(&t!("common.button.cancel").to_string())
+                                        },
+                                        |this, _, cx| {
+                                            this.directory_warning = None;
+                                            cx.notify();
+                                        },
+                                        cx,
+                                    ),
+                                )
+                                .child(
+                                    self.settings_button(
+                                        "dir-warning-confirm",
+                                        true,
+                                        |b| {
+                                            b.small()
+                                                .ghost()
+                                                .label(&t!("settings.general.screenshot_dir.warning.use_anyway").to_string())
+                                        },
+                                        |this, _, cx| {
+                                            if let Some((dir, _)) = this.directory_warning.take() {
+                                                this.switch_directory(dir, cx);
+                                            }
+                                            cx.notify();
+                                        },
+                                        cx,
+                                    ),
+                                ),
+                        ),
+                )
+            })
+            // Screenshot Organizer
+            .child(self.render_section_header(&organizer_title, cx))
+            .child(
+                self.render_setting_row(
+                    &organizer_enable_label,
+                    if organizing {
+                        None
+                    } else {
+                        Some(&organizer_enable_desc)
+                    },
+                    self.settings_switch(
+                        "organizer-enable",
+                        organizer_enabled,
+                        !organizing,
+                        {
+                            let format = organizer_format.clone();
+                            let base_dir = screenshot_dir_path.clone();
+                            move |this, checked, _, cx| {
+                                {
+                                    let app_state = cx.global::<AppState>();
+                                    let mut settings = app_state.settings.lock();
+                                    settings.organizer_enabled = checked;
+                                    let _ = settings.save();
+                                }
+                                // If enabling, organize existing files
+                                if checked && !this.organizing {
+                                    let tx = {
+                                        let app_state = cx.global::<AppState>();
+                                        app_state.message_tx.clone()
+                                    };
+                                    this.organize_cancel = Arc::new(AtomicBool::new(false));
+                                    organizer::organize_existing_files(
+                                        base_dir.clone(),
+                                        format.clone(),
+                                        this.organize_cancel.clone(),
+                                        tx,
+                                    );
+                                }
+                                cx.notify();
                             }
-                        } else {
-                            info!("No files selected for clipboard copy");
-                        }
-                    }
-                    // Ctrl+A - select all visible
-                    "a" if event.keystroke.modifiers.control => {
-                        let paths: Vec<_> = this
-                            .visible_screenshots()
-                            .iter()
-                            .map(|i| i.path.clone())
-                            .collect();
-                        this.selected.clear();
-                        for path in paths {
-                            this.selected.insert(path);
-                        }
-                        cx.notify();
-                    }
-                    _ => {}
-                }
-            }))
-            // Header bar with window controls - enhanced styling
+                        },
+                        cx,
+                    ),
+                    cx,
+                ),
+            )
+            // Progress bar when organizing
+            .when(organizing, |el| {
+                let (current, total) = organize_progress;
+                let progress_pct = if total > 0 {
+                    (current as f32 / total as f32) * 100.0
+                } else {
+                    0.0
+                };
+                el.child(
+                    v_flex()
+                        .w_full()
+                        .gap_2()
+                        .mb_4()
+                        .child(
+                            // Progress bar container
+                            div()
+                                .w_full()
+                                .h(px(8.0))
+                                .rounded(px(4.0))
+                                .bg(cx.theme().muted)
+                                .overflow_hidden()
+                                .child(
+                                    div()
+                                        .h_full()
+                                        .w(relative(progress_pct / 100.0))
+                                        .bg(cx.theme().primary)
+                                        .rounded(px(4.0)),
+                                ),
+                        )
+                        .child(
+                            h_flex()
+                                .w_full()
+                                .justify_between()
+                                .child(render_current_file(
+                                    "organize-current-file",
+                                    &self.organize_current_file,
+                                    t!("settings.general.organizer.progress.preparing").to_string(),
+                                    cx,
+                                ))
+                                .child(
+                                    h_flex()
+                                        .gap_2()
+                                        .items_center()
+                                        .child(
+                                            div()
+                                                .text_xs()
+                                                .text_color(cx.theme().muted_foreground)
+                                                .child(
+                                                    t!(
+                                                        &plural_key("settings.general.organizer.progress.status", total),
+                                                        current = current,
+                                                        total = total
+                                                    )
+                                                    .to_string(),
+                                                ),
+                                        )
+                                        .child(
+                                            self.settings_button(
+                                                "organize-cancel",
+                                                true,
+                                                |b| {
+                                                    b.xsmall()
+                                                        .ghost()
+                                                        .label(&t!("common.button.cancel").to_string())
+                                                },
+                                                |this, _, cx| {
+                                                    this.organize_cancel.store(true, Ordering::Relaxed);
+                                                    cx.notify();
+                                                },
+                                                cx,
+                                            ),
+                                        ),
+                                ),
+                        ),
+                )
+            })
             .child(
-                div()
-                    .id("header-container")
+                v_flex()
                     .w_full()
-                    .border_b_1()
-                    .border_color(cx.theme().border)
-                    // Use theme muted background for header
-                    .bg(cx.theme().popover)
+                    .gap_2()
+                    .mb_4()
                     .child(
                         h_flex()
                             .w_full()
-                            .px_4()
-                            .py_3()
-                            .gap_3()
+                            .justify_between()
                             .items_center()
-                            // Title area - draggable using native Windows API
+                            .child(
+                                div()
+                                    .text_sm()
+                                    .font_weight(FontWeight::MEDIUM)
+                                    .text_color(cx.theme().foreground)
+                                    .child(t!("settings.general.organizer.format_label").to_string()),
+                            )
                             .child(
                                 h_flex()
-                                    .id("header-drag-area")
-                                    .flex_1()
-                                    .gap_3()
-                                    .items_center()
-                                    .h(px(28.0))
-                                    .cursor(CursorStyle::Arrow)
-                                    // Use native Windows drag on mouse down
-                                    .on_mouse_down(MouseButton::Left, |_, window, _cx| {
-                                        start_window_drag(window);
-                                    })
-                                    // App icon/logo placeholder
+                                    .gap_1()
                                     .child(
-                                        div()
-                                            .w(px(24.0))
-                                            .h(px(24.0))
-                                            .rounded(px(6.0))
-                                            .bg(cx.theme().primary)
-                                            .flex()
-                                            .items_center()
-                                            .justify_center()
-                                            .text_color(cx.theme().primary_foreground)
-                                            .text_xs()
-                                            .font_weight(FontWeight::BOLD)
-                                            .child("📷"),
+                                        self.settings_button(
+                                            "fmt-ymd",
+                                            true,
+                                            |b| {
+                                                b.small()
+                                                    .when(organizer_format == "YYYY-MM-DD", |s| s.primary())
+                                                    .when(organizer_format != "YYYY-MM-DD", |s| s.outline())
+                                                    .label(&t!("settings.general.organizer.format_ymd").to_string())
+                                            },
+                                            |this, window, cx| {
+                                                this.pick_organizer_format("YYYY-MM-DD", window, cx);
+                                            },
+                                            cx,
+                                        ),
                                     )
                                     .child(
-                                        div()
-                                            .text_lg()
-                                            .font_weight(FontWeight::BOLD)
-                                            .text_color(cx.theme().foreground)
-                                            .child(t!("app.header.title").to_string()),
+                                        self.settings_button(
+                                            "fmt-ym",
+                                            true,
+                                            |b| {
+                                                b.small()
+                                                    .when(organizer_format == "YYYY-MM", |s| s.primary())
+                                                    .when(organizer_format != "YYYY-MM", |s| s.outline())
+                                                    .label(&t!("settings.general.organizer.format_ym").to_string())
+                                            },
+                                            |this, window, cx| {
+                                                this.pick_organizer_format("YYYY-MM", window, cx);
+                                            },
+                                            cx,
+                                        ),
                                     )
                                     .child(
-                                        div()
-                                            .px_2()
-                                            .py_1()
-                                            .rounded(px(12.0))
-                                            .bg(cx.theme().muted)
-                                            .text_xs()
-                                            .text_color(cx.theme().muted_foreground)
-                                            .child(t!("app.header.counter", visible = visible_count, total = total_count).to_string()),
-                                    )
-                                    .when(selected_count > 0, |this| {
-                                        this.child(
-                                            div()
-                                                .px_2()
-                                                .py_1()
-                                                .rounded(px(12.0))
-                                                .bg(cx.theme().primary)
-                                                .text_xs()
-                                                .font_weight(FontWeight::MEDIUM)
-                                                .text_color(cx.theme().primary_foreground)
-                                                .child(t!("app.header.selected", count = selected_count).to_string()),
-                                        )
-                                    }),
-                            )
-                            // Settings button (opens settings / goes back)
-                            .child(
-                                div()
-                                    .id("settings-btn")
-                                    .w(px(32.0))
-                                    .h(px(32.0))
-                                    .rounded(px(8.0))
-                                    .cursor_pointer()
-                                    .flex()
-                                    .items_center()
-                                    .justify_center()
-                                    .bg(cx.theme().muted)
-                                    .text_color(cx.theme().muted_foreground)
-                                    .hover(|s| {
-                                        s.bg(cx.theme().accent)
-                                            .text_color(cx.theme().accent_foreground)
-                                    })
-                                    .active(|s| {
-                                        s.bg(cx.theme().primary)
-                                            .text_color(cx.theme().primary_foreground)
-                                    })
-                                    .on_click(cx.listener(|this, _, _, cx| {
-                                        this.settings_open = !this.settings_open;
-                                        cx.notify();
-                                    }))
-                                    .child(if settings_open { "←" } else { "⚙" }),
-                            )
-                            // Minimize button
+                                        self.settings_button(
+                                            "fmt-ymd-slash",
+                                            true,
+                                            |b| {
+                                                b.small()
+                                                    .when(organizer_format == "YYYY/MM/DD", |s| s.primary())
+                                                    .when(organizer_format != "YYYY/MM/DD", |s| s.outline())
+                                                    .label(&t!("settings.general.organizer.format_ymd_slash").to_string())
+                                            },
+                                            |this, window, cx| {
+                                                this.pick_organizer_format("YYYY/MM/DD", window, cx);
+                                            },
+                                            cx,
+                                        ),
+                                    ),
+                            ),
+                    )
+                    // Or any format typed in, previewed as it's typed
+                    .child(div().w_full().child(Input::new(&self.organizer_format_input)))
+                    .child(
+                        div()
+                            .text_xs()
+                            .text_color(cx.theme().muted_foreground)
+                            .child(t!("settings.general.organizer.format_tokens").to_string()),
+                    )
+                    .child(match &self.organizer_format_error {
+                        Some(error) => div()
+                            .text_xs()
+                            .text_color(cx.theme().danger)
+                            .child(folder_format_error_message(error)),
+                        None => div()
+                            .text_xs()
+                            .text_color(cx.theme().muted_foreground)
+                            .child(format_preview),
+                    })
+                    // Offer to move existing date folders into the new scheme
+                    .when(self.reorganize_offer.is_some() && !organizing, |el| {
+                        el.child(
+                            h_flex()
+                                .w_full()
+                                .gap_2()
+                                .p_3()
+                                .rounded(px(6.0))
+                                .bg(cx.theme().muted)
+                                .items_center()
+                                .child(
+                                    div()
+                                        .flex_1()
+                                        .text_xs()
+                                        .text_color(cx.theme().foreground)
+                                        .child(t!("settings.general.organizer.reorganize.prompt").to_string()),
+                                )
+                                .child(
+                                    self.settings_button(
+                                        "reorganize-dismiss",
+                                        true,
+                                        |b| {
+                                            b.small()
+                                                .ghost()
+                                                .label(&t!("settings.general.organizer.reorganize.dismiss").to_string())
+                                        },
+                                        |this, _, cx| {
+                                            this.reorganize_offer = None;
+                                            cx.notify();
+                                        },
+                                        cx,
+                                    ),
+                                )
+                                .child(
+                                    self.settings_button(
+                                        "reorganize-start",
+                                        true,
+                                        |b| {
+                                            b.small()
+                                                .primary()
+                                                .label(&t!("settings.general.organizer.reorganize.start").to_string())
+                                        },
+                                        |this, _, cx| {
+                                            this.start_reorganize(cx);
+                                        },
+                                        cx,
+                                    ),
+                                ),
+                        )
+                    }),
+            )
+            .child(
+                self.render_setting_row(
+                    &t!("settings.general.organizer.file_name_date_label").to_string(),
+                    Some(&t!("settings.general.organizer.file_name_date_desc").to_string()),
+                    self.settings_switch(
+                        "date-from-file-name",
+                        settings.date_from_file_name,
+                        !organizing,
+                        |this, checked, _, cx| this.set_date_from_file_name(checked, cx),
+                        cx,
+                    ),
+                    cx,
+                ),
+            )
+            .child(
+                self.render_setting_row(
+                    &t!("settings.general.organizer.flatten.label").to_string(),
+                    Some(&t!("settings.general.organizer.flatten.desc").to_string()),
+                    self.settings_button(
+                        "flatten-ask",
+                        !organizing && !self.flatten_confirm,
+                        |b| {
+                            b.small()
+                                .outline()
+                                .label(&t!("settings.general.organizer.flatten.button").to_string())
+                        },
+                        |this, _, cx| {
+                            this.flatten_confirm = true;
+                            cx.notify();
+                        },
+                        cx,
+                    ),
+                    cx,
+                ),
+            )
+            .when(self.flatten_confirm && !organizing, |el| {
+                el.child(
+                    h_flex()
+                        .w_full()
+                        .gap_2()
+                        .p_3()
+                        .mb_4()
+                        .rounded(px(6.0))
+                        .bg(cx.theme().muted)
+                        .items_center()
+                        .child(
+                            div()
+                                .flex_1()
+                                .text_xs()
+                                .text_color(cx.theme().foreground)
+                                .child(t!("settings.general.organizer.flatten.prompt").to_string()),
+                        )
+                        .child(
+                            self.settings_button(
+                                "flatten-dismiss",
+                                true,
+                                |b| b.small().ghost().label(&t!("common.button.cancel").to_string()),
+                                |this, _, cx| {
+                                    this.flatten_confirm = false;
+                                    cx.notify();
+                                },
+                                cx,
+                            ),
+                        )
+                        .child(
+                            self.settings_button(
+                                "flatten-start",
+                                true,
+                                |b| {
+                                    b.small()
+                                        .primary()
+                                        .label(&t!("settings.general.organizer.flatten.start").to_string())
+                                },
+                                |this, _, cx| {
+                                    this.start_flatten(cx);
+                                },
+                                cx,
+                            ),
+                        ),
+                )
+            })
+            // External image editor
+            .child(self.render_section_header(&t!("settings.general.editor.title").to_string(), cx))
+            .child(
+                v_flex()
+                    .w_full()
+                    .gap_2()
+                    .mb_4()
+                    .child(
+                        h_flex()
+                            .w_full()
+                            .gap_4()
+                            .justify_between()
+                            .items_center()
                             .child(
                                 div()
-                                    .id("minimize-btn")
-                                    .w(px(32.0))
-                                    .h(px(32.0))
-                                    .rounded(px(8.0))
-                                    .cursor_pointer()
-                                    .flex()
+                                    .text_sm()
+                                    .font_weight(FontWeight::MEDIUM)
+                                    .text_color(cx.theme().foreground)
+                                    .child(t!("settings.general.editor.label").to_string()),
+                            )
+                            .child(
+                                h_flex()
+                                    .gap_2()
                                     .items_center()
-                                    .justify_center()
-                                    .bg(cx.theme().muted)
-                                    .text_color(cx.theme().muted_foreground)
-                                    .hover(|s| {
-                                        s.bg(gpui::rgb(0xE53935)).text_color(gpui::rgb(0xFFFFFF))
-                                    })
-                                    .active(|s| s.bg(gpui::rgb(0xC62828)))
-                                    .on_click(|_, window, _cx| {
-                                        window.minimize_window();
-                                    })
-                                    .child("—"),
+                                    .child(div().w(px(300.0)).child(Input::new(&self.editor_input)))
+                                    .child(
+                                        self.settings_button(
+                                            "browse-editor",
+                                            true,
+                                            |b| {
+                                                b.label(&browse_label)
+                                                    .small()
+                                                    .outline()
+                                            },
+                                            |_this, _, cx| {
+                                                let tx = {
+                                                    let app_state = cx.global::<AppState>();
+                                                    app_state.message_tx.clone()
+                                                };
+                                                std::thread::spawn(move || {
+                                                    if let Some(path) = pick_file() {
+                                                        let _ = tx.send(AppMessage::EditorPicked(path));
+                                                    }
+                                                });
+                                            },
+                                            cx,
+                                        ),
+                                    ),
                             ),
+                    )
+                    .child(match &self.editor_error {
+                        Some(path) => div()
+                            .text_xs()
+                            .text_color(cx.theme().danger)
+                            .child(t!("settings.general.editor.not_found", path = path).to_string()),
+                        None => div()
+                            .text_xs()
+                            .text_color(cx.theme().muted_foreground)
+                            .child(t!("settings.general.editor.desc").to_string()),
+                    }),
+            )
+            // Renaming new screenshots
+            .child(self.render_section_header(&t!("settings.general.file_names.title").to_string(), cx))
+            .child(
+                self.render_setting_row(
+                    &t!("settings.general.file_names.rename_label").to_string(),
+                    Some(&t!("settings.general.file_names.rename_desc").to_string()),
+                    self.settings_switch(
+                        "rename-new-screenshots",
+                        settings.rename_new_screenshots,
+                        true,
+                        |_this, checked, _, cx| {
+                            {
+                                let app_state = cx.global::<AppState>();
+                                let mut settings = app_state.settings.lock();
+                                settings.rename_new_screenshots = checked;
+                                let _ = settings.save();
+                            }
+                            cx.notify();
+                        },
+                        cx,
                     ),
+                    cx,
+                ),
             )
             .child(
-                // Main content area
-                div()
-                    .flex_1()
+                v_flex()
                     .w_full()
-                    .overflow_hidden()
-                    .child(if settings_open {
-                        self.render_settings(cx).into_any_element()
-                    } else {
-                        self.render_gallery(has_more, cx).into_any_element()
+                    .gap_2()
+                    .mb_4()
+                    .child(
+                        h_flex()
+                            .w_full()
+                            .gap_4()
+                            .justify_between()
+                            .items_center()
+                            .child(
+                                div()
+                                    .text_sm()
+                                    .font_weight(FontWeight::MEDIUM)
+                                    .text_color(cx.theme().foreground)
+                                    .child(t!("settings.general.file_names.pattern_label").to_string()),
+                            )
+                            .child(div().w(px(300.0)).child(Input::new(&self.file_name_input))),
+                    )
+                    .child(
+                        div()
+                            .text_xs()
+                            .text_color(cx.theme().muted_foreground)
+                            .child(t!("settings.general.file_names.tokens").to_string()),
+                    )
+                    .child(match &self.file_name_error {
+                        Some(error) => div()
+                            .text_xs()
+                            .text_color(cx.theme().danger)
+                            .child(pattern_error_message(error)),
+                        None => div()
+                            .text_xs()
+                            .text_color(cx.theme().muted_foreground)
+                            .child(file_name_preview),
                     }),
             )
-            // Render toast overlay at bottom center
-            .child(self.toast_manager.render())
-    }
-}
-
-impl Sukusho {
-    fn render_gallery(&self, has_more: bool, cx: &mut Context<Self>) -> impl IntoElement {
-        let search_enabled = self.models_downloaded;
-        let has_search_results = self.search_results.is_some();
-
-        v_flex()
-            .size_full()
-            // Search bar (only show if models are downloaded)
-            .when(search_enabled, |el| {
-                el.child(
-                    h_flex()
-                        .w_full()
-                        .px_4()
-                        .py_3()
-                        .bg(cx.theme().background)
-                        .border_b_1()
-                        .border_color(cx.theme().border)
-                        .child(
-                            h_flex()
-                                .w_full()
-                                .px_4()
-                                .gap_2()
-                                .items_center()
-                                .child(Input::new(&self.search_input).flex_1())
-                                .when(has_search_results, |el| {
-                                    el.child(
-                                        Button::new("clear-search")
-                                            .small()
-                                            .ghost()
-                                            .label(&t!("app.search.clear_button").to_string())
-                                            .on_click(cx.listener(|this, _, window, cx| {
-                                                this.search_input.update(cx, |input, cx| {
-                                                    input.set_value("", window, cx);
-                                                });
-                                                this.search_query.clear();
-                                                this.search_results = None;
-                                                cx.notify();
-                                            })),
-                                    )
-                                }),
-                        ),
-                )
-            })
-            // Gallery
-            .child(gallery(
-                self.visible_screenshots().to_vec(),
-                self.search_results.clone(),
-                self.selected.clone(),
-                Arc::clone(&self.thumbnail_cache),
-                self.grid_columns,
-                self.thumbnail_size,
-                has_more,
-                cx,
-            ))
-    }
-
-    fn render_settings(&mut self, cx: &mut Context<Self>) -> impl IntoElement {
-        let app_state = cx.global::<AppState>();
-        let settings = app_state.settings.lock().clone();
-        let current_page = self.settings_page;
-
-        // Pre-compute tab labels to avoid temporary value issues
-        let tab_general = t!("settings.tabs.general").to_string();
-        let tab_conversion = t!("settings.tabs.conversion").to_string();
-        let tab_indexing = t!("settings.tabs.indexing").to_string();
-        let tab_hotkey = t!("settings.tabs.hotkey").to_string();
-        let tab_about = t!("settings.tabs.about").to_string();
-
-        h_flex()
-            .size_full()
-            // Sidebar
+            // Files from other tools to leave alone
+            .child(self.render_section_header(&t!("settings.general.exclude.title").to_string(), cx))
             .child(
                 v_flex()
-                    .w(px(160.0))
-                    .min_w(px(160.0))
-                    .max_w(px(160.0))
-                    .h_full()
-                    .py_2()
-                    .px_2()
-                    .overflow_hidden()
-                    .border_r_1()
-                    .border_color(cx.theme().border)
-                    .bg(cx.theme().background)
-                    .child(self.render_settings_tab(
-                        &tab_general,
-                        SettingsPage::General,
-                        current_page,
+                    .w_full()
+                    .gap_2()
+                    .mb_4()
+                    .child(
+                        h_flex()
+                            .w_full()
+                            .gap_4()
+                            .justify_between()
+                            .items_center()
+                            .child(
+                                div()
+                                    .text_sm()
+                                    .font_weight(FontWeight::MEDIUM)
+                                    .text_color(cx.theme().foreground)
+                                    .child(t!("settings.general.exclude.label").to_string()),
+                            )
+                            .child(div().w(px(300.0)).child(Input::new(&self.exclude_input))),
+                    )
+                    .child(
+                        div()
+                            .text_xs()
+                            .text_color(cx.theme().muted_foreground)
+                            .child(t!("settings.general.exclude.desc").to_string()),
+                    ),
+            )
+            // Display Settings
+            .child(self.render_section_header(&t!("settings.general.appearance.title").to_string(), cx))
+            .child(
+                self.render_setting_row(
+                    &t!("settings.general.appearance.thumbnail_size_label").to_string(),
+                    Some(&t!("settings.general.appearance.thumbnail_size_desc").to_string()),
+                    self.settings_stepper(
+                        "thumb",
+                        true,
+                        stepper_value(
+                            60.0,
+                            t!("settings.general.appearance.thumbnail_size_value", size = thumbnail_size).to_string(),
+                            cx,
+                        ),
+                        |this, step, _, cx| {
+                            let new_size = (this.thumbnail_size as i32 + 10 * step).clamp(80, 300) as u32;
+                            this.thumbnail_size = new_size;
+                            {
+                                let app_state = cx.global::<AppState>();
+                                let mut settings = app_state.settings.lock();
+                                settings.thumbnail_size = new_size;
+                                let _ = settings.save();
+                            }
+                            cx.notify();
+                        },
                         cx,
-                    ))
-                    .child(self.render_settings_tab(
-                        &tab_conversion,
-                        SettingsPage::Conversion,
-                        current_page,
+                    ),
+                    cx,
+                ),
+            )
+            // Tile badge content
+            .child(
+                self.render_setting_row(
+                    &t!("settings.general.appearance.badge_label").to_string(),
+                    Some(&t!("settings.general.appearance.badge_desc").to_string()),
+                    h_flex()
+                        .gap_1()
+                        .children(
+                            [
+                                (BadgeContent::ExtensionSize, "badge-ext-size", t!("settings.general.appearance.badge_extension_size")),
+                                (BadgeContent::SizeOnly, "badge-size", t!("settings.general.appearance.badge_size_only")),
+                                (BadgeContent::FileName, "badge-name", t!("settings.general.appearance.badge_file_name")),
+                                (BadgeContent::None, "badge-none", t!("settings.general.appearance.badge_none")),
+                            ]
+                            .into_iter()
+                            .map(|(content, id, label)| {
+                                self.settings_button(
+                                    id,
+                                    true,
+                                    |b| {
+                                        b.small()
+                                            .when(badge_content == content, |s| s.primary())
+                                            .when(badge_content != content, |s| s.outline())
+                                            .label(&label.to_string())
+                                    },
+                                    move |this, _, cx| {
+                                        this.badge_content = content;
+                                        {
+                                            let app_state = cx.global::<AppState>();
+                                            let mut settings = app_state.settings.lock();
+                                            settings.badge_content = content;
+                                            let _ = settings.save();
+                                        }
+                                        cx.notify();
+                                    },
+                                    cx,
+                                )
+                            }),
+                        ),
+                    cx,
+                ),
+            )
+            // KB/MB like Explorer, or KiB/MiB
+            .child(
+                self.render_setting_row(
+                    &t!("settings.general.appearance.decimal_units_label").to_string(),
+                    Some(&t!("settings.general.appearance.decimal_units_desc").to_string()),
+                    self.settings_switch(
+                        "decimal-units",
+                        settings.decimal_size_units,
+                        true,
+                        |_this, checked, _, cx| {
+                            {
+                                let app_state = cx.global::<AppState>();
+                                let mut settings = app_state.settings.lock();
+                                settings.decimal_size_units = checked;
+                                let _ = settings.save();
+                            }
+                            crate::number_format::set_decimal_units(checked);
+                            cx.notify();
+                        },
                         cx,
-                    ))
-                    .child(self.render_settings_tab(
-                        &tab_indexing,
-                        SettingsPage::Indexing,
-                        current_page,
+                    ),
+                    cx,
+                ),
+            )
+            // Folder line under the preview
+            .child(
+                self.render_setting_row(
+                    &t!("settings.general.appearance.preview_folder_label").to_string(),
+                    Some(&t!("settings.general.appearance.preview_folder_desc").to_string()),
+                    self.settings_switch(
+                        "preview-folder",
+                        settings.preview_show_folder,
+                        true,
+                        |_this, checked, _, cx| {
+                            {
+                                let app_state = cx.global::<AppState>();
+                                let mut settings = app_state.settings.lock();
+                                settings.preview_show_folder = checked;
+                                let _ = settings.save();
+                            }
+                            cx.notify();
+                        },
                         cx,
-                    ))
-                    .child(self.render_settings_tab(
-                        &tab_hotkey,
-                        SettingsPage::Hotkey,
-                        current_page,
+                    ),
+                    cx,
+                ),
+            )
+            // Window Opacity slider
+            .child(
+                self.render_setting_row(
+                    &t!("settings.general.appearance.window_opacity_label").to_string(),
+                    Some(&t!("settings.general.appearance.window_opacity_desc").to_string()),
+                    self.settings_stepper(
+                        "opacity",
+                        true,
+                        stepper_value(
+                            60.0,
+                            t!("settings.general.appearance.window_opacity_value", opacity = (self.window_opacity * 100.0) as u32).to_string(),
+                            cx,
+                        ),
+                        |this, step, window, cx| {
+                            let new_opacity = (this.window_opacity + 0.05 * step as f32).clamp(0.3, 1.0);
+                            this.window_opacity = new_opacity;
+                            {
+                                let app_state = cx.global::<AppState>();
+                                let mut settings = app_state.settings.lock();
+                                settings.window_opacity = new_opacity;
+                                let _ = settings.save();
+                            }
+                            set_window_opacity(window, new_opacity);
+                            cx.notify();
+                        },
                         cx,
-                    ))
-                    .child(self.render_settings_tab(
-                        &tab_about,
-                        SettingsPage::About,
-                        current_page,
+                    ),
+                    cx,
+                ),
+            )
+            .child(
+                self.render_setting_row(
+                    &t!("settings.general.appearance.animations_label").to_string(),
+                    Some(&t!("settings.general.appearance.animations_desc").to_string()),
+                    self.settings_switch(
+                        "animations-enabled",
+                        settings.animations_enabled,
+                        true,
+                        |_this, checked, _, cx| {
+                            {
+                                let app_state = cx.global::<AppState>();
+                                let mut settings = app_state.settings.lock();
+                                settings.animations_enabled = checked;
+                                let _ = settings.save();
+                            }
+                            crate::motion::set_user_enabled(checked);
+                            cx.notify();
+                        },
                         cx,
-                    )),
+                    ),
+                    cx,
+                ),
             )
-            // Content area
+            // Window layouts
             .child(
-                div()
-                    .id("settings-content")
-                    .flex_1()
-                    .h_full()
-                    .overflow_scroll()
-                    .p_4()
-                    .child(match current_page {
-                        SettingsPage::General => self
-                            .render_general_settings(&settings, cx)
-                            .into_any_element(),
-                        SettingsPage::Conversion => self
-                            .render_conversion_settings(&settings, cx)
-                            .into_any_element(),
-                        SettingsPage::Indexing => self
-                            .render_indexing_settings(&settings, cx)
-                            .into_any_element(),
-                        SettingsPage::Hotkey => self
-                            .render_hotkey_settings(&settings, cx)
-                            .into_any_element(),
-                        SettingsPage::About => self.render_about_settings(cx).into_any_element(),
-                    }),
+                self.render_setting_row(
+                    &t!("settings.general.layouts.label").to_string(),
+                    Some(&t!("settings.general.layouts.desc", keys = self
+                        .shortcuts
+                        .shortcut(ShortcutAction::ToggleLayout)
+                        .map(|s| s.keys_label())
+                        .unwrap_or_default()).to_string()),
+                    h_flex()
+                        .gap_1()
+                        .children([LayoutSlot::A, LayoutSlot::B].map(|slot| {
+                            let saved = settings.layout(slot);
+                            self.settings_button(
+                                if slot == LayoutSlot::A { "save-layout-a" } else { "save-layout-b" },
+                                true,
+                                |b| {
+                                    b.small()
+                                        .when(settings.active_layout == Some(slot) && saved.is_some(), |s| s.primary())
+                                        .when(settings.active_layout != Some(slot) || saved.is_none(), |s| s.outline())
+                                        .label(&match saved {
+                                            Some(layout) => t!(
+                                                "settings.general.layouts.saved",
+                                                name = slot.label(),
+                                                width = layout.width,
+                                                height = layout.height
+                                            )
+                                            .to_string(),
+                                            None => t!("settings.general.layouts.save", name = slot.label()).to_string(),
+                                        })
+                                },
+                                move |this, _, cx| {
+                                    this.save_window_layout(slot, cx);
+                                },
+                                cx,
+                            )
+                        })),
+                    cx,
+                ),
             )
-    }
-
-    fn render_settings_tab(
-        &self,
-        label: &str,
-        page: SettingsPage,
-        current: SettingsPage,
-        cx: &mut Context<Self>,
-    ) -> impl IntoElement {
-        let is_active = page == current;
-        let tab_id = format!("tab-{}", label.to_lowercase());
-        div()
-            .id(SharedString::from(tab_id))
-            .w_full()
-            .px_3()
-            .py_2()
-            .cursor_pointer()
-            .text_sm()
-            .rounded(px(6.0))
-            .mb_1()
-            .when(is_active, |s| {
-                s.bg(cx.theme().primary)
-                    .text_color(cx.theme().primary_foreground)
-                    .font_weight(FontWeight::MEDIUM)
-            })
-            .when(!is_active, |s| {
-                s.text_color(cx.theme().foreground)
-                    .hover(|s| s.bg(cx.theme().muted))
-            })
-            .on_click(cx.listener(move |this, _, _, cx| {
-                this.settings_page = page;
-                cx.notify();
-            }))
-            .child(label.to_string())
-    }
-
-    fn render_setting_row(
-        &self,
-        label: &str,
-        description: Option<&str>,
-        control: impl IntoElement,
-        cx: &Context<Self>,
-    ) -> impl IntoElement {
-        v_flex()
-            .w_full()
-            .gap_1()
-            .mb_4()
+            // What Escape does last
             .child(
-                h_flex()
-                    .w_full()
-                    .justify_between()
-                    .items_center()
-                    .child(
-                        div()
-                            .text_sm()
-                            .font_weight(FontWeight::MEDIUM)
-                            .text_color(cx.theme().foreground)
-                            .child(label.to_string()),
-                    )
-                    .child(control),
+                self.render_setting_row(
+                    &t!("settings.general.escape.label").to_string(),
+                    Some(&t!("settings.general.escape.desc").to_string()),
+                    h_flex()
+                        .gap_1()
+                        .children(
+                            [
+                                (EscapeAction::Hide, "escape-hide", t!("settings.general.escape.hide")),
+                                (EscapeAction::Minimize, "escape-minimize", t!("settings.general.escape.minimize")),
+                                (EscapeAction::None, "escape-none", t!("settings.general.escape.none")),
+                            ]
+                            .into_iter()
+                            .map(|(action, id, label)| {
+                                self.settings_button(
+                                    id,
+                                    true,
+                                    |b| {
+                                        b.small()
+                                            .when(settings.escape_action == action, |s| s.primary())
+                                            .when(settings.escape_action != action, |s| s.outline())
+                                            .label(&label.to_string())
+                                    },
+                                    move |_this, _, cx| {
+                                        {
+                                            let app_state = cx.global::<AppState>();
+                                            let mut settings = app_state.settings.lock();
+                                            settings.escape_action = action;
+                                            let _ = settings.save();
+                                        }
+                                        cx.notify();
+                                    },
+                                    cx,
+                                )
+                            }),
+                        ),
+                    cx,
+                ),
+            )
+            // Read-only files
+            .child(self.render_section_header(&t!("settings.general.read_only.title").to_string(), cx))
+            .child(
+                self.render_setting_row(
+                    &t!("settings.general.read_only.label").to_string(),
+                    Some(&t!("settings.general.read_only.desc").to_string()),
+                    self.settings_switch(
+                        "handle-read-only",
+                        settings.handle_read_only_files,
+                        true,
+                        |_this, checked, _, cx| {
+                            {
+                                let app_state = cx.global::<AppState>();
+                                let mut settings = app_state.settings.lock();
+                                settings.handle_read_only_files = checked;
+                                let _ = settings.save();
+                            }
+                            file_ops::set_clear_read_only(checked);
+                            cx.notify();
+                        },
+                        cx,
+                    ),
+                    cx,
+                ),
+            )
+            // Advanced page visibility
+            .child(self.render_section_header(&t!("settings.general.advanced.title").to_string(), cx))
+            .child(
+                self.render_setting_row(
+                    &t!("settings.general.advanced.show_label").to_string(),
+                    Some(&t!("settings.general.advanced.show_desc").to_string()),
+                    self.settings_switch(
+                        "show-advanced",
+                        settings.show_advanced_settings,
+                        true,
+                        |_this, checked, _, cx| {
+                            {
+                                let app_state = cx.global::<AppState>();
+                                let mut settings = app_state.settings.lock();
+                                settings.show_advanced_settings = checked;
+                                let _ = settings.save();
+                            }
+                            cx.notify();
+                        },
+                        cx,
+                    ),
+                    cx,
+                ),
             )
-            .when_some(description, |s, desc| {
-                s.child(
-                    div()
-                        .text_xs()
-                        .text_color(cx.theme().muted_foreground)
-                        .child(desc.to_string()),
-                )
-            })
-    }
-
-    fn render_section_header(&self, title: &str, cx: &Context<Self>) -> impl IntoElement {
-        div()
-            .text_base()
-            .font_weight(FontWeight::SEMIBOLD)
-            .text_color(cx.theme().foreground)
-            .mb_3()
-            .child(title.to_string())
     }
 
-    fn render_general_settings(
+    fn render_conversion_settings(
         &self,
         settings: &crate::settings::Settings,
         cx: &mut Context<Self>,
     ) -> impl IntoElement {
-        let screenshot_dir = settings.screenshot_directory.to_string_lossy().to_string();
-        let screenshot_dir_path = settings.screenshot_directory.clone();
-        let thumbnail_size = self.thumbnail_size;
-        let organizer_enabled = settings.organizer_enabled;
-        let organizer_format = settings.organizer_format.clone();
-        let format_preview = organizer::format_preview(&organizer_format);
-        let organizing = self.organizing;
-        let organize_progress = self.organize_progress;
-        let organize_current_file = self.organize_current_file.clone();
-
-        // Pre-compute strings to avoid temporary value issues
-        let language_title = t!("settings.general.language.title").to_string();
-        let language_label = t!("settings.general.language.label").to_string();
-        let language_desc = t!("settings.general.language.desc").to_string();
-        let screenshot_dir_title = t!("settings.general.screenshot_dir.title").to_string();
-        let browse_label = t!("common.button.browse").to_string();
-        let organizer_title = t!("settings.general.organizer.title").to_string();
-        let organizer_enable_label = t!("settings.general.organizer.enable_label").to_string();
-        let organizer_enable_desc = t!("settings.general.organizer.enable_desc").to_string();
-
-        // Get current language
-        let current_lang = crate::i18n_helpers::current_language();
+        let auto_convert = settings.auto_convert_webp;
+        let format = settings.conversion_format;
+        let quality = settings.quality_for(format);
+        let lossless_webp = settings.webp_lossless;
+        // Lossless WebP has no quality to set
+        let quality_fixed = format == ConversionFormat::WebP && lossless_webp;
+        let max_dimension = settings.conversion_max_dimension;
+        let min_size_kb = settings.convert_min_size_kb;
+        let converting = self.converting;
+        let convert_progress = self.convert_progress;
+        let estimating = self.estimating_savings;
+        // An estimate made at another format or quality no longer applies
+        let savings_desc = match self.savings_estimate {
+            _ if estimating => {
+                t!("settings.conversion.estimate.sampling", count = convert::ESTIMATE_SAMPLE).to_string()
+            }
+            Some(estimate)
+                if estimate.format == format
+                    && estimate.quality == quality
+                    && (format != ConversionFormat::WebP || estimate.lossless_webp == lossless_webp) =>
+            {
+                if estimate.sampled == 0 {
+                    t!("settings.conversion.estimate.no_pngs").to_string()
+                } else if estimate.saved_fraction() <= 0.0 {
+                    t!("settings.conversion.estimate.no_savings", format = format.display_name()).to_string()
+                } else {
+                    t!(
+                        &plural_key("settings.conversion.estimate.result", estimate.pngs),
+                        percent = format!("{:.0}", estimate.saved_fraction() * 100.0),
+                        size = format_file_size(estimate.projected_savings()),
+                        count = format_count(estimate.pngs),
+                        before = format_file_size(estimate.sampled_bytes),
+                        after = format_file_size(estimate.encoded_bytes),
+                        sampled = estimate.sampled
+                    )
+                    .to_string()
+                }
+            }
+            _ => t!("settings.conversion.estimate.desc", count = convert::ESTIMATE_SAMPLE).to_string(),
+        };
 
         v_flex()
             .w_full()
             .gap_2()
-            // Startup
-            .child(self.render_section_header(&t!("settings.general.startup.title").to_string(), cx))
+            .child(self.render_section_header(&t!("settings.conversion.auto_convert.title").to_string(), cx))
+            // Auto-convert toggle
+            .child(
+                self.render_setting_row(
+                    &t!("settings.conversion.auto_convert.enable_label").to_string(),
+                    Some(&t!("settings.conversion.auto_convert.enable_desc").to_string()),
+                    self.settings_switch(
+                        "auto-convert",
+                        auto_convert,
+                        true,
+                        |_this, checked, _, cx| {
+                            {
+                                let app_state = cx.global::<AppState>();
+                                let mut settings = app_state.settings.lock();
+                                settings.auto_convert_webp = checked;
+                                let _ = settings.save();
+                            }
+                            cx.notify();
+                        },
+                        cx,
+                    ),
+                    cx,
+                ),
+            )
+            // Format selection
+            .child(
+                self.render_setting_row(
+                    &t!("settings.conversion.format.label").to_string(),
+                    Some(&t!("settings.conversion.format.desc").to_string()),
+                    h_flex()
+                        .gap_1()
+                        .child(
+                            self.settings_button(
+                                "fmt-webp",
+                                true,
+                                |b| {
+                                    b.small()
+                                        .when(format == ConversionFormat::WebP, |s| s.primary())
+                                        .when(format != ConversionFormat::WebP, |s| s.outline())
+                                        .label(&t!("settings.conversion.format.webp").to_string())
+                                },
+                                |_this, _, cx| {
+                                    {
+                                        let app_state = cx.global::<AppState>();
+                                        let mut settings = app_state.settings.lock();
+                                        settings.conversion_format = ConversionFormat::WebP;
+                                        let _ = settings.save();
+                                    }
+                                    cx.notify();
+                                },
+                                cx,
+                            ),
+                        )
+                        .child(
+                            self.settings_button(
+                                "fmt-jpeg",
+                                true,
+                                |b| {
+                                    b.small()
+                                        .when(format == ConversionFormat::Jpeg, |s| s.primary())
+                                        .when(format != ConversionFormat::Jpeg, |s| s.outline())
+                                        .label(&t!("settings.conversion.format.jpeg").to_string())
+                                },
+                                |_this, _, cx| {
+                                    {
+                                        let app_state = cx.global::<AppState>();
+                                        let mut settings = app_state.settings.lock();
+                                        settings.conversion_format = ConversionFormat::Jpeg;
+                                        let _ = settings.save();
+                                    }
+                                    cx.notify();
+                                },
+                                cx,
+                            ),
+                        )
+                        .child(
+                            self.settings_button(
+                                "fmt-avif",
+                                true,
+                                |b| {
+                                    b.small()
+                                        .when(format == ConversionFormat::Avif, |s| s.primary())
+                                        .when(format != ConversionFormat::Avif, |s| s.outline())
+                                        .label(&t!("settings.conversion.format.avif").to_string())
+                                },
+                                |_this, _, cx| {
+                                    {
+                                        let app_state = cx.global::<AppState>();
+                                        let mut settings = app_state.settings.lock();
+                                        settings.conversion_format = ConversionFormat::Avif;
+                                        let _ = settings.save();
+                                    }
+                                    cx.notify();
+                                },
+                                cx,
+                            ),
+                        ),
+                    cx,
+                ),
+            )
+            // WebP: lossless, or lossy at the quality below
+            .when(format == ConversionFormat::WebP, |el| {
+                el.child(self.render_setting_row(
+                    &t!("settings.conversion.lossless_webp.label").to_string(),
+                    Some(&t!("settings.conversion.lossless_webp.desc").to_string()),
+                    self.settings_switch(
+                        "lossless-webp",
+                        lossless_webp,
+                        true,
+                        |_this, checked, _, cx| {
+                            {
+                                let app_state = cx.global::<AppState>();
+                                let mut settings = app_state.settings.lock();
+                                settings.webp_lossless = checked;
+                                let _ = settings.save();
+                            }
+                            cx.notify();
+                        },
+                        cx,
+                    ),
+                    cx,
+                ))
+            })
+            // Quality of the selected format (kept but unused for lossless WebP)
             .child(
                 self.render_setting_row(
-                    &t!("settings.general.startup.run_on_startup_label").to_string(),
-                    Some(&t!("settings.general.startup.run_on_startup_desc").to_string()),
-                    Switch::new("run-on-startup")
-                        .checked(settings.run_on_startup)
-                        .on_click(cx.listener(move |_this, checked, _, cx| {
-                            let checked = *checked;
+                    &t!("settings.conversion.quality.label", format = format.display_name())
+                        .to_string(),
+                    Some(&t!("settings.conversion.quality.desc").to_string()),
+                    self.settings_stepper(
+                        "qual",
+                        !quality_fixed,
+                        stepper_value(50.0, format!("{}", quality), cx)
+                            .when(quality_fixed, |s| s.opacity(0.5)),
+                        |_this, step, _, cx| {
                             {
                                 let app_state = cx.global::<AppState>();
                                 let mut settings = app_state.settings.lock();
-                                settings.run_on_startup = checked;
+                                let format = settings.conversion_format;
+                                let quality = settings.quality_for_mut(format);
+                                *quality = if step < 0 {
+                                    quality.saturating_sub(5).max(1)
+                                } else {
+                                    (*quality + 5).min(100)
+                                };
                                 let _ = settings.save();
                             }
-                            // Register/unregister from Windows startup
-                            if checked {
-                                register_startup();
+                            cx.notify();
+                        },
+                        cx,
+                    ),
+                    cx,
+                ),
+            )
+            // Downscale very large screenshots before encoding
+            .child(
+                self.render_setting_row(
+                    &t!("settings.conversion.max_dimension.label").to_string(),
+                    Some(&t!("settings.conversion.max_dimension.desc").to_string()),
+                    self.settings_stepper(
+                        "max-dimension",
+                        true,
+                        stepper_value(
+                            80.0,
+                            if max_dimension == 0 {
+                                t!("settings.conversion.max_dimension.no_limit").to_string()
                             } else {
-                                unregister_startup();
+                                t!("settings.conversion.max_dimension.value", pixels = max_dimension).to_string()
+                            },
+                            cx,
+                        ),
+                        |_this, step, _, cx| {
+                            {
+                                let app_state = cx.global::<AppState>();
+                                let mut settings = app_state.settings.lock();
+                                settings.conversion_max_dimension =
+                                    convert::step_max_dimension(settings.conversion_max_dimension, step > 0);
+                                let _ = settings.save();
                             }
                             cx.notify();
-                        })),
+                        },
+                        cx,
+                    ),
                     cx,
                 ),
             )
+            // Leave small PNGs alone when auto-converting
             .child(
                 self.render_setting_row(
-                    &t!("settings.general.startup.hide_window_on_start_label").to_string(),
-                    Some(&t!("settings.general.startup.hide_window_on_start_desc").to_string()),
-                    Switch::new("hide-on-start")
-                        .checked(settings.hide_window_on_start)
-                        .on_click(cx.listener(move |_this, checked, _, cx| {
-                            let checked = *checked;
+                    &t!("settings.conversion.min_size.label").to_string(),
+                    Some(&t!("settings.conversion.min_size.desc").to_string()),
+                    self.settings_stepper(
+                        "min-size",
+                        auto_convert,
+                        stepper_value(
+                            80.0,
+                            if min_size_kb == 0 {
+                                t!("settings.conversion.min_size.no_limit").to_string()
+                            } else {
+                                t!("settings.conversion.min_size.value", kb = min_size_kb).to_string()
+                            },
+                            cx,
+                        ),
+                        |_this, step, _, cx| {
                             {
                                 let app_state = cx.global::<AppState>();
                                 let mut settings = app_state.settings.lock();
-                                settings.hide_window_on_start = checked;
+                                settings.convert_min_size_kb =
+                                    convert::step_min_size_kb(settings.convert_min_size_kb, step > 0);
                                 let _ = settings.save();
                             }
                             cx.notify();
-                        })),
+                        },
+                        cx,
+                    ),
                     cx,
                 ),
             )
-            // Language
-            .child(self.render_section_header(&language_title, cx))
+            // What auto-convert would save, from PNGs converted in memory
             .child(
                 self.render_setting_row(
-                    &language_label,
-                    Some(&language_desc),
+                    &t!("settings.conversion.estimate.label").to_string(),
+                    Some(&savings_desc),
                     h_flex()
-                        .gap_1()
+                        .gap_2()
+                        .items_center()
+                        .when(estimating, |el| el.child(Spinner::new().small()))
                         .child(
-                            Button::new("lang-en")
-                                .small()
-                                .when(current_lang == "en", |b| b.primary())
-                                .when(current_lang != "en", |b| b.outline())
-                                .label("English")
-                                .on_click(cx.listener(|_this, _, _, cx| {
-                                    crate::i18n_helpers::change_language("en");
-                                    {
-                                        let app_state = cx.global::<AppState>();
-                                        let mut settings = app_state.settings.lock();
-                                        settings.language = Some("en".to_string());
-                                        let _ = settings.save();
-                                    }
-                                    cx.notify();
-                                }))
-                        )
+                            self.settings_button(
+                                "estimate-savings",
+                                !estimating,
+                                |b| {
+                                    b.small()
+                                        .outline()
+                                        .label(&t!("settings.conversion.estimate.button").to_string())
+                                },
+                                |this, _, cx| {
+                                    this.estimate_savings(cx);
+                                },
+                                cx,
+                            ),
+                        ),
+                    cx,
+                ),
+            )
+            // Convert every PNG already in the library
+            .child(
+                self.render_setting_row(
+                    &t!("settings.conversion.convert_existing.label").to_string(),
+                    Some(&t!("settings.conversion.convert_existing.desc").to_string()),
+                    self.settings_button(
+                        "convert-existing",
+                        !converting,
+                        |b| {
+                            b.small()
+                                .outline()
+                                .label(&t!("settings.conversion.convert_existing.button").to_string())
+                        },
+                        |this, _, cx| {
+                            this.convert_library(cx);
+                        },
+                        cx,
+                    ),
+                    cx,
+                ),
+            )
+            // Progress bar when converting
+            .when(converting, |el| {
+                let (current, total) = convert_progress;
+                let progress_pct = if total > 0 {
+                    (current as f32 / total as f32) * 100.0
+                } else {
+                    0.0
+                };
+                el.child(
+                    v_flex()
+                        .w_full()
+                        .gap_2()
+                        .mb_4()
                         .child(
-                            Button::new("lang-ko")
-                                .small()
-                                .when(current_lang == "ko", |b| b.primary())
-                                .when(current_lang != "ko", |b| b.outline())
-                                .label("한국어")
-                                .on_click(cx.listener(|_this, _, _, cx| {
-                                    crate::i18n_helpers::change_language("ko");
-                                    {
-                                        let app_state = cx.global::<AppState>();
-                                        let mut settings = app_state.settings.lock();
-                                        settings.language = Some("ko".to_string());
-                                        let _ = settings.save();
-                                    }
-                                    cx.notify();
-                                }))
+                            // Progress bar container
+                            div()
+                                .w_full()
+                                .h(px(8.0))
+                                .rounded(px(4.0))
+                                .bg(cx.theme().muted)
+                                .overflow_hidden()
+                                .child(
+                                    div()
+                                        .h_full()
+                                        .w(relative(progress_pct / 100.0))
+                                        .bg(cx.theme().primary)
+                                        .rounded(px(4.0)),
+                                ),
                         )
                         .child(
-                            Button::new("lang-ja")
-                                .small()
-                                .when(current_lang == "ja", |b| b.primary())
-                                .when(current_lang != "ja", |b| b.outline())
-                                .label("日本語")
-                                .on_click(cx.listener(|_this, _, _, cx| {
-                                    crate::i18n_helpers::change_language("ja");
-                                    {
-                                        let app_state = cx.global::<AppState>();
-                                        let mut settings = app_state.settings.lock();
-                                        settings.language = Some("ja".to_string());
-                                        let _ = settings.save();
-                                    }
-                                    cx.notify();
-                                })),
+                            h_flex()
+                                .w_full()
+                                .justify_between()
+                                .child(render_current_file(
+                                    "convert-current-file",
+                                    &self.convert_current_file,
+                                    t!("settings.conversion.progress.preparing").to_string(),
+                                    cx,
+                                ))
+                                .child(
+                                    h_flex()
+                                        .gap_2()
+                                        .items_center()
+                                        .child(
+                                            div()
+                                                .text_xs()
+                                                .text_color(cx.theme().muted_foreground)
+                                                .child(
+                                                    t!(
+                                                        &plural_key("settings.conversion.progress.status", total),
+                                                        current = current,
+                                                        total = total
+                                                    )
+                                                    .to_string(),
+                                                ),
+                                        )
+                                        .child(
+                                            self.settings_button(
+                                                "convert-cancel",
+                                                true,
+                                                |b| {
+                                                    b.xsmall()
+                                                        .ghost()
+                                                        .label(&t!("common.button.cancel").to_string())
+                                                },
+                                                |this, _, cx| {
+                                                    this.convert_cancel.store(true, Ordering::Relaxed);
+                                                    cx.notify();
+                                                },
+                                                cx,
+                                            ),
+                                        ),
+                                ),
+                        ),
+                )
+            })
+    }
+
+    fn render_indexing_settings(
+        &self,
+        settings: &crate::settings::Settings,
+        cx: &mut Context<Self>,
+    ) -> impl IntoElement {
+        let indexing_enabled = settings.indexing_enabled;
+        let cpu_mode = settings.indexing_cpu_mode.clone();
+        let model_prewarm = settings.model_prewarm;
+        let indexed_count = settings.last_indexed_count;
+        let similar_threshold = settings.similar_threshold;
+        let search_min_similarity = settings.search_min_similarity;
+
+        // Pre-compute strings to avoid temporary value issues
+        let indexing_title = t!("settings.indexing.title").to_string();
+        let indexing_enable_label = t!("settings.indexing.enable_label").to_string();
+        let indexing_enable_desc = t!("settings.indexing.enable_desc").to_string();
+
+        v_flex()
+            .w_full()
+            .gap_2()
+            // Enable Image Indexing toggle
+            .child(self.render_section_header(&indexing_title, cx))
+            .child(
+                self.render_setting_row(
+                    &indexing_enable_label,
+                    if self.downloading_models || self.indexing {
+                        None
+                    } else {
+                        Some(&indexing_enable_desc)
+                    },
+                    self.settings_switch(
+                        "indexing-enable",
+                        indexing_enabled,
+                        !(self.downloading_models || self.indexing),
+                        |this, checked, _, cx| {
+                            {
+                                let app_state = cx.global::<AppState>();
+                                let mut settings = app_state.settings.lock();
+                                settings.indexing_enabled = checked;
+                                let _ = settings.save();
+                            }
+                            // If enabling and models not downloaded, trigger download
+                            if checked && !this.models_downloaded {
+                                Self::start_incremental_index(cx);
+                            }
+                            this.schedule_index_run(cx);
+                            cx.notify();
+                        },
+                        cx,
+                    ),
+                    cx,
+                ),
+            )
+            // Model download status (always show if downloading or downloaded)
+            .when(self.downloading_models || self.models_downloaded, |el| {
+                if self.downloading_models {
+                    let (current, total) = self.model_download_progress;
+                    // Extract model info from the third parameter which now contains detailed description
+                    let progress_pct = if total > 0 {
+                        (current as f32 / total as f32) * 100.0
+                    } else {
+                        0.0
+                    };
+                    el.child(
+                        v_flex()
+                            .w_full()
+                            .gap_2()
+                            .mb_4()
+                            .child(
+                                div()
+                                    .text_xs()
+                                    .font_weight(FontWeight::MEDIUM)
+                                    .text_color(cx.theme().foreground)
+                                    .child(t!("settings.indexing.model_status.title").to_string()),
+                            )
+                            .child(
+                                div()
+                                    .text_xs()
+                                    .text_color(cx.theme().muted_foreground)
+                                    .child(t!("settings.indexing.model_status.loading", current = current, total = total).to_string()),
+                            )
+                            .child(
+                                div()
+                                    .w_full()
+                                    .h(px(6.0))
+                                    .rounded(px(3.0))
+                                    .bg(cx.theme().muted)
+                                    .overflow_hidden()
+                                    .child(
+                                        div()
+                                            .h_full()
+                                            .w(relative(progress_pct / 100.0))
+                                            .bg(cx.theme().primary)
+                                            .rounded(px(3.0)),
+                                    ),
+                            )
+                            .child(
+                                div()
+                                    .text_xs()
+                                    .text_color(cx.theme().muted_foreground)
+                                    .child(t!("settings.indexing.model_status.loading_percent", percent = progress_pct as u32).to_string()),
+                            )
+                    )
+                } else {
+                    el.child(
+                        v_flex()
+                            .w_full()
+                            .gap_1()
+                            .mb_4()
+                            .child(
+                                div()
+                                    .text_xs()
+                                    .font_weight(FontWeight::MEDIUM)
+                                    .text_color(cx.theme().foreground)
+                                    .child(t!("settings.indexing.model_status.title").to_string()),
+                            )
+                            .child(
+                                div()
+                                    .text_xs()
+                                    .text_color(cx.theme().muted_foreground)
+                                    .child(if MODELS_LOADED.load(Ordering::Acquire) {
+                                        t!("settings.indexing.model_status.loaded").to_string()
+                                    } else {
+                                        t!("settings.indexing.model_status.not_loaded").to_string()
+                                    }),
+                            )
+                    )
+                }
+            })
+            // CPU Mode selection (always show, but disable when off or busy)
+            .child(self.render_section_header(&t!("settings.indexing.settings_title").to_string(), cx))
+            .child(
+                self.render_setting_row(
+                    &t!("settings.indexing.model_prewarm.label").to_string(),
+                    Some(&t!("settings.indexing.model_prewarm.desc").to_string()),
+                    h_flex()
+                        .gap_2()
+                        .children(
+                            [
+                                (ModelPrewarm::OnStartup, "prewarm-startup", t!("settings.indexing.model_prewarm.on_startup")),
+                                (ModelPrewarm::OnFirstUse, "prewarm-first-use", t!("settings.indexing.model_prewarm.on_first_use")),
+                                (ModelPrewarm::Never, "prewarm-never", t!("settings.indexing.model_prewarm.never")),
+                            ]
+                            .into_iter()
+                            .map(|(mode, id, label)| {
+                                self.settings_button(
+                                    id,
+                                    indexing_enabled,
+                                    |b| {
+                                        b.small()
+                                            .when(model_prewarm == mode, |s| s.primary())
+                                            .when(model_prewarm != mode, |s| s.outline())
+                                            .label(&label.to_string())
+                                    },
+                                    move |this, _, cx| {
+                                        {
+                                            let app_state = cx.global::<AppState>();
+                                            let mut settings = app_state.settings.lock();
+                                            settings.model_prewarm = mode;
+                                            let _ = settings.save();
+                                        }
+                                        this.schedule_index_run(cx);
+                                        // Release resident models (running jobs keep their own handles)
+                                        if mode == ModelPrewarm::Never {
+                                            *PREWARMED_VISION_MODEL.lock() = None;
+                                            *PREWARMED_TEXT_MODEL.lock() = None;
+                                            update_models_loaded();
+                                        }
+                                        cx.notify();
+                                    },
+                                    cx,
+                                )
+                            }),
                         ),
                     cx,
                 )
             )
-            // Theme
-            .child(self.render_section_header(&t!("settings.general.appearance.theme_label").to_string(), cx))
             .child(
                 self.render_setting_row(
-                    &t!("settings.general.appearance.theme_label").to_string(),
-                    Some(&t!("settings.general.appearance.theme_desc").to_string()),
+                    &t!("settings.indexing.cpu_mode.label").to_string(),
+                    Some(&t!("settings.indexing.cpu_mode.desc").to_string()),
                     h_flex()
-                        .gap_1()
-                        .child(
-                            Button::new("theme-dark")
-                                .small()
-                                .when(settings.theme == crate::settings::ThemeMode::Dark, |b| b.primary())
-                                .when(settings.theme != crate::settings::ThemeMode::Dark, |b| b.outline())
-                                .label(t!("settings.general.appearance.theme_dark").to_string())
-                                .on_click(cx.listener(|_this, _, window, cx| {
-                                    {
-                                        let app_state = cx.global::<AppState>();
-                                        let mut settings = app_state.settings.lock();
-                                        settings.theme = crate::settings::ThemeMode::Dark;
-                                        let _ = settings.save();
-                                    }
-                                    apply_theme(crate::settings::ThemeMode::Dark, window, cx);
-                                    cx.notify();
-                                }))
-                        )
+                        .gap_2()
                         .child(
-                            Button::new("theme-light")
-                                .small()
-                                .when(settings.theme == crate::settings::ThemeMode::Light, |b| b.primary())
-                                .when(settings.theme != crate::settings::ThemeMode::Light, |b| b.outline())
-                                .label(t!("settings.general.appearance.theme_light").to_string())
-                                .on_click(cx.listener(|_this, _, window, cx| {
+                            self.settings_button(
+                                "cpu-normal",
+                                !(!indexing_enabled || self.downloading_models || self.indexing),
+                                |b| {
+                                    b.small()
+                                        .when(cpu_mode == "normal", |s| s.primary())
+                                        .when(cpu_mode != "normal", |s| s.outline())
+                                        .label(&t!("settings.indexing.cpu_mode.normal").to_string())
+                                },
+                                |_this, _, cx| {
                                     {
                                         let app_state = cx.global::<AppState>();
                                         let mut settings = app_state.settings.lock();
-                                        settings.theme = crate::settings::ThemeMode::Light;
+                                        settings.indexing_cpu_mode = "normal".to_string();
                                         let _ = settings.save();
                                     }
-                                    apply_theme(crate::settings::ThemeMode::Light, window, cx);
                                     cx.notify();
-                                }))
+                                },
+                                cx,
+                            ),
                         )
                         .child(
-                            Button::new("theme-system")
-                                .small()
-                                .when(settings.theme == crate::settings::ThemeMode::System, |b| b.primary())
-                                .when(settings.theme != crate::settings::ThemeMode::System, |b| b.outline())
-                                .label(t!("settings.general.appearance.theme_system").to_string())
-                                .on_click(cx.listener(|_this, _, window, cx| {
+                            self.settings_button(
+                                "cpu-fast",
+                                !(!indexing_enabled || self.downloading_models || self.indexing),
+                                |b| {
+                                    b.small()
+                                        .when(cpu_mode == "fast", |s| s.primary())
+                                        .when(cpu_mode != "fast", |s| s.outline())
+                                        .label(&t!("settings.indexing.cpu_mode.fast").to_string())
+                                },
+                                |_this, _, cx| {
                                     {
                                         let app_state = cx.global::<AppState>();
                                         let mut settings = app_state.settings.lock();
-                                        settings.theme = crate::settings::ThemeMode::System;
+                                        settings.indexing_cpu_mode = "fast".to_string();
                                         let _ = settings.save();
                                     }
-                                    apply_theme(crate::settings::ThemeMode::System, window, cx);
                                     cx.notify();
-                                })),
+                                },
+                                cx,
+                            ),
                         ),
                     cx,
                 )
             )
-            // Screenshot Directory
-            .child(self.render_section_header(&screenshot_dir_title, cx))
+            // How alike "Select Similar" matches must be
             .child(
-                h_flex()
-                    .w_full()
-                    .gap_2()
-                    .items_center()
-                    .mb_4()
-                    .child(
-                        div()
-                            .flex_1()
-                            .px_3()
-                            .py_2()
-                            .rounded(px(6.0))
-                            .bg(cx.theme().muted)
-                            .text_sm()
-                            .text_color(cx.theme().muted_foreground)
-                            .overflow_x_hidden()
-                            .child(screenshot_dir),
-                    )
-                    .child(
-                        Button::new("browse-dir")
-                            .label(&browse_label)
-                            .small()
-                            .outline()
-                            .on_click(|_, _, cx| {
-                                let tx = {
-                                    let app_state = cx.global::<AppState>();
-                                    app_state.message_tx.clone()
+                self.render_setting_row(
+                    &t!("settings.indexing.similar.threshold_label").to_string(),
+                    Some(&t!("settings.indexing.similar.threshold_desc").to_string()),
+                    self.settings_stepper(
+                        "similar-threshold",
+                        true,
+                        stepper_value(
+                            70.0,
+                            t!("settings.indexing.similar.threshold_value", percent = similar_threshold).to_string(),
+                            cx,
+                        ),
+                        |_this, step, _, cx| {
+                            {
+                                let app_state = cx.global::<AppState>();
+                                let mut settings = app_state.settings.lock();
+                                settings.similar_threshold = if step < 0 {
+                                    settings.similar_threshold.saturating_sub(1).max(80)
+                                } else {
+                                    (settings.similar_threshold + 1).min(99)
                                 };
-                                std::thread::spawn(move || {
-                                    if let Some(path) = pick_folder() {
-                                        let _ = tx.send(AppMessage::ChangeDirectory(path));
-                                    }
-                                });
-                            }),
+                                let _ = settings.save();
+                            }
+                            cx.notify();
+                        },
+                        cx,
                     ),
+                    cx,
+                ),
             )
-            // Screenshot Organizer
-            .child(self.render_section_header(&organizer_title, cx))
+            // How relevant search results must be
             .child(
                 self.render_setting_row(
-                    &organizer_enable_label,
-                    if organizing {
-                        None
-                    } else {
-                        Some(&organizer_enable_desc)
-                    },
-                    Switch::new("organizer-enable")
-                        .checked(organizer_enabled)
-                        .disabled(organizing)
-                        .on_click({
-                            let format = organizer_format.clone();
-                            let base_dir = screenshot_dir_path.clone();
-                            cx.listener(move |this, checked: &bool, _, cx| {
-                                {
-                                    let app_state = cx.global::<AppState>();
-                                    let mut settings = app_state.settings.lock();
-                                    settings.organizer_enabled = *checked;
-                                    let _ = settings.save();
-                                }
-                                // If enabling, organize existing files
-                                if *checked && !this.organizing {
-                                    let tx = {
-                                        let app_state = cx.global::<AppState>();
-                                        app_state.message_tx.clone()
-                                    };
-                                    organizer::organize_existing_files(
-                                        base_dir.clone(),
-                                        format.clone(),
-                                        tx,
-                                    );
-                                }
-                                cx.notify();
-                            })
-                        }),
+                    &t!("settings.indexing.search_cutoff.label").to_string(),
+                    Some(&t!("settings.indexing.search_cutoff.desc").to_string()),
+                    self.settings_stepper(
+                        "search-cutoff",
+                        true,
+                        stepper_value(
+                            70.0,
+                            if search_min_similarity == 0 {
+                                t!("settings.indexing.search_cutoff.value_off").to_string()
+                            } else {
+                                t!("settings.indexing.search_cutoff.value", percent = search_min_similarity).to_string()
+                            },
+                            cx,
+                        ),
+                        |this, step, _, cx| {
+                            {
+                                let app_state = cx.global::<AppState>();
+                                let mut settings = app_state.settings.lock();
+                                settings.search_min_similarity = if step < 0 {
+                                    settings.search_min_similarity.saturating_sub(1)
+                                } else {
+                                    (settings.search_min_similarity + 1).min(50)
+                                };
+                                let _ = settings.save();
+                            }
+                            // The results waiting behind the settings follow the new cutoff
+                            if this.search_results.is_some()
+                                && this.similar_to.is_none()
+                                && !this.search_query.is_empty()
+                            {
+                                this.start_search(this.search_query.clone(), cx);
+                            }
+                            cx.notify();
+                        },
+                        cx,
+                    ),
                     cx,
                 ),
             )
-            // Progress bar when organizing
-            .when(organizing, |el| {
-                let (current, total) = organize_progress;
+            // Indexing progress
+            .when(self.indexing, |el| {
+                let (current, total) = self.index_progress;
                 let progress_pct = if total > 0 {
                     (current as f32 / total as f32) * 100.0
                 } else {
                     0.0
                 };
-                el.child(
-                    v_flex()
-                        .w_full()
+                el.child(self.render_section_header(&t!("settings.indexing.progress.title").to_string(), cx))
+                    .child(
+                        v_flex()
+                            .w_full()
+                            .gap_2()
+                            .mb_4()
+                            .child(
+                                div()
+                                    .w_full()
+                                    .h(px(8.0))
+                                    .rounded(px(4.0))
+                                    .bg(cx.theme().muted)
+                                    .overflow_hidden()
+                                    .child(
+                                        div()
+                                            .h_full()
+                                            .w(relative(progress_pct / 100.0))
+                                            .bg(cx.theme().primary)
+                                            .rounded(px(4.0)),
+                                    ),
+                            )
+                            .child(
+                                h_flex()
+                                    .w_full()
+                                    .justify_between()
+                                    .child(render_current_file(
+                                        "index-current-file",
+                                        &self.index_current_file,
+                                        t!("settings.indexing.progress.status_text").to_string(),
+                                        cx,
+                                    ))
+                                    .child(
+                                        div()
+                                            .text_xs()
+                                            .text_color(cx.theme().muted_foreground)
+                                            .child(
+                                                t!(
+                                                    &plural_key("settings.indexing.progress.status", total),
+                                                    current = current,
+                                                    total = total
+                                                )
+                                                .to_string(),
+                                            ),
+                                    ),
+                            )
+                            .when(self.index_resumed, |el| {
+                                el.child(
+                                    div()
+                                        .text_xs()
+                                        .text_color(cx.theme().muted_foreground)
+                                        .child(t!("settings.indexing.progress.resumed").to_string()),
+                                )
+                            }),
+                    )
+            })
+            // Index stats and manual re-index button (always show if models downloaded, regardless of toggle)
+            .when(self.models_downloaded, |el| {
+                el.child(self.render_section_header(&t!("settings.indexing.index_status.title").to_string(), cx))
+                    .child(
+                        h_flex()
+                            .w_full()
+                            .gap_2()
+                            .items_center()
+                            .mb_4()
+                            .child(
+                                div()
+                                    .text_xs()
+                                    .text_color(cx.theme().muted_foreground)
+                                    .child(
+                                        t!(
+                                            &plural_key("settings.indexing.index_status.count", indexed_count),
+                                            count = format_count(indexed_count)
+                                        )
+                                        .to_string(),
+                                    ),
+                            )
+                            .child(
+                                self.settings_button(
+                                    "index-new-button",
+                                    !(!indexing_enabled || self.indexing || self.downloading_models),
+                                    |b| {
+                                        b.small()
+                                            .outline()
+                                            .label(&t!("settings.indexing.index_status.button").to_string())
+                                    },
+                                    |_this, _, cx| {
+                                        Self::start_incremental_index(cx);
+                                        cx.notify();
+                                    },
+                                    cx,
+                                ),
+                            )
+                            .child(
+                                self.settings_button(
+                                    "index-changed-button",
+                                    !(!indexing_enabled || self.indexing || self.downloading_models),
+                                    |b| {
+                                        b.small()
+                                            .outline()
+                                            .label(&t!("settings.indexing.index_status.changed_button").to_string())
+                                    },
+                                    |_this, _, cx| {
+                                        let tx = {
+                                            let app_state = cx.global::<AppState>();
+                                            app_state.message_tx.clone()
+                                        };
+                                        let Some(config) = index_config(&cx.global::<AppState>().settings.lock()) else {
+                                            return;
+                                        };
+                                        // New files too, and old rows of changed ones are replaced
+                                        start_indexing_with_models(config, tx, IndexScope::Changed, cx);
+                                        cx.notify();
+                                    },
+                                    cx,
+                                ),
+                            )
+                    )
+            })
+            // Pick up what auto-index missed, on a schedule
+            .when(self.models_downloaded, |el| el.child(self.render_index_schedule(settings, cx)))
+            // Take the index to another PC instead of rebuilding it there
+            .child(self.render_section_header(&t!("settings.indexing.transfer.title").to_string(), cx))
+            .child(
+                self.render_setting_row(
+                    &t!("settings.indexing.transfer.label").to_string(),
+                    Some(&t!("settings.indexing.transfer.desc").to_string()),
+                    h_flex()
+                        .gap_2()
+                        .items_center()
+                        .when(self.transferring_index, |el| el.child(Spinner::new().small()))
+                        .child(
+                            self.settings_button(
+                                "index-export",
+                                !(self.transferring_index || self.indexing || indexed_count == 0),
+                                |b| {
+                                    b.small()
+                                        .outline()
+                                        .label(&t!("settings.indexing.transfer.export").to_string())
+                                },
+                                |_this, _, cx| {
+                                    let tx = {
+                                        let app_state = cx.global::<AppState>();
+                                        app_state.message_tx.clone()
+                                    };
+                                    std::thread::spawn(move || {
+                                        if let Some(path) = pick_folder() {
+                                            let _ = tx.send(AppMessage::IndexExportPicked(path));
+                                        }
+                                    });
+                                },
+                                cx,
+                            ),
+                        )
+                        .child(
+                            self.settings_button(
+                                "index-import",
+                                !(self.transferring_index || self.indexing),
+                                |b| {
+                                    b.small()
+                                        .outline()
+                                        .label(&t!("settings.indexing.transfer.import").to_string())
+                                },
+                                |_this, _, cx| {
+                                    let tx = {
+                                        let app_state = cx.global::<AppState>();
+                                        app_state.message_tx.clone()
+                                    };
+                                    std::thread::spawn(move || {
+                                        if let Some(path) = pick_folder() {
+                                            let _ = tx.send(AppMessage::IndexImportPicked(path));
+                                        }
+                                    });
+                                },
+                                cx,
+                            ),
+                        ),
+                    cx,
+                ),
+            )
+    }
+
+    /// Scheduled indexing: when it runs, and when it runs next
+    fn render_index_schedule(
+        &self,
+        settings: &crate::settings::Settings,
+        cx: &mut Context<Self>,
+    ) -> impl IntoElement {
+        let schedule = settings.index_schedule;
+        let hours = settings.index_schedule_hours;
+        let indexing_enabled = settings.indexing_enabled;
+        let next_run = match self.next_scheduled_index(cx) {
+            Some(due) => t!(
+                "settings.indexing.schedule.next_run",
+                time = due.format("%Y-%m-%d %H:%M").to_string()
+            )
+            .to_string(),
+            // Done (or too recent) this session
+            None if indexing_enabled && schedule == IndexSchedule::OnStart => {
+                t!("settings.indexing.schedule.next_start").to_string()
+            }
+            None => t!("settings.indexing.schedule.not_scheduled").to_string(),
+        };
+
+        v_flex()
+            .w_full()
+            .gap_2()
+            .child(self.render_section_header(&t!("settings.indexing.schedule.title").to_string(), cx))
+            .child(
+                self.render_setting_row(
+                    &t!("settings.indexing.schedule.label").to_string(),
+                    Some(&t!("settings.indexing.schedule.desc").to_string()),
+                    h_flex()
                         .gap_2()
-                        .mb_4()
-                        .child(
-                            // Progress bar container
-                            div()
-                                .w_full()
-                                .h(px(8.0))
-                                .rounded(px(4.0))
-                                .bg(cx.theme().muted)
-                                .overflow_hidden()
-                                .child(
-                                    div()
-                                        .h_full()
-                                        .w(relative(progress_pct / 100.0))
-                                        .bg(cx.theme().primary)
-                                        .rounded(px(4.0)),
-                                ),
-                        )
-                        .child(
-                            h_flex()
-                                .w_full()
-                                .justify_between()
-                                .child(
-                                    div()
-                                        .text_xs()
-                                        .text_color(cx.theme().muted_foreground)
-                                        .max_w(px(200.0))
-                                        .overflow_x_hidden()
-                                        .child(if organize_current_file.is_empty() {
-                                            t!("settings.general.organizer.progress.preparing").to_string()
-                                        } else {
-                                            organize_current_file
-                                        }),
+                        .children(
+                            [
+                                (IndexSchedule::Interval, "schedule-interval", t!("settings.indexing.schedule.interval")),
+                                (IndexSchedule::OnStart, "schedule-on-start", t!("settings.indexing.schedule.on_start")),
+                                (IndexSchedule::Off, "schedule-off", t!("settings.indexing.schedule.off")),
+                            ]
+                            .into_iter()
+                            .map(|(mode, id, label)| {
+                                self.settings_button(
+                                    id,
+                                    indexing_enabled,
+                                    |b| {
+                                        b.small()
+                                            .when(schedule == mode, |s| s.primary())
+                                            .when(schedule != mode, |s| s.outline())
+                                            .label(&label.to_string())
+                                    },
+                                    move |this, _, cx| {
+                                        this.set_index_schedule(mode, hours, cx);
+                                    },
+                                    cx,
                                 )
-                                .child(
-                                    div()
-                                        .text_xs()
-                                        .text_color(cx.theme().muted_foreground)
-                                        .child(t!("settings.general.organizer.progress.status", current = current, total = total).to_string()),
-                                ),
+                            }),
+                        ),
+                    cx,
+                ),
+            )
+            .when(schedule != IndexSchedule::Off, |el| {
+                el.child(
+                    self.render_setting_row(
+                        &t!("settings.indexing.schedule.hours_label").to_string(),
+                        Some(&t!("settings.indexing.schedule.hours_desc").to_string()),
+                        self.settings_stepper(
+                            "schedule-hours",
+                            indexing_enabled,
+                            stepper_value(
+                                70.0,
+                                t!("settings.indexing.schedule.hours_value", hours = hours).to_string(),
+                                cx,
+                            ),
+                            move |this, step, _, cx| {
+                                this.set_index_schedule(schedule, index_schedule::step_hours(hours, step > 0), cx);
+                            },
+                            cx,
                         ),
+                        cx,
+                    ),
                 )
             })
+            .child(
+                h_flex()
+                    .w_full()
+                    .gap_2()
+                    .items_center()
+                    .mb_4()
+                    .child(
+                        div()
+                            .text_xs()
+                            .text_color(cx.theme().muted_foreground)
+                            .child(next_run),
+                    )
+                    .child(
+                        self.settings_button(
+                            "schedule-run-now",
+                            !(!indexing_enabled || self.indexing || self.downloading_models),
+                            |b| {
+                                b.small()
+                                    .outline()
+                                    .label(&t!("settings.indexing.schedule.run_now").to_string())
+                            },
+                            |_this, _, cx| {
+                                Self::start_incremental_index(cx);
+                                cx.notify();
+                            },
+                            cx,
+                        ),
+                    ),
+            )
+    }
+
+    fn render_hotkey_settings(
+        &self,
+        settings: &crate::settings::Settings,
+        cx: &mut Context<Self>,
+    ) -> impl IntoElement {
+        let hotkey_enabled = settings.hotkey_enabled;
+        let hotkey_str = settings.hotkey.clone();
+        let recording = self.recording_hotkey;
+        let hotkey_error = crate::hotkey::toggle_error().filter(|_| hotkey_enabled);
+
+        v_flex()
+            .w_full()
+            .gap_2()
+            .child(self.render_section_header(&t!("settings.hotkey.title").to_string(), cx))
+            // Enable toggle
+            .child(
+                self.render_setting_row(
+                    &t!("settings.hotkey.enable_label").to_string(),
+                    Some(&t!("settings.hotkey.enable_desc").to_string()),
+                    self.settings_switch(
+                        "hotkey-enable",
+                        hotkey_enabled,
+                        true,
+                        |_this, checked, _, cx| {
+                            {
+                                let app_state = cx.global::<AppState>();
+                                let mut settings = app_state.settings.lock();
+                                settings.hotkey_enabled = checked;
+                                let _ = settings.save();
+                            }
+                            cx.notify();
+                        },
+                        cx,
+                    ),
+                    cx,
+                ),
+            )
+            // Current hotkey display + record button
             .child(
                 v_flex()
                     .w_full()
@@ -2295,775 +9055,1086 @@ impl Sukusho {
                                     .text_sm()
                                     .font_weight(FontWeight::MEDIUM)
                                     .text_color(cx.theme().foreground)
-                                    .child(t!("settings.general.organizer.format_label").to_string()),
+                                    .child(t!("settings.hotkey.current_label").to_string()),
                             )
                             .child(
                                 h_flex()
-                                    .gap_1()
-                                    .child(
-                                        Button::new("fmt-ymd")
-                                            .small()
-                                            .when(organizer_format == "YYYY-MM-DD", |s| s.primary())
-                                            .when(organizer_format != "YYYY-MM-DD", |s| s.outline())
-                                            .label(&t!("settings.general.organizer.format_ymd").to_string())
-                                            .on_click(cx.listener(|_this, _, _, cx| {
-                                                {
-                                                    let app_state = cx.global::<AppState>();
-                                                    let mut settings = app_state.settings.lock();
-                                                    settings.organizer_format =
-                                                        "YYYY-MM-DD".to_string();
-                                                    let _ = settings.save();
-                                                }
-                                                cx.notify();
-                                            })),
-                                    )
+                                    .gap_2()
+                                    .items_center()
                                     .child(
-                                        Button::new("fmt-ym")
-                                            .small()
-                                            .when(organizer_format == "YYYY-MM", |s| s.primary())
-                                            .when(organizer_format != "YYYY-MM", |s| s.outline())
-                                            .label(&t!("settings.general.organizer.format_ym").to_string())
-                                            .on_click(cx.listener(|_this, _, _, cx| {
-                                                {
-                                                    let app_state = cx.global::<AppState>();
-                                                    let mut settings = app_state.settings.lock();
-                                                    settings.organizer_format =
-                                                        "YYYY-MM".to_string();
-                                                    let _ = settings.save();
-                                                }
-                                                cx.notify();
-                                            })),
+                                        div()
+                                            .px_3()
+                                            .py_1()
+                                            .rounded(px(6.0))
+                                            .bg(if recording {
+                                                cx.theme().primary
+                                            } else {
+                                                cx.theme().muted
+                                            })
+                                            .text_sm()
+                                            .font_weight(FontWeight::SEMIBOLD)
+                                            .text_color(if recording {
+                                                cx.theme().primary_foreground
+                                            } else {
+                                                cx.theme().foreground
+                                            })
+                                            .child(if recording {
+                                                t!("settings.hotkey.recording").to_string()
+                                            } else {
+                                                hotkey_str
+                                            }),
                                     )
                                     .child(
-                                        Button::new("fmt-ymd-slash")
-                                            .small()
-                                            .when(organizer_format == "YYYY/MM/DD", |s| s.primary())
-                                            .when(organizer_format != "YYYY/MM/DD", |s| s.outline())
-                                            .label(&t!("settings.general.organizer.format_ymd_slash").to_string())
-                                            .on_click(cx.listener(|_this, _, _, cx| {
-                                                {
-                                                    let app_state = cx.global::<AppState>();
-                                                    let mut settings = app_state.settings.lock();
-                                                    settings.organizer_format =
-                                                        "YYYY/MM/DD".to_string();
-                                                    let _ = settings.save();
-                                                }
+                                        self.settings_button(
+                                            "record-hotkey",
+                                            true,
+                                            |b| {
+                                                b.small()
+                                                    .when(recording, |s| s.danger())
+                                                    .when(!recording, |s| s.outline())
+                                                    .label(&if recording { t!("settings.hotkey.cancel_button").to_string() } else { t!("settings.hotkey.record_button").to_string() })
+                                            },
+                                            |this, _, cx| {
+                                                this.recording_hotkey = !this.recording_hotkey;
+                                                this.recording_shortcut = None;
                                                 cx.notify();
-                                            })),
+                                            },
+                                            cx,
+                                        ),
                                     ),
                             ),
                     )
+                    // Another app owns the combination
+                    .when_some(hotkey_error, |el, error| {
+                        el.child(
+                            h_flex()
+                                .w_full()
+                                .gap_2()
+                                .items_center()
+                                .child(
+                                    div()
+                                        .flex_1()
+                                        .text_xs()
+                                        .text_color(cx.theme().danger)
+                                        .child(t!("settings.hotkey.not_active", error = error).to_string()),
+                                )
+                                .child(
+                                    self.settings_button(
+                                        "probe-hotkeys",
+                                        true,
+                                        |b| {
+                                            b.small()
+                                                .outline()
+                                                .label(&t!("settings.hotkey.find_free").to_string())
+                                        },
+                                        |this, _, cx| {
+                                            this.hotkey_probe =
+                                                Some(crate::hotkey::probe_free(&crate::hotkey::FALLBACK_HOTKEYS));
+                                            cx.notify();
+                                        },
+                                        cx,
+                                    ),
+                                ),
+                        )
+                    })
+                    // Probe results; free ones can be picked right here
+                    .when_some(self.hotkey_probe.clone(), |el, probe| {
+                        el.child(
+                            h_flex()
+                                .w_full()
+                                .flex_wrap()
+                                .gap_1()
+                                .items_center()
+                                .child(
+                                    div()
+                                        .text_xs()
+                                        .text_color(cx.theme().muted_foreground)
+                                        .child(t!("settings.hotkey.free_label").to_string()),
+                                )
+                                .children(probe.into_iter().enumerate().map(|(index, (candidate, free))| {
+                                    let label = if free {
+                                        candidate.clone()
+                                    } else {
+                                        t!("settings.hotkey.taken", hotkey = candidate).to_string()
+                                    };
+                                    self.settings_button(
+                                        ("hotkey-candidate", index),
+                                        free,
+                                        |b| {
+                                            b.small()
+                                                .when(free, |s| s.outline())
+                                                .when(!free, |s| s.ghost())
+                                                .label(&label)
+                                        },
+                                        move |this, window, cx| {
+                                            this.apply_hotkey(candidate.clone(), window, cx);
+                                        },
+                                        cx,
+                                    )
+                                })),
+                        )
+                    })
                     .child(
                         div()
                             .text_xs()
                             .text_color(cx.theme().muted_foreground)
-                            .child(t!("settings.general.organizer.format_preview", preview = format_preview).to_string()),
+                            .child(t!("settings.hotkey.examples").to_string()),
                     ),
             )
-            // Display Settings
-            .child(self.render_section_header(&t!("settings.general.appearance.title").to_string(), cx))
+            // Move to another monitor
             .child(
                 self.render_setting_row(
-                    &t!("settings.general.appearance.thumbnail_size_label").to_string(),
-                    Some(&t!("settings.general.appearance.thumbnail_size_desc").to_string()),
-                    h_flex()
-                        .gap_2()
-                        .items_center()
-                        .child(
-                            Button::new("thumb-minus")
-                                .ghost()
-                                .compact()
-                                .label("-")
-                                .on_click(cx.listener(|this, _, _, cx| {
-                                    let new_size = (this.thumbnail_size as i32 - 10).max(80) as u32;
-                                    this.thumbnail_size = new_size;
-                                    {
-                                        let app_state = cx.global::<AppState>();
-                                        let mut settings = app_state.settings.lock();
-                                        settings.thumbnail_size = new_size;
-                                        let _ = settings.save();
-                                    }
-                                    cx.notify();
-                                })),
-                        )
-                        .child(
-                            div()
-                                .w(px(60.0))
-                                .text_center()
-                                .px_2()
-                                .py_1()
-                                .rounded(px(4.0))
-                                .bg(cx.theme().muted)
-                                .text_sm()
-                                .child(t!("settings.general.appearance.thumbnail_size_value", size = thumbnail_size).to_string()),
-                        )
-                        .child(
-                            Button::new("thumb-plus")
-                                .ghost()
-                                .compact()
-                                .label("+")
-                                .on_click(cx.listener(|this, _, _, cx| {
-                                    let new_size = (this.thumbnail_size + 10).min(300);
-                                    this.thumbnail_size = new_size;
-                                    {
-                                        let app_state = cx.global::<AppState>();
-                                        let mut settings = app_state.settings.lock();
-                                        settings.thumbnail_size = new_size;
-                                        let _ = settings.save();
-                                    }
-                                    cx.notify();
-                                })),
-                        ),
+                    &t!("settings.hotkey.monitor_label").to_string(),
+                    Some(&t!("settings.hotkey.monitor_desc").to_string()),
+                    div()
+                        .px_3()
+                        .py_1()
+                        .rounded(px(6.0))
+                        .bg(cx.theme().muted)
+                        .text_sm()
+                        .text_color(cx.theme().foreground)
+                        .child(format!(
+                            "{} / {}",
+                            settings.previous_monitor_hotkey, settings.next_monitor_hotkey
+                        )),
                     cx,
                 ),
             )
-            // Window Opacity slider
+            // In-app shortcuts (remappable)
+            .child(self.render_section_header(&t!("settings.hotkey.shortcuts.title").to_string(), cx))
             .child(
-                self.render_setting_row(
-                    &t!("settings.general.appearance.window_opacity_label").to_string(),
-                    Some(&t!("settings.general.appearance.window_opacity_desc").to_string()),
-                    h_flex()
-                        .gap_2()
-                        .items_center()
-                        .child(
-                            Button::new("opacity-minus")
-                                .ghost()
-                                .compact()
-                                .label("-")
-                                .on_click(cx.listener(|this, _, window, cx| {
-                                    let new_opacity = (this.window_opacity - 0.05).max(0.3);
-                                    this.window_opacity = new_opacity;
-                                    {
-                                        let app_state = cx.global::<AppState>();
-                                        let mut settings = app_state.settings.lock();
-                                        settings.window_opacity = new_opacity;
-                                        let _ = settings.save();
-                                    }
-                                    set_window_opacity(window, new_opacity);
-                                    cx.notify();
-                                })),
-                        )
-                        .child(
-                            div()
-                                .w(px(60.0))
-                                .text_center()
-                                .px_2()
-                                .py_1()
-                                .rounded(px(4.0))
-                                .bg(cx.theme().muted)
-                                .text_sm()
-                                .child(t!("settings.general.appearance.window_opacity_value", opacity = (self.window_opacity * 100.0) as u32).to_string()),
-                        )
-                        .child(
-                            Button::new("opacity-plus")
-                                .ghost()
-                                .compact()
-                                .label("+")
-                                .on_click(cx.listener(|this, _, window, cx| {
-                                    let new_opacity = (this.window_opacity + 0.05).min(1.0);
-                                    this.window_opacity = new_opacity;
-                                    {
-                                        let app_state = cx.global::<AppState>();
-                                        let mut settings = app_state.settings.lock();
-                                        settings.window_opacity = new_opacity;
-                                        let _ = settings.save();
-                                    }
-                                    set_window_opacity(window, new_opacity);
-                                    cx.notify();
-                                })),
+                div()
+                    .text_xs()
+                    .text_color(cx.theme().muted_foreground)
+                    .child(t!("settings.hotkey.shortcuts.desc").to_string()),
+            )
+            .children(
+                ShortcutAction::ALL
+                    .into_iter()
+                    .enumerate()
+                    .map(|(index, action)| self.render_shortcut_row(index, action, cx)),
+            )
+    }
+
+    /// One remappable shortcut: current keys, record and reset
+    fn render_shortcut_row(
+        &self,
+        index: usize,
+        action: ShortcutAction,
+        cx: &mut Context<Self>,
+    ) -> impl IntoElement {
+        let recording = self.recording_shortcut == Some(action);
+        let is_default = self.shortcuts.is_default(action);
+        let keys = self
+            .shortcuts
+            .shortcut(action)
+            .map(|s| s.keys_label())
+            .unwrap_or_default();
+        let conflict = self
+            .shortcut_conflict
+            .filter(|(recorded, _)| *recorded == action)
+            .map(|(_, other)| other);
+
+        v_flex()
+            .w_full()
+            .gap_1()
+            .py_1()
+            .child(
+                h_flex()
+                    .w_full()
+                    .gap_2()
+                    .items_center()
+                    .child(
+                        div()
+                            .flex_1()
+                            .text_sm()
+                            .text_color(cx.theme().foreground)
+                            .child(action.description()),
+                    )
+                    .child(
+                        div()
+                            .px_3()
+                            .py_1()
+                            .rounded(px(6.0))
+                            .bg(if recording {
+                                cx.theme().primary
+                            } else {
+                                cx.theme().muted
+                            })
+                            .text_sm()
+                            .font_weight(FontWeight::SEMIBOLD)
+                            .text_color(if recording {
+                                cx.theme().primary_foreground
+                            } else {
+                                cx.theme().foreground
+                            })
+                            .child(if recording {
+                                t!("settings.hotkey.recording").to_string()
+                            } else {
+                                keys
+                            }),
+                    )
+                    .child(
+                        self.settings_button(
+                            ("shortcut-record", index),
+                            true,
+                            |b| {
+                                b.small()
+                                    .when(recording, |s| s.danger())
+                                    .when(!recording, |s| s.outline())
+                                    .label(&if recording {
+                                        t!("settings.hotkey.cancel_button").to_string()
+                                    } else {
+                                        t!("settings.hotkey.record_button").to_string()
+                                    })
+                            },
+                            move |this, _, cx| {
+                                this.recording_shortcut = if recording { None } else { Some(action) };
+                                this.recording_hotkey = false;
+                                this.shortcut_conflict = None;
+                                cx.notify();
+                            },
+                            cx,
                         ),
-                    cx,
-                ),
+                    )
+                    .child(
+                        self.settings_button(
+                            ("shortcut-reset", index),
+                            !is_default,
+                            |b| {
+                                b.small()
+                                    .ghost()
+                                    .label(&t!("settings.hotkey.shortcuts.reset_button").to_string())
+                            },
+                            move |this, _, cx| {
+                                this.reset_shortcut(action, cx);
+                            },
+                            cx,
+                        ),
+                    ),
             )
+            .when_some(conflict, |el, other| {
+                el.child(
+                    div()
+                        .text_xs()
+                        .text_color(cx.theme().danger)
+                        .child(
+                            t!("settings.hotkey.shortcuts.conflict", action = other.description())
+                                .to_string(),
+                        ),
+                )
+            })
     }
 
-    fn render_conversion_settings(
+    fn render_cleanup_settings(
         &self,
         settings: &crate::settings::Settings,
         cx: &mut Context<Self>,
     ) -> impl IntoElement {
-        let auto_convert = settings.auto_convert_webp;
-        let format = settings.conversion_format;
-        let quality = settings.webp_quality;
-        let converting = self.converting;
-        let convert_progress = self.convert_progress;
-        let convert_current_file = self.convert_current_file.clone();
+        let days = settings.cleanup_unused_days;
+        let candidates = &self.cleanup_candidates.items;
+        let candidate_paths: Vec<PathBuf> = candidates.iter().map(|(p, _, _)| p.clone()).collect();
+        let selected_count = candidate_paths
+            .iter()
+            .filter(|p| self.cleanup_selected.contains(*p))
+            .count();
+        let selected_size: u64 = candidates
+            .iter()
+            .filter(|(p, _, _)| self.cleanup_selected.contains(p))
+            .map(|(_, _, size)| *size)
+            .sum();
 
         v_flex()
             .w_full()
             .gap_2()
-            .child(self.render_section_header(&t!("settings.conversion.auto_convert.title").to_string(), cx))
-            // Auto-convert toggle
+            .child(self.render_section_header(&t!("settings.cleanup.title").to_string(), cx))
             .child(
                 self.render_setting_row(
-                    &t!("settings.conversion.auto_convert.enable_label").to_string(),
-                    Some(&t!("settings.conversion.auto_convert.enable_desc").to_string()),
-                    Switch::new("auto-convert")
-                        .checked(auto_convert)
-                        .on_click(cx.listener(|_this, checked: &bool, _, cx| {
+                    &t!("settings.cleanup.days_label").to_string(),
+                    Some(&t!("settings.cleanup.days_desc").to_string()),
+                    self.settings_stepper(
+                        "cleanup-days",
+                        true,
+                        stepper_value(
+                            70.0,
+                            t!(
+                                &plural_key("settings.cleanup.days_value", days as usize),
+                                days = days
+                            )
+                            .to_string(),
+                            cx,
+                        ),
+                        |_this, step, _, cx| {
                             {
                                 let app_state = cx.global::<AppState>();
                                 let mut settings = app_state.settings.lock();
-                                settings.auto_convert_webp = *checked;
+                                settings.cleanup_unused_days = if step < 0 {
+                                    settings.cleanup_unused_days.saturating_sub(7).max(1)
+                                } else {
+                                    (settings.cleanup_unused_days + 7).min(365)
+                                };
                                 let _ = settings.save();
                             }
                             cx.notify();
-                        })),
+                        },
+                        cx,
+                    ),
                     cx,
                 ),
             )
-            // Format selection
             .child(
-                self.render_setting_row(
-                    &t!("settings.conversion.format.label").to_string(),
-                    Some(&t!("settings.conversion.format.desc").to_string()),
-                    h_flex()
-                        .gap_1()
-                        .child(
-                            Button::new("fmt-webp")
-                                .small()
-                                .when(format == ConversionFormat::WebP, |s| s.primary())
-                                .when(format != ConversionFormat::WebP, |s| s.outline())
-                                .label(&t!("settings.conversion.format.webp").to_string())
-                                .on_click(cx.listener(|_this, _, _, cx| {
-                                    {
-                                        let app_state = cx.global::<AppState>();
-                                        let mut settings = app_state.settings.lock();
-                                        settings.conversion_format = ConversionFormat::WebP;
-                                        let _ = settings.save();
-                                    }
-                                    cx.notify();
-                                })),
-                        )
-                        .child(
-                            Button::new("fmt-jpeg")
-                                .small()
-                                .when(format == ConversionFormat::Jpeg, |s| s.primary())
-                                .when(format != ConversionFormat::Jpeg, |s| s.outline())
-                                .label(&t!("settings.conversion.format.jpeg").to_string())
-                                .on_click(cx.listener(|_this, _, _, cx| {
-                                    {
-                                        let app_state = cx.global::<AppState>();
-                                        let mut settings = app_state.settings.lock();
-                                        settings.conversion_format = ConversionFormat::Jpeg;
-                                        let _ = settings.save();
+                h_flex()
+                    .w_full()
+                    .gap_2()
+                    .items_center()
+                    .mb_2()
+                    .child(
+                        div()
+                            .flex_1()
+                            .text_xs()
+                            .text_color(cx.theme().muted_foreground)
+                            .child(
+                                t!(
+                                    &plural_key("settings.cleanup.summary", candidates.len()),
+                                    count = format_count(candidates.len()),
+                                    selected = format_count(selected_count),
+                                    size = format_file_size(selected_size)
+                                )
+                                .to_string(),
+                            ),
+                    )
+                    .child(
+                        self.settings_button(
+                            "cleanup-select-all",
+                            !candidates.is_empty(),
+                            |b| {
+                                b.small()
+                                    .outline()
+                                    .label(&t!("settings.cleanup.select_all_button").to_string())
+                            },
+                            {
+                                let candidate_paths = candidate_paths.clone();
+                                move |this, _, cx| {
+                                    if candidate_paths.iter().all(|p| this.cleanup_selected.contains(p)) {
+                                        this.cleanup_selected.clear();
+                                    } else {
+                                        this.cleanup_selected.extend(candidate_paths.iter().cloned());
                                     }
                                     cx.notify();
-                                })),
+                                }
+                            },
+                            cx,
                         ),
-                    cx,
-                ),
-            )
-            // Quality (only for JPEG, WebP doesn't support lossy quality in image crate)
-            .child(
-                self.render_setting_row(
-                    &t!("settings.conversion.quality.label").to_string(),
-                    Some(&t!("settings.conversion.quality.desc").to_string()),
-                    h_flex()
-                        .gap_2()
-                        .items_center()
-                        .child(
-                            Button::new("qual-minus")
-                                .ghost()
-                                .compact()
-                                .label("-")
-                                .when(format == ConversionFormat::WebP, |s| s.disabled(true))
-                                .on_click(cx.listener(|_this, _, _, cx| {
-                                    {
-                                        let app_state = cx.global::<AppState>();
-                                        let mut settings = app_state.settings.lock();
-                                        settings.webp_quality =
-                                            (settings.webp_quality as i32 - 5).max(1) as u32;
-                                        let _ = settings.save();
-                                    }
-                                    cx.notify();
-                                })),
-                        )
-                        .child(
-                            div()
-                                .w(px(50.0))
-                                .text_center()
-                                .px_2()
-                                .py_1()
-                                .rounded(px(4.0))
-                                .bg(cx.theme().muted)
-                                .text_sm()
-                                .when(format == ConversionFormat::WebP, |s| s.opacity(0.5))
-                                .child(format!("{}", quality)),
-                        )
-                        .child(
-                            Button::new("qual-plus")
-                                .ghost()
-                                .compact()
-                                .label("+")
-                                .when(format == ConversionFormat::WebP, |s| s.disabled(true))
-                                .on_click(cx.listener(|_this, _, _, cx| {
-                                    {
-                                        let app_state = cx.global::<AppState>();
-                                        let mut settings = app_state.settings.lock();
-                                        settings.webp_quality =
-                                            (settings.webp_quality + 5).min(100);
-                                        let _ = settings.save();
+                    )
+                    .child(
+                        self.settings_button(
+                            "cleanup-recycle",
+                            selected_count > 0,
+                            |b| {
+                                b.small()
+                                    .danger()
+                                    .label(&t!("settings.cleanup.recycle_button").to_string())
+                            },
+                            {
+                                let candidate_paths = candidate_paths.clone();
+                                move |this, window, cx| {
+                                    let files: Vec<PathBuf> = candidate_paths
+                                        .iter()
+                                        .filter(|p| this.cleanup_selected.contains(*p))
+                                        .cloned()
+                                        .collect();
+                                    let recycled = crate::recycle::recycle_files(&files);
+                                    for path in &recycled {
+                                        this.cleanup_selected.remove(path);
                                     }
+                                    // The watcher reports the removals; show the outcome now
+                                    let failed = files.len() - recycled.len();
+                                    let (message, kind) = if failed == 0 {
+                                        (
+                                            t!(
+                                                &plural_key("settings.cleanup.recycled", recycled.len()),
+                                                count = format_count(recycled.len())
+                                            )
+                                            .to_string(),
+                                            NotificationType::Success,
+                                        )
+                                    } else {
+                                        (
+                                            t!(
+                                                &plural_key("settings.cleanup.recycle_failed", failed),
+                                                count = format_count(failed)
+                                            )
+                                            .to_string(),
+                                            NotificationType::Error,
+                                        )
+                                    };
+                                    window.push_notification(
+                                        Notification::new().message(&message).with_type(kind),
+                                        cx,
+                                    );
                                     cx.notify();
-                                })),
+                                }
+                            },
+                            cx,
                         ),
-                    cx,
-                ),
+                    ),
             )
-            // Progress bar when converting
-            .when(converting, |el| {
-                let (current, total) = convert_progress;
-                let progress_pct = if total > 0 {
-                    (current as f32 / total as f32) * 100.0
-                } else {
-                    0.0
-                };
+            .when(candidates.is_empty(), |el| {
                 el.child(
-                    v_flex()
-                        .w_full()
-                        .gap_2()
-                        .mb_4()
-                        .child(
-                            // Progress bar container
-                            div()
-                                .w_full()
-                                .h(px(8.0))
-                                .rounded(px(4.0))
-                                .bg(cx.theme().muted)
-                                .overflow_hidden()
-                                .child(
-                                    div()
-                                        .h_full()
-                                        .w(relative(progress_pct / 100.0))
-                                        .bg(cx.theme().primary)
-                                        .rounded(px(4.0)),
-                                ),
-                        )
-                        .child(
-                            h_flex()
-                                .w_full()
-                                .justify_between()
-                                .child(
-                                    div()
-                                        .text_xs()
-                                        .text_color(cx.theme().muted_foreground)
-                                        .max_w(px(200.0))
-                                        .overflow_x_hidden()
-                                        .child(if convert_current_file.is_empty() {
-                                            t!("settings.conversion.progress.preparing").to_string()
-                                        } else {
-                                            convert_current_file
-                                        }),
-                                )
-                                .child(
-                                    div()
-                                        .text_xs()
-                                        .text_color(cx.theme().muted_foreground)
-                                        .child(t!("settings.conversion.progress.status", current = current, total = total).to_string()),
-                                ),
-                        ),
+                    div()
+                        .py_4()
+                        .text_sm()
+                        .text_color(cx.theme().muted_foreground)
+                        .child(t!("settings.cleanup.empty").to_string()),
+                )
+            })
+            .children(candidates.iter().cloned().enumerate().map(|(index, (path, modified, size))| {
+                let is_checked = self.cleanup_selected.contains(&path);
+                let file_name = path
+                    .file_name()
+                    .map(|n| n.to_string_lossy().to_string())
+                    .unwrap_or_default();
+                let date = chrono::DateTime::<chrono::Local>::from(modified)
+                    .format("%Y-%m-%d")
+                    .to_string();
+                let toggle = settings_action(move |this, _, _, cx| {
+                    if !this.cleanup_selected.remove(&path) {
+                        this.cleanup_selected.insert(path.clone());
+                    }
+                    cx.notify();
+                });
+                let focused = self.register_setting(("cleanup-row", index).into(), ControlKind::Toggle, toggle.clone());
+
+                h_flex()
+                    .id(("cleanup-row", index))
+                    .w_full()
+                    .gap_3()
+                    .px_2()
+                    .py_1()
+                    .rounded(px(6.0))
+                    .border_2()
+                    .border_color(ring_color(focused, cx))
+                    .items_center()
+                    .cursor_pointer()
+                    .when(is_checked, |s| s.bg(cx.theme().accent))
+                    .hover(|s| s.bg(cx.theme().muted))
+                    .on_click(cx.listener(move |this, _, window, cx| {
+                        toggle(this, ControlInput::Activate, window, cx);
+                    }))
+                    .child(
+                        div()
+                            .w(px(16.0))
+                            .text_sm()
+                            .text_color(cx.theme().primary)
+                            .child(if is_checked { "✓" } else { "" }),
+                    )
+                    .child(
+                        div()
+                            .flex_1()
+                            .text_sm()
+                            .text_color(cx.theme().foreground)
+                            .overflow_x_hidden()
+                            .child(file_name),
+                    )
+                    .child(
+                        div()
+                            .text_xs()
+                            .text_color(cx.theme().muted_foreground)
+                            .child(date),
+                    )
+                    .child(
+                        div()
+                            .w(px(70.0))
+                            .text_right()
+                            .text_xs()
+                            .text_color(cx.theme().muted_foreground)
+                            .child(format_file_size(size)),
+                    )
+            }))
+    }
+
+    fn render_diagnostics_settings(&self, cx: &mut Context<Self>) -> impl IntoElement {
+        let decode_failures = crate::thumbnail::decode_failures();
+        let (last_recovery, recovery_failures) = crate::shell_recovery::status();
+        let recovery_failing = crate::shell_recovery::is_failing();
+
+        v_flex()
+            .w_full()
+            .gap_2()
+            // Files the gallery can't display
+            .child(self.render_section_header(
+                &t!("settings.diagnostics.decode_failures.title").to_string(),
+                cx,
+            ))
+            .when(decode_failures.is_empty(), |el| {
+                el.child(
+                    div()
+                        .text_sm()
+                        .text_color(cx.theme().muted_foreground)
+                        .child(t!("settings.diagnostics.decode_failures.empty").to_string()),
                 )
             })
+            .children(decode_failures.into_iter().enumerate().map(|(index, (path, error))| {
+                let path_for_open = path.clone();
+                let open = settings_action(move |this, _, _, _| this.open_file(&path_for_open));
+                let focused = self.register_setting(("decode-failure", index).into(), ControlKind::Button, open.clone());
+                v_flex()
+                    .id(("decode-failure", index))
+                    .w_full()
+                    .px_2()
+                    .py_1()
+                    .rounded(px(6.0))
+                    .border_2()
+                    .border_color(ring_color(focused, cx))
+                    .cursor_pointer()
+                    .hover(|s| s.bg(cx.theme().muted))
+                    .on_click(cx.listener(move |this, _, window, cx| {
+                        open(this, ControlInput::Activate, window, cx);
+                    }))
+                    .child(
+                        div()
+                            .text_sm()
+                            .text_color(cx.theme().foreground)
+                            .child(path.to_string_lossy().to_string()),
+                    )
+                    .child(
+                        div()
+                            .text_xs()
+                            .text_color(cx.theme().muted_foreground)
+                            .child(error),
+                    )
+            }))
+            // Hotkeys and tray icon restored after sleep or an Explorer restart
+            .child(self.render_section_header(
+                &t!("settings.diagnostics.recovery.title").to_string(),
+                cx,
+            ))
+            .child(
+                div()
+                    .text_sm()
+                    .text_color(cx.theme().muted_foreground)
+                    .child(match &last_recovery {
+                        None => t!("settings.diagnostics.recovery.none").to_string(),
+                        Some(attempt) => {
+                            let trigger = match attempt.trigger {
+                                crate::shell_recovery::Trigger::Resume => {
+                                    t!("settings.diagnostics.recovery.resume")
+                                }
+                                crate::shell_recovery::Trigger::TaskbarCreated => {
+                                    t!("settings.diagnostics.recovery.taskbar_created")
+                                }
+                            };
+                            t!(
+                                "settings.diagnostics.recovery.last",
+                                trigger = trigger,
+                                when = format_relative_time(attempt.at, SystemTime::now())
+                            )
+                            .to_string()
+                        }
+                    }),
+            )
+            .when_some(
+                last_recovery.and_then(|attempt| attempt.error),
+                |el, error| {
+                    el.child(
+                        div()
+                            .text_sm()
+                            .text_color(if recovery_failing {
+                                cx.theme().danger
+                            } else {
+                                cx.theme().muted_foreground
+                            })
+                            .child(t!(
+                                &plural_key("settings.diagnostics.recovery.failed", recovery_failures as usize),
+                                count = recovery_failures,
+                                error = error
+                            ).to_string()),
+                    )
+                },
+            )
     }
 
-    fn render_indexing_settings(
+    /// Experimental and off-by-default features, behind "Show advanced settings"
+    fn render_advanced_settings(
         &self,
         settings: &crate::settings::Settings,
         cx: &mut Context<Self>,
     ) -> impl IntoElement {
-        let indexing_enabled = settings.indexing_enabled;
-        let cpu_mode = settings.indexing_cpu_mode.clone();
-        let indexed_count = settings.last_indexed_count;
-
-        // Pre-compute strings to avoid temporary value issues
-        let indexing_title = t!("settings.indexing.title").to_string();
-        let indexing_enable_label = t!("settings.indexing.enable_label").to_string();
-        let indexing_enable_desc = t!("settings.indexing.enable_desc").to_string();
+        let api_enabled = settings.api_server_enabled;
+        let api_token = settings.api_server_token.clone();
+        let api_port = crate::server::running_port();
+        let context_menu_threshold = settings.context_menu_confirm_threshold;
+        let watcher_debounce_ms = settings.watcher_debounce_ms;
+        let watcher_burst_threshold = settings.watcher_burst_threshold;
+        let watcher_poll_interval_secs = settings.watcher_poll_interval_secs;
+        let large_directory_files = settings.large_directory_files;
+        let conversion_workers = settings.conversion_workers;
+        let max_gallery_items = settings.max_gallery_items;
+        let stubbed = self.screenshots.stub_count();
+        // May be on from the command line without the setting
+        let perf_trace = perf::is_enabled();
 
         v_flex()
             .w_full()
             .gap_2()
-            // Enable Image Indexing toggle
-            .child(self.render_section_header(&indexing_title, cx))
+            // Warning header
+            .child(
+                v_flex()
+                    .w_full()
+                    .gap_1()
+                    .p_3()
+                    .mb_2()
+                    .rounded(px(6.0))
+                    .border_1()
+                    .border_color(cx.theme().warning)
+                    .child(
+                        div()
+                            .text_sm()
+                            .font_weight(FontWeight::SEMIBOLD)
+                            .text_color(cx.theme().warning)
+                            .child(t!("settings.advanced.warning_title").to_string()),
+                    )
+                    .child(
+                        div()
+                            .text_xs()
+                            .text_color(cx.theme().muted_foreground)
+                            .child(t!("settings.advanced.warning_desc").to_string()),
+                    ),
+            )
+            // Local API for other apps
+            .child(self.render_section_header(&t!("settings.advanced.api.title").to_string(), cx))
             .child(
                 self.render_setting_row(
-                    &indexing_enable_label,
-                    if self.downloading_models || self.indexing {
-                        None
-                    } else {
-                        Some(&indexing_enable_desc)
-                    },
-                    Switch::new("indexing-enable")
-                        .checked(indexing_enabled)
-                        .disabled(self.downloading_models || self.indexing)
-                        .on_click(cx.listener(|this, checked: &bool, _, cx| {
+                    &t!("settings.advanced.api.enable_label").to_string(),
+                    Some(&t!("settings.advanced.api.enable_desc").to_string()),
+                    self.settings_switch(
+                        "api-server",
+                        api_enabled,
+                        true,
+                        |_this, checked, _, cx| {
+                            let shared_settings = Arc::clone(&cx.global::<AppState>().settings);
                             {
-                                let app_state = cx.global::<AppState>();
-                                let mut settings = app_state.settings.lock();
-                                settings.indexing_enabled = *checked;
+                                let mut settings = shared_settings.lock();
+                                settings.api_server_enabled = checked;
                                 let _ = settings.save();
                             }
-                            // If enabling and models not downloaded, trigger download
-                            if *checked && !this.models_downloaded {
-                                let tx = {
-                                    let app_state = cx.global::<AppState>();
-                                    app_state.message_tx.clone()
-                                };
-                                let config = {
-                                    let app_state = cx.global::<AppState>();
-                                    let settings = app_state.settings.lock();
-                                    let db_path = crate::settings::Settings::config_path()
-                                        .unwrap()
-                                        .parent()
-                                        .unwrap()
-                                        .join("vector_index.db");
-                                    crate::indexer::IndexConfig {
-                                        db_path,
-                                        cpu_mode: if settings.indexing_cpu_mode == "fast" {
-                                            crate::indexer::CpuMode::Fast
-                                        } else {
-                                            crate::indexer::CpuMode::Normal
-                                        },
-                                        screenshot_dir: settings.screenshot_directory.clone(),
-                                    }
-                                };
-                                // Get prewarmed models if available
-                                let vision_model = PREWARMED_VISION_MODEL.lock().clone();
-                                let text_model = PREWARMED_TEXT_MODEL.lock().clone();
-                                crate::indexer::start_indexing(config, tx, false, vision_model, text_model);
+                            if checked {
+                                if let Err(e) = start_api_server(&shared_settings) {
+                                    error!("Failed to start local API: {}", e);
+                                }
+                            } else {
+                                crate::server::stop();
                             }
                             cx.notify();
-                        })),
+                        },
+                        cx,
+                    ),
                     cx,
                 ),
             )
-            // Model download status (always show if downloading or downloaded)
-            .when(self.downloading_models || self.models_downloaded, |el| {
-                if self.downloading_models {
-                    let (current, total) = self.model_download_progress;
-                    // Extract model info from the third parameter which now contains detailed description
-                    let progress_pct = if total > 0 {
-                        (current as f32 / total as f32) * 100.0
-                    } else {
-                        0.0
-                    };
-                    el.child(
-                        v_flex()
-                            .w_full()
+            .when(api_enabled, |el| {
+                el.child(
+                    self.render_setting_row(
+                        &t!("settings.advanced.api.port_label").to_string(),
+                        Some(&if self.api_port_error {
+                            t!("settings.advanced.api.port_invalid", min = crate::server::MIN_PORT).to_string()
+                        } else {
+                            t!("settings.advanced.api.port_desc").to_string()
+                        }),
+                        div().w(px(100.0)).child(Input::new(&self.api_port_input)),
+                        cx,
+                    ),
+                )
+                .child(
+                    self.render_setting_row(
+                        &t!("settings.advanced.api.address_label").to_string(),
+                        None,
+                        div()
+                            .text_sm()
+                            .text_color(cx.theme().muted_foreground)
+                            .child(match api_port {
+                                Some(port) => format!("http://127.0.0.1:{}", port),
+                                None => t!("settings.advanced.api.not_running").to_string(),
+                            }),
+                        cx,
+                    ),
+                )
+                .child(
+                    self.render_setting_row(
+                        &t!("settings.advanced.api.token_label").to_string(),
+                        Some(&t!("settings.advanced.api.token_desc").to_string()),
+                        h_flex()
                             .gap_2()
-                            .mb_4()
-                            .child(
-                                div()
-                                    .text_xs()
-                                    .font_weight(FontWeight::MEDIUM)
-                                    .text_color(cx.theme().foreground)
-                                    .child(t!("settings.indexing.model_status.title").to_string()),
-                            )
-                            .child(
-                                div()
-                                    .text_xs()
-                                    .text_color(cx.theme().muted_foreground)
-                                    .child(t!("settings.indexing.model_status.loading", current = current, total = total).to_string()),
-                            )
+                            .items_center()
                             .child(
                                 div()
-                                    .w_full()
-                                    .h(px(6.0))
-                                    .rounded(px(3.0))
-                                    .bg(cx.theme().muted)
-                                    .overflow_hidden()
-                                    .child(
-                                        div()
-                                            .h_full()
-                                            .w(relative(progress_pct / 100.0))
-                                            .bg(cx.theme().primary)
-                                            .rounded(px(3.0)),
-                                    ),
+                                    .text_sm()
+                                    .font_family("monospace")
+                                    .child(api_token.clone()),
                             )
                             .child(
-                                div()
-                                    .text_xs()
-                                    .text_color(cx.theme().muted_foreground)
-                                    .child(t!("settings.indexing.model_status.loading_percent", percent = progress_pct as u32).to_string()),
+                                self.settings_button(
+                                    "api-token-copy",
+                                    true,
+                                    |b| {
+                                        b.small()
+                                            .outline()
+                                            .label(&t!("settings.advanced.api.copy_button").to_string())
+                                    },
+                                    move |_this, _, cx| {
+                                        cx.write_to_clipboard(ClipboardItem::new_string(api_token.clone()));
+                                    },
+                                    cx,
+                                ),
                             )
-                    )
-                } else {
-                    el.child(
-                        v_flex()
-                            .w_full()
-                            .gap_1()
-                            .mb_4()
                             .child(
-                                div()
-                                    .text_xs()
-                                    .font_weight(FontWeight::MEDIUM)
-                                    .text_color(cx.theme().foreground)
-                                    .child(t!("settings.indexing.model_status.title").to_string()),
+                                self.settings_button(
+                                    "api-token-regenerate",
+                                    true,
+                                    |b| {
+                                        b.small()
+                                            .outline()
+                                            .label(&t!("settings.advanced.api.regenerate_button").to_string())
+                                    },
+                                    |_this, _, cx| {
+                                        let shared_settings = Arc::clone(&cx.global::<AppState>().settings);
+                                        let token = match crate::server::generate_token() {
+                                            Ok(token) => token,
+                                            Err(e) => {
+                                                error!("{}", e);
+                                                return;
+                                            }
+                                        };
+                                        {
+                                            let mut settings = shared_settings.lock();
+                                            settings.api_server_token = token;
+                                            let _ = settings.save();
+                                        }
+                                        // Old token stops working right away
+                                        if let Err(e) = start_api_server(&shared_settings) {
+                                            error!("Failed to restart local API: {}", e);
+                                        }
+                                        cx.notify();
+                                    },
+                                    cx,
+                                ),
+                            ),
+                        cx,
+                    ),
+                )
+            })
+            // Context menu guard
+            .child(self.render_section_header(&t!("settings.advanced.context_menu.title").to_string(), cx))
+            .child(
+                self.render_setting_row(
+                    &t!("settings.advanced.context_menu.threshold_label").to_string(),
+                    Some(&t!("settings.advanced.context_menu.threshold_desc").to_string()),
+                    self.settings_stepper(
+                        "context-menu-threshold",
+                        true,
+                        stepper_value(
+                            70.0,
+                            t!(
+                                &plural_key("settings.advanced.context_menu.threshold_value", context_menu_threshold),
+                                count = context_menu_threshold
                             )
-                            .child(
-                                div()
-                                    .text_xs()
-                                    .text_color(cx.theme().muted_foreground)
-                                    .child(if PREWARMED_TEXT_MODEL.lock().is_some() && PREWARMED_VISION_MODEL.lock().is_some() {
-                                        t!("settings.indexing.model_status.online").to_string()
-                                    } else {
-                                        t!("settings.indexing.model_status.ready").to_string()
-                                    }),
+                            .to_string(),
+                            cx,
+                        ),
+                        |_this, step, _, cx| {
+                            {
+                                let app_state = cx.global::<AppState>();
+                                let mut settings = app_state.settings.lock();
+                                settings.context_menu_confirm_threshold = if step < 0 {
+                                    settings.context_menu_confirm_threshold.saturating_sub(25).max(25)
+                                } else {
+                                    (settings.context_menu_confirm_threshold + 25).min(1000)
+                                };
+                                let _ = settings.save();
+                            }
+                            cx.notify();
+                        },
+                        cx,
+                    ),
+                    cx,
+                ),
+            )
+            // File watcher
+            .child(self.render_section_header(&t!("settings.advanced.watcher.title").to_string(), cx))
+            .child(
+                self.render_setting_row(
+                    &t!("settings.advanced.watcher.debounce_label").to_string(),
+                    Some(&t!("settings.advanced.watcher.debounce_desc").to_string()),
+                    self.settings_stepper(
+                        "watcher-debounce",
+                        true,
+                        stepper_value(
+                            70.0,
+                            t!("settings.advanced.watcher.debounce_value", ms = watcher_debounce_ms).to_string(),
+                            cx,
+                        ),
+                        |_this, step, _, cx| {
+                            {
+                                let app_state = cx.global::<AppState>();
+                                let mut settings = app_state.settings.lock();
+                                settings.watcher_debounce_ms = if step < 0 {
+                                    settings.watcher_debounce_ms.saturating_sub(50).max(50)
+                                } else {
+                                    (settings.watcher_debounce_ms + 50).min(5000)
+                                };
+                                let _ = settings.save();
+                            }
+                            cx.notify();
+                        },
+                        cx,
+                    ),
+                    cx,
+                ),
+            )
+            .child(
+                self.render_setting_row(
+                    &t!("settings.advanced.watcher.burst_label").to_string(),
+                    Some(&t!("settings.advanced.watcher.burst_desc").to_string()),
+                    self.settings_stepper(
+                        "watcher-burst",
+                        true,
+                        stepper_value(
+                            70.0,
+                            if watcher_burst_threshold == 0 {
+                                t!("settings.advanced.watcher.burst_off").to_string()
+                            } else {
+                                t!(
+                                    &plural_key("settings.advanced.watcher.burst_value", watcher_burst_threshold),
+                                    count = watcher_burst_threshold
+                                )
+                                .to_string()
+                            },
+                            cx,
+                        ),
+                        |_this, step, _, cx| {
+                            {
+                                let app_state = cx.global::<AppState>();
+                                let mut settings = app_state.settings.lock();
+                                settings.watcher_burst_threshold = if step < 0 {
+                                    settings.watcher_burst_threshold.saturating_sub(10).max(10)
+                                } else {
+                                    (settings.watcher_burst_threshold + 10).min(500)
+                                };
+                                let _ = settings.save();
+                            }
+                            cx.notify();
+                        },
+                        cx,
+                    ),
+                    cx,
+                ),
+            )
+            .child(
+                self.render_setting_row(
+                    &t!("settings.advanced.watcher.poll_label").to_string(),
+                    Some(&t!("settings.advanced.watcher.poll_desc").to_string()),
+                    self.settings_stepper(
+                        "watcher-poll",
+                        true,
+                        stepper_value(
+                            70.0,
+                            if watcher_poll_interval_secs == 0 {
+                                t!("settings.advanced.watcher.poll_off").to_string()
+                            } else {
+                                t!("settings.advanced.watcher.poll_value", secs = watcher_poll_interval_secs)
+                                    .to_string()
+                            },
+                            cx,
+                        ),
+                        |_this, step, _, cx| {
+                            {
+                                let app_state = cx.global::<AppState>();
+                                let mut settings = app_state.settings.lock();
+                                settings.watcher_poll_interval_secs = if step < 0 {
+                                    settings.watcher_poll_interval_secs.saturating_sub(5)
+                                } else {
+                                    (settings.watcher_poll_interval_secs + 5).min(300)
+                                };
+                                let _ = settings.save();
+                            }
+                            cx.notify();
+                        },
+                        cx,
+                    ),
+                    cx,
+                ),
+            )
+            .child(
+                self.render_setting_row(
+                    &t!("settings.advanced.watcher.large_folder_label").to_string(),
+                    Some(&t!("settings.advanced.watcher.large_folder_desc").to_string()),
+                    self.settings_stepper(
+                        "large-folder",
+                        true,
+                        stepper_value(
+                            90.0,
+                            t!(
+                                &plural_key("settings.advanced.watcher.burst_value", large_directory_files),
+                                count = format_count(large_directory_files)
                             )
-                    )
-                }
-            })
-            // CPU Mode selection (always show, but disable when off or busy)
-            .child(self.render_section_header(&t!("settings.indexing.settings_title").to_string(), cx))
+                            .to_string(),
+                            cx,
+                        ),
+                        |_this, step, _, cx| {
+                            {
+                                let app_state = cx.global::<AppState>();
+                                let mut settings = app_state.settings.lock();
+                                settings.large_directory_files = if step < 0 {
+                                    settings.large_directory_files.saturating_sub(5_000).max(5_000)
+                                } else {
+                                    (settings.large_directory_files + 5_000).min(500_000)
+                                };
+                                let _ = settings.save();
+                            }
+                            cx.notify();
+                        },
+                        cx,
+                    ),
+                    cx,
+                ),
+            )
+            // Conversion worker pool
+            .child(self.render_section_header(&t!("settings.advanced.conversion.title").to_string(), cx))
+            .child(
+                self.render_setting_row(
+                    &t!("settings.advanced.conversion.workers_label").to_string(),
+                    Some(&t!("settings.advanced.conversion.workers_desc").to_string()),
+                    self.settings_stepper(
+                        "conversion-workers",
+                        true,
+                        stepper_value(
+                            70.0,
+                            if conversion_workers == 0 {
+                                t!(
+                                    "settings.advanced.conversion.workers_auto",
+                                    count = convert_pool::default_workers()
+                                )
+                                .to_string()
+                            } else {
+                                conversion_workers.to_string()
+                            },
+                            cx,
+                        ),
+                        |_this, step, _, cx| {
+                            let cores = std::thread::available_parallelism()
+                                .map(|n| n.get())
+                                .unwrap_or(4);
+                            {
+                                let app_state = cx.global::<AppState>();
+                                let mut settings = app_state.settings.lock();
+                                settings.conversion_workers = if step < 0 {
+                                    settings.conversion_workers.saturating_sub(1)
+                                } else {
+                                    (settings.conversion_workers + 1).min(cores)
+                                };
+                                let _ = settings.save();
+                                convert_pool::set_workers(settings.conversion_workers);
+                            }
+                            cx.notify();
+                        },
+                        cx,
+                    ),
+                    cx,
+                ),
+            )
+            // Gallery memory
+            .child(self.render_section_header(&t!("settings.advanced.gallery.title").to_string(), cx))
             .child(
                 self.render_setting_row(
-                    &t!("settings.indexing.cpu_mode.label").to_string(),
-                    Some(&t!("settings.indexing.cpu_mode.desc").to_string()),
-                    h_flex()
-                        .gap_2()
-                        .child(
-                            Button::new("cpu-normal")
-                                .small()
-                                .when(cpu_mode == "normal", |s| s.primary())
-                                .when(cpu_mode != "normal", |s| s.outline())
-                                .label(&t!("settings.indexing.cpu_mode.normal").to_string())
-                                .disabled(!indexing_enabled || self.downloading_models || self.indexing)
-                                .on_click(cx.listener(|_this, _, _, cx| {
-                                    {
-                                        let app_state = cx.global::<AppState>();
-                                        let mut settings = app_state.settings.lock();
-                                        settings.indexing_cpu_mode = "normal".to_string();
-                                        let _ = settings.save();
-                                    }
-                                    cx.notify();
-                                })),
-                        )
-                        .child(
-                            Button::new("cpu-fast")
-                                .small()
-                                .when(cpu_mode == "fast", |s| s.primary())
-                                .when(cpu_mode != "fast", |s| s.outline())
-                                .label(&t!("settings.indexing.cpu_mode.fast").to_string())
-                                .disabled(!indexing_enabled || self.downloading_models || self.indexing)
-                                .on_click(cx.listener(|_this, _, _, cx| {
-                                    {
-                                        let app_state = cx.global::<AppState>();
-                                        let mut settings = app_state.settings.lock();
-                                        settings.indexing_cpu_mode = "fast".to_string();
-                                        let _ = settings.save();
-                                    }
-                                    cx.notify();
-                                })),
-                        ),
+                    &t!("settings.advanced.gallery.max_items_label").to_string(),
+                    Some(&t!("settings.advanced.gallery.max_items_desc", stubbed = stubbed).to_string()),
+                    self.settings_stepper(
+                        "max-gallery-items",
+                        true,
+                        stepper_value(70.0, max_gallery_items.to_string(), cx),
+                        |this, step, _, cx| {
+                            let cap = {
+                                let app_state = cx.global::<AppState>();
+                                let mut settings = app_state.settings.lock();
+                                settings.max_gallery_items = if step < 0 {
+                                    settings.max_gallery_items.saturating_sub(5000).max(5000)
+                                } else {
+                                    (settings.max_gallery_items + 5000).min(200_000)
+                                };
+                                let _ = settings.save();
+                                settings.max_gallery_items
+                            };
+                            this.screenshots.set_cap(cap);
+                            // Lowering drops items past the cap; raising leaves
+                            // stubs that are loaded as they're scrolled to
+                            if step < 0 {
+                                this.trim_screenshots();
+                            }
+                            cx.notify();
+                        },
+                        cx,
+                    ),
                     cx,
-                )
+                ),
             )
-            // Indexing progress
-            .when(self.indexing, |el| {
-                let (current, total) = self.index_progress;
-                let progress_pct = if total > 0 {
-                    (current as f32 / total as f32) * 100.0
-                } else {
-                    0.0
-                };
-                el.child(self.render_section_header(&t!("settings.indexing.progress.title").to_string(), cx))
-                    .child(
-                        v_flex()
-                            .w_full()
-                            .gap_2()
-                            .mb_4()
-                            .child(
-                                div()
-                                    .w_full()
-                                    .h(px(8.0))
-                                    .rounded(px(4.0))
-                                    .bg(cx.theme().muted)
-                                    .overflow_hidden()
-                                    .child(
-                                        div()
-                                            .h_full()
-                                            .w(relative(progress_pct / 100.0))
-                                            .bg(cx.theme().primary)
-                                            .rounded(px(4.0)),
-                                    ),
-                            )
-                            .child(
-                                h_flex()
-                                    .w_full()
-                                    .justify_between()
-                                    .child(
-                                        div()
-                                            .text_xs()
-                                            .text_color(cx.theme().muted_foreground)
-                                            .max_w(px(200.0))
-                                            .overflow_x_hidden()
-                                            .child(if self.index_current_file.is_empty() {
-                                                t!("settings.indexing.progress.status_text").to_string()
-                                            } else {
-                                                self.index_current_file.clone()
-                                            }),
-                                    )
-                                    .child(
-                                        div()
-                                            .text_xs()
-                                            .text_color(cx.theme().muted_foreground)
-                                            .child(t!("settings.indexing.progress.status", current = current, total = total).to_string()),
-                                    ),
-                            ),
-                    )
-            })
-            // Index stats and manual re-index button (always show if models downloaded, regardless of toggle)
-            .when(self.models_downloaded, |el| {
-                el.child(self.render_section_header(&t!("settings.indexing.index_status.title").to_string(), cx))
-                    .child(
-                        h_flex()
-                            .w_full()
-                            .gap_2()
-                            .items_center()
-                            .mb_4()
-                            .child(
-                                div()
-                                    .text_xs()
-                                    .text_color(cx.theme().muted_foreground)
-                                    .child(t!("settings.indexing.index_status.count", count = indexed_count).to_string()),
-                            )
-                            .child(
-                                Button::new("index-new-button")
-                                    .small()
-                                    .outline()
-                                    .label(&t!("settings.indexing.index_status.button").to_string())
-                                    .disabled(!indexing_enabled || self.indexing || self.downloading_models)
-                                    .on_click(cx.listener(|_this, _, _, cx| {
-                                        let tx = {
-                                            let app_state = cx.global::<AppState>();
-                                            app_state.message_tx.clone()
-                                        };
-                                        let config = {
-                                            let app_state = cx.global::<AppState>();
-                                            let settings = app_state.settings.lock();
-                                            let db_path = crate::settings::Settings::config_path()
-                                                .unwrap()
-                                                .parent()
-                                                .unwrap()
-                                                .join("vector_index.db");
-                                            crate::indexer::IndexConfig {
-                                                db_path,
-                                                cpu_mode: if settings.indexing_cpu_mode == "fast" {
-                                                    crate::indexer::CpuMode::Fast
-                                                } else {
-                                                    crate::indexer::CpuMode::Normal
-                                                },
-                                                screenshot_dir: settings.screenshot_directory.clone(),
-                                            }
-                                        };
-                                        // Get prewarmed models if available
-                                        let vision_model = PREWARMED_VISION_MODEL.lock().clone();
-                                        let text_model = PREWARMED_TEXT_MODEL.lock().clone();
-                                        crate::indexer::start_indexing(config, tx, false, vision_model, text_model);  // false = only new files
-                                        cx.notify();
-                                    })),
-                            )
-                    )
-            })
-    }
-
-    fn render_hotkey_settings(
-        &self,
-        settings: &crate::settings::Settings,
-        cx: &mut Context<Self>,
-    ) -> impl IntoElement {
-        let hotkey_enabled = settings.hotkey_enabled;
-        let hotkey_str = settings.hotkey.clone();
-        let recording = self.recording_hotkey;
-
-        v_flex()
-            .w_full()
-            .gap_2()
-            .child(self.render_section_header(&t!("settings.hotkey.title").to_string(), cx))
-            // Enable toggle
+            // Performance trace
+            .child(self.render_section_header(&t!("settings.advanced.perf.title").to_string(), cx))
             .child(
                 self.render_setting_row(
-                    &t!("settings.hotkey.enable_label").to_string(),
-                    Some(&t!("settings.hotkey.enable_desc").to_string()),
-                    Switch::new("hotkey-enable")
-                        .checked(hotkey_enabled)
-                        .on_click(cx.listener(|_this, checked: &bool, _, cx| {
+                    &t!("settings.advanced.perf.enable_label").to_string(),
+                    Some(&t!("settings.advanced.perf.enable_desc").to_string()),
+                    self.settings_switch(
+                        "perf-trace",
+                        perf_trace,
+                        true,
+                        |_this, checked, _, cx| {
                             {
                                 let app_state = cx.global::<AppState>();
                                 let mut settings = app_state.settings.lock();
-                                settings.hotkey_enabled = *checked;
+                                settings.perf_trace = checked;
                                 let _ = settings.save();
                             }
+                            perf::set_enabled(checked);
                             cx.notify();
-                        })),
+                        },
+                        cx,
+                    ),
                     cx,
                 ),
             )
-            // Current hotkey display + record button
+            // Reset
             .child(
-                v_flex()
-                    .w_full()
-                    .gap_2()
-                    .mb_4()
-                    .child(
-                        h_flex()
-                            .w_full()
-                            .justify_between()
-                            .items_center()
-                            .child(
-                                div()
-                                    .text_sm()
-                                    .font_weight(FontWeight::MEDIUM)
-                                    .text_color(cx.theme().foreground)
-                                    .child(t!("settings.hotkey.current_label").to_string()),
-                            )
-                            .child(
-                                h_flex()
-                                    .gap_2()
-                                    .items_center()
-                                    .child(
-                                        div()
-                                            .px_3()
-                                            .py_1()
-                                            .rounded(px(6.0))
-                                            .bg(if recording {
-                                                cx.theme().primary
-                                            } else {
-                                                cx.theme().muted
-                                            })
-                                            .text_sm()
-                                            .font_weight(FontWeight::SEMIBOLD)
-                                            .text_color(if recording {
-                                                cx.theme().primary_foreground
-                                            } else {
-                                                cx.theme().foreground
-                                            })
-                                            .child(if recording {
-                                                t!("settings.hotkey.recording").to_string()
-                                            } else {
-                                                hotkey_str
-                                            }),
-                                    )
-                                    .child(
-                                        Button::new("record-hotkey")
-                                            .small()
-                                            .when(recording, |s| s.danger())
-                                            .when(!recording, |s| s.outline())
-                                            .label(&if recording { t!("settings.hotkey.cancel_button").to_string() } else { t!("settings.hotkey.record_button").to_string() })
-                                            .on_click(cx.listener(|this, _, _, cx| {
-                                                this.recording_hotkey = !this.recording_hotkey;
-                                                cx.notify();
-                                            })),
-                                    ),
-                            ),
-                    )
-                    .child(
-                        div()
-                            .text_xs()
-                            .text_color(cx.theme().muted_foreground)
-                            .child(t!("settings.hotkey.examples").to_string()),
+                h_flex().w_full().justify_end().child(
+                    self.settings_button(
+                        "reset-advanced",
+                        true,
+                        |b| {
+                            b.small()
+                                .outline()
+                                .label(&t!("settings.advanced.reset_button").to_string())
+                        },
+                        |this, window, cx| {
+                            let (cap, port) = {
+                                let app_state = cx.global::<AppState>();
+                                let mut settings = app_state.settings.lock();
+                                settings.reset_advanced();
+                                let _ = settings.save();
+                                convert_pool::set_workers(settings.conversion_workers);
+                                (settings.max_gallery_items, settings.api_server_port)
+                            };
+                            crate::server::stop();
+                            this.api_port_error = false;
+                            this.api_port_input.update(cx, |input, cx| {
+                                input.set_value(port.to_string(), window, cx);
+                            });
+                            perf::set_enabled(false);
+                            this.screenshots.set_cap(cap);
+                            this.trim_screenshots();
+                            cx.notify();
+                        },
+                        cx,
                     ),
+                ),
             )
     }
 
@@ -3115,20 +10186,30 @@ impl Sukusho {
                     .gap_2()
                     .mt_4()
                     .child(
-                        Button::new("github")
-                            .outline()
-                            .small()
-                            .label(&t!("settings.about.github_button").to_string())
-                            .on_click(|_, _, cx| {
+                        self.settings_button(
+                            "github",
+                            true,
+                            |b| {
+                                b.outline()
+                                    .small()
+                                    .label(&t!("settings.about.github_button").to_string())
+                            },
+                            |_this, _, cx| {
                                 cx.open_url("https://github.com/ssut/sukusho");
-                            }),
+                            },
+                            cx,
+                        ),
                     )
                     .child(
-                        Button::new("check-updates")
-                            .outline()
-                            .small()
-                            .label(&t!("settings.about.check_updates_button").to_string())
-                            .on_click(cx.listener(|_this, _, _, _cx| {
+                        self.settings_button(
+                            "check-updates",
+                            true,
+                            |b| {
+                                b.outline()
+                                    .small()
+                                    .label(&t!("settings.about.check_updates_button").to_string())
+                            },
+                            |_this, _, _cx| {
                                 info!("Check for updates requested from About settings");
                                 std::thread::spawn(|| {
                                     use crate::update_checker;
@@ -3149,8 +10230,55 @@ impl Sukusho {
                                         }
                                     }
                                 });
-                            })),
-                    ),
+                            },
+                            cx,
+                        ),
+                    )
+                    .child(
+                        self.settings_button(
+                            "diagnostic-bundle",
+                            !self.creating_bundle,
+                            |b| {
+                                b.outline()
+                                    .small()
+                                    .label(&t!("settings.about.diagnostic_bundle_button").to_string())
+                            },
+                            |this, _, cx| this.create_diagnostic_bundle(cx),
+                            cx,
+                        ),
+                    )
+                    // Only once a trace has been recorded
+                    .when(perf::has_frames(), |el| {
+                        el.child(
+                            self.settings_button(
+                                "export-perf-trace",
+                                true,
+                                |b| {
+                                    b.outline()
+                                        .small()
+                                        .label(&t!("settings.about.export_perf_trace_button").to_string())
+                                },
+                                |this, _, cx| match perf::export_csv() {
+                                    Ok(path) => {
+                                        info!("Exported performance trace to {:?}", path);
+                                        crate::ui::progress::reveal_in_explorer(&path);
+                                        this.show_toast(
+                                            t!("notifications.perf_trace.exported").to_string(),
+                                            cx,
+                                        );
+                                    }
+                                    Err(e) => {
+                                        log::warn!("Failed to export performance trace: {}", e);
+                                        this.show_toast(
+                                            t!("notifications.perf_trace.export_failed").to_string(),
+                                            cx,
+                                        );
+                                    }
+                                },
+                                cx,
+                            ),
+                        )
+                    }),
             )
             // Copyright
             .child(
@@ -3162,3 +10290,92 @@ impl Sukusho {
             )
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::scratch_dir;
+
+    #[test]
+    fn test_selection_follows_moved_file() {
+        let png = PathBuf::from("shot.png");
+        let webp = PathBuf::from("2026-10-15/shot.webp");
+        let other = PathBuf::from("other.png");
+
+        let mut selected = HashSet::from([png.clone(), other.clone()]);
+        let mut cleanup_selected = HashSet::from([png.clone()]);
+        let mut last_selected = Some(png.clone());
+        let mut focused = Some(png.clone());
+        let mut details_path = Some(png.clone());
+        let mut preview = Some(png.clone());
+        PathState {
+            selected: &mut selected,
+            cleanup_selected: &mut cleanup_selected,
+            last_selected: &mut last_selected,
+            focused: &mut focused,
+            details_path: &mut details_path,
+            preview: &mut preview,
+        }
+        .follow_move(&png, &webp);
+
+        assert_eq!(selected, HashSet::from([webp.clone(), other.clone()]));
+        assert_eq!(cleanup_selected, HashSet::from([webp.clone()]));
+        assert_eq!(last_selected, Some(webp.clone()));
+        assert_eq!(focused, Some(webp.clone()));
+        assert_eq!(details_path, Some(webp.clone()));
+        assert_eq!(preview, Some(webp.clone()));
+    }
+
+    #[test]
+    fn test_unrelated_move_leaves_selection_alone() {
+        let selected_path = PathBuf::from("keep.png");
+        let mut selected = HashSet::from([selected_path.clone()]);
+        let mut cleanup_selected = HashSet::new();
+        let mut last_selected = Some(selected_path.clone());
+        let mut focused = Some(selected_path.clone());
+        let mut details_path = None;
+        let mut preview = Some(PathBuf::from("b.png"));
+        PathState {
+            selected: &mut selected,
+            cleanup_selected: &mut cleanup_selected,
+            last_selected: &mut last_selected,
+            focused: &mut focused,
+            details_path: &mut details_path,
+            preview: &mut preview,
+        }
+        .follow_move(&PathBuf::from("a.png"), &PathBuf::from("a.webp"));
+
+        assert_eq!(selected, HashSet::from([selected_path.clone()]));
+        assert!(cleanup_selected.is_empty());
+        assert_eq!(last_selected, Some(selected_path.clone()));
+        assert_eq!(focused, Some(selected_path));
+        assert_eq!(details_path, None);
+        assert_eq!(preview, Some(PathBuf::from("b.png")));
+    }
+
+    #[test]
+    fn test_context_menu_confirm_threshold() {
+        assert!(!needs_context_menu_confirm(100, 100));
+        assert!(needs_context_menu_confirm(101, 100));
+        assert!(!needs_context_menu_confirm(1, 100));
+        // 0 turns the guard off
+        assert!(!needs_context_menu_confirm(5000, 0));
+    }
+
+    #[test]
+    fn test_info_summary_waits_for_probe() {
+        let dir = scratch_dir("info");
+        let path = dir.join("shot.png");
+        image::RgbaImage::new(6, 4).save(&path).unwrap();
+
+        assert_eq!(info_summary(&path, false), None);
+        let summary = info_summary(&path, true).unwrap();
+        assert!(summary.contains("6×4"));
+        assert!(summary.contains("PNG"));
+        assert!(summary.ends_with(&path.display().to_string()));
+        // Read from the cache the second time
+        assert_eq!(info_summary(&path, false), Some(summary));
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
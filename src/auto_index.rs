@@ -0,0 +1,157 @@
+//! New screenshots waiting to be auto-indexed
+//!
+//! A capture that's looked at and deleted right away shouldn't cost an
+//! embedding run, or leave a row for a file that's gone. New screenshots wait
+//! here for [`AUTO_INDEX_DELAY`] (restarted by each new arrival, so a burst is
+//! indexed in one run, though never more than [`AUTO_INDEX_MAX_WAIT`] after
+//! the first of it); one removed in the meantime is dropped and never
+//! reaches the indexer. A batch that comes due while the indexer is busy is
+//! held until it's free rather than started alongside it.
+
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+/// How long a new screenshot waits before it's indexed
+pub const AUTO_INDEX_DELAY: Duration = Duration::from_secs(3);
+
+/// Longest a batch waits, so a steady stream of captures can't keep pushing
+/// it back
+pub const AUTO_INDEX_MAX_WAIT: Duration = Duration::from_secs(30);
+
+#[derive(Debug, Default)]
+pub struct PendingIndex {
+    paths: HashSet<PathBuf>,
+    /// First arrival of the waiting batch; it's due [`AUTO_INDEX_MAX_WAIT`]
+    /// after this at the latest
+    first_added: Option<Instant>,
+    /// Newest arrival; the batch is due [`AUTO_INDEX_DELAY`] after it
+    last_added: Option<Instant>,
+    /// Due, but waiting for the indexer to finish what it's doing
+    held: Vec<PathBuf>,
+}
+
+impl PendingIndex {
+    pub fn add(&mut self, path: PathBuf, now: Instant) {
+        if self.paths.is_empty() {
+            self.first_added = Some(now);
+        }
+        self.paths.insert(path);
+        self.last_added = Some(now);
+    }
+
+    /// Drop a screenshot that's gone; returns whether it was waiting
+    pub fn remove(&mut self, path: &Path) -> bool {
+        let held = self.held.len();
+        self.held.retain(|p| p != path);
+        self.paths.remove(path) || self.held.len() < held
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.paths.is_empty() && self.held.is_empty()
+    }
+
+    /// Keep a due batch until the indexer is free
+    pub fn hold(&mut self, paths: Vec<PathBuf>) {
+        for path in paths {
+            if !self.held.contains(&path) {
+                self.held.push(path);
+            }
+        }
+    }
+
+    /// Take the held screenshots once the indexer is free
+    pub fn take_held(&mut self) -> Vec<PathBuf> {
+        std::mem::take(&mut self.held)
+    }
+
+    /// How long until the batch is due, if anything is waiting
+    pub fn next_due(&self, now: Instant) -> Option<Duration> {
+        if self.paths.is_empty() {
+            return None;
+        }
+        let due = (self.last_added? + AUTO_INDEX_DELAY).min(self.first_added? + AUTO_INDEX_MAX_WAIT);
+        Some(due.saturating_duration_since(now))
+    }
+
+    /// Take everything waiting once the batch is due
+    pub fn take_due(&mut self, now: Instant) -> Vec<PathBuf> {
+        if self.next_due(now) != Some(Duration::ZERO) {
+            return Vec::new();
+        }
+        self.first_added = None;
+        self.last_added = None;
+        self.paths.drain().collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_deleted_before_due_is_dropped() {
+        let t0 = Instant::now();
+        let mut pending = PendingIndex::default();
+        assert_eq!(pending.next_due(t0), None);
+
+        pending.add("a.png".into(), t0);
+        pending.add("b.png".into(), t0 + Duration::from_secs(1));
+        assert_eq!(pending.next_due(t0 + Duration::from_secs(1)), Some(AUTO_INDEX_DELAY));
+
+        // Captured, glanced at, deleted
+        assert!(pending.remove(Path::new("b.png")));
+        assert!(pending.take_due(t0 + Duration::from_secs(2)).is_empty());
+
+        let due = pending.take_due(t0 + Duration::from_secs(1) + AUTO_INDEX_DELAY);
+        assert_eq!(due, vec![PathBuf::from("a.png")]);
+        assert!(pending.is_empty());
+    }
+
+    #[test]
+    fn test_steady_stream_due_after_max_wait() {
+        let t0 = Instant::now();
+        let mut pending = PendingIndex::default();
+        // A capture every second, each pushing the delay back
+        let mut now = t0;
+        while now < t0 + AUTO_INDEX_MAX_WAIT {
+            pending.add(format!("{:?}.png", now - t0).into(), now);
+            assert!(pending.take_due(now).is_empty());
+            now += Duration::from_secs(1);
+        }
+        pending.add("last.png".into(), now);
+        assert_eq!(pending.next_due(now), Some(Duration::ZERO));
+        let due = pending.take_due(now);
+        assert_eq!(due.len(), AUTO_INDEX_MAX_WAIT.as_secs() as usize + 1);
+
+        // The next batch waits from its own first arrival
+        pending.add("next.png".into(), now + Duration::from_secs(1));
+        assert_eq!(pending.next_due(now + Duration::from_secs(1)), Some(AUTO_INDEX_DELAY));
+    }
+
+    #[test]
+    fn test_everything_deleted() {
+        let t0 = Instant::now();
+        let mut pending = PendingIndex::default();
+        pending.add("a.png".into(), t0);
+        pending.remove(Path::new("a.png"));
+        assert_eq!(pending.next_due(t0 + AUTO_INDEX_DELAY), None);
+        assert!(pending.take_due(t0 + AUTO_INDEX_DELAY).is_empty());
+    }
+
+    #[test]
+    fn test_held_while_indexer_busy() {
+        let t0 = Instant::now();
+        let mut pending = PendingIndex::default();
+        pending.add("a.png".into(), t0);
+        pending.add("b.png".into(), t0);
+        pending.hold(pending.take_due(t0 + AUTO_INDEX_DELAY));
+        pending.hold(vec!["a.png".into()]);
+        assert!(!pending.is_empty());
+
+        // Deleted while held
+        assert!(pending.remove(Path::new("b.png")));
+        assert_eq!(pending.take_held(), vec![PathBuf::from("a.png")]);
+        assert!(pending.is_empty());
+    }
+}
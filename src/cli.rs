@@ -0,0 +1,519 @@
+//! Subcommands for scripts: `sukusho search` and `sukusho stats`
+//!
+//! These run without the window or tray, next to a running instance if there
+//! is one. Output goes to stdout, one line per item by default; `--json`
+//! prints a single JSON document instead, with a `version` field for its
+//! schema:
+//!
+//! ```json
+//! {"version": 1, "query": "blue dashboard", "results": [
+//!   {"path": "C:\\...\\shot.png", "score": 0.82, "mtime": "2024-01-15T10:30:00Z", "size": 48213}
+//! ]}
+//! ```
+//!
+//! Exit codes: [`EXIT_FOUND`] when there are results (or screenshots, for
+//! `stats`), [`EXIT_NONE`] when there are none, [`EXIT_ERROR`] when the
+//! command failed; the error goes to stderr.
+
+use anyhow::{Context, Result};
+use serde::Serialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use crate::indexer;
+use crate::paths;
+use crate::settings::Settings;
+use crate::watcher::ScreenshotWatcher;
+
+/// Version of the JSON schema printed with `--json`
+pub const SCHEMA_VERSION: u64 = 1;
+
+/// Results found
+pub const EXIT_FOUND: i32 = 0;
+/// Ran fine, but nothing matched (or the folder is empty)
+pub const EXIT_NONE: i32 = 1;
+/// Bad arguments, search indexing off, or the index couldn't be read
+pub const EXIT_ERROR: i32 = 2;
+
+/// Matches printed by `search` when no `--limit` is given
+const DEFAULT_LIMIT: usize = 20;
+
+const USAGE: &str =
+    "usage: sukusho search <query> [--limit N] [--json]\n       sukusho stats [--json]";
+
+/// How results are printed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    /// One line per item
+    Text,
+    /// One JSON document
+    Json,
+}
+
+/// A subcommand and its arguments
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Subcommand {
+    Search {
+        query: String,
+        limit: usize,
+        format: Format,
+    },
+    Stats {
+        format: Format,
+    },
+}
+
+/// The subcommand the arguments ask for: `None` when the first argument
+/// isn't one (start the app as usual), what's wrong when its arguments are
+pub fn from_args(args: &[String]) -> Option<Result<Subcommand, String>> {
+    let name = args.get(1)?;
+    if name != "search" && name != "stats" {
+        return None;
+    }
+
+    let mut format = Format::Text;
+    let mut limit = DEFAULT_LIMIT;
+    let mut words = Vec::new();
+    let mut rest = args[2..].iter();
+    while let Some(arg) = rest.next() {
+        match arg.as_str() {
+            "--json" => format = Format::Json,
+            "--limit" if name == "search" => {
+                match rest
+                    .next()
+                    .and_then(|n| n.parse::<usize>().ok())
+                    .filter(|n| *n > 0)
+                {
+                    Some(n) => limit = n,
+                    None => return Some(Err("--limit needs a positive number".to_string())),
+                }
+            }
+            flag if flag.starts_with("--") => {
+                return Some(Err(format!("unknown option {}", flag)));
+            }
+            word => words.push(word),
+        }
+    }
+
+    Some(match name.as_str() {
+        "search" => {
+            let query = words.join(" ");
+            let query = query.trim();
+            if query.is_empty() {
+                Err("search needs a query".to_string())
+            } else {
+                Ok(Subcommand::Search {
+                    query: query.to_string(),
+                    limit,
+                    format,
+                })
+            }
+        }
+        _ if words.is_empty() => Ok(Subcommand::Stats { format }),
+        _ => Err("stats takes no arguments".to_string()),
+    })
+}
+
+/// Printed by `search`
+#[derive(Debug, Serialize)]
+pub struct SearchReport {
+    version: u64,
+    query: String,
+    results: Vec<SearchHit>,
+}
+
+#[derive(Debug, Serialize)]
+struct SearchHit {
+    path: PathBuf,
+    /// Cosine similarity to the query, 0 to 1
+    score: f32,
+    /// Last modified, RFC 3339 in UTC
+    mtime: String,
+    /// Bytes
+    size: u64,
+}
+
+/// Printed by `stats`
+#[derive(Debug, Serialize)]
+pub struct StatsReport {
+    version: u64,
+    directory: PathBuf,
+    screenshots: usize,
+    /// Bytes, all screenshots together
+    total_size: u64,
+    /// Rows in the search index (0 when indexing is off)
+    indexed: usize,
+    newest: Option<String>,
+    oldest: Option<String>,
+}
+
+/// What the output layer needs from a report besides its JSON form
+pub trait Report: Serialize {
+    /// The line-oriented form
+    fn lines(&self) -> Vec<String>;
+    /// Nothing found, for the exit code
+    fn is_empty(&self) -> bool;
+}
+
+impl Report for SearchReport {
+    fn lines(&self) -> Vec<String> {
+        self.results
+            .iter()
+            .map(|hit| format!("{:.2}\t{}", hit.score, hit.path.display()))
+            .collect()
+    }
+
+    fn is_empty(&self) -> bool {
+        self.results.is_empty()
+    }
+}
+
+impl Report for StatsReport {
+    fn lines(&self) -> Vec<String> {
+        let mut lines = vec![
+            format!("directory\t{}", self.directory.display()),
+            format!("screenshots\t{}", self.screenshots),
+            format!("total_size\t{}", self.total_size),
+            format!("indexed\t{}", self.indexed),
+        ];
+        lines.extend(self.newest.as_ref().map(|at| format!("newest\t{}", at)));
+        lines.extend(self.oldest.as_ref().map(|at| format!("oldest\t{}", at)));
+        lines
+    }
+
+    fn is_empty(&self) -> bool {
+        self.screenshots == 0
+    }
+}
+
+/// A report as printed in `format`
+pub fn render(report: &impl Report, format: Format) -> String {
+    match format {
+        Format::Text => report.lines().join("\n"),
+        Format::Json => serde_json::to_string_pretty(report).unwrap_or_default(),
+    }
+}
+
+/// Run a subcommand from [`from_args`], printing its output; returns the
+/// exit code
+pub fn run(command: Result<Subcommand, String>) -> i32 {
+    let command = match command {
+        Ok(command) => command,
+        Err(problem) => {
+            eprintln!("sukusho: {}\n{}", problem, USAGE);
+            return EXIT_ERROR;
+        }
+    };
+
+    let settings = Settings::load().unwrap_or_default();
+    crate::exclude::configure(&settings.exclude_patterns);
+    let outcome = match command {
+        Subcommand::Search {
+            query,
+            limit,
+            format,
+        } => search(&settings, &query, limit)
+            .map(|report| (render(&report, format), report.is_empty())),
+        Subcommand::Stats { format } => {
+            stats(&settings).map(|report| (render(&report, format), report.is_empty()))
+        }
+    };
+
+    match outcome {
+        Ok((output, empty)) => {
+            if !output.is_empty() {
+                println!("{}", output);
+            }
+            if empty { EXIT_NONE } else { EXIT_FOUND }
+        }
+        Err(e) => {
+            eprintln!("sukusho: {:#}", e);
+            EXIT_ERROR
+        }
+    }
+}
+
+/// Print to the console this was started from. The app has no console of
+/// its own; output redirected to a file or pipe already has somewhere to go.
+#[cfg(windows)]
+pub fn attach_parent_console() {
+    use windows::Win32::System::Console::{
+        ATTACH_PARENT_PROCESS, AttachConsole, GetStdHandle, STD_OUTPUT_HANDLE,
+    };
+
+    unsafe {
+        let redirected = GetStdHandle(STD_OUTPUT_HANDLE).is_ok_and(|handle| !handle.is_invalid());
+        if !redirected {
+            let _ = AttachConsole(ATTACH_PARENT_PROCESS);
+        }
+    }
+}
+
+#[cfg(not(windows))]
+pub fn attach_parent_console() {}
+
+fn search(settings: &Settings, query: &str, limit: usize) -> Result<SearchReport> {
+    let matches = crate::app::search_index(settings, query, limit)?;
+    // Rows for files that are gone since they were indexed are left out
+    let results = matches
+        .into_iter()
+        .filter_map(|(path, distance)| {
+            let (modified, size) = file_info(&path)?;
+            Some(SearchHit {
+                path,
+                score: similarity(distance),
+                mtime: timestamp(modified),
+                size,
+            })
+        })
+        .collect();
+    Ok(SearchReport {
+        version: SCHEMA_VERSION,
+        query: query.to_string(),
+        results,
+    })
+}
+
+fn stats(settings: &Settings) -> Result<StatsReport> {
+    let directory = settings.screenshot_directory.clone();
+    if !paths::long_path(&directory).is_dir() {
+        anyhow::bail!("Screenshot folder {} doesn't exist", directory.display());
+    }
+    let files: Vec<(SystemTime, u64)> = ScreenshotWatcher::collect_images(&directory)
+        .iter()
+        .filter_map(|path| file_info(path))
+        .collect();
+    let indexed = if settings.indexing_enabled {
+        indexed_count(settings)?
+    } else {
+        0
+    };
+    Ok(StatsReport {
+        version: SCHEMA_VERSION,
+        directory,
+        screenshots: files.len(),
+        total_size: files.iter().map(|(_, size)| size).sum(),
+        indexed,
+        newest: files
+            .iter()
+            .map(|(modified, _)| *modified)
+            .max()
+            .map(timestamp),
+        oldest: files
+            .iter()
+            .map(|(modified, _)| *modified)
+            .min()
+            .map(timestamp),
+    })
+}
+
+/// Rows in the search index; 0 before anything was indexed
+fn indexed_count(settings: &Settings) -> Result<usize> {
+    let config = crate::app::index_config(settings).context("No config directory")?;
+    if !config.db_path.exists() {
+        return Ok(0);
+    }
+    indexer::get_indexed_count(&config)
+}
+
+fn file_info(path: &Path) -> Option<(SystemTime, u64)> {
+    let metadata = fs::metadata(paths::long_path(path)).ok()?;
+    Some((metadata.modified().ok()?, metadata.len()))
+}
+
+/// Cosine distance as a similarity score, to 4 places
+fn similarity(distance: f32) -> f32 {
+    ((1.0 - distance).clamp(0.0, 1.0) * 10_000.0).round() / 10_000.0
+}
+
+fn timestamp(time: SystemTime) -> String {
+    chrono::DateTime::<chrono::Utc>::from(time).to_rfc3339_opts(chrono::SecondsFormat::Secs, true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::{Duration, UNIX_EPOCH};
+
+    fn args(line: &str) -> Vec<String> {
+        std::iter::once("sukusho.exe")
+            .chain(line.split_whitespace())
+            .map(String::from)
+            .collect()
+    }
+
+    /// 2024-01-15 10:30:00 UTC
+    fn jan_15() -> SystemTime {
+        UNIX_EPOCH + Duration::from_secs(1_705_314_600)
+    }
+
+    #[test]
+    fn test_from_args() {
+        assert_eq!(
+            from_args(&args("search blue dashboard --json")),
+            Some(Ok(Subcommand::Search {
+                query: "blue dashboard".to_string(),
+                limit: DEFAULT_LIMIT,
+                format: Format::Json,
+            }))
+        );
+        assert_eq!(
+            from_args(&args("search --limit 5 cat")),
+            Some(Ok(Subcommand::Search {
+                query: "cat".to_string(),
+                limit: 5,
+                format: Format::Text,
+            }))
+        );
+        assert_eq!(
+            from_args(&args("stats")),
+            Some(Ok(Subcommand::Stats {
+                format: Format::Text
+            }))
+        );
+        assert_eq!(
+            from_args(&args("stats --json")),
+            Some(Ok(Subcommand::Stats {
+                format: Format::Json
+            }))
+        );
+
+        assert!(matches!(from_args(&args("search")), Some(Err(_))));
+        assert!(matches!(
+            from_args(&args("search cat --limit 0")),
+            Some(Err(_))
+        ));
+        assert!(matches!(
+            from_args(&args("search cat --limit")),
+            Some(Err(_))
+        ));
+        assert!(matches!(from_args(&args("stats --limit 5")), Some(Err(_))));
+        assert!(matches!(from_args(&args("stats extra")), Some(Err(_))));
+
+        // Not a subcommand: the app starts as usual
+        assert_eq!(from_args(&args("")), None);
+        assert_eq!(from_args(&args("--console")), None);
+        assert_eq!(from_args(&args("search-ui blue dashboard")), None);
+    }
+
+    #[test]
+    fn test_search_output() {
+        let report = SearchReport {
+            version: SCHEMA_VERSION,
+            query: "blue dashboard".to_string(),
+            results: vec![
+                SearchHit {
+                    path: PathBuf::from("shots/dashboard.png"),
+                    score: similarity(0.18),
+                    mtime: timestamp(jan_15()),
+                    size: 48213,
+                },
+                SearchHit {
+                    path: PathBuf::from("shots/2024-01-14/chart.webp"),
+                    score: similarity(0.4),
+                    mtime: timestamp(jan_15() - Duration::from_secs(24 * 60 * 60)),
+                    size: 1200,
+                },
+            ],
+        };
+        assert!(!report.is_empty());
+
+        assert_eq!(
+            render(&report, Format::Json),
+            r#"{
+  "version": 1,
+  "query": "blue dashboard",
+  "results": [
+    {
+      "path": "shots/dashboard.png",
+      "score": 0.82,
+      "mtime": "2024-01-15T10:30:00Z",
+      "size": 48213
+    },
+    {
+      "path": "shots/2024-01-14/chart.webp",
+      "score": 0.6,
+      "mtime": "2024-01-14T10:30:00Z",
+      "size": 1200
+    }
+  ]
+}"#
+        );
+        assert_eq!(
+            render(&report, Format::Text),
+            "0.82\tshots/dashboard.png\n0.60\tshots/2024-01-14/chart.webp"
+        );
+
+        let none = SearchReport {
+            version: SCHEMA_VERSION,
+            query: "nothing".to_string(),
+            results: Vec::new(),
+        };
+        assert!(none.is_empty());
+        assert_eq!(
+            render(&none, Format::Json),
+            r#"{
+  "version": 1,
+  "query": "nothing",
+  "results": []
+}"#
+        );
+        assert_eq!(render(&none, Format::Text), "");
+    }
+
+    #[test]
+    fn test_stats_output() {
+        let report = StatsReport {
+            version: SCHEMA_VERSION,
+            directory: PathBuf::from("shots"),
+            screenshots: 3,
+            total_size: 123_456,
+            indexed: 2,
+            newest: Some(timestamp(jan_15())),
+            oldest: Some(timestamp(UNIX_EPOCH + Duration::from_secs(1_672_531_200))),
+        };
+        assert!(!report.is_empty());
+
+        assert_eq!(
+            render(&report, Format::Json),
+            r#"{
+  "version": 1,
+  "directory": "shots",
+  "screenshots": 3,
+  "total_size": 123456,
+  "indexed": 2,
+  "newest": "2024-01-15T10:30:00Z",
+  "oldest": "2023-01-01T00:00:00Z"
+}"#
+        );
+        assert_eq!(
+            render(&report, Format::Text),
+            "directory\tshots\nscreenshots\t3\ntotal_size\t123456\nindexed\t2\n\
+             newest\t2024-01-15T10:30:00Z\noldest\t2023-01-01T00:00:00Z"
+        );
+
+        let empty = StatsReport {
+            version: SCHEMA_VERSION,
+            directory: PathBuf::from("shots"),
+            screenshots: 0,
+            total_size: 0,
+            indexed: 0,
+            newest: None,
+            oldest: None,
+        };
+        assert!(empty.is_empty());
+        assert_eq!(
+            render(&empty, Format::Json),
+            r#"{
+  "version": 1,
+  "directory": "shots",
+  "screenshots": 0,
+  "total_size": 0,
+  "indexed": 0,
+  "newest": null,
+  "oldest": null
+}"#
+        );
+    }
+}
@@ -4,18 +4,61 @@
 
 use log::{debug, error, info};
 use std::path::PathBuf;
+#[cfg(windows)]
+use std::time::Duration;
 
 #[cfg(windows)]
 use windows::Win32::{
-    Foundation::{BOOL, HANDLE, POINT},
+    Foundation::{GlobalFree, BOOL, HANDLE, HGLOBAL, POINT},
     System::{
-        DataExchange::{CloseClipboard, EmptyClipboard, OpenClipboard, SetClipboardData},
+        DataExchange::{
+            CloseClipboard, EmptyClipboard, OpenClipboard, SetClipboardData,
+        },
         Memory::{GlobalAlloc, GlobalLock, GlobalUnlock, GHND},
         Ole::CF_HDROP,
     },
     UI::Shell::DROPFILES,
 };
 
+/// Tries at opening the clipboard while another program has it open
+#[cfg(windows)]
+const OPEN_ATTEMPTS: u32 = 10;
+
+/// Wait between tries; all of them take about 500 ms, like Explorer
+#[cfg(windows)]
+const OPEN_RETRY_DELAY: Duration = Duration::from_millis(50);
+
+/// The open clipboard, closed again on drop whatever happens in between
+#[cfg(windows)]
+struct OpenClipboardGuard;
+
+#[cfg(windows)]
+impl OpenClipboardGuard {
+    /// Open the clipboard, retrying while another process holds it (it's
+    /// usually only for a few milliseconds)
+    fn open() -> Option<Self> {
+        for attempt in 1..=OPEN_ATTEMPTS {
+            // SAFETY: No owner window; closed by Drop
+            if unsafe { OpenClipboard(None) }.is_ok() {
+                return Some(Self);
+            }
+            debug!("Clipboard busy (attempt {}/{})", attempt, OPEN_ATTEMPTS);
+            if attempt < OPEN_ATTEMPTS {
+                std::thread::sleep(OPEN_RETRY_DELAY);
+            }
+        }
+        None
+    }
+}
+
+#[cfg(windows)]
+impl Drop for OpenClipboardGuard {
+    fn drop(&mut self) {
+        // SAFETY: Opened in `open`
+        let _ = unsafe { CloseClipboard() };
+    }
+}
+
 /// Copy files to clipboard using CF_HDROP format
 /// This allows pasting files in Explorer and other applications
 #[cfg(windows)]
@@ -29,43 +72,39 @@ pub fn copy_files_to_clipboard(files: &[PathBuf]) -> bool {
         debug!("  - {:?}", file);
     }
 
-    unsafe {
-        // Open clipboard
-        if OpenClipboard(None).is_err() {
-            error!("Failed to open clipboard");
-            return false;
-        }
-
-        // Empty clipboard
-        if EmptyClipboard().is_err() {
-            error!("Failed to empty clipboard");
-            let _ = CloseClipboard();
-            return false;
-        }
-
-        // Create HDROP data
-        let hdrop = match create_hdrop(files) {
-            Some(h) => h,
-            None => {
-                error!("Failed to create HDROP data");
-                let _ = CloseClipboard();
-                return false;
-            }
-        };
-
-        // Set clipboard data
-        let result = SetClipboardData(CF_HDROP.0 as u32, hdrop);
-        let success = result.is_ok();
+    // Built before opening the clipboard, to hold it as briefly as possible
+    // SAFETY: Freed below unless the clipboard takes it
+    let Some(hdrop) = (unsafe { create_hdrop(files) }) else {
+        error!("Failed to create HDROP data");
+        return false;
+    };
+    let free_hdrop = || {
+        // SAFETY: Allocated by create_hdrop and not owned by the clipboard
+        let _ = unsafe { GlobalFree(HGLOBAL(hdrop.0)) };
+    };
+
+    let Some(_clipboard) = OpenClipboardGuard::open() else {
+        error!("Failed to open clipboard: another program kept it open");
+        free_hdrop();
+        return false;
+    };
 
-        if success {
-            info!("Successfully copied files to clipboard");
-        } else {
-            error!("Failed to set clipboard data: {:?}", result);
-        }
+    // SAFETY: The clipboard is open
+    if unsafe { EmptyClipboard() }.is_err() {
+        error!("Failed to empty clipboard");
+        free_hdrop();
+        return false;
+    }
 
-        let _ = CloseClipboard();
-        success
+    // SAFETY: The clipboard is open; on success it owns hdrop
+    if let Err(e) = unsafe { SetClipboardData(CF_HDROP.0 as u32, hdrop) } {
+        error!("Failed to set clipboard data: {:?}", e);
+        free_hdrop();
+        return false;
     }
+
+    info!("Successfully copied files to clipboard");
+    true
 }
 
 /// Create DROPFILES structure in global memory
@@ -99,6 +138,8 @@ unsafe fn create_hdrop(files: &[PathBuf]) -> Option<HANDLE> {
     // SAFETY: Locking global memory to write data
     let ptr = unsafe { GlobalLock(hglobal) };
     if ptr.is_null() {
+        // SAFETY: Allocated above and not handed out
+        let _ = unsafe { GlobalFree(hglobal) };
         return None;
     }
 
@@ -0,0 +1,370 @@
+//! When a screenshot was really taken, from inside the file
+//!
+//! Editing a screenshot in another program bumps its mtime, which moves it in
+//! the gallery and puts it in the wrong organizer folder. The capture date
+//! usually survives somewhere: EXIF `DateTimeOriginal` (JPEG, WebP, or a PNG
+//! `eXIf` chunk), a PNG "Creation Time" text chunk, or the file name. "Fix
+//! timestamp" reads the best of those and writes it back as the mtime.
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Local, NaiveDateTime, TimeZone};
+use filetime::{set_file_mtime, FileTime};
+use regex::Regex;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+use crate::filename_date;
+use crate::paths;
+
+/// Differences below this are left alone (file systems round mtimes)
+const TOLERANCE: Duration = Duration::from_secs(2);
+
+/// EXIF tags read
+const EXIF_IFD_POINTER: u16 = 0x8769;
+const DATE_TIME_ORIGINAL: u16 = 0x9003;
+
+/// Where a content date came from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DateSource {
+    Exif,
+    PngText,
+    FileName,
+}
+
+impl DateSource {
+    pub fn display_name(&self) -> String {
+        match self {
+            DateSource::Exif => t!("gallery.fix_timestamps.source.exif").to_string(),
+            DateSource::PngText => t!("gallery.fix_timestamps.source.png_text").to_string(),
+            DateSource::FileName => t!("gallery.fix_timestamps.source.file_name").to_string(),
+        }
+    }
+}
+
+/// One file whose mtime doesn't match its content date
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TimestampFix {
+    pub path: PathBuf,
+    pub from: SystemTime,
+    pub to: SystemTime,
+    pub source: DateSource,
+}
+
+/// What fixing timestamps for a set of files would do
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Plan {
+    pub fixes: Vec<TimestampFix>,
+    /// Files with no content date, left alone
+    pub no_date: usize,
+    /// Files whose mtime already matches
+    pub unchanged: usize,
+}
+
+/// Content date of a file: embedded metadata first, then the file name
+pub fn read(path: &Path, name_patterns: &[Regex]) -> Option<(SystemTime, DateSource)> {
+    std::fs::read(paths::long_path(path))
+        .ok()
+        .and_then(|bytes| embedded_date(&bytes))
+        .or_else(|| {
+            let name = path.file_name()?.to_str()?;
+            filename_date::parse(name, name_patterns).map(|time| (time, DateSource::FileName))
+        })
+}
+
+/// Work out which of `files` need their mtime changed
+pub fn plan(files: &[PathBuf], name_patterns: &[Regex]) -> Plan {
+    let mut plan = Plan::default();
+    for path in files {
+        let Ok(from) = std::fs::metadata(paths::long_path(path)).and_then(|m| m.modified()) else {
+            continue;
+        };
+        let Some((to, source)) = read(path, name_patterns) else {
+            plan.no_date += 1;
+            continue;
+        };
+        let difference = from.duration_since(to).or_else(|_| to.duration_since(from));
+        if difference.is_ok_and(|d| d < TOLERANCE) {
+            plan.unchanged += 1;
+            continue;
+        }
+        plan.fixes.push(TimestampFix {
+            path: path.clone(),
+            from,
+            to,
+            source,
+        });
+    }
+    plan
+}
+
+/// Write a fix's content date as the file's mtime
+pub fn apply(fix: &TimestampFix) -> Result<()> {
+    set_file_mtime(paths::long_path(&fix.path), FileTime::from_system_time(fix.to))
+        .with_context(|| format!("Failed to set the modified time of {:?}", fix.path))
+}
+
+/// Date from EXIF or PNG text, whatever the format
+fn embedded_date(bytes: &[u8]) -> Option<(SystemTime, DateSource)> {
+    if bytes.starts_with(b"\x89PNG\r\n\x1a\n") {
+        png_date(&bytes[8..])
+    } else if bytes.starts_with(&[0xFF, 0xD8]) {
+        jpeg_exif(&bytes[2..]).and_then(exif_date).map(|t| (t, DateSource::Exif))
+    } else if bytes.len() >= 12 && &bytes[..4] == b"RIFF" && &bytes[8..12] == b"WEBP" {
+        webp_exif(&bytes[12..]).and_then(exif_date).map(|t| (t, DateSource::Exif))
+    } else {
+        None
+    }
+}
+
+/// EXIF from an `eXIf` chunk, else a "Creation Time" text chunk
+fn png_date(mut chunks: &[u8]) -> Option<(SystemTime, DateSource)> {
+    let mut text_date = None;
+    while chunks.len() >= 12 {
+        let length = u32::from_be_bytes(chunks[..4].try_into().ok()?) as usize;
+        let kind = &chunks[4..8];
+        let data = chunks.get(8..8 + length)?;
+        match kind {
+            b"eXIf" => {
+                if let Some(time) = exif_date(data) {
+                    return Some((time, DateSource::Exif));
+                }
+            }
+            b"tEXt" | b"iTXt" if text_date.is_none() => text_date = png_creation_time(kind, data),
+            b"IEND" => break,
+            _ => {}
+        }
+        chunks = chunks.get(12 + length..)?;
+    }
+    text_date.map(|time| (time, DateSource::PngText))
+}
+
+/// "Creation Time" from a tEXt or (uncompressed) iTXt chunk
+fn png_creation_time(kind: &[u8], data: &[u8]) -> Option<SystemTime> {
+    let split = data.iter().position(|b| *b == 0)?;
+    if &data[..split] != b"Creation Time" {
+        return None;
+    }
+    let text = match kind {
+        b"tEXt" => &data[split + 1..],
+        _ => {
+            // Compression flag and method, then language and translated keyword
+            let rest = data.get(split + 1..)?;
+            if rest.first() != Some(&0) {
+                return None;
+            }
+            let rest = rest.get(2..)?;
+            let language_end = rest.iter().position(|b| *b == 0)?;
+            let rest = &rest[language_end + 1..];
+            let keyword_end = rest.iter().position(|b| *b == 0)?;
+            &rest[keyword_end + 1..]
+        }
+    };
+    parse_date_text(std::str::from_utf8(text).ok()?.trim())
+}
+
+/// PNG creation times are free text; RFC 1123 is suggested, ISO 8601 and
+/// EXIF style are common
+fn parse_date_text(text: &str) -> Option<SystemTime> {
+    if let Ok(time) = DateTime::parse_from_rfc2822(text) {
+        return Some(time.into());
+    }
+    if let Ok(time) = DateTime::parse_from_rfc3339(text) {
+        return Some(time.into());
+    }
+    ["%Y:%m:%d %H:%M:%S", "%Y-%m-%d %H:%M:%S", "%Y-%m-%dT%H:%M:%S"]
+        .iter()
+        .find_map(|format| NaiveDateTime::parse_from_str(text, format).ok())
+        .and_then(|naive| Local.from_local_datetime(&naive).earliest())
+        .map(Into::into)
+}
+
+/// TIFF block of a JPEG's APP1 Exif segment
+fn jpeg_exif(mut segments: &[u8]) -> Option<&[u8]> {
+    while segments.len() >= 4 && segments[0] == 0xFF {
+        let marker = segments[1];
+        // Start of scan: metadata comes before the image data
+        if marker == 0xDA {
+            break;
+        }
+        let length = u16::from_be_bytes([segments[2], segments[3]]) as usize;
+        let data = segments.get(4..2 + length)?;
+        if marker == 0xE1 && data.starts_with(b"Exif\0\0") {
+            return Some(&data[6..]);
+        }
+        segments = segments.get(2 + length..)?;
+    }
+    None
+}
+
+/// TIFF block of a WebP's EXIF chunk
+fn webp_exif(mut chunks: &[u8]) -> Option<&[u8]> {
+    while chunks.len() >= 8 {
+        let length = u32::from_le_bytes(chunks[4..8].try_into().ok()?) as usize;
+        let data = chunks.get(8..8 + length)?;
+        if &chunks[..4] == b"EXIF" {
+            // Some writers keep the JPEG-style header
+            return Some(data.strip_prefix(b"Exif\0\0").unwrap_or(data));
+        }
+        // Chunks are padded to an even size
+        chunks = chunks.get(8 + length + (length & 1)..)?;
+    }
+    None
+}
+
+/// `DateTimeOriginal` from a TIFF-structured EXIF block
+fn exif_date(tiff: &[u8]) -> Option<SystemTime> {
+    let little_endian = match tiff.get(..2)? {
+        b"II" => true,
+        b"MM" => false,
+        _ => return None,
+    };
+    let u16_at = |offset: usize| -> Option<u16> {
+        let bytes: [u8; 2] = tiff.get(offset..offset + 2)?.try_into().ok()?;
+        Some(if little_endian { u16::from_le_bytes(bytes) } else { u16::from_be_bytes(bytes) })
+    };
+    let u32_at = |offset: usize| -> Option<u32> {
+        let bytes: [u8; 4] = tiff.get(offset..offset + 4)?.try_into().ok()?;
+        Some(if little_endian { u32::from_le_bytes(bytes) } else { u32::from_be_bytes(bytes) })
+    };
+    // Offset of the value of `tag` in the IFD at `ifd`
+    let find = |ifd: usize, tag: u16| -> Option<usize> {
+        let count = u16_at(ifd)? as usize;
+        (0..count)
+            .map(|i| ifd + 2 + i * 12)
+            .find(|entry| u16_at(*entry) == Some(tag))
+            .map(|entry| entry + 8)
+    };
+
+    let ifd0 = u32_at(4)? as usize;
+    let exif_ifd = u32_at(find(ifd0, EXIF_IFD_POINTER)?)? as usize;
+    // "YYYY:MM:DD HH:MM:SS\0" is 20 bytes, so the value is stored at an offset
+    let value = u32_at(find(exif_ifd, DATE_TIME_ORIGINAL)?)? as usize;
+    let text = tiff.get(value..value + 19)?;
+    let text = std::str::from_utf8(text).ok()?;
+    let naive = NaiveDateTime::parse_from_str(text, "%Y:%m:%d %H:%M:%S").ok()?;
+    Local.from_local_datetime(&naive).earliest().map(Into::into)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::scratch_dir;
+    use chrono::{Datelike, Timelike};
+
+    /// Little-endian TIFF with IFD0 -> Exif IFD -> DateTimeOriginal
+    fn exif_block(date: &str) -> Vec<u8> {
+        let mut tiff = b"II*\0".to_vec();
+        tiff.extend(8u32.to_le_bytes());
+        // IFD0: one entry, the Exif IFD pointer (at 8 + 2 + 12 + 4 = 26)
+        tiff.extend(1u16.to_le_bytes());
+        tiff.extend(EXIF_IFD_POINTER.to_le_bytes());
+        tiff.extend(4u16.to_le_bytes());
+        tiff.extend(1u32.to_le_bytes());
+        tiff.extend(26u32.to_le_bytes());
+        tiff.extend(0u32.to_le_bytes());
+        // Exif IFD: DateTimeOriginal, value right after it (at 26 + 2 + 12 + 4 = 44)
+        tiff.extend(1u16.to_le_bytes());
+        tiff.extend(DATE_TIME_ORIGINAL.to_le_bytes());
+        tiff.extend(2u16.to_le_bytes());
+        tiff.extend(20u32.to_le_bytes());
+        tiff.extend(44u32.to_le_bytes());
+        tiff.extend(0u32.to_le_bytes());
+        tiff.extend(date.as_bytes());
+        tiff.push(0);
+        tiff
+    }
+
+    fn png_chunk(kind: &[u8], data: &[u8]) -> Vec<u8> {
+        let mut chunk = (data.len() as u32).to_be_bytes().to_vec();
+        chunk.extend(kind);
+        chunk.extend(data);
+        // CRC isn't checked
+        chunk.extend([0; 4]);
+        chunk
+    }
+
+    fn ymd_hms(time: SystemTime) -> (i32, u32, u32, u32, u32, u32) {
+        let dt = DateTime::<Local>::from(time);
+        (dt.year(), dt.month(), dt.day(), dt.hour(), dt.minute(), dt.second())
+    }
+
+    #[test]
+    fn test_exif_in_jpeg_and_webp() {
+        let exif = exif_block("2021:06:05 14:03:02");
+
+        let mut jpeg = vec![0xFF, 0xD8, 0xFF, 0xE1];
+        jpeg.extend(((exif.len() + 8) as u16).to_be_bytes());
+        jpeg.extend(b"Exif\0\0");
+        jpeg.extend(&exif);
+        jpeg.extend([0xFF, 0xDA, 0, 2]);
+        let (time, source) = embedded_date(&jpeg).unwrap();
+        assert_eq!(source, DateSource::Exif);
+        assert_eq!(ymd_hms(time), (2021, 6, 5, 14, 3, 2));
+
+        let mut webp = b"RIFF\0\0\0\0WEBP".to_vec();
+        webp.extend(b"VP8L");
+        webp.extend(3u32.to_le_bytes());
+        webp.extend([1, 2, 3, 0]);
+        webp.extend(b"EXIF");
+        webp.extend((exif.len() as u32).to_le_bytes());
+        webp.extend(&exif);
+        assert_eq!(embedded_date(&webp).map(|(t, _)| ymd_hms(t)), Some((2021, 6, 5, 14, 3, 2)));
+    }
+
+    #[test]
+    fn test_png_chunks() {
+        let mut png = b"\x89PNG\r\n\x1a\n".to_vec();
+        png.extend(png_chunk(b"IHDR", &[0; 13]));
+        png.extend(png_chunk(b"tEXt", b"Creation Time\0Sat, 05 Jun 2021 14:03:02 +0000"));
+        png.extend(png_chunk(b"IEND", &[]));
+        let (time, source) = embedded_date(&png).unwrap();
+        assert_eq!(source, DateSource::PngText);
+        assert_eq!(
+            DateTime::<chrono::Utc>::from(time).to_rfc3339(),
+            "2021-06-05T14:03:02+00:00"
+        );
+
+        // EXIF wins over the text chunk
+        let mut png = b"\x89PNG\r\n\x1a\n".to_vec();
+        png.extend(png_chunk(b"tEXt", b"Creation Time\02020-01-01 00:00:00"));
+        png.extend(png_chunk(b"eXIf", &exif_block("2021:06:05 14:03:02")));
+        png.extend(png_chunk(b"IEND", &[]));
+        let (time, source) = embedded_date(&png).unwrap();
+        assert_eq!(source, DateSource::Exif);
+        assert_eq!(ymd_hms(time), (2021, 6, 5, 14, 3, 2));
+
+        // Other text and truncated files have no date
+        let mut png = b"\x89PNG\r\n\x1a\n".to_vec();
+        png.extend(png_chunk(b"tEXt", b"Software\0Snipping Tool"));
+        assert_eq!(embedded_date(&png), None);
+        assert_eq!(embedded_date(&png[..20]), None);
+    }
+
+    #[test]
+    fn test_plan_and_apply() {
+        let dir = scratch_dir("content-date");
+        let patterns = filename_date::compile(&filename_date::default_patterns());
+
+        let edited = dir.join("Screenshot 2022-03-14 101530.png");
+        let undated = dir.join("image.png");
+        let correct = dir.join("Screenshot 2020-01-02 030405.png");
+        for path in [&edited, &undated, &correct] {
+            std::fs::write(path, b"not really a png").unwrap();
+        }
+        let (correct_time, _) = read(&correct, &patterns).unwrap();
+        set_file_mtime(&correct, FileTime::from_system_time(correct_time)).unwrap();
+
+        let plan = plan(&[edited.clone(), undated, correct], &patterns);
+        assert_eq!((plan.no_date, plan.unchanged), (1, 1));
+        assert_eq!(plan.fixes.len(), 1);
+        let fix = &plan.fixes[0];
+        assert_eq!((fix.path.clone(), fix.source), (edited.clone(), DateSource::FileName));
+        assert_eq!(ymd_hms(fix.to), (2022, 3, 14, 10, 15, 30));
+
+        apply(fix).unwrap();
+        let modified = std::fs::metadata(&edited).unwrap().modified().unwrap();
+        assert_eq!(ymd_hms(modified), (2022, 3, 14, 10, 15, 30));
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
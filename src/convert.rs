@@ -1,30 +1,201 @@
 //! Image conversion utilities
 
 use anyhow::{Context, Result};
+use crossbeam_channel::Sender;
 use filetime::{set_file_mtime, FileTime};
+use image::codecs::avif::AvifEncoder;
 use image::codecs::jpeg::JpegEncoder;
-use image::codecs::webp::WebPEncoder;
+use image::codecs::webp::{WebPEncoder, WebPQuality};
 use image::io::Reader as ImageReader;
-use log::{error, info};
+use image::imageops::FilterType;
+use image::DynamicImage;
+use log::{error, info, warn};
+use serde::{Deserialize, Serialize};
 use std::fs;
 use std::io::{BufWriter, Write};
 use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 
-use crate::settings::ConversionFormat;
+use crate::convert_pool::{self, Priority};
+use crate::file_ops;
+use crate::indexer::{self, IndexConfig};
+use crate::kept_pngs;
+use crate::paths;
+use crate::pipeline::{self, Transition};
+use crate::settings::{ConversionFormat, Settings};
+use crate::timeline::{self, EventKind};
+use crate::AppMessage;
 
-/// Convert an image to the specified format
+/// PNGs converted in memory by [`estimate_savings`]
+pub const ESTIMATE_SAMPLE: usize = 20;
+
+/// ravif speed (1 slowest - 10 fastest). AVIF is by far the slowest format to
+/// encode; below 6 a large screenshot takes tens of seconds for little gain.
+const AVIF_SPEED: u8 = 6;
+
+/// How a conversion encodes, taken from the Conversion settings when it's
+/// started
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ConvertOptions {
+    pub format: ConversionFormat,
+    /// 1-100, as the format's encoder reads it
+    pub quality: u32,
+    /// Encode WebP losslessly, ignoring `quality` ("Lossless WebP")
+    pub lossless_webp: bool,
+    /// Longest edge converted images are downscaled to (0 = no resize)
+    pub max_dimension: u32,
+}
+
+impl ConvertOptions {
+    /// Options for converting with the current settings
+    pub fn from_settings(settings: &Settings) -> Self {
+        Self {
+            format: settings.conversion_format,
+            quality: settings.quality_for(settings.conversion_format),
+            lossless_webp: settings.webp_lossless,
+            max_dimension: settings.conversion_max_dimension,
+        }
+    }
+}
+
+/// Steps of the "Resize if larger than" setting; below the smallest it's off
+const MAX_DIMENSION_STEP: u32 = 320;
+const MAX_DIMENSION_MIN: u32 = 640;
+const MAX_DIMENSION_MAX: u32 = 7680;
+
+/// The "Resize if larger than" value one step up or down from `current`;
+/// stepping down from the smallest turns it off, stepping up from off
+/// starts at the smallest
+pub fn step_max_dimension(current: u32, up: bool) -> u32 {
+    match (current, up) {
+        (0, true) => MAX_DIMENSION_MIN,
+        (0, false) => 0,
+        (current, false) if current <= MAX_DIMENSION_MIN => 0,
+        (current, false) => (current - MAX_DIMENSION_STEP).max(MAX_DIMENSION_MIN),
+        (current, true) => (current + MAX_DIMENSION_STEP).min(MAX_DIMENSION_MAX),
+    }
+}
+
+/// Steps of the "Skip files smaller than" setting, in KB (0 = convert all)
+const MIN_SIZE_STEPS_KB: [u32; 8] = [0, 16, 32, 64, 128, 256, 512, 1024];
+
+/// The "Skip files smaller than" value one step up or down from `current`;
+/// values set by hand snap to the nearest step in that direction
+pub fn step_min_size_kb(current: u32, up: bool) -> u32 {
+    let steps = MIN_SIZE_STEPS_KB;
+    if up {
+        steps.into_iter().find(|&kb| kb > current).unwrap_or(steps[steps.len() - 1])
+    } else {
+        steps.into_iter().rev().find(|&kb| kb < current).unwrap_or(0)
+    }
+}
+
+/// Whether auto-convert should take `path`: a PNG of at least `min_size_kb`.
+/// Small PNGs (tooltips, icons) rarely shrink and aren't worth the CPU.
+pub fn should_auto_convert(path: &Path, min_size_kb: u32) -> bool {
+    if !is_convertible(path) {
+        return false;
+    }
+    if min_size_kb == 0 {
+        return true;
+    }
+    fs::metadata(paths::long_path(path)).is_ok_and(|m| m.len() >= u64::from(min_size_kb) * 1024)
+}
+
+/// A converted file that came out no smaller than its PNG; it was deleted and
+/// the PNG kept
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NotSmaller {
+    pub original_size: u64,
+    pub output_size: u64,
+}
+
+impl std::fmt::Display for NotSmaller {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Converted file was not smaller ({} bytes -> {} bytes), kept the original",
+            self.original_size, self.output_size
+        )
+    }
+}
+
+impl std::error::Error for NotSmaller {}
+
+/// Longest [`wait_for_file_ready`] waits for a file to be finished
+const READY_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// First pause between readiness checks; doubled after each one, up to
+/// [`READY_MAX_INTERVAL`]
+const READY_FIRST_INTERVAL: Duration = Duration::from_millis(25);
+const READY_MAX_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Wait until whoever is writing `path` is done with it: its size is the
+/// same on two checks in a row and no other process has it open. Small files
+/// pass within a few tens of milliseconds; a big PNG on a slow disk gets up to
+/// [`READY_TIMEOUT`]. Errors if the file is gone or still changing by then.
+pub fn wait_for_file_ready(path: &Path) -> Result<()> {
+    wait_for_file_ready_within(path, READY_TIMEOUT)
+}
+
+fn wait_for_file_ready_within(path: &Path, timeout: Duration) -> Result<()> {
+    let started = Instant::now();
+    let mut interval = READY_FIRST_INTERVAL;
+    let mut last_size = None;
+    loop {
+        let size = fs::metadata(paths::long_path(path))
+            .with_context(|| format!("Can't read {}", path.display()))?
+            .len();
+        // Empty is what a file looks like right after it's created
+        if last_size == Some(size) && size > 0 && !file_ops::is_in_use(path) {
+            return Ok(());
+        }
+        last_size = Some(size);
+
+        let elapsed = started.elapsed();
+        if elapsed >= timeout {
+            anyhow::bail!("{} was still being written after {:?}", path.display(), timeout);
+        }
+        std::thread::sleep(interval.min(timeout - elapsed));
+        interval = (interval * 2).min(READY_MAX_INTERVAL);
+    }
+}
+
+/// `img` with its longest edge brought down to `limit` (keeping the aspect
+/// ratio); smaller images, or a `limit` of 0, come back as they are
+fn fit_within(img: DynamicImage, limit: u32) -> DynamicImage {
+    if limit == 0 || img.width().max(img.height()) <= limit {
+        return img;
+    }
+    img.resize(limit, limit, FilterType::Lanczos3)
+}
+
+/// Convert an image as `options` say
 ///
 /// Returns the path to the new file if successful.
 /// The original file is deleted after successful conversion.
 /// Preserves the original file's modification timestamp.
-pub fn convert_image(
-    source_path: &Path,
-    format: ConversionFormat,
-    quality: u32,
-) -> Result<PathBuf> {
+/// If the new file is no smaller than the original, it's deleted instead and
+/// a [`NotSmaller`] error is returned, and the PNG is skipped the same way
+/// until it or `options` change.
+pub fn convert_image(source_path: &Path, options: ConvertOptions) -> Result<PathBuf> {
+    let result = convert_image_inner(source_path, options);
+    match &result {
+        Ok(output_path) => {
+            timeline::record_moved(source_path, output_path, EventKind::Converted(output_path.clone()))
+        }
+        Err(e) => timeline::record(source_path, EventKind::ConvertFailed(format!("{:#}", e))),
+    }
+    result
+}
+
+fn convert_image_inner(source_path: &Path, options: ConvertOptions) -> Result<PathBuf> {
+    let format = options.format;
     info!(
         "Converting to {:?}: {:?} (quality: {})",
-        format, source_path, quality
+        format, source_path, options.quality
     );
 
     // Only convert PNG files
@@ -37,51 +208,49 @@ pub fn convert_image(
         anyhow::bail!("Only PNG files can be converted");
     }
 
-    // Wait a bit to ensure the source file is fully written
-    std::thread::sleep(std::time::Duration::from_millis(100));
+    // Tried before with these options and the file hasn't changed since
+    if let Some(not_smaller) = kept_pngs::lookup(source_path, options) {
+        info!("Keeping {:?}: it didn't get smaller last time", source_path);
+        return Err(not_smaller.into());
+    }
+
+    // Found by a scan or picked in the gallery while still being copied in
+    wait_for_file_ready(source_path)?;
+
+    // Use extended-length paths so odd names (CON.png, trailing dots, >260 chars) work
+    let source_fs_path = paths::long_path(source_path);
 
     // Get original file's modification time BEFORE reading
-    let original_mtime = fs::metadata(source_path).and_then(|m| m.modified()).ok();
+    let original_mtime = fs::metadata(&source_fs_path).and_then(|m| m.modified()).ok();
 
     // Read the source image
-    let img = ImageReader::open(source_path)
+    let img = ImageReader::open(&source_fs_path)
         .context("Failed to open source image")?
         .decode()
         .context("Failed to decode source image")?;
+    let (original_width, original_height) = (img.width(), img.height());
+    let img = fit_within(img, options.max_dimension);
 
     // Create output path with appropriate extension
     let output_path = source_path.with_extension(format.extension());
+    let output_fs_path = paths::long_path(&output_path);
 
     // Create output file
-    let output_file = fs::File::create(&output_path).context(format!(
+    let output_file = fs::File::create(&output_fs_path).context(format!(
         "Failed to create output {} file",
         format.display_name()
     ))?;
 
     let mut writer = BufWriter::new(output_file);
-
-    // Encode based on format
-    match format {
-        ConversionFormat::WebP => {
-            // Use lossless encoding (image crate 0.24 doesn't support lossy quality setting directly)
-            let encoder = WebPEncoder::new_lossless(&mut writer);
-            img.write_with_encoder(encoder)
-                .context("Failed to encode WebP image")?;
-        }
-        ConversionFormat::Jpeg => {
-            // JPEG supports quality setting (1-100)
-            let encoder = JpegEncoder::new_with_quality(&mut writer, quality.clamp(1, 100) as u8);
-            img.write_with_encoder(encoder)
-                .context("Failed to encode JPEG image")?;
-        }
-    }
+    let encoded = encode_to_memory(&img, options)?;
+    writer.write_all(&encoded).context("Failed to write output file")?;
 
     // Ensure buffer is flushed to disk
     writer.flush().context("Failed to flush output file")?;
     drop(writer);
 
     // Verify the file was created successfully and has content
-    let output_meta = fs::metadata(&output_path).context("Output file not created")?;
+    let output_meta = fs::metadata(&output_fs_path).context("Output file not created")?;
     if output_meta.len() == 0 {
         anyhow::bail!("Output file is empty");
     }
@@ -89,28 +258,53 @@ pub fn convert_image(
     // Preserve original file's modification time on the new file
     if let Some(mtime) = original_mtime {
         let file_time = FileTime::from_system_time(mtime);
-        if let Err(e) = set_file_mtime(&output_path, file_time) {
+        if let Err(e) = set_file_mtime(&output_fs_path, file_time) {
             error!("Failed to preserve modification time: {}", e);
         } else {
             info!("Preserved original modification time on output file");
         }
     }
 
-    let original_size = fs::metadata(source_path).map(|m| m.len()).unwrap_or(0);
+    let original_size = fs::metadata(&source_fs_path).map(|m| m.len()).unwrap_or(0);
     let output_size = output_meta.len();
 
     info!(
-        "{} conversion complete: {:?} -> {:?} ({} bytes -> {} bytes, {:.1}% of original)",
+        "{} conversion complete: {:?} -> {:?} ({}x{} -> {}x{}, {} bytes -> {} bytes, {:.1}% of original)",
         format.display_name(),
         source_path,
         output_path,
+        original_width,
+        original_height,
+        img.width(),
+        img.height(),
         original_size,
         output_size,
         (output_size as f64 / original_size as f64) * 100.0
     );
 
-    // Delete the original file after successful conversion
-    if let Err(e) = fs::remove_file(source_path) {
+    // Nothing gained: keep the PNG and throw the converted file away
+    if original_size > 0 && output_size >= original_size {
+        info!(
+            "Keeping {:?}: the {} file is {} bytes, the original {} bytes",
+            source_path,
+            format.display_name(),
+            output_size,
+            original_size
+        );
+        if let Err(e) = fs::remove_file(&output_fs_path) {
+            error!("Failed to delete converted file {:?}: {}", output_path, e);
+        }
+        kept_pngs::record(source_path, options, output_size);
+        return Err(NotSmaller {
+            original_size,
+            output_size,
+        }
+        .into());
+    }
+
+    // Delete the original file after successful conversion. A read-only or
+    // locked source is reported to the UI, which can retry the delete.
+    if let Err(e) = file_ops::remove_file(source_path) {
         error!(
             "Failed to delete original file after conversion: {:?} - {}",
             source_path, e
@@ -122,6 +316,346 @@ pub fn convert_image(
     Ok(output_path)
 }
 
+/// Encode an image the way [`convert_image`] does, without writing anything
+/// (`options.max_dimension` is left to the caller)
+pub fn encode_to_memory(img: &DynamicImage, options: ConvertOptions) -> Result<Vec<u8>> {
+    let mut encoded = Vec::new();
+    let quality = options.quality;
+    match options.format {
+        ConversionFormat::WebP => return encode_webp(img, quality, options.lossless_webp),
+        ConversionFormat::Jpeg => {
+            // JPEG supports quality setting (1-100)
+            let encoder = JpegEncoder::new_with_quality(&mut encoded, quality.clamp(1, 100) as u8);
+            img.write_with_encoder(encoder)
+                .context("Failed to encode JPEG image")?;
+        }
+        ConversionFormat::Avif => return encode_avif(img, quality),
+    }
+    Ok(encoded)
+}
+
+/// Encode AVIF at `quality` (1-100), which ravif maps onto the AV1 quantizer.
+/// Runs below normal priority: it keeps a core busy for seconds per screenshot.
+fn encode_avif(img: &DynamicImage, quality: u32) -> Result<Vec<u8>> {
+    let _priority = BelowNormalPriority::enter();
+    // ravif takes 8-bit RGB or RGBA
+    let img = if img.color().has_alpha() {
+        DynamicImage::ImageRgba8(img.to_rgba8())
+    } else {
+        DynamicImage::ImageRgb8(img.to_rgb8())
+    };
+    let mut encoded = Vec::new();
+    let encoder =
+        AvifEncoder::new_with_speed_quality(&mut encoded, AVIF_SPEED, quality.clamp(1, 100) as u8);
+    img.write_with_encoder(encoder)
+        .context("Failed to encode AVIF image")?;
+    Ok(encoded)
+}
+
+/// Lowers the current thread's priority until dropped
+struct BelowNormalPriority {
+    #[cfg(windows)]
+    previous: i32,
+}
+
+impl BelowNormalPriority {
+    #[cfg(windows)]
+    fn enter() -> Self {
+        use windows::Win32::System::Threading::{
+            GetCurrentThread, GetThreadPriority, SetThreadPriority, THREAD_PRIORITY_BELOW_NORMAL,
+        };
+
+        // SAFETY: The pseudo handle from GetCurrentThread is always valid
+        // for the calling thread and needs no closing
+        let previous = unsafe { GetThreadPriority(GetCurrentThread()) };
+        // SAFETY: As above; only this thread's priority changes
+        if let Err(e) = unsafe { SetThreadPriority(GetCurrentThread(), THREAD_PRIORITY_BELOW_NORMAL) } {
+            warn!("Failed to lower encoder thread priority: {}", e);
+        }
+        Self { previous }
+    }
+
+    #[cfg(not(windows))]
+    fn enter() -> Self {
+        Self {}
+    }
+}
+
+#[cfg(windows)]
+impl Drop for BelowNormalPriority {
+    fn drop(&mut self) {
+        use windows::Win32::System::Threading::{GetCurrentThread, SetThreadPriority, THREAD_PRIORITY};
+
+        // SAFETY: The pseudo handle from GetCurrentThread is always valid for
+        // the calling thread. The guard is a local in the encoder, so this is
+        // the thread `enter` lowered; a priority `enter` failed to read is
+        // refused without harm.
+        let _ = unsafe { SetThreadPriority(GetCurrentThread(), THREAD_PRIORITY(self.previous)) };
+    }
+}
+
+/// Encode WebP, lossless or lossy at `quality` (1-100). Lossy keeps the
+/// alpha channel when there is one.
+fn encode_webp(img: &DynamicImage, quality: u32, lossless: bool) -> Result<Vec<u8>> {
+    let mut encoded = Vec::new();
+    if lossless {
+        let encoder = WebPEncoder::new_lossless(&mut encoded);
+        img.write_with_encoder(encoder)
+            .context("Failed to encode WebP image")?;
+        return Ok(encoded);
+    }
+
+    // libwebp takes 8-bit RGB or RGBA only
+    let img = if img.color().has_alpha() {
+        DynamicImage::ImageRgba8(img.to_rgba8())
+    } else {
+        DynamicImage::ImageRgb8(img.to_rgb8())
+    };
+    // The libwebp encoder is the only lossy one in image 0.24
+    #[allow(deprecated)]
+    let encoder =
+        WebPEncoder::new_with_quality(&mut encoded, WebPQuality::lossy(quality.clamp(1, 100) as u8));
+    img.write_with_encoder(encoder)
+        .context("Failed to encode WebP image")?;
+    Ok(encoded)
+}
+
+/// What converting the library's PNGs would save, judged from a sample
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SavingsEstimate {
+    pub format: ConversionFormat,
+    pub quality: u32,
+    /// Whether WebP was encoded losslessly
+    pub lossless_webp: bool,
+    /// PNGs in the library
+    pub pngs: usize,
+    /// Their total size
+    pub png_bytes: u64,
+    /// PNGs converted for the estimate
+    pub sampled: usize,
+    /// Size of the sampled PNGs before and after converting
+    pub sampled_bytes: u64,
+    pub encoded_bytes: u64,
+}
+
+impl SavingsEstimate {
+    /// Share of the size converting saves (negative when files would grow)
+    pub fn saved_fraction(&self) -> f64 {
+        if self.sampled_bytes == 0 {
+            return 0.0;
+        }
+        1.0 - self.encoded_bytes as f64 / self.sampled_bytes as f64
+    }
+
+    /// Bytes converting every PNG would save
+    pub fn projected_savings(&self) -> u64 {
+        (self.png_bytes as f64 * self.saved_fraction().max(0.0)) as u64
+    }
+}
+
+/// Estimate what converting `pngs` (newest first) would save by encoding the
+/// first [`ESTIMATE_SAMPLE`] that decode in memory. Nothing on disk is touched.
+pub fn estimate_savings(pngs: &[PathBuf], options: ConvertOptions) -> SavingsEstimate {
+    let size = |path: &Path| fs::metadata(paths::long_path(path)).map(|m| m.len()).unwrap_or(0);
+    let (format, quality) = (options.format, options.quality);
+    let mut estimate = SavingsEstimate {
+        format,
+        quality,
+        lossless_webp: options.lossless_webp,
+        pngs: pngs.len(),
+        png_bytes: pngs.iter().map(|path| size(path)).sum(),
+        sampled: 0,
+        sampled_bytes: 0,
+        encoded_bytes: 0,
+    };
+
+    for path in pngs {
+        if estimate.sampled == ESTIMATE_SAMPLE {
+            break;
+        }
+        let encoded = ImageReader::open(paths::long_path(path))
+            .map_err(anyhow::Error::from)
+            .and_then(|reader| Ok(reader.decode()?))
+            .and_then(|img| encode_to_memory(&fit_within(img, options.max_dimension), options));
+        match encoded {
+            Ok(encoded) => {
+                estimate.sampled += 1;
+                estimate.sampled_bytes += size(path);
+                estimate.encoded_bytes += encoded.len() as u64;
+            }
+            Err(e) => warn!("Skipping {:?} in the savings estimate: {:#}", path, e),
+        }
+    }
+
+    info!(
+        "Savings estimate ({} at {}): {} of {} PNGs sampled, {:.1}% smaller",
+        format.display_name(),
+        quality,
+        estimate.sampled,
+        estimate.pngs,
+        estimate.saved_fraction() * 100.0
+    );
+    estimate
+}
+
+/// How a batch conversion went
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ConvertSummary {
+    pub converted: usize,
+    /// Not PNGs, being converted already, or no smaller once converted
+    pub skipped: usize,
+    pub failed: usize,
+    /// Still open in another program when retried at the end
+    pub in_use: usize,
+    /// Size saved across the converted files (negative if they grew)
+    pub saved_bytes: i64,
+    pub canceled: bool,
+}
+
+/// Convert picked `files` one by one on the shared pool, behind new
+/// screenshots; anything that isn't a PNG, or is being converted already, is
+/// skipped. Each converted file is swapped in the gallery right away, and its
+/// index row (with `index_config`) points at the new file. Sends progress via the
+/// Convert* messages. Runs in a background thread.
+pub fn convert_files(
+    files: Vec<PathBuf>,
+    options: ConvertOptions,
+    index_config: Option<IndexConfig>,
+    cancel: Arc<AtomicBool>,
+    message_tx: Sender<AppMessage>,
+) {
+    std::thread::spawn(move || {
+        let (pngs, others): (Vec<PathBuf>, Vec<PathBuf>) =
+            files.into_iter().partition(|path| is_convertible(path));
+        info!("Converting {} picked PNGs to {:?} ({} skipped)", pngs.len(), options.format, others.len());
+        let summary = ConvertSummary {
+            skipped: others.len(),
+            ..Default::default()
+        };
+        run_batch(pngs, summary, options, index_config, &cancel, &message_tx);
+    });
+}
+
+/// Convert every PNG under `base_dir` (newest first), like [`convert_files`].
+/// Converted files are gone from the scan and PNGs that didn't shrink are
+/// remembered, so a second run finds nothing.
+pub fn convert_library(
+    base_dir: PathBuf,
+    options: ConvertOptions,
+    index_config: Option<IndexConfig>,
+    cancel: Arc<AtomicBool>,
+    message_tx: Sender<AppMessage>,
+) {
+    std::thread::spawn(move || {
+        let pngs: Vec<PathBuf> = crate::watcher::ScreenshotWatcher::collect_images(&base_dir)
+            .into_iter()
+            .filter(|path| is_convertible(path) && kept_pngs::lookup(path, options).is_none())
+            .collect();
+        info!("Converting {} PNGs under {:?} to {:?}", pngs.len(), base_dir, options.format);
+        run_batch(pngs, ConvertSummary::default(), options, index_config, &cancel, &message_tx);
+    });
+}
+
+/// Convert `pngs` in order until `cancel` is set. Files open in another
+/// program are put off to the end and tried once more there.
+fn run_batch(
+    pngs: Vec<PathBuf>,
+    mut summary: ConvertSummary,
+    options: ConvertOptions,
+    index_config: Option<IndexConfig>,
+    cancel: &AtomicBool,
+    message_tx: &Sender<AppMessage>,
+) {
+    let total = pngs.len();
+    let index_config = index_config.as_ref();
+    let mut retry_later = Vec::new();
+    let mut current = 0;
+    let _ = message_tx.send(AppMessage::ConvertStarted(total));
+
+    for path in pngs {
+        if cancel.load(Ordering::Relaxed) {
+            summary.canceled = true;
+            break;
+        }
+        if file_ops::is_in_use(&path) {
+            info!("{:?} is in use, trying it again at the end", path);
+            retry_later.push(path);
+            continue;
+        }
+        current += 1;
+        let _ = message_tx.send(AppMessage::ConvertProgress(current, total, path.clone()));
+        convert_one(path, options, index_config, &mut summary, message_tx);
+    }
+
+    for path in retry_later {
+        if summary.canceled || cancel.load(Ordering::Relaxed) {
+            summary.canceled = true;
+            break;
+        }
+        current += 1;
+        let _ = message_tx.send(AppMessage::ConvertProgress(current, total, path.clone()));
+        if file_ops::is_in_use(&path) {
+            warn!("{:?} is still in use, skipping it", path);
+            summary.in_use += 1;
+            continue;
+        }
+        convert_one(path, options, index_config, &mut summary, message_tx);
+    }
+
+    info!(
+        "Converted {} PNGs ({} skipped, {} failed, {} in use, {} bytes saved{})",
+        summary.converted,
+        summary.skipped,
+        summary.failed,
+        summary.in_use,
+        summary.saved_bytes,
+        if summary.canceled { ", canceled" } else { "" }
+    );
+    let _ = message_tx.send(AppMessage::ConvertCompleted(summary));
+}
+
+/// Convert one PNG on the pool, point its index rows (with `index_config`)
+/// at the new file, and swap it in the gallery as a rename, so it keeps its
+/// selection and last-used time
+fn convert_one(
+    path: PathBuf,
+    options: ConvertOptions,
+    index_config: Option<&IndexConfig>,
+    summary: &mut ConvertSummary,
+    message_tx: &Sender<AppMessage>,
+) {
+    let size = |path: &Path| fs::metadata(paths::long_path(path)).map(|m| m.len() as i64).unwrap_or(0);
+    let original_size = size(&path);
+    match convert_pool::convert(path.clone(), options, Priority::Background) {
+        Some(Ok(output_path)) => {
+            summary.converted += 1;
+            summary.saved_bytes += original_size - size(&output_path);
+            // Right away: the watcher is about to report the PNG deleted
+            let moved = [(path.clone(), output_path.clone())];
+            if let Some(Err(e)) = index_config.map(|config| indexer::rename_in_index_blocking(&moved, config)) {
+                warn!("Failed to update the index for {:?}: {}", output_path, e);
+            }
+            pipeline::report(
+                Transition::Moved {
+                    from: path.clone(),
+                    to: output_path.clone(),
+                },
+                message_tx,
+            );
+            let _ = message_tx.send(AppMessage::ScreenshotRenamed(path, output_path));
+        }
+        // Kept as it is; convert_image logged why
+        Some(Err(e)) if e.is::<NotSmaller>() => summary.skipped += 1,
+        Some(Err(e)) => {
+            error!("Failed to convert {:?}: {}", path, e);
+            summary.failed += 1;
+            let _ = message_tx.send(AppMessage::ConvertFileFailed(path, format!("{:#}", e)));
+        }
+        // The watcher got to it first
+        None => summary.skipped += 1,
+    }
+}
+
 /// Check if a file is a PNG that can be converted
 pub fn is_convertible(path: &Path) -> bool {
     path.extension()
@@ -132,6 +666,75 @@ pub fn is_convertible(path: &Path) -> bool {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::test_support::scratch_dir;
+
+    /// Append `chunks` blocks of `chunk` bytes to `path` from another thread,
+    /// pausing between them like a slow writer; the flag is set once it's done
+    fn slow_writer(
+        path: &Path,
+        chunks: usize,
+        chunk: usize,
+        pause: Duration,
+    ) -> (std::thread::JoinHandle<()>, Arc<AtomicBool>) {
+        fs::File::create(path).unwrap();
+        let finished = Arc::new(AtomicBool::new(false));
+        let writer = {
+            let (path, finished) = (path.to_path_buf(), Arc::clone(&finished));
+            std::thread::spawn(move || {
+                let mut file = fs::OpenOptions::new().append(true).open(&path).unwrap();
+                for _ in 0..chunks {
+                    file.write_all(&vec![0u8; chunk]).unwrap();
+                    file.flush().unwrap();
+                    std::thread::sleep(pause);
+                }
+                finished.store(true, Ordering::SeqCst);
+            })
+        };
+        (writer, finished)
+    }
+
+    #[test]
+    fn test_wait_for_file_ready_waits_for_the_writer() {
+        let dir = scratch_dir("ready");
+        let path = dir.join("slow.png");
+        let (writer, finished) = slow_writer(&path, 60, 4096, Duration::from_millis(5));
+
+        wait_for_file_ready(&path).unwrap();
+        assert!(finished.load(Ordering::SeqCst));
+        assert_eq!(fs::metadata(&path).unwrap().len(), 60 * 4096);
+        writer.join().unwrap();
+
+        // Finished files pass right away
+        let started = Instant::now();
+        wait_for_file_ready(&path).unwrap();
+        assert!(started.elapsed() < Duration::from_millis(500));
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_wait_for_file_ready_gives_up() {
+        let dir = scratch_dir("ready-timeout");
+
+        // Still growing when the time is up
+        let path = dir.join("growing.png");
+        let (writer, finished) = slow_writer(&path, 100, 1024, Duration::from_millis(5));
+        let started = Instant::now();
+        assert!(wait_for_file_ready_within(&path, Duration::from_millis(150)).is_err());
+        assert!(started.elapsed() < Duration::from_millis(400));
+        assert!(!finished.load(Ordering::SeqCst));
+        writer.join().unwrap();
+
+        // Never written at all
+        let empty = dir.join("empty.png");
+        fs::File::create(&empty).unwrap();
+        assert!(wait_for_file_ready_within(&empty, Duration::from_millis(100)).is_err());
+
+        // Gone
+        assert!(wait_for_file_ready(&dir.join("missing.png")).is_err());
+
+        let _ = fs::remove_dir_all(&dir);
+    }
 
     #[test]
     fn test_is_convertible() {
@@ -164,12 +767,257 @@ mod tests {
     fn test_conversion_format_extension() {
         assert_eq!(ConversionFormat::WebP.extension(), "webp");
         assert_eq!(ConversionFormat::Jpeg.extension(), "jpg");
+        assert_eq!(ConversionFormat::Avif.extension(), "avif");
     }
 
     #[test]
     fn test_conversion_format_display_name() {
         assert_eq!(ConversionFormat::WebP.display_name(), "WebP");
         assert_eq!(ConversionFormat::Jpeg.display_name(), "JPEG");
+        assert_eq!(ConversionFormat::Avif.display_name(), "AVIF");
+    }
+
+    /// JPEG at quality 80, full size
+    fn jpeg_80() -> ConvertOptions {
+        ConvertOptions {
+            format: ConversionFormat::Jpeg,
+            quality: 80,
+            lossless_webp: false,
+            max_dimension: 0,
+        }
+    }
+
+    #[test]
+    fn test_convert_adversarial_names() {
+        let dir = scratch_dir("convert");
+
+        let names = [
+            "CON.png".to_string(),
+            "trailing dot..png".to_string(),
+            format!("{}.png", "y".repeat(200)),
+        ];
+
+        for name in &names {
+            let source = dir.join(name);
+            // Noisy, so the JPEG comes out smaller than the PNG
+            noisy_rgba()
+                .to_rgb8()
+                .save_with_format(paths::long_path(&source), image::ImageFormat::Png)
+                .unwrap();
+
+            let output = convert_image(&source, jpeg_80()).unwrap();
+
+            assert_eq!(output, source.with_extension("jpg"));
+            assert!(paths::long_path(&output).exists());
+            assert!(!paths::long_path(&source).exists());
+        }
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_convert_keeps_smaller_original() {
+        let dir = scratch_dir("convert-larger");
+
+        // A few bytes as a PNG; any JPEG is bigger than that
+        let source = dir.join("tiny.png");
+        image::RgbImage::from_pixel(8, 8, image::Rgb([40, 40, 40]))
+            .save_with_format(&source, image::ImageFormat::Png)
+            .unwrap();
+        let original = fs::read(&source).unwrap();
+
+        let err = convert_image(&source, jpeg_80()).unwrap_err();
+        let not_smaller = err.downcast_ref::<NotSmaller>().unwrap();
+        assert_eq!(not_smaller.original_size, original.len() as u64);
+        assert!(not_smaller.output_size >= not_smaller.original_size);
+
+        // The PNG is untouched and the JPEG is gone
+        assert_eq!(fs::read(&source).unwrap(), original);
+        assert!(!source.with_extension("jpg").exists());
+
+        // Not encoded again: the same answer without writing anything
+        let again = convert_image(&source, jpeg_80()).unwrap_err();
+        assert_eq!(again.downcast_ref::<NotSmaller>(), Some(not_smaller));
+        assert!(!source.with_extension("jpg").exists());
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_should_auto_convert() {
+        let dir = scratch_dir("min-size");
+        let small = dir.join("small.png");
+        fs::write(&small, vec![0u8; 10 * 1024]).unwrap();
+        let large = dir.join("large.png");
+        fs::write(&large, vec![0u8; 40 * 1024]).unwrap();
+
+        assert!(should_auto_convert(&small, 0));
+        assert!(!should_auto_convert(&small, 16));
+        assert!(should_auto_convert(&large, 16));
+        assert!(!should_auto_convert(&dir.join("large.jpg"), 0));
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_convert_library_twice() {
+        let dir = scratch_dir("convert-library");
+        fs::create_dir_all(dir.join("2024-01")).unwrap();
+        for path in [dir.join("a.png"), dir.join("2024-01").join("b.png")] {
+            noisy_rgba()
+                .to_rgb8()
+                .save_with_format(&path, image::ImageFormat::Png)
+                .unwrap();
+        }
+
+        let run = || {
+            let (tx, rx) = crossbeam_channel::unbounded();
+            convert_library(dir.clone(), jpeg_80(), None, Arc::new(AtomicBool::new(false)), tx);
+            rx.iter()
+                .find_map(|message| match message {
+                    AppMessage::ConvertCompleted(summary) => Some(summary),
+                    _ => None,
+                })
+                .unwrap()
+        };
+
+        let first = run();
+        assert_eq!((first.converted, first.failed, first.in_use), (2, 0, 0));
+        assert!(dir.join("2024-01").join("b.jpg").exists());
+        // Nothing left to convert
+        assert_eq!(run(), ConvertSummary::default());
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_estimate_savings() {
+        let dir = scratch_dir("estimate");
+
+        let mut pngs = Vec::new();
+        for i in 0..ESTIMATE_SAMPLE + 2 {
+            let path = dir.join(format!("{i}.png"));
+            image::RgbImage::from_fn(64, 64, |x, y| image::Rgb([(x * 4) as u8, (y * 4) as u8, i as u8]))
+                .save_with_format(&path, image::ImageFormat::Png)
+                .unwrap();
+            pngs.push(path);
+        }
+        // Unreadable files are skipped, but still counted for the library
+        let broken = dir.join("broken.png");
+        fs::write(&broken, b"not a png").unwrap();
+        pngs.insert(0, broken);
+
+        let estimate = estimate_savings(&pngs, jpeg_80());
+        assert_eq!(estimate.pngs, ESTIMATE_SAMPLE + 3);
+        assert_eq!(estimate.sampled, ESTIMATE_SAMPLE);
+        assert!(estimate.sampled_bytes > 0 && estimate.encoded_bytes > 0);
+        assert!(estimate.projected_savings() <= estimate.png_bytes);
+
+        // The originals are left alone
+        assert!(pngs.iter().all(|path| path.exists()));
+        assert!(fs::read_dir(&dir).unwrap().count() == pngs.len());
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_fit_within() {
+        let wide = DynamicImage::new_rgb8(3840, 1600);
+        let resized = fit_within(wide, 2560);
+        assert_eq!((resized.width(), resized.height()), (2560, 1067));
+
+        let tall = DynamicImage::new_rgb8(1000, 3000);
+        let resized = fit_within(tall, 1500);
+        assert_eq!((resized.width(), resized.height()), (500, 1500));
+
+        // Never upscaled, and 0 means no limit
+        let small = DynamicImage::new_rgb8(800, 600);
+        let kept = fit_within(small, 2560);
+        assert_eq!((kept.width(), kept.height()), (800, 600));
+        let kept = fit_within(DynamicImage::new_rgb8(4000, 100), 0);
+        assert_eq!((kept.width(), kept.height()), (4000, 100));
+    }
+
+    #[test]
+    fn test_step_max_dimension() {
+        assert_eq!(step_max_dimension(0, true), MAX_DIMENSION_MIN);
+        assert_eq!(step_max_dimension(0, false), 0);
+        assert_eq!(step_max_dimension(MAX_DIMENSION_MIN, false), 0);
+        assert_eq!(step_max_dimension(2560, false), 2240);
+        assert_eq!(step_max_dimension(2560, true), 2880);
+        assert_eq!(step_max_dimension(MAX_DIMENSION_MAX, true), MAX_DIMENSION_MAX);
+        // Values set by hand land back on the steps' range
+        assert_eq!(step_max_dimension(700, false), MAX_DIMENSION_MIN);
+        assert_eq!(step_max_dimension(100_000, true), MAX_DIMENSION_MAX);
+    }
+
+    #[test]
+    fn test_step_min_size_kb() {
+        assert_eq!(step_min_size_kb(0, true), 16);
+        assert_eq!(step_min_size_kb(0, false), 0);
+        assert_eq!(step_min_size_kb(16, false), 0);
+        assert_eq!(step_min_size_kb(64, true), 128);
+        assert_eq!(step_min_size_kb(1024, true), 1024);
+        // Values set by hand snap to the steps
+        assert_eq!(step_min_size_kb(100, true), 128);
+        assert_eq!(step_min_size_kb(100, false), 64);
+        assert_eq!(step_min_size_kb(5000, false), 1024);
+    }
+
+    /// Noisy RGBA, opaque on the left half and transparent on the right
+    fn noisy_rgba() -> DynamicImage {
+        DynamicImage::ImageRgba8(image::RgbaImage::from_fn(256, 256, |x, y| {
+            let noise = (x.wrapping_mul(2_654_435_761) ^ y.wrapping_mul(40_503)) >> 7;
+            let alpha = if x < 128 { 255 } else { 0 };
+            image::Rgba([(x + noise % 32) as u8, (y + noise % 16) as u8, (noise % 64) as u8, alpha])
+        }))
+    }
+
+    #[test]
+    fn test_lossy_webp_is_smaller() {
+        let img = noisy_rgba();
+        let lossless = encode_webp(&img, 75, true).unwrap();
+        let lossy = encode_webp(&img, 75, false).unwrap();
+        assert!(
+            lossy.len() * 2 < lossless.len(),
+            "lossy {} bytes vs lossless {} bytes",
+            lossy.len(),
+            lossless.len()
+        );
+
+        // Quality matters
+        let low = encode_webp(&img, 10, false).unwrap();
+        assert!(low.len() < lossy.len());
+    }
+
+    #[test]
+    fn test_lossy_webp_keeps_alpha() {
+        let lossy = encode_webp(&noisy_rgba(), 75, false).unwrap();
+        let decoded = image::load_from_memory_with_format(&lossy, image::ImageFormat::WebP)
+            .unwrap()
+            .to_rgba8();
+        assert_eq!(decoded.dimensions(), (256, 256));
+        assert!(decoded.get_pixel(20, 20)[3] > 240);
+        assert!(decoded.get_pixel(200, 200)[3] < 16);
+
+        // Opaque images don't gain an alpha channel
+        let opaque = DynamicImage::ImageRgb8(noisy_rgba().to_rgb8());
+        let lossy = encode_webp(&opaque, 75, false).unwrap();
+        let decoded = image::load_from_memory_with_format(&lossy, image::ImageFormat::WebP).unwrap();
+        assert!(!decoded.color().has_alpha());
+    }
+
+    #[test]
+    fn test_avif_encodes() {
+        let img = noisy_rgba().thumbnail_exact(32, 32);
+        let options = ConvertOptions {
+            format: ConversionFormat::Avif,
+            quality: 70,
+            ..jpeg_80()
+        };
+        let encoded = encode_to_memory(&img, options).unwrap();
+        // ISOBMFF "ftyp" box with the AVIF brand
+        assert_eq!(&encoded[4..12], b"ftypavif");
     }
 
     #[test]
@@ -0,0 +1,335 @@
+//! Bounded worker pool for image conversion
+//!
+//! Encoding a screenshot keeps a core busy for a good while, so conversions
+//! from every source (new screenshots, PNGs found by a folder scan) go
+//! through one queue served by a few threads instead of a thread per file.
+//! A burst of new screenshots then can't take every core, and a screenshot
+//! just taken jumps ahead of background work. A file already queued or
+//! being converted isn't queued again, so two sources spotting the same PNG
+//! can't both convert it (one would find the source already deleted).
+//! Other jobs that rewrite a file (stripping metadata) share the queue and
+//! the claim, so a file is never converted and rewritten at the same time.
+
+use anyhow::Result;
+use log::{debug, error};
+use parking_lot::{Condvar, Mutex, MutexGuard};
+use std::collections::{HashSet, VecDeque};
+use std::path::PathBuf;
+use std::sync::{Arc, OnceLock};
+
+use crate::convert::{self, ConvertOptions};
+
+/// How soon a job should run
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Priority {
+    /// A screenshot just taken; someone is waiting to see it
+    New,
+    /// Bulk work that can wait
+    Background,
+}
+
+type Job = Box<dyn FnOnce() + Send>;
+
+struct Queue {
+    new: VecDeque<Job>,
+    background: VecDeque<Job>,
+    /// Jobs allowed to run at once
+    limit: usize,
+    running: usize,
+    threads: usize,
+}
+
+struct Shared {
+    queue: Mutex<Queue>,
+    changed: Condvar,
+}
+
+/// Threads taking jobs by priority, never more than `limit` at once
+pub struct WorkerPool {
+    shared: Arc<Shared>,
+}
+
+impl WorkerPool {
+    pub fn new(limit: usize) -> Self {
+        let pool = Self {
+            shared: Arc::new(Shared {
+                queue: Mutex::new(Queue {
+                    new: VecDeque::new(),
+                    background: VecDeque::new(),
+                    limit: 0,
+                    running: 0,
+                    threads: 0,
+                }),
+                changed: Condvar::new(),
+            }),
+        };
+        pool.set_limit(limit);
+        pool
+    }
+
+    /// Change how many jobs may run at once. Running jobs finish either way;
+    /// threads past a lowered limit just wait.
+    pub fn set_limit(&self, limit: usize) {
+        let limit = limit.max(1);
+        let mut queue = self.shared.queue.lock();
+        queue.limit = limit;
+        while queue.threads < limit {
+            queue.threads += 1;
+            let shared = Arc::clone(&self.shared);
+            std::thread::Builder::new()
+                .name(format!("convert-{}", queue.threads))
+                .spawn(move || run_worker(&shared))
+                .expect("failed to spawn conversion worker");
+        }
+        self.shared.changed.notify_all();
+    }
+
+    pub fn limit(&self) -> usize {
+        self.shared.queue.lock().limit
+    }
+
+    pub fn submit(&self, priority: Priority, job: impl FnOnce() + Send + 'static) {
+        let mut queue = self.shared.queue.lock();
+        match priority {
+            Priority::New => queue.new.push_back(Box::new(job)),
+            Priority::Background => queue.background.push_back(Box::new(job)),
+        }
+        self.shared.changed.notify_one();
+    }
+}
+
+fn run_worker(shared: &Shared) {
+    let mut queue = shared.queue.lock();
+    loop {
+        let job = if queue.running < queue.limit {
+            match queue.new.pop_front() {
+                Some(job) => Some(job),
+                None => queue.background.pop_front(),
+            }
+        } else {
+            None
+        };
+        let Some(job) = job else {
+            shared.changed.wait(&mut queue);
+            continue;
+        };
+
+        queue.running += 1;
+        MutexGuard::unlocked(&mut queue, || {
+            if std::panic::catch_unwind(std::panic::AssertUnwindSafe(job)).is_err() {
+                error!("Conversion job panicked");
+            }
+        });
+        queue.running -= 1;
+        // A slot opened up; with a lowered limit another waiting thread may be the one to use it
+        shared.changed.notify_all();
+    }
+}
+
+/// Threads when the setting is automatic: a quarter of the cores, at most 2
+pub fn default_workers() -> usize {
+    let cores = std::thread::available_parallelism()
+        .map(|n| n.get())
+        .unwrap_or(4);
+    (cores / 4).clamp(1, 2)
+}
+
+/// Threads for the `conversion_workers` setting (0 = automatic)
+pub fn workers_for(setting: usize) -> usize {
+    if setting == 0 {
+        default_workers()
+    } else {
+        setting
+    }
+}
+
+static POOL: OnceLock<WorkerPool> = OnceLock::new();
+
+fn pool() -> &'static WorkerPool {
+    POOL.get_or_init(|| WorkerPool::new(default_workers()))
+}
+
+/// Files queued or being worked on in the shared pool
+static IN_FLIGHT: Mutex<Option<HashSet<PathBuf>>> = Mutex::new(None);
+
+/// Claim on a file in [`IN_FLIGHT`], released when the job ends (even by panic)
+struct InFlight(PathBuf);
+
+impl InFlight {
+    fn claim(path: &PathBuf) -> Option<Self> {
+        let mut guard = IN_FLIGHT.lock();
+        let claimed = guard.get_or_insert_with(HashSet::new).insert(path.clone());
+        claimed.then(|| InFlight(path.clone()))
+    }
+}
+
+impl Drop for InFlight {
+    fn drop(&mut self) {
+        if let Some(paths) = IN_FLIGHT.lock().as_mut() {
+            paths.remove(&self.0);
+        }
+    }
+}
+
+/// Size the shared pool from the `conversion_workers` setting
+pub fn set_workers(setting: usize) {
+    pool().set_limit(workers_for(setting));
+}
+
+/// Convert on the pool; `done` gets the result on the worker thread. Returns
+/// false without queueing anything (or calling `done`) if `path` is already
+/// queued or being converted: that conversion reports the outcome.
+pub fn submit(
+    path: PathBuf,
+    options: ConvertOptions,
+    priority: Priority,
+    done: impl FnOnce(Result<PathBuf>) + Send + 'static,
+) -> bool {
+    let Some(claim) = InFlight::claim(&path) else {
+        debug!("Already converting {:?}", path);
+        return false;
+    };
+    pool().submit(priority, move || {
+        let result = convert::convert_image(&path, options);
+        drop(claim);
+        done(result)
+    });
+    true
+}
+
+/// Run a job that rewrites `path` on the pool. Returns false without queueing
+/// it if `path` is already queued or being worked on; the claim is released
+/// when `job` returns.
+pub fn run(path: PathBuf, priority: Priority, job: impl FnOnce() + Send + 'static) -> bool {
+    let Some(claim) = InFlight::claim(&path) else {
+        debug!("Already working on {:?}", path);
+        return false;
+    };
+    pool().submit(priority, move || {
+        job();
+        drop(claim);
+    });
+    true
+}
+
+/// Convert on the pool and wait for the result; `None` if `path` is already
+/// being converted
+pub fn convert(path: PathBuf, options: ConvertOptions, priority: Priority) -> Option<Result<PathBuf>> {
+    let (tx, rx) = crossbeam_channel::bounded(1);
+    let queued = submit(path, options, priority, move |result| {
+        let _ = tx.send(result);
+    });
+    queued.then(|| {
+        rx.recv()
+            .unwrap_or_else(|_| Err(anyhow::anyhow!("Conversion worker stopped")))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::settings::ConversionFormat;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::time::Duration;
+
+    #[test]
+    fn test_pool_never_exceeds_limit() {
+        let pool = WorkerPool::new(3);
+        let running = Arc::new(AtomicUsize::new(0));
+        let peak = Arc::new(AtomicUsize::new(0));
+        let (done_tx, done_rx) = crossbeam_channel::unbounded();
+
+        for i in 0..200 {
+            let running = Arc::clone(&running);
+            let peak = Arc::clone(&peak);
+            let done_tx = done_tx.clone();
+            let priority = if i % 5 == 0 { Priority::New } else { Priority::Background };
+            pool.submit(priority, move || {
+                let now = running.fetch_add(1, Ordering::SeqCst) + 1;
+                peak.fetch_max(now, Ordering::SeqCst);
+                std::thread::sleep(Duration::from_millis(1));
+                running.fetch_sub(1, Ordering::SeqCst);
+                let _ = done_tx.send(());
+            });
+        }
+        for _ in 0..200 {
+            done_rx.recv_timeout(Duration::from_secs(30)).unwrap();
+        }
+        assert!(peak.load(Ordering::SeqCst) <= 3);
+
+        // Lowering the limit holds back the extra threads
+        pool.set_limit(1);
+        peak.store(0, Ordering::SeqCst);
+        for _ in 0..50 {
+            let running = Arc::clone(&running);
+            let peak = Arc::clone(&peak);
+            let done_tx = done_tx.clone();
+            pool.submit(Priority::Background, move || {
+                let now = running.fetch_add(1, Ordering::SeqCst) + 1;
+                peak.fetch_max(now, Ordering::SeqCst);
+                std::thread::sleep(Duration::from_millis(1));
+                running.fetch_sub(1, Ordering::SeqCst);
+                let _ = done_tx.send(());
+            });
+        }
+        for _ in 0..50 {
+            done_rx.recv_timeout(Duration::from_secs(30)).unwrap();
+        }
+        assert_eq!(peak.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn test_new_jobs_run_before_background() {
+        let pool = WorkerPool::new(1);
+        let (gate_tx, gate_rx) = crossbeam_channel::bounded::<()>(0);
+        let (order_tx, order_rx) = crossbeam_channel::unbounded();
+
+        // Hold the only slot while the queue fills up
+        pool.submit(Priority::Background, move || {
+            let _ = gate_rx.recv();
+        });
+        for name in ["batch 1", "batch 2"] {
+            let order_tx = order_tx.clone();
+            pool.submit(Priority::Background, move || {
+                let _ = order_tx.send(name);
+            });
+        }
+        let new_tx = order_tx.clone();
+        pool.submit(Priority::New, move || {
+            let _ = new_tx.send("new");
+        });
+        gate_tx.send(()).unwrap();
+
+        let order: Vec<&str> = (0..3)
+            .map(|_| order_rx.recv_timeout(Duration::from_secs(10)).unwrap())
+            .collect();
+        assert_eq!(order, ["new", "batch 1", "batch 2"]);
+    }
+
+    #[test]
+    fn test_same_file_is_converted_once_at_a_time() {
+        let path = std::env::temp_dir().join(format!("sukusho-in-flight-{}.png", std::process::id()));
+        let options = ConvertOptions {
+            format: ConversionFormat::WebP,
+            quality: 80,
+            lossless_webp: false,
+            max_dimension: 0,
+        };
+        let first = InFlight::claim(&path).unwrap();
+        assert!(InFlight::claim(&path).is_none());
+        assert!(!submit(path.clone(), options, Priority::New, |_| {
+            panic!("a second conversion of the same file was queued")
+        }));
+        assert!(convert(path.clone(), options, Priority::New).is_none());
+
+        // Free again once the first one is done
+        drop(first);
+        assert!(InFlight::claim(&path).is_some());
+    }
+
+    #[test]
+    fn test_workers_for_setting() {
+        assert_eq!(workers_for(3), 3);
+        assert!((1..=2).contains(&workers_for(0)));
+    }
+}
@@ -0,0 +1,490 @@
+//! Diagnostic bundle for bug reports
+//!
+//! Collects what an issue usually needs (version, Windows build, settings,
+//! the logs, the Diagnostics page checks and index row counts) into a zip on
+//! the Desktop for the user to attach by hand; nothing is uploaded.
+//! Personal paths are redacted before anything is written: the profile
+//! folder becomes `%USERPROFILE%`, other profiles' names and the user name
+//! anywhere else become `<user>`, and the API token and last search are
+//! blanked.
+
+use chrono::{Datelike, Local, Timelike};
+use regex::Regex;
+use serde_json::Value;
+use std::fmt::Write as _;
+use std::fs;
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+
+use crate::settings::Settings;
+
+/// Log written in console mode (`--console`), next to the working directory
+const CONSOLE_LOG_FILE: &str = "sukusho_debug.log";
+
+/// End of each log that goes into the bundle
+const LOG_TAIL_BYTES: u64 = 1024 * 1024;
+
+/// Settings blanked rather than redacted: secrets and what the user searched for
+const BLANKED_SETTINGS: &[&str] = &["api_server_token", "last_search_query"];
+
+/// Stand-in for a user name
+const USER_PLACEHOLDER: &str = "<user>";
+
+/// Strips personal paths and the user name out of text
+pub struct Redactor {
+    /// The profile folder, any separator, with or without `\\?\`
+    home: Option<Regex>,
+    /// `\Users\<name>` of any profile
+    profiles: Regex,
+    /// The user name on its own
+    user: Option<Regex>,
+}
+
+impl Redactor {
+    /// Redactor for a profile folder and user name (either may be unknown)
+    pub fn new(home: Option<&Path>, user: Option<&str>) -> Self {
+        let home = home
+            .map(|home| {
+                home.to_string_lossy()
+                    .split(['\\', '/'])
+                    .filter(|part| !part.is_empty())
+                    .map(regex::escape)
+                    .collect::<Vec<_>>()
+                    .join(r"[\\/]+")
+            })
+            .filter(|pattern| !pattern.is_empty())
+            // Up to the end of the folder name, so C:\Users\al doesn't eat C:\Users\alice
+            .and_then(|pattern| Regex::new(&format!(r"(?i)(?:\\\\\?\\)?{pattern}([\\/\x22\s]|$)")).ok());
+        // Short names ("a", "pc") would blank half the text
+        let user = user
+            .filter(|user| user.chars().count() >= 3)
+            .and_then(|user| Regex::new(&format!("(?i){}", regex::escape(user))).ok());
+
+        Self {
+            home,
+            profiles: Regex::new(r"(?i)([\\/]Users[\\/]+)[^\\/\x22\s]+").expect("valid regex"),
+            user,
+        }
+    }
+
+    /// Redactor for the signed-in user
+    pub fn current() -> Self {
+        let home = directories::UserDirs::new().map(|dirs| dirs.home_dir().to_path_buf());
+        let user = std::env::var("USERNAME").or_else(|_| std::env::var("USER")).ok();
+        Self::new(home.as_deref(), user.as_deref())
+    }
+
+    pub fn redact(&self, text: &str) -> String {
+        let mut text = text.to_string();
+        if let Some(home) = &self.home {
+            text = home.replace_all(&text, "%USERPROFILE%${1}").into_owned();
+        }
+        text = self
+            .profiles
+            .replace_all(&text, format!("${{1}}{USER_PLACEHOLDER}"))
+            .into_owned();
+        if let Some(user) = &self.user {
+            text = user.replace_all(&text, USER_PLACEHOLDER).into_owned();
+        }
+        text
+    }
+
+    /// Redact every string in settings JSON, blanking [`BLANKED_SETTINGS`]
+    pub fn redact_settings(&self, settings: &mut Value) {
+        if let Value::Object(map) = settings {
+            for key in BLANKED_SETTINGS {
+                if let Some(value) = map.get_mut(*key).filter(|value| value.is_string()) {
+                    *value = Value::String(String::new());
+                }
+            }
+        }
+        self.redact_json(settings);
+    }
+
+    fn redact_json(&self, value: &mut Value) {
+        match value {
+            Value::String(text) => *text = self.redact(text),
+            Value::Array(items) => items.iter_mut().for_each(|item| self.redact_json(item)),
+            Value::Object(map) => {
+                // Keys can be paths too
+                let entries = std::mem::take(map);
+                for (key, mut item) in entries {
+                    self.redact_json(&mut item);
+                    map.insert(self.redact(&key), item);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Write the bundle to the Desktop (or the config folder, if there's no
+/// Desktop) and return its path. `db_path` is the search index, when indexing
+/// is on. Reads files and the index; call off the UI thread.
+pub fn create_bundle(settings: &Settings, version: &str, db_path: Option<&Path>) -> io::Result<PathBuf> {
+    let redactor = Redactor::current();
+    let mut entries: Vec<(&str, Vec<u8>)> = Vec::new();
+
+    let logs = find_logs();
+    entries.push(("summary.txt", summary(version, db_path, &logs, &redactor).into_bytes()));
+
+    let mut settings_json = serde_json::to_value(settings).map_err(io::Error::other)?;
+    redactor.redact_settings(&mut settings_json);
+    let settings_json = serde_json::to_string_pretty(&settings_json).map_err(io::Error::other)?;
+    entries.push(("settings.json", settings_json.into_bytes()));
+
+    for (name, log) in &logs {
+        entries.push((*name, redactor.redact(&log_tail(log)?).into_bytes()));
+    }
+
+    let dir = directories::UserDirs::new()
+        .and_then(|dirs| dirs.desktop_dir().map(Path::to_path_buf))
+        .or_else(|| Settings::config_path().and_then(|p| p.parent().map(Path::to_path_buf)))
+        .ok_or_else(|| io::Error::other("no Desktop or config directory"))?;
+    let path = dir.join(format!(
+        "sukusho-diagnostics-{}.zip",
+        Local::now().format("%Y%m%d-%H%M%S")
+    ));
+    let mut file = io::BufWriter::new(fs::File::create(&path)?);
+    write_zip(&mut file, &entries)?;
+    file.flush()?;
+    Ok(path)
+}
+
+/// Version, Windows build, Diagnostics page checks and index row counts
+fn summary(version: &str, db_path: Option<&Path>, logs: &[(&str, PathBuf)], redactor: &Redactor) -> String {
+    let mut text = String::new();
+    let _ = writeln!(text, "Sukusho {version}");
+    let _ = writeln!(text, "Windows: {}", windows_build());
+    let _ = writeln!(text, "Created: {}", Local::now().format("%Y-%m-%d %H:%M:%S %z"));
+    if logs.is_empty() {
+        let _ = writeln!(text, "Log: none");
+    }
+    for (_, log) in logs {
+        let _ = writeln!(text, "Log: {}", redactor.redact(&log.to_string_lossy()));
+    }
+
+    let _ = writeln!(text, "\n[Index]");
+    match db_path {
+        None => {
+            let _ = writeln!(text, "Indexing is off");
+        }
+        Some(db_path) if !db_path.exists() => {
+            let _ = writeln!(text, "No index yet");
+        }
+        Some(db_path) => match crate::indexer::table_row_counts(db_path) {
+            Ok(counts) if counts.is_empty() => {
+                let _ = writeln!(text, "No tables");
+            }
+            Ok(counts) => {
+                for (table, rows) in counts {
+                    let _ = writeln!(text, "{table}: {rows} rows");
+                }
+            }
+            Err(e) => {
+                let _ = writeln!(text, "Can't open: {}", redactor.redact(&format!("{e:#}")));
+            }
+        },
+    }
+
+    let _ = writeln!(text, "\n[Files that can't be decoded]");
+    let failures = crate::thumbnail::decode_failures();
+    if failures.is_empty() {
+        let _ = writeln!(text, "None");
+    }
+    for (path, error) in failures {
+        let _ = writeln!(text, "{}: {}", redactor.redact(&path.to_string_lossy()), redactor.redact(&error));
+    }
+
+    let _ = writeln!(text, "\n[Hotkey and tray recovery]");
+    let (last, failures) = crate::shell_recovery::status();
+    match last {
+        None => {
+            let _ = writeln!(text, "No recovery needed");
+        }
+        Some(attempt) => {
+            let at: chrono::DateTime<Local> = attempt.at.into();
+            let _ = writeln!(
+                text,
+                "Last: {:?} at {} ({})",
+                attempt.trigger,
+                at.format("%Y-%m-%d %H:%M:%S"),
+                attempt.error.as_deref().unwrap_or("ok")
+            );
+            let _ = writeln!(text, "Failures in a row: {failures}");
+        }
+    }
+    text
+}
+
+/// The logs there are, by the name they get in the bundle: the normal-mode
+/// log (oldest first) and the console-mode one
+fn find_logs() -> Vec<(&'static str, PathBuf)> {
+    let mut logs = Vec::new();
+    if let Some(log) = crate::rolling_log::path() {
+        logs.push((crate::rolling_log::PREVIOUS_LOG_FILE, crate::rolling_log::previous_path(&log)));
+        logs.push((crate::rolling_log::LOG_FILE, log));
+    }
+    let exe_dir = std::env::current_exe().ok().and_then(|exe| exe.parent().map(Path::to_path_buf));
+    let console_log = [std::env::current_dir().ok(), exe_dir]
+        .into_iter()
+        .flatten()
+        .map(|dir| dir.join(CONSOLE_LOG_FILE))
+        .find(|path| path.is_file());
+    logs.extend(console_log.map(|path| (CONSOLE_LOG_FILE, path)));
+    logs.retain(|(_, path)| path.is_file());
+    logs
+}
+
+/// The last [`LOG_TAIL_BYTES`] of a log, starting at a whole line
+fn log_tail(path: &Path) -> io::Result<String> {
+    let mut file = fs::File::open(path)?;
+    let len = file.metadata()?.len();
+    let start = len.saturating_sub(LOG_TAIL_BYTES);
+    file.seek(SeekFrom::Start(start))?;
+    let mut bytes = Vec::new();
+    file.read_to_end(&mut bytes)?;
+    let text = String::from_utf8_lossy(&bytes);
+    Ok(match (start > 0, text.find('\n')) {
+        (true, Some(newline)) => text[newline + 1..].to_string(),
+        _ => text.into_owned(),
+    })
+}
+
+/// "Windows 11 Pro 23H2 (build 22631.4317)", from the registry
+#[cfg(windows)]
+fn windows_build() -> String {
+    use windows::Win32::System::Registry::{
+        HKEY, HKEY_LOCAL_MACHINE, KEY_READ, REG_VALUE_TYPE, RegCloseKey, RegOpenKeyExW, RegQueryValueExW,
+    };
+    use windows::core::PCWSTR;
+
+    let wide = |s: &str| s.encode_utf16().chain(std::iter::once(0)).collect::<Vec<u16>>();
+    // SAFETY: Every name passed is a NUL-terminated UTF-16 buffer that lives
+    // across its call. RegQueryValueExW gets `data`'s real length in `size`
+    // and writes no more than that. `hkey` is only used after RegOpenKeyExW
+    // succeeded and is closed once, after the last query.
+    unsafe {
+        let subkey = wide("SOFTWARE\\Microsoft\\Windows NT\\CurrentVersion");
+        let mut hkey = HKEY::default();
+        if RegOpenKeyExW(HKEY_LOCAL_MACHINE, PCWSTR(subkey.as_ptr()), 0, KEY_READ, &mut hkey).is_err() {
+            return "unknown".to_string();
+        }
+
+        let query = |name: &str| -> Option<(REG_VALUE_TYPE, Vec<u8>)> {
+            let name = wide(name);
+            let mut data = vec![0u8; 512];
+            let mut size = data.len() as u32;
+            let mut kind = REG_VALUE_TYPE(0);
+            RegQueryValueExW(
+                hkey,
+                PCWSTR(name.as_ptr()),
+                None,
+                Some(&mut kind),
+                Some(data.as_mut_ptr()),
+                Some(&mut size),
+            )
+            .ok()
+            .ok()?;
+            data.truncate(size as usize);
+            Some((kind, data))
+        };
+        let string = |name: &str| {
+            query(name).map(|(_, data)| {
+                let units: Vec<u16> = data.chunks_exact(2).map(|c| u16::from_le_bytes([c[0], c[1]])).collect();
+                String::from_utf16_lossy(&units).trim_end_matches('\0').to_string()
+            })
+        };
+        let number = |name: &str| {
+            query(name)
+                .filter(|(_, data)| data.len() == 4)
+                .map(|(_, data)| u32::from_le_bytes([data[0], data[1], data[2], data[3]]))
+        };
+
+        let product = string("ProductName").unwrap_or_else(|| "Windows".to_string());
+        let release = string("DisplayVersion").unwrap_or_default();
+        let build = string("CurrentBuild").unwrap_or_else(|| "?".to_string());
+        let revision = number("UBR").map(|ubr| format!(".{ubr}")).unwrap_or_default();
+        let _ = RegCloseKey(hkey);
+        format!("{product} {release} (build {build}{revision})")
+    }
+}
+
+#[cfg(not(windows))]
+fn windows_build() -> String {
+    std::env::consts::OS.to_string()
+}
+
+/// Write `entries` (name, contents) as an uncompressed zip
+fn write_zip(out: &mut impl Write, entries: &[(&str, Vec<u8>)]) -> io::Result<()> {
+    let now = Local::now();
+    let time = (now.hour() << 11 | now.minute() << 5 | now.second() / 2) as u16;
+    let date = ((now.year().max(1980) - 1980) as u32) << 9 | now.month() << 5 | now.day();
+    let date = date as u16;
+    // Names are UTF-8
+    let flags: u16 = 1 << 11;
+
+    let mut offset = 0u32;
+    let mut central = Vec::new();
+    for (name, data) in entries {
+        let crc = crc32(data);
+        let size = data.len() as u32;
+        let mut local = Vec::with_capacity(30 + name.len());
+        local.extend(0x0403_4b50u32.to_le_bytes());
+        local.extend(20u16.to_le_bytes()); // version needed
+        local.extend(flags.to_le_bytes());
+        local.extend(0u16.to_le_bytes()); // stored
+        local.extend(time.to_le_bytes());
+        local.extend(date.to_le_bytes());
+        local.extend(crc.to_le_bytes());
+        local.extend(size.to_le_bytes());
+        local.extend(size.to_le_bytes());
+        local.extend((name.len() as u16).to_le_bytes());
+        local.extend(0u16.to_le_bytes()); // extra field
+        local.extend(name.as_bytes());
+        out.write_all(&local)?;
+        out.write_all(data)?;
+
+        central.extend(0x0201_4b50u32.to_le_bytes());
+        central.extend(20u16.to_le_bytes()); // version made by
+        central.extend(local[4..30].iter().copied());
+        central.extend(0u16.to_le_bytes()); // comment
+        central.extend(0u16.to_le_bytes()); // disk
+        central.extend(0u16.to_le_bytes()); // internal attributes
+        central.extend(0u32.to_le_bytes()); // external attributes
+        central.extend(offset.to_le_bytes());
+        central.extend(name.as_bytes());
+
+        offset += local.len() as u32 + size;
+    }
+
+    out.write_all(&central)?;
+    let mut end = Vec::with_capacity(22);
+    end.extend(0x0605_4b50u32.to_le_bytes());
+    end.extend(0u16.to_le_bytes()); // this disk
+    end.extend(0u16.to_le_bytes()); // disk with the directory
+    end.extend((entries.len() as u16).to_le_bytes());
+    end.extend((entries.len() as u16).to_le_bytes());
+    end.extend((central.len() as u32).to_le_bytes());
+    end.extend(offset.to_le_bytes());
+    end.extend(0u16.to_le_bytes()); // comment
+    out.write_all(&end)
+}
+
+/// CRC-32 (IEEE), as zip wants it
+fn crc32(data: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for &byte in data {
+        crc ^= byte as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 != 0 { (crc >> 1) ^ 0xEDB8_8320 } else { crc >> 1 };
+        }
+    }
+    !crc
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn redactor() -> Redactor {
+        Redactor::new(Some(Path::new(r"C:\Users\Jane.Doe")), Some("Jane.Doe"))
+    }
+
+    #[test]
+    fn test_redacts_the_profile_folder() {
+        let redactor = redactor();
+        assert_eq!(
+            redactor.redact(r"C:\Users\Jane.Doe\Pictures\Screenshots"),
+            r"%USERPROFILE%\Pictures\Screenshots"
+        );
+        assert_eq!(
+            redactor.redact(r"\\?\c:\users\jane.doe\Pictures\a.png"),
+            r"%USERPROFILE%\Pictures\a.png"
+        );
+        assert_eq!(redactor.redact("C:/Users/Jane.Doe/Desktop"), "%USERPROFILE%/Desktop");
+        assert_eq!(
+            redactor.redact(r#"Failed to open "C:\Users\Jane.Doe\a.png": denied"#),
+            r#"Failed to open "%USERPROFILE%\a.png": denied"#
+        );
+    }
+
+    #[test]
+    fn test_redacts_other_profiles_and_the_user_name() {
+        let redactor = redactor();
+        assert_eq!(
+            redactor.redact(r"D:\Users\bob\Screenshots"),
+            r"D:\Users\<user>\Screenshots"
+        );
+        assert_eq!(redactor.redact(r"D:\jane.doe-shots\2024"), r"D:\<user>-shots\2024");
+        assert_eq!(redactor.redact("Hello from JANE.DOE"), "Hello from <user>");
+        // The dot is literal, not "any character"
+        assert_eq!(redactor.redact("JanexDoe"), "JanexDoe");
+        assert_eq!(redactor.redact(r"E:\Screenshots"), r"E:\Screenshots");
+    }
+
+    #[test]
+    fn test_short_user_names_are_left_in_text() {
+        let redactor = Redactor::new(Some(Path::new(r"C:\Users\al")), Some("al"));
+        assert_eq!(redactor.redact(r"C:\Users\al\Pictures"), r"%USERPROFILE%\Pictures");
+        assert_eq!(redactor.redact(r"C:\Users\alice\Pictures"), r"C:\Users\<user>\Pictures");
+        assert_eq!(redactor.redact("Normal mode"), "Normal mode");
+        let redactor = Redactor::new(None, None);
+        assert_eq!(redactor.redact(r"C:\Users\al\Pictures"), r"C:\Users\<user>\Pictures");
+    }
+
+    #[test]
+    fn test_redact_settings() {
+        let mut settings = serde_json::json!({
+            "screenshot_directory": r"C:\Users\Jane.Doe\Pictures\Screenshots",
+            "preferred_editor": r"C:\Users\Jane.Doe\AppData\Local\Programs\editor.exe",
+            "api_server_token": "s3cret",
+            "last_search_query": "bank statement",
+            "file_name_date_patterns": [r"C:\Users\Jane.Doe\x"],
+            "shortcut_bindings": { r"C:\Users\Jane.Doe": "Ctrl+A" },
+            "thumbnail_size": 150,
+        });
+        redactor().redact_settings(&mut settings);
+
+        assert_eq!(settings["screenshot_directory"], r"%USERPROFILE%\Pictures\Screenshots");
+        assert_eq!(
+            settings["preferred_editor"],
+            r"%USERPROFILE%\AppData\Local\Programs\editor.exe"
+        );
+        assert_eq!(settings["api_server_token"], "");
+        assert_eq!(settings["last_search_query"], "");
+        assert_eq!(settings["file_name_date_patterns"][0], r"%USERPROFILE%\x");
+        assert_eq!(settings["shortcut_bindings"]["%USERPROFILE%"], "Ctrl+A");
+        assert_eq!(settings["thumbnail_size"], 150);
+
+        let text = settings.to_string();
+        assert!(!text.to_lowercase().contains("jane"));
+    }
+
+    #[test]
+    fn test_crc32() {
+        assert_eq!(crc32(b""), 0);
+        assert_eq!(crc32(b"123456789"), 0xCBF4_3926);
+    }
+
+    #[test]
+    fn test_write_zip_layout() {
+        let entries = vec![("a.txt", b"hello".to_vec()), ("b.json", b"{}".to_vec())];
+        let mut zip = Vec::new();
+        write_zip(&mut zip, &entries).unwrap();
+
+        assert_eq!(&zip[..4], b"PK\x03\x04");
+        // Local header (30 bytes + name), then the data as it is
+        assert_eq!(&zip[30..35], b"a.txt");
+        assert_eq!(&zip[35..40], b"hello");
+        assert_eq!(&zip[14..18], &crc32(b"hello").to_le_bytes());
+
+        let end = &zip[zip.len() - 22..];
+        assert_eq!(&end[..4], b"PK\x05\x06");
+        assert_eq!(u16::from_le_bytes([end[10], end[11]]), 2);
+        let directory_size = u32::from_le_bytes(end[12..16].try_into().unwrap()) as usize;
+        let directory_offset = u32::from_le_bytes(end[16..20].try_into().unwrap()) as usize;
+        assert_eq!(directory_offset + directory_size, zip.len() - 22);
+        assert_eq!(&zip[directory_offset..directory_offset + 4], b"PK\x01\x02");
+    }
+}
@@ -0,0 +1,160 @@
+//! Warn before watching a drive root, a system folder or a huge folder
+//!
+//! Pointing the app at `C:\` or a whole user profile makes the recursive
+//! watcher and the first scan go through hundreds of thousands of files, and
+//! auto-convert would start on PNGs that were never screenshots. Switching to
+//! such a folder asks first. The file count comes from a breadth-first walk
+//! that reads a limited number of folders and extrapolates from them, so the
+//! check stays quick on exactly the folders it's meant for.
+
+use std::collections::VecDeque;
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+
+use crate::paths;
+
+/// Folders read by the sampling walk before it extrapolates
+const SAMPLE_DIRS: usize = 300;
+
+/// Why a folder makes a poor screenshot directory
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Concern {
+    /// The top of a drive or network share
+    DriveRoot,
+    /// Windows, Program Files, ProgramData, AppData or the profile itself
+    SystemFolder,
+    /// More files than the limit, by the sampled count
+    LargeFolder { estimate: usize },
+}
+
+/// What's wrong with watching `dir`, if anything. `limit` is the file count
+/// above which a folder counts as large.
+pub fn check(dir: &Path, limit: usize) -> Option<Concern> {
+    if is_drive_root(dir) {
+        Some(Concern::DriveRoot)
+    } else if system_folders().iter().any(|(folder, nested)| covers(folder, *nested, dir)) {
+        Some(Concern::SystemFolder)
+    } else {
+        let estimate = sample_count(dir, limit);
+        (estimate > limit).then_some(Concern::LargeFolder { estimate })
+    }
+}
+
+fn is_drive_root(dir: &Path) -> bool {
+    !dir.components()
+        .any(|c| matches!(c, Component::Normal(_) | Component::ParentDir | Component::CurDir))
+}
+
+/// Known system folders, and whether folders inside them count too (the
+/// profile itself is a poor choice, its Pictures folder a good one)
+fn system_folders() -> Vec<(PathBuf, bool)> {
+    let mut folders: Vec<(PathBuf, bool)> = ["SystemRoot", "ProgramFiles", "ProgramFiles(x86)", "ProgramData"]
+        .iter()
+        .filter_map(|var| std::env::var_os(var))
+        .map(|path| (PathBuf::from(path), true))
+        .collect();
+    if let Some(base) = directories::BaseDirs::new() {
+        folders.push((base.home_dir().to_path_buf(), false));
+        folders.push((base.data_dir().to_path_buf(), true));
+        folders.push((base.data_local_dir().to_path_buf(), true));
+    }
+    folders
+}
+
+/// Whether `dir` is `folder` (or, with `nested`, anywhere inside it)
+fn covers(folder: &Path, nested: bool, dir: &Path) -> bool {
+    let normalize = |path: &Path| -> Vec<String> {
+        path.components()
+            .map(|c| c.as_os_str().to_string_lossy().to_lowercase())
+            .collect()
+    };
+    let folder = normalize(folder);
+    let dir = normalize(dir);
+    if nested {
+        dir.starts_with(&folder)
+    } else {
+        dir == folder
+    }
+}
+
+/// Files under `dir`, counted breadth first over at most [`SAMPLE_DIRS`]
+/// folders. Stops once past `limit`; if folders are left unread, they're
+/// assumed to hold as many files as the average one read.
+fn sample_count(dir: &Path, limit: usize) -> usize {
+    let mut queue = VecDeque::from([dir.to_path_buf()]);
+    let mut files = 0;
+    let mut read = 0;
+    while read < SAMPLE_DIRS && files <= limit {
+        let Some(next) = queue.pop_front() else {
+            return files;
+        };
+        read += 1;
+        let Ok(entries) = fs::read_dir(paths::long_path(&next)) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            if entry.file_type().is_ok_and(|t| t.is_dir()) {
+                queue.push_back(next.join(entry.file_name()));
+            } else {
+                files += 1;
+            }
+        }
+    }
+    extrapolate(files, read, queue.len())
+}
+
+/// Estimated total from `files` in `read` folders with `unread` left
+fn extrapolate(files: usize, read: usize, unread: usize) -> usize {
+    if read == 0 {
+        return files;
+    }
+    files + unread * files / read
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::scratch_dir;
+
+    #[test]
+    fn test_drive_root() {
+        let root = std::env::temp_dir().ancestors().last().unwrap().to_path_buf();
+        assert!(is_drive_root(&root));
+        assert_eq!(check(&root, 1_000_000), Some(Concern::DriveRoot));
+        assert!(!is_drive_root(&std::env::temp_dir()));
+    }
+
+    #[test]
+    fn test_covers() {
+        let windows = Path::new("C:/Windows");
+        assert!(covers(windows, true, Path::new("C:/windows/System32")));
+        assert!(!covers(windows, true, Path::new("C:/WindowsApps")));
+        let profile = Path::new("C:/Users/me");
+        assert!(covers(profile, false, Path::new("C:/Users/Me")));
+        assert!(!covers(profile, false, Path::new("C:/Users/me/Pictures")));
+    }
+
+    #[test]
+    fn test_large_folder() {
+        let dir = scratch_dir("dir-check");
+        fs::create_dir_all(dir.join("sub")).unwrap();
+        for i in 0..6 {
+            fs::write(dir.join(format!("{i}.png")), b"").unwrap();
+            fs::write(dir.join("sub").join(format!("{i}.png")), b"").unwrap();
+        }
+
+        assert_eq!(sample_count(&dir, 100), 12);
+        assert_eq!(check(&dir, 100), None);
+        assert!(matches!(check(&dir, 10), Some(Concern::LargeFolder { estimate }) if estimate > 10));
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_extrapolate() {
+        assert_eq!(extrapolate(0, 0, 0), 0);
+        assert_eq!(extrapolate(300, 300, 0), 300);
+        // 10 files per folder read, 50 folders left
+        assert_eq!(extrapolate(3000, 300, 50), 3500);
+    }
+}
@@ -9,34 +9,6 @@ use std::sync::atomic::{AtomicBool, Ordering};
 /// Flag to prevent multiple concurrent drag operations
 static DRAG_IN_PROGRESS: AtomicBool = AtomicBool::new(false);
 
-/// Simple drag threshold check (for use with separate start_drag call)
-#[cfg(windows)]
-pub fn check_drag_threshold() -> bool {
-    use crate::tray::WINDOW_HWND;
-    use windows::Win32::Foundation::{HWND, POINT};
-    use windows::Win32::UI::Input::KeyboardAndMouse::DragDetect;
-    use windows::Win32::UI::WindowsAndMessaging::GetCursorPos;
-
-    unsafe {
-        let mut pt = POINT::default();
-        if GetCursorPos(&mut pt).is_err() {
-            return false;
-        }
-
-        let hwnd = match *WINDOW_HWND.lock() {
-            Some(h) => HWND(h as *mut std::ffi::c_void),
-            None => return false,
-        };
-
-        DragDetect(hwnd, pt).as_bool()
-    }
-}
-
-#[cfg(not(windows))]
-pub fn check_drag_threshold() -> bool {
-    false
-}
-
 // Windows HRESULT constants for drag-drop
 #[cfg(windows)]
 const DRAGDROP_S_DROP: i32 = 0x00040100;
@@ -77,6 +49,14 @@ pub fn start_drag(files: &[PathBuf]) -> bool {
     use windows::Win32::System::SystemServices::MODIFIERKEYS_FLAGS;
     use windows::Win32::UI::Shell::{SHCreateStdEnumFmtEtc, DROPFILES};
 
+    // Files may have been deleted since the caller picked them
+    let files: Vec<PathBuf> = files
+        .iter()
+        .filter(|path| crate::paths::long_path(path).exists())
+        .cloned()
+        .collect();
+    let files = files.as_slice();
+
     if files.is_empty() {
         info!("start_drag called with empty files list");
         return false;
@@ -382,6 +362,7 @@ pub fn start_drag(files: &[PathBuf]) -> bool {
     // This allows the caller to handle clicks separately
     if result.0 == DRAGDROP_S_DROP || (result.is_ok() && drop_effect != DROPEFFECT_NONE) {
         info!("Drag was completed successfully");
+        crate::usage::mark_used(files);
         true
     } else if result.0 == DRAGDROP_S_CANCEL {
         info!("Drag was cancelled (user clicked without dragging or pressed ESC)");
@@ -399,3 +380,22 @@ pub fn start_drag(files: &[PathBuf]) -> bool {
 pub fn start_drag(_files: &[PathBuf]) -> bool {
     false
 }
+
+/// Drag the newest screenshot (tray icon drag). Skips entries that no longer
+/// exist and falls back to the next newest.
+pub fn start_latest_drag() -> bool {
+    let candidates = crate::get_latest_screenshots();
+    let Some(path) = candidates.iter().find(|path| {
+        let exists = crate::paths::long_path(path).exists();
+        if !exists {
+            debug!("Latest screenshot {:?} is gone, trying the next newest", path);
+        }
+        exists
+    }) else {
+        debug!("No screenshots available for tray drag");
+        return false;
+    };
+
+    info!("Starting tray drag with: {:?}", path);
+    start_drag(std::slice::from_ref(path))
+}
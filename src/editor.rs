@@ -0,0 +1,177 @@
+//! Opening screenshots in an external image editor
+//!
+//! Uses the editor configured in settings, or the shell "edit" verb when none is set.
+
+use anyhow::Result;
+use log::{debug, error, info};
+use parking_lot::Mutex;
+use std::path::{Path, PathBuf};
+
+/// Editor configured in settings (None = shell "edit" verb).
+/// Mirrored here so the tray and hotkey threads can use it without the settings lock.
+static PREFERRED_EDITOR: Mutex<Option<PathBuf>> = Mutex::new(None);
+
+/// Update the configured editor
+pub fn set_preferred_editor(editor: Option<PathBuf>) {
+    *PREFERRED_EDITOR.lock() = editor;
+}
+
+/// Get the configured editor
+pub fn preferred_editor() -> Option<PathBuf> {
+    PREFERRED_EDITOR.lock().clone()
+}
+
+/// Check an editor path before saving it to settings
+pub fn validate_editor(path: &Path) -> Result<()> {
+    if !path.is_file() {
+        anyhow::bail!("Editor not found: {}", path.display());
+    }
+    Ok(())
+}
+
+/// Short editor name for menus ("mspaint" for `C:\Windows\System32\mspaint.exe`)
+pub fn editor_name(editor: &Path) -> String {
+    editor
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_else(|| editor.display().to_string())
+}
+
+/// Quote one command line argument the way the MSVC runtime splits them, so
+/// paths with spaces, quotes or trailing backslashes arrive intact
+pub fn quote_argument(arg: &str) -> String {
+    if !arg.is_empty() && !arg.contains([' ', '\t', '"']) {
+        return arg.to_string();
+    }
+
+    let mut quoted = String::from("\"");
+    let mut backslashes = 0;
+    for ch in arg.chars() {
+        match ch {
+            '\\' => backslashes += 1,
+            '"' => {
+                // Backslashes before a quote are escapes; double them, then escape the quote
+                quoted.extend(std::iter::repeat_n('\\', backslashes * 2 + 1));
+                quoted.push('"');
+                backslashes = 0;
+            }
+            _ => {
+                quoted.extend(std::iter::repeat_n('\\', backslashes));
+                quoted.push(ch);
+                backslashes = 0;
+            }
+        }
+    }
+    // Backslashes before the closing quote would escape it
+    quoted.extend(std::iter::repeat_n('\\', backslashes * 2));
+    quoted.push('"');
+    quoted
+}
+
+/// Open `path` in the configured editor, or with the shell "edit" verb
+pub fn open_in_editor(path: &Path) -> Result<()> {
+    match preferred_editor() {
+        Some(editor) => launch_editor(&editor, path),
+        None => shell_edit(path),
+    }
+}
+
+/// Open the newest screenshot that still exists in the editor
+pub fn edit_latest() -> bool {
+    let candidates = crate::get_latest_screenshots();
+    let Some(path) = candidates
+        .iter()
+        .find(|path| crate::paths::long_path(path).exists())
+    else {
+        debug!("No screenshots available to edit");
+        return false;
+    };
+
+    match open_in_editor(path) {
+        Ok(()) => {
+            crate::usage::mark_used(std::slice::from_ref(path));
+            true
+        }
+        Err(e) => {
+            error!("Failed to open {:?} in editor: {}", path, e);
+            false
+        }
+    }
+}
+
+#[cfg(windows)]
+fn launch_editor(editor: &Path, path: &Path) -> Result<()> {
+    use std::os::windows::process::CommandExt;
+
+    info!("Opening {:?} in {:?}", path, editor);
+    std::process::Command::new(editor)
+        .raw_arg(quote_argument(&path.to_string_lossy()))
+        .spawn()?;
+    Ok(())
+}
+
+#[cfg(not(windows))]
+fn launch_editor(editor: &Path, path: &Path) -> Result<()> {
+    info!("Opening {:?} in {:?}", path, editor);
+    std::process::Command::new(editor).arg(path).spawn()?;
+    Ok(())
+}
+
+/// Open with the file type's registered "edit" verb
+#[cfg(windows)]
+fn shell_edit(path: &Path) -> Result<()> {
+    use std::ffi::OsStr;
+    use std::os::windows::ffi::OsStrExt;
+    use windows::core::{w, PCWSTR};
+    use windows::Win32::Foundation::HWND;
+    use windows::Win32::UI::Shell::ShellExecuteW;
+    use windows::Win32::UI::WindowsAndMessaging::SW_SHOWNORMAL;
+
+    info!("Opening {:?} with the shell edit verb", path);
+    let wide_path: Vec<u16> = OsStr::new(path)
+        .encode_wide()
+        .chain(std::iter::once(0))
+        .collect();
+
+    let result = unsafe {
+        ShellExecuteW(
+            HWND::default(),
+            w!("edit"),
+            PCWSTR(wide_path.as_ptr()),
+            PCWSTR::null(),
+            PCWSTR::null(),
+            SW_SHOWNORMAL,
+        )
+    };
+    // Values up to 32 are error codes
+    if result.0 as usize <= 32 {
+        anyhow::bail!("ShellExecute edit failed with code {}", result.0 as usize);
+    }
+    Ok(())
+}
+
+#[cfg(not(windows))]
+fn shell_edit(path: &Path) -> Result<()> {
+    open::that(path)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_quote_argument() {
+        assert_eq!(quote_argument(r"C:\Shots\a.png"), r"C:\Shots\a.png");
+        assert_eq!(quote_argument(r"C:\My Shots\a.png"), r#""C:\My Shots\a.png""#);
+        assert_eq!(quote_argument(r"C:\My Shots\"), r#""C:\My Shots\\""#);
+        assert_eq!(quote_argument(r#"a "b".png"#), r#""a \"b\".png""#);
+        assert_eq!(quote_argument(""), r#""""#);
+    }
+
+    #[test]
+    fn test_editor_name() {
+        assert_eq!(editor_name(&PathBuf::from("tools").join("mspaint.exe")), "mspaint");
+        assert_eq!(editor_name(&PathBuf::from("tools").join("gimp")), "gimp");
+    }
+}
@@ -0,0 +1,145 @@
+//! File names to leave alone
+//!
+//! Other tools often write into the screenshot folder too (a recorder's
+//! `Recording_*.png`, half-written `*.tmp` files). Names matching one of the
+//! user's exclude patterns aren't shown, converted, organized or indexed.
+//! Patterns are simple globs on the file name: `*` matches any run of
+//! characters and `?` a single one. Matching ignores case on Windows, like
+//! the file system.
+
+use parking_lot::Mutex;
+use std::path::Path;
+
+/// Patterns in effect, folded for comparison
+static PATTERNS: Mutex<Vec<String>> = Mutex::new(Vec::new());
+
+/// Use `patterns` from now on
+pub fn configure(patterns: &[String]) {
+    *PATTERNS.lock() = patterns
+        .iter()
+        .map(|p| fold(p.trim()))
+        .filter(|p| !p.is_empty())
+        .collect();
+}
+
+/// Whether `path`'s file name matches one of the configured patterns
+pub fn is_excluded(path: &Path) -> bool {
+    let patterns = PATTERNS.lock();
+    if patterns.is_empty() {
+        return false;
+    }
+    let Some(name) = path.file_name() else {
+        return false;
+    };
+    let name = fold(&name.to_string_lossy());
+    patterns.iter().any(|pattern| matches(pattern, &name))
+}
+
+/// Patterns typed as a comma-separated list, blanks dropped
+pub fn parse_list(text: &str) -> Vec<String> {
+    text.split(',')
+        .map(str::trim)
+        .filter(|p| !p.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+/// Patterns as the settings input shows them
+pub fn format_list(patterns: &[String]) -> String {
+    patterns.join(", ")
+}
+
+/// Case-fold on Windows, where `Shot.PNG` and `shot.png` are the same file
+fn fold(text: &str) -> String {
+    if cfg!(windows) {
+        text.to_lowercase()
+    } else {
+        text.to_string()
+    }
+}
+
+/// Whether `name` matches the glob `pattern` as a whole
+pub fn matches(pattern: &str, name: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let name: Vec<char> = name.chars().collect();
+    let (mut p, mut n) = (0, 0);
+    // Where the last `*` was, and how much of the name it has taken so far
+    let mut star: Option<(usize, usize)> = None;
+
+    while n < name.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                star = Some((p, n));
+                p += 1;
+            }
+            Some(&c) if c == '?' || c == name[n] => {
+                p += 1;
+                n += 1;
+            }
+            _ => match star {
+                // Let the last `*` take one more character and retry
+                Some((star_p, star_n)) => {
+                    star = Some((star_p, star_n + 1));
+                    p = star_p + 1;
+                    n = star_n + 1;
+                }
+                None => return false,
+            },
+        }
+    }
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_literal_names() {
+        assert!(matches("shot.png", "shot.png"));
+        assert!(!matches("shot.png", "shot.png.tmp"));
+        assert!(!matches("shot.png", "my shot.png"));
+        assert!(matches("", ""));
+        assert!(!matches("", "shot.png"));
+    }
+
+    #[test]
+    fn test_star_wildcard() {
+        assert!(matches("*.tmp", "shot.tmp"));
+        assert!(matches("*.tmp", ".tmp"));
+        assert!(!matches("*.tmp", "shot.tmp.png"));
+        assert!(matches("Recording_*.png", "Recording_2024-01-15.png"));
+        assert!(matches("Recording_*.png", "Recording_.png"));
+        assert!(!matches("Recording_*.png", "Screenshot Recording_1.png"));
+        assert!(matches("*Recording*", "Screenshot Recording_1.png"));
+        assert!(matches("*", "anything.png"));
+        assert!(matches("**", ""));
+        // Backtracks past an early partial match
+        assert!(matches("*.png.*", "a.png.b.png.tmp"));
+        assert!(matches("a*b*c", "a_b_b_c"));
+        assert!(!matches("a*b*c", "a_c_b"));
+    }
+
+    #[test]
+    fn test_question_wildcard() {
+        assert!(matches("shot?.png", "shot1.png"));
+        assert!(!matches("shot?.png", "shot.png"));
+        assert!(!matches("shot?.png", "shot12.png"));
+        assert!(matches("????.png", "스크린샷.png"));
+        assert!(matches("*_??.png", "capture_01.png"));
+    }
+
+    #[test]
+    fn test_case_folding() {
+        assert_eq!(fold("Recording_*.PNG") == fold("recording_*.png"), cfg!(windows));
+        assert!(matches(&fold("*.TMP"), &fold("shot.tmp")) == cfg!(windows));
+    }
+
+    #[test]
+    fn test_parse_list() {
+        assert_eq!(parse_list("*.tmp, Recording_*.png,,  "), vec!["*.tmp", "Recording_*.png"]);
+        assert!(parse_list(" , ").is_empty());
+        let patterns = parse_list("*.tmp,Recording_*.png");
+        assert_eq!(format_list(&patterns), "*.tmp, Recording_*.png");
+    }
+}
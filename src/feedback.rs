@@ -0,0 +1,63 @@
+//! Audio cue for user actions that landed while the window may be hidden
+//!
+//! Only for explicit actions (copy, save, export) — never for passive events
+//! like watcher detections. Off unless `sound_feedback` is enabled, and
+//! silent when Windows feedback sounds are turned off.
+
+use log::debug;
+
+/// Play the system "asterisk" sound if sound feedback is enabled
+pub fn play_success(enabled: bool) {
+    if !enabled {
+        return;
+    }
+    if !system_sounds_enabled() {
+        debug!("System feedback sounds are off, skipping cue");
+        return;
+    }
+    play_alias("SystemAsterisk");
+}
+
+/// Check the Windows "warning beeper" setting (Ease of Access feedback sounds)
+#[cfg(windows)]
+fn system_sounds_enabled() -> bool {
+    use windows::Win32::Foundation::BOOL;
+    use windows::Win32::UI::WindowsAndMessaging::{
+        SystemParametersInfoW, SPI_GETBEEP, SYSTEM_PARAMETERS_INFO_UPDATE_FLAGS,
+    };
+
+    let mut enabled = BOOL(1);
+    let result = unsafe {
+        SystemParametersInfoW(
+            SPI_GETBEEP,
+            0,
+            Some(&mut enabled as *mut BOOL as *mut std::ffi::c_void),
+            SYSTEM_PARAMETERS_INFO_UPDATE_FLAGS(0),
+        )
+    };
+    // Assume sounds are allowed if the query fails
+    result.is_err() || enabled.as_bool()
+}
+
+#[cfg(not(windows))]
+fn system_sounds_enabled() -> bool {
+    false
+}
+
+/// Play a system sound alias asynchronously
+#[cfg(windows)]
+fn play_alias(alias: &str) {
+    use windows::core::PCWSTR;
+    use windows::Win32::Media::Audio::{PlaySoundW, SND_ALIAS, SND_ASYNC, SND_NODEFAULT};
+
+    let wide: Vec<u16> = alias.encode_utf16().chain(std::iter::once(0)).collect();
+    let played = unsafe {
+        PlaySoundW(PCWSTR(wide.as_ptr()), None, SND_ALIAS | SND_ASYNC | SND_NODEFAULT)
+    };
+    if !played.as_bool() {
+        debug!("PlaySound failed for {}", alias);
+    }
+}
+
+#[cfg(not(windows))]
+fn play_alias(_alias: &str) {}
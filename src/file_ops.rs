@@ -0,0 +1,454 @@
+//! Renames and deletes that cope with read-only and locked files
+//!
+//! Windows refuses to delete a read-only file, and a file another process
+//! holds open can't be moved or deleted until it lets go. A single failed
+//! `fs::rename`/`fs::remove_file` leaves the pipeline half done (a converted
+//! copy next to a source that couldn't be deleted), so locked files are
+//! retried a few times, and the read-only attribute is cleared only when the
+//! user opted in. Anything still blocked is sent to the UI as
+//! [`AppMessage::FileBlocked`].
+
+use crossbeam_channel::Sender;
+use log::{info, warn};
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+
+use crate::paths;
+use crate::AppMessage;
+
+/// Waits between attempts while another process holds the file
+pub const LOCK_RETRY_DELAYS: [Duration; 3] = [
+    Duration::from_millis(200),
+    Duration::from_millis(500),
+    Duration::from_millis(1500),
+];
+
+/// Win32 errors for a file another process has open
+#[cfg(windows)]
+pub const ERROR_SHARING_VIOLATION: i32 = 32;
+#[cfg(windows)]
+const ERROR_LOCK_VIOLATION: i32 = 33;
+
+/// Clear the read-only attribute instead of asking ("Handle read-only files")
+static CLEAR_READ_ONLY: AtomicBool = AtomicBool::new(false);
+
+/// Where blocked operations are reported
+static MESSAGE_SENDER: OnceLock<Sender<AppMessage>> = OnceLock::new();
+
+/// Report blocked operations to the UI from now on
+pub fn init(message_tx: Sender<AppMessage>) {
+    let _ = MESSAGE_SENDER.set(message_tx);
+}
+
+/// Set whether read-only files may be made writable without asking
+pub fn set_clear_read_only(enabled: bool) {
+    CLEAR_READ_ONLY.store(enabled, Ordering::Relaxed);
+}
+
+/// A file operation that can be reported and retried
+#[derive(Debug, Clone, PartialEq)]
+pub enum FileOp {
+    Remove(PathBuf),
+    Rename { from: PathBuf, to: PathBuf },
+    /// Swap a rewritten copy (`from`) in over the file at `to`
+    Replace { from: PathBuf, to: PathBuf },
+}
+
+impl FileOp {
+    /// The file being operated on
+    pub fn path(&self) -> &Path {
+        match self {
+            FileOp::Remove(path) => path,
+            FileOp::Rename { from, .. } => from,
+            FileOp::Replace { to, .. } => to,
+        }
+    }
+}
+
+/// Why an operation couldn't go ahead
+#[derive(Debug, Clone, PartialEq)]
+pub enum BlockReason {
+    /// Read-only, and clearing that isn't allowed
+    ReadOnly,
+    /// Still open in another process after every retry (process names, when known)
+    Locked { owners: Vec<String> },
+}
+
+#[derive(Debug)]
+pub enum FileOpError {
+    Blocked(BlockReason),
+    Io(io::Error),
+}
+
+impl fmt::Display for FileOpError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FileOpError::Blocked(BlockReason::ReadOnly) => write!(f, "file is read-only"),
+            FileOpError::Blocked(BlockReason::Locked { owners }) if owners.is_empty() => {
+                write!(f, "file is in use by another process")
+            }
+            FileOpError::Blocked(BlockReason::Locked { owners }) => {
+                write!(f, "file is in use by {}", owners.join(", "))
+            }
+            FileOpError::Io(e) => e.fmt(f),
+        }
+    }
+}
+
+impl std::error::Error for FileOpError {}
+
+impl From<io::Error> for FileOpError {
+    fn from(e: io::Error) -> Self {
+        FileOpError::Io(e)
+    }
+}
+
+/// Delete a file, reporting it to the UI if it's read-only or locked
+pub fn remove_file(path: &Path) -> Result<(), FileOpError> {
+    run_and_report(FileOp::Remove(path.to_path_buf()))
+}
+
+/// Rename a file, reporting it to the UI if it's read-only or locked
+pub fn rename(from: &Path, to: &Path) -> Result<(), FileOpError> {
+    run_and_report(FileOp::Rename {
+        from: from.to_path_buf(),
+        to: to.to_path_buf(),
+    })
+}
+
+/// Replace `to` with `from`, reporting it to the UI if `to` is read-only or
+/// locked. The read-only attribute stays on the new file.
+pub fn replace(from: &Path, to: &Path) -> Result<(), FileOpError> {
+    run_and_report(FileOp::Replace {
+        from: from.to_path_buf(),
+        to: to.to_path_buf(),
+    })
+}
+
+/// Run a reported operation again (from the read-only prompt). Doesn't report.
+pub fn retry(op: &FileOp, clear_read_only: bool) -> Result<(), FileOpError> {
+    run(op, clear_read_only)
+}
+
+fn run_and_report(op: FileOp) -> Result<(), FileOpError> {
+    let result = run(&op, CLEAR_READ_ONLY.load(Ordering::Relaxed));
+    if let Err(FileOpError::Blocked(reason)) = &result {
+        report(op, reason.clone());
+    }
+    result
+}
+
+/// Tell the UI an operation was blocked
+pub fn report(op: FileOp, reason: BlockReason) {
+    warn!("{:?} blocked: {:?}", op, reason);
+    if let Some(tx) = MESSAGE_SENDER.get() {
+        let _ = tx.send(AppMessage::FileBlocked(op, reason));
+    }
+}
+
+fn run(op: &FileOp, clear_read_only: bool) -> Result<(), FileOpError> {
+    let path = op.path();
+    if !is_read_only(path) {
+        return with_lock_retries(op);
+    }
+
+    match op {
+        FileOp::Remove(_) => {
+            // Only Windows refuses these, but check up front so every
+            // platform asks the same way
+            if !clear_read_only {
+                return Err(FileOpError::Blocked(BlockReason::ReadOnly));
+            }
+            set_read_only(path, false)?;
+            let result = with_lock_retries(op);
+            if result.is_err() {
+                let _ = set_read_only(path, true);
+            }
+            result
+        }
+        FileOp::Rename { to, .. } => {
+            // Renaming a read-only file usually works; clear it only if refused
+            match with_lock_retries(op) {
+                Err(FileOpError::Io(e)) if e.kind() == io::ErrorKind::PermissionDenied => {
+                    if !clear_read_only {
+                        return Err(FileOpError::Blocked(BlockReason::ReadOnly));
+                    }
+                    set_read_only(path, false)?;
+                    match with_lock_retries(op) {
+                        Ok(()) => Ok(set_read_only(to, true)?),
+                        Err(e) => {
+                            let _ = set_read_only(path, true);
+                            Err(e)
+                        }
+                    }
+                }
+                result => result,
+            }
+        }
+        FileOp::Replace { .. } => {
+            // Windows won't rename over a read-only file
+            if !clear_read_only {
+                return Err(FileOpError::Blocked(BlockReason::ReadOnly));
+            }
+            set_read_only(path, false)?;
+            let result = with_lock_retries(op);
+            let _ = set_read_only(path, true);
+            result
+        }
+    }
+}
+
+fn attempt(op: &FileOp) -> io::Result<()> {
+    match op {
+        FileOp::Remove(path) => fs::remove_file(paths::long_path(path)),
+        FileOp::Rename { from, to } | FileOp::Replace { from, to } => {
+            fs::rename(paths::long_path(from), paths::long_path(to))
+        }
+    }
+}
+
+fn with_lock_retries(op: &FileOp) -> Result<(), FileOpError> {
+    retry_locked(op.path(), || attempt(op))
+}
+
+/// Run `f` on `path`, waiting out [`LOCK_RETRY_DELAYS`] while another
+/// process has the file open. Doesn't report.
+pub fn retry_locked<T>(path: &Path, mut f: impl FnMut() -> io::Result<T>) -> Result<T, FileOpError> {
+    let mut delays = LOCK_RETRY_DELAYS.iter();
+    loop {
+        match f() {
+            Ok(value) => return Ok(value),
+            Err(e) if is_locked(&e) => match delays.next() {
+                Some(delay) => {
+                    info!("{:?} is in use, retrying in {:?}", path, delay);
+                    std::thread::sleep(*delay);
+                }
+                None => {
+                    return Err(FileOpError::Blocked(BlockReason::Locked {
+                        owners: lock_owners(path),
+                    }));
+                }
+            },
+            Err(e) => return Err(FileOpError::Io(e)),
+        }
+    }
+}
+
+/// Whether another process has `path` open right now. Doesn't wait.
+#[cfg(windows)]
+pub fn is_in_use(path: &Path) -> bool {
+    use std::os::windows::fs::OpenOptionsExt;
+
+    // Sharing nothing fails while anyone else has it open
+    fs::OpenOptions::new()
+        .read(true)
+        .share_mode(0)
+        .open(paths::long_path(path))
+        .is_err_and(|e| is_locked(&e))
+}
+
+#[cfg(not(windows))]
+pub fn is_in_use(_path: &Path) -> bool {
+    false
+}
+
+/// Whether an error means another process has the file open
+fn is_locked(e: &io::Error) -> bool {
+    #[cfg(windows)]
+    {
+        matches!(
+            e.raw_os_error(),
+            Some(ERROR_SHARING_VIOLATION) | Some(ERROR_LOCK_VIOLATION)
+        )
+    }
+    #[cfg(not(windows))]
+    {
+        let _ = e;
+        false
+    }
+}
+
+fn is_read_only(path: &Path) -> bool {
+    fs::metadata(paths::long_path(path)).is_ok_and(|m| m.permissions().readonly())
+}
+
+fn set_read_only(path: &Path, read_only: bool) -> io::Result<()> {
+    let fs_path = paths::long_path(path);
+    let mut permissions = fs::metadata(&fs_path)?.permissions();
+    permissions.set_readonly(read_only);
+    fs::set_permissions(&fs_path, permissions)?;
+    info!("Set read-only = {} on {:?}", read_only, path);
+    Ok(())
+}
+
+/// Names of the processes holding `path` open, via the Restart Manager
+#[cfg(windows)]
+pub fn lock_owners(path: &Path) -> Vec<String> {
+    use std::ffi::OsStr;
+    use std::os::windows::ffi::OsStrExt;
+    use windows::core::{PCWSTR, PWSTR};
+    use windows::Win32::Foundation::ERROR_SUCCESS;
+    use windows::Win32::System::RestartManager::{
+        RmEndSession, RmGetList, RmRegisterResources, RmStartSession, CCH_RM_SESSION_KEY,
+        RM_PROCESS_INFO,
+    };
+
+    // The Restart Manager doesn't accept \\?\ paths
+    let wide: Vec<u16> = OsStr::new(&paths::display_path(path))
+        .encode_wide()
+        .chain(std::iter::once(0))
+        .collect();
+    let mut session = 0u32;
+    let mut key = [0u16; CCH_RM_SESSION_KEY as usize + 1];
+
+    unsafe {
+        if RmStartSession(&mut session, 0, PWSTR(key.as_mut_ptr())) != ERROR_SUCCESS {
+            return Vec::new();
+        }
+
+        let mut owners = Vec::new();
+        let files = [PCWSTR(wide.as_ptr())];
+        if RmRegisterResources(session, Some(&files), None, None) == ERROR_SUCCESS {
+            let mut needed = 0u32;
+            let mut count = 0u32;
+            let mut reasons = 0u32;
+            // First call only asks how many entries there are
+            let _ = RmGetList(session, &mut needed, &mut count, None, &mut reasons);
+            if needed > 0 {
+                let mut infos = vec![RM_PROCESS_INFO::default(); needed as usize];
+                count = needed;
+                if RmGetList(
+                    session,
+                    &mut needed,
+                    &mut count,
+                    Some(infos.as_mut_ptr()),
+                    &mut reasons,
+                ) == ERROR_SUCCESS
+                {
+                    owners = infos[..count as usize]
+                        .iter()
+                        .map(|info| {
+                            let name = &info.strAppName;
+                            let len = name.iter().position(|&c| c == 0).unwrap_or(name.len());
+                            String::from_utf16_lossy(&name[..len])
+                        })
+                        .filter(|name| !name.is_empty())
+                        .collect();
+                }
+            }
+        }
+
+        let _ = RmEndSession(session);
+        owners
+    }
+}
+
+#[cfg(not(windows))]
+pub fn lock_owners(_path: &Path) -> Vec<String> {
+    Vec::new()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::scratch_dir;
+
+    #[test]
+    fn test_read_only_remove() {
+        let dir = scratch_dir("fileops-remove");
+        let file = dir.join("shot.png");
+        fs::write(&file, b"png").unwrap();
+        set_read_only(&file, true).unwrap();
+
+        // Not opted in: blocked, file left as it was
+        let op = FileOp::Remove(file.clone());
+        assert!(matches!(
+            run(&op, false),
+            Err(FileOpError::Blocked(BlockReason::ReadOnly))
+        ));
+        assert!(file.exists());
+        assert!(is_read_only(&file));
+
+        // Opted in (or "Make writable" in the prompt): deleted
+        retry(&op, true).unwrap();
+        assert!(!file.exists());
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_rename_keeps_read_only() {
+        let dir = scratch_dir("fileops-rename");
+        let from = dir.join("shot.png");
+        let to = dir.join("moved.png");
+        fs::write(&from, b"png").unwrap();
+        set_read_only(&from, true).unwrap();
+
+        run(
+            &FileOp::Rename {
+                from: from.clone(),
+                to: to.clone(),
+            },
+            false,
+        )
+        .unwrap();
+        assert!(!from.exists());
+        assert!(is_read_only(&to));
+
+        set_read_only(&to, false).unwrap();
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_replace_read_only() {
+        let dir = scratch_dir("fileops-replace");
+        let file = dir.join("shot.jpg");
+        let copy = dir.join("shot.jpg.strip-tmp");
+        fs::write(&file, b"old").unwrap();
+        fs::write(&copy, b"new").unwrap();
+        set_read_only(&file, true).unwrap();
+
+        let op = FileOp::Replace {
+            from: copy.clone(),
+            to: file.clone(),
+        };
+        assert_eq!(op.path(), file);
+        assert!(matches!(
+            run(&op, false),
+            Err(FileOpError::Blocked(BlockReason::ReadOnly))
+        ));
+        assert_eq!(fs::read(&file).unwrap(), b"old");
+        assert!(copy.exists());
+
+        retry(&op, true).unwrap();
+        assert_eq!(fs::read(&file).unwrap(), b"new");
+        assert!(!copy.exists());
+        assert!(is_read_only(&file));
+
+        set_read_only(&file, false).unwrap();
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_writable_file_unaffected() {
+        let dir = scratch_dir("fileops-writable");
+        let file = dir.join("shot.png");
+        fs::write(&file, b"png").unwrap();
+
+        run(&FileOp::Remove(file.clone()), false).unwrap();
+        assert!(!file.exists());
+
+        // Missing files are plain I/O errors, not blocked
+        assert!(matches!(
+            run(&FileOp::Remove(file), false),
+            Err(FileOpError::Io(_))
+        ));
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,163 @@
+//! Capture dates embedded in screenshot file names
+//!
+//! Files restored from a backup all carry the restore date as their mtime,
+//! which would put years of screenshots into one organizer folder and one
+//! gallery group. Most screenshot tools put the capture date in the name
+//! ("Screenshot 2022-03-14 101530.png", "스크린샷 2023-01-02 ..."), so when
+//! enabled that date is used first and the mtime is only the fallback.
+
+use chrono::{Local, NaiveDate, NaiveTime, TimeZone};
+use log::warn;
+use parking_lot::Mutex;
+use regex::{Captures, Regex};
+use std::path::Path;
+use std::time::SystemTime;
+
+/// Patterns shipped as the default, tried in order. Each needs `year`,
+/// `month` and `day` groups; `hour`, `minute` and `second` are used when present.
+pub const DEFAULT_PATTERNS: &[&str] = &[
+    // Windows Snipping Tool in every language: "Screenshot 2022-03-14 101530",
+    // "スクリーンショット 2022-03-14 101530", "스크린샷 2022-03-14 101530"
+    r"(?:^|\D)(?P<year>\d{4})-(?P<month>\d{2})-(?P<day>\d{2})[ _](?P<hour>\d{2})(?P<minute>\d{2})(?P<second>\d{2})(?:\D|$)",
+    // Android and ShareX: "Screenshot_20220314-101530", "Screenshot_20220314_101530"
+    r"(?:^|\D)(?P<year>\d{4})(?P<month>\d{2})(?P<day>\d{2})[_-](?P<hour>\d{2})(?P<minute>\d{2})(?P<second>\d{2})(?:\D|$)",
+    // Any other ISO date: "Screenshot 2022-03-14 at 10.15.30", "스크린샷 2023-01-02 오후 3.04.05"
+    r"(?:^|\D)(?P<year>\d{4})-(?P<month>\d{2})-(?P<day>\d{2})(?:\D|$)",
+    // Written-out Korean and Japanese dates: "2023년 1월 2일", "2023年1月2日"
+    r"(?P<year>\d{4})년\s*(?P<month>\d{1,2})월\s*(?P<day>\d{1,2})일",
+    r"(?P<year>\d{4})年(?P<month>\d{1,2})月(?P<day>\d{1,2})日",
+];
+
+/// Compiled patterns while file-name dates are enabled
+static PATTERNS: Mutex<Option<Vec<Regex>>> = Mutex::new(None);
+
+/// Default patterns as settings values
+pub fn default_patterns() -> Vec<String> {
+    DEFAULT_PATTERNS.iter().map(|p| p.to_string()).collect()
+}
+
+/// Turn file-name dates on (with `patterns`) or off
+pub fn configure(enabled: bool, patterns: &[String]) {
+    *PATTERNS.lock() = enabled.then(|| compile(patterns));
+}
+
+/// Compile patterns, skipping (and logging) ones that don't parse or lack a
+/// year, month or day group
+pub fn compile(patterns: &[String]) -> Vec<Regex> {
+    patterns
+        .iter()
+        .filter_map(|pattern| match Regex::new(pattern) {
+            Ok(regex) => {
+                let names: Vec<_> = regex.capture_names().flatten().collect();
+                if ["year", "month", "day"].iter().all(|g| names.contains(g)) {
+                    Some(regex)
+                } else {
+                    warn!("File name date pattern needs year, month and day groups: {}", pattern);
+                    None
+                }
+            }
+            Err(e) => {
+                warn!("Invalid file name date pattern {:?}: {}", pattern, e);
+                None
+            }
+        })
+        .collect()
+}
+
+/// Capture time from a file name; the first pattern giving a valid date wins
+pub fn parse(file_name: &str, patterns: &[Regex]) -> Option<SystemTime> {
+    patterns
+        .iter()
+        .filter_map(|regex| regex.captures(file_name))
+        .find_map(|caps| time_from_captures(&caps))
+}
+
+fn time_from_captures(caps: &Captures) -> Option<SystemTime> {
+    let number = |name: &str| caps.name(name).and_then(|m| m.as_str().parse::<u32>().ok());
+
+    let date = NaiveDate::from_ymd_opt(number("year")? as i32, number("month")?, number("day")?)?;
+    let time = match (number("hour"), number("minute"), number("second")) {
+        (Some(h), Some(m), s) => NaiveTime::from_hms_opt(h, m, s.unwrap_or(0))?,
+        _ => NaiveTime::MIN,
+    };
+    let local = Local.from_local_datetime(&date.and_time(time)).earliest()?;
+    Some(local.into())
+}
+
+/// When a screenshot was taken: the date in its file name when enabled and
+/// found, else `modified`
+pub fn capture_time(path: &Path, modified: SystemTime) -> SystemTime {
+    let patterns = PATTERNS.lock();
+    patterns
+        .as_deref()
+        .zip(path.file_name().and_then(|n| n.to_str()))
+        .and_then(|(patterns, name)| parse(name, patterns))
+        .unwrap_or(modified)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{DateTime, Datelike, Timelike};
+
+    fn parse_default(name: &str) -> Option<DateTime<Local>> {
+        parse(name, &compile(&default_patterns())).map(DateTime::<Local>::from)
+    }
+
+    fn ymd_hms(dt: DateTime<Local>) -> (i32, u32, u32, u32, u32, u32) {
+        (dt.year(), dt.month(), dt.day(), dt.hour(), dt.minute(), dt.second())
+    }
+
+    #[test]
+    fn test_windows_names_in_shipped_locales() {
+        for name in [
+            "Screenshot 2022-03-14 101530.png",
+            "スクリーンショット 2022-03-14 101530.png",
+            "스크린샷 2022-03-14 101530.png",
+        ] {
+            let dt = parse_default(name).unwrap_or_else(|| panic!("no date in {name}"));
+            assert_eq!(ymd_hms(dt), (2022, 3, 14, 10, 15, 30), "{name}");
+        }
+    }
+
+    #[test]
+    fn test_other_formats() {
+        let dt = parse_default("Screenshot_20230102-150405.png").unwrap();
+        assert_eq!(ymd_hms(dt), (2023, 1, 2, 15, 4, 5));
+
+        // Date only: midnight
+        for name in [
+            "Screenshot 2023-01-02 at 15.04.05.png",
+            "스크린샷 2023-01-02 오후 3.04.05.png",
+            "スクリーンショット 2023-01-02 15.04.05.png",
+            "캡처 2023년 1월 2일.png",
+            "キャプチャ 2023年1月2日.png",
+        ] {
+            let dt = parse_default(name).unwrap_or_else(|| panic!("no date in {name}"));
+            assert_eq!(ymd_hms(dt), (2023, 1, 2, 0, 0, 0), "{name}");
+        }
+    }
+
+    #[test]
+    fn test_no_date() {
+        assert!(parse_default("Screenshot (12).png").is_none());
+        assert!(parse_default("image.png").is_none());
+        // Impossible dates and digits inside longer numbers are ignored
+        assert!(parse_default("Screenshot 2022-13-40 101530.png").is_none());
+        assert!(parse_default("id_120220314_1015301.png").is_none());
+    }
+
+    #[test]
+    fn test_custom_patterns() {
+        let patterns = compile(&[
+            // Missing day group: skipped
+            r"(?P<year>\d{4})-(?P<month>\d{2})".to_string(),
+            // Doesn't compile: skipped
+            r"(?P<year>\d{4}".to_string(),
+            r"(?P<day>\d{2})\.(?P<month>\d{2})\.(?P<year>\d{4})".to_string(),
+        ]);
+        assert_eq!(patterns.len(), 1);
+        let dt = DateTime::<Local>::from(parse("Bildschirmfoto 14.03.2022.png", &patterns).unwrap());
+        assert_eq!((dt.year(), dt.month(), dt.day()), (2022, 3, 14));
+    }
+}
@@ -0,0 +1,224 @@
+//! Polling for folders without change notifications
+//!
+//! Network shares and cloud-synced folders often don't report changes, or
+//! report only some of them. For those the watcher polls instead: each
+//! refresh compares the folder with the last snapshot and returns the files
+//! that appeared or disappeared. Adding, removing or renaming a file changes
+//! its directory's mtime, so only directories whose mtime moved are listed
+//! again and the rest cost one metadata call each. Some sync clients don't
+//! keep directory mtimes, so every [`FULL_REFRESH_EVERY`]th refresh lists
+//! everything anyway.
+
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+use crate::index_session::dir_mtime;
+use crate::paths;
+
+/// Every this many refreshes, all directories are listed again
+const FULL_REFRESH_EVERY: u32 = 12;
+
+/// A listing taken this soon after its directory changed isn't trusted: a
+/// second change within the mtime's resolution wouldn't move it
+const MTIME_RESOLUTION: Duration = Duration::from_secs(2);
+
+/// One directory as last listed
+struct Listing {
+    /// `None` when the listing should be taken again next time
+    mtime: Option<SystemTime>,
+    files: HashSet<PathBuf>,
+    dirs: Vec<PathBuf>,
+}
+
+/// Files that appeared or disappeared since the last refresh, sorted
+#[derive(Debug, Default, PartialEq)]
+pub struct Changes {
+    pub created: Vec<PathBuf>,
+    pub removed: Vec<PathBuf>,
+}
+
+impl Changes {
+    pub fn is_empty(&self) -> bool {
+        self.created.is_empty() && self.removed.is_empty()
+    }
+}
+
+/// Files under a folder as of the last refresh
+pub struct FolderSnapshot {
+    root: PathBuf,
+    /// Which files are tracked
+    wanted: fn(&Path) -> bool,
+    listings: HashMap<PathBuf, Listing>,
+    refreshes: u32,
+}
+
+impl FolderSnapshot {
+    /// Snapshot of the files under `root` that `wanted` accepts
+    pub fn new(root: &Path, wanted: fn(&Path) -> bool) -> Self {
+        let mut snapshot = Self {
+            root: root.to_path_buf(),
+            wanted,
+            listings: HashMap::new(),
+            refreshes: 0,
+        };
+        snapshot.update(true);
+        snapshot
+    }
+
+    /// Files currently in the snapshot
+    pub fn file_count(&self) -> usize {
+        self.listings.values().map(|l| l.files.len()).sum()
+    }
+
+    /// Compare the folder with the snapshot and bring the snapshot up to date
+    pub fn refresh(&mut self) -> Changes {
+        self.refreshes = self.refreshes.wrapping_add(1);
+        self.update(self.refreshes % FULL_REFRESH_EVERY == 0)
+    }
+
+    /// Walk the folder, listing directories whose mtime changed (or all of
+    /// them when `full`)
+    fn update(&mut self, full: bool) -> Changes {
+        let mut changes = Changes::default();
+        let mut seen = HashSet::new();
+        let mut pending = vec![self.root.clone()];
+
+        while let Some(dir) = pending.pop() {
+            // Gone (or unreadable): dropped with its files below
+            let Some(mtime) = dir_mtime(&dir) else {
+                continue;
+            };
+            let current = self.listings.get(&dir);
+            if full || current.and_then(|l| l.mtime) != Some(mtime) {
+                let listing = self.list(&dir, mtime);
+                match current {
+                    Some(old) => {
+                        changes
+                            .created
+                            .extend(listing.files.difference(&old.files).cloned());
+                        changes
+                            .removed
+                            .extend(old.files.difference(&listing.files).cloned());
+                    }
+                    None => changes.created.extend(listing.files.iter().cloned()),
+                }
+                self.listings.insert(dir.clone(), listing);
+            }
+            pending.extend(self.listings[&dir].dirs.iter().cloned());
+            seen.insert(dir);
+        }
+
+        // Directories that disappeared take their files with them
+        self.listings.retain(|dir, listing| {
+            if seen.contains(dir) {
+                return true;
+            }
+            changes.removed.extend(listing.files.drain());
+            false
+        });
+
+        changes.created.sort();
+        changes.removed.sort();
+        changes
+    }
+
+    /// List one directory. Symlinked directories aren't followed, so a link
+    /// back up the tree can't loop.
+    fn list(&self, dir: &Path, mtime: SystemTime) -> Listing {
+        let mut files = HashSet::new();
+        let mut dirs = Vec::new();
+        if let Ok(entries) = fs::read_dir(paths::long_path(dir)) {
+            for entry in entries.flatten() {
+                let Ok(file_type) = entry.file_type() else {
+                    continue;
+                };
+                let path = dir.join(entry.file_name());
+                if file_type.is_dir() {
+                    dirs.push(path);
+                } else if file_type.is_file() && (self.wanted)(&path) {
+                    files.insert(path);
+                }
+            }
+        }
+        let settled = SystemTime::now()
+            .duration_since(mtime)
+            .is_ok_and(|age| age >= MTIME_RESOLUTION);
+        Listing {
+            mtime: settled.then_some(mtime),
+            files,
+            dirs,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::scratch_dir;
+
+    fn is_png(path: &Path) -> bool {
+        path.extension().is_some_and(|ext| ext == "png")
+    }
+
+    #[test]
+    fn test_refresh_changes() {
+        let dir = scratch_dir("snapshot");
+        fs::create_dir_all(dir.join("2024-01-15")).unwrap();
+        fs::write(dir.join("a.png"), b"png").unwrap();
+        fs::write(dir.join("2024-01-15").join("b.png"), b"png").unwrap();
+        fs::write(dir.join("notes.txt"), b"txt").unwrap();
+
+        let mut snapshot = FolderSnapshot::new(&dir, is_png);
+        assert_eq!(snapshot.file_count(), 2);
+        assert!(snapshot.refresh().is_empty());
+
+        // New files, including in a new subdirectory; other files are ignored
+        fs::write(dir.join("c.png"), b"png").unwrap();
+        fs::write(dir.join("d.txt"), b"txt").unwrap();
+        fs::create_dir_all(dir.join("2024-01-16")).unwrap();
+        fs::write(dir.join("2024-01-16").join("e.png"), b"png").unwrap();
+        let changes = snapshot.refresh();
+        assert_eq!(changes.created, vec![dir.join("2024-01-16").join("e.png"), dir.join("c.png")]);
+        assert!(changes.removed.is_empty());
+        assert!(snapshot.refresh().is_empty());
+
+        // A rename shows as one removed and one created file
+        fs::rename(dir.join("a.png"), dir.join("2024-01-15").join("a.png")).unwrap();
+        let changes = snapshot.refresh();
+        assert_eq!(changes.created, vec![dir.join("2024-01-15").join("a.png")]);
+        assert_eq!(changes.removed, vec![dir.join("a.png")]);
+
+        // A removed directory takes its files with it
+        fs::remove_dir_all(dir.join("2024-01-15")).unwrap();
+        let changes = snapshot.refresh();
+        assert!(changes.created.is_empty());
+        assert_eq!(
+            changes.removed,
+            vec![dir.join("2024-01-15").join("a.png"), dir.join("2024-01-15").join("b.png")]
+        );
+        assert_eq!(snapshot.file_count(), 2);
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_unchanged_directories_are_skipped() {
+        let dir = scratch_dir("snapshot-skip");
+        fs::write(dir.join("a.png"), b"png").unwrap();
+
+        let mut snapshot = FolderSnapshot::new(&dir, is_png);
+        // Pretend the listing was taken long after the last change, then
+        // drop a file from it: only a full refresh notices
+        let listing = snapshot.listings.get_mut(&dir).unwrap();
+        listing.mtime = dir_mtime(&dir);
+        listing.files.clear();
+        for _ in 1..FULL_REFRESH_EVERY {
+            assert!(snapshot.refresh().is_empty());
+        }
+        assert_eq!(snapshot.refresh().created, vec![dir.join("a.png")]);
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
@@ -10,9 +10,20 @@ use parking_lot::Mutex;
 use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
 use std::sync::OnceLock;
 
-use crate::tray::toggle_window;
+use crate::tray::{move_window_to_monitor, toggle_window};
 use crate::AppMessage;
 
+/// Actions that can be bound to extra global hotkeys
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HotkeyAction {
+    /// Move the window to the next monitor
+    NextMonitor,
+    /// Move the window to the previous monitor
+    PreviousMonitor,
+    /// Open the latest screenshot in the editor
+    EditLatest,
+}
+
 /// Global flag to track if hotkey is enabled at runtime
 static HOTKEY_ENABLED: AtomicBool = AtomicBool::new(true);
 
@@ -22,6 +33,25 @@ static CURRENT_HOTKEY_ID: AtomicU32 = AtomicU32::new(0);
 /// Current registered hotkey (for unregistering)
 static CURRENT_HOTKEY: Mutex<Option<HotKey>> = Mutex::new(None);
 
+/// Why the toggle hotkey couldn't be registered (usually another app owns it)
+static TOGGLE_ERROR: Mutex<Option<String>> = Mutex::new(None);
+
+/// Combinations offered when the toggle hotkey is taken. Win+Shift+S is
+/// left out: it belongs to the Snipping Tool.
+pub const FALLBACK_HOTKEYS: [&str; 8] = [
+    "Ctrl+Shift+S",
+    "Ctrl+Shift+X",
+    "Ctrl+Shift+Q",
+    "Ctrl+Alt+S",
+    "Ctrl+Shift+Space",
+    "Ctrl+Alt+Space",
+    "Ctrl+Shift+`",
+    "Ctrl+Shift+F12",
+];
+
+/// Registered action hotkeys besides the toggle hotkey
+static ACTION_HOTKEYS: Mutex<Vec<(HotKey, HotkeyAction)>> = Mutex::new(Vec::new());
+
 /// Message sender for sending UI messages
 static MESSAGE_SENDER: OnceLock<Sender<AppMessage>> = OnceLock::new();
 
@@ -38,7 +68,9 @@ static HOTKEY_MANAGER: OnceLock<Mutex<HotKeyManagerWrapper>> = OnceLock::new();
 
 /// Initialize global hotkey manager with custom hotkey string
 /// IMPORTANT: Must be called from main thread before GPUI app starts
-/// The manager is stored globally for runtime hotkey updates
+/// The manager is stored globally for runtime hotkey updates. Returns false
+/// only if there's no manager; a toggle hotkey another app owns is reported
+/// as [`AppMessage::HotkeyUnavailable`] and can be changed later.
 pub fn init_global_hotkey(message_tx: Sender<AppMessage>, hotkey_str: &str) -> bool {
     let manager = match GlobalHotKeyManager::new() {
         Ok(m) => m,
@@ -62,18 +94,23 @@ pub fn init_global_hotkey(message_tx: Sender<AppMessage>, hotkey_str: &str) -> b
 
     let hotkey = HotKey::new(Some(modifiers), code);
 
-    if let Err(e) = manager.register(hotkey) {
-        error!("Failed to register hotkey {}: {:?}", hotkey_str, e);
-        return false;
+    match manager.register(hotkey) {
+        Ok(()) => {
+            info!("Registered global hotkey: {}", hotkey_str);
+            // Store the hotkey ID and hotkey for later updates
+            CURRENT_HOTKEY_ID.store(hotkey.id(), Ordering::SeqCst);
+            *CURRENT_HOTKEY.lock() = Some(hotkey);
+        }
+        Err(e) => {
+            error!("Failed to register hotkey {}: {:?}", hotkey_str, e);
+            *TOGGLE_ERROR.lock() = Some(e.to_string());
+            let _ = message_tx.send(AppMessage::HotkeyUnavailable(
+                hotkey_str.to_string(),
+                e.to_string(),
+            ));
+        }
     }
 
-    info!("Registered global hotkey: {}", hotkey_str);
-
-    // Store the hotkey ID and hotkey for later updates
-    let hotkey_id = hotkey.id();
-    CURRENT_HOTKEY_ID.store(hotkey_id, Ordering::SeqCst);
-    *CURRENT_HOTKEY.lock() = Some(hotkey);
-
     // Store manager globally for runtime updates
     let _ = HOTKEY_MANAGER.set(Mutex::new(HotKeyManagerWrapper(manager)));
 
@@ -100,6 +137,17 @@ pub fn init_global_hotkey(message_tx: Sender<AppMessage>, hotkey_str: &str) -> b
                     } else {
                         warn!("Global hotkey pressed but disabled");
                     }
+                } else if event.state == HotKeyState::Pressed {
+                    let action = ACTION_HOTKEYS
+                        .lock()
+                        .iter()
+                        .find(|(hotkey, _)| hotkey.id() == event.id)
+                        .map(|(_, action)| *action);
+                    if let Some(action) = action {
+                        if HOTKEY_ENABLED.load(Ordering::SeqCst) {
+                            run_action(action);
+                        }
+                    }
                 }
             }
         }
@@ -109,8 +157,9 @@ pub fn init_global_hotkey(message_tx: Sender<AppMessage>, hotkey_str: &str) -> b
 }
 
 /// Update the global hotkey to a new key combination
-/// This performs runtime re-registration of the hotkey
-pub fn update_hotkey(new_hotkey_str: &str) -> bool {
+/// This performs runtime re-registration of the hotkey. On failure the
+/// error is kept for [`toggle_error`] and returned.
+pub fn update_hotkey(new_hotkey_str: &str) -> Result<(), String> {
     info!("Updating hotkey to: {}", new_hotkey_str);
 
     // Parse the new hotkey string
@@ -118,7 +167,7 @@ pub fn update_hotkey(new_hotkey_str: &str) -> bool {
         Some((m, c)) => (m, c),
         None => {
             error!("Invalid hotkey string: {}", new_hotkey_str);
-            return false;
+            return Err(format!("invalid hotkey {}", new_hotkey_str));
         }
     };
 
@@ -129,7 +178,7 @@ pub fn update_hotkey(new_hotkey_str: &str) -> bool {
         Some(m) => m,
         None => {
             error!("Hotkey manager not initialized");
-            return false;
+            return Err("hotkey manager not initialized".to_string());
         }
     };
 
@@ -152,18 +201,154 @@ pub fn update_hotkey(new_hotkey_str: &str) -> bool {
     // Register the new hotkey
     if let Err(e) = manager.register(new_hotkey) {
         error!("Failed to register new hotkey {}: {:?}", new_hotkey_str, e);
-        return false;
+        *TOGGLE_ERROR.lock() = Some(e.to_string());
+        return Err(e.to_string());
     }
 
     // Update the stored hotkey info
     let new_id = new_hotkey.id();
     CURRENT_HOTKEY_ID.store(new_id, Ordering::SeqCst);
     *CURRENT_HOTKEY.lock() = Some(new_hotkey);
+    *TOGGLE_ERROR.lock() = None;
 
     info!("Successfully updated hotkey to: {}", new_hotkey_str);
+    Ok(())
+}
+
+/// Why the toggle hotkey isn't active, if it isn't
+pub fn toggle_error() -> Option<String> {
+    TOGGLE_ERROR.lock().clone()
+}
+
+/// Which of `candidates` could be registered right now, found by
+/// registering each one and unregistering it again. The toggle hotkey we
+/// hold ourselves counts as free. Must be called on the main thread.
+pub fn probe_free(candidates: &[&str]) -> Vec<(String, bool)> {
+    let Some(manager_cell) = HOTKEY_MANAGER.get() else {
+        return Vec::new();
+    };
+    let manager_guard = manager_cell.lock();
+    let manager = &manager_guard.0;
+    let current = *CURRENT_HOTKEY.lock();
+
+    candidates
+        .iter()
+        .filter_map(|candidate| {
+            let (modifiers, code) = parse_hotkey_string(candidate)?;
+            let hotkey = HotKey::new(Some(modifiers), code);
+            let free = if current.is_some_and(|c| c.id() == hotkey.id()) {
+                true
+            } else {
+                match manager.register(hotkey) {
+                    Ok(()) => {
+                        let _ = manager.unregister(hotkey);
+                        true
+                    }
+                    Err(e) => {
+                        info!("{} is taken: {}", candidate, e);
+                        false
+                    }
+                }
+            };
+            Some((candidate.to_string(), free))
+        })
+        .collect()
+}
+
+/// Bind an action to a global hotkey, replacing any previous binding for it.
+/// Must be called after `init_global_hotkey`.
+pub fn register_action_hotkey(action: HotkeyAction, hotkey_str: &str) -> bool {
+    let (modifiers, code) = match parse_hotkey_string(hotkey_str) {
+        Some((m, c)) => (m, c),
+        None => {
+            error!("Invalid hotkey string for {:?}: {}", action, hotkey_str);
+            return false;
+        }
+    };
+
+    let hotkey = HotKey::new(Some(modifiers), code);
+
+    let manager_cell = match HOTKEY_MANAGER.get() {
+        Some(m) => m,
+        None => {
+            error!("Hotkey manager not initialized");
+            return false;
+        }
+    };
+    let manager_guard = manager_cell.lock();
+    let manager = &manager_guard.0;
+
+    let mut actions = ACTION_HOTKEYS.lock();
+    if let Some(index) = actions.iter().position(|(_, a)| *a == action) {
+        let (old_hotkey, _) = actions.remove(index);
+        if let Err(e) = manager.unregister(old_hotkey) {
+            warn!("Failed to unregister old hotkey for {:?}: {:?}", action, e);
+        }
+    }
+
+    if let Err(e) = manager.register(hotkey) {
+        error!("Failed to register hotkey {} for {:?}: {:?}", hotkey_str, action, e);
+        return false;
+    }
+
+    actions.push((hotkey, action));
+    info!("Registered {} for {:?}", hotkey_str, action);
     true
 }
 
+/// Unregister and register every hotkey again, for when the system dropped
+/// them (after sleep, or an Explorer restart). Returns what couldn't be
+/// registered; nothing to do if hotkeys were never set up.
+pub fn reregister_all() -> Result<(), String> {
+    let Some(manager_cell) = HOTKEY_MANAGER.get() else {
+        return Ok(());
+    };
+    let manager_guard = manager_cell.lock();
+    let manager = &manager_guard.0;
+
+    let mut hotkeys: Vec<(HotKey, String)> = Vec::new();
+    if let Some(hotkey) = *CURRENT_HOTKEY.lock() {
+        hotkeys.push((hotkey, "toggle".to_string()));
+    }
+    hotkeys.extend(
+        ACTION_HOTKEYS
+            .lock()
+            .iter()
+            .map(|(hotkey, action)| (*hotkey, format!("{:?}", action))),
+    );
+
+    let mut failed = Vec::new();
+    for (hotkey, name) in hotkeys {
+        // Usually already gone; registering on top of a live one would fail
+        let _ = manager.unregister(hotkey);
+        if let Err(e) = manager.register(hotkey) {
+            error!("Failed to re-register {} hotkey: {:?}", name, e);
+            failed.push(format!("{}: {}", name, e));
+        }
+    }
+
+    if failed.is_empty() {
+        info!("Re-registered global hotkeys");
+        Ok(())
+    } else {
+        Err(failed.join(", "))
+    }
+}
+
+/// Run an action hotkey (called from the hotkey thread)
+fn run_action(action: HotkeyAction) {
+    let delta = match action {
+        HotkeyAction::NextMonitor => 1,
+        HotkeyAction::PreviousMonitor => -1,
+        HotkeyAction::EditLatest => {
+            crate::editor::edit_latest();
+            return;
+        }
+    };
+
+    move_window_to_monitor(delta);
+}
+
 /// Enable or disable the hotkey
 #[allow(dead_code)]
 pub fn set_hotkey_enabled(enabled: bool) {
@@ -171,94 +356,265 @@ pub fn set_hotkey_enabled(enabled: bool) {
     info!("Hotkey enabled: {}", enabled);
 }
 
+/// Every key a global hotkey can use: (GPUI key name, name in hotkey
+/// strings, key code). Both recording and parsing go through this table.
+const KEYS: &[(&str, &str, Code)] = &[
+    ("a", "A", Code::KeyA),
+    ("b", "B", Code::KeyB),
+    ("c", "C", Code::KeyC),
+    ("d", "D", Code::KeyD),
+    ("e", "E", Code::KeyE),
+    ("f", "F", Code::KeyF),
+    ("g", "G", Code::KeyG),
+    ("h", "H", Code::KeyH),
+    ("i", "I", Code::KeyI),
+    ("j", "J", Code::KeyJ),
+    ("k", "K", Code::KeyK),
+    ("l", "L", Code::KeyL),
+    ("m", "M", Code::KeyM),
+    ("n", "N", Code::KeyN),
+    ("o", "O", Code::KeyO),
+    ("p", "P", Code::KeyP),
+    ("q", "Q", Code::KeyQ),
+    ("r", "R", Code::KeyR),
+    ("s", "S", Code::KeyS),
+    ("t", "T", Code::KeyT),
+    ("u", "U", Code::KeyU),
+    ("v", "V", Code::KeyV),
+    ("w", "W", Code::KeyW),
+    ("x", "X", Code::KeyX),
+    ("y", "Y", Code::KeyY),
+    ("z", "Z", Code::KeyZ),
+    ("0", "0", Code::Digit0),
+    ("1", "1", Code::Digit1),
+    ("2", "2", Code::Digit2),
+    ("3", "3", Code::Digit3),
+    ("4", "4", Code::Digit4),
+    ("5", "5", Code::Digit5),
+    ("6", "6", Code::Digit6),
+    ("7", "7", Code::Digit7),
+    ("8", "8", Code::Digit8),
+    ("9", "9", Code::Digit9),
+    ("f1", "F1", Code::F1),
+    ("f2", "F2", Code::F2),
+    ("f3", "F3", Code::F3),
+    ("f4", "F4", Code::F4),
+    ("f5", "F5", Code::F5),
+    ("f6", "F6", Code::F6),
+    ("f7", "F7", Code::F7),
+    ("f8", "F8", Code::F8),
+    ("f9", "F9", Code::F9),
+    ("f10", "F10", Code::F10),
+    ("f11", "F11", Code::F11),
+    ("f12", "F12", Code::F12),
+    ("space", "Space", Code::Space),
+    ("tab", "Tab", Code::Tab),
+    ("enter", "Enter", Code::Enter),
+    ("backspace", "Backspace", Code::Backspace),
+    ("delete", "Delete", Code::Delete),
+    ("insert", "Insert", Code::Insert),
+    ("home", "Home", Code::Home),
+    ("end", "End", Code::End),
+    ("pageup", "PageUp", Code::PageUp),
+    ("pagedown", "PageDown", Code::PageDown),
+    ("up", "Up", Code::ArrowUp),
+    ("down", "Down", Code::ArrowDown),
+    ("left", "Left", Code::ArrowLeft),
+    ("right", "Right", Code::ArrowRight),
+    ("`", "`", Code::Backquote),
+    ("-", "-", Code::Minus),
+    ("=", "=", Code::Equal),
+    ("[", "[", Code::BracketLeft),
+    ("]", "]", Code::BracketRight),
+    ("\\", "\\", Code::Backslash),
+    (";", ";", Code::Semicolon),
+    ("'", "'", Code::Quote),
+    (",", ",", Code::Comma),
+    (".", ".", Code::Period),
+    ("/", "/", Code::Slash),
+];
+
+/// Other names accepted when parsing (hand-edited settings.json)
+const KEY_ALIASES: &[(&str, Code)] = &[("RETURN", Code::Enter), ("BACKQUOTE", Code::Backquote)];
+
+/// Whether a key is allowed as a hotkey without any modifier
+fn works_alone(code: Code) -> bool {
+    matches!(
+        code,
+        Code::F1
+            | Code::F2
+            | Code::F3
+            | Code::F4
+            | Code::F5
+            | Code::F6
+            | Code::F7
+            | Code::F8
+            | Code::F9
+            | Code::F10
+            | Code::F11
+            | Code::F12
+    )
+}
+
 /// Parse a hotkey string like "Ctrl+Shift+S" into components
-/// Returns (modifiers, key_code) if valid
+/// Returns (modifiers, key_code) if valid. Names are case-insensitive and
+/// modifiers may come in any order.
 pub fn parse_hotkey_string(s: &str) -> Option<(Modifiers, Code)> {
-    let parts: Vec<&str> = s.split('+').map(|p| p.trim()).collect();
-    if parts.is_empty() {
-        return None;
-    }
-
     let mut modifiers = Modifiers::empty();
     let mut key_code = None;
 
-    for part in parts {
+    for part in s.split('+').map(|p| p.trim()) {
         match part.to_uppercase().as_str() {
             "CTRL" | "CONTROL" => modifiers |= Modifiers::CONTROL,
             "SHIFT" => modifiers |= Modifiers::SHIFT,
             "ALT" => modifiers |= Modifiers::ALT,
             "WIN" | "SUPER" | "META" => modifiers |= Modifiers::META,
             key => {
-                // Parse key code
-                key_code = match key {
-                    "A" => Some(Code::KeyA),
-                    "B" => Some(Code::KeyB),
-                    "C" => Some(Code::KeyC),
-                    "D" => Some(Code::KeyD),
-                    "E" => Some(Code::KeyE),
-                    "F" => Some(Code::KeyF),
-                    "G" => Some(Code::KeyG),
-                    "H" => Some(Code::KeyH),
-                    "I" => Some(Code::KeyI),
-                    "J" => Some(Code::KeyJ),
-                    "K" => Some(Code::KeyK),
-                    "L" => Some(Code::KeyL),
-                    "M" => Some(Code::KeyM),
-                    "N" => Some(Code::KeyN),
-                    "O" => Some(Code::KeyO),
-                    "P" => Some(Code::KeyP),
-                    "Q" => Some(Code::KeyQ),
-                    "R" => Some(Code::KeyR),
-                    "S" => Some(Code::KeyS),
-                    "T" => Some(Code::KeyT),
-                    "U" => Some(Code::KeyU),
-                    "V" => Some(Code::KeyV),
-                    "W" => Some(Code::KeyW),
-                    "X" => Some(Code::KeyX),
-                    "Y" => Some(Code::KeyY),
-                    "Z" => Some(Code::KeyZ),
-                    "0" => Some(Code::Digit0),
-                    "1" => Some(Code::Digit1),
-                    "2" => Some(Code::Digit2),
-                    "3" => Some(Code::Digit3),
-                    "4" => Some(Code::Digit4),
-                    "5" => Some(Code::Digit5),
-                    "6" => Some(Code::Digit6),
-                    "7" => Some(Code::Digit7),
-                    "8" => Some(Code::Digit8),
-                    "9" => Some(Code::Digit9),
-                    "F1" => Some(Code::F1),
-                    "F2" => Some(Code::F2),
-                    "F3" => Some(Code::F3),
-                    "F4" => Some(Code::F4),
-                    "F5" => Some(Code::F5),
-                    "F6" => Some(Code::F6),
-                    "F7" => Some(Code::F7),
-                    "F8" => Some(Code::F8),
-                    "F9" => Some(Code::F9),
-                    "F10" => Some(Code::F10),
-                    "F11" => Some(Code::F11),
-                    "F12" => Some(Code::F12),
-                    "SPACE" => Some(Code::Space),
-                    "TAB" => Some(Code::Tab),
-                    "ENTER" | "RETURN" => Some(Code::Enter),
-                    "BACKSPACE" => Some(Code::Backspace),
-                    "DELETE" => Some(Code::Delete),
-                    "INSERT" => Some(Code::Insert),
-                    "HOME" => Some(Code::Home),
-                    "END" => Some(Code::End),
-                    "PAGEUP" => Some(Code::PageUp),
-                    "PAGEDOWN" => Some(Code::PageDown),
-                    "UP" => Some(Code::ArrowUp),
-                    "DOWN" => Some(Code::ArrowDown),
-                    "LEFT" => Some(Code::ArrowLeft),
-                    "RIGHT" => Some(Code::ArrowRight),
-                    "`" | "BACKQUOTE" => Some(Code::Backquote),
-                    _ => None,
-                };
+                // Only one key per hotkey
+                if key_code.is_some() {
+                    return None;
+                }
+                key_code = KEYS
+                    .iter()
+                    .find(|(_, name, _)| name.to_uppercase() == key)
+                    .map(|(_, _, code)| *code)
+                    .or_else(|| {
+                        KEY_ALIASES
+                            .iter()
+                            .find(|(alias, _)| *alias == key)
+                            .map(|(_, code)| *code)
+                    });
+                key_code?;
+            }
+        }
+    }
+
+    let code = key_code?;
+    (!modifiers.is_empty() || works_alone(code)).then_some((modifiers, code))
+}
+
+/// Canonical hotkey string: modifiers as Ctrl, Shift, Alt, Win in that
+/// order, then the key's name from [`KEYS`]
+pub fn format_hotkey(modifiers: Modifiers, code: Code) -> Option<String> {
+    let (_, name, _) = KEYS.iter().find(|(_, _, c)| *c == code)?;
+    let mut parts = Vec::new();
+    for (modifier, label) in [
+        (Modifiers::CONTROL, "Ctrl"),
+        (Modifiers::SHIFT, "Shift"),
+        (Modifiers::ALT, "Alt"),
+        (Modifiers::META, "Win"),
+    ] {
+        if modifiers.contains(modifier) {
+            parts.push(*label);
+        }
+    }
+    parts.push(name);
+    Some(parts.join("+"))
+}
+
+/// Hotkey string for a recorded key press, or None for modifier-only
+/// presses, keys that can't be a hotkey, and keys that need a modifier
+pub fn keystroke_to_hotkey_string(keystroke: &gpui::Keystroke) -> Option<String> {
+    let (_, _, code) = KEYS.iter().find(|(key, _, _)| *key == keystroke.key)?;
+    let mut modifiers = Modifiers::empty();
+    for (pressed, modifier) in [
+        (keystroke.modifiers.control, Modifiers::CONTROL),
+        (keystroke.modifiers.shift, Modifiers::SHIFT),
+        (keystroke.modifiers.alt, Modifiers::ALT),
+        (keystroke.modifiers.platform, Modifiers::META),
+    ] {
+        if pressed {
+            modifiers |= modifier;
+        }
+    }
+
+    if modifiers.is_empty() && !works_alone(*code) {
+        return None;
+    }
+    format_hotkey(modifiers, *code)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn keystroke(key: &str, control: bool, shift: bool, alt: bool, win: bool) -> gpui::Keystroke {
+        gpui::Keystroke {
+            modifiers: gpui::Modifiers {
+                control,
+                shift,
+                alt,
+                platform: win,
+                ..Default::default()
+            },
+            key: key.to_string(),
+            key_char: None,
+        }
+    }
+
+    #[test]
+    fn test_recorded_hotkeys_round_trip() {
+        for &(key, _, code) in KEYS {
+            for mask in 0..16u8 {
+                let (control, shift, alt, win) =
+                    (mask & 1 != 0, mask & 2 != 0, mask & 4 != 0, mask & 8 != 0);
+                let recorded = keystroke_to_hotkey_string(&keystroke(key, control, shift, alt, win));
+                if mask == 0 && !works_alone(code) {
+                    assert_eq!(recorded, None, "{} alone", key);
+                    continue;
+                }
+                let recorded = recorded.unwrap_or_else(|| panic!("{} ({:04b}) not recorded", key, mask));
+
+                let (modifiers, parsed) = parse_hotkey_string(&recorded)
+                    .unwrap_or_else(|| panic!("{:?} doesn't parse", recorded));
+                assert_eq!(parsed, code, "{:?}", recorded);
+                assert_eq!(modifiers.contains(Modifiers::CONTROL), control);
+                assert_eq!(modifiers.contains(Modifiers::SHIFT), shift);
+                assert_eq!(modifiers.contains(Modifiers::ALT), alt);
+                assert_eq!(modifiers.contains(Modifiers::META), win);
+
+                let canonical = format_hotkey(modifiers, parsed).unwrap();
+                assert_eq!(canonical, recorded);
+                assert_eq!(parse_hotkey_string(&canonical), Some((modifiers, parsed)));
             }
         }
     }
 
-    key_code.map(|code| (modifiers, code))
+    #[test]
+    fn test_parse_accepts_any_order_and_case() {
+        let expected = Some((Modifiers::CONTROL | Modifiers::SHIFT, Code::KeyS));
+        assert_eq!(parse_hotkey_string("Ctrl+Shift+S"), expected);
+        assert_eq!(parse_hotkey_string("shift + control + s"), expected);
+        assert_eq!(parse_hotkey_string("CTRL+SHIFT+S"), expected);
+
+        let win = Some((Modifiers::META | Modifiers::SHIFT, Code::KeyS));
+        assert_eq!(parse_hotkey_string("Win+Shift+S"), win);
+        assert_eq!(parse_hotkey_string("Super+Shift+S"), win);
+        assert_eq!(parse_hotkey_string("Meta+Shift+S"), win);
+        assert_eq!(format_hotkey(Modifiers::META | Modifiers::SHIFT, Code::KeyS).unwrap(), "Shift+Win+S");
+
+        assert_eq!(parse_hotkey_string("Ctrl+Backquote"), Some((Modifiers::CONTROL, Code::Backquote)));
+        assert_eq!(parse_hotkey_string("Ctrl+Return"), Some((Modifiers::CONTROL, Code::Enter)));
+    }
+
+    #[test]
+    fn test_parse_rejects_incomplete_hotkeys() {
+        assert_eq!(parse_hotkey_string("F12"), Some((Modifiers::empty(), Code::F12)));
+        // A plain letter would swallow typing everywhere
+        assert_eq!(parse_hotkey_string("S"), None);
+        assert_eq!(parse_hotkey_string("F"), None);
+        assert_eq!(parse_hotkey_string("Ctrl+Shift"), None);
+        assert_eq!(parse_hotkey_string("Ctrl+"), None);
+        assert_eq!(parse_hotkey_string("Ctrl+A+B"), None);
+        assert_eq!(parse_hotkey_string("Ctrl+Escape"), None);
+        assert_eq!(parse_hotkey_string(""), None);
+    }
+
+    #[test]
+    fn test_fallback_hotkeys_parse() {
+        for hotkey in FALLBACK_HOTKEYS {
+            let (modifiers, code) = parse_hotkey_string(hotkey).unwrap();
+            assert_eq!(format_hotkey(modifiers, code).as_deref(), Some(hotkey));
+        }
+    }
 }
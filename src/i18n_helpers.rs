@@ -11,9 +11,12 @@ pub const SUPPORTED_LANGUAGES: &[(&str, &str)] = &[
 pub fn init_language(settings: &Settings) {
     let locale = if let Some(ref lang) = settings.language {
         // User preference
+        crate::number_format::set_locale(lang);
         lang.clone()
     } else {
-        // Auto-detect from system
+        // Auto-detect from system; numbers follow the system locale even
+        // when the UI falls back to English
+        crate::number_format::set_locale(&sys_locale::get_locale().unwrap_or_else(|| "en".to_string()));
         detect_system_language()
     };
 
@@ -60,10 +63,28 @@ pub fn current_language() -> String {
     rust_i18n::locale().to_string()
 }
 
+/// Plural form a count takes in a language, named like the sub-keys of
+/// counted strings. Japanese and Korean don't mark plurals; everything else
+/// falls back to English, which tells one from other.
+fn plural_category(locale: &str, count: usize) -> &'static str {
+    match locale {
+        "ja" | "ko" => "other",
+        _ if count == 1 => "one",
+        _ => "other",
+    }
+}
+
+/// Key of the form of a counted string (one with `one`/`other` sub-keys)
+/// that reads right for `count` in the current language
+pub fn plural_key(key: &str, count: usize) -> String {
+    format!("{}.{}", key, plural_category(&rust_i18n::locale(), count))
+}
+
 /// Change the current language and save to settings
 pub fn change_language(lang: &str) {
     if SUPPORTED_LANGUAGES.iter().any(|(code, _)| *code == lang) {
         rust_i18n::set_locale(lang);
+        crate::number_format::set_locale(lang);
         log::info!("Language changed to: {}", lang);
     } else {
         log::warn!("Attempted to set unsupported language: {}", lang);
@@ -82,6 +103,75 @@ mod tests {
         assert!(SUPPORTED_LANGUAGES.iter().any(|(code, _)| *code == "ja"));
     }
 
+    /// Strings that go through `plural_key`
+    const COUNTED_KEYS: &[&str] = &[
+        "app.search.stats",
+        "app.fix_timestamps.title",
+        "app.fix_timestamps.skipped",
+        "gallery.context_menu.confirm_desc",
+        "gallery.context_menu.app_menu_title",
+        "tray.status.library",
+        "settings.general.screenshot_dir.drop_confirm",
+        "settings.general.screenshot_dir.warning.large_folder",
+        "settings.general.organizer.progress.status",
+        "settings.conversion.estimate.result",
+        "settings.conversion.progress.status",
+        "settings.indexing.progress.status",
+        "settings.indexing.index_status.count",
+        "settings.cleanup.days_value",
+        "settings.cleanup.summary",
+        "settings.cleanup.recycled",
+        "settings.cleanup.recycle_failed",
+        "settings.diagnostics.recovery.failed",
+        "settings.advanced.context_menu.threshold_value",
+        "settings.advanced.watcher.burst_value",
+        "notifications.copied_to_clipboard",
+        "notifications.info_copied",
+        "notifications.delete.recycled",
+        "notifications.delete.deleted",
+        "notifications.delete.failed",
+        "notifications.delete.confirm_permanent",
+        "notifications.similar.added",
+        "notifications.similar.capped",
+        "notifications.timestamps.fixed",
+        "notifications.timestamps.fixed_with_failures",
+        "notifications.indexing.refreshed",
+        "notifications.index_transfer.imported",
+    ];
+
+    #[test]
+    fn test_plural_category() {
+        for (count, expected) in [(0, "other"), (1, "one"), (5, "other")] {
+            assert_eq!(plural_category("en", count), expected, "en {}", count);
+        }
+        for locale in ["ja", "ko"] {
+            for count in [0, 1, 5] {
+                assert_eq!(plural_category(locale, count), "other", "{} {}", locale, count);
+            }
+        }
+    }
+
+    #[test]
+    fn test_counted_strings_have_every_form() {
+        for (locale, _) in SUPPORTED_LANGUAGES {
+            for key in COUNTED_KEYS {
+                for count in [0, 1, 5] {
+                    let form = format!("{}.{}", key, plural_category(locale, count));
+                    let text = t!(&form, locale = *locale, count = count).to_string();
+                    assert!(!text.contains(&form), "{} is missing {}", locale, form);
+                }
+            }
+        }
+
+        let en = |count| {
+            let form = format!("notifications.copied_to_clipboard.{}", plural_category("en", count));
+            t!(&form, locale = "en", count = count).to_string()
+        };
+        assert_eq!(en(0), "0 items copied to clipboard");
+        assert_eq!(en(1), "1 item copied to clipboard");
+        assert_eq!(en(5), "5 items copied to clipboard");
+    }
+
     #[test]
     fn test_current_language_name() {
         // Default should be English
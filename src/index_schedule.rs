@@ -0,0 +1,179 @@
+//! Scheduled incremental indexing
+//!
+//! Auto-index only sees screenshots that arrive while the app is running with
+//! indexing on. Ones that came in while it was closed, or while indexing was
+//! turned off, wait for someone to press Index. The schedule runs that same
+//! incremental pass on its own: every so many hours, or once at startup when
+//! enough time has passed. It's kept from `last_index_run` in the settings,
+//! so a restart doesn't reset it. A run that comes due while the PC is on
+//! battery or in use waits until neither is true.
+
+use chrono::{DateTime, Duration as ChronoDuration, Local};
+use std::time::Duration;
+
+use crate::settings::IndexSchedule;
+
+/// How long after startup a scheduled run starts at the earliest, so it
+/// doesn't compete with loading the gallery
+pub const STARTUP_DELAY: Duration = Duration::from_secs(2 * 60);
+
+/// How long the keyboard and mouse must have been left alone before a run
+pub const IDLE_BEFORE_RUN: Duration = Duration::from_secs(3 * 60);
+
+/// How soon a run put off for battery or activity is tried again
+pub const RETRY_DELAY: Duration = Duration::from_secs(10 * 60);
+
+/// How soon a run that failed is tried again
+pub const FAILED_RETRY_DELAY: Duration = Duration::from_secs(60 * 60);
+
+/// Interval choices (hours), stepped through with - and +
+pub const HOUR_CHOICES: &[u32] = &[1, 3, 6, 12, 24, 48, 72, 168];
+
+/// The interval choice after (or before) `hours`
+pub fn step_hours(hours: u32, up: bool) -> u32 {
+    let next = if up {
+        HOUR_CHOICES.iter().find(|&&h| h > hours)
+    } else {
+        HOUR_CHOICES.iter().rev().find(|&&h| h < hours)
+    };
+    next.copied().unwrap_or(hours)
+}
+
+/// Why a due run is put off
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Deferral {
+    OnBattery,
+    InUse,
+}
+
+/// When the next scheduled run is due; `None` if there's none this session
+pub fn next_run(
+    schedule: IndexSchedule,
+    hours: u32,
+    last_run: Option<DateTime<Local>>,
+    started: DateTime<Local>,
+) -> Option<DateTime<Local>> {
+    let earliest = started + ChronoDuration::seconds(STARTUP_DELAY.as_secs() as i64);
+    let after_last = last_run.map(|last| last + ChronoDuration::hours(hours.max(1) as i64));
+    match schedule {
+        IndexSchedule::Off => None,
+        IndexSchedule::Interval => Some(after_last.map_or(earliest, |due| due.max(earliest))),
+        // Once, right after startup, if the last run was long enough before it
+        IndexSchedule::OnStart => match after_last {
+            Some(due) if due > started => None,
+            _ => Some(earliest),
+        },
+    }
+}
+
+/// Why a due run should wait, given the power source and how long input's been idle
+pub fn deferral_for(on_battery: bool, idle: Duration) -> Option<Deferral> {
+    if on_battery {
+        Some(Deferral::OnBattery)
+    } else if idle < IDLE_BEFORE_RUN {
+        Some(Deferral::InUse)
+    } else {
+        None
+    }
+}
+
+/// Why a due run should wait right now, if it should
+pub fn deferral() -> Option<Deferral> {
+    deferral_for(on_battery(), idle_time())
+}
+
+#[cfg(windows)]
+fn on_battery() -> bool {
+    use windows::Win32::System::Power::{GetSystemPowerStatus, SYSTEM_POWER_STATUS};
+
+    let mut status = SYSTEM_POWER_STATUS::default();
+    // 0 = offline; 1 = on AC, 255 = unknown (desktops without a battery report 1)
+    unsafe { GetSystemPowerStatus(&mut status) }.is_ok() && status.ACLineStatus == 0
+}
+
+#[cfg(not(windows))]
+fn on_battery() -> bool {
+    false
+}
+
+/// Time since the last keyboard or mouse input in this session
+#[cfg(windows)]
+fn idle_time() -> Duration {
+    use windows::Win32::System::SystemInformation::GetTickCount;
+    use windows::Win32::UI::Input::KeyboardAndMouse::{GetLastInputInfo, LASTINPUTINFO};
+
+    let mut info = LASTINPUTINFO {
+        cbSize: std::mem::size_of::<LASTINPUTINFO>() as u32,
+        dwTime: 0,
+    };
+    if !unsafe { GetLastInputInfo(&mut info) }.as_bool() {
+        return Duration::MAX;
+    }
+    // Both tick counts wrap after 49.7 days
+    let now = unsafe { GetTickCount() };
+    Duration::from_millis(now.wrapping_sub(info.dwTime) as u64)
+}
+
+#[cfg(not(windows))]
+fn idle_time() -> Duration {
+    Duration::MAX
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn at(hour: u32) -> DateTime<Local> {
+        Local.with_ymd_and_hms(2026, 3, 10, hour, 0, 0).unwrap()
+    }
+
+    #[test]
+    fn test_interval() {
+        let started = at(9);
+        let earliest = started + ChronoDuration::seconds(STARTUP_DELAY.as_secs() as i64);
+
+        // Never run: shortly after startup
+        assert_eq!(next_run(IndexSchedule::Interval, 24, None, started), Some(earliest));
+        // Overdue: the same
+        let long_ago = at(9) - ChronoDuration::days(3);
+        assert_eq!(next_run(IndexSchedule::Interval, 24, Some(long_ago), started), Some(earliest));
+        // Ran at 7: due at 13 with a 6-hour interval, and again after that one
+        assert_eq!(next_run(IndexSchedule::Interval, 6, Some(at(7)), started), Some(at(13)));
+        assert_eq!(next_run(IndexSchedule::Interval, 6, Some(at(13)), started), Some(at(19)));
+
+        assert_eq!(next_run(IndexSchedule::Off, 24, None, started), None);
+    }
+
+    #[test]
+    fn test_on_start() {
+        let started = at(9);
+        let earliest = started + ChronoDuration::seconds(STARTUP_DELAY.as_secs() as i64);
+        assert_eq!(next_run(IndexSchedule::OnStart, 12, None, started), Some(earliest));
+        let yesterday = at(9) - ChronoDuration::days(1);
+        assert_eq!(next_run(IndexSchedule::OnStart, 12, Some(yesterday), started), Some(earliest));
+
+        // Too recent, or already done this session: wait for the next launch
+        assert_eq!(next_run(IndexSchedule::OnStart, 12, Some(at(1)), started), None);
+        assert_eq!(next_run(IndexSchedule::OnStart, 12, Some(earliest), started), None);
+    }
+
+    #[test]
+    fn test_step_hours() {
+        assert_eq!(step_hours(24, true), 48);
+        assert_eq!(step_hours(24, false), 12);
+        assert_eq!(step_hours(168, true), 168);
+        assert_eq!(step_hours(1, false), 1);
+        // Hand-edited values snap to the nearest choice in that direction
+        assert_eq!(step_hours(30, true), 48);
+        assert_eq!(step_hours(30, false), 24);
+    }
+
+    #[test]
+    fn test_deferral() {
+        let idle = IDLE_BEFORE_RUN + Duration::from_secs(1);
+        assert_eq!(deferral_for(true, idle), Some(Deferral::OnBattery));
+        assert_eq!(deferral_for(false, Duration::from_secs(5)), Some(Deferral::InUse));
+        assert_eq!(deferral_for(false, idle), None);
+    }
+}
@@ -0,0 +1,235 @@
+//! Saved progress of a long indexing run
+//!
+//! A first index of a big folder can take hours. After each chunk the files
+//! still to do are written next to the vector DB, so a restart continues with
+//! the right progress total instead of walking the folder again. The saved
+//! list is only trusted while every directory it was built from keeps its
+//! mtime; adding, removing or renaming a file changes that, and the next run
+//! walks the folder from scratch.
+
+use anyhow::Result;
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+use std::collections::hash_map::DefaultHasher;
+use std::fs;
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use crate::paths;
+
+/// Indexing run that can be resumed
+#[derive(Debug, Serialize, Deserialize)]
+pub struct IndexSession {
+    screenshot_dir: PathBuf,
+    /// Files discovered when the run started
+    pub total: usize,
+    /// Files processed so far (indexed or failed)
+    pub completed: usize,
+    /// Files still to process, in order
+    pending: Vec<PathBuf>,
+    /// Hash of `pending`, so a damaged file isn't trusted
+    pending_hash: u64,
+    /// Every directory walked, with its mtime at the time
+    dir_mtimes: Vec<(PathBuf, SystemTime)>,
+    #[serde(skip)]
+    path: PathBuf,
+}
+
+/// Where the session for an index lives (next to the vector DB)
+pub fn session_path(db_path: &Path) -> PathBuf {
+    db_path.with_file_name("index_session.json")
+}
+
+/// Modification time of a directory, read before listing it
+pub fn dir_mtime(dir: &Path) -> Option<SystemTime> {
+    fs::metadata(paths::long_path(dir))
+        .and_then(|m| m.modified())
+        .ok()
+}
+
+fn hash_paths(paths: &[PathBuf]) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    paths.hash(&mut hasher);
+    hasher.finish()
+}
+
+impl IndexSession {
+    /// New session for a freshly walked folder; saved right away
+    pub fn start(
+        path: PathBuf,
+        screenshot_dir: &Path,
+        pending: Vec<PathBuf>,
+        dir_mtimes: Vec<(PathBuf, SystemTime)>,
+    ) -> Self {
+        let mut session = Self {
+            screenshot_dir: screenshot_dir.to_path_buf(),
+            total: pending.len(),
+            completed: 0,
+            pending_hash: 0,
+            pending,
+            dir_mtimes,
+            path,
+        };
+        if let Err(e) = session.save() {
+            warn!("Failed to save indexing session {:?}: {}", session.path, e);
+        }
+        session
+    }
+
+    /// Saved session for `screenshot_dir`, if there is one and the folder
+    /// hasn't changed since. A stale or unreadable session is deleted.
+    pub fn resume(path: &Path, screenshot_dir: &Path) -> Option<Self> {
+        let data = fs::read_to_string(path).ok()?;
+        let session = match serde_json::from_str::<IndexSession>(&data) {
+            Ok(mut session) => {
+                session.path = path.to_path_buf();
+                session
+            }
+            Err(e) => {
+                warn!("Discarding unreadable indexing session: {}", e);
+                discard(path);
+                return None;
+            }
+        };
+
+        if let Some(reason) = session.stale_reason(screenshot_dir) {
+            info!("Previous indexing session is stale ({}), walking the folder again", reason);
+            discard(path);
+            return None;
+        }
+
+        info!(
+            "Resuming indexing session: {}/{} done, {} pending",
+            session.completed,
+            session.total,
+            session.pending.len()
+        );
+        Some(session)
+    }
+
+    fn stale_reason(&self, screenshot_dir: &Path) -> Option<&'static str> {
+        if self.screenshot_dir != screenshot_dir {
+            return Some("different folder");
+        }
+        if hash_paths(&self.pending) != self.pending_hash {
+            return Some("pending list doesn't match its hash");
+        }
+        if self.completed + self.pending.len() != self.total {
+            return Some("counts don't add up");
+        }
+        let changed = self
+            .dir_mtimes
+            .iter()
+            .any(|(dir, mtime)| dir_mtime(dir) != Some(*mtime));
+        changed.then_some("folder changed")
+    }
+
+    /// Files still to process
+    pub fn pending(&self) -> &[PathBuf] {
+        &self.pending
+    }
+
+    /// Mark the next `count` pending files processed and save
+    pub fn complete(&mut self, count: usize) {
+        let count = count.min(self.pending.len());
+        self.pending.drain(..count);
+        self.completed += count;
+        if let Err(e) = self.save() {
+            warn!("Failed to save indexing session {:?}: {}", self.path, e);
+        }
+    }
+
+    fn save(&mut self) -> Result<()> {
+        self.pending_hash = hash_paths(&self.pending);
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        // Write then rename, so a crash never leaves half a session behind
+        let tmp = self.path.with_extension("json.tmp");
+        fs::write(&tmp, serde_json::to_vec(self)?)?;
+        fs::rename(&tmp, &self.path)?;
+        Ok(())
+    }
+
+    /// Run finished; drop the saved session
+    pub fn finish(self) {
+        discard(&self.path);
+    }
+}
+
+/// Delete a saved session (no-op if there's none)
+pub fn discard(path: &Path) {
+    if let Err(e) = fs::remove_file(path) {
+        if e.kind() != std::io::ErrorKind::NotFound {
+            warn!("Failed to remove indexing session {:?}: {}", path, e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::scratch_dir;
+
+    fn start(dir: &Path, shots: &Path, files: Vec<PathBuf>) -> IndexSession {
+        let mtimes = vec![(shots.to_path_buf(), dir_mtime(shots).unwrap())];
+        IndexSession::start(dir.join("index_session.json"), shots, files, mtimes)
+    }
+
+    #[test]
+    fn test_resume_after_restart() {
+        let dir = scratch_dir("session-resume");
+        let shots = dir.join("shots");
+        fs::create_dir_all(&shots).unwrap();
+        let files: Vec<PathBuf> = (0..5).map(|i| shots.join(format!("{i}.png"))).collect();
+
+        let mut session = start(&dir, &shots, files.clone());
+        session.complete(3);
+        drop(session);
+
+        let resumed = IndexSession::resume(&dir.join("index_session.json"), &shots).unwrap();
+        assert_eq!(resumed.total, 5);
+        assert_eq!(resumed.completed, 3);
+        assert_eq!(resumed.pending(), &files[3..]);
+
+        resumed.finish();
+        assert!(IndexSession::resume(&dir.join("index_session.json"), &shots).is_none());
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_stale_when_folder_changes() {
+        let dir = scratch_dir("session-stale");
+        let shots = dir.join("shots");
+        fs::create_dir_all(&shots).unwrap();
+        let path = dir.join("index_session.json");
+
+        // Different folder
+        start(&dir, &shots, vec![shots.join("a.png")]);
+        assert!(IndexSession::resume(&path, &dir.join("elsewhere")).is_none());
+        assert!(!path.exists());
+
+        // A file added since the walk
+        let mut session = start(&dir, &shots, vec![shots.join("a.png")]);
+        // Pretend the walk happened earlier than the mtime now on disk
+        session.dir_mtimes[0].1 = SystemTime::UNIX_EPOCH;
+        session.save().unwrap();
+        fs::write(shots.join("b.png"), b"png").unwrap();
+        assert!(IndexSession::resume(&path, &shots).is_none());
+
+        // Pending list edited by hand
+        start(&dir, &shots, vec![shots.join("a.png")]);
+        let data = fs::read_to_string(&path).unwrap().replace("a.png", "z.png");
+        fs::write(&path, data).unwrap();
+        assert!(IndexSession::resume(&path, &shots).is_none());
+
+        // Garbage
+        fs::write(&path, b"{").unwrap();
+        assert!(IndexSession::resume(&path, &shots).is_none());
+        assert!(!path.exists());
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,302 @@
+//! Taking the search index to another PC
+//!
+//! Re-embedding a big library takes hours, but the vectors don't depend on
+//! where the files live. An export is a folder holding a copy of the vector
+//! DB and a manifest with the screenshot folder and embedding model it was
+//! built with. Importing checks the model, swaps the DB in and points every
+//! row at the current screenshot folder.
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Local};
+use crossbeam_channel::Sender;
+use log::{error, info, warn};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::index_session;
+use crate::indexer::{self, IndexConfig};
+use crate::AppMessage;
+
+const MANIFEST_FILE: &str = "manifest.json";
+const DB_DIR: &str = "vector_index.db";
+
+/// What an export was built from
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Manifest {
+    /// Screenshot folder the rows' paths start with
+    pub screenshot_root: PathBuf,
+    pub model: String,
+    pub dimension: i32,
+    pub rows: usize,
+    pub exported_at: DateTime<Local>,
+}
+
+impl Manifest {
+    /// Whether this app's embeddings can be searched alongside the export's
+    pub fn matches_current_model(&self) -> bool {
+        self.model == indexer::MODEL_NAME && self.dimension == indexer::EMBEDDING_DIM
+    }
+}
+
+/// An export made with another embedding model
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ModelMismatch {
+    pub model: String,
+    pub dimension: i32,
+}
+
+impl fmt::Display for ModelMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "index was built with {} ({} dimensions), this app uses {} ({} dimensions)",
+            self.model,
+            self.dimension,
+            indexer::MODEL_NAME,
+            indexer::EMBEDDING_DIM
+        )
+    }
+}
+
+impl std::error::Error for ModelMismatch {}
+
+/// Copy the index into a new folder under `destination` in the background;
+/// answers with [`AppMessage::IndexExported`] or [`AppMessage::IndexTransferFailed`]
+pub fn export(config: IndexConfig, destination: PathBuf, message_tx: Sender<AppMessage>) {
+    std::thread::spawn(move || {
+        match export_impl(&config.db_path, &destination, &config.screenshot_dir) {
+            Ok(folder) => {
+                info!("Exported the index to {:?}", folder);
+                let _ = message_tx.send(AppMessage::IndexExported(folder));
+            }
+            Err(e) => {
+                error!("Index export failed: {:#}", e);
+                let _ = message_tx.send(AppMessage::IndexTransferFailed(
+                    t!("notifications.index_transfer.export_failed", error = format!("{:#}", e)).to_string(),
+                ));
+            }
+        }
+    });
+}
+
+/// Replace the index with the export in `archive` in the background;
+/// answers with [`AppMessage::IndexImported`] or [`AppMessage::IndexTransferFailed`]
+pub fn import(config: IndexConfig, archive: PathBuf, message_tx: Sender<AppMessage>) {
+    std::thread::spawn(move || {
+        let rt = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap();
+
+        match rt.block_on(import_impl(&archive, &config.db_path, &config.screenshot_dir)) {
+            Ok((rows, remapped)) => {
+                info!("Imported {} index rows from {:?} ({} remapped)", rows, archive, remapped);
+                let _ = message_tx.send(AppMessage::IndexImported(rows));
+            }
+            Err(e) => {
+                error!("Index import failed: {:#}", e);
+                let message = match e.downcast_ref::<ModelMismatch>() {
+                    Some(mismatch) => t!(
+                        "notifications.index_transfer.model_mismatch",
+                        model = mismatch.model.clone()
+                    )
+                    .to_string(),
+                    None => t!("notifications.index_transfer.import_failed", error = format!("{:#}", e))
+                        .to_string(),
+                };
+                let _ = message_tx.send(AppMessage::IndexTransferFailed(message));
+            }
+        }
+    });
+}
+
+/// Read the manifest of an export folder
+pub fn read_manifest(archive: &Path) -> Result<Manifest> {
+    let content = fs::read_to_string(archive.join(MANIFEST_FILE))
+        .with_context(|| format!("{:?} isn't an index export (no {})", archive, MANIFEST_FILE))?;
+    serde_json::from_str(&content).context("Damaged index manifest")
+}
+
+fn export_impl(db_path: &Path, destination: &Path, screenshot_root: &Path) -> Result<PathBuf> {
+    if !db_path.exists() {
+        anyhow::bail!("Nothing has been indexed yet");
+    }
+    let rows = indexer::get_indexed_count(&IndexConfig {
+        db_path: db_path.to_path_buf(),
+        cpu_mode: indexer::CpuMode::Normal,
+        screenshot_dir: screenshot_root.to_path_buf(),
+    })?;
+
+    let exported_at = Local::now();
+    let folder = destination.join(format!("sukusho-index-{}", exported_at.format("%Y%m%d-%H%M%S")));
+    copy_dir(db_path, &folder.join(DB_DIR))?;
+
+    let manifest = Manifest {
+        screenshot_root: screenshot_root.to_path_buf(),
+        model: indexer::MODEL_NAME.to_string(),
+        dimension: indexer::EMBEDDING_DIM,
+        rows,
+        exported_at,
+    };
+    fs::write(folder.join(MANIFEST_FILE), serde_json::to_string_pretty(&manifest)?)
+        .context("Failed to write the index manifest")?;
+    Ok(folder)
+}
+
+/// Returns the rows imported and how many of them were moved to `screenshot_root`
+async fn import_impl(archive: &Path, db_path: &Path, screenshot_root: &Path) -> Result<(usize, usize)> {
+    let manifest = read_manifest(archive)?;
+    if !manifest.matches_current_model() {
+        return Err(ModelMismatch {
+            model: manifest.model,
+            dimension: manifest.dimension,
+        }
+        .into());
+    }
+
+    // Copy next to the live DB first, so a failed copy leaves it alone
+    let staging = db_path.with_extension("db.importing");
+    if staging.exists() {
+        fs::remove_dir_all(&staging)?;
+    }
+    copy_dir(&archive.join(DB_DIR), &staging)?;
+    swap_in(&staging, db_path)?;
+
+    // An interrupted run's file list belongs to the old index
+    index_session::discard(&index_session::session_path(db_path));
+    indexer::invalidate_health();
+
+    let remapped = indexer::remap_root(db_path, &manifest.screenshot_root, screenshot_root).await?;
+    if remapped < manifest.rows {
+        warn!(
+            "{} of {} imported rows weren't under {:?}",
+            manifest.rows - remapped,
+            manifest.rows,
+            manifest.screenshot_root
+        );
+    }
+    Ok((manifest.rows, remapped))
+}
+
+/// Put the DB at `staging` in place of the one at `db_path`. The live DB is
+/// only renamed aside until the new one is in, and renamed back if that
+/// fails, so a locked file never leaves a half-deleted index behind.
+fn swap_in(staging: &Path, db_path: &Path) -> Result<()> {
+    let previous = db_path.with_extension("db.replaced");
+    if previous.exists() {
+        fs::remove_dir_all(&previous)
+            .with_context(|| format!("Failed to remove the earlier replaced index {:?}", previous))?;
+    }
+    let had_live = db_path.exists();
+    if had_live {
+        fs::rename(db_path, &previous).context("Failed to move the current index aside")?;
+    }
+    if let Err(e) = fs::rename(staging, db_path) {
+        if had_live {
+            if let Err(back) = fs::rename(&previous, db_path) {
+                error!("Failed to put the index back from {:?}: {}", previous, back);
+            }
+        }
+        return Err(e).context("Failed to move the imported index into place");
+    }
+    // Only a leftover now; the next import clears it if this fails
+    if had_live {
+        if let Err(e) = fs::remove_dir_all(&previous) {
+            warn!("Failed to remove the replaced index {:?}: {}", previous, e);
+        }
+    }
+    Ok(())
+}
+
+fn copy_dir(from: &Path, to: &Path) -> Result<()> {
+    fs::create_dir_all(to).with_context(|| format!("Failed to create {:?}", to))?;
+    for entry in fs::read_dir(from).with_context(|| format!("Failed to read {:?}", from))? {
+        let entry = entry?;
+        let target = to.join(entry.file_name());
+        if entry.file_type()?.is_dir() {
+            copy_dir(&entry.path(), &target)?;
+        } else {
+            fs::copy(entry.path(), &target).with_context(|| format!("Failed to copy {:?}", entry.path()))?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::scratch_dir;
+
+    #[test]
+    fn test_export_and_import() {
+        let dir = scratch_dir("index-transfer");
+        let old_root = dir.join("old").join("Screenshots");
+        let new_root = dir.join("new").join("Shots");
+        let old_db = dir.join("old").join("vector_index.db");
+        let new_db = dir.join("new").join("vector_index.db");
+        fs::create_dir_all(&new_root).unwrap();
+        let shot = new_root.join("2024").join("a.png");
+        fs::create_dir_all(shot.parent().unwrap()).unwrap();
+        fs::write(&shot, b"png").unwrap();
+
+        let rt = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap();
+        rt.block_on(indexer::write_rows(
+            &old_db,
+            &[
+                (old_root.join("2024").join("a.png"), vec![1.0; 768]),
+                (dir.join("elsewhere.png"), vec![1.0; 768]),
+            ],
+        ))
+        .unwrap();
+
+        let archive = export_impl(&old_db, &dir, &old_root).unwrap();
+        let manifest = read_manifest(&archive).unwrap();
+        assert_eq!((manifest.rows, manifest.screenshot_root.clone()), (2, old_root.clone()));
+        assert!(manifest.matches_current_model());
+
+        let (rows, remapped) = rt.block_on(import_impl(&archive, &new_db, &new_root)).unwrap();
+        assert_eq!((rows, remapped), (2, 1));
+        let moved = rt
+            .block_on(indexer::search_by_vector(&new_db, &[1.0; 768], 10))
+            .unwrap();
+        // Only the remapped row points at a file that exists
+        assert_eq!(moved, vec![shot]);
+
+        // Another model's vectors can't be mixed in
+        let mut foreign = manifest;
+        foreign.model = "clip-vit-b-32".to_string();
+        fs::write(archive.join(MANIFEST_FILE), serde_json::to_string(&foreign).unwrap()).unwrap();
+        let error = rt.block_on(import_impl(&archive, &new_db, &new_root)).unwrap_err();
+        assert!(error.downcast_ref::<ModelMismatch>().is_some());
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_swap_in_keeps_live_index_on_failure() {
+        let dir = scratch_dir("index-swap");
+        let db = dir.join("vector_index.db");
+        let staging = dir.join("vector_index.db.importing");
+        fs::create_dir_all(&db).unwrap();
+        fs::write(db.join("data.lance"), b"old").unwrap();
+
+        // Nothing staged: the rename in fails and the live index is back
+        assert!(swap_in(&staging, &db).is_err());
+        assert_eq!(fs::read(db.join("data.lance")).unwrap(), b"old");
+        assert!(!dir.join("vector_index.db.replaced").exists());
+
+        fs::create_dir_all(&staging).unwrap();
+        fs::write(staging.join("data.lance"), b"new").unwrap();
+        swap_in(&staging, &db).unwrap();
+        assert_eq!(fs::read(db.join("data.lance")).unwrap(), b"new");
+        assert!(!staging.exists());
+        assert!(!dir.join("vector_index.db.replaced").exists());
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
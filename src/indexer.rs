@@ -2,8 +2,8 @@
 
 use anyhow::{Context, Result};
 use arrow_array::{
-    Array, FixedSizeListArray, Int64Array, RecordBatch, RecordBatchIterator, StringArray,
-    UInt64Array, types::Float32Type,
+    Array, FixedSizeListArray, Float32Array, Int64Array, RecordBatch, RecordBatchIterator,
+    StringArray, UInt64Array, types::Float32Type,
 };
 use arrow_schema::{DataType, Field, Schema};
 use crossbeam_channel::Sender;
@@ -12,18 +12,30 @@ use fastembed::{
     TextEmbedding,
 };
 use futures::stream::TryStreamExt;
-use lancedb::Connection;
-use lancedb::query::ExecutableQuery;
-use log::{error, info, warn};
+use lancedb::query::{ExecutableQuery, QueryBase};
+use lancedb::{Connection, DistanceType, Table};
+use log::{debug, error, info, warn};
 use parking_lot::Mutex;
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use std::fs;
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
-use std::time::{Duration, SystemTime, UNIX_EPOCH};
-
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use crate::exclude;
+use crate::index_session::{self, IndexSession};
+use crate::paths;
+use crate::removals;
+use crate::thumbnail;
+use crate::timeline::{self, EventKind};
 use crate::AppMessage;
 
+/// Vision model every stored embedding comes from (recorded in exports)
+pub const MODEL_NAME: &str = "nomic-embed-vision-v1.5";
+
+/// Length of the stored embeddings
+pub const EMBEDDING_DIM: i32 = 768;
+
 /// Image file extensions to index
 const IMAGE_EXTENSIONS: &[&str] = &["png", "jpg", "jpeg", "gif", "bmp", "webp", "avif"];
 
@@ -79,13 +91,116 @@ impl Default for IndexStats {
     }
 }
 
+/// Attempts at opening the vector DB before giving up
+const OPEN_ATTEMPTS: u32 = 3;
+
+/// Wait before the first retry; doubled for each one after
+const OPEN_BACKOFF: Duration = Duration::from_millis(250);
+
+/// The vector DB couldn't be opened, even after retrying
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DbUnavailable(pub String);
+
+impl std::fmt::Display for DbUnavailable {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "the search index couldn't be opened: {}", self.0)
+    }
+}
+
+impl std::error::Error for DbUnavailable {}
+
+/// Move an unopenable vector DB aside (never deleting it) so a new index can
+/// be built in its place; returns where it went
+pub fn quarantine(db_path: &Path) -> Result<PathBuf> {
+    let stamp = chrono::Local::now().format("%Y%m%d-%H%M%S");
+    let file_name = db_path
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let backup = db_path.with_file_name(format!("{}.corrupt-{}", file_name, stamp));
+    if db_path.exists() {
+        fs::rename(db_path, &backup)
+            .with_context(|| format!("Failed to move {:?} aside", db_path))?;
+        info!("Moved the vector DB aside to {:?}", backup);
+    }
+    // An interrupted run's file list belongs to the old index
+    index_session::discard(&index_session::session_path(db_path));
+    invalidate_health();
+    Ok(backup)
+}
+
+/// State of the vector DB as seen before a search
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IndexHealth {
+    /// Table exists with this many rows
+    Ready(usize),
+    /// Table exists but holds no rows
+    Empty,
+    /// Table hasn't been created yet
+    Missing,
+    /// The DB couldn't be opened (locked or damaged)
+    Unavailable,
+}
+
+/// How long a health check result is reused (typing shouldn't hammer the DB)
+const HEALTH_CACHE_TTL: Duration = Duration::from_secs(60);
+
+/// Last health check: DB path, when it ran, and the result
+static HEALTH_CACHE: Mutex<Option<(PathBuf, Instant, IndexHealth)>> = Mutex::new(None);
+
+/// Check that the images table exists and has rows, reusing a recent result
+pub async fn check_health(config: &IndexConfig) -> Result<IndexHealth> {
+    if let Some((db_path, checked_at, health)) = HEALTH_CACHE.lock().clone() {
+        if db_path == config.db_path && checked_at.elapsed() < HEALTH_CACHE_TTL {
+            return Ok(health);
+        }
+    }
+
+    // Not cached: it may open fine on the next try
+    let db = match IndexerState::open_or_create_db(&config.db_path).await {
+        Ok(db) => db,
+        Err(e) if e.is::<DbUnavailable>() => return Ok(IndexHealth::Unavailable),
+        Err(e) => return Err(e),
+    };
+    let table_names = db.table_names().execute().await?;
+    let health = if !table_names.contains(&"images".to_string()) {
+        IndexHealth::Missing
+    } else {
+        match db.open_table("images").execute().await?.count_rows(None).await? {
+            0 => IndexHealth::Empty,
+            count => IndexHealth::Ready(count),
+        }
+    };
+
+    *HEALTH_CACHE.lock() = Some((config.db_path.clone(), Instant::now(), health));
+    Ok(health)
+}
+
+/// Drop the cached health check (after the table changed)
+pub fn invalidate_health() {
+    *HEALTH_CACHE.lock() = None;
+}
+
+/// Which files an indexing run picks up
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IndexScope {
+    /// Files not in the index yet
+    New,
+    /// New files, and indexed ones modified since their row was stored
+    Changed,
+    /// Every file, indexed or not
+    #[allow(dead_code)]
+    All,
+}
+
 /// Main indexer state
 pub struct IndexerState {
     config: IndexConfig,
     db: Option<Connection>,
     image_model: Option<Arc<Mutex<ImageEmbedding>>>,
     text_model: Option<Arc<Mutex<TextEmbedding>>>,
-    indexed_files: Arc<Mutex<HashSet<PathBuf>>>,
+    /// Indexed files with the modified time stored for them
+    indexed_files: Arc<Mutex<HashMap<PathBuf, i64>>>,
     message_tx: Sender<AppMessage>,
 }
 
@@ -97,7 +212,7 @@ impl IndexerState {
             db: None,
             image_model: None,
             text_model: None,
-            indexed_files: Arc::new(Mutex::new(HashSet::new())),
+            indexed_files: Arc::new(Mutex::new(HashMap::new())),
             message_tx,
         }
     }
@@ -155,35 +270,47 @@ impl IndexerState {
             Field::new("modified_time", DataType::Int64, false),
             Field::new(
                 "vector",
-                DataType::FixedSizeList(Arc::new(Field::new("item", DataType::Float32, true)), 768),
+                DataType::FixedSizeList(
+                    Arc::new(Field::new("item", DataType::Float32, true)),
+                    EMBEDDING_DIM,
+                ),
                 true,
             ),
         ]))
     }
 
-    /// Open or create database
+    /// Open or create database. A backup tool or virus scanner can hold the
+    /// files for a moment, so failures are retried with backoff; after that
+    /// the error is [`DbUnavailable`] and the directory is left as it is.
     async fn open_or_create_db(db_path: &Path) -> Result<Connection> {
         // Create parent directory if it doesn't exist
         if let Some(parent) = db_path.parent() {
             fs::create_dir_all(parent)?;
         }
 
-        match lancedb::connect(db_path.to_str().unwrap()).execute().await {
-            Ok(db) => {
-                info!("Connected to database: {:?}", db_path);
-                Ok(db)
-            }
-            Err(e) => {
-                warn!("Failed to open database, recreating: {}", e);
-                // Delete corrupt DB
-                if db_path.exists() {
-                    fs::remove_dir_all(db_path)?;
+        let mut delay = OPEN_BACKOFF;
+        let mut attempt = 1;
+        loop {
+            // Listing tables reads the DB, which connecting alone doesn't
+            let opened = match lancedb::connect(db_path.to_str().unwrap()).execute().await {
+                Ok(db) => db.table_names().execute().await.map(|_| db),
+                Err(e) => Err(e),
+            };
+            match opened {
+                Ok(db) => {
+                    info!("Connected to database: {:?}", db_path);
+                    return Ok(db);
+                }
+                Err(e) if attempt < OPEN_ATTEMPTS => {
+                    warn!("Failed to open database (attempt {}), retrying: {}", attempt, e);
+                    tokio::time::sleep(delay).await;
+                    delay *= 2;
+                    attempt += 1;
+                }
+                Err(e) => {
+                    error!("Failed to open database after {} attempts: {}", attempt, e);
+                    return Err(DbUnavailable(e.to_string()).into());
                 }
-                // Recreate
-                lancedb::connect(db_path.to_str().unwrap())
-                    .execute()
-                    .await
-                    .context("Failed to create new database")
             }
         }
     }
@@ -214,10 +341,14 @@ impl IndexerState {
             if let Some(path_col) = batch.column_by_name("file_path") {
                 let path_array: &StringArray =
                     path_col.as_any().downcast_ref::<StringArray>().unwrap();
+                let mtimes = batch
+                    .column_by_name("modified_time")
+                    .and_then(|c| c.as_any().downcast_ref::<Int64Array>());
                 for i in 0..path_array.len() {
                     if !path_array.is_null(i) {
                         let path_str = path_array.value(i);
-                        indexed.insert(PathBuf::from(path_str));
+                        let mtime = mtimes.filter(|m| !m.is_null(i)).map_or(0, |m| m.value(i));
+                        indexed.insert(PathBuf::from(path_str), mtime);
                     }
                 }
             }
@@ -227,23 +358,24 @@ impl IndexerState {
         Ok(())
     }
 
-    /// Check if a file should be indexed
-    #[allow(dead_code)]
-    fn should_index(&self, path: &Path) -> bool {
-        let indexed = self.indexed_files.lock();
-
-        if !indexed.contains(path) {
+    /// Check if a file should be indexed in a run over `scope`
+    fn should_index(scope: IndexScope, path: &Path, indexed: &HashMap<PathBuf, i64>) -> bool {
+        let Some(&stored) = indexed.get(path) else {
             return true; // New file
+        };
+        match scope {
+            IndexScope::New => false,
+            // Rewritten since its row was stored (converted, edited)
+            IndexScope::Changed => fs::metadata(paths::long_path(path))
+                .map(|metadata| mtime_secs(&metadata) > stored)
+                .unwrap_or(false),
+            IndexScope::All => true,
         }
-
-        // For now, skip files that are already indexed
-        // TODO: Check modification time for updates
-        false
     }
 
-    /// Check if path is an image file
+    /// Check if path is an image file that isn't excluded
     fn is_image_file(path: &Path) -> bool {
-        if !path.is_file() {
+        if !path.is_file() || exclude::is_excluded(path) {
             return false;
         }
         path.extension()
@@ -255,27 +387,57 @@ impl IndexerState {
             })
     }
 
-    /// Collect files to index
-    fn collect_files_to_index(&self, force_all: bool) -> Result<Vec<PathBuf>> {
+    /// Check if an image is too large to embed: the vision model squashes
+    /// every image to a small square, which leaves nothing of a scrolling
+    /// capture. Noted once in the file's timeline.
+    fn is_oversized(path: &Path) -> bool {
+        let Some((width, height)) = fs::metadata(paths::long_path(path))
+            .and_then(|metadata| metadata.modified())
+            .ok()
+            .and_then(|modified| thumbnail::dimensions(path, modified))
+        else {
+            return false;
+        };
+        if !thumbnail::is_oversized(width, height) {
+            return false;
+        }
+
+        let skipped = EventKind::IndexSkipped(width, height);
+        if timeline::events(path).last().map(|e| &e.kind) != Some(&skipped) {
+            debug!("Not indexing {:?}: {}x{} is too large", path, width, height);
+            timeline::record(path, skipped);
+        }
+        true
+    }
+
+    /// Collect files to index, along with every directory walked and its mtime
+    fn collect_files_to_index(&self, scope: IndexScope) -> Result<(Vec<PathBuf>, Vec<(PathBuf, SystemTime)>)> {
         let mut files = Vec::new();
+        let mut dirs = Vec::new();
 
         fn visit_dirs(
             dir: &Path,
             files: &mut Vec<PathBuf>,
-            should_check: bool,
-            indexed_set: &HashSet<PathBuf>,
+            dirs: &mut Vec<(PathBuf, SystemTime)>,
+            scope: IndexScope,
+            indexed: &HashMap<PathBuf, i64>,
         ) -> Result<()> {
             if dir.is_dir() {
+                // Read before listing, so a change during the walk invalidates the session
+                if let Some(mtime) = index_session::dir_mtime(dir) {
+                    dirs.push((dir.to_path_buf(), mtime));
+                }
                 for entry in fs::read_dir(dir)? {
                     let entry = entry?;
                     let path = entry.path();
                     if path.is_dir() {
                         // Recursively visit subdirectories
-                        visit_dirs(&path, files, should_check, indexed_set)?;
-                    } else if IndexerState::is_image_file(&path) {
-                        if !should_check || !indexed_set.contains(&path) {
-                            files.push(path);
-                        }
+                        visit_dirs(&path, files, dirs, scope, indexed)?;
+                    } else if IndexerState::is_image_file(&path)
+                        && IndexerState::should_index(scope, &path, indexed)
+                        && !IndexerState::is_oversized(&path)
+                    {
+                        files.push(path);
                     }
                 }
             }
@@ -286,98 +448,35 @@ impl IndexerState {
         visit_dirs(
             &self.config.screenshot_dir,
             &mut files,
-            !force_all,
+            &mut dirs,
+            scope,
             &indexed,
         )?;
 
         info!("Found {} files to index", files.len());
-        Ok(files)
+        Ok((files, dirs))
     }
 
-    /// Insert embeddings into database
+    /// Insert embeddings into database; returns how many replaced older rows
     async fn insert_embeddings(
         &mut self,
         paths: &[PathBuf],
         embeddings: Vec<Vec<f32>>,
-    ) -> Result<()> {
-        if paths.len() != embeddings.len() {
-            anyhow::bail!("Mismatch between paths and embeddings count");
-        }
-
+    ) -> Result<usize> {
         let db = self.db.as_ref().unwrap();
-
-        // Prepare data
-        let mut file_paths = Vec::new();
-        let mut file_sizes = Vec::new();
-        let mut modified_times = Vec::new();
-        let mut vectors = Vec::new();
-
-        for (path, embedding) in paths.iter().zip(embeddings.iter()) {
-            if let Ok(metadata) = fs::metadata(path) {
-                file_paths.push(path.to_str().unwrap().to_string());
-                file_sizes.push(metadata.len());
-
-                let mtime = metadata
-                    .modified()
-                    .unwrap_or(SystemTime::now())
-                    .duration_since(UNIX_EPOCH)
-                    .unwrap()
-                    .as_secs() as i64;
-                modified_times.push(mtime);
-
-                // Convert Vec<f32> to Vec<Option<f32>> for Arrow
-                let embedding_opts: Vec<Option<f32>> = embedding.iter().map(|&v| Some(v)).collect();
-                vectors.push(Some(embedding_opts));
-            }
-        }
-
-        if file_paths.is_empty() {
-            return Ok(());
-        }
-
-        // Create Arrow arrays
-        let schema = Self::create_schema();
-
-        let path_array = StringArray::from(file_paths.clone());
-        let size_array = UInt64Array::from(file_sizes);
-        let mtime_array = Int64Array::from(modified_times);
-        let vector_array =
-            FixedSizeListArray::from_iter_primitive::<Float32Type, _, _>(vectors.into_iter(), 768);
-
-        let batch = RecordBatch::try_new(
-            schema.clone(),
-            vec![
-                Arc::new(path_array),
-                Arc::new(size_array),
-                Arc::new(mtime_array),
-                Arc::new(vector_array),
-            ],
-        )?;
-
-        // Check if table exists
-        let table_names = db.table_names().execute().await?;
-        if table_names.contains(&"images".to_string()) {
-            // Append to existing table
-            let table = db.open_table("images").execute().await?;
-            let batches = RecordBatchIterator::new(vec![Ok(batch)].into_iter(), schema.clone());
-            table.add(Box::new(batches)).execute().await?;
-        } else {
-            // Create new table
-            let batches = RecordBatchIterator::new(vec![Ok(batch)].into_iter(), schema.clone());
-            db.create_table("images", Box::new(batches))
-                .execute()
-                .await?;
-        }
+        let replaced = append_embeddings(db, paths, embeddings).await?;
 
         // Update indexed files set
         {
             let mut indexed = self.indexed_files.lock();
             for path in paths {
-                indexed.insert(path.clone());
+                if let Ok(metadata) = fs::metadata(paths::long_path(path)) {
+                    indexed.insert(path.clone(), mtime_secs(&metadata));
+                }
             }
         }
 
-        Ok(())
+        Ok(replaced)
     }
 
     /// Index a batch of files
@@ -385,56 +484,75 @@ impl IndexerState {
         &mut self,
         files: Vec<PathBuf>,
         indexed_count: &mut usize,
+        refreshed_count: &mut usize,
+        processed: &mut usize,
         total: usize,
+        mut session: Option<&mut IndexSession>,
     ) -> Result<()> {
         let batch_size = self.config.cpu_mode.batch_size();
         let delay_ms = self.config.cpu_mode.delay_ms();
 
-        for (chunk_idx, chunk) in files.chunks(batch_size).enumerate() {
+        for (chunk_idx, batch) in files.chunks(batch_size).enumerate() {
+            // Screenshots deleted since the file list was made are skipped quietly
+            let chunk: Vec<PathBuf> = batch
+                .iter()
+                .filter(|p| paths::long_path(p).exists())
+                .cloned()
+                .collect();
+            if chunk.len() < batch.len() {
+                debug!("Batch {}: skipping {} files that no longer exist", chunk_idx, batch.len() - chunk.len());
+            }
+            if chunk.is_empty() {
+                *processed += batch.len();
+                if let Some(session) = session.as_deref_mut() {
+                    session.complete(batch.len());
+                }
+                continue;
+            }
+
+            // Keep path strings aligned 1:1 with `chunk` so embeddings map back correctly.
+            // Extended-length form lets the decoder open reserved/long names.
             let file_path_strings: Vec<String> = chunk
                 .iter()
-                .filter_map(|p| p.to_str().map(|s| s.to_string()))
+                .map(|p| paths::long_path(p).to_string_lossy().to_string())
                 .collect();
 
             info!("Processing batch {}: {} files", chunk_idx, chunk.len());
 
-            if file_path_strings.is_empty() {
-                warn!("Batch {} has no valid file paths, skipping", chunk_idx);
-                continue;
-            }
-
-            // Get current file name for progress
-            let current_file = chunk[0]
-                .file_name()
-                .and_then(|n| n.to_str())
-                .unwrap_or("unknown")
-                .to_string();
+            // Current file for progress
+            let current_file = chunk[0].clone();
 
             // Embed images (blocking operation)
             let image_model = self.image_model.as_ref().unwrap().clone();
-            let embeddings_result = tokio::task::spawn_blocking(move || {
-                let file_path_refs: Vec<&str> =
-                    file_path_strings.iter().map(|s| s.as_str()).collect();
-                let mut model = image_model.lock();
-                model.embed(file_path_refs, None)
+            let (embedded_paths, embeddings, failures) = tokio::task::spawn_blocking({
+                let chunk = chunk.clone();
+                move || embed_chunk(&image_model, &chunk, &file_path_strings)
             })
-            .await??;
-
-            // Convert embeddings to Vec<Vec<f32>>
-            let embeddings: Vec<Vec<f32>> = embeddings_result.into_iter().collect();
+            .await?;
 
             info!("Batch {}: Got {} embeddings for {} files", chunk_idx, embeddings.len(), chunk.len());
+            for (path, error) in failures {
+                let _ = self.message_tx.send(AppMessage::IndexFileFailed(path, error));
+            }
 
             // Insert into database (only files with valid embeddings)
-            let num_inserted = embeddings.len().min(chunk.len());
-            self.insert_embeddings(&chunk[..num_inserted], embeddings).await?;
-
-            *indexed_count += num_inserted;
-            info!("Batch {}: Successfully indexed {} files (total: {}/{})", chunk_idx, num_inserted, *indexed_count, total);
+            let num_inserted = embedded_paths.len();
+            let num_refreshed = self.insert_embeddings(&embedded_paths, embeddings).await?;
+
+            // Duplicate rows left by older versions count as refreshed too
+            *indexed_count += num_inserted.saturating_sub(num_refreshed);
+            *refreshed_count += num_refreshed;
+            *processed += batch.len();
+            info!("Batch {}: Successfully indexed {} files (total: {}/{})", chunk_idx, num_inserted, *processed, total);
+
+            // Files that failed to embed count as done too; they'd fail again
+            if let Some(session) = session.as_deref_mut() {
+                session.complete(batch.len());
+            }
 
             // Send progress update
             let _ = self.message_tx.send(AppMessage::IndexProgress(
-                *indexed_count,
+                *processed,
                 total,
                 current_file,
             ));
@@ -449,39 +567,98 @@ impl IndexerState {
     }
 
     /// Run the indexing process
-    pub async fn run_indexing(&mut self, force_all: bool) -> Result<()> {
-        info!("Starting indexing process (force_all: {})", force_all);
-
-        // Open database
-        self.db = Some(Self::open_or_create_db(&self.config.db_path).await?);
+    pub async fn run_indexing(&mut self, scope: IndexScope) -> Result<()> {
+        info!("Starting indexing process ({:?})", scope);
+        let force_all = scope == IndexScope::All;
 
-        // Load existing indexed files
-        if !force_all {
-            self.load_indexed_files().await?;
+        // Open database; if it can't be, the user decides what happens to it
+        match Self::open_or_create_db(&self.config.db_path).await {
+            Ok(db) => self.db = Some(db),
+            Err(e) => {
+                if let Some(unavailable) = e.downcast_ref::<DbUnavailable>() {
+                    let _ = self.message_tx.send(AppMessage::IndexUnavailable(unavailable.0.clone()));
+                    return Ok(());
+                }
+                return Err(e);
+            }
         }
 
-        // Collect files to index
-        let files = self.collect_files_to_index(force_all)?;
-        let total = files.len();
+        // A forced re-index starts over; otherwise pick up an interrupted run
+        let session_path = index_session::session_path(&self.config.db_path);
+        let mut session = if force_all {
+            index_session::discard(&session_path);
+            None
+        } else {
+            IndexSession::resume(&session_path, &self.config.screenshot_dir)
+        };
+        let resumed = session.is_some();
+
+        let (files, total, mut processed) = match session.as_ref() {
+            Some(session) => (session.pending().to_vec(), session.total, session.completed),
+            None => {
+                // Load existing indexed files
+                if !force_all {
+                    self.load_indexed_files().await?;
+                }
+
+                // Collect files to index
+                let (files, dirs) = self.collect_files_to_index(scope)?;
+                let total = files.len();
+                if !force_all && total > 0 {
+                    session = Some(IndexSession::start(
+                        session_path,
+                        &self.config.screenshot_dir,
+                        files.clone(),
+                        dirs,
+                    ));
+                }
+                (files, total, 0)
+            }
+        };
 
-        info!("Found {} files to index", total);
+        info!("Found {} files to index", files.len());
 
-        if total == 0 {
+        if files.is_empty() {
             info!("No files to index");
-            let _ = self.message_tx.send(AppMessage::IndexCompleted(0));
+            if let Some(session) = session {
+                session.finish();
+            }
+            let _ = self.message_tx.send(AppMessage::IndexCompleted(0, 0));
             return Ok(());
         }
 
         // Send start message
-        let _ = self.message_tx.send(AppMessage::IndexStarted(total));
+        if resumed {
+            let _ = self.message_tx.send(AppMessage::IndexResumed(processed, total));
+        } else {
+            let _ = self.message_tx.send(AppMessage::IndexStarted(total));
+        }
 
         // Index files
         let mut indexed_count = 0;
-        match self.index_batch(files, &mut indexed_count, total).await {
+        let mut refreshed_count = 0;
+        match self
+            .index_batch(
+                files,
+                &mut indexed_count,
+                &mut refreshed_count,
+                &mut processed,
+                total,
+                session.as_mut(),
+            )
+            .await
+        {
             Ok(_) => {
-                info!("Successfully indexed {} out of {} files", indexed_count, total);
+                info!(
+                    "Successfully indexed {} and refreshed {} out of {} files",
+                    indexed_count, refreshed_count, total
+                );
+                if let Some(session) = session {
+                    session.finish();
+                }
             }
             Err(e) => {
+                // The session stays on disk so the next run resumes here
                 error!("Error during indexing: {}. Indexed {} files before error.", e, indexed_count);
                 // Continue and send the count of files that were successfully indexed
             }
@@ -490,19 +667,198 @@ impl IndexerState {
         // Send completion message with count
         let _ = self
             .message_tx
-            .send(AppMessage::IndexCompleted(indexed_count));
+            .send(AppMessage::IndexCompleted(indexed_count, refreshed_count));
         info!("Indexing completed: {} files processed", indexed_count);
 
         Ok(())
     }
 }
 
+/// Modified time as stored in the index (whole seconds since the epoch)
+fn mtime_secs(metadata: &fs::Metadata) -> i64 {
+    metadata
+        .modified()
+        .unwrap_or(SystemTime::now())
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs() as i64
+}
+
+/// Add rows for embedded files, with their size and modified time, in place
+/// of any rows they already had. Returns how many were replaced.
+async fn append_embeddings(
+    db: &Connection,
+    paths: &[PathBuf],
+    embeddings: Vec<Vec<f32>>,
+) -> Result<usize> {
+    if paths.len() != embeddings.len() {
+        anyhow::bail!("Mismatch between paths and embeddings count");
+    }
+
+    // Prepare data
+    let mut file_paths = Vec::new();
+    let mut file_sizes = Vec::new();
+    let mut modified_times = Vec::new();
+    let mut vectors = Vec::new();
+
+    for (path, embedding) in paths.iter().zip(embeddings.iter()) {
+        if let Ok(metadata) = fs::metadata(paths::long_path(path)) {
+            // Store the plain path so lookups from the gallery match exactly
+            file_paths.push(paths::display_path(path).to_string_lossy().to_string());
+            file_sizes.push(metadata.len());
+            modified_times.push(mtime_secs(&metadata));
+
+            // Convert Vec<f32> to Vec<Option<f32>> for Arrow
+            let embedding_opts: Vec<Option<f32>> = embedding.iter().map(|&v| Some(v)).collect();
+            vectors.push(Some(embedding_opts));
+        }
+    }
+
+    if file_paths.is_empty() {
+        return Ok(0);
+    }
+
+    // Rows from before the file changed
+    let table_names = db.table_names().execute().await?;
+    let replaced = if table_names.contains(&"images".to_string()) {
+        let table = db.open_table("images").execute().await?;
+        delete_paths(&table, &file_paths).await?
+    } else {
+        0
+    };
+
+    append_rows(db, file_paths, file_sizes, modified_times, vectors).await?;
+    invalidate_health();
+
+    for path in paths {
+        timeline::record(path, EventKind::Indexed);
+    }
+    Ok(replaced)
+}
+
+/// Append rows to the images table, creating it on first use
+async fn append_rows(
+    db: &Connection,
+    file_paths: Vec<String>,
+    file_sizes: Vec<u64>,
+    modified_times: Vec<i64>,
+    vectors: Vec<Option<Vec<Option<f32>>>>,
+) -> Result<()> {
+    // Create Arrow arrays
+    let schema = IndexerState::create_schema();
+
+    let path_array = StringArray::from(file_paths);
+    let size_array = UInt64Array::from(file_sizes);
+    let mtime_array = Int64Array::from(modified_times);
+    let vector_array = FixedSizeListArray::from_iter_primitive::<Float32Type, _, _>(
+        vectors.into_iter(),
+        EMBEDDING_DIM,
+    );
+
+    let batch = RecordBatch::try_new(
+        schema.clone(),
+        vec![
+            Arc::new(path_array),
+            Arc::new(size_array),
+            Arc::new(mtime_array),
+            Arc::new(vector_array),
+        ],
+    )?;
+
+    // Check if table exists
+    let table_names = db.table_names().execute().await?;
+    if table_names.contains(&"images".to_string()) {
+        // Append to existing table
+        let table = db.open_table("images").execute().await?;
+        let batches = RecordBatchIterator::new(vec![Ok(batch)].into_iter(), schema.clone());
+        table.add(Box::new(batches)).execute().await?;
+    } else {
+        // Create new table
+        let batches = RecordBatchIterator::new(vec![Ok(batch)].into_iter(), schema.clone());
+        db.create_table("images", Box::new(batches))
+            .execute()
+            .await?;
+    }
+    Ok(())
+}
+
+/// Write embeddings for `paths` straight into the index at `db_path` (test fixtures)
+#[cfg(test)]
+pub async fn write_rows(db_path: &Path, rows: &[(PathBuf, Vec<f32>)]) -> Result<()> {
+    let db = IndexerState::open_or_create_db(db_path).await?;
+    append_rows(
+        &db,
+        rows.iter().map(|(p, _)| p.to_string_lossy().to_string()).collect(),
+        rows.iter().map(|_| 0).collect(),
+        rows.iter().map(|_| 0).collect(),
+        rows.iter()
+            .map(|(_, v)| Some(v.iter().map(|&x| Some(x)).collect()))
+            .collect(),
+    )
+    .await
+}
+
+/// Embed a chunk of images, falling back to one-by-one embedding when the
+/// batch fails so a single undecodable file doesn't drop the whole chunk.
+/// Returns the paths that were embedded along with their embeddings.
+fn embed_chunk(
+    image_model: &Arc<Mutex<ImageEmbedding>>,
+    chunk: &[PathBuf],
+    file_path_strings: &[String],
+) -> (Vec<PathBuf>, Vec<Vec<f32>>, Vec<(PathBuf, String)>) {
+    let mut model = image_model.lock();
+
+    let refs: Vec<&str> = file_path_strings.iter().map(|s| s.as_str()).collect();
+    match model.embed(refs, None) {
+        Ok(embeddings) if embeddings.len() == chunk.len() => {
+            return (chunk.to_vec(), embeddings, Vec::new());
+        }
+        Ok(embeddings) => {
+            warn!(
+                "Batch returned {} embeddings for {} files, retrying per file",
+                embeddings.len(),
+                chunk.len()
+            );
+        }
+        Err(e) => {
+            warn!("Batch embedding failed, retrying per file: {}", e);
+        }
+    }
+
+    let mut embedded_paths = Vec::new();
+    let mut embeddings = Vec::new();
+    let mut failures = Vec::new();
+    for (path, path_str) in chunk.iter().zip(file_path_strings) {
+        match model.embed(vec![path_str.as_str()], None) {
+            Ok(mut result) if !result.is_empty() => {
+                embedded_paths.push(path.clone());
+                embeddings.push(result.remove(0));
+            }
+            Ok(_) => {
+                warn!("No embedding produced for {:?}", path);
+                timeline::record(path, EventKind::IndexFailed("No embedding produced".to_string()));
+                failures.push((path.clone(), "No embedding produced".to_string()));
+            }
+            // Deleted while the batch ran
+            Err(_) if !paths::long_path(path).exists() => {
+                debug!("{:?} was deleted before it could be embedded", path);
+            }
+            Err(e) => {
+                warn!("Failed to embed {:?}: {}", path, e);
+                timeline::record(path, EventKind::IndexFailed(e.to_string()));
+                failures.push((path.clone(), e.to_string()));
+            }
+        }
+    }
+    (embedded_paths, embeddings, failures)
+}
+
 /// Start indexing in a background thread
 /// If prewarmed models are provided, they will be used instead of loading fresh models
 pub fn start_indexing(
     config: IndexConfig,
     message_tx: Sender<AppMessage>,
-    force_all: bool,
+    scope: IndexScope,
     prewarmed_vision: Option<Arc<Mutex<ImageEmbedding>>>,
     prewarmed_text: Option<Arc<Mutex<TextEmbedding>>>,
 ) {
@@ -549,7 +905,7 @@ pub fn start_indexing(
             }
 
             // Run indexing
-            match state.run_indexing(force_all).await {
+            match state.run_indexing(scope).await {
                 Ok(_) => {
                     info!("Indexing completed successfully");
                 }
@@ -562,20 +918,130 @@ pub fn start_indexing(
     });
 }
 
-/// Index a single file (for auto-indexing new screenshots)
-#[allow(dead_code)]
-pub fn index_single_file(_path: PathBuf, _config: IndexConfig, _message_tx: Sender<AppMessage>) {
-    // TODO: Implement single file indexing
-    // For now, this is a placeholder - full re-indexing will pick up new files
+/// Index a few new screenshots without walking the screenshot tree: embed
+/// just `files` with `image_model` and append their rows. Files already
+/// indexed with the same modified time are skipped, and a changed one has its
+/// old row replaced. Answers with [`AppMessage::FilesIndexed`].
+pub fn index_files(
+    files: Vec<PathBuf>,
+    config: IndexConfig,
+    image_model: Arc<Mutex<ImageEmbedding>>,
+    message_tx: Sender<AppMessage>,
+) {
+    std::thread::spawn(move || {
+        let rt = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap();
+
+        let indexed = match rt.block_on(index_files_impl(&files, &config.db_path, image_model)) {
+            Ok(indexed) => {
+                info!("Indexed {} of {} new screenshots", indexed, files.len());
+                indexed
+            }
+            Err(e) => {
+                error!("Failed to index {} new screenshots: {}", files.len(), e);
+                if let Some(unavailable) = e.downcast_ref::<DbUnavailable>() {
+                    let _ = message_tx.send(AppMessage::IndexUnavailable(unavailable.0.clone()));
+                }
+                0
+            }
+        };
+        let _ = message_tx.send(AppMessage::FilesIndexed(indexed));
+    });
+}
+
+/// Embed and store the files in `files` that aren't indexed as they are now.
+/// Returns how many were indexed.
+async fn index_files_impl(
+    files: &[PathBuf],
+    db_path: &Path,
+    image_model: Arc<Mutex<ImageEmbedding>>,
+) -> Result<usize> {
+    let db = IndexerState::open_or_create_db(db_path).await?;
+    let table_names = db.table_names().execute().await?;
+    let table = if table_names.contains(&"images".to_string()) {
+        Some(db.open_table("images").execute().await?)
+    } else {
+        None
+    };
+
+    let mut pending = Vec::new();
+    for path in files {
+        // Deleted since it was queued
+        let Ok(metadata) = fs::metadata(paths::long_path(path)) else {
+            continue;
+        };
+        if IndexerState::is_oversized(path) {
+            continue;
+        }
+        if let Some(table) = &table {
+            let stored = stored_mtime(table, &paths::display_path(path)).await?;
+            if stored == Some(mtime_secs(&metadata)) {
+                debug!("{:?} is already indexed", path);
+                continue;
+            }
+        }
+        pending.push(path.clone());
+    }
+    if pending.is_empty() {
+        return Ok(0);
+    }
+
+    let file_path_strings: Vec<String> = pending
+        .iter()
+        .map(|p| paths::long_path(p).to_string_lossy().to_string())
+        .collect();
+    let (embedded_paths, embeddings, failures) = tokio::task::spawn_blocking({
+        let pending = pending.clone();
+        move || embed_chunk(&image_model, &pending, &file_path_strings)
+    })
+    .await?;
+    for (path, error) in failures {
+        debug!("Skipping {:?}: {}", path, error);
+    }
+    if embedded_paths.is_empty() {
+        return Ok(0);
+    }
+
+    // A changed file's old row is replaced
+    append_embeddings(&db, &embedded_paths, embeddings).await?;
+    Ok(embedded_paths.len())
+}
+
+/// Modified time stored with `path`'s row, if it's indexed
+async fn stored_mtime(table: &Table, path: &Path) -> Result<Option<i64>> {
+    let path_str = path.to_string_lossy().to_string();
+    let mut results = table
+        .query()
+        .only_if(format!("file_path = {}", sql_string_literal(&path_str)))
+        .limit(1)
+        .execute()
+        .await?;
+
+    while let Some(batch) = results.try_next().await? {
+        let Some(mtimes) = batch
+            .column_by_name("modified_time")
+            .and_then(|c| c.as_any().downcast_ref::<Int64Array>())
+        else {
+            continue;
+        };
+        if !mtimes.is_empty() && !mtimes.is_null(0) {
+            return Ok(Some(mtimes.value(0)));
+        }
+    }
+    Ok(None)
 }
 
-/// Search for images by text query
+/// Search for images by text query, dropping matches less similar than
+/// `min_similarity`
 pub fn search_images(
     query: String,
     config: IndexConfig,
     text_model: Arc<Mutex<TextEmbedding>>,
     message_tx: Sender<AppMessage>,
     limit: usize,
+    min_similarity: f32,
 ) {
     std::thread::spawn(move || {
         let rt = tokio::runtime::Builder::new_current_thread()
@@ -584,29 +1050,59 @@ pub fn search_images(
             .unwrap();
 
         rt.block_on(async {
-            match search_images_impl(query, config, text_model, limit).await {
-                Ok(paths) => {
-                    let _ = message_tx.send(AppMessage::SearchResults(paths));
+            // A missing or empty table means "nothing indexed yet", not "no matches"
+            match check_health(&config).await {
+                Ok(health) => {
+                    let _ = message_tx.send(AppMessage::SearchIndexHealth(health));
+                    if !matches!(health, IndexHealth::Ready(_)) {
+                        let _ = message_tx.send(AppMessage::SearchResults(Vec::new(), None));
+                        return;
+                    }
+                }
+                Err(e) => warn!("Index health check failed: {}", e),
+            }
+
+            match search_images_impl(query, config, text_model, limit, min_similarity).await {
+                Ok((matches, stats)) => {
+                    let _ = message_tx.send(AppMessage::SearchResults(matches, Some(stats)));
                 }
                 Err(e) => {
                     error!("Search failed: {}", e);
-                    let _ = message_tx.send(AppMessage::SearchResults(Vec::new()));
+                    let _ = message_tx.send(AppMessage::SearchResults(Vec::new(), None));
                 }
             }
         });
     });
 }
 
+/// Search by text query on the calling thread (for callers outside the UI);
+/// matches are `(path, cosine distance)`, most relevant first
+pub fn search_images_blocking(
+    query: String,
+    config: IndexConfig,
+    text_model: Arc<Mutex<TextEmbedding>>,
+    limit: usize,
+    min_similarity: f32,
+) -> Result<Vec<(PathBuf, f32)>> {
+    let rt = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()?;
+    rt.block_on(search_images_impl(query, config, text_model, limit, min_similarity))
+        .map(|(matches, _)| matches)
+}
+
 /// Internal search implementation
 async fn search_images_impl(
     query: String,
     config: IndexConfig,
     text_model: Arc<Mutex<TextEmbedding>>,
     limit: usize,
-) -> Result<Vec<PathBuf>> {
+    min_similarity: f32,
+) -> Result<(Vec<(PathBuf, f32)>, SearchStats)> {
     info!("Searching for: {}", query);
 
     // Embed text query
+    let embed_start = Instant::now();
     let query_string_vec = vec![query.clone()];
     let query_embedding_result = tokio::task::spawn_blocking(move || {
         let query_strs: Vec<&str> = query_string_vec.iter().map(|s| s.as_str()).collect();
@@ -615,56 +1111,344 @@ async fn search_images_impl(
     })
     .await??;
 
+    let embed = embed_start.elapsed();
+
     if query_embedding_result.is_empty() {
-        return Ok(Vec::new());
+        return Ok((Vec::new(), SearchStats { embed, ..SearchStats::default() }));
     }
 
     // Convert embedding to Vec<f32>
     let query_vec: Vec<f32> = query_embedding_result[0].clone().into_iter().collect();
 
+    let (candidates, stats) = search_by_vector_with_stats(&config.db_path, &query_vec, limit).await?;
+    let found = candidates.len();
+    let matches = rank_matches(candidates, min_similarity);
+    let stats = SearchStats { embed, ..stats };
+    debug!(
+        "Search took {:?} (embed {:?}, query {:?}) over {} rows using {}; kept {} of {} matches at {} similarity",
+        stats.total(),
+        stats.embed,
+        stats.query,
+        stats.rows,
+        if stats.ann { "the ANN index" } else { "a full scan" },
+        matches.len(),
+        found,
+        min_similarity
+    );
+    Ok((matches, stats))
+}
+
+/// Nearest neighbours as `(path, cosine distance)`, most relevant first,
+/// without the ones less similar than `min_similarity` or listed twice
+pub fn rank_matches(candidates: Vec<(PathBuf, f32)>, min_similarity: f32) -> Vec<(PathBuf, f32)> {
+    let max_distance = 1.0 - min_similarity;
+    let mut matches: Vec<(PathBuf, f32)> = candidates
+        .into_iter()
+        .filter(|(_, distance)| *distance <= max_distance)
+        .collect();
+    // Batches come back ranked on their own, not necessarily across batches
+    matches.sort_by(|a, b| a.1.total_cmp(&b.1));
+    let mut seen = HashSet::new();
+    matches.retain(|(path, _)| seen.insert(path.clone()));
+    matches
+}
+
+/// Timings of one search, shown under the search box and logged to help tune
+/// when an ANN index is worth building
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct SearchStats {
+    /// Embedding the query text
+    pub embed: Duration,
+    /// Vector query, including dropping files that no longer exist
+    pub query: Duration,
+    /// Whether an ANN index on the vector column served the query (else brute force)
+    pub ann: bool,
+    /// Rows in the table when searched
+    pub rows: usize,
+}
+
+impl SearchStats {
+    pub fn total(&self) -> Duration {
+        self.embed + self.query
+    }
+}
+
+/// Nearest neighbours of an embedded query, most similar first, skipping
+/// files that no longer exist (test fixtures)
+#[cfg(test)]
+pub async fn search_by_vector(db_path: &Path, query_vec: &[f32], limit: usize) -> Result<Vec<PathBuf>> {
+    search_by_vector_with_stats(db_path, query_vec, limit)
+        .await
+        .map(|(candidates, _)| candidates.into_iter().map(|(path, _)| path).collect())
+}
+
+/// Nearest neighbours of an embedded query with each path's cosine distance,
+/// also reporting how the query ran (`embed` left zero)
+async fn search_by_vector_with_stats(
+    db_path: &Path,
+    query_vec: &[f32],
+    limit: usize,
+) -> Result<(Vec<(PathBuf, f32)>, SearchStats)> {
+    let start = Instant::now();
+
     // Open database
-    let db = IndexerState::open_or_create_db(&config.db_path).await?;
+    let db = IndexerState::open_or_create_db(db_path).await?;
 
     // Check if table exists
     let table_names = db.table_names().execute().await?;
     if !table_names.contains(&"images".to_string()) {
-        return Ok(Vec::new());
+        return Ok((Vec::new(), SearchStats::default()));
     }
 
     let table = db.open_table("images").execute().await?;
 
-    // Vector search
+    // Without an index on the vector column LanceDB falls back to a flat scan
+    let ann = table
+        .list_indices()
+        .await?
+        .iter()
+        .any(|index| index.columns.iter().any(|column| column == "vector"));
+    let rows = table.count_rows(None).await?;
+    if log::log_enabled!(log::Level::Debug) {
+        match table.query().nearest_to(query_vec)?.explain_plan(true).await {
+            Ok(plan) => debug!("Search plan:\n{}", plan),
+            Err(e) => debug!("No search plan: {}", e),
+        }
+    }
+
+    // Vector search, cosine like "Select Similar" so the cutoff reads as a similarity
     let mut results = table
         .query()
-        .nearest_to(query_vec.as_slice())?
+        .nearest_to(query_vec)?
+        .distance_type(DistanceType::Cosine)
+        .limit(limit)
         .execute()
         .await?;
 
-    // Extract file paths
-    let mut paths = Vec::new();
+    // Extract file paths with their distance
+    let mut candidates = Vec::new();
     while let Some(batch) = results.try_next().await? {
-        if let Some(path_col) = batch.column_by_name("file_path") {
-            let path_array: &StringArray = path_col.as_any().downcast_ref::<StringArray>().unwrap();
-            for i in 0..path_array.len() {
-                if paths.len() >= limit {
-                    break;
-                }
-                if !path_array.is_null(i) {
-                    let path_str = path_array.value(i);
-                    let path = PathBuf::from(path_str);
-                    if path.exists() {
-                        paths.push(path);
-                    }
+        let (Some(path_col), Some(distance_col)) =
+            (batch.column_by_name("file_path"), batch.column_by_name("_distance"))
+        else {
+            continue;
+        };
+        let path_array: &StringArray = path_col.as_any().downcast_ref::<StringArray>().unwrap();
+        let distances: &Float32Array = distance_col.as_any().downcast_ref::<Float32Array>().unwrap();
+        for i in 0..path_array.len() {
+            if candidates.len() >= limit {
+                break;
+            }
+            if !path_array.is_null(i) {
+                let path_str = path_array.value(i);
+                let path = PathBuf::from(path_str);
+                if path.exists() {
+                    candidates.push((path, distances.value(i)));
                 }
             }
         }
-        if paths.len() >= limit {
+        if candidates.len() >= limit {
             break;
         }
     }
 
-    info!("Found {} matching images", paths.len());
-    Ok(paths)
+    let stats = SearchStats {
+        query: start.elapsed(),
+        ann,
+        rows,
+        ..SearchStats::default()
+    };
+    info!("Found {} matching images in {:?}", candidates.len(), stats.query);
+    Ok((candidates, stats))
+}
+
+/// Look for screenshots that look like `path` in the background; answers
+/// with [`AppMessage::SimilarFound`]
+pub fn find_similar(
+    path: PathBuf,
+    config: IndexConfig,
+    min_similarity: f32,
+    limit: usize,
+    message_tx: Sender<AppMessage>,
+) {
+    std::thread::spawn(move || {
+        let rt = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap();
+
+        let matches = match rt.block_on(find_similar_scored(&config.db_path, &path, min_similarity, limit)) {
+            Ok(matches) => matches,
+            Err(e) => {
+                error!("Similar search for {:?} failed: {}", path, e);
+                None
+            }
+        };
+        let _ = message_tx.send(AppMessage::SimilarFound(path, matches));
+    });
+}
+
+/// Search for screenshots that look like `path`, indexing it first if it
+/// isn't indexed as it is now. Answers with [`AppMessage::SearchResults`]
+/// like a text search, without `path` itself.
+pub fn search_similar(
+    path: PathBuf,
+    config: IndexConfig,
+    image_model: Arc<Mutex<ImageEmbedding>>,
+    message_tx: Sender<AppMessage>,
+    limit: usize,
+) {
+    std::thread::spawn(move || {
+        let rt = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap();
+
+        match rt.block_on(search_similar_impl(&path, &config.db_path, image_model, limit)) {
+            Ok(Some((matches, stats))) => {
+                let _ = message_tx.send(AppMessage::SearchResults(matches, Some(stats)));
+            }
+            Ok(None) => {
+                warn!("{:?} couldn't be indexed, nothing to compare with", path);
+                let _ = message_tx.send(AppMessage::SearchResults(Vec::new(), None));
+            }
+            Err(e) => {
+                error!("Similar search for {:?} failed: {}", path, e);
+                if let Some(unavailable) = e.downcast_ref::<DbUnavailable>() {
+                    let _ = message_tx.send(AppMessage::IndexUnavailable(unavailable.0.clone()));
+                }
+                let _ = message_tx.send(AppMessage::SearchResults(Vec::new(), None));
+            }
+        }
+    });
+}
+
+/// Nearest neighbours of `path`'s embedding, most similar first. `None` if
+/// it can't be indexed (too large or undecodable).
+async fn search_similar_impl(
+    path: &Path,
+    db_path: &Path,
+    image_model: Arc<Mutex<ImageEmbedding>>,
+    limit: usize,
+) -> Result<Option<(Vec<(PathBuf, f32)>, SearchStats)>> {
+    info!("Searching for screenshots like {:?}", path);
+
+    // Skipped when it's indexed as it is now
+    let embed_start = Instant::now();
+    if index_files_impl(&[path.to_path_buf()], db_path, image_model).await? > 0 {
+        info!("Indexed {:?} to search for similar screenshots", path);
+    }
+    let embed = embed_start.elapsed();
+
+    let db = IndexerState::open_or_create_db(db_path).await?;
+    let table_names = db.table_names().execute().await?;
+    if !table_names.contains(&"images".to_string()) {
+        return Ok(None);
+    }
+    let table = db.open_table("images").execute().await?;
+    let source = paths::display_path(path);
+    let Some(vector) = stored_vector(&table, &source).await? else {
+        return Ok(None);
+    };
+
+    // One extra for the source, which is its own nearest neighbour
+    let (candidates, stats) = search_by_vector_with_stats(db_path, &vector, limit + 1).await?;
+    let candidates = candidates
+        .into_iter()
+        .filter(|(candidate, _)| *candidate != source)
+        .collect();
+    // No cutoff beyond unrelated images: screenshots are all fairly alike to
+    // the model, so the ranking does the work
+    let mut matches = rank_matches(candidates, 0.0);
+    matches.truncate(limit);
+    Ok(Some((matches, SearchStats { embed, ..stats })))
+}
+
+/// Screenshots with a cosine similarity of at least `min_similarity` to
+/// `path`, most similar first, with their similarity; `path` itself and
+/// files that no longer exist are left out. `None` if `path` isn't indexed.
+async fn find_similar_scored(
+    db_path: &Path,
+    path: &Path,
+    min_similarity: f32,
+    limit: usize,
+) -> Result<Option<Vec<(PathBuf, f32)>>> {
+    let db = IndexerState::open_or_create_db(db_path).await?;
+    let table_names = db.table_names().execute().await?;
+    if !table_names.contains(&"images".to_string()) {
+        return Ok(None);
+    }
+    let table = db.open_table("images").execute().await?;
+
+    let source = paths::display_path(path);
+    let Some(vector) = stored_vector(&table, &source).await? else {
+        return Ok(None);
+    };
+
+    // One extra for the source, which is its own nearest neighbour
+    let mut results = table
+        .query()
+        .nearest_to(vector.as_slice())?
+        .distance_type(DistanceType::Cosine)
+        .limit(limit + 1)
+        .execute()
+        .await?;
+
+    let mut matches = Vec::new();
+    while let Some(batch) = results.try_next().await? {
+        let (Some(path_col), Some(distance_col)) =
+            (batch.column_by_name("file_path"), batch.column_by_name("_distance"))
+        else {
+            continue;
+        };
+        let path_array: &StringArray = path_col.as_any().downcast_ref::<StringArray>().unwrap();
+        let distances: &Float32Array = distance_col.as_any().downcast_ref::<Float32Array>().unwrap();
+        for i in 0..path_array.len() {
+            if path_array.is_null(i) {
+                continue;
+            }
+            // Cosine distance is 1 - similarity
+            let similarity = 1.0 - distances.value(i);
+            let candidate = PathBuf::from(path_array.value(i));
+            if similarity >= min_similarity
+                && candidate != source
+                && paths::long_path(&candidate).exists()
+            {
+                matches.push((candidate, similarity));
+            }
+        }
+    }
+    matches.truncate(limit);
+    debug!("{} screenshots similar to {:?}", matches.len(), source);
+    Ok(Some(matches))
+}
+
+/// Embedding stored for `path`, if it's indexed
+async fn stored_vector(table: &Table, path: &Path) -> Result<Option<Vec<f32>>> {
+    let path_str = path.to_string_lossy().to_string();
+    let mut results = table
+        .query()
+        .only_if(format!("file_path = {}", sql_string_literal(&path_str)))
+        .limit(1)
+        .execute()
+        .await?;
+
+    while let Some(batch) = results.try_next().await? {
+        let Some(vectors) = batch
+            .column_by_name("vector")
+            .and_then(|c| c.as_any().downcast_ref::<FixedSizeListArray>())
+        else {
+            continue;
+        };
+        if vectors.is_empty() || vectors.is_null(0) {
+            continue;
+        }
+        let values = vectors.value(0);
+        if let Some(values) = values.as_any().downcast_ref::<Float32Array>() {
+            return Ok(Some(values.values().to_vec()));
+        }
+    }
+    Ok(None)
 }
 
 /// Get index statistics
@@ -723,6 +1507,24 @@ pub fn get_indexed_count(config: &IndexConfig) -> Result<usize> {
     })
 }
 
+/// Row count of every table in the index at `db_path`, for diagnostic bundles
+pub fn table_row_counts(db_path: &Path) -> Result<Vec<(String, usize)>> {
+    let rt = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()?;
+
+    rt.block_on(async {
+        let db = IndexerState::open_or_create_db(db_path).await?;
+        let mut counts = Vec::new();
+        for name in db.table_names().execute().await? {
+            let table = db.open_table(&name).execute().await?;
+            let count = table.count_rows(None).await?;
+            counts.push((name, count));
+        }
+        Ok(counts)
+    })
+}
+
 /// Remove a file from the index by path (cleanup for deleted files)
 pub fn remove_from_index(path: PathBuf, config: IndexConfig) {
     std::thread::spawn(move || {
@@ -733,8 +1535,11 @@ pub fn remove_from_index(path: PathBuf, config: IndexConfig) {
 
         rt.block_on(async {
             match remove_from_index_impl(path.clone(), config).await {
-                Ok(_) => {
-                    info!("Removed {:?} from vector index", path);
+                Ok(0) => {
+                    debug!("{:?} wasn't in the vector index", path);
+                }
+                Ok(removed) => {
+                    info!("Removed {:?} from vector index ({} rows)", path, removed);
                 }
                 Err(e) => {
                     warn!("Failed to remove {:?} from index: {}", path, e);
@@ -744,24 +1549,492 @@ pub fn remove_from_index(path: PathBuf, config: IndexConfig) {
     });
 }
 
-/// Remove implementation (async)
-async fn remove_from_index_impl(path: PathBuf, config: IndexConfig) -> Result<()> {
+/// Remove implementation (async); returns how many rows were deleted
+async fn remove_from_index_impl(path: PathBuf, config: IndexConfig) -> Result<usize> {
     let db = IndexerState::open_or_create_db(&config.db_path).await?;
 
     let table_names = db.table_names().execute().await?;
     if !table_names.contains(&"images".to_string()) {
         // Table doesn't exist, nothing to remove
-        return Ok(());
+        return Ok(0);
     }
 
     let table = db.open_table("images").execute().await?;
+    let path_str = paths::display_path(&path).to_string_lossy().to_string();
+    delete_paths(&table, &[path_str]).await
+}
 
-    // Delete rows where path matches
-    let path_str = path.to_string_lossy().to_string();
+/// Point the rows for converted or renamed screenshots (old path, new path)
+/// at the new files. The picture is the same, so the embedding still holds.
+pub fn rename_in_index(moves: Vec<(PathBuf, PathBuf)>, config: IndexConfig) {
+    std::thread::spawn(move || {
+        if let Err(e) = rename_in_index_blocking(&moves, &config) {
+            warn!("Failed to update the index for moved files: {}", e);
+        }
+    });
+}
+
+/// [`rename_in_index`] on the calling thread, for batches that must have a
+/// file's rows moved before the watcher reports the old path gone (which
+/// deletes any rows still under it); returns how many rows were rewritten
+pub fn rename_in_index_blocking(moves: &[(PathBuf, PathBuf)], config: &IndexConfig) -> Result<usize> {
+    let rt = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()?;
+    let renamed = rt.block_on(rename_in_index_impl(&config.db_path, moves))?;
+    match renamed {
+        0 => debug!("None of the {} moved files were in the vector index", moves.len()),
+        renamed => info!("Pointed {} index rows at moved files", renamed),
+    }
+    Ok(renamed)
+}
+
+/// Rename implementation (async); returns how many rows were rewritten
+async fn rename_in_index_impl(db_path: &Path, moves: &[(PathBuf, PathBuf)]) -> Result<usize> {
+    let db = IndexerState::open_or_create_db(db_path).await?;
+    let table_names = db.table_names().execute().await?;
+    if !table_names.contains(&"images".to_string()) {
+        return Ok(0);
+    }
+    let table = db.open_table("images").execute().await?;
+
+    let mut renamed = 0;
+    for (from, to) in moves {
+        let from_str = paths::display_path(from).to_string_lossy().to_string();
+        let filter = format!("file_path = {}", sql_string_literal(&from_str));
+        let matching = table.count_rows(Some(filter.clone())).await?;
+        if matching == 0 {
+            continue;
+        }
+        let to_str = paths::display_path(to).to_string_lossy().to_string();
+        let mut update = table
+            .update()
+            .only_if(filter)
+            .column("file_path", sql_string_literal(&to_str));
+        // Size and mtime as the Changed scope compares them
+        if let Ok(metadata) = fs::metadata(paths::long_path(to)) {
+            update = update
+                .column("file_size", metadata.len().to_string())
+                .column("modified_time", mtime_secs(&metadata).to_string());
+        }
+        update.execute().await?;
+        renamed += matching;
+    }
+    if renamed > 0 {
+        invalidate_health();
+    }
+    Ok(renamed)
+}
+
+/// Paths deleted per filter expression
+const DELETE_CHUNK: usize = 100;
+
+/// Delete the rows for `file_paths`, checking that none are left behind.
+/// Returns how many rows were deleted.
+async fn delete_paths(table: &Table, file_paths: &[String]) -> Result<usize> {
+    let mut removed = 0;
+    for chunk in file_paths.chunks(DELETE_CHUNK) {
+        let literals: Vec<String> = chunk.iter().map(|p| sql_string_literal(p)).collect();
+        let filter = format!("file_path IN ({})", literals.join(", "));
+        let matching = table.count_rows(Some(filter.clone())).await?;
+        if matching == 0 {
+            continue;
+        }
+        table.delete(&filter).await?;
+        let left = table.count_rows(Some(filter)).await?;
+        if left > 0 {
+            anyhow::bail!("{} index rows still there after deleting", left);
+        }
+        removed += matching;
+    }
+    if removed > 0 {
+        invalidate_health();
+    }
+    Ok(removed)
+}
+
+/// Remove index rows for screenshots that no longer exist (deleted while the
+/// app wasn't running, or before deletions reached the index). Recycled ones
+/// still in their restore grace period are kept.
+pub fn prune_missing(config: IndexConfig) {
+    std::thread::spawn(move || {
+        let rt = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap();
+
+        match rt.block_on(prune_missing_impl(&config.db_path)) {
+            Ok(0) => debug!("No stale index entries"),
+            Ok(removed) => info!("Removed {} stale index entries", removed),
+            Err(e) => warn!("Failed to prune the vector index: {}", e),
+        }
+    });
+}
+
+async fn prune_missing_impl(db_path: &Path) -> Result<usize> {
+    let db = IndexerState::open_or_create_db(db_path).await?;
+    let table_names = db.table_names().execute().await?;
+    if !table_names.contains(&"images".to_string()) {
+        return Ok(0);
+    }
+    let table = db.open_table("images").execute().await?;
+
+    let mut missing = Vec::new();
+    let mut results = table.query().execute().await?;
+    while let Some(batch) = results.try_next().await? {
+        let Some(path_array) = batch
+            .column_by_name("file_path")
+            .and_then(|c| c.as_any().downcast_ref::<StringArray>())
+        else {
+            continue;
+        };
+        for i in 0..path_array.len() {
+            if path_array.is_null(i) {
+                continue;
+            }
+            let path = Path::new(path_array.value(i));
+            // Recycled files keep their rows until their grace period runs out
+            if !paths::long_path(path).exists() && !removals::is_pending(path) {
+                missing.push(path_array.value(i).to_string());
+            }
+        }
+    }
+    if missing.is_empty() {
+        return Ok(0);
+    }
+    debug!("{} index entries point at missing files", missing.len());
+    delete_paths(&table, &missing).await
+}
+
+/// Point every row for a file under `old_root` at the same file under
+/// `new_root` (an index brought over from another PC). Returns how many rows
+/// were rewritten.
+pub async fn remap_root(db_path: &Path, old_root: &Path, new_root: &Path) -> Result<usize> {
+    let db = IndexerState::open_or_create_db(db_path).await?;
+    let table_names = db.table_names().execute().await?;
+    if !table_names.contains(&"images".to_string()) {
+        return Ok(0);
+    }
+    let table = db.open_table("images").execute().await?;
+
+    let old_prefix = root_prefix(old_root);
+    let new_prefix = root_prefix(new_root);
+    if old_prefix == new_prefix {
+        return Ok(0);
+    }
+    // substr counts characters from 1
+    let prefix_len = old_prefix.chars().count();
+    let filter = format!(
+        "substr(file_path, 1, {}) = {}",
+        prefix_len,
+        sql_string_literal(&old_prefix)
+    );
+    let matching = table.count_rows(Some(filter.clone())).await?;
+    if matching == 0 {
+        return Ok(0);
+    }
     table
-        .delete(&format!("path = '{}'", path_str))
+        .update()
+        .only_if(filter)
+        .column(
+            "file_path",
+            format!(
+                "concat({}, substr(file_path, {}))",
+                sql_string_literal(&new_prefix),
+                prefix_len + 1
+            ),
+        )
+        .execute()
         .await?;
+    invalidate_health();
+    Ok(matching)
+}
 
-    info!("Deleted index entry for: {:?}", path);
-    Ok(())
+/// A folder as stored paths start with it, separator included
+fn root_prefix(root: &Path) -> String {
+    let mut prefix = paths::display_path(root).to_string_lossy().to_string();
+    if !prefix.ends_with(['/', '\\']) {
+        prefix.push(std::path::MAIN_SEPARATOR);
+    }
+    prefix
+}
+
+/// Quote a string for use in a LanceDB filter expression
+fn sql_string_literal(value: &str) -> String {
+    format!("'{}'", value.replace('\'', "''"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::scratch_dir;
+
+    #[test]
+    fn test_sql_string_literal_escapes_quotes() {
+        assert_eq!(sql_string_literal(r"C:\Shots\a.png"), r"'C:\Shots\a.png'");
+        assert_eq!(sql_string_literal("it's.png"), "'it''s.png'");
+        assert_eq!(sql_string_literal("'; DROP"), "'''; DROP'");
+    }
+
+    #[test]
+    fn test_remove_and_prune() {
+        let dir = scratch_dir("remove");
+        let db_path = dir.join("vector_index.db");
+        // Quotes and backslashes (as in Windows paths) have to survive the filter
+        let removed = dir.join(r"it's C:\Shots.png");
+        let kept = dir.join("kept.png");
+        let gone = dir.join("gone.png");
+        for path in [&removed, &kept] {
+            fs::write(path, b"png").unwrap();
+        }
+        let config = IndexConfig {
+            db_path: db_path.clone(),
+            cpu_mode: CpuMode::Normal,
+            screenshot_dir: dir.clone(),
+        };
+
+        let rt = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap();
+        rt.block_on(async {
+            let vector = vec![1.0; 768];
+            write_rows(
+                &db_path,
+                &[
+                    (removed.clone(), vector.clone()),
+                    (kept.clone(), vector.clone()),
+                    (gone.clone(), vector),
+                ],
+            )
+            .await
+            .unwrap();
+
+            assert_eq!(remove_from_index_impl(removed.clone(), config.clone()).await.unwrap(), 1);
+            assert_eq!(remove_from_index_impl(removed.clone(), config.clone()).await.unwrap(), 0);
+
+            // Only the row for the file that's gone from disk
+            assert_eq!(prune_missing_impl(&db_path).await.unwrap(), 1);
+            assert_eq!(prune_missing_impl(&db_path).await.unwrap(), 0);
+
+            let db = IndexerState::open_or_create_db(&db_path).await.unwrap();
+            let table = db.open_table("images").execute().await.unwrap();
+            assert_eq!(table.count_rows(None).await.unwrap(), 1);
+            let kept_filter = format!("file_path = {}", sql_string_literal(&kept.to_string_lossy()));
+            assert_eq!(table.count_rows(Some(kept_filter)).await.unwrap(), 1);
+        });
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_rename_converted() {
+        let dir = scratch_dir("rename");
+        let db_path = dir.join("vector_index.db");
+        let png = dir.join("it's a shot.png");
+        let webp = dir.join("it's a shot.webp");
+        fs::write(&webp, b"webp").unwrap();
+
+        let rt = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap();
+        rt.block_on(async {
+            write_rows(&db_path, &[(png.clone(), vec![1.0; 768])]).await.unwrap();
+            let moves = [
+                (png.clone(), webp.clone()),
+                // Converted, but never indexed
+                (dir.join("unindexed.png"), dir.join("unindexed.webp")),
+            ];
+            assert_eq!(rename_in_index_impl(&db_path, &moves).await.unwrap(), 1);
+
+            let db = IndexerState::open_or_create_db(&db_path).await.unwrap();
+            let table = db.open_table("images").execute().await.unwrap();
+            assert_eq!(table.count_rows(None).await.unwrap(), 1);
+            let mtime = mtime_secs(&fs::metadata(&webp).unwrap());
+            assert_eq!(stored_mtime(&table, &webp).await.unwrap(), Some(mtime));
+            assert_eq!(stored_mtime(&table, &png).await.unwrap(), None);
+        });
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_search_stats_without_vector_index() {
+        let dir = scratch_dir("search-stats");
+        let db_path = dir.join("vector_index.db");
+        let shot = dir.join("a.png");
+        fs::write(&shot, b"png").unwrap();
+
+        let rt = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap();
+        let (candidates, stats) = rt.block_on(async {
+            write_rows(
+                &db_path,
+                &[(shot.clone(), vec![1.0; 768]), (dir.join("gone.png"), vec![1.0; 768])],
+            )
+            .await
+            .unwrap();
+            search_by_vector_with_stats(&db_path, &[1.0; 768], 10).await.unwrap()
+        });
+
+        assert_eq!(candidates.len(), 1);
+        assert_eq!(candidates[0].0, shot);
+        assert!(candidates[0].1.abs() < 0.001);
+        // A fresh table has no ANN index, so the query is a flat scan
+        assert!(!stats.ann);
+        assert_eq!(stats.rows, 2);
+        assert_eq!(stats.embed, Duration::ZERO);
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_rank_matches() {
+        let path = |name: &str| PathBuf::from(name);
+        // Two batches, each ranked on its own, with a row listed twice
+        let candidates = vec![
+            (path("close.png"), 0.70),
+            (path("weak.png"), 0.97),
+            (path("best.png"), 0.62),
+            (path("close.png"), 0.70),
+            (path("opposite.png"), 1.2),
+        ];
+
+        let ranked = rank_matches(candidates.clone(), 0.05);
+        let names: Vec<&str> = ranked.iter().map(|(p, _)| p.to_str().unwrap()).collect();
+        assert_eq!(names, vec!["best.png", "close.png"]);
+        assert_eq!(ranked[0].1, 0.62);
+
+        // A cutoff of 0 keeps everything that isn't dissimilar, -1 everything
+        assert_eq!(rank_matches(candidates.clone(), 0.0).len(), 3);
+        assert_eq!(rank_matches(candidates.clone(), -1.0).len(), 4);
+        assert!(rank_matches(candidates, 0.5).is_empty());
+    }
+
+    #[test]
+    fn test_should_index_changed_files() {
+        let dir = scratch_dir("should-index");
+        let shot = dir.join("shot.png");
+        fs::write(&shot, b"png").unwrap();
+        let mtime = mtime_secs(&fs::metadata(&shot).unwrap());
+
+        let new = HashMap::new();
+        let current = HashMap::from([(shot.clone(), mtime)]);
+        // Converted or edited after it was indexed
+        let stale = HashMap::from([(shot.clone(), mtime - 60)]);
+        for scope in [IndexScope::New, IndexScope::Changed, IndexScope::All] {
+            assert!(IndexerState::should_index(scope, &shot, &new));
+        }
+        assert!(!IndexerState::should_index(IndexScope::New, &shot, &stale));
+        assert!(IndexerState::should_index(IndexScope::Changed, &shot, &stale));
+        assert!(!IndexerState::should_index(IndexScope::Changed, &shot, &current));
+        assert!(IndexerState::should_index(IndexScope::All, &shot, &current));
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_stored_mtime() {
+        let dir = scratch_dir("stored-mtime");
+        let db_path = dir.join("vector_index.db");
+        let shot = dir.join("shot.png");
+        fs::write(&shot, b"png").unwrap();
+        let mtime = mtime_secs(&fs::metadata(&shot).unwrap());
+
+        let rt = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap();
+        rt.block_on(async {
+            let db = IndexerState::open_or_create_db(&db_path).await.unwrap();
+            append_embeddings(&db, std::slice::from_ref(&shot), vec![vec![1.0; 768]])
+                .await
+                .unwrap();
+            let table = db.open_table("images").execute().await.unwrap();
+
+            // What a new screenshot that's already indexed is skipped by
+            assert_eq!(stored_mtime(&table, &shot).await.unwrap(), Some(mtime));
+
+            // Indexing it again replaces its row
+            let replaced = append_embeddings(&db, std::slice::from_ref(&shot), vec![vec![0.5; 768]])
+                .await
+                .unwrap();
+            assert_eq!(replaced, 1);
+            assert_eq!(table.count_rows(None).await.unwrap(), 1);
+            assert_eq!(stored_mtime(&table, &dir.join("new.png")).await.unwrap(), None);
+        });
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_find_similar_scored() {
+        let dir = scratch_dir("similar");
+        let db_path = dir.join("vector_index.db");
+        let shot = |name: &str| {
+            let path = dir.join(name);
+            fs::write(&path, b"png").unwrap();
+            path
+        };
+        let vector = |x: f32, y: f32| {
+            let mut v = vec![0.0; 768];
+            v[0] = x;
+            v[1] = y;
+            v
+        };
+        let source = shot("source.png");
+        let burst = shot("burst.png");
+        let related = shot("related.png");
+        let other = shot("other.png");
+
+        let rt = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap();
+        let (matches, unindexed) = rt.block_on(async {
+            write_rows(
+                &db_path,
+                &[
+                    (source.clone(), vector(1.0, 0.0)),
+                    (burst.clone(), vector(1.0, 0.1)),
+                    (dir.join("gone.png"), vector(1.0, 0.0)),
+                    (related.clone(), vector(1.0, 1.0)),
+                    (other.clone(), vector(0.0, 1.0)),
+                ],
+            )
+            .await
+            .unwrap();
+            (
+                find_similar_scored(&db_path, &source, 0.9, 10).await.unwrap(),
+                find_similar_scored(&db_path, &dir.join("new.png"), 0.9, 10).await.unwrap(),
+            )
+        });
+
+        // Only the near-duplicate: not the source, the deleted file, or the merely related one
+        let matches = matches.unwrap();
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].0, burst);
+        assert!((matches[0].1 - 0.995).abs() < 0.001);
+        assert_eq!(unindexed, None);
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_quarantine_keeps_the_old_db() {
+        let dir = scratch_dir("quarantine");
+        let db_path = dir.join("vector_index.db");
+        fs::create_dir_all(db_path.join("images.lance")).unwrap();
+        fs::write(db_path.join("images.lance").join("data"), b"rows").unwrap();
+
+        let backup = quarantine(&db_path).unwrap();
+        assert!(!db_path.exists());
+        assert!(backup.file_name().unwrap().to_string_lossy().starts_with("vector_index.db.corrupt-"));
+        assert_eq!(fs::read(backup.join("images.lance").join("data")).unwrap(), b"rows");
+
+        let _ = fs::remove_dir_all(&dir);
+    }
 }
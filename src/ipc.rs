@@ -0,0 +1,336 @@
+//! Commands from a second launch to the running instance
+//!
+//! The single-instance check only tells a second launch that another copy is
+//! running. To hand it work (`sukusho search-ui "blue dashboard"` from a
+//! launcher), the running instance listens on a named pipe scoped to the
+//! current user, and the second launch writes one JSON message and exits:
+//!
+//! ```json
+//! {"version": 1, "command": "search", "query": "blue dashboard"}
+//! ```
+//!
+//! Messages carry a schema version; anything malformed or from another
+//! version is logged and dropped.
+
+use anyhow::Result;
+use log::warn;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// Version of the message schema written by [`send`]
+pub const VERSION: u64 = 1;
+
+/// Largest message read from the pipe
+#[cfg(windows)]
+const MAX_MESSAGE_BYTES: u64 = 64 * 1024;
+
+/// Something the running instance is asked to do
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "command", rename_all = "snake_case")]
+pub enum Command {
+    /// Show the window and search, as if the query was typed and Enter pressed
+    Search { query: String },
+}
+
+#[derive(Serialize, Deserialize)]
+struct Envelope {
+    version: u64,
+    #[serde(flatten)]
+    command: Command,
+}
+
+/// Flags the app itself reads, which may come anywhere on the command line
+const LAUNCH_FLAGS: &[&str] = &["--console", "-c", "--perf-trace"];
+
+/// Command line form of a command, if the arguments ask for one. Words after
+/// `search-ui` make the query, less the app's own flags; after `--` every
+/// word is part of it.
+pub fn from_args(args: &[String]) -> Option<Command> {
+    let position = args.iter().position(|arg| arg == "search-ui")?;
+    let mut words = Vec::new();
+    let mut literal = false;
+    for arg in &args[position + 1..] {
+        if literal {
+            words.push(arg.as_str());
+        } else if arg == "--" {
+            literal = true;
+        } else if !LAUNCH_FLAGS.contains(&arg.as_str()) {
+            words.push(arg.as_str());
+        }
+    }
+    let query = words.join(" ");
+    let query = query.trim();
+    (!query.is_empty()).then(|| Command::Search {
+        query: query.to_string(),
+    })
+}
+
+pub fn encode(command: &Command) -> String {
+    let envelope = Envelope {
+        version: VERSION,
+        command: command.clone(),
+    };
+    serde_json::to_string(&envelope).unwrap_or_default()
+}
+
+/// Read a message; malformed ones and other versions are logged and dropped
+pub fn decode(payload: &str) -> Option<Command> {
+    let value: Value = match serde_json::from_str(payload) {
+        Ok(value) => value,
+        Err(e) => {
+            warn!("Ignoring malformed IPC message: {}", e);
+            return None;
+        }
+    };
+    match value.get("version").and_then(Value::as_u64) {
+        Some(VERSION) => {}
+        other => {
+            warn!("Ignoring IPC message with unsupported version {:?}", other);
+            return None;
+        }
+    }
+    match serde_json::from_value::<Envelope>(value) {
+        Ok(envelope) => Some(envelope.command),
+        Err(e) => {
+            warn!("Ignoring IPC message with an unknown command: {}", e);
+            None
+        }
+    }
+}
+
+/// Pipe name, per user so another session's instance never gets our messages
+#[cfg(windows)]
+fn pipe_name() -> String {
+    let user = std::env::var("USERNAME").unwrap_or_default();
+    format!(r"\\.\pipe\sukusho-{}", user)
+}
+
+/// Hand a command to the running instance
+#[cfg(windows)]
+pub fn send(command: &Command) -> Result<()> {
+    use std::io::Write;
+
+    let mut pipe = std::fs::OpenOptions::new().write(true).open(pipe_name())?;
+    pipe.write_all(encode(command).as_bytes())?;
+    Ok(())
+}
+
+#[cfg(not(windows))]
+pub fn send(_command: &Command) -> Result<()> {
+    anyhow::bail!("IPC is only available on Windows")
+}
+
+/// String form (`S-1-5-21-...`) of the user this process runs as
+#[cfg(windows)]
+fn current_user_sid() -> Result<String> {
+    use windows::core::PWSTR;
+    use windows::Win32::Foundation::{CloseHandle, HANDLE, HLOCAL, LocalFree};
+    use windows::Win32::Security::Authorization::ConvertSidToStringSidW;
+    use windows::Win32::Security::{GetTokenInformation, TokenUser, TOKEN_QUERY, TOKEN_USER};
+    use windows::Win32::System::Threading::{GetCurrentProcess, OpenProcessToken};
+
+    // SAFETY: The token is closed once read. TOKEN_USER is read from a
+    // buffer of the size the first call asked for, aligned for its pointer,
+    // and the SID string is freed after copying.
+    unsafe {
+        let mut token = HANDLE::default();
+        OpenProcessToken(GetCurrentProcess(), TOKEN_QUERY, &mut token)?;
+        let mut len = 0;
+        // Fails, reporting the size needed
+        let _ = GetTokenInformation(token, TokenUser, None, 0, &mut len);
+        let mut buffer = vec![0u64; (len as usize).div_ceil(8)];
+        let read = GetTokenInformation(token, TokenUser, Some(buffer.as_mut_ptr().cast()), len, &mut len);
+        let _ = CloseHandle(token);
+        read?;
+
+        let user = &*(buffer.as_ptr() as *const TOKEN_USER);
+        let mut string = PWSTR::null();
+        ConvertSidToStringSidW(user.User.Sid, &mut string)?;
+        let sid = string.to_string();
+        LocalFree(HLOCAL(string.0.cast()));
+        Ok(sid?)
+    }
+}
+
+/// Security descriptor letting only the current user open the pipe or
+/// create instances of it. Freed when dropped.
+#[cfg(windows)]
+struct OwnerOnly(windows::Win32::Security::PSECURITY_DESCRIPTOR);
+
+#[cfg(windows)]
+impl OwnerOnly {
+    fn new() -> Result<Self> {
+        use windows::core::HSTRING;
+        use windows::Win32::Security::Authorization::{
+            ConvertStringSecurityDescriptorToSecurityDescriptorW, SDDL_REVISION_1,
+        };
+        use windows::Win32::Security::PSECURITY_DESCRIPTOR;
+
+        // Protected DACL with a single entry: full access for us
+        let sddl = HSTRING::from(format!("D:P(A;;GA;;;{})", current_user_sid()?));
+        let mut descriptor = PSECURITY_DESCRIPTOR::default();
+        // SAFETY: `sddl` outlives the call; the descriptor is freed in Drop
+        unsafe {
+            ConvertStringSecurityDescriptorToSecurityDescriptorW(&sddl, SDDL_REVISION_1, &mut descriptor, None)?
+        };
+        Ok(Self(descriptor))
+    }
+}
+
+#[cfg(windows)]
+impl Drop for OwnerOnly {
+    fn drop(&mut self) {
+        use windows::Win32::Foundation::{HLOCAL, LocalFree};
+
+        // SAFETY: Allocated with LocalAlloc by the SDDL conversion, freed once
+        unsafe { LocalFree(HLOCAL(self.0 .0)) };
+    }
+}
+
+/// Accept commands from later launches on a background thread, forwarding
+/// each one as [`crate::AppMessage::Ipc`]. Only the current user can reach
+/// the pipe, and an instance of it stays open the whole time so no other
+/// program can take over the name in between.
+#[cfg(windows)]
+pub fn listen(message_tx: crossbeam_channel::Sender<crate::AppMessage>) {
+    use log::{error, info};
+    use std::io::Read;
+    use std::os::windows::io::FromRawHandle;
+    use windows::core::HSTRING;
+    use windows::Win32::Foundation::{ERROR_PIPE_CONNECTED, HANDLE};
+    use windows::Win32::Security::SECURITY_ATTRIBUTES;
+    use windows::Win32::Storage::FileSystem::{FILE_FLAG_FIRST_PIPE_INSTANCE, PIPE_ACCESS_INBOUND};
+    use windows::Win32::System::Pipes::{
+        ConnectNamedPipe, CreateNamedPipeW, PIPE_READMODE_BYTE, PIPE_REJECT_REMOTE_CLIENTS,
+        PIPE_TYPE_BYTE, PIPE_UNLIMITED_INSTANCES, PIPE_WAIT,
+    };
+
+    let name = HSTRING::from(pipe_name());
+    std::thread::spawn(move || {
+        let security = match OwnerOnly::new() {
+            Ok(security) => security,
+            Err(e) => {
+                error!("Not listening for IPC, the pipe can't be restricted to this user: {}", e);
+                return;
+            }
+        };
+        let create = |first: bool| -> HANDLE {
+            let attributes = SECURITY_ATTRIBUTES {
+                nLength: std::mem::size_of::<SECURITY_ATTRIBUTES>() as u32,
+                lpSecurityDescriptor: security.0 .0,
+                bInheritHandle: false.into(),
+            };
+            // Creating the first instance fails if someone else holds the name
+            let mut open_mode = PIPE_ACCESS_INBOUND;
+            if first {
+                open_mode |= FILE_FLAG_FIRST_PIPE_INSTANCE;
+            }
+            // SAFETY: `name` and `attributes` outlive the call
+            unsafe {
+                CreateNamedPipeW(
+                    &name,
+                    open_mode,
+                    PIPE_TYPE_BYTE | PIPE_READMODE_BYTE | PIPE_WAIT | PIPE_REJECT_REMOTE_CLIENTS,
+                    PIPE_UNLIMITED_INSTANCES,
+                    0,
+                    MAX_MESSAGE_BYTES as u32,
+                    0,
+                    Some(&attributes),
+                )
+            }
+        };
+
+        let mut pipe = create(true);
+        loop {
+            if pipe.is_invalid() {
+                error!("Failed to create IPC pipe: {}", std::io::Error::last_os_error());
+                return;
+            }
+
+            // A client that connected between create and connect is fine too
+            // SAFETY: `pipe` is a valid pipe instance we own
+            if let Err(e) = unsafe { ConnectNamedPipe(pipe, None) } {
+                if e.code() != ERROR_PIPE_CONNECTED.to_hresult() {
+                    warn!("IPC client failed to connect: {}", e);
+                }
+            }
+
+            // The next instance is open before this one closes
+            let next = create(false);
+
+            // Owns the handle from here; dropping it closes this instance
+            // SAFETY: Nothing else closes `pipe`
+            let file = unsafe { std::fs::File::from_raw_handle(pipe.0 as _) };
+            let mut payload = String::new();
+            match file.take(MAX_MESSAGE_BYTES).read_to_string(&mut payload) {
+                Ok(_) => {
+                    if let Some(command) = decode(&payload) {
+                        info!("IPC command: {:?}", command);
+                        let _ = message_tx.send(crate::AppMessage::Ipc(command));
+                    }
+                }
+                Err(e) => warn!("Failed to read IPC message: {}", e),
+            }
+            pipe = next;
+        }
+    });
+}
+
+#[cfg(not(windows))]
+pub fn listen(_message_tx: crossbeam_channel::Sender<crate::AppMessage>) {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip() {
+        let command = Command::Search {
+            query: "blue dashboard".to_string(),
+        };
+        let payload = encode(&command);
+        assert!(payload.contains("\"version\":1"));
+        assert_eq!(decode(&payload), Some(command));
+    }
+
+    #[test]
+    fn test_decode_drops_bad_messages() {
+        assert_eq!(decode("not json"), None);
+        assert_eq!(decode(r#"{"command":"search","query":"x"}"#), None);
+        assert_eq!(decode(r#"{"version":2,"command":"search","query":"x"}"#), None);
+        assert_eq!(decode(r#"{"version":1,"command":"delete_all"}"#), None);
+        assert_eq!(decode(r#"{"version":1,"command":"search"}"#), None);
+    }
+
+    #[test]
+    fn test_from_args() {
+        let args = |list: &[&str]| list.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+        assert_eq!(
+            from_args(&args(&["sukusho.exe", "search-ui", "blue dashboard"])),
+            Some(Command::Search {
+                query: "blue dashboard".to_string()
+            })
+        );
+        assert_eq!(
+            from_args(&args(&["sukusho.exe", "--console", "search-ui", "blue", "dashboard"])),
+            Some(Command::Search {
+                query: "blue dashboard".to_string()
+            })
+        );
+        // Only the app's own flags are left out
+        assert_eq!(
+            from_args(&args(&["sukusho.exe", "search-ui", "--force", "push", "--console"])),
+            Some(Command::Search {
+                query: "--force push".to_string()
+            })
+        );
+        assert_eq!(
+            from_args(&args(&["sukusho.exe", "search-ui", "--", "--console", "flag"])),
+            Some(Command::Search {
+                query: "--console flag".to_string()
+            })
+        );
+        assert_eq!(from_args(&args(&["sukusho.exe", "search-ui", "  "])), None);
+        assert_eq!(from_args(&args(&["sukusho.exe", "--console"])), None);
+    }
+}
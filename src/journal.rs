@@ -0,0 +1,269 @@
+//! Write-ahead journal for organizer batch moves
+//!
+//! Each planned move is appended (and synced) before the rename happens, and
+//! marked done afterwards. If the app dies mid-batch, the next startup finds
+//! the leftover journal, checks the disk to see which moves actually
+//! happened, finishes the rest where it safely can, and reports what moved so
+//! the gallery and index can catch up.
+
+use anyhow::Result;
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+
+use crate::paths;
+
+/// One journal line
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+enum Entry {
+    /// About to move `from` to `to`
+    Plan { from: PathBuf, to: PathBuf },
+    /// The move of `from` finished
+    Done { from: PathBuf },
+}
+
+/// Open journal for a running batch
+pub struct Journal {
+    path: PathBuf,
+    file: File,
+}
+
+impl Journal {
+    /// Start (or continue) a journal at `path`
+    pub fn open(path: &Path) -> Result<Self> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        Ok(Self {
+            path: path.to_path_buf(),
+            file,
+        })
+    }
+
+    fn append(&mut self, entry: &Entry) -> Result<()> {
+        let mut line = serde_json::to_string(entry)?;
+        line.push('\n');
+        self.file.write_all(line.as_bytes())?;
+        // The entry must be on disk before the rename it describes
+        self.file.sync_data()?;
+        Ok(())
+    }
+
+    /// Record a move before doing it
+    pub fn plan(&mut self, from: &Path, to: &Path) -> Result<()> {
+        self.append(&Entry::Plan {
+            from: from.to_path_buf(),
+            to: to.to_path_buf(),
+        })
+    }
+
+    /// Record that a planned move finished
+    pub fn done(&mut self, from: &Path) -> Result<()> {
+        self.append(&Entry::Done {
+            from: from.to_path_buf(),
+        })
+    }
+
+    /// Batch finished cleanly; drop the journal
+    pub fn finish(self) {
+        drop(self.file);
+        if let Err(e) = fs::remove_file(&self.path) {
+            warn!("Failed to remove organizer journal {:?}: {}", self.path, e);
+        }
+    }
+}
+
+/// Where the organizer journal lives (next to settings.json)
+pub fn default_path() -> Option<PathBuf> {
+    crate::settings::Settings::config_path()
+        .and_then(|p| p.parent().map(|d| d.join("organize_journal.jsonl")))
+}
+
+/// What happened to a move left unfinished by a crash
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Recovered {
+    /// The rename had happened; only the "done" mark was missing
+    AlreadyMoved { from: PathBuf, to: PathBuf },
+    /// The rename hadn't happened; it was done now
+    Finished { from: PathBuf, to: PathBuf },
+    /// Couldn't be resolved (both or neither path exist, or the rename failed)
+    Unresolved { from: PathBuf, to: PathBuf },
+}
+
+/// Planned moves without a matching "done", in journal order.
+/// A torn last line (crash while writing) is ignored.
+fn pending_moves(path: &Path) -> Result<Vec<(PathBuf, PathBuf)>> {
+    let reader = BufReader::new(File::open(path)?);
+    let mut planned = Vec::new();
+    let mut done = HashSet::new();
+
+    for line in reader.lines() {
+        let line = line?;
+        match serde_json::from_str::<Entry>(&line) {
+            Ok(Entry::Plan { from, to }) => planned.push((from, to)),
+            Ok(Entry::Done { from }) => {
+                done.insert(from);
+            }
+            Err(e) => warn!("Skipping unreadable journal line: {}", e),
+        }
+    }
+
+    Ok(planned
+        .into_iter()
+        .filter(|(from, _)| !done.contains(from))
+        .collect())
+}
+
+/// Settle one unfinished move by looking at the disk
+fn recover_move(from: PathBuf, to: PathBuf) -> Recovered {
+    let from_exists = paths::long_path(&from).exists();
+    let to_exists = paths::long_path(&to).exists();
+
+    match (from_exists, to_exists) {
+        (false, true) => Recovered::AlreadyMoved { from, to },
+        (true, false) => {
+            let result = to
+                .parent()
+                .map_or(Ok(()), |dir| fs::create_dir_all(paths::long_path(dir)))
+                .and_then(|_| fs::rename(paths::long_path(&from), paths::long_path(&to)));
+            match result {
+                Ok(()) => Recovered::Finished { from, to },
+                Err(e) => {
+                    warn!("Failed to finish journaled move {:?} -> {:?}: {}", from, to, e);
+                    Recovered::Unresolved { from, to }
+                }
+            }
+        }
+        _ => {
+            warn!("Journaled move {:?} -> {:?} can't be resolved", from, to);
+            Recovered::Unresolved { from, to }
+        }
+    }
+}
+
+/// Finish or report moves left by an interrupted batch, then delete the journal.
+/// Returns an empty list when there's no journal.
+pub fn recover(path: &Path) -> Result<Vec<Recovered>> {
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+
+    let pending = pending_moves(path)?;
+    info!("Recovering {} unfinished organizer move(s)", pending.len());
+    let outcomes = pending
+        .into_iter()
+        .map(|(from, to)| recover_move(from, to))
+        .collect();
+
+    fs::remove_file(path)?;
+    Ok(outcomes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::scratch_dir;
+
+    #[test]
+    fn test_clean_batch_leaves_nothing() {
+        let dir = scratch_dir("journal-clean");
+        let journal_path = dir.join("journal.jsonl");
+        let from = dir.join("a.png");
+        let to = dir.join("2024-01").join("a.png");
+        fs::write(&from, b"a").unwrap();
+
+        let mut journal = Journal::open(&journal_path).unwrap();
+        journal.plan(&from, &to).unwrap();
+        fs::create_dir_all(to.parent().unwrap()).unwrap();
+        fs::rename(&from, &to).unwrap();
+        journal.done(&from).unwrap();
+        journal.finish();
+
+        assert!(!journal_path.exists());
+        assert_eq!(recover(&journal_path).unwrap(), Vec::new());
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_recover_after_crash_mid_batch() {
+        let dir = scratch_dir("journal-crash");
+        let journal_path = dir.join("journal.jsonl");
+        let target = dir.join("2024-01");
+        fs::create_dir_all(&target).unwrap();
+
+        let names = ["done.png", "renamed.png", "planned.png"];
+        for name in names {
+            fs::write(dir.join(name), name.as_bytes()).unwrap();
+        }
+
+        {
+            let mut journal = Journal::open(&journal_path).unwrap();
+
+            // Fully finished move
+            journal.plan(&dir.join("done.png"), &target.join("done.png")).unwrap();
+            fs::rename(dir.join("done.png"), target.join("done.png")).unwrap();
+            journal.done(&dir.join("done.png")).unwrap();
+
+            // Renamed, then crashed before marking done
+            journal.plan(&dir.join("renamed.png"), &target.join("renamed.png")).unwrap();
+            fs::rename(dir.join("renamed.png"), target.join("renamed.png")).unwrap();
+
+            // Planned, crashed before the rename
+            journal.plan(&dir.join("planned.png"), &target.join("planned.png")).unwrap();
+            // Journal dropped without finish() = crash
+        }
+
+        // Torn final line from a crash during the write
+        let mut file = OpenOptions::new().append(true).open(&journal_path).unwrap();
+        file.write_all(b"{\"op\":\"pl").unwrap();
+        drop(file);
+
+        let outcomes = recover(&journal_path).unwrap();
+        assert_eq!(
+            outcomes,
+            vec![
+                Recovered::AlreadyMoved {
+                    from: dir.join("renamed.png"),
+                    to: target.join("renamed.png"),
+                },
+                Recovered::Finished {
+                    from: dir.join("planned.png"),
+                    to: target.join("planned.png"),
+                },
+            ]
+        );
+        assert!(target.join("planned.png").exists());
+        assert!(!dir.join("planned.png").exists());
+        assert!(!journal_path.exists());
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_unresolved_when_both_exist() {
+        let dir = scratch_dir("journal-conflict");
+        let journal_path = dir.join("journal.jsonl");
+        let from = dir.join("a.png");
+        let to = dir.join("b.png");
+        fs::write(&from, b"a").unwrap();
+        fs::write(&to, b"b").unwrap();
+
+        let mut journal = Journal::open(&journal_path).unwrap();
+        journal.plan(&from, &to).unwrap();
+        drop(journal);
+
+        assert_eq!(
+            recover(&journal_path).unwrap(),
+            vec![Recovered::Unresolved { from: from.clone(), to: to.clone() }]
+        );
+        assert!(from.exists() && to.exists());
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,178 @@
+//! PNGs kept because converting them didn't make them smaller
+//!
+//! Without a record of these, every startup scan and every "Convert now"
+//! would decode and encode the same PNGs again only to throw the result
+//! away. Each one is remembered with its size, modified time and the
+//! options it was converted with, in a sidecar file next to settings.json;
+//! a changed file or different conversion settings give it another try.
+
+use log::debug;
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+use crate::convert::{ConvertOptions, NotSmaller};
+use crate::paths;
+use crate::sidecar;
+
+/// A PNG as it was when converting it didn't pay off
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+struct KeptPng {
+    size: u64,
+    /// Modified time (seconds since UNIX epoch)
+    modified: u64,
+    options: ConvertOptions,
+    /// Size the converted file came out at
+    output_size: u64,
+}
+
+/// Size and modified time of a file, which tell whether it changed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FileStamp {
+    pub size: u64,
+    pub modified: u64,
+}
+
+impl FileStamp {
+    pub fn read(path: &Path) -> Option<Self> {
+        let metadata = fs::metadata(paths::long_path(path)).ok()?;
+        let modified = metadata.modified().ok()?.duration_since(UNIX_EPOCH).ok()?.as_secs();
+        Some(Self {
+            size: metadata.len(),
+            modified,
+        })
+    }
+}
+
+/// Path -> the PNG kept there
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct KeptPngs {
+    entries: HashMap<PathBuf, KeptPng>,
+}
+
+impl KeptPngs {
+    /// Remember that converting `path` with `options` came out at `output_size`
+    pub fn record(&mut self, path: &Path, stamp: FileStamp, options: ConvertOptions, output_size: u64) {
+        self.entries.insert(
+            path.to_path_buf(),
+            KeptPng {
+                size: stamp.size,
+                modified: stamp.modified,
+                options,
+                output_size,
+            },
+        );
+    }
+
+    /// How converting `path` went last time, if the file and the options
+    /// are still the same
+    pub fn lookup(&self, path: &Path, stamp: FileStamp, options: ConvertOptions) -> Option<NotSmaller> {
+        self.entries
+            .get(path)
+            .filter(|kept| kept.size == stamp.size && kept.modified == stamp.modified && kept.options == options)
+            .map(|kept| NotSmaller {
+                original_size: kept.size,
+                output_size: kept.output_size,
+            })
+    }
+
+    /// Forget files that are gone or were changed since
+    fn prune(&mut self) {
+        self.entries.retain(|path, kept| {
+            FileStamp::read(path).is_some_and(|stamp| stamp.size == kept.size && stamp.modified == kept.modified)
+        });
+    }
+}
+
+/// Kept PNGs, loaded lazily
+static KEPT: Mutex<Option<KeptPngs>> = Mutex::new(None);
+
+/// Sidecar file holding [`KEPT`]
+const STORE: &str = "kept_pngs.json";
+
+/// Tests never touch the real sidecar
+fn load() -> KeptPngs {
+    if cfg!(test) {
+        KeptPngs::default()
+    } else {
+        sidecar::load(STORE)
+    }
+}
+
+fn save(kept: &KeptPngs) {
+    if !cfg!(test) {
+        sidecar::save(STORE, kept);
+    }
+}
+
+/// Remember a PNG that converting with `options` didn't shrink
+pub fn record(path: &Path, options: ConvertOptions, output_size: u64) {
+    let Some(stamp) = FileStamp::read(path) else {
+        return;
+    };
+    let mut guard = KEPT.lock();
+    let kept = guard.get_or_insert_with(load);
+    kept.prune();
+    kept.record(path, stamp, options, output_size);
+    save(kept);
+    debug!("Remembered {:?} as not worth converting", path);
+}
+
+/// How converting `path` with `options` went before, when it didn't shrink
+/// it and the file hasn't changed since
+pub fn lookup(path: &Path, options: ConvertOptions) -> Option<NotSmaller> {
+    let stamp = FileStamp::read(path)?;
+    let mut guard = KEPT.lock();
+    guard.get_or_insert_with(load).lookup(path, stamp, options)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::settings::ConversionFormat;
+
+    fn webp(quality: u32) -> ConvertOptions {
+        ConvertOptions {
+            format: ConversionFormat::WebP,
+            quality,
+            lossless_webp: false,
+            max_dimension: 0,
+        }
+    }
+
+    #[test]
+    fn test_lookup_matches_file_and_options() {
+        let path = Path::new("shot.png");
+        let stamp = FileStamp {
+            size: 1000,
+            modified: 1_700_000_000,
+        };
+        let mut kept = KeptPngs::default();
+        kept.record(path, stamp, webp(80), 1200);
+
+        assert_eq!(
+            kept.lookup(path, stamp, webp(80)),
+            Some(NotSmaller {
+                original_size: 1000,
+                output_size: 1200
+            })
+        );
+        // Other settings, a changed file or another file: try again
+        assert_eq!(kept.lookup(path, stamp, webp(70)), None);
+        let lossless = ConvertOptions {
+            lossless_webp: true,
+            ..webp(80)
+        };
+        assert_eq!(kept.lookup(path, stamp, lossless), None);
+        let edited = FileStamp {
+            modified: stamp.modified + 5,
+            ..stamp
+        };
+        assert_eq!(kept.lookup(path, edited, webp(80)), None);
+        assert_eq!(kept.lookup(Path::new("other.png"), stamp, webp(80)), None);
+    }
+}
@@ -9,18 +9,55 @@ extern crate rust_i18n;
 i18n!("locales", fallback = "en");
 
 mod app;
+mod auto_index;
+mod cli;
 mod clipboard;
+mod content_date;
 mod convert;
+mod convert_pool;
+mod diagnostics;
+mod directory_check;
 mod drag_drop;
+mod editor;
+mod exclude;
+mod feedback;
+mod file_ops;
+mod filename_date;
+mod folder_snapshot;
 mod hotkey;
 mod i18n_helpers;
+mod index_schedule;
+mod index_session;
+mod index_transfer;
 mod indexer;
+mod ipc;
+mod journal;
+mod kept_pngs;
+mod metadata_strip;
+mod motion;
+mod naming;
+mod number_format;
 mod organizer;
+mod paths;
+mod perf;
+mod pipeline;
+mod recycle;
+mod removals;
+mod rolling_log;
+mod screenshot_list;
+mod server;
 mod settings;
+mod shell_recovery;
+mod shortcuts;
+mod sidecar;
+#[cfg(test)]
+mod test_support;
 mod thumbnail;
+mod timeline;
 mod tray;
 mod ui;
 mod update_checker;
+mod usage;
 mod watcher;
 
 use anyhow::Result;
@@ -33,10 +70,11 @@ use std::path::PathBuf;
 use std::sync::Arc;
 
 use crate::app::Sukusho;
-use crate::hotkey::init_global_hotkey;
+use crate::hotkey::{init_global_hotkey, register_action_hotkey, HotkeyAction};
+use crate::journal::Recovered;
 use crate::settings::Settings;
 use crate::tray::TrayManager;
-use crate::watcher::ScreenshotWatcher;
+use crate::watcher::{ScreenshotWatcher, WatcherCommand};
 
 /// Allocate a console window for debugging output (Windows only)
 #[cfg(windows)]
@@ -62,30 +100,73 @@ fn attach_console() {
 pub enum AppMessage {
     /// New screenshot detected (with auto_index flag)
     NewScreenshot(PathBuf, bool),
+    /// Screenshots found by a folder scan, a batch at a time (not auto-indexed)
+    ScreenshotsScanned(Vec<app::ScreenshotInfo>),
     /// Screenshot removed
     ScreenshotRemoved(PathBuf),
+    /// Screenshot renamed or moved within the watched folder (old path, new path)
+    ScreenshotRenamed(PathBuf, PathBuf),
+    /// Files deleted from the gallery with the Delete key (deleted, how many
+    /// failed, whether they skipped the Recycle Bin)
+    FilesDeleted(Vec<PathBuf>, usize, bool),
+    /// Read-only files made writable from the prompt and retried (how many still failed)
+    BlockedRetried(usize),
+    /// A screenshot moved through the convert/organize pipeline (refreshes the tray's Recent list)
+    PipelineTransition(pipeline::Transition),
     /// Toggle window visibility (from tray click)
     ToggleWindow,
     /// Show main window (not settings) from tray icon click
     ShowMainWindow,
     /// Open settings
     OpenSettings,
-    /// Change screenshot directory
+    /// Processing of new files paused or resumed from the tray menu
+    ProcessingPaused(bool),
+    /// Change screenshot directory (checked first, see `directory_check`)
     ChangeDirectory(PathBuf),
+    /// A new screenshot directory was checked; switch unless there's a concern
+    DirectoryChecked(PathBuf, Option<directory_check::Concern>),
+    /// The first scan stopped after this many images; there are more
+    ScanCapped(usize),
+    /// Editor executable picked in the settings file dialog
+    EditorPicked(PathBuf),
+    /// Directory dropped onto the settings window (path, images found inside)
+    DirectoryDropped(PathBuf, usize),
     /// Request latest screenshot path (for tray drag)
     RequestLatestScreenshot,
     /// Organization started with total file count
     OrganizeStarted(usize),
     /// Organization progress update (current, total, current_file)
-    OrganizeProgress(usize, usize, String),
+    OrganizeProgress(usize, usize, PathBuf),
+    /// A file couldn't be organized (path, error)
+    OrganizeFileFailed(PathBuf, String),
     /// Organization completed
     OrganizeCompleted,
+    /// Organizing the selected files finished or was canceled
+    SelectionOrganized(organizer::OrganizeSummary),
     /// Conversion started with total file count
     ConvertStarted(usize),
     /// Conversion progress update (current, total, current_file)
-    ConvertProgress(usize, usize, String),
-    /// Conversion completed
-    ConvertCompleted,
+    ConvertProgress(usize, usize, PathBuf),
+    /// A file couldn't be converted (path, error)
+    ConvertFileFailed(PathBuf, String),
+    /// Conversion completed or canceled
+    ConvertCompleted(convert::ConvertSummary),
+    /// Metadata stripping started with total file count
+    StripStarted(usize),
+    /// Metadata stripping progress update (current, total, current_file)
+    StripProgress(usize, usize, PathBuf),
+    /// Metadata stripping completed, with every file's outcome
+    StripCompleted(metadata_strip::StripReport),
+    /// Sample conversion of the library's PNGs finished
+    SavingsEstimated(convert::SavingsEstimate),
+    /// Content dates read for "Fix Timestamp", waiting for the preview dialog
+    TimestampPlan(content_date::Plan),
+    /// A screenshot's modified time was set back to its content date
+    ScreenshotRedated(PathBuf),
+    /// "Copy Info" summaries read off the UI thread, one line per file
+    InfoSummaries(Vec<String>),
+    /// Timestamp fix finished (fixed, failed)
+    TimestampsFixed(usize, usize),
     /// Model download progress (current, total, model_name)
     ModelDownloadProgress(usize, usize, String),
     /// Model download completed
@@ -94,33 +175,69 @@ pub enum AppMessage {
     ModelDownloadFailed(String),
     /// Indexing started with total file count
     IndexStarted(usize),
+    /// Interrupted indexing run picked up again (already done, total)
+    IndexResumed(usize, usize),
     /// Indexing progress update (current, total, current_file)
-    IndexProgress(usize, usize, String),
-    /// Indexing completed (total_indexed_count)
-    IndexCompleted(usize),
+    IndexProgress(usize, usize, PathBuf),
+    /// A file couldn't be indexed (path, error)
+    IndexFileFailed(PathBuf, String),
+    /// Indexing completed (newly indexed, changed files re-indexed)
+    IndexCompleted(usize, usize),
+    /// New screenshots added to the index on their own, without a full run (newly indexed)
+    FilesIndexed(usize),
     /// Indexing failed
     IndexFailed(String),
+    /// Folder picked to export the index into
+    IndexExportPicked(PathBuf),
+    /// Export folder picked to import the index from
+    IndexImportPicked(PathBuf),
+    /// Index copied out for another PC (export folder)
+    IndexExported(PathBuf),
+    /// Index brought in from an export (rows)
+    IndexImported(usize),
+    /// Export or import didn't go through (message to show)
+    IndexTransferFailed(String),
+    /// The vector DB couldn't be opened even after retrying (error)
+    IndexUnavailable(String),
     /// Search query submitted
     SearchQuery(String),
-    /// Search results returned
-    SearchResults(Vec<PathBuf>),
+    /// Search results returned, most relevant first with their cosine
+    /// distance, and how the search ran (None if it didn't)
+    SearchResults(Vec<(PathBuf, f32)>, Option<indexer::SearchStats>),
+    /// Screenshots similar to one (source, matches with similarity; None if it isn't indexed)
+    SimilarFound(PathBuf, Option<Vec<(PathBuf, f32)>>),
+    /// Vector DB state checked before a search
+    SearchIndexHealth(indexer::IndexHealth),
+    /// Diagnostic bundle written (its path, or why it failed)
+    DiagnosticBundleCreated(Result<PathBuf, String>),
+    /// A grid thumbnail finished generating (or failed) for this screenshot
+    ThumbnailReady(PathBuf),
     /// Files copied to clipboard (count)
     CopiedToClipboard(usize),
+    /// A delete or move was stopped by a read-only or locked file
+    FileBlocked(file_ops::FileOp, file_ops::BlockReason),
+    /// The toggle hotkey couldn't be registered at startup (combination, OS error)
+    HotkeyUnavailable(String, String),
+    /// A later launch asked this instance to do something (`search-ui`)
+    Ipc(ipc::Command),
+    /// The system resumed or Explorer restarted; hotkeys and the tray icon need registering again
+    ShellRecovery(shell_recovery::Trigger),
     /// Quit application
     Quit,
 }
 
-/// Shared latest screenshot path for tray icon drag
-pub static LATEST_SCREENSHOT: parking_lot::Mutex<Option<PathBuf>> = parking_lot::Mutex::new(None);
+/// Newest screenshot paths (newest first) for tray icon drag. A few are kept
+/// so the drag can fall back when the newest file was deleted behind our back.
+pub static LATEST_SCREENSHOTS: parking_lot::Mutex<Vec<PathBuf>> = parking_lot::Mutex::new(Vec::new());
 
-/// Set the latest screenshot path
-pub fn set_latest_screenshot(path: Option<PathBuf>) {
-    *LATEST_SCREENSHOT.lock() = path;
+/// Set the newest screenshot paths
+pub fn set_latest_screenshots(paths: Vec<PathBuf>) {
+    *LATEST_SCREENSHOTS.lock() = paths;
 }
 
-/// Get the latest screenshot path
-pub fn get_latest_screenshot() -> Option<PathBuf> {
-    LATEST_SCREENSHOT.lock().clone()
+/// Get the newest screenshot paths
+pub fn get_latest_screenshots() -> Vec<PathBuf> {
+    LATEST_SCREENSHOTS.lock().clone()
 }
 
 /// Global application state shared across threads
@@ -130,14 +247,51 @@ pub struct AppState {
     pub message_rx: Receiver<AppMessage>,
     pub tray_manager: Arc<Mutex<Option<TrayManager>>>,
     pub hide_window_on_start: bool,
+    /// Commands for the file watcher thread
+    pub watcher_tx: Sender<WatcherCommand>,
 }
 
 impl Global for AppState {}
 
+/// Finish moves left in the organizer journal and tell the gallery/index where files went
+fn recover_organizer_journal(message_tx: &Sender<AppMessage>) {
+    let Some(path) = journal::default_path() else {
+        return;
+    };
+    let outcomes = match journal::recover(&path) {
+        Ok(outcomes) => outcomes,
+        Err(e) => {
+            error!("Failed to recover organizer journal {:?}: {}", path, e);
+            return;
+        }
+    };
+
+    for outcome in outcomes {
+        match outcome {
+            Recovered::AlreadyMoved { from, to } | Recovered::Finished { from, to } => {
+                info!("Reconciled interrupted move: {:?} -> {:?}", from, to);
+                timeline::record_moved(&from, &to, timeline::EventKind::Organized(to.clone()));
+                organizer::report_moved(&from, &to, message_tx);
+            }
+            Recovered::Unresolved { from, to } => {
+                warn!("Left interrupted move unresolved: {:?} -> {:?}", from, to);
+            }
+        }
+    }
+}
+
 fn main() -> Result<()> {
     // Check for --console flag to enable debug console
     let args: Vec<String> = std::env::args().collect();
     let console_mode = args.iter().any(|arg| arg == "--console" || arg == "-c");
+    let ipc_command = ipc::from_args(&args);
+
+    // `search` and `stats` print their results and exit without starting the app
+    if let Some(command) = cli::from_args(&args) {
+        cli::attach_parent_console();
+        env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("warn")).init();
+        std::process::exit(cli::run(command));
+    }
 
     if console_mode {
         attach_console();
@@ -160,7 +314,12 @@ fn main() -> Result<()> {
         println!("Logging to: sukusho_debug.log");
         println!("Logging level: {}", log_level);
     } else {
-        env_logger::Builder::from_env(env_logger::Env::default().default_filter_or(log_level)).init();
+        let mut builder = env_logger::Builder::from_env(env_logger::Env::default().default_filter_or(log_level));
+        // Kept in the config folder for the diagnostic bundle
+        if let Ok(log) = rolling_log::open() {
+            builder.target(env_logger::Target::Pipe(Box::new(log)));
+        }
+        builder.init();
     }
 
     info!("Starting Sukusho...");
@@ -169,6 +328,12 @@ fn main() -> Result<()> {
     let instance = SingleInstance::new("sukusho-screenshot-manager").unwrap();
     if !instance.is_single() {
         warn!("Another instance of Sukusho is already running");
+        if let Some(command) = ipc_command {
+            match ipc::send(&command) {
+                Ok(()) => info!("Sent {:?} to the running instance", command),
+                Err(e) => error!("Failed to reach the running instance: {}", e),
+            }
+        }
         return Ok(());
     }
     info!("Single instance check passed");
@@ -185,6 +350,16 @@ fn main() -> Result<()> {
     i18n_helpers::init_language(&settings);
 
     let screenshot_dir = settings.screenshot_directory.clone();
+    editor::set_preferred_editor(settings.preferred_editor.clone());
+    file_ops::set_clear_read_only(settings.handle_read_only_files);
+    convert_pool::set_workers(settings.conversion_workers);
+    number_format::set_decimal_units(settings.decimal_size_units);
+    filename_date::configure(settings.date_from_file_name, &settings.file_name_date_patterns);
+    exclude::configure(&settings.exclude_patterns);
+    perf::set_enabled(settings.perf_trace || args.iter().any(|arg| arg == "--perf-trace"));
+    motion::refresh_system();
+    motion::set_user_enabled(settings.animations_enabled);
+    tray::set_active_layout(settings.active_window_layout());
     let window_width = settings.window_width;
     let window_height = settings.window_height;
     let hide_window_on_start = settings.hide_window_on_start;
@@ -196,6 +371,16 @@ fn main() -> Result<()> {
 
     // Create message channels
     let (message_tx, message_rx) = unbounded::<AppMessage>();
+    file_ops::init(message_tx.clone());
+
+    // Take commands from later launches; a first launch with one runs it itself
+    ipc::listen(message_tx.clone());
+    if let Some(command) = ipc_command {
+        let _ = message_tx.send(AppMessage::Ipc(command));
+    }
+
+    // Settle an organizer batch interrupted by a crash before anything scans the folder
+    recover_organizer_journal(&message_tx);
 
     // Initialize OLE for Windows APIs (required for drag-drop)
     // OleInitialize is required instead of CoInitializeEx for DoDragDrop to work
@@ -216,13 +401,25 @@ fn main() -> Result<()> {
 
     // Initialize global hotkey with custom setting
     let hotkey_message_tx = message_tx.clone();
-    let (hotkey_str, hotkey_enabled) = {
+    let (hotkey_str, hotkey_enabled, next_monitor_hotkey, previous_monitor_hotkey, edit_latest_hotkey) = {
         let s = settings.lock();
-        (s.hotkey.clone(), s.hotkey_enabled)
+        (
+            s.hotkey.clone(),
+            s.hotkey_enabled,
+            s.next_monitor_hotkey.clone(),
+            s.previous_monitor_hotkey.clone(),
+            s.edit_latest_hotkey.clone(),
+        )
     };
     if hotkey_enabled {
         if !init_global_hotkey(hotkey_message_tx, &hotkey_str) {
             warn!("Failed to initialize global hotkey");
+        } else {
+            register_action_hotkey(HotkeyAction::NextMonitor, &next_monitor_hotkey);
+            register_action_hotkey(HotkeyAction::PreviousMonitor, &previous_monitor_hotkey);
+            if !edit_latest_hotkey.is_empty() {
+                register_action_hotkey(HotkeyAction::EditLatest, &edit_latest_hotkey);
+            }
         }
     } else {
         info!("Global hotkey disabled in settings");
@@ -232,12 +429,23 @@ fn main() -> Result<()> {
     let watcher_tx = message_tx.clone();
     let watcher_dir = screenshot_dir.clone();
     let watcher_settings = Arc::clone(&settings);
+    let (watcher_control_tx, watcher_control_rx) = unbounded::<WatcherCommand>();
     std::thread::spawn(move || {
-        if let Err(e) = ScreenshotWatcher::new(watcher_dir, watcher_tx, watcher_settings).run() {
+        let watcher = ScreenshotWatcher::new(watcher_dir, watcher_tx, watcher_settings);
+        if let Err(e) = watcher.run(watcher_control_rx) {
             error!("File watcher error: {}", e);
         }
     });
 
+    // Drop index entries for screenshots deleted while the app wasn't running
+    let prune_config = {
+        let s = settings.lock();
+        s.indexing_enabled.then(|| app::index_config(&s)).flatten()
+    };
+    if let Some(config) = prune_config {
+        indexer::prune_missing(config);
+    }
+
     // Run the GPUI application
     let app = Application::new();
 
@@ -252,6 +460,7 @@ fn main() -> Result<()> {
             message_rx,
             tray_manager: Arc::new(Mutex::new(Some(tray_manager))),
             hide_window_on_start,
+            watcher_tx: watcher_control_tx,
         });
 
         // Open main window - use Bounds::centered like official GPUI example
@@ -283,6 +492,10 @@ fn main() -> Result<()> {
                         if let RawWindowHandle::Win32(win32) = handle.as_raw() {
                             let hwnd_value = win32.hwnd.get() as isize;
                             tray::set_window_hwnd(hwnd_value);
+                            shell_recovery::install(
+                                hwnd_value,
+                                cx.global::<AppState>().message_tx.clone(),
+                            );
                             info!("Window HWND captured: {}", hwnd_value);
                         }
                     }
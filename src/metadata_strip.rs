@@ -0,0 +1,673 @@
+//! Strip EXIF, XMP and location data before sharing
+//!
+//! Photos synced from a phone carry where they were taken. "Strip metadata"
+//! removes the metadata blocks from JPEG, PNG and WebP files in place: the
+//! container is rewritten without them and the pixels are copied as they
+//! are, never re-encoded. JPEG loses its APP1 (Exif, XMP) and APP13
+//! (Photoshop/IPTC) segments, PNG its `eXIf` and text chunks, WebP its `EXIF`
+//! and `XMP ` chunks. Color profiles stay, and so does the EXIF orientation:
+//! a phone photo taken sideways gets a minimal EXIF block holding only that
+//! tag, so it isn't shown rotated afterwards. The new file replaces the old one
+//! with the same modified time, so the gallery order and organizer folder
+//! don't change.
+
+use anyhow::{bail, Context, Result};
+use crossbeam_channel::Sender;
+use filetime::{set_file_mtime, FileTime};
+use log::{error, info};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::convert;
+use crate::convert_pool::{self, Priority};
+use crate::file_ops::{self, BlockReason, FileOpError};
+use crate::paths;
+use crate::AppMessage;
+
+/// EXIF tag pointing at the GPS IFD
+const GPS_IFD_POINTER: u16 = 0x8825;
+
+/// EXIF tag saying how the image is rotated or flipped for display
+const ORIENTATION: u16 = 0x0112;
+
+/// GPS tag that says which version of the tags follows, not where
+const GPS_VERSION_ID: u16 = 0x0000;
+
+/// Names XMP uses for coordinates
+const XMP_GPS_PROPERTIES: [&[u8]; 2] = [b"GPSLatitude", b"GPSLongitude"];
+
+/// What happened to one file
+#[derive(Debug, Clone, PartialEq)]
+pub enum FileOutcome {
+    /// Metadata removed (whether it included a location)
+    Stripped { had_gps: bool },
+    /// Nothing to remove
+    Clean,
+    /// Being converted or rewritten already; left alone
+    Busy,
+    /// Not a JPEG, PNG or WebP
+    Unsupported,
+    Failed(String),
+}
+
+/// Outcome of a batch, file by file, in the order they finished
+#[derive(Debug, Clone, Default)]
+pub struct StripReport {
+    pub files: Vec<(PathBuf, FileOutcome)>,
+}
+
+impl StripReport {
+    pub fn stripped(&self) -> usize {
+        self.count(|o| matches!(o, FileOutcome::Stripped { .. }))
+    }
+
+    /// Files that had a location in them
+    pub fn had_gps(&self) -> usize {
+        self.count(|o| matches!(o, FileOutcome::Stripped { had_gps: true }))
+    }
+
+    /// Files left as they were: already clean, busy or another format
+    pub fn unchanged(&self) -> usize {
+        self.count(|o| matches!(o, FileOutcome::Clean | FileOutcome::Busy | FileOutcome::Unsupported))
+    }
+
+    pub fn failed(&self) -> usize {
+        self.count(|o| matches!(o, FileOutcome::Failed(_)))
+    }
+
+    fn count(&self, f: impl Fn(&FileOutcome) -> bool) -> usize {
+        self.files.iter().filter(|(_, outcome)| f(outcome)).count()
+    }
+}
+
+/// A file's bytes without their metadata
+#[derive(Debug, PartialEq)]
+struct Stripped {
+    data: Vec<u8>,
+    /// Whether anything was removed
+    removed: bool,
+    had_gps: bool,
+}
+
+/// Strip metadata from `files` on the conversion pool, reporting progress and
+/// then the whole [`StripReport`]
+pub fn strip_files(files: Vec<PathBuf>, message_tx: Sender<AppMessage>) {
+    std::thread::spawn(move || {
+        let total = files.len();
+        info!("Stripping metadata from {} files", total);
+        let _ = message_tx.send(AppMessage::StripStarted(total));
+
+        let (done_tx, done_rx) = crossbeam_channel::unbounded();
+        let mut report = StripReport::default();
+        for path in files {
+            if !is_strippable(&path) {
+                let _ = done_tx.send((path, FileOutcome::Unsupported));
+                continue;
+            }
+            let done_tx = done_tx.clone();
+            let job_path = path.clone();
+            let queued = convert_pool::run(path.clone(), Priority::Background, move || {
+                let outcome = match strip_file(&job_path) {
+                    Ok(Some(had_gps)) => FileOutcome::Stripped { had_gps },
+                    Ok(None) => FileOutcome::Clean,
+                    Err(e) => {
+                        error!("Failed to strip metadata from {:?}: {:#}", job_path, e);
+                        FileOutcome::Failed(format!("{:#}", e))
+                    }
+                };
+                let _ = done_tx.send((job_path, outcome));
+            });
+            if !queued {
+                let _ = done_tx.send((path, FileOutcome::Busy));
+            }
+        }
+        drop(done_tx);
+
+        for (path, outcome) in done_rx {
+            let current = report.files.len() + 1;
+            let _ = message_tx.send(AppMessage::StripProgress(current, total, path.clone()));
+            report.files.push((path, outcome));
+        }
+
+        info!(
+            "Stripped metadata from {} files ({} with GPS, {} failed)",
+            report.stripped(),
+            report.had_gps(),
+            report.failed()
+        );
+        let _ = message_tx.send(AppMessage::StripCompleted(report));
+    });
+}
+
+/// Strip one file in place, keeping its modified time. `Some(had_gps)` if
+/// anything was removed.
+pub fn strip_file(path: &Path) -> Result<Option<bool>> {
+    // Rewriting a file still being written would cut it short
+    convert::wait_for_file_ready(path)?;
+    let fs_path = paths::long_path(path);
+    let data = file_ops::retry_locked(path, || fs::read(&fs_path))
+        .with_context(|| format!("Failed to read {:?}", path))?;
+    let stripped = strip(&data)?;
+    if !stripped.removed {
+        return Ok(None);
+    }
+
+    let metadata = fs::metadata(&fs_path)?;
+    let mtime = FileTime::from_last_modification_time(&metadata);
+
+    // Written next to the original, then swapped in (asking first if the
+    // original is read-only); the watcher ignores the extension
+    let mut temp_name = path.file_name().context("No file name")?.to_os_string();
+    temp_name.push(".strip-tmp");
+    let temp_path = path.with_file_name(temp_name);
+    let temp_fs_path = paths::long_path(&temp_path);
+    let result = fs::write(&temp_fs_path, &stripped.data)
+        .and_then(|()| set_file_mtime(&temp_fs_path, mtime))
+        .map_err(FileOpError::from)
+        .and_then(|()| file_ops::replace(&temp_path, path));
+    if let Err(e) = result {
+        // A read-only file's prompt swaps the copy in later, or drops it
+        if !matches!(e, FileOpError::Blocked(BlockReason::ReadOnly)) {
+            let _ = fs::remove_file(&temp_fs_path);
+        }
+        return Err(e).with_context(|| format!("Failed to rewrite {:?}", path));
+    }
+
+    info!(
+        "Stripped metadata from {:?} ({} -> {} bytes{})",
+        path,
+        data.len(),
+        stripped.data.len(),
+        if stripped.had_gps { ", had GPS" } else { "" }
+    );
+    Ok(Some(stripped.had_gps))
+}
+
+/// Whether `path` is a format metadata can be stripped from
+pub fn is_strippable(path: &Path) -> bool {
+    path.extension()
+        .and_then(|e| e.to_str())
+        .is_some_and(|ext| {
+            ["jpg", "jpeg", "png", "webp"]
+                .iter()
+                .any(|known| ext.eq_ignore_ascii_case(known))
+        })
+}
+
+/// Metadata-free copy of a JPEG, PNG or WebP, by content rather than name
+fn strip(bytes: &[u8]) -> Result<Stripped> {
+    if bytes.starts_with(b"\x89PNG\r\n\x1a\n") {
+        strip_png(bytes)
+    } else if bytes.starts_with(&[0xFF, 0xD8]) {
+        strip_jpeg(bytes)
+    } else if bytes.len() >= 12 && &bytes[..4] == b"RIFF" && &bytes[8..12] == b"WEBP" {
+        strip_webp(bytes)
+    } else {
+        bail!("Not a JPEG, PNG or WebP file")
+    }
+}
+
+/// Drop APP1 and APP13 segments; everything from the start of scan on is
+/// image data and copied as is
+fn strip_jpeg(bytes: &[u8]) -> Result<Stripped> {
+    let mut out = bytes[..2].to_vec();
+    let mut removed = false;
+    let mut had_gps = false;
+    let mut kept_orientation = false;
+    let mut rest = &bytes[2..];
+    loop {
+        // Markers may be padded with extra 0xFF bytes
+        let fill = rest.iter().take_while(|b| **b == 0xFF).count();
+        if fill == 0 || rest.len() < fill + 1 {
+            bail!("Malformed JPEG");
+        }
+        let marker = rest[fill];
+        let segment_start = fill - 1;
+        // Start of scan or end of image: no more metadata
+        if marker == 0xDA || marker == 0xD9 {
+            out.extend_from_slice(&rest[segment_start..]);
+            break;
+        }
+        // Standalone markers have no length
+        if (0xD0..=0xD7).contains(&marker) || marker == 0x01 {
+            out.extend_from_slice(&rest[segment_start..fill + 1]);
+            rest = &rest[fill + 1..];
+            continue;
+        }
+        let length = rest
+            .get(fill + 1..fill + 3)
+            .map(|b| u16::from_be_bytes([b[0], b[1]]) as usize)
+            .filter(|length| *length >= 2)
+            .context("Malformed JPEG")?;
+        let end = fill + 1 + length;
+        let data = rest.get(fill + 3..end).context("Truncated JPEG")?;
+        match marker {
+            0xE1 | 0xED => match data.strip_prefix(b"Exif\0\0") {
+                Some(tiff) => {
+                    had_gps |= exif_has_gps(tiff);
+                    match exif_orientation(tiff).filter(|_| !kept_orientation) {
+                        Some(orientation) => {
+                            kept_orientation = true;
+                            let mut exif = b"Exif\0\0".to_vec();
+                            exif.extend(orientation_exif(orientation));
+                            removed |= exif[6..] != *tiff;
+                            out.extend([0xFF, 0xE1]);
+                            out.extend(((exif.len() + 2) as u16).to_be_bytes());
+                            out.extend(exif);
+                        }
+                        None => removed = true,
+                    }
+                }
+                None => {
+                    removed = true;
+                    had_gps |= xmp_has_gps(data);
+                }
+            },
+            _ => out.extend_from_slice(&rest[segment_start..end]),
+        }
+        rest = &rest[end..];
+    }
+    Ok(Stripped { data: out, removed, had_gps })
+}
+
+/// Drop `eXIf` and text chunks
+fn strip_png(bytes: &[u8]) -> Result<Stripped> {
+    let mut out = bytes[..8].to_vec();
+    let mut removed = false;
+    let mut had_gps = false;
+    let mut rest = &bytes[8..];
+    while !rest.is_empty() {
+        let length = rest
+            .get(..4)
+            .map(|b| u32::from_be_bytes([b[0], b[1], b[2], b[3]]) as usize)
+            .context("Truncated PNG")?;
+        let chunk = rest.get(..12 + length).context("Truncated PNG")?;
+        let kind = &chunk[4..8];
+        let data = &chunk[8..8 + length];
+        match kind {
+            b"eXIf" => {
+                removed = true;
+                had_gps |= exif_has_gps(data);
+            }
+            b"tEXt" | b"zTXt" | b"iTXt" => {
+                removed = true;
+                had_gps |= xmp_has_gps(data);
+            }
+            _ => out.extend_from_slice(chunk),
+        }
+        rest = &rest[12 + length..];
+        if kind == b"IEND" {
+            break;
+        }
+    }
+    Ok(Stripped { data: out, removed, had_gps })
+}
+
+/// Drop `EXIF` and `XMP ` chunks and clear their flags in `VP8X`, keeping
+/// an `EXIF` chunk with only the orientation when it isn't upright
+fn strip_webp(bytes: &[u8]) -> Result<Stripped> {
+    // VP8X flags for the two chunks
+    const XMP_FLAG: u8 = 0x04;
+    const EXIF_FLAG: u8 = 0x08;
+
+    let mut out = bytes[..12].to_vec();
+    let mut removed = false;
+    let mut had_gps = false;
+    let mut kept_orientation = false;
+    let mut rest = &bytes[12..];
+    while rest.len() >= 8 {
+        let length = u32::from_le_bytes([rest[4], rest[5], rest[6], rest[7]]) as usize;
+        // Chunks are padded to an even size
+        let end = (8 + length + (length & 1)).min(rest.len());
+        let data = rest.get(8..8 + length).context("Truncated WebP")?;
+        match &rest[..4] {
+            b"EXIF" => {
+                let tiff = data.strip_prefix(b"Exif\0\0").unwrap_or(data);
+                had_gps |= exif_has_gps(tiff);
+                match exif_orientation(tiff).filter(|_| !kept_orientation) {
+                    Some(orientation) => {
+                        kept_orientation = true;
+                        let exif = orientation_exif(orientation);
+                        removed |= exif != tiff;
+                        // Always an even size, so no padding
+                        out.extend_from_slice(b"EXIF");
+                        out.extend((exif.len() as u32).to_le_bytes());
+                        out.extend(exif);
+                    }
+                    None => removed = true,
+                }
+            }
+            b"XMP " => {
+                removed = true;
+                had_gps |= xmp_has_gps(data);
+            }
+            _ => out.extend_from_slice(&rest[..end]),
+        }
+        rest = &rest[end..];
+    }
+
+    if removed {
+        if out.len() > 20 && &out[12..16] == b"VP8X" {
+            out[20] &= !XMP_FLAG;
+            if !kept_orientation {
+                out[20] &= !EXIF_FLAG;
+            }
+        }
+        let riff_size = (out.len() - 8) as u32;
+        out[4..8].copy_from_slice(&riff_size.to_le_bytes());
+    }
+    Ok(Stripped { data: out, removed, had_gps })
+}
+
+/// Whether a TIFF-structured EXIF block has a GPS IFD with a location in it
+/// (some cameras write the IFD with only the version)
+fn exif_has_gps(tiff: &[u8]) -> bool {
+    gps_entries(tiff).is_some_and(|tags| tags.iter().any(|tag| *tag != GPS_VERSION_ID))
+}
+
+/// Tags in the GPS IFD
+fn gps_entries(tiff: &[u8]) -> Option<Vec<u16>> {
+    let tiff = Tiff::new(tiff)?;
+    let pointer = tiff.ifd0_entry(GPS_IFD_POINTER)?;
+    let ifd = tiff.u32_at(pointer + 8)? as usize;
+    let count = tiff.u16_at(ifd)? as usize;
+    (0..count).map(|i| tiff.u16_at(ifd + 2 + i * 12)).collect()
+}
+
+/// Orientation in IFD0, if it's there and anything but upright (1)
+fn exif_orientation(tiff: &[u8]) -> Option<u16> {
+    let tiff = Tiff::new(tiff)?;
+    let entry = tiff.ifd0_entry(ORIENTATION)?;
+    // A single SHORT, stored in the entry's value field
+    let orientation = tiff.u16_at(entry + 8)?;
+    (2..=8).contains(&orientation).then_some(orientation)
+}
+
+/// Little-endian TIFF whose IFD0 holds nothing but `orientation`
+fn orientation_exif(orientation: u16) -> Vec<u8> {
+    // TIFF type for an unsigned 16-bit value
+    const SHORT: u16 = 3;
+
+    let mut tiff = b"II*\0".to_vec();
+    tiff.extend(8u32.to_le_bytes());
+    tiff.extend(1u16.to_le_bytes());
+    tiff.extend(ORIENTATION.to_le_bytes());
+    tiff.extend(SHORT.to_le_bytes());
+    tiff.extend(1u32.to_le_bytes());
+    tiff.extend(u32::from(orientation).to_le_bytes());
+    // No next IFD
+    tiff.extend(0u32.to_le_bytes());
+    tiff
+}
+
+/// Reads a TIFF-structured EXIF block in its own byte order
+struct Tiff<'a> {
+    bytes: &'a [u8],
+    little_endian: bool,
+}
+
+impl<'a> Tiff<'a> {
+    fn new(bytes: &'a [u8]) -> Option<Self> {
+        let little_endian = match bytes.get(..2)? {
+            b"II" => true,
+            b"MM" => false,
+            _ => return None,
+        };
+        Some(Tiff { bytes, little_endian })
+    }
+
+    fn u16_at(&self, offset: usize) -> Option<u16> {
+        let bytes: [u8; 2] = self.bytes.get(offset..offset + 2)?.try_into().ok()?;
+        Some(if self.little_endian { u16::from_le_bytes(bytes) } else { u16::from_be_bytes(bytes) })
+    }
+
+    fn u32_at(&self, offset: usize) -> Option<u32> {
+        let bytes: [u8; 4] = self.bytes.get(offset..offset + 4)?.try_into().ok()?;
+        Some(if self.little_endian { u32::from_le_bytes(bytes) } else { u32::from_be_bytes(bytes) })
+    }
+
+    /// Offset of `tag`'s entry in IFD0
+    fn ifd0_entry(&self, tag: u16) -> Option<usize> {
+        let ifd0 = self.u32_at(4)? as usize;
+        (0..self.u16_at(ifd0)? as usize)
+            .map(|i| ifd0 + 2 + i * 12)
+            .find(|entry| self.u16_at(*entry) == Some(tag))
+    }
+}
+
+/// Whether an XMP packet (or any text) names coordinates
+fn xmp_has_gps(text: &[u8]) -> bool {
+    XMP_GPS_PROPERTIES
+        .iter()
+        .any(|property| text.windows(property.len()).any(|w| w == *property))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::scratch_dir;
+
+    /// Little-endian TIFF with IFD0 -> GPS IFD holding `gps_tags`
+    fn exif_block(gps_tags: &[u16]) -> Vec<u8> {
+        oriented_exif_block(None, gps_tags)
+    }
+
+    /// [`exif_block`] with an Orientation entry in IFD0 too
+    fn oriented_exif_block(orientation: Option<u16>, gps_tags: &[u16]) -> Vec<u8> {
+        let entries = 1 + orientation.is_some() as u32;
+        let mut tiff = b"II*\0".to_vec();
+        tiff.extend(8u32.to_le_bytes());
+        // IFD0, entries in tag order; the GPS IFD follows it
+        tiff.extend((entries as u16).to_le_bytes());
+        if let Some(orientation) = orientation {
+            tiff.extend(ORIENTATION.to_le_bytes());
+            tiff.extend(3u16.to_le_bytes());
+            tiff.extend(1u32.to_le_bytes());
+            tiff.extend(u32::from(orientation).to_le_bytes());
+        }
+        tiff.extend(GPS_IFD_POINTER.to_le_bytes());
+        tiff.extend(4u16.to_le_bytes());
+        tiff.extend(1u32.to_le_bytes());
+        tiff.extend((8 + 2 + 12 * entries + 4).to_le_bytes());
+        tiff.extend(0u32.to_le_bytes());
+        tiff.extend((gps_tags.len() as u16).to_le_bytes());
+        for tag in gps_tags {
+            tiff.extend(tag.to_le_bytes());
+            tiff.extend(1u16.to_le_bytes());
+            tiff.extend(4u32.to_le_bytes());
+            tiff.extend(0u32.to_le_bytes());
+        }
+        tiff.extend(0u32.to_le_bytes());
+        tiff
+    }
+
+    fn jpeg_segment(marker: u8, data: &[u8]) -> Vec<u8> {
+        let mut segment = vec![0xFF, marker];
+        segment.extend(((data.len() + 2) as u16).to_be_bytes());
+        segment.extend(data);
+        segment
+    }
+
+    fn png_chunk(kind: &[u8], data: &[u8]) -> Vec<u8> {
+        let mut chunk = (data.len() as u32).to_be_bytes().to_vec();
+        chunk.extend(kind);
+        chunk.extend(data);
+        chunk.extend([0; 4]);
+        chunk
+    }
+
+    fn webp_chunk(kind: &[u8], data: &[u8]) -> Vec<u8> {
+        let mut chunk = kind.to_vec();
+        chunk.extend((data.len() as u32).to_le_bytes());
+        chunk.extend(data);
+        if data.len() % 2 == 1 {
+            chunk.push(0);
+        }
+        chunk
+    }
+
+    #[test]
+    fn test_jpeg() {
+        let mut exif = b"Exif\0\0".to_vec();
+        exif.extend(exif_block(&[0x0000, 0x0002, 0x0004]));
+        let jfif = jpeg_segment(0xE0, b"JFIF\0\x01\x01\0\0\x01\0\x01\0\0");
+        let icc = jpeg_segment(0xE2, b"ICC_PROFILE\0\x01\x01");
+        let scan = [0xFF, 0xDA, 0, 2, 0x12, 0x34, 0xFF, 0xE1, 0xFF, 0xD9];
+
+        let mut jpeg = vec![0xFF, 0xD8];
+        jpeg.extend(&jfif);
+        jpeg.extend(jpeg_segment(0xE1, &exif));
+        jpeg.extend(&icc);
+        jpeg.extend(jpeg_segment(0xE1, b"http://ns.adobe.com/xap/1.0/\0<x:xmpmeta/>"));
+        jpeg.extend(scan);
+
+        let stripped = strip(&jpeg).unwrap();
+        assert!(stripped.removed);
+        assert!(stripped.had_gps);
+        // JFIF and the color profile stay; bytes after the scan start are untouched
+        let mut expected = vec![0xFF, 0xD8];
+        expected.extend(&jfif);
+        expected.extend(&icc);
+        expected.extend(scan);
+        assert_eq!(stripped.data, expected);
+
+        // Already clean: unchanged
+        let again = strip(&stripped.data).unwrap();
+        assert!(!again.removed);
+        assert_eq!(again.data, expected);
+    }
+
+    #[test]
+    fn test_jpeg_keeps_orientation() {
+        let scan = [0xFF, 0xDA, 0, 2, 0x12, 0x34, 0xFF, 0xD9];
+        let jpeg_with = |tiff: Vec<u8>| {
+            let mut exif = b"Exif\0\0".to_vec();
+            exif.extend(tiff);
+            let mut jpeg = vec![0xFF, 0xD8];
+            jpeg.extend(jpeg_segment(0xE1, &exif));
+            jpeg.extend(scan);
+            jpeg
+        };
+
+        // Taken with the phone on its side (rotate 90° clockwise to view)
+        let stripped = strip(&jpeg_with(oriented_exif_block(Some(6), &[0x0002]))).unwrap();
+        assert!(stripped.removed);
+        assert!(stripped.had_gps);
+        assert_eq!(stripped.data, jpeg_with(orientation_exif(6)));
+        // After SOI, the marker, its length and "Exif\0\0"
+        let tiff = &stripped.data[12..12 + orientation_exif(6).len()];
+        assert_eq!(exif_orientation(tiff), Some(6));
+        assert!(!exif_has_gps(tiff));
+
+        // Only the orientation left: nothing more to strip
+        let again = strip(&stripped.data).unwrap();
+        assert!(!again.removed);
+        assert_eq!(again.data, stripped.data);
+
+        // Upright: the EXIF block goes entirely
+        let stripped = strip(&jpeg_with(oriented_exif_block(Some(1), &[0x0002]))).unwrap();
+        let mut expected = vec![0xFF, 0xD8];
+        expected.extend(scan);
+        assert_eq!(stripped.data, expected);
+    }
+
+    #[test]
+    fn test_png() {
+        let mut png = b"\x89PNG\r\n\x1a\n".to_vec();
+        png.extend(png_chunk(b"IHDR", &[0; 13]));
+        png.extend(png_chunk(b"tEXt", b"Software\0Snipping Tool"));
+        png.extend(png_chunk(b"IDAT", &[1, 2, 3]));
+        png.extend(png_chunk(b"IEND", &[]));
+        let stripped = strip(&png).unwrap();
+        assert!(stripped.removed);
+        assert!(!stripped.had_gps);
+        let mut expected = b"\x89PNG\r\n\x1a\n".to_vec();
+        expected.extend(png_chunk(b"IHDR", &[0; 13]));
+        expected.extend(png_chunk(b"IDAT", &[1, 2, 3]));
+        expected.extend(png_chunk(b"IEND", &[]));
+        assert_eq!(stripped.data, expected);
+
+        let mut png = b"\x89PNG\r\n\x1a\n".to_vec();
+        png.extend(png_chunk(b"eXIf", &exif_block(&[0x0002])));
+        png.extend(png_chunk(b"IEND", &[]));
+        assert!(strip(&png).unwrap().had_gps);
+
+        assert!(strip(&png[..png.len() - 4]).is_err());
+    }
+
+    #[test]
+    fn test_webp() {
+        let mut vp8x_data = vec![0x08 | 0x04 | 0x10, 0, 0, 0];
+        vp8x_data.extend([0; 6]);
+        let vp8l = webp_chunk(b"VP8L", &[1, 2, 3]);
+        let mut body = b"WEBP".to_vec();
+        body.extend(webp_chunk(b"VP8X", &vp8x_data));
+        body.extend(&vp8l);
+        body.extend(webp_chunk(b"EXIF", &exif_block(&[0x0001, 0x0002])));
+        body.extend(webp_chunk(b"XMP ", b"<x:xmpmeta/>"));
+        let mut webp = b"RIFF".to_vec();
+        webp.extend((body.len() as u32).to_le_bytes());
+        webp.extend(body);
+
+        let stripped = strip(&webp).unwrap();
+        assert!(stripped.removed);
+        assert!(stripped.had_gps);
+        let data = &stripped.data;
+        assert_eq!(u32::from_le_bytes(data[4..8].try_into().unwrap()) as usize, data.len() - 8);
+        // Only the alpha flag is left
+        assert_eq!(data[20], 0x10);
+        assert!(data.ends_with(&vp8l));
+
+        // A sideways photo keeps an EXIF chunk with just its orientation
+        let mut body = b"WEBP".to_vec();
+        body.extend(webp_chunk(b"VP8X", &vp8x_data));
+        body.extend(&vp8l);
+        body.extend(webp_chunk(b"EXIF", &oriented_exif_block(Some(6), &[0x0002])));
+        let mut webp = b"RIFF".to_vec();
+        webp.extend((body.len() as u32).to_le_bytes());
+        webp.extend(body);
+
+        let stripped = strip(&webp).unwrap();
+        assert!(stripped.removed);
+        assert!(stripped.had_gps);
+        let data = &stripped.data;
+        assert_eq!(u32::from_le_bytes(data[4..8].try_into().unwrap()) as usize, data.len() - 8);
+        assert_eq!(data[20], 0x08 | 0x10);
+        assert!(data.ends_with(&webp_chunk(b"EXIF", &orientation_exif(6))));
+        assert!(!strip(data).unwrap().removed);
+    }
+
+    #[test]
+    fn test_gps_detection() {
+        // Only the version: no location
+        assert!(!exif_has_gps(&exif_block(&[0x0000])));
+        assert!(!exif_has_gps(&exif_block(&[])));
+        assert!(exif_has_gps(&exif_block(&[0x0000, 0x0002])));
+        assert!(!exif_has_gps(b"II*\0"));
+        assert!(xmp_has_gps(b"<exif:GPSLatitude>37,33.5N</exif:GPSLatitude>"));
+        assert!(!xmp_has_gps(b"<xmp:CreatorTool>Camera</xmp:CreatorTool>"));
+    }
+
+    #[test]
+    fn test_strip_file_keeps_mtime() {
+        let dir = scratch_dir("strip");
+
+        let path = dir.join("photo.png");
+        let mut png = b"\x89PNG\r\n\x1a\n".to_vec();
+        png.extend(png_chunk(b"eXIf", &exif_block(&[0x0002])));
+        png.extend(png_chunk(b"IEND", &[]));
+        fs::write(&path, &png).unwrap();
+        let mtime = FileTime::from_unix_time(1_600_000_000, 0);
+        set_file_mtime(&path, mtime).unwrap();
+
+        assert_eq!(strip_file(&path).unwrap(), Some(true));
+        let metadata = fs::metadata(&path).unwrap();
+        assert_eq!(FileTime::from_last_modification_time(&metadata), mtime);
+        assert_eq!(metadata.len() as usize, png.len() - 12 - exif_block(&[0x0002]).len());
+        // Nothing left to strip, and no temp file behind
+        assert_eq!(strip_file(&path).unwrap(), None);
+        assert_eq!(fs::read_dir(&dir).unwrap().count(), 1);
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
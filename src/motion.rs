@@ -0,0 +1,58 @@
+//! Whether to animate
+//!
+//! Animations follow Windows' "Show animations" accessibility setting
+//! (`SPI_GETCLIENTAREAANIMATION`, read again on `WM_SETTINGCHANGE`) and can
+//! also be turned off on the General page. Everything animated asks
+//! [`animations_enabled`] and, when it's false, goes straight to its final
+//! state.
+
+use log::info;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Windows' "Show animations"
+static SYSTEM_ENABLED: AtomicBool = AtomicBool::new(true);
+
+/// The Animations setting
+static USER_ENABLED: AtomicBool = AtomicBool::new(true);
+
+pub fn animations_enabled() -> bool {
+    USER_ENABLED.load(Ordering::Relaxed) && SYSTEM_ENABLED.load(Ordering::Relaxed)
+}
+
+/// Apply the Animations setting
+pub fn set_user_enabled(enabled: bool) {
+    USER_ENABLED.store(enabled, Ordering::Relaxed);
+}
+
+/// Read the system setting again (at startup and when Windows settings change)
+pub fn refresh_system() {
+    let enabled = system_animations();
+    if SYSTEM_ENABLED.swap(enabled, Ordering::Relaxed) != enabled {
+        info!("System animations {}", if enabled { "on" } else { "off" });
+    }
+}
+
+#[cfg(windows)]
+fn system_animations() -> bool {
+    use windows::Win32::Foundation::BOOL;
+    use windows::Win32::UI::WindowsAndMessaging::{
+        SystemParametersInfoW, SPI_GETCLIENTAREAANIMATION, SYSTEM_PARAMETERS_INFO_UPDATE_FLAGS,
+    };
+
+    let mut enabled = BOOL(1);
+    let result = unsafe {
+        SystemParametersInfoW(
+            SPI_GETCLIENTAREAANIMATION,
+            0,
+            Some(&mut enabled as *mut BOOL as *mut std::ffi::c_void),
+            SYSTEM_PARAMETERS_INFO_UPDATE_FLAGS(0),
+        )
+    };
+    // Animate if it can't be read, as Windows does by default
+    result.is_err() || enabled.as_bool()
+}
+
+#[cfg(not(windows))]
+fn system_animations() -> bool {
+    true
+}
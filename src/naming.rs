@@ -0,0 +1,319 @@
+//! File name patterns with date token substitution
+//!
+//! Shared by everything that has to invent a name: new screenshots renamed
+//! by the pipeline, and the organizer's date folders.
+//!
+//! Tokens: `{YYYY}`, `{YY}`, `{MMM}` (short month name), `{MM}`, `{WW}`
+//! (ISO week), `{DD}`, `{HH}`, `{mm}`, `{ss}`. Organizer folder formats also
+//! accept the upper-case tokens without braces (e.g. "YYYY-MM-DD").
+//!
+//! In a pattern with `{WW}`, the year tokens give the ISO week-based year,
+//! so the first days of January can still belong to the previous year's last
+//! week (2021-01-01 is "2020-53", not "2021-53").
+
+use anyhow::Result;
+use chrono::{DateTime, Local};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::file_ops;
+use crate::filename_date;
+use crate::paths;
+
+/// Default pattern for synthesized screenshot names
+pub const DEFAULT_PATTERN: &str = "Screenshot {YYYY}-{MM}-{DD} {HH}{mm}{ss}";
+
+/// Known tokens and their chrono format, longest first so YYYY wins over YY
+const TOKENS: &[(&str, &str)] = &[
+    ("YYYY", "%Y"),
+    ("YY", "%y"),
+    ("MMM", "%b"),
+    ("MM", "%m"),
+    ("WW", "%V"),
+    ("DD", "%d"),
+    ("HH", "%H"),
+    ("mm", "%M"),
+    ("ss", "%S"),
+];
+
+/// Tokens allowed without braces (organizer folder formats), longest first
+const BARE_TOKENS: &[&str] = &["YYYY", "YY", "MMM", "MM", "WW", "DD", "HH"];
+
+/// Give up after this many collision suffixes
+const MAX_COLLISIONS: usize = 1000;
+
+/// Why a pattern was rejected
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PatternError {
+    /// Pattern is empty or only whitespace
+    Empty,
+    /// Pattern contains a character Windows doesn't allow in file names
+    IllegalChar(char),
+    /// `{...}` that isn't a known token
+    UnknownToken(String),
+    /// `{` without a matching `}`
+    Unclosed,
+    /// Folder name Windows can't create as written: a reserved device name
+    /// (`CON`, `NUL`, ...), `.` or `..`, or one ending in a dot or space
+    InvalidName(String),
+}
+
+/// Expand `{TOKEN}` placeholders. Unknown placeholders are kept as-is.
+pub fn expand(pattern: &str, date: DateTime<Local>) -> String {
+    expand_impl(pattern, date, false)
+}
+
+/// Expand placeholders and bare date tokens (`YYYY-MM-DD`), for organizer formats
+pub fn expand_with_bare_dates(pattern: &str, date: DateTime<Local>) -> String {
+    expand_impl(pattern, date, true)
+}
+
+fn expand_impl(pattern: &str, date: DateTime<Local>, bare_dates: bool) -> String {
+    let mut result = String::with_capacity(pattern.len() + 8);
+    let mut rest = pattern;
+    // No other token has a W in it
+    let iso_week = pattern.contains("{WW}") || (bare_dates && pattern.contains("WW"));
+    let format_for = |token: &str| match token_format(token)? {
+        "%Y" if iso_week => Some("%G"),
+        "%y" if iso_week => Some("%g"),
+        format => Some(format),
+    };
+
+    'outer: while !rest.is_empty() {
+        if let Some(inner) = rest.strip_prefix('{') {
+            if let Some(end) = inner.find('}') {
+                if let Some(format) = format_for(&inner[..end]) {
+                    result.push_str(&date.format(format).to_string());
+                    rest = &inner[end + 1..];
+                    continue;
+                }
+            }
+        }
+
+        if bare_dates {
+            for token in BARE_TOKENS {
+                if let Some(after) = rest.strip_prefix(token) {
+                    if let Some(format) = format_for(token) {
+                        result.push_str(&date.format(format).to_string());
+                    }
+                    rest = after;
+                    continue 'outer;
+                }
+            }
+        }
+
+        let ch = rest.chars().next().unwrap_or_default();
+        result.push(ch);
+        rest = &rest[ch.len_utf8()..];
+    }
+
+    result
+}
+
+/// Look up the chrono format for a token name
+fn token_format(token: &str) -> Option<&'static str> {
+    TOKENS
+        .iter()
+        .find(|(name, _)| *name == token)
+        .map(|(_, format)| *format)
+}
+
+/// Check a file name pattern before saving it to settings
+pub fn validate_pattern(pattern: &str) -> Result<(), PatternError> {
+    if pattern.trim().is_empty() {
+        return Err(PatternError::Empty);
+    }
+
+    let mut rest = pattern;
+    while let Some(start) = rest.find('{') {
+        check_literal(&rest[..start])?;
+        let inner = &rest[start + 1..];
+        let end = inner.find('}').ok_or(PatternError::Unclosed)?;
+        let token = &inner[..end];
+        if token_format(token).is_none() {
+            return Err(PatternError::UnknownToken(token.to_string()));
+        }
+        rest = &inner[end + 1..];
+    }
+    check_literal(rest)
+}
+
+/// Reject illegal characters in the literal parts of a pattern
+fn check_literal(text: &str) -> Result<(), PatternError> {
+    match text.chars().find(|c| paths::is_illegal_file_name_char(*c)) {
+        Some(c) => Err(PatternError::IllegalChar(c)),
+        None => Ok(()),
+    }
+}
+
+/// Build a safe file name (with extension) from a pattern
+pub fn file_name(pattern: &str, date: DateTime<Local>, extension: &str) -> String {
+    let stem = paths::sanitize_file_name(&expand(pattern, date));
+    if extension.is_empty() {
+        stem
+    } else {
+        format!("{}.{}", stem, extension)
+    }
+}
+
+/// Find a free path for `stem.ext` in `dir`, appending `_1`, `_2`, ... on collision
+pub fn unique_path(dir: &Path, stem: &str, extension: &str) -> Result<PathBuf> {
+    let name_for = |suffix: Option<usize>| {
+        let stem = match suffix {
+            Some(n) => format!("{}_{}", stem, n),
+            None => stem.to_string(),
+        };
+        if extension.is_empty() {
+            stem
+        } else {
+            format!("{}.{}", stem, extension)
+        }
+    };
+
+    let mut candidate = dir.join(name_for(None));
+    let mut counter = 1;
+    while paths::long_path(&candidate).exists() {
+        if counter > MAX_COLLISIONS {
+            return Err(anyhow::anyhow!("Too many duplicate files"));
+        }
+        candidate = dir.join(name_for(Some(counter)));
+        counter += 1;
+    }
+
+    Ok(candidate)
+}
+
+/// Pick a new, unused path in `dir` for a synthesized image
+pub fn new_file_path(
+    dir: &Path,
+    pattern: &str,
+    date: DateTime<Local>,
+    extension: &str,
+) -> Result<PathBuf> {
+    let stem = paths::sanitize_file_name(&expand(pattern, date));
+    unique_path(dir, &stem, extension)
+}
+
+/// Rename a new screenshot after `pattern`, dated by its capture time, in the
+/// same folder. `Ok(None)` when its name already is what the pattern gives.
+pub fn rename_to_pattern(path: &Path, pattern: &str) -> Result<Option<PathBuf>> {
+    let dir = path
+        .parent()
+        .ok_or_else(|| anyhow::anyhow!("Invalid file path"))?;
+    let modified = fs::metadata(paths::long_path(path))?.modified()?;
+    let date: DateTime<Local> = filename_date::capture_time(path, modified).into();
+    let extension = path
+        .extension()
+        .map(|e| e.to_string_lossy().to_string())
+        .unwrap_or_default();
+
+    if path.file_name().is_some_and(|name| *name == *file_name(pattern, date, &extension)) {
+        return Ok(None);
+    }
+    let target = new_file_path(dir, pattern, date, &extension)?;
+    file_ops::rename(path, &target)?;
+    Ok(Some(target))
+}
+
+/// Example output for a pattern using the current time
+pub fn preview(pattern: &str, extension: &str) -> String {
+    file_name(pattern, Local::now(), extension)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::scratch_dir;
+    use chrono::TimeZone;
+    use std::fs;
+
+    fn sample_date() -> DateTime<Local> {
+        Local.with_ymd_and_hms(2024, 1, 5, 9, 7, 3).unwrap()
+    }
+
+    #[test]
+    fn test_expand_braced_tokens() {
+        let date = sample_date();
+        assert_eq!(expand(DEFAULT_PATTERN, date), "Screenshot 2024-01-05 090703");
+        assert_eq!(expand("{YY}{MM}{DD}_{HH}-{mm}-{ss}", date), "240105_09-07-03");
+        // Bare tokens and unknown placeholders are literal text
+        assert_eq!(expand("YYYY {foo} {YYYY", date), "YYYY {foo} {YYYY");
+    }
+
+    #[test]
+    fn test_expand_with_bare_dates() {
+        let date = sample_date();
+        assert_eq!(expand_with_bare_dates("YYYY-MM-DD", date), "2024-01-05");
+        assert_eq!(expand_with_bare_dates("YY/MM", date), "24/01");
+        assert_eq!(expand_with_bare_dates("{YYYY}-MM {HH}", date), "2024-01 09");
+    }
+
+    #[test]
+    fn test_week_uses_iso_year() {
+        // Friday of the last ISO week of 2020
+        let new_year = Local.with_ymd_and_hms(2021, 1, 1, 12, 0, 0).unwrap();
+        assert_eq!(expand("{YYYY}/{WW}", new_year), "2020/53");
+        assert_eq!(expand_with_bare_dates("YY-WW", new_year), "20-53");
+        // Monday of the first ISO week of 2025
+        let year_end = Local.with_ymd_and_hms(2024, 12, 30, 12, 0, 0).unwrap();
+        assert_eq!(expand("{YYYY}/{WW}", year_end), "2025/01");
+        assert_eq!(expand_with_bare_dates("YYYY/WW", year_end), "2025/01");
+        // Without a week the year is the calendar year
+        assert_eq!(expand_with_bare_dates("YYYY-MM-DD", new_year), "2021-01-01");
+        assert_eq!(expand("{YYYY} WW", year_end), "2024 WW");
+    }
+
+    #[test]
+    fn test_validate_pattern() {
+        assert_eq!(validate_pattern(DEFAULT_PATTERN), Ok(()));
+        assert_eq!(validate_pattern("  "), Err(PatternError::Empty));
+        assert_eq!(validate_pattern("shot:{HH}"), Err(PatternError::IllegalChar(':')));
+        assert_eq!(validate_pattern("a/b"), Err(PatternError::IllegalChar('/')));
+        assert_eq!(
+            validate_pattern("{YYYY}-{Q}"),
+            Err(PatternError::UnknownToken("Q".to_string()))
+        );
+        assert_eq!(validate_pattern("{YYYY"), Err(PatternError::Unclosed));
+    }
+
+    #[test]
+    fn test_file_name_sanitizes() {
+        let date = sample_date();
+        assert_eq!(file_name("{YYYY}.", date, "png"), "2024.png");
+        assert_eq!(file_name("CON", date, "png"), "_CON.png");
+        assert_eq!(file_name("a|b", date, ""), "a_b");
+    }
+
+    #[test]
+    fn test_new_file_path_collision_suffix() {
+        let dir = scratch_dir("naming");
+
+        let date = sample_date();
+        let first = new_file_path(&dir, "Shot {HH}{mm}", date, "png").unwrap();
+        assert_eq!(first.file_name().unwrap().to_string_lossy(), "Shot 0907.png");
+        fs::write(&first, b"one").unwrap();
+
+        let second = new_file_path(&dir, "Shot {HH}{mm}", date, "png").unwrap();
+        assert_eq!(second.file_name().unwrap().to_string_lossy(), "Shot 0907_1.png");
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_rename_to_pattern() {
+        let dir = scratch_dir("naming-rename");
+
+        let shot = dir.join("Screenshot (12).png");
+        fs::write(&shot, b"png").unwrap();
+        let modified = filetime::FileTime::from_system_time(sample_date().into());
+        filetime::set_file_mtime(&shot, modified).unwrap();
+
+        let renamed = rename_to_pattern(&shot, "Shot {HH}{mm}").unwrap().unwrap();
+        assert_eq!(renamed, dir.join("Shot 0907.png"));
+        assert!(!shot.exists());
+        // Already named after the pattern: left alone
+        assert_eq!(rename_to_pattern(&renamed, "Shot {HH}{mm}").unwrap(), None);
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,182 @@
+//! Counts and file sizes written the way the reader expects
+//!
+//! Digit grouping and the decimal mark follow the chosen app language, or
+//! the Windows user locale while the language is automatic (so an English UI
+//! on a German system still shows 12.345). Unit names go through the locale
+//! files (`common.units`). Sizes are IEC (KiB = 1024 bytes) unless decimal
+//! units are turned on, which gives Explorer-style KB and MB.
+
+use parking_lot::Mutex;
+use rust_i18n::t;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Locale tag numbers are formatted for ("en", "de-DE", ...)
+static LOCALE: Mutex<String> = Mutex::new(String::new());
+
+/// Show sizes in KB/MB/GB (powers of 1000) instead of KiB/MiB/GiB
+static DECIMAL_UNITS: AtomicBool = AtomicBool::new(false);
+
+/// Digit group and decimal separators of a locale
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Separators {
+    pub group: char,
+    pub decimal: char,
+}
+
+impl Separators {
+    /// Separators for a locale tag; unknown locales read like English
+    pub fn for_locale(tag: &str) -> Self {
+        let tag = tag.replace('_', "-");
+        let language = tag.split('-').next().unwrap_or("").to_ascii_lowercase();
+        let (group, decimal) = match (language.as_str(), tag.to_ascii_lowercase().as_str()) {
+            (_, "de-ch" | "it-ch") => ('\u{2019}', '.'),
+            (_, "es-mx" | "es-us") => (',', '.'),
+            ("de" | "es" | "it" | "nl" | "pt" | "id" | "tr" | "da" | "el" | "ro" | "hr" | "sl", _) => {
+                ('.', ',')
+            }
+            ("fr" | "ru" | "uk" | "pl" | "cs" | "sk" | "sv" | "fi" | "nb" | "no" | "hu" | "bg", _) => {
+                ('\u{00A0}', ',')
+            }
+            _ => (',', '.'),
+        };
+        Self { group, decimal }
+    }
+
+    /// Separators numbers are currently formatted with
+    pub fn current() -> Self {
+        let locale = LOCALE.lock();
+        if locale.is_empty() {
+            Self::for_locale(&rust_i18n::locale())
+        } else {
+            Self::for_locale(&locale)
+        }
+    }
+}
+
+/// Set the locale numbers follow: the app language when one is picked, the
+/// Windows user locale while it's automatic
+pub fn set_locale(tag: &str) {
+    *LOCALE.lock() = tag.to_string();
+}
+
+/// Set whether sizes use decimal units (KB, MB) instead of IEC (KiB, MiB)
+pub fn set_decimal_units(enabled: bool) {
+    DECIMAL_UNITS.store(enabled, Ordering::Relaxed);
+}
+
+pub fn decimal_units() -> bool {
+    DECIMAL_UNITS.load(Ordering::Relaxed)
+}
+
+/// A count with its digits grouped ("12,345")
+pub fn format_count(count: usize) -> String {
+    group_digits(count as u64, Separators::current())
+}
+
+/// A file size in the current units, language and separators
+pub fn format_file_size(bytes: u64) -> String {
+    file_size_in(bytes, decimal_units(), Separators::current(), &rust_i18n::locale())
+}
+
+fn group_digits(value: u64, separators: Separators) -> String {
+    let digits = value.to_string();
+    let mut grouped = String::with_capacity(digits.len() + digits.len() / 3);
+    for (i, digit) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i) % 3 == 0 {
+            grouped.push(separators.group);
+        }
+        grouped.push(digit);
+    }
+    grouped
+}
+
+/// `value` with one decimal place ("1,234.5")
+fn one_decimal(value: f64, separators: Separators) -> String {
+    let tenths = (value * 10.0).round() as u64;
+    format!(
+        "{}{}{}",
+        group_digits(tenths / 10, separators),
+        separators.decimal,
+        tenths % 10
+    )
+}
+
+/// Bytes and KiB/KB as whole numbers, bigger units with one decimal place
+fn file_size_in(bytes: u64, decimal: bool, separators: Separators, language: &str) -> String {
+    let base: u64 = if decimal { 1000 } else { 1024 };
+    let (kilo, mega, giga) = (base, base * base, base * base * base);
+    let unit = |key: &str, value: String| {
+        let key = if decimal {
+            format!("common.units.decimal.{key}")
+        } else {
+            format!("common.units.iec.{key}")
+        };
+        t!(&key, locale = language, value = value).to_string()
+    };
+
+    if bytes >= giga {
+        unit("giga", one_decimal(bytes as f64 / giga as f64, separators))
+    } else if bytes >= mega {
+        unit("mega", one_decimal(bytes as f64 / mega as f64, separators))
+    } else if bytes >= kilo {
+        unit("kilo", group_digits(bytes / kilo, separators))
+    } else {
+        t!("common.units.bytes", locale = language, value = group_digits(bytes, separators)).to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_separators_for_locale() {
+        let english = Separators::for_locale("en");
+        assert_eq!(Separators::for_locale("ko"), english);
+        assert_eq!(Separators::for_locale("ja-JP"), english);
+        assert_eq!(Separators::for_locale("de-DE"), Separators { group: '.', decimal: ',' });
+        assert_eq!(Separators::for_locale("de_CH").decimal, '.');
+        assert_eq!(Separators::for_locale("fr-FR").group, '\u{00A0}');
+        assert_eq!(Separators::for_locale(""), english);
+    }
+
+    #[test]
+    fn test_group_digits() {
+        let english = Separators::for_locale("en");
+        assert_eq!(group_digits(0, english), "0");
+        assert_eq!(group_digits(999, english), "999");
+        assert_eq!(group_digits(12_345, english), "12,345");
+        assert_eq!(group_digits(1_234_567, english), "1,234,567");
+        assert_eq!(group_digits(12_345, Separators::for_locale("de")), "12.345");
+    }
+
+    #[test]
+    fn test_file_size_iec() {
+        let english = Separators::for_locale("en");
+        assert_eq!(file_size_in(512, false, english, "en"), "512 B");
+        assert_eq!(file_size_in(2048, false, english, "en"), "2 KiB");
+        assert_eq!(file_size_in(1_572_864, false, english, "en"), "1.5 MiB");
+        assert_eq!(file_size_in(5 * 1024 * 1024 * 1024, false, english, "en"), "5.0 GiB");
+        // Grouped when a unit runs into the thousands
+        assert_eq!(file_size_in(1023 * 1024 * 1024, false, english, "en"), "1,023.0 MiB");
+
+        assert_eq!(file_size_in(512, false, english, "ja"), "512 バイト");
+        assert_eq!(file_size_in(512, false, english, "ko"), "512 바이트");
+        assert_eq!(file_size_in(1_572_864, false, english, "ko"), "1.5 MiB");
+        assert_eq!(
+            file_size_in(1_572_864, false, Separators::for_locale("de-DE"), "en"),
+            "1,5 MiB"
+        );
+    }
+
+    #[test]
+    fn test_file_size_decimal() {
+        let english = Separators::for_locale("en");
+        assert_eq!(file_size_in(999, true, english, "en"), "999 B");
+        assert_eq!(file_size_in(2048, true, english, "en"), "2 KB");
+        assert_eq!(file_size_in(1_500_000, true, english, "en"), "1.5 MB");
+        assert_eq!(file_size_in(2_500_000_000, true, english, "ja"), "2.5 GB");
+        assert_eq!(file_size_in(1_500_000, true, english, "ko"), "1.5 MB");
+        assert_eq!(file_size_in(1_200, true, english, "ja"), "1 KB");
+    }
+}
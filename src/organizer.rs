@@ -6,27 +6,61 @@ use crossbeam_channel::Sender;
 use log::{error, info};
 use std::fs;
 use std::path::{Path, PathBuf};
-
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use crate::exclude;
+use crate::file_ops;
+use crate::filename_date;
+use crate::journal::{self, Journal};
+use crate::naming;
+use crate::paths;
+use crate::pipeline::{self, Transition};
+use crate::timeline::{self, EventKind};
 use crate::AppMessage;
 
+/// Folder formats offered in settings; re-organizing recognizes folders in any of them
+pub const KNOWN_FORMATS: &[&str] = &["YYYY-MM-DD", "YYYY-MM", "YYYY/MM/DD"];
+
 /// Format a date according to the user-specified format string.
-/// Supports: YYYY, YY, MM, DD, and common separators (-, /, .)
+/// Supports YYYY, YY, MMM (short month name), MM, WW (ISO week), DD and HH
+/// (24-hour), bare or braced, plus the other `naming` tokens in braces.
+/// Anything else is kept as it is; `/` makes nested folders.
 ///
 /// Examples:
 /// - "YYYY-MM-DD" -> "2024-01-15"
 /// - "YYYY/MM/DD" -> "2024/01/15"
 /// - "YYYY-MM" -> "2024-01"
 /// - "YY-MM-DD" -> "24-01-15"
+/// - "Screenshots YYYY/MMM/DD HH" -> "Screenshots 2024/Jan/15 10"
 pub fn format_date(date: DateTime<Local>, format: &str) -> String {
-    let mut result = format.to_string();
-
-    // Replace tokens with actual values
-    result = result.replace("YYYY", &date.format("%Y").to_string());
-    result = result.replace("YY", &date.format("%y").to_string());
-    result = result.replace("MM", &date.format("%m").to_string());
-    result = result.replace("DD", &date.format("%d").to_string());
+    naming::expand_with_bare_dates(format, date)
+}
 
-    result
+/// Check a folder format typed in settings before saving it: it must name at
+/// least one folder, and nothing in it may be illegal in a Windows path
+/// (`/` and `\` separate folders). Folder names Windows would refuse or
+/// silently change are rejected too, or re-organizing couldn't find the
+/// folders again.
+pub fn validate_format(format: &str) -> Result<(), naming::PatternError> {
+    if format.split(['/', '\\']).all(|part| part.trim().is_empty()) {
+        return Err(naming::PatternError::Empty);
+    }
+    if let Some(c) = format
+        .chars()
+        .find(|&c| c != '/' && c != '\\' && paths::is_illegal_file_name_char(c))
+    {
+        return Err(naming::PatternError::IllegalChar(c));
+    }
+    // "." and ".." end in a dot as well
+    match format
+        .split(['/', '\\'])
+        .filter(|part| !part.is_empty())
+        .find(|part| part.ends_with(['.', ' ']) || paths::is_reserved_name(part))
+    {
+        Some(part) => Err(naming::PatternError::InvalidName(part.to_string())),
+        None => Ok(()),
+    }
 }
 
 /// Organize a screenshot file by moving it to a date-based subdirectory.
@@ -41,6 +75,33 @@ pub fn format_date(date: DateTime<Local>, format: &str) -> String {
 /// * `Ok(None)` - File is already organized or in a subdirectory
 /// * `Err(_)` - Error occurred
 pub fn organize_file(file_path: &Path, base_dir: &Path, format: &str) -> Result<Option<PathBuf>> {
+    organize_file_journaled(file_path, base_dir, format, None)
+}
+
+/// [`organize_file`], recording the move in `journal` (when given) before renaming
+fn organize_file_journaled(
+    file_path: &Path,
+    base_dir: &Path,
+    format: &str,
+    journal: Option<&mut Journal>,
+) -> Result<Option<PathBuf>> {
+    let result = organize_file_inner(file_path, base_dir, format, journal);
+    match &result {
+        Ok(Some(target_path)) => {
+            timeline::record_moved(file_path, target_path, EventKind::Organized(target_path.clone()))
+        }
+        Ok(None) => {}
+        Err(e) => timeline::record(file_path, EventKind::OrganizeFailed(format!("{:#}", e))),
+    }
+    result
+}
+
+fn organize_file_inner(
+    file_path: &Path,
+    base_dir: &Path,
+    format: &str,
+    journal: Option<&mut Journal>,
+) -> Result<Option<PathBuf>> {
     // Only organize files that are directly in the base directory
     let file_parent = file_path.parent();
     if file_parent != Some(base_dir) {
@@ -48,76 +109,481 @@ pub fn organize_file(file_path: &Path, base_dir: &Path, format: &str) -> Result<
         return Ok(None);
     }
 
-    // Get file modification time
-    let metadata = fs::metadata(file_path)?;
-    let modified = metadata.modified()?;
-    let datetime: DateTime<Local> = modified.into();
+    // Capture date from the file name when enabled, else the modification time
+    let metadata = fs::metadata(paths::long_path(file_path))?;
+    let captured = filename_date::capture_time(file_path, metadata.modified()?);
+    let datetime: DateTime<Local> = captured.into();
 
     // Create subdirectory name from format
-    let subdir_name = format_date(datetime, format);
-    let target_dir = base_dir.join(&subdir_name);
+    let target_dir = base_dir.join(subdir_for_date(datetime, format));
 
     // Create subdirectory if it doesn't exist
-    if !target_dir.exists() {
-        fs::create_dir_all(&target_dir)?;
+    if !paths::long_path(&target_dir).exists() {
+        fs::create_dir_all(paths::long_path(&target_dir))?;
         info!("Created organizer directory: {:?}", target_dir);
     }
 
-    // Build target path
-    let file_name = file_path
-        .file_name()
+    // Build target path, suffixing the name if it's already taken
+    let stem = file_path
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_string())
         .ok_or_else(|| anyhow::anyhow!("Invalid file name"))?;
-    let target_path = target_dir.join(file_name);
-
-    // Check if target already exists
-    if target_path.exists() {
-        // Generate unique name by appending number
-        let stem = file_path
-            .file_stem()
-            .and_then(|s| s.to_str())
-            .unwrap_or("file");
-        let ext = file_path.extension().and_then(|e| e.to_str()).unwrap_or("");
-
-        let mut counter = 1;
-        let mut unique_path = target_path.clone();
-        while unique_path.exists() {
-            let new_name = if ext.is_empty() {
-                format!("{}_{}", stem, counter)
-            } else {
-                format!("{}_{}.{}", stem, counter, ext)
-            };
-            unique_path = target_dir.join(new_name);
-            counter += 1;
-
-            // Safety limit
-            if counter > 1000 {
-                return Err(anyhow::anyhow!("Too many duplicate files"));
-            }
-        }
-        // Move file
-        fs::rename(file_path, &unique_path)?;
-        info!("Organized (renamed): {:?} -> {:?}", file_path, unique_path);
-        return Ok(Some(unique_path));
-    }
+    let ext = file_path
+        .extension()
+        .map(|e| e.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let target_path = naming::unique_path(&target_dir, &stem, &ext)?;
 
     // Move file
-    fs::rename(file_path, &target_path)?;
+    journaled_rename(file_path, &target_path, journal)?;
     info!("Organized: {:?} -> {:?}", file_path, target_path);
 
     Ok(Some(target_path))
 }
 
+/// Rename `from` to `to`, bracketing it with plan/done journal entries
+fn journaled_rename(from: &Path, to: &Path, journal: Option<&mut Journal>) -> Result<()> {
+    match journal {
+        Some(journal) => {
+            journal.plan(from, to)?;
+            file_ops::rename(from, to)?;
+            // The file did move; a missing "done" is settled by recovery
+            if let Err(e) = journal.done(from) {
+                error!("Failed to journal finished move of {:?}: {}", from, e);
+            }
+            Ok(())
+        }
+        None => Ok(file_ops::rename(from, to)?),
+    }
+}
+
+/// Build the relative subdirectory for a date, sanitizing each path component.
+/// `/` in the format still creates nested folders.
+fn subdir_for_date(date: DateTime<Local>, format: &str) -> PathBuf {
+    format_date(date, format)
+        .split(['/', '\\'])
+        .filter(|part| !part.is_empty())
+        .map(paths::sanitize_file_name)
+        .collect()
+}
+
 /// Get example output for a format string using current date.
 pub fn format_preview(format: &str) -> String {
     format_date(Local::now(), format)
 }
 
+/// Check whether a folder name matches one component of a format
+/// (e.g. "2024-01" matches "YYYY-MM")
+fn matches_format_component(name: &str, format: &str) -> bool {
+    let mut name = name;
+    let mut format = format;
+    let digits: fn(&str) -> bool = |s| s.bytes().all(|b| b.is_ascii_digit());
+    let letters: fn(&str) -> bool = |s| s.bytes().all(|b| b.is_ascii_alphabetic());
+
+    'outer: while !format.is_empty() {
+        // Longer tokens first, so "MMM" isn't read as "MM"
+        for (token, len, matches) in [
+            ("YYYY", 4, digits),
+            ("YY", 2, digits),
+            ("MMM", 3, letters),
+            ("MM", 2, digits),
+            ("WW", 2, digits),
+            ("DD", 2, digits),
+            ("HH", 2, digits),
+        ] {
+            if let Some(rest) = format.strip_prefix(token) {
+                if !name.get(..len).is_some_and(matches) {
+                    return false;
+                }
+                name = &name[len..];
+                format = rest;
+                continue 'outer;
+            }
+        }
+
+        let ch = format.chars().next().unwrap_or_default();
+        match name.strip_prefix(ch) {
+            Some(rest) => name = rest,
+            None => return false,
+        }
+        format = &format[ch.len_utf8()..];
+    }
+
+    name.is_empty()
+}
+
+/// Check whether a folder (relative to the base directory) is a date folder in `format`
+fn matches_format(relative_dir: &Path, format: &str) -> bool {
+    let names: Vec<String> = relative_dir
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy().to_string())
+        .collect();
+    let parts: Vec<&str> = format.split(['/', '\\']).filter(|p| !p.is_empty()).collect();
+
+    names.len() == parts.len()
+        && names
+            .iter()
+            .zip(&parts)
+            .all(|(name, part)| matches_format_component(name, part))
+}
+
+/// Deepest nesting of any of `formats` (3 for YYYY/MM/DD)
+fn max_format_depth(formats: &[&str]) -> usize {
+    formats
+        .iter()
+        .map(|f| f.split(['/', '\\']).filter(|p| !p.is_empty()).count())
+        .max()
+        .unwrap_or(1)
+}
+
+/// The preset formats plus `format`, which may be one typed in settings
+fn known_formats_and(format: &str) -> Vec<&str> {
+    let mut formats = KNOWN_FORMATS.to_vec();
+    if !formats.contains(&format) {
+        formats.push(format);
+    }
+    formats
+}
+
+/// Collect date folders under `base_dir` matching any of `formats`, deepest first
+fn collect_date_dirs(base_dir: &Path, formats: &[&str]) -> Vec<PathBuf> {
+    let max_depth = max_format_depth(formats);
+    let mut found = Vec::new();
+    let mut pending = vec![(base_dir.to_path_buf(), 0)];
+
+    while let Some((dir, depth)) = pending.pop() {
+        if depth >= max_depth {
+            continue;
+        }
+        let Ok(entries) = fs::read_dir(paths::long_path(&dir)) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let path = dir.join(entry.file_name());
+            if !entry.file_type().is_ok_and(|t| t.is_dir()) {
+                continue;
+            }
+            pending.push((path.clone(), depth + 1));
+            if let Ok(relative) = path.strip_prefix(base_dir) {
+                if formats.iter().any(|f| matches_format(relative, f)) {
+                    found.push(path);
+                }
+            }
+        }
+    }
+
+    found.sort_by_key(|dir| std::cmp::Reverse(dir.components().count()));
+    found
+}
+
+/// Move one already-organized file into the folder `format` gives it.
+/// Returns `Ok(None)` if it's already in the right folder.
+fn reorganize_file(
+    file_path: &Path,
+    base_dir: &Path,
+    format: &str,
+    journal: Option<&mut Journal>,
+) -> Result<Option<PathBuf>> {
+    let modified = fs::metadata(paths::long_path(file_path))?.modified()?;
+    let captured = filename_date::capture_time(file_path, modified);
+    let target_dir = base_dir.join(subdir_for_date(captured.into(), format));
+    if file_path.parent() == Some(target_dir.as_path()) {
+        return Ok(None);
+    }
+
+    fs::create_dir_all(paths::long_path(&target_dir))?;
+    let stem = file_path
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .ok_or_else(|| anyhow::anyhow!("Invalid file name"))?;
+    let ext = file_path
+        .extension()
+        .map(|e| e.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let target_path = naming::unique_path(&target_dir, &stem, &ext)?;
+
+    journaled_rename(file_path, &target_path, journal)?;
+    info!("Re-organized: {:?} -> {:?}", file_path, target_path);
+    Ok(Some(target_path))
+}
+
+/// Remove date folders that ended up empty, deepest first, along with
+/// parents left empty by them (e.g. "2024/01" after "2024/01/15")
+fn remove_empty_dirs(base_dir: &Path, dirs: &[PathBuf]) {
+    for dir in dirs {
+        let mut current = Some(dir.as_path());
+        while let Some(dir) = current.filter(|d| *d != base_dir && d.starts_with(base_dir)) {
+            let is_empty = fs::read_dir(paths::long_path(dir))
+                .map(|mut entries| entries.next().is_none())
+                .unwrap_or(false);
+            if !is_empty {
+                break;
+            }
+            match fs::remove_dir(paths::long_path(dir)) {
+                Ok(()) => info!("Removed empty folder: {:?}", dir),
+                Err(e) => {
+                    error!("Failed to remove {:?}: {}", dir, e);
+                    break;
+                }
+            }
+            current = dir.parent();
+        }
+    }
+}
+
+/// Move files from existing date folders (`previous_format`'s or any known
+/// format's) into `format`'s scheme. Emptied folders are deleted. Sends
+/// progress via the Organize* messages and stops early when `cancel` is set.
+/// Runs in a background thread.
+pub fn reorganize_existing_files(
+    base_dir: PathBuf,
+    previous_format: String,
+    format: String,
+    cancel: Arc<AtomicBool>,
+    message_tx: Sender<AppMessage>,
+) {
+    std::thread::spawn(move || {
+        info!("Re-organizing {:?} from {} into {}", base_dir, previous_format, format);
+
+        let date_dirs = collect_date_dirs(&base_dir, &known_formats_and(&previous_format));
+        let files: Vec<PathBuf> = date_dirs
+            .iter()
+            .filter(|dir| {
+                // Folders already in the new scheme stay put
+                dir.strip_prefix(&base_dir)
+                    .map(|relative| !matches_format(relative, &format))
+                    .unwrap_or(false)
+            })
+            .filter_map(|dir| fs::read_dir(paths::long_path(dir)).ok().map(|e| (dir, e)))
+            .flat_map(|(dir, entries)| {
+                entries
+                    .flatten()
+                    .map(|entry| dir.join(entry.file_name()))
+                    .filter(|path| is_image_file(path))
+                    .collect::<Vec<_>>()
+            })
+            .collect();
+
+        let total = files.len();
+        if total == 0 {
+            info!("No files to re-organize");
+            let _ = message_tx.send(AppMessage::OrganizeCompleted);
+            return;
+        }
+
+        let _ = message_tx.send(AppMessage::OrganizeStarted(total));
+        let mut journal = open_journal();
+
+        for (index, file_path) in files.iter().enumerate() {
+            if cancel.load(Ordering::Relaxed) {
+                info!("Re-organization canceled after {} of {} files", index, total);
+                break;
+            }
+
+            let _ = message_tx.send(AppMessage::OrganizeProgress(index + 1, total, file_path.clone()));
+
+            match reorganize_file(file_path, &base_dir, &format, journal.as_mut()) {
+                Ok(Some(new_path)) => {
+                    timeline::record_moved(file_path, &new_path, EventKind::Organized(new_path.clone()));
+                    report_moved(file_path, &new_path, &message_tx);
+                }
+                Ok(None) => {}
+                Err(e) => {
+                    error!("Failed to re-organize {:?}: {}", file_path, e);
+                    timeline::record(file_path, EventKind::OrganizeFailed(format!("{:#}", e)));
+                    let _ = message_tx.send(AppMessage::OrganizeFileFailed(file_path.clone(), format!("{:#}", e)));
+                }
+            }
+        }
+
+        if let Some(journal) = journal {
+            journal.finish();
+        }
+        remove_empty_dirs(&base_dir, &date_dirs);
+
+        let _ = message_tx.send(AppMessage::OrganizeCompleted);
+        info!("Re-organization finished");
+    });
+}
+
+/// Move one organized file back into the base directory, suffixing its
+/// name if one there already has it
+fn flatten_file(file_path: &Path, base_dir: &Path, journal: Option<&mut Journal>) -> Result<PathBuf> {
+    let stem = file_path
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .ok_or_else(|| anyhow::anyhow!("Invalid file name"))?;
+    let ext = file_path
+        .extension()
+        .map(|e| e.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let target_path = naming::unique_path(base_dir, &stem, &ext)?;
+
+    journaled_rename(file_path, &target_path, journal)?;
+    info!("Flattened: {:?} -> {:?}", file_path, target_path);
+    Ok(target_path)
+}
+
+/// Images in date folders that `format` or any known format would make,
+/// with the folders, deepest first
+fn collect_organized_files(base_dir: &Path, format: &str) -> (Vec<PathBuf>, Vec<PathBuf>) {
+    let date_dirs = collect_date_dirs(base_dir, &known_formats_and(format));
+    let files = date_dirs
+        .iter()
+        .filter_map(|dir| fs::read_dir(paths::long_path(dir)).ok().map(|e| (dir, e)))
+        .flat_map(|(dir, entries)| {
+            entries
+                .flatten()
+                .map(|entry| dir.join(entry.file_name()))
+                .filter(|path| is_image_file(path))
+                .collect::<Vec<_>>()
+        })
+        .collect();
+    (files, date_dirs)
+}
+
+/// Undo organizing: move the images in date folders (`format`'s or any
+/// known format's) back into the base directory and delete the folders
+/// left empty. Folders with other names are left alone. Sends progress via
+/// the Organize* messages and stops early when `cancel` is set. Runs in a
+/// background thread.
+pub fn flatten_organized_files(
+    base_dir: PathBuf,
+    format: String,
+    cancel: Arc<AtomicBool>,
+    message_tx: Sender<AppMessage>,
+) {
+    std::thread::spawn(move || {
+        info!("Flattening date folders in {:?}", base_dir);
+
+        let (files, date_dirs) = collect_organized_files(&base_dir, &format);
+        let total = files.len();
+        if total == 0 {
+            info!("No organized files to flatten");
+            remove_empty_dirs(&base_dir, &date_dirs);
+            let _ = message_tx.send(AppMessage::OrganizeCompleted);
+            return;
+        }
+
+        let _ = message_tx.send(AppMessage::OrganizeStarted(total));
+        let mut journal = open_journal();
+
+        for (index, file_path) in files.iter().enumerate() {
+            if cancel.load(Ordering::Relaxed) {
+                info!("Flattening canceled after {} of {} files", index, total);
+                break;
+            }
+
+            let _ = message_tx.send(AppMessage::OrganizeProgress(index + 1, total, file_path.clone()));
+
+            match flatten_file(file_path, &base_dir, journal.as_mut()) {
+                Ok(new_path) => {
+                    timeline::record_moved(file_path, &new_path, EventKind::Organized(new_path.clone()));
+                    report_moved(file_path, &new_path, &message_tx);
+                }
+                Err(e) => {
+                    error!("Failed to flatten {:?}: {}", file_path, e);
+                    timeline::record(file_path, EventKind::OrganizeFailed(format!("{:#}", e)));
+                    let _ = message_tx.send(AppMessage::OrganizeFileFailed(file_path.clone(), format!("{:#}", e)));
+                }
+            }
+        }
+
+        if let Some(journal) = journal {
+            journal.finish();
+        }
+        remove_empty_dirs(&base_dir, &date_dirs);
+
+        let _ = message_tx.send(AppMessage::OrganizeCompleted);
+        info!("Flattening finished");
+    });
+}
+
+/// How organizing a picked set of files went
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct OrganizeSummary {
+    pub organized: usize,
+    /// Already in a subdirectory of the base directory (or outside it)
+    pub skipped: usize,
+    pub failed: usize,
+    pub canceled: bool,
+}
+
+/// Organize just the picked `files` into `format`'s date folders, whether
+/// or not the organizer is on. Files that aren't directly in `base_dir` are
+/// skipped. Sends progress via the Organize* messages, then
+/// `SelectionOrganized`; stops early when `cancel` is set. Runs in a
+/// background thread.
+pub fn organize_selected_files(
+    files: Vec<PathBuf>,
+    base_dir: PathBuf,
+    format: String,
+    cancel: Arc<AtomicBool>,
+    message_tx: Sender<AppMessage>,
+) {
+    std::thread::spawn(move || {
+        let total = files.len();
+        info!("Organizing {} selected files in {:?}", total, base_dir);
+        let _ = message_tx.send(AppMessage::OrganizeStarted(total));
+
+        let mut summary = OrganizeSummary::default();
+        let mut journal = open_journal();
+
+        for (index, file_path) in files.iter().enumerate() {
+            if cancel.load(Ordering::Relaxed) {
+                info!("Organizing selection canceled after {} of {} files", index, total);
+                summary.canceled = true;
+                break;
+            }
+
+            let _ = message_tx.send(AppMessage::OrganizeProgress(index + 1, total, file_path.clone()));
+
+            match organize_file_journaled(file_path, &base_dir, &format, journal.as_mut()) {
+                Ok(Some(new_path)) => {
+                    summary.organized += 1;
+                    report_moved(file_path, &new_path, &message_tx);
+                }
+                Ok(None) => summary.skipped += 1,
+                Err(e) => {
+                    error!("Failed to organize {:?}: {}", file_path, e);
+                    summary.failed += 1;
+                    let _ = message_tx.send(AppMessage::OrganizeFileFailed(file_path.clone(), format!("{:#}", e)));
+                }
+            }
+        }
+
+        if let Some(journal) = journal {
+            journal.finish();
+        }
+
+        info!(
+            "Organized selection: {} moved, {} skipped, {} failed",
+            summary.organized, summary.skipped, summary.failed
+        );
+        let _ = message_tx.send(AppMessage::SelectionOrganized(summary));
+    });
+}
+
+/// Report a file a batch moved. It reaches the UI as a rename, ahead of the
+/// watcher's, so the tile, last-used time and index rows follow it; as a
+/// removal they'd be dropped.
+pub fn report_moved(from: &Path, to: &Path, message_tx: &Sender<AppMessage>) {
+    pipeline::report(
+        Transition::Moved {
+            from: from.to_path_buf(),
+            to: to.to_path_buf(),
+        },
+        message_tx,
+    );
+    let _ = message_tx.send(AppMessage::ScreenshotRenamed(from.to_path_buf(), to.to_path_buf()));
+}
+
 /// Image extensions we care about
 const IMAGE_EXTENSIONS: &[&str] = &["png", "jpg", "jpeg", "gif", "bmp", "webp", "avif"];
 
-/// Check if a path is an image file
+/// Check if a path is an image file that isn't excluded
 fn is_image_file(path: &Path) -> bool {
-    if !path.is_file() {
+    if !paths::long_path(path).is_file() || exclude::is_excluded(path) {
         return false;
     }
     path.extension()
@@ -129,10 +595,27 @@ fn is_image_file(path: &Path) -> bool {
         })
 }
 
+/// Open the crash journal for a batch. Without one the batch still runs, just unjournaled.
+fn open_journal() -> Option<Journal> {
+    let path = journal::default_path()?;
+    match Journal::open(&path) {
+        Ok(journal) => Some(journal),
+        Err(e) => {
+            error!("Failed to open organizer journal {:?}: {}", path, e);
+            None
+        }
+    }
+}
+
 /// Organize all existing files in the base directory.
-/// Sends progress updates via the message channel.
-/// This function runs in a background thread.
-pub fn organize_existing_files(base_dir: PathBuf, format: String, message_tx: Sender<AppMessage>) {
+/// Sends progress updates via the message channel and stops early when
+/// `cancel` is set. This function runs in a background thread.
+pub fn organize_existing_files(
+    base_dir: PathBuf,
+    format: String,
+    cancel: Arc<AtomicBool>,
+    message_tx: Sender<AppMessage>,
+) {
     std::thread::spawn(move || {
         info!("Starting organization of existing files in {:?}", base_dir);
 
@@ -165,35 +648,35 @@ pub fn organize_existing_files(base_dir: PathBuf, format: String, message_tx: Se
 
         // Send start message
         let _ = message_tx.send(AppMessage::OrganizeStarted(total));
+        let mut journal = open_journal();
 
         // Organize each file
         for (index, file_path) in files_to_organize.iter().enumerate() {
-            let file_name = file_path
-                .file_name()
-                .and_then(|n| n.to_str())
-                .unwrap_or("unknown")
-                .to_string();
+            if cancel.load(Ordering::Relaxed) {
+                info!("Organization canceled after {} of {} files", index, total);
+                break;
+            }
 
             // Send progress update
             let _ = message_tx.send(AppMessage::OrganizeProgress(
                 index + 1,
                 total,
-                file_name.clone(),
+                file_path.clone(),
             ));
 
             // Organize the file
-            match organize_file(file_path, &base_dir, &format) {
+            match organize_file_journaled(file_path, &base_dir, &format, journal.as_mut()) {
                 Ok(Some(new_path)) => {
                     info!("Organized: {:?} -> {:?}", file_path, new_path);
-                    // Notify about the file move (don't auto-index, just organizing existing files)
-                    let _ = message_tx.send(AppMessage::ScreenshotRemoved(file_path.clone()));
-                    let _ = message_tx.send(AppMessage::NewScreenshot(new_path, false));
+                    // A move, not a new file: not auto-indexed, keeps its index rows
+                    report_moved(file_path, &new_path, &message_tx);
                 }
                 Ok(None) => {
                     // File was already organized, skip
                 }
                 Err(e) => {
                     error!("Failed to organize {:?}: {}", file_path, e);
+                    let _ = message_tx.send(AppMessage::OrganizeFileFailed(file_path.clone(), format!("{:#}", e)));
                 }
             }
 
@@ -201,6 +684,10 @@ pub fn organize_existing_files(base_dir: PathBuf, format: String, message_tx: Se
             std::thread::sleep(std::time::Duration::from_millis(50));
         }
 
+        if let Some(journal) = journal {
+            journal.finish();
+        }
+
         // Send completion message
         let _ = message_tx.send(AppMessage::OrganizeCompleted);
         info!("Organization completed: {} files processed", total);
@@ -210,7 +697,9 @@ pub fn organize_existing_files(base_dir: PathBuf, format: String, message_tx: Se
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::test_support::scratch_dir;
     use chrono::TimeZone;
+    use std::collections::HashSet;
 
     #[test]
     fn test_format_date() {
@@ -223,6 +712,47 @@ mod tests {
         assert_eq!(format_date(date, "YYYY.MM.DD"), "2024.01.15");
     }
 
+    #[test]
+    fn test_format_date_new_tokens() {
+        let date = Local.with_ymd_and_hms(2024, 1, 15, 9, 30, 0).unwrap();
+
+        assert_eq!(format_date(date, "YYYY-MM-DD/HH"), "2024-01-15/09");
+        assert_eq!(format_date(date, "YYYY/MMM"), "2024/Jan");
+        assert_eq!(format_date(date, "YYYY/WW"), "2024/03");
+        assert_eq!(format_date(date, "{YYYY} {MMM} {HH}"), "2024 Jan 09");
+        // Text that isn't a token passes through
+        assert_eq!(format_date(date, "Screenshots YYYY/MM"), "Screenshots 2024/01");
+
+        // ISO weeks: Jan 1st 2021 is in week 53 of 2020
+        let date = Local.with_ymd_and_hms(2021, 1, 1, 23, 0, 0).unwrap();
+        assert_eq!(format_date(date, "WW HH"), "53 23");
+    }
+
+    #[test]
+    fn test_validate_format() {
+        assert_eq!(validate_format("YYYY-MM-DD"), Ok(()));
+        assert_eq!(validate_format("Screenshots YYYY/MM/HH"), Ok(()));
+        assert_eq!(validate_format("YYYY\\MMM"), Ok(()));
+
+        assert_eq!(validate_format("YYYY:MM"), Err(naming::PatternError::IllegalChar(':')));
+        assert_eq!(validate_format("YYYY/MM?"), Err(naming::PatternError::IllegalChar('?')));
+        assert_eq!(validate_format("<YYYY>"), Err(naming::PatternError::IllegalChar('<')));
+        assert_eq!(validate_format(""), Err(naming::PatternError::Empty));
+        assert_eq!(validate_format(" / "), Err(naming::PatternError::Empty));
+
+        let invalid = |name: &str| Err(naming::PatternError::InvalidName(name.to_string()));
+        assert_eq!(validate_format("CON"), invalid("CON"));
+        assert_eq!(validate_format("YYYY/nul"), invalid("nul"));
+        assert_eq!(validate_format("Com1.YYYY"), invalid("Com1.YYYY"));
+        assert_eq!(validate_format("../YYYY"), invalid(".."));
+        assert_eq!(validate_format("YYYY/./MM"), invalid("."));
+        assert_eq!(validate_format("YYYY./MM"), invalid("YYYY."));
+        assert_eq!(validate_format("YYYY /MM"), invalid("YYYY "));
+        // Leading spaces and dots inside a name are fine, as are reserved names with more to them
+        assert_eq!(validate_format(" YYYY/MM.DD"), Ok(()));
+        assert_eq!(validate_format("Console YYYY"), Ok(()));
+    }
+
     #[test]
     fn test_format_date_edge_cases() {
         // Test single digit month and day
@@ -258,4 +788,164 @@ mod tests {
         assert!(!is_image_file(Path::new("nonexistent.txt")));
     }
 
+    #[test]
+    fn test_subdir_for_date_blocks_traversal() {
+        let date = Local.with_ymd_and_hms(2024, 1, 15, 10, 30, 0).unwrap();
+
+        assert_eq!(subdir_for_date(date, "YYYY/MM/DD"), PathBuf::from("2024").join("01").join("15"));
+        assert_eq!(subdir_for_date(date, "../YYYY"), PathBuf::from("_").join("2024"));
+        assert_eq!(subdir_for_date(date, "CON"), PathBuf::from("_CON"));
+        assert_eq!(subdir_for_date(date, "YYYY-MM. "), PathBuf::from("2024-01"));
+    }
+
+    #[test]
+    fn test_organize_adversarial_names() {
+        let base = scratch_dir("organizer-adversarial");
+        let names = [
+            "CON.png".to_string(),
+            "trailing dot..png".to_string(),
+            "spaces  .png".to_string(),
+            format!("{}.png", "x".repeat(200)),
+        ];
+
+        for name in &names {
+            let source = base.join(name);
+            fs::write(paths::long_path(&source), b"not really a png").unwrap();
+
+            let organized = organize_file(&source, &base, "YYYY-MM-DD")
+                .unwrap()
+                .expect("file in base dir should be organized");
+
+            assert!(!paths::long_path(&source).exists());
+            assert!(paths::long_path(&organized).exists());
+            assert_eq!(organized.file_name().unwrap().to_string_lossy(), name.as_str());
+            assert_ne!(organized.parent(), Some(base.as_path()));
+
+            fs::remove_file(paths::long_path(&organized)).unwrap();
+        }
+
+        let _ = fs::remove_dir_all(&base);
+    }
+
+    #[test]
+    fn test_organize_collision_suffix() {
+        let base = scratch_dir("organizer-collision");
+        let first = base.join("AUX.webp");
+        fs::write(paths::long_path(&first), b"one").unwrap();
+        let first_target = organize_file(&first, &base, "YYYY-MM").unwrap().unwrap();
+
+        let second = base.join("AUX.webp");
+        fs::write(paths::long_path(&second), b"two").unwrap();
+        let second_target = organize_file(&second, &base, "YYYY-MM").unwrap().unwrap();
+
+        assert_ne!(first_target, second_target);
+        assert_eq!(second_target.file_name().unwrap().to_string_lossy(), "AUX_1.webp");
+
+        let _ = fs::remove_dir_all(&base);
+    }
+
+    #[test]
+    fn test_matches_format() {
+        assert!(matches_format(Path::new("2024-01-15"), "YYYY-MM-DD"));
+        assert!(matches_format(Path::new("2024-01"), "YYYY-MM"));
+        assert!(matches_format(&PathBuf::from("2024").join("01").join("15"), "YYYY/MM/DD"));
+
+        assert!(!matches_format(Path::new("2024-01"), "YYYY-MM-DD"));
+        assert!(!matches_format(Path::new("2024-01-15"), "YYYY-MM"));
+        assert!(!matches_format(Path::new("2024"), "YYYY/MM/DD"));
+        assert!(!matches_format(Path::new("vacation"), "YYYY-MM"));
+
+        assert!(matches_format(&PathBuf::from("Shots 2024").join("Jan"), "Shots YYYY/MMM"));
+        assert!(matches_format(Path::new("2024 week 03 10"), "YYYY week WW HH"));
+        assert!(!matches_format(&PathBuf::from("Shots 2024").join("01"), "Shots YYYY/MMM"));
+    }
+
+    #[test]
+    fn test_flatten_nested_date_folders() {
+        let base = scratch_dir("organizer-flatten");
+        let write = |relative: &str| {
+            let path = base.join(relative);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(&path, relative).unwrap();
+            path
+        };
+        write("shot.png");
+        write("2024-01-15/shot.png");
+        write("2024-01-15/a.png");
+        write("2024/02/03/b.webp");
+        write("2024-03/c.jpg");
+        write("Shots 2024/Jan/e.png");
+        write("2024-04/notes.txt");
+        write("vacation/d.png");
+
+        let (files, date_dirs) = collect_organized_files(&base, "Shots YYYY/MMM");
+        assert_eq!(files.len(), 5);
+        for file in &files {
+            flatten_file(file, &base, None).unwrap();
+        }
+        remove_empty_dirs(&base, &date_dirs);
+
+        let mut names: Vec<String> = fs::read_dir(&base)
+            .unwrap()
+            .flatten()
+            .map(|e| e.file_name().to_string_lossy().to_string())
+            .collect();
+        names.sort();
+        assert_eq!(
+            names,
+            ["2024-04", "a.png", "b.webp", "c.jpg", "e.png", "shot.png", "shot_1.png", "vacation"]
+        );
+        // Both shots kept, whichever got the suffix
+        let contents: HashSet<String> = ["shot.png", "shot_1.png"]
+            .iter()
+            .map(|name| fs::read_to_string(base.join(name)).unwrap())
+            .collect();
+        assert_eq!(contents, HashSet::from(["shot.png".to_string(), "2024-01-15/shot.png".to_string()]));
+        // Other files keep their folders
+        assert!(base.join("2024-04").join("notes.txt").exists());
+        assert!(base.join("vacation").join("d.png").exists());
+
+        // Nothing left to flatten
+        assert!(collect_organized_files(&base, "YYYY-MM-DD").0.is_empty());
+
+        let _ = fs::remove_dir_all(&base);
+    }
+
+    #[test]
+    fn test_collect_date_dirs_of_typed_format() {
+        let base = scratch_dir("organizer-typed-format");
+        let typed = base.join("Shots 2024").join("Jan");
+        fs::create_dir_all(&typed).unwrap();
+        fs::create_dir_all(base.join("2024-01-15")).unwrap();
+
+        // Only found when the format that made it is passed along
+        assert_eq!(collect_date_dirs(&base, KNOWN_FORMATS), [base.join("2024-01-15")]);
+        let found = collect_date_dirs(&base, &known_formats_and("Shots YYYY/MMM"));
+        assert_eq!(found.len(), 2);
+        assert_eq!(found[0], typed);
+
+        let _ = fs::remove_dir_all(&base);
+    }
+
+    #[test]
+    fn test_reorganize_file_moves_and_skips() {
+        let base = scratch_dir("organizer-reorganize");
+        let source = base.join("AUX.png");
+        fs::write(paths::long_path(&source), b"one").unwrap();
+        let organized = organize_file(&source, &base, "YYYY-MM-DD").unwrap().unwrap();
+
+        // Already in the right folder
+        assert_eq!(reorganize_file(&organized, &base, "YYYY-MM-DD", None).unwrap(), None);
+
+        let moved = reorganize_file(&organized, &base, "YYYY-MM", None).unwrap().unwrap();
+        assert!(paths::long_path(&moved).exists());
+        assert!(matches_format(moved.parent().unwrap().strip_prefix(&base).unwrap(), "YYYY-MM"));
+
+        let old_dir = organized.parent().unwrap().to_path_buf();
+        remove_empty_dirs(&base, &collect_date_dirs(&base, KNOWN_FORMATS));
+        assert!(!paths::long_path(&old_dir).exists());
+        assert!(paths::long_path(&moved).exists());
+
+        let _ = fs::remove_dir_all(&base);
+    }
 }
@@ -0,0 +1,179 @@
+//! Path helpers for awkward Windows file names
+//!
+//! Screenshot folders end up with names Win32 normally refuses to handle:
+//! trailing dots/spaces, reserved device names (`CON.png`, `AUX.webp`) and
+//! paths longer than `MAX_PATH`. File operations go through [`long_path`],
+//! which adds the `\\?\` prefix so Windows skips its legacy normalization,
+//! and generated names go through [`sanitize_file_name`].
+
+use std::path::{Path, PathBuf};
+
+/// Verbatim prefix that disables Win32 path normalization
+const VERBATIM_PREFIX: &str = r"\\?\";
+
+/// Verbatim prefix for UNC paths (`\\server\share` -> `\\?\UNC\server\share`)
+const VERBATIM_UNC_PREFIX: &str = r"\\?\UNC\";
+
+/// Characters Windows does not allow in file names
+const ILLEGAL_CHARS: &[char] = &['<', '>', ':', '"', '/', '\\', '|', '?', '*'];
+
+/// Device names Windows reserves regardless of extension
+const RESERVED_NAMES: &[&str] = &[
+    "CON", "PRN", "AUX", "NUL", "COM1", "COM2", "COM3", "COM4", "COM5", "COM6", "COM7", "COM8",
+    "COM9", "LPT1", "LPT2", "LPT3", "LPT4", "LPT5", "LPT6", "LPT7", "LPT8", "LPT9",
+];
+
+/// Return a path suitable for file system calls.
+///
+/// On Windows, absolute paths get the `\\?\` prefix so names with trailing
+/// dots/spaces, reserved device names and >260 char paths work. Relative
+/// paths and other platforms are returned unchanged.
+pub fn long_path(path: &Path) -> PathBuf {
+    #[cfg(windows)]
+    {
+        if path.is_absolute() {
+            return PathBuf::from(add_verbatim_prefix(&path.to_string_lossy()));
+        }
+    }
+    path.to_path_buf()
+}
+
+/// Strip the `\\?\` prefix for display, storage and handing paths to other apps
+pub fn display_path(path: &Path) -> PathBuf {
+    PathBuf::from(strip_verbatim_prefix(&path.to_string_lossy()))
+}
+
+/// Add the verbatim prefix to an absolute Windows path string
+fn add_verbatim_prefix(path: &str) -> String {
+    if path.starts_with(VERBATIM_PREFIX) {
+        return path.to_string();
+    }
+
+    // Verbatim paths skip separator normalization, so use backslashes only
+    let path = path.replace('/', "\\");
+    if let Some(unc) = path.strip_prefix(r"\\") {
+        format!("{}{}", VERBATIM_UNC_PREFIX, unc)
+    } else {
+        format!("{}{}", VERBATIM_PREFIX, path)
+    }
+}
+
+/// Remove the verbatim prefix from a Windows path string
+fn strip_verbatim_prefix(path: &str) -> String {
+    if let Some(unc) = path.strip_prefix(VERBATIM_UNC_PREFIX) {
+        format!(r"\\{}", unc)
+    } else if let Some(rest) = path.strip_prefix(VERBATIM_PREFIX) {
+        rest.to_string()
+    } else {
+        path.to_string()
+    }
+}
+
+/// Check if a file name (with or without extension) is a reserved device name
+pub fn is_reserved_name(name: &str) -> bool {
+    let stem = name.split('.').next().unwrap_or("").trim_end();
+    RESERVED_NAMES
+        .iter()
+        .any(|reserved| reserved.eq_ignore_ascii_case(stem))
+}
+
+/// Check if a character can't appear in a Windows file name
+pub fn is_illegal_file_name_char(c: char) -> bool {
+    ILLEGAL_CHARS.contains(&c) || c.is_control()
+}
+
+/// Make a generated file or folder name safe to create on Windows.
+///
+/// Illegal and control characters become `_`, trailing dots and spaces are
+/// removed, and reserved device names get a `_` prefix.
+pub fn sanitize_file_name(name: &str) -> String {
+    let mut sanitized: String = name
+        .chars()
+        .map(|c| if is_illegal_file_name_char(c) { '_' } else { c })
+        .collect();
+
+    // Windows silently drops trailing dots and spaces
+    let trimmed_len = sanitized.trim_end_matches(['.', ' ']).len();
+    sanitized.truncate(trimmed_len);
+
+    if sanitized.is_empty() {
+        return "_".to_string();
+    }
+
+    if is_reserved_name(&sanitized) {
+        sanitized.insert(0, '_');
+    }
+
+    sanitized
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_add_verbatim_prefix() {
+        assert_eq!(
+            add_verbatim_prefix(r"C:\Shots\CON.png"),
+            r"\\?\C:\Shots\CON.png"
+        );
+        assert_eq!(
+            add_verbatim_prefix("C:/Shots/trailing. .png"),
+            r"\\?\C:\Shots\trailing. .png"
+        );
+        assert_eq!(
+            add_verbatim_prefix(r"\\nas\share\shot.png"),
+            r"\\?\UNC\nas\share\shot.png"
+        );
+        // Already prefixed paths are left alone
+        assert_eq!(
+            add_verbatim_prefix(r"\\?\C:\Shots\a.png"),
+            r"\\?\C:\Shots\a.png"
+        );
+    }
+
+    #[test]
+    fn test_strip_verbatim_prefix_roundtrip() {
+        let long_dir = "d".repeat(200);
+        let paths = [
+            r"C:\Shots\AUX.webp".to_string(),
+            r"\\nas\share\shot.png".to_string(),
+            format!(r"C:\Shots\{}\{}.png", long_dir, "x".repeat(100)),
+        ];
+
+        for path in &paths {
+            assert_eq!(&strip_verbatim_prefix(&add_verbatim_prefix(path)), path);
+        }
+        assert_eq!(strip_verbatim_prefix(r"C:\plain.png"), r"C:\plain.png");
+    }
+
+    #[test]
+    fn test_is_reserved_name() {
+        assert!(is_reserved_name("CON"));
+        assert!(is_reserved_name("con.png"));
+        assert!(is_reserved_name("Aux.webp"));
+        assert!(is_reserved_name("LPT9.tar.gz"));
+        assert!(!is_reserved_name("CONSOLE.png"));
+        assert!(!is_reserved_name("COM10.png"));
+        assert!(!is_reserved_name("Screenshot.png"));
+    }
+
+    #[test]
+    fn test_sanitize_file_name() {
+        assert_eq!(sanitize_file_name("Screenshot 2024-01-15"), "Screenshot 2024-01-15");
+        assert_eq!(sanitize_file_name("a<b>c:d\"e|f?g*h"), "a_b_c_d_e_f_g_h");
+        assert_eq!(sanitize_file_name("dir/name\\x"), "dir_name_x");
+        assert_eq!(sanitize_file_name("trailing. . "), "trailing");
+        assert_eq!(sanitize_file_name("CON"), "_CON");
+        assert_eq!(sanitize_file_name("nul.png"), "_nul.png");
+        assert_eq!(sanitize_file_name("tab\there"), "tab_here");
+        assert_eq!(sanitize_file_name("..."), "_");
+        assert_eq!(sanitize_file_name(""), "_");
+    }
+
+    #[test]
+    fn test_sanitize_keeps_unicode() {
+        assert_eq!(sanitize_file_name("スクショ 2024"), "スクショ 2024");
+        assert_eq!(sanitize_file_name("스크린샷."), "스크린샷");
+    }
+}
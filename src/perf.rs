@@ -0,0 +1,255 @@
+//! Frame timing trace for diagnosing gallery stutter
+//!
+//! Off unless started with `--perf-trace` or turned on from the Advanced
+//! page; while off every hook is a single relaxed atomic load. While on, each
+//! rendered frame's phases and thumbnail cache hits/misses go into a ring
+//! buffer of the last [`CAPACITY`] frames. The newest frame is shown in a
+//! small overlay, and the whole buffer can be saved as CSV from the About
+//! page to attach to an issue.
+
+use parking_lot::Mutex;
+use std::collections::VecDeque;
+use std::fmt::Write as _;
+use std::io;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use crate::settings::Settings;
+
+/// Frames kept (about 15 seconds of continuous scrolling at 60 fps)
+pub const CAPACITY: usize = 1000;
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+
+static TRACE: Mutex<Option<Trace>> = Mutex::new(None);
+
+/// Part of a frame timed separately
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Phase {
+    /// Draining messages from background threads
+    Messages,
+    /// Grouping the gallery by date
+    Grouping,
+}
+
+/// One rendered frame
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct FrameTiming {
+    /// When the frame started, since the trace started
+    pub at: Duration,
+    pub messages: Duration,
+    pub grouping: Duration,
+    /// Building the element tree after messages (includes grouping)
+    pub elements: Duration,
+    /// Whole render call
+    pub total: Duration,
+    pub thumbnail_hits: u32,
+    pub thumbnail_misses: u32,
+}
+
+#[derive(Debug)]
+struct Trace {
+    started: Instant,
+    frame_start: Option<Instant>,
+    current: FrameTiming,
+    frames: VecDeque<FrameTiming>,
+}
+
+impl Trace {
+    fn new(now: Instant) -> Self {
+        Self {
+            started: now,
+            frame_start: None,
+            current: FrameTiming::default(),
+            frames: VecDeque::with_capacity(CAPACITY),
+        }
+    }
+
+    fn begin(&mut self, now: Instant) {
+        self.frame_start = Some(now);
+        self.current = FrameTiming {
+            at: now.duration_since(self.started),
+            ..FrameTiming::default()
+        };
+    }
+
+    fn add(&mut self, phase: Phase, elapsed: Duration) {
+        match phase {
+            Phase::Messages => self.current.messages += elapsed,
+            Phase::Grouping => self.current.grouping += elapsed,
+        }
+    }
+
+    fn thumbnail_lookup(&mut self, hit: bool) {
+        if hit {
+            self.current.thumbnail_hits += 1;
+        } else {
+            self.current.thumbnail_misses += 1;
+        }
+    }
+
+    fn end(&mut self, now: Instant) {
+        let Some(start) = self.frame_start.take() else {
+            return;
+        };
+        let mut frame = self.current;
+        frame.total = now.duration_since(start);
+        frame.elements = frame.total.saturating_sub(frame.messages);
+        if self.frames.len() == CAPACITY {
+            self.frames.pop_front();
+        }
+        self.frames.push_back(frame);
+    }
+
+    fn to_csv(&self) -> String {
+        let ms = |d: Duration| d.as_secs_f64() * 1000.0;
+        let mut csv = String::from(
+            "at_ms,messages_ms,grouping_ms,elements_ms,total_ms,thumbnail_hits,thumbnail_misses\n",
+        );
+        for f in &self.frames {
+            let _ = writeln!(
+                csv,
+                "{:.3},{:.3},{:.3},{:.3},{:.3},{},{}",
+                ms(f.at),
+                ms(f.messages),
+                ms(f.grouping),
+                ms(f.elements),
+                ms(f.total),
+                f.thumbnail_hits,
+                f.thumbnail_misses
+            );
+        }
+        csv
+    }
+}
+
+/// Turn tracing on or off. Frames recorded so far are kept for export.
+pub fn set_enabled(enabled: bool) {
+    if enabled {
+        TRACE.lock().get_or_insert_with(|| Trace::new(Instant::now()));
+    }
+    ENABLED.store(enabled, Ordering::Relaxed);
+}
+
+pub fn is_enabled() -> bool {
+    ENABLED.load(Ordering::Relaxed)
+}
+
+/// A frame's render started
+pub fn begin_frame() {
+    if !is_enabled() {
+        return;
+    }
+    if let Some(trace) = TRACE.lock().as_mut() {
+        trace.begin(Instant::now());
+    }
+}
+
+/// Run `f`, adding its time to `phase` of the current frame
+pub fn time<T>(phase: Phase, f: impl FnOnce() -> T) -> T {
+    if !is_enabled() {
+        return f();
+    }
+    let start = Instant::now();
+    let result = f();
+    if let Some(trace) = TRACE.lock().as_mut() {
+        trace.add(phase, start.elapsed());
+    }
+    result
+}
+
+/// A thumbnail cache lookup during the current frame
+pub fn thumbnail_lookup(hit: bool) {
+    if !is_enabled() {
+        return;
+    }
+    if let Some(trace) = TRACE.lock().as_mut() {
+        trace.thumbnail_lookup(hit);
+    }
+}
+
+/// The frame's render finished
+pub fn end_frame() {
+    if !is_enabled() {
+        return;
+    }
+    if let Some(trace) = TRACE.lock().as_mut() {
+        trace.end(Instant::now());
+    }
+}
+
+/// Newest finished frame
+pub fn last_frame() -> Option<FrameTiming> {
+    TRACE.lock().as_ref().and_then(|trace| trace.frames.back().copied())
+}
+
+/// Whether there's anything to export
+pub fn has_frames() -> bool {
+    TRACE.lock().as_ref().is_some_and(|trace| !trace.frames.is_empty())
+}
+
+/// Write the recorded frames as CSV next to settings.json; returns the file
+pub fn export_csv() -> io::Result<PathBuf> {
+    let csv = TRACE
+        .lock()
+        .as_ref()
+        .map(Trace::to_csv)
+        .ok_or_else(|| io::Error::other("no performance trace recorded"))?;
+    let dir = Settings::config_path()
+        .and_then(|p| p.parent().map(|d| d.to_path_buf()))
+        .ok_or_else(|| io::Error::other("no config directory"))?;
+    std::fs::create_dir_all(&dir)?;
+    let stamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    let path = dir.join(format!("perf_trace_{}.csv", stamp));
+    std::fs::write(&path, csv)?;
+    Ok(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_frame_phases() {
+        let t0 = Instant::now();
+        let mut trace = Trace::new(t0);
+
+        trace.begin(t0 + Duration::from_millis(10));
+        trace.add(Phase::Messages, Duration::from_millis(2));
+        trace.add(Phase::Grouping, Duration::from_millis(1));
+        trace.thumbnail_lookup(true);
+        trace.thumbnail_lookup(true);
+        trace.thumbnail_lookup(false);
+        trace.end(t0 + Duration::from_millis(15));
+
+        let frame = trace.frames.back().unwrap();
+        assert_eq!(frame.at, Duration::from_millis(10));
+        assert_eq!(frame.total, Duration::from_millis(5));
+        assert_eq!(frame.elements, Duration::from_millis(3));
+        assert_eq!((frame.thumbnail_hits, frame.thumbnail_misses), (2, 1));
+
+        // An end without a begin records nothing
+        trace.end(t0 + Duration::from_millis(20));
+        assert_eq!(trace.frames.len(), 1);
+
+        let csv = trace.to_csv();
+        assert_eq!(csv.lines().count(), 2);
+        assert_eq!(csv.lines().nth(1), Some("10.000,2.000,1.000,3.000,5.000,2,1"));
+    }
+
+    #[test]
+    fn test_ring_buffer() {
+        let t0 = Instant::now();
+        let mut trace = Trace::new(t0);
+        for i in 0..CAPACITY as u64 + 5 {
+            trace.begin(t0 + Duration::from_millis(i));
+            trace.end(t0 + Duration::from_millis(i));
+        }
+        assert_eq!(trace.frames.len(), CAPACITY);
+        assert_eq!(trace.frames.front().unwrap().at, Duration::from_millis(5));
+    }
+}
@@ -0,0 +1,186 @@
+//! Transitions of new screenshots through the convert/organize pipeline
+//!
+//! The tray's Recent list is kept from these instead of raw `NewScreenshot`
+//! messages, so it always shows the final path and never offers a file the
+//! converter is about to delete.
+
+use crossbeam_channel::Sender;
+use parking_lot::Mutex;
+use std::path::PathBuf;
+
+use crate::settings::ConversionFormat;
+use crate::AppMessage;
+
+/// Entries shown in the tray's Recent submenu
+pub const RECENT_LIMIT: usize = 5;
+
+/// One step of a screenshot through the pipeline
+#[derive(Debug, Clone, PartialEq)]
+pub enum Transition {
+    /// Conversion to a format started; the file at this path will be replaced
+    Converting(PathBuf, ConversionFormat),
+    /// Pipeline done for a new screenshot. `to` is the final path (same as
+    /// `from` when nothing was converted or moved).
+    Finished { from: PathBuf, to: PathBuf },
+    /// An existing file was moved (organizer batch, journal recovery)
+    Moved { from: PathBuf, to: PathBuf },
+    /// File deleted outside the pipeline
+    Removed(PathBuf),
+}
+
+/// A Recent entry
+#[derive(Debug, Clone, PartialEq)]
+pub struct RecentEntry {
+    pub path: PathBuf,
+    /// Format it's still being converted to; not safe to drag or open until done
+    pub converting: Option<ConversionFormat>,
+}
+
+/// Newest screenshots (newest first) as seen through pipeline transitions
+#[derive(Debug, Default)]
+pub struct RecentList {
+    entries: Vec<RecentEntry>,
+}
+
+impl RecentList {
+    pub const fn new() -> Self {
+        Self {
+            entries: Vec::new(),
+        }
+    }
+
+    pub fn entries(&self) -> &[RecentEntry] {
+        &self.entries
+    }
+
+    /// Whether the newest entry is mid-conversion
+    pub fn latest_converting(&self) -> bool {
+        self.entries.first().is_some_and(|e| e.converting.is_some())
+    }
+
+    pub fn apply(&mut self, transition: &Transition) {
+        match transition {
+            Transition::Converting(path, format) => {
+                self.entries.retain(|e| e.path != *path);
+                self.push_front(RecentEntry {
+                    path: path.clone(),
+                    converting: Some(*format),
+                });
+            }
+            Transition::Finished { from, to } => {
+                self.entries.retain(|e| e.path != *from && e.path != *to);
+                self.push_front(RecentEntry {
+                    path: to.clone(),
+                    converting: None,
+                });
+            }
+            Transition::Moved { from, to } => {
+                // Older files moved by a batch don't belong in Recent
+                if let Some(entry) = self.entries.iter_mut().find(|e| e.path == *from) {
+                    entry.path = to.clone();
+                }
+            }
+            Transition::Removed(path) => {
+                // The converter deletes its source; that entry is swapped on Finished
+                self.entries.retain(|e| e.path != *path || e.converting.is_some());
+            }
+        }
+    }
+
+    fn push_front(&mut self, entry: RecentEntry) {
+        self.entries.insert(0, entry);
+        self.entries.truncate(RECENT_LIMIT);
+    }
+}
+
+static RECENT: Mutex<RecentList> = Mutex::new(RecentList::new());
+
+/// Record a transition and pass it on to the UI (which refreshes the tray menu).
+/// The shared list is updated right away so tray drags see it without waiting
+/// for the UI to render.
+pub fn report(transition: Transition, message_tx: &Sender<AppMessage>) {
+    RECENT.lock().apply(&transition);
+    let _ = message_tx.send(AppMessage::PipelineTransition(transition));
+}
+
+/// Current Recent entries (newest first)
+pub fn recent() -> Vec<RecentEntry> {
+    RECENT.lock().entries().to_vec()
+}
+
+/// Whether the newest screenshot is still being converted
+pub fn latest_converting() -> bool {
+    RECENT.lock().latest_converting()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn paths(list: &RecentList) -> Vec<(&str, bool)> {
+        list.entries()
+            .iter()
+            .map(|e| (e.path.to_str().unwrap(), e.converting.is_some()))
+            .collect()
+    }
+
+    #[test]
+    fn test_conversion_swaps_entry() {
+        let mut list = RecentList::new();
+        list.apply(&Transition::Finished {
+            from: "old.png".into(),
+            to: "old.png".into(),
+        });
+        list.apply(&Transition::Converting("new.png".into(), ConversionFormat::Avif));
+        assert!(list.latest_converting());
+        assert_eq!(list.entries()[0].converting, Some(ConversionFormat::Avif));
+        assert_eq!(paths(&list), vec![("new.png", true), ("old.png", false)]);
+
+        // The converter deleting its source doesn't drop the entry
+        list.apply(&Transition::Removed("new.png".into()));
+        assert!(list.latest_converting());
+
+        list.apply(&Transition::Finished {
+            from: "new.png".into(),
+            to: "2026/new.webp".into(),
+        });
+        assert!(!list.latest_converting());
+        assert_eq!(paths(&list), vec![("2026/new.webp", false), ("old.png", false)]);
+    }
+
+    #[test]
+    fn test_moves_and_removals() {
+        let mut list = RecentList::new();
+        list.apply(&Transition::Finished {
+            from: "a.png".into(),
+            to: "a.png".into(),
+        });
+        list.apply(&Transition::Moved {
+            from: "a.png".into(),
+            to: "2026/a.png".into(),
+        });
+        // Moves of files not in the list don't add them
+        list.apply(&Transition::Moved {
+            from: "b.png".into(),
+            to: "2026/b.png".into(),
+        });
+        assert_eq!(paths(&list), vec![("2026/a.png", false)]);
+
+        list.apply(&Transition::Removed("2026/a.png".into()));
+        assert!(list.entries().is_empty());
+    }
+
+    #[test]
+    fn test_limit() {
+        let mut list = RecentList::new();
+        for i in 0..RECENT_LIMIT + 2 {
+            let path = PathBuf::from(format!("{i}.png"));
+            list.apply(&Transition::Finished {
+                from: path.clone(),
+                to: path,
+            });
+        }
+        assert_eq!(list.entries().len(), RECENT_LIMIT);
+        assert_eq!(list.entries()[0].path, PathBuf::from(format!("{}.png", RECENT_LIMIT + 1)));
+    }
+}
@@ -0,0 +1,79 @@
+//! Move files to the Windows Recycle Bin
+
+use log::error;
+use std::path::PathBuf;
+
+/// Move files to the Recycle Bin, one at a time so a failure only affects
+/// that file. A file another process has open is retried a few times, then
+/// reported. Returns the paths that were recycled.
+#[cfg(windows)]
+pub fn recycle_files(files: &[PathBuf]) -> Vec<PathBuf> {
+    use crate::file_ops::{self, BlockReason, FileOp};
+    use log::info;
+    use std::ffi::OsStr;
+    use std::os::windows::ffi::OsStrExt;
+    use windows::core::PCWSTR;
+    use windows::Win32::UI::Shell::{
+        SHFileOperationW, FOF_ALLOWUNDO, FOF_NOCONFIRMATION, FOF_NOERRORUI, FOF_SILENT,
+        FO_DELETE, SHFILEOPSTRUCTW,
+    };
+
+    let mut recycled = Vec::new();
+
+    for file in files {
+        // pFrom must be double-null terminated; the shell API does not accept \\?\ paths
+        let wide: Vec<u16> = OsStr::new(&crate::paths::display_path(file))
+            .encode_wide()
+            .chain([0, 0])
+            .collect();
+
+        let mut delays = file_ops::LOCK_RETRY_DELAYS.iter();
+        loop {
+            let mut op = SHFILEOPSTRUCTW {
+                wFunc: FO_DELETE,
+                pFrom: PCWSTR(wide.as_ptr()),
+                fFlags: (FOF_ALLOWUNDO | FOF_NOCONFIRMATION | FOF_NOERRORUI | FOF_SILENT).0
+                    as u16,
+                ..Default::default()
+            };
+
+            let result = unsafe { SHFileOperationW(&mut op) };
+            if result == 0 && !op.fAnyOperationsAborted.as_bool() {
+                info!("Moved to Recycle Bin: {:?}", file);
+                recycled.push(file.clone());
+                break;
+            }
+
+            if result != file_ops::ERROR_SHARING_VIOLATION {
+                error!("Failed to recycle {:?} (code {})", file, result);
+                break;
+            }
+            match delays.next() {
+                Some(delay) => {
+                    info!("{:?} is in use, retrying in {:?}", file, delay);
+                    std::thread::sleep(*delay);
+                }
+                None => {
+                    file_ops::report(
+                        FileOp::Remove(file.clone()),
+                        BlockReason::Locked {
+                            owners: file_ops::lock_owners(file),
+                        },
+                    );
+                    break;
+                }
+            }
+        }
+    }
+
+    // Keep their metadata for a while in case they're restored
+    crate::removals::mark_recycled(&recycled);
+    recycled
+}
+
+#[cfg(not(windows))]
+pub fn recycle_files(files: &[PathBuf]) -> Vec<PathBuf> {
+    // No recycle bin integration; leave files untouched
+    error!("Recycle Bin is not supported on this platform ({} files)", files.len());
+    Vec::new()
+}
@@ -0,0 +1,328 @@
+//! Deleted vs moved-out screenshots
+//!
+//! The watcher only sees a file disappear. A file sent to the Recycle Bin may
+//! come back, so its search index rows and last-used time are kept for
+//! [`RESTORE_GRACE`] and dropped only if it hasn't been restored by then. A
+//! file that is simply gone (moved out of the folder, or deleted for good) is
+//! cleaned up right away. Recycled files are recognized by our own deletes
+//! and, for deletes made elsewhere, by the `$I` records the Recycle Bin keeps
+//! for each file. Pending cleanups live in a sidecar file next to
+//! settings.json so the grace period survives restarts.
+
+use log::{debug, info};
+use parking_lot::Mutex;
+use std::collections::HashMap;
+use std::ffi::{OsStr, OsString};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::sidecar;
+
+/// How long metadata of a recycled screenshot is kept in case it's restored
+pub const RESTORE_GRACE: Duration = Duration::from_secs(7 * 24 * 60 * 60);
+
+/// How a screenshot left the folder
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Removal {
+    /// In the Recycle Bin; may be restored
+    Recycled,
+    /// Moved out of the folder or deleted permanently
+    Gone,
+}
+
+/// Recycled files waiting for their grace period to run out
+#[derive(Debug, Default)]
+pub struct PendingRemovals {
+    /// Path -> when it was recycled (seconds since UNIX epoch)
+    entries: HashMap<PathBuf, u64>,
+}
+
+impl PendingRemovals {
+    /// Start the grace period for a recycled file
+    pub fn defer(&mut self, path: PathBuf, now: SystemTime) {
+        self.entries.entry(path).or_insert_with(|| secs(now));
+    }
+
+    pub fn contains(&self, path: &Path) -> bool {
+        self.entries.contains_key(path)
+    }
+
+    /// The file is back; returns whether it was waiting
+    pub fn restore(&mut self, path: &Path) -> bool {
+        self.entries.remove(path).is_some()
+    }
+
+    /// Take every file whose grace period has run out
+    pub fn take_expired(&mut self, now: SystemTime) -> Vec<PathBuf> {
+        let cutoff = secs(now).saturating_sub(RESTORE_GRACE.as_secs());
+        let expired: Vec<PathBuf> = self
+            .entries
+            .iter()
+            .filter(|(_, removed_at)| **removed_at <= cutoff)
+            .map(|(path, _)| path.clone())
+            .collect();
+        for path in &expired {
+            self.entries.remove(path);
+        }
+        expired
+    }
+}
+
+fn secs(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// Pending cleanups, loaded lazily
+static PENDING: Mutex<Option<PendingRemovals>> = Mutex::new(None);
+
+/// Sidecar file holding [`PENDING`]
+const STORE: &str = "pending_removals.json";
+
+fn load() -> PendingRemovals {
+    PendingRemovals {
+        entries: sidecar::load(STORE),
+    }
+}
+
+fn save(pending: &PendingRemovals) {
+    sidecar::save(STORE, &pending.entries);
+}
+
+/// Record that we sent these files to the Recycle Bin ourselves
+pub fn mark_recycled(paths: &[PathBuf]) {
+    if paths.is_empty() {
+        return;
+    }
+    let now = SystemTime::now();
+    let mut guard = PENDING.lock();
+    let pending = guard.get_or_insert_with(load);
+    for path in paths {
+        pending.defer(path.clone(), now);
+    }
+    save(pending);
+}
+
+/// Work out how a file that just disappeared left, and start the grace
+/// period if it was recycled. May read the Recycle Bin into `bin`; call off
+/// the UI thread.
+pub fn classify(path: &Path, bin: &mut RecycleBin) -> Removal {
+    let mut guard = PENDING.lock();
+    let pending = guard.get_or_insert_with(load);
+    if pending.contains(path) {
+        return Removal::Recycled;
+    }
+    if bin.contains(path) {
+        debug!("{:?} was sent to the Recycle Bin", path);
+        pending.defer(path.to_path_buf(), SystemTime::now());
+        save(pending);
+        return Removal::Recycled;
+    }
+    Removal::Gone
+}
+
+/// Whether a removed file is waiting out its grace period (keep its metadata)
+pub fn is_pending(path: &Path) -> bool {
+    PENDING.lock().get_or_insert_with(load).contains(path)
+}
+
+/// A file showed up again; stop its cleanup if one was waiting
+pub fn restored(path: &Path) {
+    let mut guard = PENDING.lock();
+    let pending = guard.get_or_insert_with(load);
+    if pending.restore(path) {
+        info!("{:?} restored within the grace period, keeping its metadata", path);
+        save(pending);
+    }
+}
+
+/// Recycled files whose grace period ran out; their metadata should go now
+pub fn take_expired() -> Vec<PathBuf> {
+    let mut guard = PENDING.lock();
+    let pending = guard.get_or_insert_with(load);
+    let expired = pending.take_expired(SystemTime::now());
+    if !expired.is_empty() {
+        save(pending);
+    }
+    expired
+}
+
+/// Records newer than this are checked when looking for a just-deleted file
+#[cfg(windows)]
+const RECENT_RECORD: Duration = Duration::from_secs(10 * 60);
+
+/// Original paths of recently recycled files, listed once per drive. Kept
+/// for one batch of watcher events, so deleting many files at once reads
+/// the Recycle Bin once instead of once per file.
+#[derive(Debug, Default)]
+pub struct RecycleBin {
+    /// Drive prefix (`C:`) -> original paths of its recent `$I` records
+    drives: HashMap<OsString, Vec<PathBuf>>,
+}
+
+impl RecycleBin {
+    /// Whether the Recycle Bin on `path`'s drive holds a recent `$I` record for it
+    #[cfg(windows)]
+    fn contains(&mut self, path: &Path) -> bool {
+        use std::path::Component;
+
+        let path = crate::paths::display_path(path);
+        let Some(Component::Prefix(prefix)) = path.components().next() else {
+            return false;
+        };
+        self.contains_on(prefix.as_os_str(), &path, recent_records)
+    }
+
+    #[cfg(not(windows))]
+    fn contains(&mut self, _path: &Path) -> bool {
+        false
+    }
+
+    /// Whether `drive`'s records, read with `read` the first time they're
+    /// needed, include `path`
+    #[cfg_attr(not(windows), allow(dead_code))]
+    fn contains_on(&mut self, drive: &OsStr, path: &Path, read: impl FnOnce(&OsStr) -> Vec<PathBuf>) -> bool {
+        self.drives
+            .entry(drive.to_os_string())
+            .or_insert_with(|| read(drive))
+            .iter()
+            .any(|original| original.as_os_str().eq_ignore_ascii_case(path.as_os_str()))
+    }
+}
+
+/// Original paths of the `$I` records on `drive` written in the last
+/// [`RECENT_RECORD`]
+#[cfg(windows)]
+fn recent_records(drive: &OsStr) -> Vec<PathBuf> {
+    let bin = PathBuf::from(drive).join("\\$Recycle.Bin");
+
+    // One folder per user SID; only ours is readable
+    let Ok(sids) = fs::read_dir(&bin) else {
+        return Vec::new();
+    };
+    let now = SystemTime::now();
+    let records: Vec<PathBuf> = sids
+        .flatten()
+        .filter_map(|sid| fs::read_dir(sid.path()).ok())
+        .flat_map(|entries| entries.flatten())
+        .filter(|entry| entry.file_name().to_string_lossy().starts_with("$I"))
+        .filter(|entry| {
+            entry
+                .metadata()
+                .and_then(|m| m.modified())
+                .is_ok_and(|modified| {
+                    now.duration_since(modified).unwrap_or_default() < RECENT_RECORD
+                })
+        })
+        .filter_map(|entry| fs::read(entry.path()).ok())
+        .filter_map(|data| parse_info_record(&data))
+        .collect();
+    debug!("Read {} recent Recycle Bin records on {:?}", records.len(), drive);
+    records
+}
+
+/// Original path from a Recycle Bin `$I` record. Version 1 (Vista to 8.1)
+/// has a fixed 260-character path; version 2 (Windows 10+) a length prefix.
+#[cfg_attr(not(windows), allow(dead_code))]
+fn parse_info_record(data: &[u8]) -> Option<PathBuf> {
+    // Version, original size, deletion time
+    const HEADER: usize = 24;
+    let version = u64::from_le_bytes(data.get(..8)?.try_into().ok()?);
+    let units: &[u8] = match version {
+        1 => data.get(HEADER..HEADER + 520)?,
+        2 => {
+            let len = u32::from_le_bytes(data.get(HEADER..HEADER + 4)?.try_into().ok()?) as usize;
+            data.get(HEADER + 4..HEADER + 4 + len * 2)?
+        }
+        _ => return None,
+    };
+    let wide: Vec<u16> = units
+        .chunks_exact(2)
+        .map(|c| u16::from_le_bytes([c[0], c[1]]))
+        .take_while(|&c| c != 0)
+        .collect();
+    (!wide.is_empty()).then(|| PathBuf::from(String::from_utf16_lossy(&wide)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DAY: Duration = Duration::from_secs(24 * 60 * 60);
+
+    #[test]
+    fn test_restore_within_grace() {
+        let start = UNIX_EPOCH + 100 * DAY;
+        let mut pending = PendingRemovals::default();
+        pending.defer("a.png".into(), start);
+        pending.defer("b.png".into(), start);
+
+        // a.png comes back from the Recycle Bin two days later
+        assert!(pending.restore(Path::new("a.png")));
+        assert!(!pending.restore(Path::new("a.png")));
+        assert!(pending.take_expired(start + 2 * DAY).is_empty());
+
+        // b.png never does
+        assert_eq!(pending.take_expired(start + RESTORE_GRACE), vec![PathBuf::from("b.png")]);
+        assert!(!pending.contains(Path::new("b.png")));
+    }
+
+    #[test]
+    fn test_recycle_bin_read_once_per_drive() {
+        let mut bin = RecycleBin::default();
+        let mut reads = 0;
+        let mut read = |_drive: &OsStr| {
+            reads += 1;
+            vec![PathBuf::from("C:\\Shots\\a.png")]
+        };
+
+        assert!(bin.contains_on(OsStr::new("C:"), Path::new("C:\\Shots\\a.png"), &mut read));
+        // Same drive: answered from the listing, case-insensitively
+        assert!(bin.contains_on(OsStr::new("C:"), Path::new("c:\\shots\\A.png"), &mut read));
+        assert!(!bin.contains_on(OsStr::new("C:"), Path::new("C:\\Shots\\b.png"), &mut read));
+        // Another drive is read on its own
+        assert!(!bin.contains_on(OsStr::new("D:"), Path::new("D:\\Shots\\b.png"), &mut read));
+        assert_eq!(reads, 2);
+    }
+
+    #[test]
+    fn test_defer_keeps_first_time() {
+        let start = UNIX_EPOCH + 100 * DAY;
+        let mut pending = PendingRemovals::default();
+        pending.defer("a.png".into(), start);
+        // Seen again by the watcher after our own delete marked it
+        pending.defer("a.png".into(), start + 6 * DAY);
+        assert_eq!(pending.take_expired(start + RESTORE_GRACE).len(), 1);
+    }
+
+    fn record(version: u64, path: &str) -> Vec<u8> {
+        let wide: Vec<u16> = path.encode_utf16().collect();
+        let mut data = Vec::new();
+        data.extend(version.to_le_bytes());
+        data.extend(1234u64.to_le_bytes());
+        data.extend(0u64.to_le_bytes());
+        match version {
+            1 => {
+                let mut fixed = wide.clone();
+                fixed.resize(260, 0);
+                data.extend(fixed.iter().flat_map(|c| c.to_le_bytes()));
+            }
+            _ => {
+                data.extend((wide.len() as u32 + 1).to_le_bytes());
+                data.extend(wide.iter().chain([&0]).flat_map(|c| c.to_le_bytes()));
+            }
+        }
+        data
+    }
+
+    #[test]
+    fn test_parse_info_record() {
+        let path = r"C:\Users\me\Pictures\Screenshots\스크린샷 2024-01-02.png";
+        assert_eq!(parse_info_record(&record(1, path)), Some(PathBuf::from(path)));
+        assert_eq!(parse_info_record(&record(2, path)), Some(PathBuf::from(path)));
+        assert_eq!(parse_info_record(&record(3, path)), None);
+        assert_eq!(parse_info_record(&record(2, path)[..30]), None);
+    }
+}
@@ -0,0 +1,110 @@
+//! Log file written in normal mode
+//!
+//! Without `--console` there's no log anyone can read, so a bug report had
+//! nothing to go on. The log goes to `sukusho.log` in the config folder
+//! instead; once it passes [`MAX_LOG_BYTES`] it's renamed to
+//! `sukusho.old.log` (replacing the one before) and started over, so the two
+//! never take more than twice that. The diagnostic bundle includes both.
+
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+use crate::settings::Settings;
+
+pub const LOG_FILE: &str = "sukusho.log";
+
+/// The log before the last roll-over
+pub const PREVIOUS_LOG_FILE: &str = "sukusho.old.log";
+
+/// Size at which the log starts over
+const MAX_LOG_BYTES: u64 = 1024 * 1024;
+
+/// Where the log is written, next to the settings
+pub fn path() -> Option<PathBuf> {
+    Settings::config_path().map(|settings| settings.with_file_name(LOG_FILE))
+}
+
+/// Where the log before the last roll-over is kept
+pub fn previous_path(log: &Path) -> PathBuf {
+    log.with_file_name(PREVIOUS_LOG_FILE)
+}
+
+/// Open the log for appending, for `env_logger`'s pipe target
+pub fn open() -> io::Result<RollingLog> {
+    let path = path().ok_or_else(|| io::Error::other("no config directory"))?;
+    RollingLog::open(path, MAX_LOG_BYTES)
+}
+
+/// Appends to a log file, moving it aside once it's full
+pub struct RollingLog {
+    path: PathBuf,
+    file: File,
+    /// Size of the current file
+    written: u64,
+    max_bytes: u64,
+}
+
+impl RollingLog {
+    fn open(path: PathBuf, max_bytes: u64) -> io::Result<Self> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let file = OpenOptions::new().create(true).append(true).open(&path)?;
+        let written = file.metadata()?.len();
+        Ok(Self { path, file, written, max_bytes })
+    }
+
+    fn roll(&mut self) -> io::Result<()> {
+        fs::rename(&self.path, previous_path(&self.path))?;
+        self.file = File::create(&self.path)?;
+        self.written = 0;
+        Ok(())
+    }
+}
+
+impl Write for RollingLog {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        // Keeps writing to the full file if it can't be moved (another
+        // instance has the old one open)
+        if self.written > 0 && self.written + buf.len() as u64 > self.max_bytes {
+            let _ = self.roll();
+        }
+        let written = self.file.write(buf)?;
+        self.written += written as u64;
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.file.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::scratch_dir;
+
+    #[test]
+    fn test_rolls_over_when_full() {
+        let dir = scratch_dir("rolling-log");
+        let path = dir.join(LOG_FILE);
+        fs::write(&path, b"from the last run\n").unwrap();
+
+        // Appends to what's there
+        let mut log = RollingLog::open(path.clone(), 32).unwrap();
+        log.write_all(b"first\n").unwrap();
+        assert_eq!(fs::read(&path).unwrap(), b"from the last run\nfirst\n");
+
+        log.write_all(b"this line doesn't fit\n").unwrap();
+        assert_eq!(fs::read(&path).unwrap(), b"this line doesn't fit\n");
+        assert_eq!(fs::read(previous_path(&path)).unwrap(), b"from the last run\nfirst\n");
+
+        // Only one old log is kept
+        log.write_all(b"and neither does this one\n").unwrap();
+        assert_eq!(fs::read(previous_path(&path)).unwrap(), b"this line doesn't fit\n");
+        assert_eq!(fs::read_dir(&dir).unwrap().count(), 2);
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,471 @@
+//! Gallery screenshots in the chosen [`SortMode`], with a soft cap on what's
+//! kept in memory
+//!
+//! A folder with 100k screenshots shouldn't hold 100k [`ScreenshotInfo`]s
+//! just to show the first few pages. Up to the cap, the first screenshots
+//! are kept in full; the rest are a [`Stub`] (path, mtime and size, enough to
+//! keep their place) until something needs them: scrolling down, a search
+//! match. Selecting everything takes the stubs' paths as they are. The full
+//! list is always counted, so totals don't change with the cap.
+
+use std::cmp::Ordering;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use crate::app::ScreenshotInfo;
+use crate::settings::SortMode;
+
+/// Default for `max_gallery_items`
+pub const DEFAULT_MAX_IN_MEMORY: usize = 20_000;
+
+/// A screenshot further down the list whose details aren't loaded
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Stub {
+    pub path: PathBuf,
+    pub modified: SystemTime,
+    pub size: u64,
+}
+
+impl Stub {
+    fn of(info: &ScreenshotInfo) -> Self {
+        Stub {
+            path: info.path.clone(),
+            modified: info.modified,
+            size: info.file_size,
+        }
+    }
+}
+
+/// What a [`SortMode`] looks at, from a loaded screenshot or a stub
+struct SortKey<'a> {
+    path: &'a Path,
+    modified: SystemTime,
+    size: u64,
+}
+
+impl<'a> From<&'a ScreenshotInfo> for SortKey<'a> {
+    fn from(info: &'a ScreenshotInfo) -> Self {
+        SortKey {
+            path: &info.path,
+            modified: info.modified,
+            size: info.file_size,
+        }
+    }
+}
+
+impl<'a> From<&'a Stub> for SortKey<'a> {
+    fn from(stub: &'a Stub) -> Self {
+        SortKey {
+            path: &stub.path,
+            modified: stub.modified,
+            size: stub.size,
+        }
+    }
+}
+
+/// Whether `a` goes before (`Less`) or after `b` in the gallery
+fn compare(sort: SortMode, a: SortKey, b: SortKey) -> Ordering {
+    let newest = || b.modified.cmp(&a.modified);
+    match sort {
+        SortMode::Newest => newest(),
+        SortMode::Oldest => a.modified.cmp(&b.modified),
+        SortMode::Largest => b.size.cmp(&a.size).then_with(newest),
+        SortMode::Name => {
+            let name = |path: &Path| {
+                path.file_name()
+                    .map(|n| n.to_string_lossy().to_lowercase())
+                    .unwrap_or_default()
+            };
+            name(a.path).cmp(&name(b.path)).then_with(|| a.path.cmp(b.path))
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct ScreenshotList {
+    /// First screenshots in full
+    loaded: Vec<ScreenshotInfo>,
+    /// The rest, in the same order; all sort after (or with) `loaded`
+    stubs: VecDeque<Stub>,
+    /// Every path in either, to skip duplicates without a scan
+    paths: HashSet<PathBuf>,
+    /// Screenshots kept in full when nothing needs more
+    cap: usize,
+    sort: SortMode,
+}
+
+impl ScreenshotList {
+    pub fn new(cap: usize) -> Self {
+        Self {
+            loaded: Vec::new(),
+            stubs: VecDeque::new(),
+            paths: HashSet::new(),
+            cap,
+            sort: SortMode::Newest,
+        }
+    }
+
+    pub fn set_cap(&mut self, cap: usize) {
+        self.cap = cap;
+    }
+
+    pub fn sort(&self) -> SortMode {
+        self.sort
+    }
+
+    /// Re-sort everything. As many screenshots as before stay loaded, now the
+    /// first ones in the new order; ones `load` can't read any more are dropped.
+    pub fn set_sort(&mut self, sort: SortMode, mut load: impl FnMut(&Path) -> Option<ScreenshotInfo>) {
+        self.sort = sort;
+        let count = self.loaded.len();
+        let mut kept: HashMap<PathBuf, ScreenshotInfo> =
+            self.loaded.drain(..).map(|info| (info.path.clone(), info)).collect();
+        let mut all: Vec<Stub> = kept.values().map(Stub::of).chain(self.stubs.drain(..)).collect();
+        all.sort_by(|a, b| compare(sort, a.into(), b.into()));
+        self.stubs = all.into();
+        self.hydrate(count, |path| kept.remove(path).or_else(|| load(path)));
+    }
+
+    /// Screenshots kept in full, in gallery order
+    pub fn loaded(&self) -> &[ScreenshotInfo] {
+        &self.loaded
+    }
+
+    pub fn loaded_mut(&mut self) -> &mut [ScreenshotInfo] {
+        &mut self.loaded
+    }
+
+    /// Every screenshot, loaded or not
+    pub fn len(&self) -> usize {
+        self.loaded.len() + self.stubs.len()
+    }
+
+    pub fn stub_count(&self) -> usize {
+        self.stubs.len()
+    }
+
+    pub fn contains(&self, path: &Path) -> bool {
+        self.paths.contains(path)
+    }
+
+    pub fn clear(&mut self) {
+        self.loaded.clear();
+        self.stubs.clear();
+        self.paths.clear();
+    }
+
+    /// Add a screenshot in sort order, after any it ties with; returns its
+    /// position in the full list (`None` if it was already there)
+    pub fn insert(&mut self, info: ScreenshotInfo) -> Option<usize> {
+        if !self.paths.insert(info.path.clone()) {
+            return None;
+        }
+        let sort = self.sort;
+        let goes_before = |key: SortKey| compare(sort, key, (&info).into()) != Ordering::Greater;
+        let loaded_pos = self.loaded.partition_point(|s| goes_before(s.into()));
+
+        // Belongs among the stubs: don't load it just to unload it again
+        if loaded_pos == self.loaded.len() && !self.stubs.is_empty() {
+            let stub_pos = self.stubs.partition_point(|s| goes_before(s.into()));
+            self.stubs.insert(stub_pos, Stub::of(&info));
+            return Some(self.loaded.len() + stub_pos);
+        }
+
+        self.loaded.insert(loaded_pos, info);
+        Some(loaded_pos)
+    }
+
+    /// Add many screenshots with one sort instead of an insert each (a folder
+    /// scan); ties keep the ones already listed first. Returns how many were new.
+    pub fn extend(&mut self, infos: Vec<ScreenshotInfo>) -> usize {
+        let sort = self.sort;
+        let mut added = 0;
+        let mut stubs = Vec::new();
+        for info in infos {
+            if !self.paths.insert(info.path.clone()) {
+                continue;
+            }
+            added += 1;
+            // Sorts after the first stub: a stub too, like in `insert`
+            match self.stubs.front() {
+                Some(first) if compare(sort, (&info).into(), first.into()) != Ordering::Less => {
+                    stubs.push(Stub::of(&info));
+                }
+                _ => self.loaded.push(info),
+            }
+        }
+        if added == 0 {
+            return 0;
+        }
+
+        // Both sorts are stable and mostly see sorted runs
+        self.loaded.sort_by(|a, b| compare(sort, a.into(), b.into()));
+        if !stubs.is_empty() {
+            self.stubs.extend(stubs);
+            self.stubs
+                .make_contiguous()
+                .sort_by(|a, b| compare(sort, a.into(), b.into()));
+        }
+        added
+    }
+
+    /// Remove a screenshot wherever it is; returns whether it was there
+    pub fn remove(&mut self, path: &Path) -> bool {
+        if !self.paths.remove(path) {
+            return false;
+        }
+        if let Some(pos) = self.loaded.iter().position(|s| s.path == path) {
+            self.loaded.remove(pos);
+        } else if let Some(pos) = self.stubs.iter().position(|s| s.path == path) {
+            self.stubs.remove(pos);
+        }
+        true
+    }
+
+    /// Load stubs until `count` screenshots are loaded (or none are left).
+    /// Stubs `load` can't read any more (deleted meanwhile) are dropped.
+    pub fn hydrate(&mut self, count: usize, mut load: impl FnMut(&Path) -> Option<ScreenshotInfo>) {
+        while self.loaded.len() < count {
+            let Some(stub) = self.stubs.pop_front() else {
+                break;
+            };
+            match load(&stub.path) {
+                Some(info) => self.loaded.push(info),
+                None => {
+                    self.paths.remove(&stub.path);
+                }
+            }
+        }
+    }
+
+    /// Load stubs through the oldest one in `paths` (search matches)
+    pub fn hydrate_matching(
+        &mut self,
+        paths: &[PathBuf],
+        load: impl FnMut(&Path) -> Option<ScreenshotInfo>,
+    ) {
+        let wanted: HashSet<&Path> = paths.iter().map(PathBuf::as_path).collect();
+        if let Some(last) = self.stubs.iter().rposition(|s| wanted.contains(s.path.as_path())) {
+            self.hydrate(self.loaded.len() + last + 1, load);
+        }
+    }
+
+    /// Paths of the screenshots that aren't loaded, in gallery order
+    pub fn stub_paths(&self) -> impl Iterator<Item = &Path> {
+        self.stubs.iter().map(|s| s.path.as_path())
+    }
+
+    /// Paths of the `limit` most recently modified screenshots, whatever the
+    /// gallery order
+    pub fn newest(&self, limit: usize) -> Vec<PathBuf> {
+        let entries = self
+            .loaded
+            .iter()
+            .map(|s| (s.modified, &s.path))
+            .chain(self.stubs.iter().map(|s| (s.modified, &s.path)));
+        if self.sort == SortMode::Newest {
+            return entries.take(limit).map(|(_, path)| path.clone()).collect();
+        }
+        let mut all: Vec<_> = entries.collect();
+        all.sort_by(|a, b| b.0.cmp(&a.0));
+        all.into_iter().take(limit).map(|(_, path)| path.clone()).collect()
+    }
+
+    /// The most recently modified loaded screenshot. Sorted newest first
+    /// that's the newest of all; otherwise a stub may be newer.
+    pub fn newest_loaded(&self) -> Option<&ScreenshotInfo> {
+        match self.sort {
+            SortMode::Newest => self.loaded.first(),
+            _ => self.loaded.iter().max_by_key(|s| s.modified),
+        }
+    }
+
+    /// Unload the oldest screenshots beyond the cap, keeping at least `keep`
+    /// (what's on screen) loaded
+    pub fn trim(&mut self, keep: usize) {
+        let keep = keep.max(self.cap);
+        while self.loaded.len() > keep {
+            let Some(info) = self.loaded.pop() else {
+                break;
+            };
+            self.stubs.push_front(Stub::of(&info));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+    use std::time::{Duration, UNIX_EPOCH};
+
+    fn at(secs: u64) -> SystemTime {
+        UNIX_EPOCH + Duration::from_secs(secs)
+    }
+
+    fn screenshot(secs: u64) -> ScreenshotInfo {
+        let name = format!("{secs}.png");
+        ScreenshotInfo {
+            path: PathBuf::from(&name),
+            filename: name,
+            modified: at(secs),
+            captured: at(secs),
+            // Every seventh one is big, so size order differs from time order
+            file_size: if secs % 7 == 0 { 10_000 + secs } else { secs },
+            extension: "PNG".to_string(),
+        }
+    }
+
+    /// Files on "disk": secs -> still there
+    fn disk(range: std::ops::Range<u64>) -> HashMap<PathBuf, u64> {
+        range.map(|s| (PathBuf::from(format!("{s}.png")), s)).collect()
+    }
+
+    fn loader(disk: &HashMap<PathBuf, u64>) -> impl FnMut(&Path) -> Option<ScreenshotInfo> + '_ {
+        |path| disk.get(path).map(|&secs| screenshot(secs))
+    }
+
+    fn filled(cap: usize, count: u64) -> ScreenshotList {
+        let mut list = ScreenshotList::new(cap);
+        // Scan order: newest first
+        for secs in (0..count).rev() {
+            list.insert(screenshot(secs));
+            list.trim(0);
+        }
+        list
+    }
+
+    #[test]
+    fn test_cap_keeps_newest_loaded() {
+        let list = filled(10, 100);
+        assert_eq!(list.len(), 100);
+        assert_eq!(list.loaded().len(), 10);
+        assert_eq!(list.loaded()[0].modified, at(99));
+        assert_eq!(list.loaded()[9].modified, at(90));
+        assert_eq!(list.stub_count(), 90);
+    }
+
+    #[test]
+    fn test_insert_order_and_duplicates() {
+        let mut list = filled(10, 100);
+        // Newer than everything
+        assert_eq!(list.insert(screenshot(500)), Some(0));
+        // Already listed
+        assert_eq!(list.insert(screenshot(50)), None);
+        // Among the stubs: stays a stub, after the other 50s one
+        let mut late = screenshot(50);
+        late.path = PathBuf::from("late.png");
+        assert_eq!(list.insert(late), Some(11 + 40));
+        assert_eq!(list.loaded().len(), 11);
+        assert_eq!(list.len(), 102);
+
+        assert!(list.remove(Path::new("late.png")));
+        assert!(list.remove(Path::new("0.png")));
+        assert!(!list.remove(Path::new("0.png")));
+        assert_eq!(list.len(), 100);
+    }
+
+    #[test]
+    fn test_extend_matches_insert() {
+        // Scan batches arrive newest first, some overlapping
+        let mut batched = ScreenshotList::new(10);
+        for batch in [60..100, 0..30, 25..65] {
+            let added = batched.extend(batch.rev().map(screenshot).collect());
+            assert!(added > 0);
+            batched.trim(0);
+        }
+        assert_eq!(batched.extend(vec![screenshot(5)]), 0);
+
+        let inserted = filled(10, 100);
+        assert_eq!(batched.len(), inserted.len());
+        assert_eq!(batched.loaded().len(), 10);
+        let paths = |list: &ScreenshotList| list.newest(100);
+        assert_eq!(paths(&batched), paths(&inserted));
+
+        // Newer than everything loads; older than the loaded ones stays a stub
+        let mut late = screenshot(3);
+        late.path = PathBuf::from("late.png");
+        assert_eq!(batched.extend(vec![screenshot(500), late]), 2);
+        assert_eq!(batched.loaded()[0].modified, at(500));
+        assert_eq!(batched.loaded().len(), 11);
+        assert_eq!(batched.len(), 102);
+    }
+
+    #[test]
+    fn test_hydrate_and_trim() {
+        let mut on_disk = disk(0..100);
+        let mut list = filled(10, 100);
+        // A stub deleted behind our back is dropped when reached
+        on_disk.remove(Path::new("85.png"));
+
+        list.hydrate(20, loader(&on_disk));
+        assert_eq!(list.loaded().len(), 20);
+        assert_eq!(list.loaded()[19].modified, at(79));
+        assert!(!list.contains(Path::new("85.png")));
+        assert_eq!(list.len(), 99);
+
+        // On screen stays loaded; the rest goes back to stubs
+        list.trim(15);
+        assert_eq!(list.loaded().len(), 15);
+        list.trim(0);
+        assert_eq!(list.loaded().len(), 10);
+        assert_eq!(list.len(), 99);
+    }
+
+    #[test]
+    fn test_hydrate_matching() {
+        let on_disk = disk(0..100);
+        let mut list = filled(10, 100);
+        list.hydrate_matching(&[PathBuf::from("95.png"), PathBuf::from("42.png")], loader(&on_disk));
+        assert_eq!(list.loaded().len(), 58);
+        assert_eq!(list.loaded().last().unwrap().modified, at(42));
+
+        assert_eq!(list.stub_count(), 42);
+        assert_eq!(list.stub_paths().next(), Some(Path::new("41.png")));
+    }
+
+    #[test]
+    fn test_newest_after_removal() {
+        let mut list = filled(10, 3);
+        assert_eq!(list.newest(2), vec![PathBuf::from("2.png"), PathBuf::from("1.png")]);
+
+        // Deleting the newest promotes the next one
+        list.remove(Path::new("2.png"));
+        assert_eq!(list.newest(2), vec![PathBuf::from("1.png"), PathBuf::from("0.png")]);
+
+        list.clear();
+        assert!(list.newest(2).is_empty());
+    }
+
+    #[test]
+    fn test_set_sort() {
+        let on_disk = disk(0..100);
+        let mut list = filled(10, 100);
+
+        list.set_sort(SortMode::Oldest, loader(&on_disk));
+        assert_eq!(list.loaded().len(), 10);
+        assert_eq!(list.loaded()[0].modified, at(0));
+        assert_eq!(list.insert(screenshot(500)), Some(100));
+        assert_eq!(list.newest(1), vec![PathBuf::from("500.png")]);
+        assert_eq!(list.newest_loaded().unwrap().modified, at(9));
+        list.remove(Path::new("500.png"));
+
+        // The big ones first, then by size
+        list.set_sort(SortMode::Largest, loader(&on_disk));
+        let sizes: Vec<u64> = list.loaded().iter().map(|s| s.file_size).take(3).collect();
+        assert_eq!(sizes, vec![10_098, 10_091, 10_084]);
+        assert_eq!(list.insert(screenshot(700)), Some(0));
+        list.remove(Path::new("700.png"));
+
+        // "10.png" sorts before "2.png" by name
+        list.set_sort(SortMode::Name, loader(&on_disk));
+        let names: Vec<&str> = list.loaded().iter().take(3).map(|s| s.filename.as_str()).collect();
+        assert_eq!(names, vec!["0.png", "1.png", "10.png"]);
+        assert_eq!(list.len(), 100);
+
+        list.set_sort(SortMode::Newest, loader(&on_disk));
+        assert_eq!(list.loaded()[0].modified, at(99));
+        assert_eq!(list.loaded().len(), 10);
+    }
+}
@@ -0,0 +1,479 @@
+//! Read-only HTTP API for local integrations (opt-in)
+//!
+//! Listens on 127.0.0.1 only and requires the bearer token from settings.
+//!
+//! - `GET /latest` - newest screenshots as JSON
+//! - `GET /search?q=...&limit=` - semantic search results as JSON
+//! - `GET /file?path=` - image bytes, restricted to the screenshot directory
+
+use anyhow::Result;
+use log::{debug, error, info, warn};
+use parking_lot::Mutex;
+use serde_json::{json, Value};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::thread::JoinHandle;
+use std::time::UNIX_EPOCH;
+use tiny_http::{Header, Response, Server};
+
+/// Results returned by `/search` when no limit is given
+const DEFAULT_SEARCH_LIMIT: usize = 20;
+
+/// Upper bound for `/search?limit=`
+const MAX_SEARCH_LIMIT: usize = 100;
+
+/// Runs a text query against the index, returning at most `limit` paths
+pub type SearchFn = Box<dyn Fn(&str, usize) -> Result<Vec<PathBuf>> + Send + Sync>;
+
+/// What the request handler needs
+pub struct ApiContext {
+    pub screenshot_dir: PathBuf,
+    pub token: String,
+    pub search: SearchFn,
+}
+
+/// A running listener
+struct ApiServer {
+    server: Arc<Server>,
+    thread: Option<JoinHandle<()>>,
+    port: u16,
+}
+
+static SERVER: Mutex<Option<ApiServer>> = Mutex::new(None);
+
+/// Start listening on 127.0.0.1 (port 0 = any free port), replacing a running
+/// server. Returns the bound port.
+pub fn start(port: u16, context: ApiContext) -> Result<u16> {
+    stop();
+
+    let server = Server::http(("127.0.0.1", port))
+        .map_err(|e| anyhow::anyhow!("Failed to bind 127.0.0.1:{}: {}", port, e))?;
+    let port = server
+        .server_addr()
+        .to_ip()
+        .map(|addr| addr.port())
+        .unwrap_or(port);
+    let server = Arc::new(server);
+
+    let thread = std::thread::spawn({
+        let server = Arc::clone(&server);
+        move || {
+            // Ends when `stop` unblocks the server
+            for request in server.incoming_requests() {
+                serve(request, &context);
+            }
+            debug!("Local API listener stopped");
+        }
+    });
+
+    info!("Local API listening on http://127.0.0.1:{}", port);
+    *SERVER.lock() = Some(ApiServer {
+        server,
+        thread: Some(thread),
+        port,
+    });
+    Ok(port)
+}
+
+/// Stop the listener (no-op when it isn't running)
+pub fn stop() {
+    let Some(mut running) = SERVER.lock().take() else {
+        return;
+    };
+    running.server.unblock();
+    if let Some(thread) = running.thread.take() {
+        let _ = thread.join();
+    }
+    info!("Local API on port {} stopped", running.port);
+}
+
+/// Port the listener is bound to, if it's running
+pub fn running_port() -> Option<u16> {
+    SERVER.lock().as_ref().map(|s| s.port)
+}
+
+/// Lowest port the local API can be set to; lower ones need admin rights
+pub const MIN_PORT: u16 = 1024;
+
+/// New bearer token: 128 bits from the OS random number generator
+pub fn generate_token() -> Result<String> {
+    let mut bytes = [0u8; 16];
+    getrandom::fill(&mut bytes).map_err(|e| anyhow::anyhow!("No random bytes for the API token: {}", e))?;
+    Ok(bytes.iter().map(|b| format!("{:02x}", b)).collect())
+}
+
+/// Outcome of a request before it's written to the socket
+#[derive(Debug)]
+enum Reply {
+    Json(u16, Value),
+    File(PathBuf, &'static str),
+}
+
+fn serve(request: tiny_http::Request, context: &ApiContext) {
+    let auth = request
+        .headers()
+        .iter()
+        .find(|h| h.field.equiv("Authorization"))
+        .map(|h| h.value.as_str().to_string());
+    let reply = handle(context, request.method().as_str(), request.url(), auth.as_deref());
+
+    let result = match reply {
+        Reply::Json(status, body) => request.respond(
+            Response::from_string(body.to_string())
+                .with_status_code(status)
+                .with_header(content_type_header("application/json")),
+        ),
+        Reply::File(path, content_type) => match std::fs::File::open(&path) {
+            Ok(file) => {
+                request.respond(Response::from_file(file).with_header(content_type_header(content_type)))
+            }
+            Err(e) => {
+                warn!("Failed to open {:?} for the local API: {}", path, e);
+                request.respond(
+                    Response::from_string(json!({ "error": "not found" }).to_string())
+                        .with_status_code(404)
+                        .with_header(content_type_header("application/json")),
+                )
+            }
+        },
+    };
+    if let Err(e) = result {
+        debug!("Failed to write local API response: {}", e);
+    }
+}
+
+fn content_type_header(value: &str) -> Header {
+    Header::from_bytes(&b"Content-Type"[..], value.as_bytes()).expect("valid header")
+}
+
+fn error_reply(status: u16, message: &str) -> Reply {
+    Reply::Json(status, json!({ "error": message }))
+}
+
+/// Route one request
+fn handle(context: &ApiContext, method: &str, url: &str, auth: Option<&str>) -> Reply {
+    let expected = format!("Bearer {}", context.token);
+    let authorized = auth.is_some_and(|auth| constant_time_eq(auth.as_bytes(), expected.as_bytes()));
+    if context.token.is_empty() || !authorized {
+        return error_reply(401, "unauthorized");
+    }
+    if method != "GET" {
+        return error_reply(405, "method not allowed");
+    }
+
+    let (route, query) = url.split_once('?').unwrap_or((url, ""));
+    let params = parse_query(query);
+    let param = |name: &str| {
+        params
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.as_str())
+    };
+
+    match route {
+        "/latest" => {
+            let screenshots: Vec<Value> = crate::get_latest_screenshots()
+                .iter()
+                .filter_map(|path| file_entry(path))
+                .collect();
+            Reply::Json(200, json!({ "screenshots": screenshots }))
+        }
+        "/search" => {
+            let Some(q) = param("q").filter(|q| !q.trim().is_empty()) else {
+                return error_reply(400, "missing q");
+            };
+            let limit = match param("limit").map(str::parse::<usize>) {
+                None => DEFAULT_SEARCH_LIMIT,
+                Some(Ok(limit)) => limit.clamp(1, MAX_SEARCH_LIMIT),
+                Some(Err(_)) => return error_reply(400, "invalid limit"),
+            };
+            match (context.search)(q, limit) {
+                Ok(paths) => {
+                    let results: Vec<Value> = paths
+                        .iter()
+                        .take(limit)
+                        .filter_map(|path| file_entry(path))
+                        .collect();
+                    Reply::Json(200, json!({ "query": q, "results": results }))
+                }
+                Err(e) => {
+                    error!("Local API search failed: {}", e);
+                    error_reply(503, &format!("{:#}", e))
+                }
+            }
+        }
+        "/file" => {
+            let Some(requested) = param("path") else {
+                return error_reply(400, "missing path");
+            };
+            match resolve_file(&context.screenshot_dir, requested) {
+                Some((path, content_type)) => Reply::File(path, content_type),
+                None => error_reply(404, "not found"),
+            }
+        }
+        _ => error_reply(404, "not found"),
+    }
+}
+
+/// Resolve a requested image path (absolute, or relative to the screenshot
+/// directory). Both sides are canonicalized so `..` and links can't escape
+/// the directory; only existing image files are served.
+fn resolve_file(screenshot_dir: &Path, requested: &str) -> Option<(PathBuf, &'static str)> {
+    let requested = Path::new(requested);
+    let candidate = if requested.is_absolute() {
+        requested.to_path_buf()
+    } else {
+        screenshot_dir.join(requested)
+    };
+
+    let root = screenshot_dir.canonicalize().ok()?;
+    let file = candidate.canonicalize().ok()?;
+    if !file.starts_with(&root) || !file.is_file() {
+        return None;
+    }
+    let content_type = image_content_type(&file)?;
+    Some((file, content_type))
+}
+
+fn image_content_type(path: &Path) -> Option<&'static str> {
+    let ext = path.extension()?.to_str()?.to_lowercase();
+    match ext.as_str() {
+        "png" => Some("image/png"),
+        "jpg" | "jpeg" => Some("image/jpeg"),
+        "gif" => Some("image/gif"),
+        "bmp" => Some("image/bmp"),
+        "webp" => Some("image/webp"),
+        "avif" => Some("image/avif"),
+        _ => None,
+    }
+}
+
+/// JSON description of a screenshot; None if it's gone
+fn file_entry(path: &Path) -> Option<Value> {
+    let metadata = std::fs::metadata(crate::paths::long_path(path)).ok()?;
+    let modified = metadata
+        .modified()
+        .ok()
+        .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+        .map(|d| d.as_secs());
+    Some(json!({
+        "path": path.to_string_lossy(),
+        "name": path.file_name().map(|n| n.to_string_lossy().to_string()),
+        "size": metadata.len(),
+        "modified": modified,
+    }))
+}
+
+/// Split a query string into decoded key/value pairs
+fn parse_query(query: &str) -> Vec<(String, String)> {
+    query
+        .split('&')
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| {
+            let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+            (percent_decode(key), percent_decode(value))
+        })
+        .collect()
+}
+
+/// Decode `%XX` escapes and `+` (form-encoded space)
+fn percent_decode(s: &str) -> String {
+    let bytes = s.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'+' => decoded.push(b' '),
+            b'%' if i + 2 < bytes.len() => {
+                let hex = std::str::from_utf8(&bytes[i + 1..i + 3]).ok();
+                match hex.and_then(|hex| u8::from_str_radix(hex, 16).ok()) {
+                    Some(byte) => {
+                        decoded.push(byte);
+                        i += 2;
+                    }
+                    None => decoded.push(b'%'),
+                }
+            }
+            byte => decoded.push(byte),
+        }
+        i += 1;
+    }
+    String::from_utf8_lossy(&decoded).to_string()
+}
+
+/// Compare without returning early on the first differing byte, so response
+/// times don't reveal how much of a guessed token was right
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0u8, |diff, (x, y)| diff | (x ^ y)) == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::scratch_dir;
+
+    fn context(screenshot_dir: PathBuf, search: SearchFn) -> ApiContext {
+        ApiContext {
+            screenshot_dir,
+            token: "secret".to_string(),
+            search,
+        }
+    }
+
+    fn get(context: &ApiContext, url: &str) -> Reply {
+        handle(context, "GET", url, Some("Bearer secret"))
+    }
+
+    fn status(reply: &Reply) -> u16 {
+        match reply {
+            Reply::Json(status, _) => *status,
+            Reply::File(..) => 200,
+        }
+    }
+
+    #[test]
+    fn test_generate_token() {
+        let token = generate_token().unwrap();
+        assert_eq!(token.len(), 32);
+        assert!(token.chars().all(|c| c.is_ascii_hexdigit()));
+        assert_ne!(token, generate_token().unwrap());
+    }
+
+    #[test]
+    fn test_percent_decode() {
+        assert_eq!(percent_decode("a%20b+c"), "a b c");
+        assert_eq!(percent_decode("%E3%82%B9"), "ス");
+        assert_eq!(percent_decode("100%"), "100%");
+        assert_eq!(percent_decode("%zz"), "%zz");
+    }
+
+    #[test]
+    fn test_constant_time_eq() {
+        assert!(constant_time_eq(b"Bearer secret", b"Bearer secret"));
+        assert!(!constant_time_eq(b"Bearer secreT", b"Bearer secret"));
+        assert!(!constant_time_eq(b"Bearer secret!", b"Bearer secret"));
+        assert!(!constant_time_eq(b"", b"Bearer secret"));
+    }
+
+    #[test]
+    fn test_requires_token() {
+        let dir = scratch_dir("server-auth");
+        let context = context(dir.clone(), Box::new(|_, _| Ok(Vec::new())));
+
+        assert_eq!(status(&handle(&context, "GET", "/latest", None)), 401);
+        assert_eq!(status(&handle(&context, "GET", "/latest", Some("Bearer wrong"))), 401);
+        assert_eq!(status(&handle(&context, "GET", "/latest", Some("Bearer secre"))), 401);
+        assert_eq!(status(&handle(&context, "POST", "/latest", Some("Bearer secret"))), 405);
+        assert_eq!(status(&get(&context, "/latest")), 200);
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_file_restricted_to_screenshot_dir() {
+        let root = scratch_dir("server-file");
+        let shots = root.join("shots");
+        std::fs::create_dir_all(shots.join("2026")).unwrap();
+        std::fs::write(shots.join("2026").join("a.png"), b"png").unwrap();
+        std::fs::write(shots.join("notes.txt"), b"text").unwrap();
+        std::fs::write(root.join("secret.png"), b"secret").unwrap();
+
+        // Relative and absolute paths inside the directory are served
+        let (path, content_type) = resolve_file(&shots, "2026/a.png").unwrap();
+        assert!(path.ends_with("a.png"));
+        assert_eq!(content_type, "image/png");
+        let absolute = shots.join("2026").join("a.png");
+        assert!(resolve_file(&shots, &absolute.to_string_lossy()).is_some());
+
+        // Traversal and absolute paths outside are refused
+        assert!(resolve_file(&shots, "../secret.png").is_none());
+        assert!(resolve_file(&shots, "2026/../../secret.png").is_none());
+        assert!(resolve_file(&shots, &root.join("secret.png").to_string_lossy()).is_none());
+
+        // Only existing image files
+        assert!(resolve_file(&shots, "notes.txt").is_none());
+        assert!(resolve_file(&shots, "missing.png").is_none());
+        assert!(resolve_file(&shots, "2026").is_none());
+
+        let context = context(shots.clone(), Box::new(|_, _| Ok(Vec::new())));
+        assert_eq!(status(&get(&context, "/file?path=..%2Fsecret.png")), 404);
+        assert!(matches!(get(&context, "/file?path=2026%2Fa.png"), Reply::File(..)));
+
+        let _ = std::fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn test_search_against_temp_index() {
+        let dir = scratch_dir("server-search");
+        let db_path = dir.join("vector_index.db");
+
+        // "cat" -> dimension 0, "dog" -> dimension 1; the deleted file sits
+        // close to "dog" so it would rank second if it weren't filtered out
+        let embed = |word: &str| {
+            let mut vector = vec![0.0f32; 768];
+            match word {
+                "gone" => {
+                    vector[1] = 0.8;
+                    vector[2] = 0.6;
+                }
+                _ if word.contains("dog") => vector[1] = 1.0,
+                _ => vector[0] = 1.0,
+            }
+            vector
+        };
+        let cat = dir.join("cat.png");
+        let dog = dir.join("dog.png");
+        let gone = dir.join("gone.png");
+        std::fs::write(&cat, b"png").unwrap();
+        std::fs::write(&dog, b"png").unwrap();
+
+        let rt = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap();
+        rt.block_on(crate::indexer::write_rows(
+            &db_path,
+            &[
+                (cat.clone(), embed("cat")),
+                (dog.clone(), embed("dog")),
+                (gone.clone(), embed("gone")),
+            ],
+        ))
+        .unwrap();
+
+        let search_db = db_path.clone();
+        let search: SearchFn = Box::new(move |query, limit| {
+            let rt = tokio::runtime::Builder::new_current_thread()
+                .enable_all()
+                .build()?;
+            rt.block_on(crate::indexer::search_by_vector(&search_db, &embed(query), limit))
+        });
+        let context = context(dir.clone(), search);
+
+        let Reply::Json(200, body) = get(&context, "/search?q=a+dog&limit=1") else {
+            panic!("search failed");
+        };
+        assert_eq!(body["query"], "a dog");
+        let results = body["results"].as_array().unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0]["name"], "dog.png");
+
+        // Deleted files never show up
+        let Reply::Json(200, body) = get(&context, "/search?q=dog") else {
+            panic!("search failed");
+        };
+        let names: Vec<_> = body["results"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|r| r["name"].as_str().unwrap().to_string())
+            .collect();
+        assert!(!names.contains(&"gone.png".to_string()));
+        assert_eq!(names.first().map(String::as_str), Some("dog.png"));
+
+        assert_eq!(status(&get(&context, "/search")), 400);
+        assert_eq!(status(&get(&context, "/search?q=dog&limit=x")), 400);
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -1,9 +1,11 @@
 //! Application settings and persistence
 
 use anyhow::Result;
+use chrono::{DateTime, Local};
 use directories::ProjectDirs;
 use log::info;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::fs;
 use std::path::PathBuf;
 
@@ -31,11 +33,46 @@ impl ThemeMode {
     }
 }
 
+/// One of the two saved window layouts
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum LayoutSlot {
+    A,
+    B,
+}
+
+impl LayoutSlot {
+    pub fn other(self) -> Self {
+        match self {
+            LayoutSlot::A => LayoutSlot::B,
+            LayoutSlot::B => LayoutSlot::A,
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            LayoutSlot::A => "A",
+            LayoutSlot::B => "B",
+        }
+    }
+}
+
+/// A saved window position and size in physical pixels, with the DPI of the
+/// monitor it was saved on
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WindowLayout {
+    pub x: i32,
+    pub y: i32,
+    pub width: i32,
+    pub height: i32,
+    pub dpi: u32,
+}
+
 /// Supported conversion formats
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum ConversionFormat {
     WebP,
     Jpeg,
+    Avif,
 }
 
 impl Default for ConversionFormat {
@@ -49,6 +86,7 @@ impl ConversionFormat {
         match self {
             ConversionFormat::WebP => "webp",
             ConversionFormat::Jpeg => "jpg",
+            ConversionFormat::Avif => "avif",
         }
     }
 
@@ -56,10 +94,139 @@ impl ConversionFormat {
         match self {
             ConversionFormat::WebP => "WebP",
             ConversionFormat::Jpeg => "JPEG",
+            ConversionFormat::Avif => "AVIF",
+        }
+    }
+}
+
+/// When the embedding models are loaded into memory
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ModelPrewarm {
+    /// Load at startup and keep resident
+    OnStartup,
+    /// Load the first time search/indexing needs them, then keep resident
+    OnFirstUse,
+    /// Load for each use and drop afterwards; no automatic indexing
+    Never,
+}
+
+impl Default for ModelPrewarm {
+    fn default() -> Self {
+        ModelPrewarm::OnStartup
+    }
+}
+
+/// When files auto-index missed are picked up without pressing Index
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum IndexSchedule {
+    /// Only on request
+    Off,
+    /// Every `index_schedule_hours`
+    Interval,
+    /// At startup, when the last run is `index_schedule_hours` old
+    OnStart,
+}
+
+impl Default for IndexSchedule {
+    fn default() -> Self {
+        IndexSchedule::Interval
+    }
+}
+
+/// What the badge in a gallery tile's corner shows
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BadgeContent {
+    /// "PNG | 1.2 MB"
+    ExtensionSize,
+    /// "1.2 MB"
+    SizeOnly,
+    /// File name, middle-ellipsized to fit the tile
+    FileName,
+    /// No badge
+    None,
+}
+
+impl Default for BadgeContent {
+    fn default() -> Self {
+        BadgeContent::ExtensionSize
+    }
+}
+
+/// Order of the gallery
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SortMode {
+    /// Most recently modified first, grouped by date
+    Newest,
+    /// Least recently modified first, grouped by date
+    Oldest,
+    /// Biggest files first
+    Largest,
+    /// File name A–Z, ignoring case
+    Name,
+}
+
+impl Default for SortMode {
+    fn default() -> Self {
+        SortMode::Newest
+    }
+}
+
+impl SortMode {
+    /// Whether the gallery shows Today/Yesterday/... headers in this order
+    pub fn groups_by_date(self) -> bool {
+        matches!(self, SortMode::Newest | SortMode::Oldest)
+    }
+}
+
+/// What the gallery's group headers split screenshots by
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GalleryGrouping {
+    /// Today/Yesterday/..., in the orders that go by date
+    Date,
+    /// Folder relative to the screenshot folder, in every order
+    Folder,
+}
+
+impl Default for GalleryGrouping {
+    fn default() -> Self {
+        GalleryGrouping::Date
+    }
+}
+
+impl GalleryGrouping {
+    /// Whether the gallery has group headers with this grouping and order
+    pub fn has_headers(self, sort: SortMode) -> bool {
+        match self {
+            GalleryGrouping::Date => sort.groups_by_date(),
+            GalleryGrouping::Folder => true,
         }
     }
 }
 
+/// What Escape does once there's no selection to clear or settings page to close
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EscapeAction {
+    /// Hide the window to the tray
+    Hide,
+    /// Minimize the window to the taskbar
+    Minimize,
+    /// Nothing; the window only closes from its buttons or the hotkey
+    None,
+}
+
+impl Default for EscapeAction {
+    fn default() -> Self {
+        // Becomes Hide once the header has a hide-window button to match
+        EscapeAction::Minimize
+    }
+}
+
 /// Application settings
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Settings {
@@ -79,9 +246,31 @@ pub struct Settings {
     #[serde(default)]
     pub conversion_format: ConversionFormat,
 
-    /// Conversion quality (0-100)
+    /// WebP conversion quality (1-100)
     pub webp_quality: u32,
 
+    /// Encode WebP losslessly instead of at `webp_quality`
+    #[serde(default)]
+    pub webp_lossless: bool,
+
+    /// JPEG conversion quality (1-100). Settings saved before this existed
+    /// used `webp_quality` for both formats, so it's carried over from there.
+    #[serde(default = "default_jpeg_quality")]
+    pub jpeg_quality: u32,
+
+    /// AVIF conversion quality (1-100)
+    #[serde(default = "default_avif_quality")]
+    pub avif_quality: u32,
+
+    /// Downscale converted images whose longest edge is over this many
+    /// pixels (0 = keep the original size)
+    #[serde(default)]
+    pub conversion_max_dimension: u32,
+
+    /// Auto-convert leaves PNGs under this many KB alone (0 = convert all)
+    #[serde(default)]
+    pub convert_min_size_kb: u32,
+
     /// Window width
     pub window_width: f32,
 
@@ -96,6 +285,40 @@ pub struct Settings {
     #[serde(default = "default_hotkey")]
     pub hotkey: String,
 
+    /// Global hotkey to move the window to the next monitor
+    #[serde(default = "default_next_monitor_hotkey")]
+    pub next_monitor_hotkey: String,
+
+    /// Global hotkey to move the window to the previous monitor
+    #[serde(default = "default_previous_monitor_hotkey")]
+    pub previous_monitor_hotkey: String,
+
+    /// Remapped in-app shortcuts (action id -> keystroke string); missing actions use defaults
+    #[serde(default)]
+    pub shortcut_bindings: BTreeMap<String, String>,
+
+    /// Global hotkey to open the latest screenshot in the editor (empty = unbound)
+    #[serde(default)]
+    pub edit_latest_hotkey: String,
+
+    /// External image editor (None = the shell "edit" verb)
+    #[serde(default)]
+    pub preferred_editor: Option<PathBuf>,
+
+    /// Saved window layouts, switched between from the header
+    #[serde(default)]
+    pub layout_a: Option<WindowLayout>,
+    #[serde(default)]
+    pub layout_b: Option<WindowLayout>,
+
+    /// Layout last applied; showing the window from the tray puts it back there
+    #[serde(default)]
+    pub active_layout: Option<LayoutSlot>,
+
+    /// What Escape does after clearing the selection and closing settings
+    #[serde(default)]
+    pub escape_action: EscapeAction,
+
     /// Screenshot organizer enabled
     #[serde(default)]
     pub organizer_enabled: bool,
@@ -104,6 +327,32 @@ pub struct Settings {
     #[serde(default = "default_organizer_format")]
     pub organizer_format: String,
 
+    /// Date screenshots by the date in their file name when there is one
+    /// (organizer folders and gallery groups), falling back to the mtime
+    #[serde(default)]
+    pub date_from_file_name: bool,
+
+    /// Regexes for `date_from_file_name`, with named year/month/day groups
+    #[serde(default = "crate::filename_date::default_patterns")]
+    pub file_name_date_patterns: Vec<String>,
+
+    /// File name globs (`*`, `?`) the watcher, organizer and indexer skip
+    #[serde(default)]
+    pub exclude_patterns: Vec<String>,
+
+    /// Name pattern for new screenshots, when `rename_new_screenshots` is on
+    #[serde(default = "default_file_name_pattern")]
+    pub file_name_pattern: String,
+
+    /// Rename new screenshots after `file_name_pattern` before they're
+    /// converted or organized
+    #[serde(default)]
+    pub rename_new_screenshots: bool,
+
+    /// Last search run, offered again after a restart
+    #[serde(default)]
+    pub last_search_query: String,
+
     /// Vector search indexing enabled
     #[serde(default)]
     pub indexing_enabled: bool,
@@ -116,14 +365,58 @@ pub struct Settings {
     #[serde(default)]
     pub models_downloaded: bool,
 
+    /// When to load the embedding models
+    #[serde(default)]
+    pub model_prewarm: ModelPrewarm,
+
+    /// How alike (percent) screenshots must be for "Select Similar"
+    #[serde(default = "default_similar_threshold")]
+    pub similar_threshold: u32,
+
+    /// How alike (percent) a screenshot must be to a search to be shown
+    #[serde(default = "default_search_min_similarity")]
+    pub search_min_similarity: u32,
+
     /// Last indexed image count (for stats display)
     #[serde(default)]
     pub last_indexed_count: usize,
 
+    /// When the incremental index runs on its own
+    #[serde(default)]
+    pub index_schedule: IndexSchedule,
+
+    /// Hours between scheduled index runs
+    #[serde(default = "default_index_schedule_hours")]
+    pub index_schedule_hours: u32,
+
+    /// When an index run last finished, for the schedule
+    #[serde(default)]
+    pub last_index_run: Option<DateTime<Local>>,
+
     /// User-selected language (None = auto-detect from system)
     #[serde(default)]
     pub language: Option<String>,
 
+    /// Gallery tile badge content
+    #[serde(default)]
+    pub badge_content: BadgeContent,
+
+    /// Show sizes in KB/MB like Explorer (powers of 1000) instead of KiB/MiB
+    #[serde(default)]
+    pub decimal_size_units: bool,
+
+    /// Show the folder a screenshot is in under the preview
+    #[serde(default)]
+    pub preview_show_folder: bool,
+
+    /// Gallery order
+    #[serde(default)]
+    pub gallery_sort: SortMode,
+
+    /// Gallery group headers
+    #[serde(default)]
+    pub gallery_grouping: GalleryGrouping,
+
     /// Window transparency (0.0 = fully transparent, 1.0 = fully opaque)
     #[serde(default = "default_window_opacity")]
     pub window_opacity: f32,
@@ -139,6 +432,85 @@ pub struct Settings {
     /// Hide window on start (show only tray icon)
     #[serde(default)]
     pub hide_window_on_start: bool,
+
+    /// Animate toasts and notifications (also off when Windows' "Show animations" is off)
+    #[serde(default = "default_animations_enabled")]
+    pub animations_enabled: bool,
+
+    /// Play a system sound when a copy/save/export completes
+    #[serde(default)]
+    pub sound_feedback: bool,
+
+    /// Clear the read-only attribute when deleting or moving a file instead of asking
+    #[serde(default)]
+    pub handle_read_only_files: bool,
+
+    /// Minimum age in days before an unused screenshot shows up on the cleanup page
+    #[serde(default = "default_cleanup_unused_days")]
+    pub cleanup_unused_days: u32,
+
+    /// Show the Advanced page in the settings sidebar
+    #[serde(default)]
+    pub show_advanced_settings: bool,
+
+    /// Serve the local read-only HTTP API (127.0.0.1 only)
+    #[serde(default)]
+    pub api_server_enabled: bool,
+
+    /// Port for the local API, so other apps can be set up once
+    #[serde(default = "default_api_server_port")]
+    pub api_server_port: u16,
+
+    /// Bearer token required by the local API (generated on first enable)
+    #[serde(default)]
+    pub api_server_token: String,
+
+    /// Ask before opening the shell context menu for more files than this
+    #[serde(default = "default_context_menu_confirm_threshold")]
+    pub context_menu_confirm_threshold: usize,
+
+    /// How long the watcher waits for a file to settle before handling it
+    #[serde(default = "default_watcher_debounce_ms")]
+    pub watcher_debounce_ms: u64,
+
+    /// New files in one debounce window above which the folder is rescanned
+    /// instead of running the pipeline per file (0 = never)
+    #[serde(default = "default_watcher_burst_threshold")]
+    pub watcher_burst_threshold: usize,
+
+    /// Rescan the folder this often instead of relying on change
+    /// notifications, for network and cloud-synced folders (0 = notifications)
+    #[serde(default)]
+    pub watcher_poll_interval_secs: u64,
+
+    /// Files in a screenshot directory above which switching to it asks
+    /// first, and the first scan shows only the newest this many
+    #[serde(default = "default_large_directory_files")]
+    pub large_directory_files: usize,
+
+    /// Conversions run at once (0 = automatic, see `convert_pool::default_workers`)
+    #[serde(default)]
+    pub conversion_workers: usize,
+
+    /// Screenshots kept in memory in full; older ones are loaded when needed
+    #[serde(default = "default_max_gallery_items")]
+    pub max_gallery_items: usize,
+
+    /// Record per-frame render timings (also turned on by `--perf-trace`)
+    #[serde(default)]
+    pub perf_trace: bool,
+}
+
+fn default_jpeg_quality() -> u32 {
+    90
+}
+
+fn default_avif_quality() -> u32 {
+    70
+}
+
+fn default_index_schedule_hours() -> u32 {
+    24
 }
 
 fn default_hotkey_enabled() -> bool {
@@ -149,18 +521,70 @@ fn default_hotkey() -> String {
     "Ctrl+Shift+S".to_string()
 }
 
+fn default_next_monitor_hotkey() -> String {
+    "Ctrl+Shift+Alt+Right".to_string()
+}
+
+fn default_previous_monitor_hotkey() -> String {
+    "Ctrl+Shift+Alt+Left".to_string()
+}
+
 fn default_organizer_format() -> String {
     "YYYY-MM-DD".to_string()
 }
 
+fn default_file_name_pattern() -> String {
+    crate::naming::DEFAULT_PATTERN.to_string()
+}
+
 fn default_cpu_mode() -> String {
     "normal".to_string()
 }
 
+fn default_similar_threshold() -> u32 {
+    92
+}
+
+fn default_search_min_similarity() -> u32 {
+    3
+}
+
+fn default_animations_enabled() -> bool {
+    true
+}
+
 fn default_window_opacity() -> f32 {
     1.0 // Fully opaque by default
 }
 
+fn default_cleanup_unused_days() -> u32 {
+    30
+}
+
+fn default_api_server_port() -> u16 {
+    48123
+}
+
+fn default_context_menu_confirm_threshold() -> usize {
+    100
+}
+
+fn default_watcher_debounce_ms() -> u64 {
+    200
+}
+
+fn default_watcher_burst_threshold() -> usize {
+    50
+}
+
+fn default_large_directory_files() -> usize {
+    20_000
+}
+
+fn default_max_gallery_items() -> usize {
+    crate::screenshot_list::DEFAULT_MAX_IN_MEMORY
+}
+
 impl Default for Settings {
     fn default() -> Self {
         Self {
@@ -170,21 +594,68 @@ impl Default for Settings {
             auto_convert_webp: false,
             conversion_format: ConversionFormat::WebP,
             webp_quality: 85,
+            webp_lossless: false,
+            jpeg_quality: default_jpeg_quality(),
+            avif_quality: default_avif_quality(),
+            conversion_max_dimension: 0,
+            convert_min_size_kb: 0,
             window_width: 815.0,
             window_height: 550.0,
             hotkey_enabled: true,
             hotkey: "Ctrl+Shift+S".to_string(),
+            next_monitor_hotkey: default_next_monitor_hotkey(),
+            previous_monitor_hotkey: default_previous_monitor_hotkey(),
+            shortcut_bindings: BTreeMap::new(),
+            edit_latest_hotkey: String::new(),
+            preferred_editor: None,
+            layout_a: None,
+            layout_b: None,
+            active_layout: None,
+            escape_action: EscapeAction::Minimize,
             organizer_enabled: false,
             organizer_format: "YYYY-MM-DD".to_string(),
+            date_from_file_name: false,
+            file_name_date_patterns: crate::filename_date::default_patterns(),
+            exclude_patterns: Vec::new(),
+            file_name_pattern: default_file_name_pattern(),
+            rename_new_screenshots: false,
+            last_search_query: String::new(),
             indexing_enabled: false,
             indexing_cpu_mode: "normal".to_string(),
             models_downloaded: false,
+            model_prewarm: ModelPrewarm::OnStartup,
+            similar_threshold: default_similar_threshold(),
+            search_min_similarity: default_search_min_similarity(),
             last_indexed_count: 0,
+            index_schedule: IndexSchedule::default(),
+            index_schedule_hours: default_index_schedule_hours(),
+            last_index_run: None,
             language: None, // Auto-detect from system
+            badge_content: BadgeContent::ExtensionSize,
+            decimal_size_units: false,
+            preview_show_folder: false,
+            gallery_sort: SortMode::Newest,
+            gallery_grouping: GalleryGrouping::Date,
             window_opacity: 1.0, // Fully opaque by default
             theme: ThemeMode::Dark, // Dark theme by default
             run_on_startup: false, // Don't run on startup by default
             hide_window_on_start: false, // Show window by default
+            animations_enabled: true,
+            sound_feedback: false,
+            handle_read_only_files: false,
+            cleanup_unused_days: 30,
+            show_advanced_settings: false,
+            api_server_enabled: false,
+            api_server_port: default_api_server_port(),
+            api_server_token: String::new(),
+            context_menu_confirm_threshold: default_context_menu_confirm_threshold(),
+            watcher_debounce_ms: default_watcher_debounce_ms(),
+            watcher_burst_threshold: default_watcher_burst_threshold(),
+            watcher_poll_interval_secs: 0,
+            large_directory_files: default_large_directory_files(),
+            conversion_workers: 0,
+            max_gallery_items: default_max_gallery_items(),
+            perf_trace: false,
         }
     }
 }
@@ -223,12 +694,88 @@ impl Settings {
         }
 
         let content = fs::read_to_string(&path)?;
-        let settings: Self = serde_json::from_str(&content)?;
+        let settings = Self::from_json(&content)?;
 
         info!("Loaded settings from {:?}", path);
         Ok(settings)
     }
 
+    pub fn layout(&self, slot: LayoutSlot) -> Option<WindowLayout> {
+        match slot {
+            LayoutSlot::A => self.layout_a,
+            LayoutSlot::B => self.layout_b,
+        }
+    }
+
+    pub fn set_layout(&mut self, slot: LayoutSlot, layout: WindowLayout) {
+        match slot {
+            LayoutSlot::A => self.layout_a = Some(layout),
+            LayoutSlot::B => self.layout_b = Some(layout),
+        }
+    }
+
+    /// The layout the window should be in, if one was applied
+    pub fn active_window_layout(&self) -> Option<WindowLayout> {
+        self.active_layout.and_then(|slot| self.layout(slot))
+    }
+
+    /// Layout the toggle switches to: the other one, or the only one saved
+    pub fn next_layout(&self) -> Option<LayoutSlot> {
+        let preferred = self.active_layout.map_or(LayoutSlot::A, LayoutSlot::other);
+        [preferred, preferred.other()]
+            .into_iter()
+            .find(|slot| self.layout(*slot).is_some())
+    }
+
+    /// Parse settings.json, carrying values over into fields older versions
+    /// didn't have
+    fn from_json(content: &str) -> Result<Self> {
+        let value: serde_json::Value = serde_json::from_str(content)?;
+        let mut settings: Self = serde_json::from_value(value.clone())?;
+        if value.get("jpeg_quality").is_none() {
+            settings.jpeg_quality = settings.webp_quality;
+        }
+        // 0 used to mean a random port each start
+        if settings.api_server_port < crate::server::MIN_PORT {
+            settings.api_server_port = default_api_server_port();
+        }
+        Ok(settings)
+    }
+
+    /// Quality setting for converting to `format`
+    pub fn quality_for(&self, format: ConversionFormat) -> u32 {
+        match format {
+            ConversionFormat::WebP => self.webp_quality,
+            ConversionFormat::Jpeg => self.jpeg_quality,
+            ConversionFormat::Avif => self.avif_quality,
+        }
+    }
+
+    /// Quality setting for `format`, to adjust it
+    pub fn quality_for_mut(&mut self, format: ConversionFormat) -> &mut u32 {
+        match format {
+            ConversionFormat::WebP => &mut self.webp_quality,
+            ConversionFormat::Jpeg => &mut self.jpeg_quality,
+            ConversionFormat::Avif => &mut self.avif_quality,
+        }
+    }
+
+    /// Put everything on the Advanced page back to its default
+    pub fn reset_advanced(&mut self) {
+        let defaults = Self::default();
+        self.api_server_enabled = defaults.api_server_enabled;
+        self.api_server_port = defaults.api_server_port;
+        self.api_server_token = defaults.api_server_token;
+        self.context_menu_confirm_threshold = defaults.context_menu_confirm_threshold;
+        self.watcher_debounce_ms = defaults.watcher_debounce_ms;
+        self.watcher_burst_threshold = defaults.watcher_burst_threshold;
+        self.watcher_poll_interval_secs = defaults.watcher_poll_interval_secs;
+        self.large_directory_files = defaults.large_directory_files;
+        self.conversion_workers = defaults.conversion_workers;
+        self.max_gallery_items = defaults.max_gallery_items;
+        self.perf_trace = defaults.perf_trace;
+    }
+
     /// Save settings to disk
     pub fn save(&self) -> Result<()> {
         let path = Self::config_path()
@@ -268,12 +815,14 @@ mod tests {
     fn test_conversion_format_extension() {
         assert_eq!(ConversionFormat::WebP.extension(), "webp");
         assert_eq!(ConversionFormat::Jpeg.extension(), "jpg");
+        assert_eq!(ConversionFormat::Avif.extension(), "avif");
     }
 
     #[test]
     fn test_conversion_format_display_name() {
         assert_eq!(ConversionFormat::WebP.display_name(), "WebP");
         assert_eq!(ConversionFormat::Jpeg.display_name(), "JPEG");
+        assert_eq!(ConversionFormat::Avif.display_name(), "AVIF");
     }
 
     #[test]
@@ -294,6 +843,42 @@ mod tests {
         assert_eq!(settings.organizer_format, "YYYY-MM-DD");
     }
 
+    #[test]
+    fn test_reset_advanced() {
+        let mut settings = Settings {
+            show_advanced_settings: true,
+            api_server_enabled: true,
+            api_server_port: 9000,
+            api_server_token: "token".to_string(),
+            context_menu_confirm_threshold: 500,
+            watcher_debounce_ms: 1000,
+            watcher_burst_threshold: 0,
+            watcher_poll_interval_secs: 30,
+            large_directory_files: 1000,
+            conversion_workers: 6,
+            max_gallery_items: 5000,
+            perf_trace: true,
+            thumbnail_size: 200,
+            ..Settings::default()
+        };
+        settings.reset_advanced();
+
+        assert!(!settings.api_server_enabled);
+        assert_eq!(settings.api_server_port, 48123);
+        assert!(settings.api_server_token.is_empty());
+        assert_eq!(settings.context_menu_confirm_threshold, 100);
+        assert_eq!(settings.watcher_debounce_ms, 200);
+        assert_eq!(settings.watcher_burst_threshold, 50);
+        assert_eq!(settings.watcher_poll_interval_secs, 0);
+        assert_eq!(settings.large_directory_files, 20_000);
+        assert_eq!(settings.conversion_workers, 0);
+        assert_eq!(settings.max_gallery_items, 20_000);
+        assert!(!settings.perf_trace);
+        // The page stays visible, and other settings are untouched
+        assert!(settings.show_advanced_settings);
+        assert_eq!(settings.thumbnail_size, 200);
+    }
+
     #[test]
     fn test_settings_serialization() {
         let settings = Settings::default();
@@ -353,6 +938,12 @@ mod tests {
         let json = serde_json::to_string(&format).unwrap();
         let deserialized: ConversionFormat = serde_json::from_str(&json).unwrap();
         assert_eq!(deserialized, ConversionFormat::Jpeg);
+
+        // Test Avif
+        let format = ConversionFormat::Avif;
+        let json = serde_json::to_string(&format).unwrap();
+        let deserialized: ConversionFormat = serde_json::from_str(&json).unwrap();
+        assert_eq!(deserialized, ConversionFormat::Avif);
     }
 
     #[test]
@@ -362,5 +953,99 @@ mod tests {
         // Default quality should be in valid range
         assert!(settings.webp_quality >= 1);
         assert!(settings.webp_quality <= 100);
+        assert!(settings.jpeg_quality >= 1);
+        assert!(settings.jpeg_quality <= 100);
+        assert!(settings.avif_quality >= 1);
+        assert!(settings.avif_quality <= 100);
+    }
+
+    #[test]
+    fn test_next_layout() {
+        let layout = WindowLayout {
+            x: 0,
+            y: 0,
+            width: 480,
+            height: 360,
+            dpi: 96,
+        };
+        let mut settings = Settings::default();
+        assert_eq!(settings.next_layout(), None);
+
+        // Only B saved: that's where the toggle goes, every time
+        settings.set_layout(LayoutSlot::B, layout);
+        assert_eq!(settings.next_layout(), Some(LayoutSlot::B));
+        settings.active_layout = Some(LayoutSlot::B);
+        assert_eq!(settings.next_layout(), Some(LayoutSlot::B));
+
+        // Both saved: back and forth
+        settings.set_layout(LayoutSlot::A, layout);
+        assert_eq!(settings.next_layout(), Some(LayoutSlot::A));
+        settings.active_layout = Some(LayoutSlot::A);
+        assert_eq!(settings.next_layout(), Some(LayoutSlot::B));
+        assert_eq!(settings.active_window_layout(), Some(layout));
+    }
+
+    #[test]
+    fn test_index_schedule_roundtrip() {
+        let mut settings = Settings::default();
+        assert_eq!(settings.index_schedule, IndexSchedule::Interval);
+        assert_eq!(settings.index_schedule_hours, 24);
+        assert_eq!(settings.last_index_run, None);
+
+        settings.index_schedule = IndexSchedule::OnStart;
+        settings.last_index_run = Some(Local::now());
+        let json = serde_json::to_string(&settings).unwrap();
+        let restored = Settings::from_json(&json).unwrap();
+        assert_eq!(restored.index_schedule, IndexSchedule::OnStart);
+        assert_eq!(restored.last_index_run, settings.last_index_run);
+    }
+
+    #[test]
+    fn test_jpeg_quality_migrates_from_shared_quality() {
+        // Saved before JPEG had its own quality: the shared value applied to both
+        let json = r#"{
+            "screenshot_directory": "/custom/path",
+            "grid_columns": 4,
+            "thumbnail_size": 150,
+            "auto_convert_webp": true,
+            "conversion_format": "Jpeg",
+            "webp_quality": 70,
+            "window_width": 815.0,
+            "window_height": 550.0
+        }"#;
+        let settings = Settings::from_json(json).unwrap();
+        assert_eq!(settings.webp_quality, 70);
+        assert_eq!(settings.jpeg_quality, 70);
+        assert_eq!(settings.quality_for(ConversionFormat::Jpeg), 70);
+        assert_eq!(settings.conversion_max_dimension, 0);
+        assert_eq!(settings.convert_min_size_kb, 0);
+
+        // Saved since: each keeps its own
+        let mut settings = settings;
+        *settings.quality_for_mut(ConversionFormat::Jpeg) = 95;
+        *settings.quality_for_mut(ConversionFormat::Avif) = 40;
+        let json = serde_json::to_string(&settings).unwrap();
+        let settings = Settings::from_json(&json).unwrap();
+        assert_eq!(settings.quality_for(ConversionFormat::WebP), 70);
+        assert_eq!(settings.quality_for(ConversionFormat::Jpeg), 95);
+        assert_eq!(settings.quality_for(ConversionFormat::Avif), 40);
+    }
+
+    #[test]
+    fn test_api_port_migrated() {
+        // Saved when 0 picked a random port: now the fixed default
+        let settings = Settings {
+            api_server_port: 0,
+            ..Settings::default()
+        };
+        let json = serde_json::to_string(&settings).unwrap();
+        assert_eq!(Settings::from_json(&json).unwrap().api_server_port, 48123);
+
+        let settings = Settings {
+            api_server_port: 9000,
+            ..Settings::default()
+        };
+        let json = serde_json::to_string(&settings).unwrap();
+        assert_eq!(Settings::from_json(&json).unwrap().api_server_port, 9000);
     }
 }
@@ -0,0 +1,211 @@
+//! Getting the global hotkeys and tray icon back after the shell loses them
+//!
+//! Waking from sleep can leave `RegisterHotKey` registrations dead, and an
+//! Explorer restart takes the notification area (and our icon) with it. The
+//! main window is subclassed to watch for `WM_POWERBROADCAST` with
+//! `PBT_APMRESUMEAUTOMATIC` and for the `TaskbarCreated` broadcast; either one
+//! sends [`AppMessage::ShellRecovery`] so the UI thread, which owns the hotkey
+//! manager and the tray icon, can register them again. Outcomes are kept
+//! here for the Diagnostics page. The same subclass picks up changes to the
+//! system animation setting (`WM_SETTINGCHANGE`) for [`crate::motion`].
+
+use crossbeam_channel::Sender;
+use parking_lot::Mutex;
+use std::time::SystemTime;
+
+use crate::AppMessage;
+
+/// Failures in a row before the Diagnostics page calls it out
+pub const REPEATED_FAILURES: u32 = 2;
+
+/// What knocked the hotkeys or tray icon out
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Trigger {
+    /// The system woke from sleep or hibernation
+    Resume,
+    /// Explorer restarted and recreated the taskbar
+    TaskbarCreated,
+}
+
+/// One recovery attempt
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Attempt {
+    pub at: SystemTime,
+    pub trigger: Trigger,
+    /// What couldn't be registered again, if anything
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Default)]
+pub struct RecoveryLog {
+    last: Option<Attempt>,
+    /// Failed attempts since the last one that worked
+    consecutive_failures: u32,
+}
+
+impl RecoveryLog {
+    pub fn record(&mut self, attempt: Attempt) {
+        if attempt.error.is_some() {
+            self.consecutive_failures += 1;
+        } else {
+            self.consecutive_failures = 0;
+        }
+        self.last = Some(attempt);
+    }
+
+    pub fn last(&self) -> Option<&Attempt> {
+        self.last.as_ref()
+    }
+
+    pub fn consecutive_failures(&self) -> u32 {
+        self.consecutive_failures
+    }
+
+    /// Whether recovery keeps failing
+    pub fn is_failing(&self) -> bool {
+        self.consecutive_failures >= REPEATED_FAILURES
+    }
+}
+
+static LOG: Mutex<RecoveryLog> = Mutex::new(RecoveryLog {
+    last: None,
+    consecutive_failures: 0,
+});
+
+/// Record how a recovery went
+pub fn record(trigger: Trigger, error: Option<String>) {
+    LOG.lock().record(Attempt {
+        at: SystemTime::now(),
+        trigger,
+        error,
+    });
+}
+
+/// Latest attempt and failures in a row, for the Diagnostics page
+pub fn status() -> (Option<Attempt>, u32) {
+    let log = LOG.lock();
+    (log.last().cloned(), log.consecutive_failures())
+}
+
+/// Whether recovery keeps failing
+pub fn is_failing() -> bool {
+    LOG.lock().is_failing()
+}
+
+#[cfg(windows)]
+mod subclass {
+    use std::sync::atomic::{AtomicU32, Ordering};
+    use std::sync::OnceLock;
+
+    use crossbeam_channel::Sender;
+    use log::{debug, info, warn};
+    use windows::core::w;
+    use windows::Win32::Foundation::{HWND, LPARAM, LRESULT, WPARAM};
+    use windows::Win32::UI::Shell::{DefSubclassProc, SetWindowSubclass};
+    use windows::Win32::UI::WindowsAndMessaging::{
+        RegisterWindowMessageW, PBT_APMRESUMEAUTOMATIC, WM_POWERBROADCAST, WM_SETTINGCHANGE,
+    };
+
+    use super::Trigger;
+    use crate::AppMessage;
+
+    const SUBCLASS_ID: usize = 0x5355_4B55;
+
+    /// Message id of the `TaskbarCreated` broadcast (0 until registered)
+    static TASKBAR_CREATED: AtomicU32 = AtomicU32::new(0);
+
+    static SENDER: OnceLock<Sender<AppMessage>> = OnceLock::new();
+
+    pub fn install(hwnd: isize, message_tx: Sender<AppMessage>) {
+        let _ = SENDER.set(message_tx);
+        unsafe {
+            let taskbar_created = RegisterWindowMessageW(w!("TaskbarCreated"));
+            if taskbar_created == 0 {
+                warn!("Failed to register the TaskbarCreated message");
+            }
+            TASKBAR_CREATED.store(taskbar_created, Ordering::SeqCst);
+
+            let hwnd = HWND(hwnd as *mut std::ffi::c_void);
+            if SetWindowSubclass(hwnd, Some(subclass_proc), SUBCLASS_ID, 0).as_bool() {
+                info!("Watching for resume and taskbar restarts");
+            } else {
+                warn!("Failed to subclass the main window; hotkeys won't be restored after sleep");
+            }
+        }
+    }
+
+    unsafe extern "system" fn subclass_proc(
+        hwnd: HWND,
+        msg: u32,
+        wparam: WPARAM,
+        lparam: LPARAM,
+        _id: usize,
+        _data: usize,
+    ) -> LRESULT {
+        if msg == WM_SETTINGCHANGE {
+            crate::motion::refresh_system();
+        }
+
+        let taskbar_created = TASKBAR_CREATED.load(Ordering::SeqCst);
+        let trigger = if msg == WM_POWERBROADCAST && wparam.0 == PBT_APMRESUMEAUTOMATIC as usize {
+            Some(Trigger::Resume)
+        } else if taskbar_created != 0 && msg == taskbar_created {
+            Some(Trigger::TaskbarCreated)
+        } else {
+            None
+        };
+
+        if let Some(trigger) = trigger {
+            debug!("Shell event: {:?}", trigger);
+            if let Some(sender) = SENDER.get() {
+                let _ = sender.send(AppMessage::ShellRecovery(trigger));
+            }
+        }
+
+        unsafe { DefSubclassProc(hwnd, msg, wparam, lparam) }
+    }
+}
+
+/// Start watching the main window for resume and taskbar restarts
+#[cfg(windows)]
+pub fn install(hwnd: isize, message_tx: Sender<AppMessage>) {
+    subclass::install(hwnd, message_tx);
+}
+
+#[cfg(not(windows))]
+#[allow(dead_code)]
+pub fn install(_hwnd: isize, _message_tx: Sender<AppMessage>) {
+    // Not implemented for non-Windows
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn attempt(error: Option<&str>) -> Attempt {
+        Attempt {
+            at: SystemTime::UNIX_EPOCH,
+            trigger: Trigger::Resume,
+            error: error.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn test_repeated_failures() {
+        let mut log = RecoveryLog::default();
+        assert!(log.last().is_none());
+
+        log.record(attempt(Some("toggle: already registered")));
+        assert_eq!(log.consecutive_failures(), 1);
+        assert!(!log.is_failing());
+
+        log.record(attempt(Some("toggle: already registered")));
+        assert!(log.is_failing());
+
+        // One that works clears the streak
+        log.record(attempt(None));
+        assert_eq!(log.consecutive_failures(), 0);
+        assert!(!log.is_failing());
+        assert_eq!(log.last().unwrap().error, None);
+    }
+}
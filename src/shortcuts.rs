@@ -0,0 +1,476 @@
+//! In-window keyboard shortcuts
+//!
+//! One registry drives both the key handler and the help overlay, so the
+//! cheatsheet can't drift from what the keys actually do.
+
+use gpui::Keystroke;
+use std::collections::BTreeMap;
+
+/// Where a shortcut shows up in the help overlay
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ShortcutGroup {
+    Gallery,
+    Selection,
+    Search,
+    Window,
+}
+
+impl ShortcutGroup {
+    /// Overlay order
+    pub const ALL: [ShortcutGroup; 4] = [
+        ShortcutGroup::Gallery,
+        ShortcutGroup::Selection,
+        ShortcutGroup::Search,
+        ShortcutGroup::Window,
+    ];
+
+    pub fn display_name(&self) -> String {
+        match self {
+            ShortcutGroup::Gallery => t!("shortcuts.group.gallery").to_string(),
+            ShortcutGroup::Selection => t!("shortcuts.group.selection").to_string(),
+            ShortcutGroup::Search => t!("shortcuts.group.search").to_string(),
+            ShortcutGroup::Window => t!("shortcuts.group.window").to_string(),
+        }
+    }
+}
+
+/// Something a shortcut can do
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ShortcutAction {
+    /// Copy the selected files to the clipboard
+    CopySelection,
+    /// Show the selected screenshot full-size
+    Preview,
+    /// Select everything in the current (searched) set
+    SelectAll,
+    /// Select only the loaded page
+    SelectPage,
+    /// Move the selected files to the Recycle Bin
+    DeleteSelection,
+    /// Delete the selected files for good (press twice)
+    DeleteSelectionPermanently,
+    /// Put the cursor in the search box
+    FocusSearch,
+    /// Clear the selection, close settings, or do what the Escape setting says
+    Back,
+    /// Show or hide this cheatsheet
+    ToggleHelp,
+    /// Switch between the two saved window layouts
+    ToggleLayout,
+}
+
+impl ShortcutAction {
+    /// Every action, in overlay and settings order
+    pub const ALL: [ShortcutAction; 10] = [
+        ShortcutAction::CopySelection,
+        ShortcutAction::Preview,
+        ShortcutAction::SelectAll,
+        ShortcutAction::SelectPage,
+        ShortcutAction::DeleteSelection,
+        ShortcutAction::DeleteSelectionPermanently,
+        ShortcutAction::FocusSearch,
+        ShortcutAction::Back,
+        ShortcutAction::ToggleHelp,
+        ShortcutAction::ToggleLayout,
+    ];
+
+    /// Stable key used in settings.json
+    pub fn id(&self) -> &'static str {
+        match self {
+            ShortcutAction::CopySelection => "copy_selection",
+            ShortcutAction::Preview => "preview",
+            ShortcutAction::SelectAll => "select_all",
+            ShortcutAction::SelectPage => "select_page",
+            ShortcutAction::DeleteSelection => "delete_selection",
+            ShortcutAction::DeleteSelectionPermanently => "delete_selection_permanently",
+            ShortcutAction::FocusSearch => "focus_search",
+            ShortcutAction::Back => "back",
+            ShortcutAction::ToggleHelp => "toggle_help",
+            ShortcutAction::ToggleLayout => "toggle_layout",
+        }
+    }
+
+    /// Built-in bindings
+    pub fn default_keys(&self) -> &'static [&'static str] {
+        match self {
+            ShortcutAction::CopySelection => &["Ctrl+C"],
+            ShortcutAction::Preview => &["Space"],
+            ShortcutAction::SelectAll => &["Ctrl+A"],
+            ShortcutAction::SelectPage => &["Ctrl+Shift+A"],
+            ShortcutAction::DeleteSelection => &["Delete"],
+            ShortcutAction::DeleteSelectionPermanently => &["Shift+Delete"],
+            ShortcutAction::FocusSearch => &["Ctrl+F"],
+            ShortcutAction::Back => &["Escape"],
+            ShortcutAction::ToggleHelp => &["?", "F1"],
+            ShortcutAction::ToggleLayout => &["Ctrl+L"],
+        }
+    }
+
+    pub fn default_bindings(&self) -> Vec<Binding> {
+        self.default_keys()
+            .iter()
+            .filter_map(|k| Binding::parse(k))
+            .collect()
+    }
+
+    pub fn group(&self) -> ShortcutGroup {
+        match self {
+            ShortcutAction::CopySelection | ShortcutAction::Preview => ShortcutGroup::Gallery,
+            ShortcutAction::SelectAll
+            | ShortcutAction::SelectPage
+            | ShortcutAction::DeleteSelection
+            | ShortcutAction::DeleteSelectionPermanently => ShortcutGroup::Selection,
+            ShortcutAction::FocusSearch => ShortcutGroup::Search,
+            ShortcutAction::Back | ShortcutAction::ToggleHelp | ShortcutAction::ToggleLayout => {
+                ShortcutGroup::Window
+            }
+        }
+    }
+
+    pub fn description(&self) -> String {
+        match self {
+            ShortcutAction::CopySelection => t!("shortcuts.action.copy_selection").to_string(),
+            ShortcutAction::Preview => t!("shortcuts.action.preview").to_string(),
+            ShortcutAction::SelectAll => t!("shortcuts.action.select_all").to_string(),
+            ShortcutAction::SelectPage => t!("shortcuts.action.select_page").to_string(),
+            ShortcutAction::DeleteSelection => t!("shortcuts.action.delete_selection").to_string(),
+            ShortcutAction::DeleteSelectionPermanently => {
+                t!("shortcuts.action.delete_selection_permanently").to_string()
+            }
+            ShortcutAction::FocusSearch => t!("shortcuts.action.focus_search").to_string(),
+            ShortcutAction::Back => t!("shortcuts.action.back").to_string(),
+            ShortcutAction::ToggleHelp => t!("shortcuts.action.toggle_help").to_string(),
+            ShortcutAction::ToggleLayout => t!("shortcuts.action.toggle_layout").to_string(),
+        }
+    }
+}
+
+/// One key combination
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Binding {
+    /// GPUI key name ("a", "f1", "escape", "?")
+    key: String,
+    control: bool,
+    shift: bool,
+    alt: bool,
+}
+
+impl Binding {
+    /// Parse "Ctrl+Shift+A", "F1", "Escape" or "?"
+    pub fn parse(s: &str) -> Option<Self> {
+        let mut binding = Binding {
+            key: String::new(),
+            control: false,
+            shift: false,
+            alt: false,
+        };
+
+        for part in s.split('+').map(str::trim) {
+            match part.to_lowercase().as_str() {
+                "ctrl" | "control" => binding.control = true,
+                "shift" => binding.shift = true,
+                "alt" => binding.alt = true,
+                // The Windows key belongs to the shell; in-app shortcuts don't use it
+                "" | "win" | "super" => return None,
+                key if binding.key.is_empty() => {
+                    binding.key = match key {
+                        "esc" => "escape".to_string(),
+                        _ => key.to_string(),
+                    };
+                }
+                _ => return None,
+            }
+        }
+
+        (!binding.key.is_empty()).then_some(binding)
+    }
+
+    /// Binding for a recorded key press. Keys work without modifiers, and a
+    /// shifted symbol is kept as the symbol ("?", not "Shift+/"). `None` for
+    /// the Windows key, a lone modifier, or keys a binding can't spell ("+").
+    pub fn from_keystroke(keystroke: &Keystroke) -> Option<Self> {
+        let modifiers = &keystroke.modifiers;
+        if modifiers.platform {
+            return None;
+        }
+        if matches!(
+            keystroke.key.as_str(),
+            "" | "shift" | "control" | "alt" | "platform" | "function"
+        ) {
+            return None;
+        }
+
+        let symbol = keystroke.key_char.as_deref().filter(|c| {
+            let mut chars = c.chars();
+            matches!((chars.next(), chars.next()), (Some(ch), None)
+                if !ch.is_alphanumeric() && !ch.is_whitespace())
+                && *c != keystroke.key
+        });
+        let binding = match symbol {
+            Some(symbol) => Binding {
+                key: symbol.to_string(),
+                control: modifiers.control,
+                shift: false,
+                alt: modifiers.alt,
+            },
+            None => Binding {
+                key: keystroke.key.to_lowercase(),
+                control: modifiers.control,
+                shift: modifiers.shift,
+                alt: modifiers.alt,
+            },
+        };
+
+        // Saved as its label, so it has to read back the same
+        (Binding::parse(&binding.label()).as_ref() == Some(&binding)).then_some(binding)
+    }
+
+    /// Whether a key press triggers this binding
+    pub fn matches(&self, keystroke: &Keystroke) -> bool {
+        let modifiers = &keystroke.modifiers;
+        if modifiers.control != self.control || modifiers.alt != self.alt || modifiers.platform {
+            return false;
+        }
+        if keystroke.key == self.key {
+            return modifiers.shift == self.shift;
+        }
+        // Shifted symbols like `?` can arrive as the base key plus Shift
+        keystroke.key_char.as_deref() == Some(self.key.as_str())
+    }
+
+    /// Display form, e.g. "Ctrl+Shift+A"
+    pub fn label(&self) -> String {
+        let mut parts = Vec::new();
+        if self.control {
+            parts.push("Ctrl".to_string());
+        }
+        if self.shift {
+            parts.push("Shift".to_string());
+        }
+        if self.alt {
+            parts.push("Alt".to_string());
+        }
+        parts.push(match self.key.as_str() {
+            "escape" => "Esc".to_string(),
+            "delete" => "Delete".to_string(),
+            "space" => "Space".to_string(),
+            key => key.to_uppercase(),
+        });
+        parts.join("+")
+    }
+}
+
+/// An action and the keys bound to it
+#[derive(Debug, Clone)]
+pub struct Shortcut {
+    pub action: ShortcutAction,
+    pub bindings: Vec<Binding>,
+}
+
+impl Shortcut {
+    /// All bindings for display, e.g. "? / F1"
+    pub fn keys_label(&self) -> String {
+        self.bindings
+            .iter()
+            .map(Binding::label)
+            .collect::<Vec<_>>()
+            .join(" / ")
+    }
+}
+
+/// All active shortcuts
+pub struct ShortcutRegistry {
+    shortcuts: Vec<Shortcut>,
+}
+
+impl ShortcutRegistry {
+    /// Registry with user remappings (action id -> keystroke string) applied
+    /// over the defaults. Unknown actions and unparsable keys are ignored.
+    pub fn with_overrides(overrides: &BTreeMap<String, String>) -> Self {
+        let shortcuts = ShortcutAction::ALL
+            .into_iter()
+            .map(|action| {
+                let bindings = overrides
+                    .get(action.id())
+                    .and_then(|keys| Binding::parse(keys))
+                    .map(|binding| vec![binding])
+                    .unwrap_or_else(|| action.default_bindings());
+                Shortcut { action, bindings }
+            })
+            .collect();
+        Self { shortcuts }
+    }
+
+    /// Action bound to a key press, if any
+    pub fn action_for(&self, keystroke: &Keystroke) -> Option<ShortcutAction> {
+        self.shortcuts
+            .iter()
+            .find(|s| s.bindings.iter().any(|b| b.matches(keystroke)))
+            .map(|s| s.action)
+    }
+
+    /// Bindings of one action
+    pub fn shortcut(&self, action: ShortcutAction) -> Option<&Shortcut> {
+        self.shortcuts.iter().find(|s| s.action == action)
+    }
+
+    /// Whether an action still uses its built-in bindings
+    pub fn is_default(&self, action: ShortcutAction) -> bool {
+        self.shortcut(action)
+            .is_some_and(|s| s.bindings == action.default_bindings())
+    }
+
+    /// Another action already using `binding`, if any
+    pub fn conflict(&self, action: ShortcutAction, binding: &Binding) -> Option<ShortcutAction> {
+        self.shortcuts
+            .iter()
+            .find(|s| s.action != action && s.bindings.contains(binding))
+            .map(|s| s.action)
+    }
+
+    /// Shortcuts grouped for the help overlay; empty groups are left out
+    pub fn grouped(&self) -> Vec<(ShortcutGroup, Vec<&Shortcut>)> {
+        ShortcutGroup::ALL
+            .iter()
+            .map(|group| {
+                let shortcuts = self
+                    .shortcuts
+                    .iter()
+                    .filter(|s| s.action.group() == *group && !s.bindings.is_empty())
+                    .collect::<Vec<_>>();
+                (*group, shortcuts)
+            })
+            .filter(|(_, shortcuts)| !shortcuts.is_empty())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gpui::Modifiers;
+
+    fn keystroke(key: &str, key_char: Option<&str>, control: bool, shift: bool) -> Keystroke {
+        Keystroke {
+            modifiers: Modifiers {
+                control,
+                shift,
+                ..Default::default()
+            },
+            key: key.to_string(),
+            key_char: key_char.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn test_parse_and_label() {
+        assert_eq!(Binding::parse("Ctrl+Shift+A").unwrap().label(), "Ctrl+Shift+A");
+        assert_eq!(Binding::parse("esc").unwrap().label(), "Esc");
+        assert_eq!(Binding::parse("F1").unwrap().label(), "F1");
+        assert_eq!(Binding::parse("?").unwrap().label(), "?");
+        assert_eq!(Binding::parse("Shift+Delete").unwrap().label(), "Shift+Delete");
+        assert_eq!(Binding::parse("Space").unwrap().label(), "Space");
+        assert!(Binding::parse("Ctrl+").is_none());
+        assert!(Binding::parse("Ctrl+A+B").is_none());
+    }
+
+    #[test]
+    fn test_action_for() {
+        let registry = ShortcutRegistry::with_overrides(&BTreeMap::new());
+
+        assert_eq!(
+            registry.action_for(&keystroke("a", None, true, false)),
+            Some(ShortcutAction::SelectAll)
+        );
+        assert_eq!(
+            registry.action_for(&keystroke("a", None, true, true)),
+            Some(ShortcutAction::SelectPage)
+        );
+        assert_eq!(
+            registry.action_for(&keystroke("delete", None, false, false)),
+            Some(ShortcutAction::DeleteSelection)
+        );
+        assert_eq!(
+            registry.action_for(&keystroke("delete", None, false, true)),
+            Some(ShortcutAction::DeleteSelectionPermanently)
+        );
+        assert_eq!(
+            registry.action_for(&keystroke("/", Some("?"), false, true)),
+            Some(ShortcutAction::ToggleHelp)
+        );
+        assert_eq!(
+            registry.action_for(&keystroke("f1", None, false, false)),
+            Some(ShortcutAction::ToggleHelp)
+        );
+        assert_eq!(
+            registry.action_for(&keystroke("space", Some(" "), false, false)),
+            Some(ShortcutAction::Preview)
+        );
+        assert_eq!(registry.action_for(&keystroke("a", Some("a"), false, false)), None);
+    }
+
+    #[test]
+    fn test_grouped_covers_every_action() {
+        let registry = ShortcutRegistry::with_overrides(&BTreeMap::new());
+        let listed: usize = registry.grouped().iter().map(|(_, s)| s.len()).sum();
+        assert_eq!(listed, ShortcutAction::ALL.len());
+    }
+
+    #[test]
+    fn test_overrides_and_conflicts() {
+        let mut overrides = BTreeMap::new();
+        overrides.insert("select_page".to_string(), "Ctrl+Shift+P".to_string());
+        overrides.insert("unknown".to_string(), "Ctrl+U".to_string());
+        overrides.insert("copy_selection".to_string(), "Ctrl+".to_string());
+        let registry = ShortcutRegistry::with_overrides(&overrides);
+
+        assert_eq!(
+            registry.action_for(&keystroke("p", None, true, true)),
+            Some(ShortcutAction::SelectPage)
+        );
+        assert_eq!(registry.action_for(&keystroke("a", None, true, true)), None);
+        assert!(!registry.is_default(ShortcutAction::SelectPage));
+        // Unparsable override falls back to the default
+        assert!(registry.is_default(ShortcutAction::CopySelection));
+
+        let ctrl_a = Binding::parse("Ctrl+A").unwrap();
+        assert_eq!(
+            registry.conflict(ShortcutAction::SelectPage, &ctrl_a),
+            Some(ShortcutAction::SelectAll)
+        );
+        assert_eq!(registry.conflict(ShortcutAction::SelectAll, &ctrl_a), None);
+    }
+
+    #[test]
+    fn test_record_bindings() {
+        let pressed = [
+            keystroke("/", Some("?"), false, true),
+            keystroke("space", Some(" "), false, false),
+            keystroke("escape", None, false, false),
+            keystroke("delete", None, false, true),
+            keystroke("p", Some("p"), false, false),
+            keystroke("a", None, true, true),
+            keystroke("f1", None, false, false),
+        ];
+        let labels = ["?", "Space", "Esc", "Shift+Delete", "P", "Ctrl+Shift+A", "F1"];
+        for (keystroke, label) in pressed.iter().zip(labels) {
+            let binding = Binding::from_keystroke(keystroke).unwrap();
+            assert_eq!(binding.label(), label);
+            // Saved as the label, read back the same, and triggered by the key
+            let parsed = Binding::parse(&binding.label()).unwrap();
+            assert_eq!(parsed, binding);
+            assert!(parsed.matches(keystroke), "{label}");
+        }
+
+        // The recorded "?" and Space are the built-in bindings
+        let registry = ShortcutRegistry::with_overrides(&BTreeMap::new());
+        let help = Binding::from_keystroke(&pressed[0]).unwrap();
+        assert_eq!(registry.conflict(ShortcutAction::Back, &help), Some(ShortcutAction::ToggleHelp));
+        let space = Binding::from_keystroke(&pressed[1]).unwrap();
+        assert_eq!(ShortcutAction::Preview.default_bindings(), vec![space]);
+
+        assert!(Binding::from_keystroke(&keystroke("shift", None, false, true)).is_none());
+        assert!(Binding::from_keystroke(&keystroke("=", Some("+"), false, true)).is_none());
+    }
+
+}
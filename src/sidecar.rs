@@ -0,0 +1,53 @@
+//! Small JSON stores kept next to settings.json
+//!
+//! Last-used times and pending cleanups each live in their own file so a
+//! corrupt one only costs its own data. A missing or unreadable file loads as
+//! the empty store; failing to save is logged and otherwise ignored.
+
+use log::warn;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::fs;
+use std::path::PathBuf;
+
+use crate::settings::Settings;
+
+/// Location of the sidecar file `name`
+fn path(name: &str) -> Option<PathBuf> {
+    Settings::config_path().and_then(|p| p.parent().map(|d| d.join(name)))
+}
+
+/// Load the store from disk (missing or corrupt file = empty store)
+pub fn load<T: DeserializeOwned + Default>(name: &str) -> T {
+    let Some(path) = path(name) else {
+        return T::default();
+    };
+
+    match fs::read_to_string(&path) {
+        Ok(content) => serde_json::from_str(&content).unwrap_or_else(|e| {
+            warn!("Ignoring unreadable {:?}: {}", path, e);
+            T::default()
+        }),
+        Err(_) => T::default(),
+    }
+}
+
+/// Persist the store to disk
+pub fn save<T: Serialize>(name: &str, value: &T) {
+    let Some(path) = path(name) else {
+        return;
+    };
+
+    if let Some(parent) = path.parent() {
+        let _ = fs::create_dir_all(parent);
+    }
+
+    match serde_json::to_string(value) {
+        Ok(content) => {
+            if let Err(e) = fs::write(&path, content) {
+                warn!("Failed to save {}: {}", name, e);
+            }
+        }
+        Err(e) => warn!("Failed to serialize {}: {}", name, e),
+    }
+}
@@ -0,0 +1,13 @@
+//! Fixtures shared by the unit tests
+
+use std::fs;
+use std::path::PathBuf;
+
+/// A fresh, empty folder under the system temp dir. `name` keeps tests
+/// apart within a run, the process id keeps runs apart.
+pub fn scratch_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("sukusho-{}-{}", name, std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
@@ -1,16 +1,35 @@
-//! Windows thumbnail extraction using Shell APIs
+//! Gallery thumbnails
 //!
-//! Note: Currently images are loaded directly by gpui. This module provides
-//! thumbnail caching infrastructure for future optimization.
+//! The grid shows downscaled copies, never the originals: a small worker pool
+//! decodes each screenshot once, shrinks it to about twice the tile size and
+//! writes it as WebP under the config directory, keyed by path and modified
+//! time, so later scrolls and restarts load the small file. Files that can't
+//! be decoded are found by the same workers, which fetch a shell-rendered
+//! thumbnail where Windows has a codec and the file type's icon otherwise.
+//!
+//! Oversized images (scrolling captures, panoramas) are never decoded whole:
+//! PNGs are box-filtered row by row as they stream in and JPEGs are scaled
+//! by the decoder, and the preview gets a capped copy cut into strips.
 
 #![allow(dead_code)]
 
-use image::{DynamicImage, RgbaImage};
+use crossbeam_channel::{unbounded, Receiver, Sender};
+use image::codecs::jpeg::JpegDecoder;
+use image::codecs::png::PngDecoder;
+use image::codecs::webp::WebPEncoder;
+use image::{ColorType, DynamicImage, ImageDecoder, ImageFormat, RgbaImage};
 use log::{debug, warn};
 use parking_lot::Mutex;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use std::io::Read;
 use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::AppMessage;
+
+use crate::timeline::{self, EventKind};
 
 #[cfg(windows)]
 use windows::{
@@ -28,242 +47,1191 @@ use windows::{
 /// Default thumbnail size
 pub const THUMBNAIL_SIZE: u32 = 150;
 
+/// Images with more pixels than this aren't decoded whole (160 MB as RGBA)
+pub const LARGE_IMAGE_PIXELS: u64 = 40_000_000;
+
+/// Longest edge every GPU takes as a single texture
+pub const MAX_TEXTURE_EDGE: u32 = 16_384;
+
+/// Shorter edge of an oversized image's preview copy
+const PREVIEW_MAX_SHORT_EDGE: u32 = 2048;
+
+/// Pixels in an oversized image's preview copy, across all its strips
+const PREVIEW_MAX_PIXELS: u64 = 24_000_000;
+
+/// Length of each preview strip along the image's long edge
+const PREVIEW_STRIP_EDGE: u32 = 4096;
+
+/// Result of reading an image header: its dimensions, or why it can't be decoded
+type Probe = Result<(u32, u32), String>;
+
+/// Decode probe results: path -> (mtime probed, probe result)
+static DECODE_STATUS: Mutex<Option<HashMap<PathBuf, (SystemTime, Probe)>>> = Mutex::new(None);
+
+/// Cached shell file type icons: extension -> PNG path (None = unavailable)
+static FILE_TYPE_ICONS: Mutex<Option<HashMap<String, Option<PathBuf>>>> = Mutex::new(None);
+
+/// Check whether a file can be decoded, returning the decode error if not.
+///
+/// Only reads the header, and caches the result per path+mtime so a broken
+/// file isn't retried on every render.
+pub fn decode_error(path: &Path, modified: SystemTime) -> Option<String> {
+    probe(path, modified).err()
+}
+
+/// Pixel dimensions of an image, from the same cached header probe
+pub fn dimensions(path: &Path, modified: SystemTime) -> Option<(u32, u32)> {
+    probe(path, modified).ok()
+}
+
+/// Dimensions if the header was already probed for this mtime, without
+/// touching the file. `None` means it hasn't been probed yet.
+pub fn cached_dimensions(path: &Path, modified: SystemTime) -> Option<Option<(u32, u32)>> {
+    let guard = DECODE_STATUS.lock();
+    guard
+        .as_ref()
+        .and_then(|m| m.get(path))
+        .filter(|(probed_mtime, _)| *probed_mtime == modified)
+        .map(|(_, result)| result.as_ref().ok().copied())
+}
+
+fn probe(path: &Path, modified: SystemTime) -> Probe {
+    {
+        let guard = DECODE_STATUS.lock();
+        if let Some((probed_mtime, result)) = guard.as_ref().and_then(|m| m.get(path)) {
+            if *probed_mtime == modified {
+                crate::perf::thumbnail_lookup(true);
+                return result.clone();
+            }
+        }
+    }
+
+    crate::perf::thumbnail_lookup(false);
+    let result = probe_decode(path);
+    if let Err(e) = &result {
+        warn!("Cannot decode {:?}: {}", path, e);
+        timeline::record(path, EventKind::DecodeFailed(e.clone()));
+    }
+
+    DECODE_STATUS
+        .lock()
+        .get_or_insert_with(HashMap::new)
+        .insert(path.to_path_buf(), (modified, result.clone()));
+    result
+}
+
+/// Files that failed to decode, with their errors (for diagnostics)
+pub fn decode_failures() -> Vec<(PathBuf, String)> {
+    let guard = DECODE_STATUS.lock();
+    let mut failures: Vec<_> = guard
+        .iter()
+        .flatten()
+        .filter_map(|(path, (_, result))| result.clone().err().map(|e| (path.clone(), e)))
+        .collect();
+    failures.sort();
+    failures
+}
+
+/// Forget the decode status of a path (file removed or replaced)
+pub fn forget_decode_status(path: &Path) {
+    if let Some(map) = DECODE_STATUS.lock().as_mut() {
+        map.remove(path);
+    }
+}
+
+/// Whether an image is too large to decode whole or show as one texture
+pub fn is_oversized(width: u32, height: u32) -> bool {
+    width.max(height) > MAX_TEXTURE_EDGE || width as u64 * height as u64 > LARGE_IMAGE_PIXELS
+}
+
+/// Read just enough of the file to know whether the image crate can decode
+/// it, and how large it is
+fn probe_decode(path: &Path) -> Probe {
+    let reader = image::io::Reader::open(crate::paths::long_path(path))
+        .map_err(|e| e.to_string())?
+        .with_guessed_format()
+        .map_err(|e| e.to_string())?;
+
+    if reader.format().is_none() {
+        return Err("Unrecognized image format".to_string());
+    }
+
+    reader.into_dimensions().map_err(|e| e.to_string())
+}
+
+/// Get the shell icon for a file extension as a PNG path gpui can load.
+///
+/// Icons are rendered once per extension into the temp directory.
+pub fn file_type_icon(extension: &str) -> Option<PathBuf> {
+    let extension = extension.to_lowercase();
+
+    let mut guard = FILE_TYPE_ICONS.lock();
+    let icons = guard.get_or_insert_with(HashMap::new);
+    if let Some(icon) = icons.get(&extension) {
+        return icon.clone();
+    }
+
+    let icon = render_file_type_icon(&extension);
+    icons.insert(extension, icon.clone());
+    icon
+}
+
+/// Render the shell icon for an extension to a PNG file
+#[cfg(windows)]
+fn render_file_type_icon(extension: &str) -> Option<PathBuf> {
+    use std::ffi::OsStr;
+    use std::os::windows::ffi::OsStrExt;
+    use windows::Win32::Storage::FileSystem::FILE_ATTRIBUTE_NORMAL;
+    use windows::Win32::UI::Shell::{
+        SHGetFileInfoW, SHFILEINFOW, SHGFI_ICON, SHGFI_LARGEICON, SHGFI_USEFILEATTRIBUTES,
+    };
+    use windows::Win32::UI::WindowsAndMessaging::{DestroyIcon, GetIconInfo, ICONINFO};
+
+    /// Large shell icons are 32x32
+    const ICON_SIZE: u32 = 32;
+
+    // SHGFI_USEFILEATTRIBUTES looks the icon up by extension, no file needed
+    let wide_name: Vec<u16> = OsStr::new(&format!("file.{}", extension))
+        .encode_wide()
+        .chain(std::iter::once(0))
+        .collect();
+
+    let rgba = unsafe {
+        let mut info = SHFILEINFOW::default();
+        let result = SHGetFileInfoW(
+            PCWSTR(wide_name.as_ptr()),
+            FILE_ATTRIBUTE_NORMAL,
+            Some(&mut info),
+            std::mem::size_of::<SHFILEINFOW>() as u32,
+            SHGFI_ICON | SHGFI_LARGEICON | SHGFI_USEFILEATTRIBUTES,
+        );
+        if result == 0 || info.hIcon.is_invalid() {
+            debug!("No shell icon for .{}", extension);
+            return None;
+        }
+
+        let mut icon_info = ICONINFO::default();
+        let rgba = if GetIconInfo(info.hIcon, &mut icon_info).is_ok() {
+            let rgba = hbitmap_to_rgba(icon_info.hbmColor, ICON_SIZE);
+            let _ = DeleteObject(icon_info.hbmColor);
+            let _ = DeleteObject(icon_info.hbmMask);
+            rgba
+        } else {
+            None
+        };
+
+        let _ = DestroyIcon(info.hIcon);
+        rgba?
+    };
+
+    let dir = std::env::temp_dir().join("sukusho-icons");
+    let _ = std::fs::create_dir_all(&dir);
+    let icon_path = dir.join(format!("{}.png", crate::paths::sanitize_file_name(extension)));
+    match rgba.save(&icon_path) {
+        Ok(()) => Some(icon_path),
+        Err(e) => {
+            warn!("Failed to save file type icon for .{}: {}", extension, e);
+            None
+        }
+    }
+}
+
+#[cfg(not(windows))]
+fn render_file_type_icon(_extension: &str) -> Option<PathBuf> {
+    None
+}
+
+/// Rendered thumbnails on disk, named by path, modified time and size like
+/// the grid thumbnails. A renamed or moved file keeps the name it had, so it
+/// keeps its thumbnail.
+pub struct DiskThumbnails {
+    dir: PathBuf,
+    /// Path -> (mtime, cache key) of files seen so far
+    keys: Mutex<HashMap<PathBuf, (SystemTime, u64)>>,
+}
+
+impl DiskThumbnails {
+    pub fn new(dir: PathBuf) -> Self {
+        Self {
+            dir,
+            keys: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// PNG for `path`, calling `render` only when it has none yet
+    pub fn get_or_render(
+        &self,
+        path: &Path,
+        modified: SystemTime,
+        render: impl FnOnce() -> Option<RgbaImage>,
+    ) -> Option<PathBuf> {
+        let key = self.key_for(path, modified)?;
+        let output = self.dir.join(format!("{:016x}.png", key));
+        if output.exists() {
+            return Some(output);
+        }
+
+        let thumbnail = render()?;
+        let _ = std::fs::create_dir_all(&self.dir);
+        match thumbnail.save(&output) {
+            Ok(()) => Some(output),
+            Err(e) => {
+                warn!("Failed to save thumbnail for {:?}: {}", path, e);
+                None
+            }
+        }
+    }
+
+    fn key_for(&self, path: &Path, modified: SystemTime) -> Option<u64> {
+        if let Some((keyed_mtime, key)) = self.keys.lock().get(path) {
+            if *keyed_mtime == modified {
+                return Some(*key);
+            }
+        }
+
+        let size = match std::fs::metadata(crate::paths::long_path(path)) {
+            Ok(metadata) => metadata.len(),
+            Err(e) => {
+                warn!("Failed to read {:?}: {}", path, e);
+                return None;
+            }
+        };
+        let key = cache_key(path, modified, size);
+        self.keys
+            .lock()
+            .insert(path.to_path_buf(), (modified, key));
+        Some(key)
+    }
+
+    /// Drop the thumbnail of a removed file
+    pub fn forget(&self, path: &Path) {
+        if let Some((_, key)) = self.keys.lock().remove(path) {
+            let _ = std::fs::remove_file(self.dir.join(format!("{:016x}.png", key)));
+        }
+    }
+
+    /// A file moved (organizer, rename); its content and thumbnail didn't change
+    pub fn follow_move(&self, from: &Path, to: &Path) {
+        let mut keys = self.keys.lock();
+        if let Some(entry) = keys.remove(from) {
+            keys.insert(to.to_path_buf(), entry);
+        }
+    }
+}
+
+/// Longest edge of grid thumbnails for a tile size: about twice the tile so
+/// they stay sharp on high-DPI screens, rounded up to 64px steps so moving
+/// the size slider doesn't regenerate everything on every pixel
+pub fn grid_edge(tile_size: u32) -> u32 {
+    (tile_size * 2).div_ceil(64).max(1) * 64
+}
+
+/// Where a grid thumbnail stands
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GridThumbnail {
+    /// Queued or being generated; show a placeholder
+    Pending,
+    /// WebP on disk, ready to load
+    Ready(PathBuf),
+    /// The original couldn't be decoded
+    Failed(Undecodable),
+}
+
+/// What to show for a file the grid workers couldn't decode
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Undecodable {
+    /// Why its header couldn't be read (None: the header was fine but the
+    /// image wasn't, e.g. truncated)
+    pub error: Option<String>,
+    /// Shell-rendered thumbnail, where Windows has a codec for it
+    pub fallback: Option<PathBuf>,
+    /// Shell icon for the file type
+    pub icon: Option<PathBuf>,
+}
+
+/// One piece of an oversized image's preview copy, in order along its long edge
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PreviewStrip {
+    pub path: PathBuf,
+    pub width: u32,
+    pub height: u32,
+}
+
+/// Where the preview copy of an oversized image stands
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PreviewStrips {
+    /// Being generated; show a spinner
+    Pending,
+    /// PNG strips on disk, ready to load
+    Ready(Vec<PreviewStrip>),
+    /// The original couldn't be decoded
+    Failed,
+}
+
+struct GridEntry {
+    modified: SystemTime,
+    edge: u32,
+    state: GridThumbnail,
+    /// Lookup tick, for evicting the least recently used
+    used: u64,
+}
+
+/// Grid thumbnail state shared with the workers
+struct GridState {
+    entries: HashMap<PathBuf, GridEntry>,
+    /// Output files a worker is producing, so nothing is decoded twice
+    in_flight: HashSet<PathBuf>,
+    tick: u64,
+}
+
+struct GridShared {
+    state: Mutex<GridState>,
+    dir: PathBuf,
+    max_entries: usize,
+    /// Originals decoded so far
+    decodes: AtomicUsize,
+    /// Shell-rendered PNGs for files the image crate can't decode
+    fallbacks: DiskThumbnails,
+    ready_tx: Sender<AppMessage>,
+}
+
+impl GridShared {
+    fn set(&self, path: &Path, modified: SystemTime, edge: u32, thumbnail: GridThumbnail) {
+        let mut state = self.state.lock();
+        state.tick += 1;
+        let used = state.tick;
+        state.entries.insert(
+            path.to_path_buf(),
+            GridEntry {
+                modified,
+                edge,
+                state: thumbnail,
+                used,
+            },
+        );
+        if state.entries.len() > self.max_entries {
+            // Pending entries stay; their workers will fill them in
+            let oldest = state
+                .entries
+                .iter()
+                .filter(|(_, entry)| entry.state != GridThumbnail::Pending)
+                .min_by_key(|(_, entry)| entry.used)
+                .map(|(path, _)| path.clone());
+            if let Some(oldest) = oldest {
+                state.entries.remove(&oldest);
+            }
+        }
+    }
+}
+
+/// One thumbnail for a worker to generate
+struct GridJob {
+    path: PathBuf,
+    modified: SystemTime,
+    edge: u32,
+    output: PathBuf,
+}
+
 /// Thumbnail cache to avoid regenerating thumbnails
 pub struct ThumbnailCache {
-    /// Path -> RGBA image data
-    cache: Mutex<HashMap<PathBuf, Arc<RgbaImage>>>,
-    /// Maximum cache size
-    max_size: usize,
+    /// Grid thumbnails: in-memory LRU over the WebP files on disk
+    grid: Arc<GridShared>,
+    /// Queue for the thumbnail workers
+    jobs: Sender<GridJob>,
+    /// Preview strips of the oversized image last previewed
+    preview: Arc<Mutex<Option<(PathBuf, SystemTime, PreviewStrips)>>>,
 }
 
 impl ThumbnailCache {
-    pub fn new(max_size: usize) -> Self {
+    /// Cache keeping up to `max_entries` grid lookups in memory, with
+    /// thumbnails written to `dir` and [`AppMessage::ThumbnailReady`] sent
+    /// as each one is done
+    pub fn new(max_entries: usize, dir: PathBuf, ready_tx: Sender<AppMessage>) -> Self {
+        // Earlier versions kept shell thumbnails in the temp dir
+        let _ = std::fs::remove_dir_all(std::env::temp_dir().join("sukusho-fallbacks"));
+
+        let grid = Arc::new(GridShared {
+            state: Mutex::new(GridState {
+                entries: HashMap::new(),
+                in_flight: HashSet::new(),
+                tick: 0,
+            }),
+            dir: dir.clone(),
+            max_entries,
+            decodes: AtomicUsize::new(0),
+            fallbacks: DiskThumbnails::new(dir.join("fallbacks")),
+            ready_tx,
+        });
+
+        let (jobs, job_rx) = unbounded::<GridJob>();
+        let workers = std::thread::available_parallelism()
+            .map(|n| n.get() / 2)
+            .unwrap_or(2)
+            .clamp(1, 4);
+        for _ in 0..workers {
+            let grid = Arc::clone(&grid);
+            let job_rx = job_rx.clone();
+            std::thread::spawn(move || run_grid_worker(&grid, job_rx));
+        }
+
         Self {
-            cache: Mutex::new(HashMap::new()),
-            max_size,
+            grid,
+            jobs,
+            preview: Arc::new(Mutex::new(None)),
         }
     }
 
-    /// Get a cached thumbnail or generate a new one
-    pub fn get_or_create(&self, path: &Path, size: u32) -> Option<Arc<RgbaImage>> {
-        // Check cache first
+    /// Preview strips for an oversized image, generated on a background
+    /// thread the first time they're asked for ([`AppMessage::ThumbnailReady`]
+    /// is sent when they're done). Only the last previewed image is kept.
+    pub fn preview_strips(&self, path: &Path, modified: SystemTime) -> PreviewStrips {
         {
-            let cache = self.cache.lock();
-            if let Some(img) = cache.get(path) {
-                return Some(Arc::clone(img));
+            let mut preview = self.preview.lock();
+            if let Some((previewed, previewed_mtime, strips)) = preview.as_ref() {
+                if previewed == path && *previewed_mtime == modified {
+                    return strips.clone();
+                }
             }
+            *preview = Some((path.to_path_buf(), modified, PreviewStrips::Pending));
         }
 
-        // Generate thumbnail
-        let img = self.generate_thumbnail(path, size)?;
-        let img = Arc::new(img);
+        let path = path.to_path_buf();
+        let dir = self.grid.dir.join("previews");
+        let preview = Arc::clone(&self.preview);
+        let ready_tx = self.grid.ready_tx.clone();
+        std::thread::spawn(move || {
+            let strips = match write_preview_strips(&path, modified, &dir) {
+                Ok(strips) => PreviewStrips::Ready(strips),
+                Err(e) => {
+                    warn!("Failed to generate preview for {:?}: {}", path, e);
+                    PreviewStrips::Failed
+                }
+            };
+            if let Some((previewed, previewed_mtime, current)) = preview.lock().as_mut() {
+                // Unless another image was previewed in the meantime
+                if *previewed == path && *previewed_mtime == modified {
+                    *current = strips;
+                }
+            }
+            let _ = ready_tx.send(AppMessage::ThumbnailReady(path));
+        });
+        PreviewStrips::Pending
+    }
 
-        // Store in cache
+    /// Grid thumbnail for a tile of `tile_size`, queueing it for the workers
+    /// when it hasn't been generated yet. Never touches the original here.
+    pub fn grid_thumbnail(&self, path: &Path, modified: SystemTime, tile_size: u32) -> GridThumbnail {
+        let edge = grid_edge(tile_size);
         {
-            let mut cache = self.cache.lock();
-
-            // Evict oldest entries if cache is full
-            if cache.len() >= self.max_size {
-                // Simple eviction: remove first entry
-                if let Some(key) = cache.keys().next().cloned() {
-                    cache.remove(&key);
+            let mut state = self.grid.state.lock();
+            state.tick += 1;
+            let tick = state.tick;
+            if let Some(entry) = state.entries.get_mut(path) {
+                if entry.modified == modified && entry.edge == edge {
+                    entry.used = tick;
+                    crate::perf::thumbnail_lookup(true);
+                    return entry.state.clone();
                 }
             }
+        }
 
-            cache.insert(path.to_path_buf(), Arc::clone(&img));
+        crate::perf::thumbnail_lookup(false);
+        let output = self.grid.dir.join(grid_file_name(path, modified, edge));
+        if output.exists() {
+            let ready = GridThumbnail::Ready(output);
+            self.grid.set(path, modified, edge, ready.clone());
+            return ready;
         }
 
-        Some(img)
+        self.grid.set(path, modified, edge, GridThumbnail::Pending);
+        if self.grid.state.lock().in_flight.insert(output.clone()) {
+            let _ = self.jobs.send(GridJob {
+                path: path.to_path_buf(),
+                modified,
+                edge,
+                output,
+            });
+        }
+        GridThumbnail::Pending
     }
 
-    /// Remove a path from the cache
-    pub fn invalidate(&self, path: &Path) {
-        let mut cache = self.cache.lock();
-        cache.remove(path);
+    /// Originals decoded for grid thumbnails so far
+    pub fn originals_decoded(&self) -> usize {
+        self.grid.decodes.load(Ordering::Relaxed)
     }
 
-    /// Clear all cached thumbnails
-    pub fn clear(&self) {
-        let mut cache = self.cache.lock();
-        cache.clear();
+    /// Remove a path from the cache (file removed or replaced)
+    pub fn invalidate(&self, path: &Path) {
+        if let Some(entry) = self.grid.state.lock().entries.remove(path) {
+            if let GridThumbnail::Ready(file) = entry.state {
+                let _ = std::fs::remove_file(file);
+            }
+        }
+        self.grid.fallbacks.forget(path);
+        forget_decode_status(path);
     }
 
-    /// Generate a thumbnail for the given path
-    fn generate_thumbnail(&self, path: &Path, size: u32) -> Option<RgbaImage> {
-        // Try Windows Shell API first (fastest, uses system cache)
-        #[cfg(windows)]
-        if let Some(img) = self.get_windows_thumbnail(path, size) {
-            return Some(img);
+    /// A file moved without changing; keep its thumbnail
+    pub fn follow_move(&self, from: &Path, to: &Path) {
+        {
+            let mut state = self.grid.state.lock();
+            if let Some(entry) = state.entries.remove(from) {
+                state.entries.insert(to.to_path_buf(), entry);
+            }
         }
-
-        // Fall back to manual thumbnail generation
-        self.generate_manual_thumbnail(path, size)
+        self.grid.fallbacks.follow_move(from, to);
     }
 
-    /// Get thumbnail using Windows Shell API
-    #[cfg(windows)]
-    fn get_windows_thumbnail(&self, path: &Path, size: u32) -> Option<RgbaImage> {
-        use std::ffi::OsStr;
-        use std::os::windows::ffi::OsStrExt;
+    /// Forget the in-memory lookups; thumbnails on disk are kept
+    pub fn clear(&self) {
+        self.grid.state.lock().entries.clear();
+    }
+}
 
-        unsafe {
-            // Convert path to wide string
-            let wide_path: Vec<u16> = OsStr::new(path)
-                .encode_wide()
-                .chain(std::iter::once(0))
-                .collect();
+/// Thumbnail the Windows shell renders for `path`, with its own codecs
+#[cfg(windows)]
+fn shell_thumbnail(path: &Path, size: u32) -> Option<RgbaImage> {
+    use std::ffi::OsStr;
+    use std::os::windows::ffi::OsStrExt;
 
-            // Create shell item
-            let shell_item: IShellItemImageFactory =
-                match SHCreateItemFromParsingName(PCWSTR(wide_path.as_ptr()), None) {
-                    Ok(item) => item,
-                    Err(e) => {
-                        debug!("Failed to create shell item for {:?}: {}", path, e);
-                        return None;
-                    }
-                };
+    unsafe {
+        // Convert path to wide string
+        let wide_path: Vec<u16> = OsStr::new(path)
+            .encode_wide()
+            .chain(std::iter::once(0))
+            .collect();
 
-            // Get thumbnail
-            let hbitmap: HBITMAP = match shell_item.GetImage(
-                SIZE {
-                    cx: size as i32,
-                    cy: size as i32,
-                },
-                SIIGBF_THUMBNAILONLY,
-            ) {
-                Ok(bmp) => bmp,
+        // Create shell item
+        let shell_item: IShellItemImageFactory =
+            match SHCreateItemFromParsingName(PCWSTR(wide_path.as_ptr()), None) {
+                Ok(item) => item,
                 Err(e) => {
-                    debug!("Failed to get thumbnail for {:?}: {}", path, e);
+                    debug!("Failed to create shell item for {:?}: {}", path, e);
                     return None;
                 }
             };
 
-            // Convert HBITMAP to RgbaImage
-            let result = self.hbitmap_to_rgba(hbitmap, size);
+        // Get thumbnail
+        let hbitmap: HBITMAP = match shell_item.GetImage(
+            SIZE {
+                cx: size as i32,
+                cy: size as i32,
+            },
+            SIIGBF_THUMBNAILONLY,
+        ) {
+            Ok(bmp) => bmp,
+            Err(e) => {
+                debug!("Failed to get thumbnail for {:?}: {}", path, e);
+                return None;
+            }
+        };
+
+        // Convert HBITMAP to RgbaImage
+        let result = hbitmap_to_rgba(hbitmap, size);
+
+        // Clean up
+        let _ = DeleteObject(hbitmap);
+
+        result
+    }
+}
+
+#[cfg(not(windows))]
+fn shell_thumbnail(_path: &Path, _size: u32) -> Option<RgbaImage> {
+    None
+}
 
-            // Clean up
-            let _ = DeleteObject(hbitmap);
+/// Path, modified time and a size (thumbnail edge or file length), hashed
+/// for cache file names
+fn cache_key(path: &Path, modified: SystemTime, size: u64) -> u64 {
+    let mtime = modified
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_nanos())
+        .unwrap_or(0);
+    let key = format!("{}|{}|{}", path.to_string_lossy(), mtime, size);
+    xxhash_rust::xxh3::xxh3_64(key.as_bytes())
+}
 
-            result
+/// Thumbnail file name: path, modified time and size, hashed
+fn grid_file_name(path: &Path, modified: SystemTime, edge: u32) -> String {
+    format!("{:016x}.webp", cache_key(path, modified, edge.into()))
+}
+
+/// Take grid thumbnail jobs until the cache is dropped
+fn run_grid_worker(grid: &GridShared, jobs: Receiver<GridJob>) {
+    for job in jobs {
+        let thumbnail = match probe(&job.path, job.modified) {
+            // The shell may have a codec the image crate lacks
+            Err(error) => GridThumbnail::Failed(undecodable(grid, &job, Some(error))),
+            Ok(dimensions) => {
+                // Another job for the same file may have finished first
+                let result = if job.output.exists() {
+                    Ok(())
+                } else {
+                    grid.decodes.fetch_add(1, Ordering::Relaxed);
+                    write_grid_thumbnail(&job.path, dimensions, job.edge, &job.output)
+                };
+                match result {
+                    Ok(()) => {
+                        timeline::record(&job.path, EventKind::ThumbnailGenerated);
+                        GridThumbnail::Ready(job.output.clone())
+                    }
+                    Err(e) => {
+                        warn!("Failed to generate thumbnail for {:?}: {}", job.path, e);
+                        GridThumbnail::Failed(undecodable(grid, &job, None))
+                    }
+                }
+            }
+        };
+        grid.state.lock().in_flight.remove(&job.output);
+        grid.set(&job.path, job.modified, job.edge, thumbnail);
+        let _ = grid.ready_tx.send(AppMessage::ThumbnailReady(job.path));
+    }
+}
+
+/// What to show for a job's file instead of a thumbnail. Only a file whose
+/// header is unreadable is worth asking the shell about.
+fn undecodable(grid: &GridShared, job: &GridJob, error: Option<String>) -> Undecodable {
+    let fallback = error.as_ref().and_then(|_| {
+        grid.fallbacks.get_or_render(&job.path, job.modified, || {
+            let thumbnail = shell_thumbnail(&crate::paths::display_path(&job.path), job.edge)?;
+            debug!("Using shell thumbnail for {:?}", job.path);
+            timeline::record(&job.path, EventKind::ThumbnailGenerated);
+            Some(thumbnail)
+        })
+    });
+    let extension = job
+        .path
+        .extension()
+        .map(|ext| ext.to_string_lossy().to_string())
+        .unwrap_or_default();
+    Undecodable {
+        error,
+        fallback,
+        icon: file_type_icon(&extension),
+    }
+}
+
+/// Shrink `path` (of `width` x `height`) to fit `edge` and write it to
+/// `output` as WebP
+fn write_grid_thumbnail(path: &Path, (width, height): (u32, u32), edge: u32, output: &Path) -> Result<(), String> {
+    // Oversized originals come out of the decoder at about twice the edge
+    let factor = width.max(height) / (edge * 2);
+    let img = decode_reduced(path, (width, height), factor)?;
+    let (width, height) = (img.width(), img.height());
+    // Small screenshots stay as they are
+    let thumbnail = if width.max(height) > edge {
+        resize_to_fit(&img, edge)
+    } else {
+        img.to_rgba8()
+    };
+
+    let mut encoded = Vec::new();
+    DynamicImage::ImageRgba8(thumbnail)
+        .write_with_encoder(WebPEncoder::new_lossless(&mut encoded))
+        .map_err(|e| e.to_string())?;
+
+    // Written aside and renamed so a reader never sees half a file
+    if let Some(dir) = output.parent() {
+        std::fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+    }
+    let partial = output.with_extension("webp.partial");
+    std::fs::write(&partial, encoded).map_err(|e| e.to_string())?;
+    std::fs::rename(&partial, output).map_err(|e| e.to_string())
+}
+
+/// Size of an oversized image's preview copy: the short edge and the pixel
+/// count capped, the aspect ratio kept
+fn preview_size(width: u32, height: u32) -> (u32, u32) {
+    let pixels = width as f64 * height as f64;
+    let scale = (PREVIEW_MAX_SHORT_EDGE as f64 / width.min(height) as f64)
+        .min((PREVIEW_MAX_PIXELS as f64 / pixels).sqrt())
+        .min(1.0);
+    (
+        ((width as f64 * scale) as u32).max(1),
+        ((height as f64 * scale) as u32).max(1),
+    )
+}
+
+/// Write the preview copy of an oversized image to `dir` as PNG strips of
+/// at most [`PREVIEW_STRIP_EDGE`] along its long edge. Strips already there
+/// for this path and modified time are reused.
+fn write_preview_strips(path: &Path, modified: SystemTime, dir: &Path) -> Result<Vec<PreviewStrip>, String> {
+    let (width, height) = probe_decode(path)?;
+    let (preview_width, preview_height) = preview_size(width, height);
+    let tall = preview_height >= preview_width;
+    let long_edge = preview_width.max(preview_height);
+    let key = cache_key(path, modified, long_edge);
+    let strip_path = |i: u32| dir.join(format!("{key:016x}-{i}.png"));
+
+    let count = long_edge.div_ceil(PREVIEW_STRIP_EDGE);
+    let strip_rect = |i: u32| {
+        let start = i * PREVIEW_STRIP_EDGE;
+        let length = PREVIEW_STRIP_EDGE.min(long_edge - start);
+        if tall {
+            (0, start, preview_width, length)
+        } else {
+            (start, 0, length, preview_height)
         }
+    };
+    let strips: Vec<PreviewStrip> = (0..count)
+        .map(|i| {
+            let (_, _, width, height) = strip_rect(i);
+            PreviewStrip {
+                path: strip_path(i),
+                width,
+                height,
+            }
+        })
+        .collect();
+    // Written last to first, so a first strip means the whole set is there
+    if strips[0].path.exists() {
+        return Ok(strips);
     }
 
-    /// Convert Windows HBITMAP to RgbaImage
-    #[cfg(windows)]
-    unsafe fn hbitmap_to_rgba(&self, hbitmap: HBITMAP, size: u32) -> Option<RgbaImage> {
-        let hdc = unsafe { CreateCompatibleDC(None) };
-        if hdc.is_invalid() {
-            return None;
+    let factor = (width / preview_width).min(height / preview_height);
+    let mut img = decode_reduced(path, (width, height), factor)?;
+    if (img.width(), img.height()) != (preview_width, preview_height) {
+        img = DynamicImage::ImageRgba8(resize_to_fit(&img, long_edge));
+    }
+    let img = img.to_rgba8();
+
+    std::fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+    for i in (0..count).rev() {
+        let (x, y, w, h) = strip_rect(i);
+        // Rounding in the resize can leave the copy a pixel short
+        let w = w.min(img.width().saturating_sub(x)).max(1);
+        let h = h.min(img.height().saturating_sub(y)).max(1);
+        let strip = image::imageops::crop_imm(&img, x, y, w, h).to_image();
+        let output = strip_path(i);
+        let partial = output.with_extension("png.partial");
+        strip
+            .save_with_format(&partial, ImageFormat::Png)
+            .map_err(|e| e.to_string())?;
+        std::fs::rename(&partial, &output).map_err(|e| e.to_string())?;
+    }
+    Ok(strips)
+}
+
+/// Decode `path` (`width` x `height`) shrunk about `factor` times.
+///
+/// Images that aren't oversized are decoded whole and returned as they
+/// are. Oversized 8-bit PNGs are box-filtered row by row as they stream in;
+/// oversized JPEGs are scaled by the decoder (in steps of 1/2, 1/4 and 1/8,
+/// so they can come out larger than asked). Other formats and 16-bit PNGs
+/// have no reduced path and are decoded whole.
+fn decode_reduced(path: &Path, (width, height): (u32, u32), factor: u32) -> Result<DynamicImage, String> {
+    let long_path = crate::paths::long_path(path);
+    if factor <= 1 || !is_oversized(width, height) {
+        return image::open(&long_path).map_err(|e| e.to_string());
+    }
+
+    let reader = image::io::Reader::open(&long_path)
+        .map_err(|e| e.to_string())?
+        .with_guessed_format()
+        .map_err(|e| e.to_string())?;
+    let format = reader.format();
+    let file = reader.into_inner();
+    match format {
+        Some(ImageFormat::Png) => {
+            let decoder = PngDecoder::new(file).map_err(|e| e.to_string())?;
+            if matches!(
+                decoder.color_type(),
+                ColorType::L8 | ColorType::La8 | ColorType::Rgb8 | ColorType::Rgba8
+            ) {
+                debug!("Reducing {:?} ({}x{}) {}x while decoding", path, width, height, factor);
+                let color = decoder.color_type();
+                #[allow(deprecated)]
+                let rows = decoder.into_reader().map_err(|e| e.to_string())?;
+                return box_reduce(rows, width, height, color, factor).map(DynamicImage::ImageRgba8);
+            }
+        }
+        Some(ImageFormat::Jpeg) => {
+            let mut decoder = JpegDecoder::new(file).map_err(|e| e.to_string())?;
+            let requested = |edge: u32| (edge / factor).clamp(1, u16::MAX as u32) as u16;
+            decoder
+                .scale(requested(width), requested(height))
+                .map_err(|e| e.to_string())?;
+            return DynamicImage::from_decoder(decoder).map_err(|e| e.to_string());
+        }
+        _ => {}
+    }
+    warn!("No reduced decode for {:?} ({}x{}), decoding it whole", path, width, height);
+    image::open(&long_path).map_err(|e| e.to_string())
+}
+
+/// Shrink an image `factor` times by averaging each `factor` x `factor`
+/// block, reading its 8-bit `color` rows from `rows` one at a time, so only
+/// a row and one row of sums are held besides the output
+fn box_reduce(
+    mut rows: impl Read,
+    width: u32,
+    height: u32,
+    color: ColorType,
+    factor: u32,
+) -> Result<RgbaImage, String> {
+    let channels = color.channel_count() as usize;
+    let (out_width, out_height) = (width.div_ceil(factor), height.div_ceil(factor));
+    let mut output = RgbaImage::new(out_width, out_height);
+    let mut row = vec![0u8; width as usize * channels];
+    let mut sums = vec![0u32; out_width as usize * 4];
+
+    for y in 0..height {
+        rows.read_exact(&mut row).map_err(|e| e.to_string())?;
+        for (x, pixel) in row.chunks_exact(channels).enumerate() {
+            let rgba = match *pixel {
+                [l] => [l, l, l, 255],
+                [l, a] => [l, l, l, a],
+                [r, g, b] => [r, g, b, 255],
+                [r, g, b, a] => [r, g, b, a],
+                _ => unreachable!("8-bit color has one to four channels"),
+            };
+            let sum = &mut sums[x / factor as usize * 4..][..4];
+            for (sum, value) in sum.iter_mut().zip(rgba) {
+                *sum += value as u32;
+            }
         }
 
-        let _old = unsafe { SelectObject(hdc, hbitmap) };
-
-        let mut bi = BITMAPINFO {
-            bmiHeader: BITMAPINFOHEADER {
-                biSize: std::mem::size_of::<BITMAPINFOHEADER>() as u32,
-                biWidth: size as i32,
-                biHeight: -(size as i32), // Negative for top-down
-                biPlanes: 1,
-                biBitCount: 32,
-                biCompression: BI_RGB.0,
-                ..Default::default()
-            },
+        // Last row of a block (or of the image): average it into the output
+        if (y + 1) % factor == 0 || y + 1 == height {
+            let block_rows = y % factor + 1;
+            for out_x in 0..out_width {
+                let block_cols = factor.min(width - out_x * factor);
+                let count = block_rows * block_cols;
+                let sum = &sums[out_x as usize * 4..][..4];
+                let average: [u8; 4] = std::array::from_fn(|c| ((sum[c] + count / 2) / count) as u8);
+                output.put_pixel(out_x, y / factor, image::Rgba(average));
+            }
+            sums.fill(0);
+        }
+    }
+    Ok(output)
+}
+
+/// Resize so the longest edge is `target_size`, keeping the aspect ratio
+fn resize_to_fit(img: &DynamicImage, target_size: u32) -> RgbaImage {
+    use fast_image_resize::{images::Image, ResizeAlg, ResizeOptions, Resizer};
+
+    let rgba = img.to_rgba8();
+    let (width, height) = rgba.dimensions();
+
+    // Calculate aspect-ratio-preserving dimensions
+    let (new_width, new_height) = if width > height {
+        let ratio = target_size as f32 / width as f32;
+        (target_size, (height as f32 * ratio) as u32)
+    } else {
+        let ratio = target_size as f32 / height as f32;
+        ((width as f32 * ratio) as u32, target_size)
+    };
+
+    let new_width = new_width.max(1);
+    let new_height = new_height.max(1);
+
+    // Create source image
+    let src_image = Image::from_vec_u8(
+        width,
+        height,
+        rgba.into_raw(),
+        fast_image_resize::PixelType::U8x4,
+    )
+    .expect("Failed to create source image");
+
+    // Create destination image
+    let mut dst_image = Image::new(new_width, new_height, fast_image_resize::PixelType::U8x4);
+
+    // Resize
+    let mut resizer = Resizer::new();
+    let options = ResizeOptions::new().resize_alg(ResizeAlg::Convolution(
+        fast_image_resize::FilterType::Lanczos3,
+    ));
+
+    resizer
+        .resize(&src_image, &mut dst_image, &options)
+        .expect("Failed to resize image");
+
+    RgbaImage::from_raw(new_width, new_height, dst_image.into_vec())
+        .unwrap_or_else(|| RgbaImage::new(new_width, new_height))
+}
+
+/// Convert Windows HBITMAP to RgbaImage
+#[cfg(windows)]
+unsafe fn hbitmap_to_rgba(hbitmap: HBITMAP, size: u32) -> Option<RgbaImage> {
+    let hdc = unsafe { CreateCompatibleDC(None) };
+    if hdc.is_invalid() {
+        return None;
+    }
+
+    let _old = unsafe { SelectObject(hdc, hbitmap) };
+
+    let mut bi = BITMAPINFO {
+        bmiHeader: BITMAPINFOHEADER {
+            biSize: std::mem::size_of::<BITMAPINFOHEADER>() as u32,
+            biWidth: size as i32,
+            biHeight: -(size as i32), // Negative for top-down
+            biPlanes: 1,
+            biBitCount: 32,
+            biCompression: BI_RGB.0,
             ..Default::default()
-        };
+        },
+        ..Default::default()
+    };
 
-        let mut buffer = vec![0u8; (size * size * 4) as usize];
-
-        let result = unsafe {
-            GetDIBits(
-                hdc,
-                hbitmap,
-                0,
-                size,
-                Some(buffer.as_mut_ptr() as *mut _),
-                &mut bi,
-                DIB_RGB_COLORS,
-            )
-        };
+    let mut buffer = vec![0u8; (size * size * 4) as usize];
+
+    let result = unsafe {
+        GetDIBits(
+            hdc,
+            hbitmap,
+            0,
+            size,
+            Some(buffer.as_mut_ptr() as *mut _),
+            &mut bi,
+            DIB_RGB_COLORS,
+        )
+    };
 
-        let _ = unsafe { DeleteDC(hdc) };
+    let _ = unsafe { DeleteDC(hdc) };
 
-        if result == 0 {
-            return None;
+    if result == 0 {
+        return None;
+    }
+
+    // Convert BGRA to RGBA
+    for chunk in buffer.chunks_exact_mut(4) {
+        chunk.swap(0, 2); // Swap B and R
+    }
+
+    RgbaImage::from_raw(size, size, buffer)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::scratch_dir;
+
+    #[test]
+    fn test_decode_error_flags_undecodable_files() {
+        let dir = scratch_dir("decode");
+
+        let good = dir.join("good.png");
+        RgbaImage::new(4, 4).save(&good).unwrap();
+        let bad = dir.join("bad.webp");
+        std::fs::write(&bad, b"RIFF\0\0\0\0not really webp").unwrap();
+
+        let mtime = SystemTime::now();
+        assert_eq!(cached_dimensions(&good, mtime), None);
+        assert_eq!(decode_error(&good, mtime), None);
+        assert_eq!(cached_dimensions(&good, mtime), Some(Some((4, 4))));
+        assert!(decode_error(&bad, mtime).is_some());
+        assert_eq!(dimensions(&bad, mtime), None);
+        assert!(decode_failures().iter().any(|(p, _)| p == &bad));
+
+        forget_decode_status(&bad);
+        assert!(!decode_failures().iter().any(|(p, _)| p == &bad));
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_disk_thumbnails_survive_moves() {
+        let dir = scratch_dir("disk-thumbs");
+        let shots = dir.join("shots");
+        let organized = shots.join("2026").join("10");
+        std::fs::create_dir_all(&organized).unwrap();
+        let disk = DiskThumbnails::new(dir.join("cache"));
+
+        let renders = std::cell::Cell::new(0);
+        let render = || {
+            renders.set(renders.get() + 1);
+            Some(RgbaImage::new(2, 2))
+        };
+        let modified = SystemTime::now();
+
+        let files: Vec<PathBuf> = (0..1000).map(|i| shots.join(format!("{i}.png"))).collect();
+        for (i, file) in files.iter().enumerate() {
+            std::fs::write(file, format!("screenshot {i}")).unwrap();
+            assert!(disk.get_or_render(file, modified, render).is_some());
         }
+        assert_eq!(renders.get(), 1000);
 
-        // Convert BGRA to RGBA
-        for chunk in buffer.chunks_exact_mut(4) {
-            chunk.swap(0, 2); // Swap B and R
+        // Organizer run: every file moves, none is rendered again
+        for file in &files {
+            let to = organized.join(file.file_name().unwrap());
+            std::fs::rename(file, &to).unwrap();
+            disk.follow_move(file, &to);
+            assert!(disk.get_or_render(&to, modified, render).is_some());
         }
+        assert_eq!(renders.get(), 1000);
+
+        // A copy is a file of its own, and an edited file is rendered again
+        let copy = shots.join("copy of 7.png");
+        std::fs::copy(organized.join("7.png"), &copy).unwrap();
+        assert_ne!(
+            disk.get_or_render(&copy, modified, render),
+            disk.get_or_render(&organized.join("7.png"), modified, render)
+        );
+        assert_eq!(renders.get(), 1001);
+        let edited = modified + std::time::Duration::from_secs(1);
+        assert!(disk.get_or_render(&copy, edited, render).is_some());
+        assert_eq!(renders.get(), 1002);
 
-        RgbaImage::from_raw(size, size, buffer)
+        let _ = std::fs::remove_dir_all(&dir);
     }
 
-    /// Manual thumbnail generation using image crate
-    fn generate_manual_thumbnail(&self, path: &Path, size: u32) -> Option<RgbaImage> {
-        debug!("Generating manual thumbnail for {:?}", path);
+    #[test]
+    fn test_is_oversized() {
+        assert!(!is_oversized(3840, 2160));
+        assert!(!is_oversized(7680, 4320));
+        assert!(is_oversized(1200, 40_000));
+        assert!(is_oversized(200, 20_000));
+        assert!(is_oversized(8000, 8000));
+    }
 
-        // Load image
-        let img = match image::open(path) {
-            Ok(img) => img,
-            Err(e) => {
-                warn!("Failed to open image {:?}: {}", path, e);
-                return None;
+    #[test]
+    fn test_box_reduce_averages_blocks() {
+        // 5x3 RGB: the last column and row are partial blocks
+        let mut rows = Vec::new();
+        for y in 0..3u8 {
+            for x in 0..5u8 {
+                rows.extend([x * 10, y * 100, 7]);
             }
-        };
+        }
+        let reduced = box_reduce(rows.as_slice(), 5, 3, ColorType::Rgb8, 2).unwrap();
+        assert_eq!(reduced.dimensions(), (3, 2));
+        assert_eq!(reduced.get_pixel(0, 0).0, [5, 50, 7, 255]);
+        assert_eq!(reduced.get_pixel(2, 0).0, [40, 50, 7, 255]);
+        assert_eq!(reduced.get_pixel(1, 1).0, [25, 200, 7, 255]);
 
-        // Use fast_image_resize for better performance
-        Some(self.resize_with_fast_image_resize(&img, size))
+        let gray = [10u8, 200, 30, 100];
+        let reduced = box_reduce(gray.as_slice(), 2, 1, ColorType::La8, 2).unwrap();
+        assert_eq!(reduced.get_pixel(0, 0).0, [20, 20, 20, 150]);
     }
 
-    /// Resize image using fast_image_resize crate
-    fn resize_with_fast_image_resize(&self, img: &DynamicImage, target_size: u32) -> RgbaImage {
-        use fast_image_resize::{images::Image, ResizeAlg, ResizeOptions, Resizer};
+    #[test]
+    fn test_tall_images_are_reduced() {
+        let dir = scratch_dir("tall");
 
-        let rgba = img.to_rgba8();
-        let (width, height) = rgba.dimensions();
+        // A scrolling capture: 240 px wide, 20000 px tall, striped
+        let tall = RgbaImage::from_fn(240, 20_000, |x, y| {
+            let v = if (y / 50) % 2 == 0 { 255 } else { 0 };
+            image::Rgba([v, (x % 256) as u8, 128, 255])
+        });
+        let png = dir.join("scroll.png");
+        tall.save(&png).unwrap();
+        let jpg = dir.join("scroll.jpg");
+        DynamicImage::ImageRgba8(tall).to_rgb8().save(&jpg).unwrap();
+        assert!(is_oversized(240, 20_000));
 
-        // Calculate aspect-ratio-preserving dimensions
-        let (new_width, new_height) = if width > height {
-            let ratio = target_size as f32 / width as f32;
-            (target_size, (height as f32 * ratio) as u32)
-        } else {
-            let ratio = target_size as f32 / height as f32;
-            ((width as f32 * ratio) as u32, target_size)
-        };
+        for original in [&png, &jpg] {
+            let output = dir.join("thumbs").join(format!(
+                "{}.webp",
+                original.extension().unwrap().to_string_lossy()
+            ));
+            write_grid_thumbnail(original, (240, 20_000), 320, &output).unwrap();
+            assert_eq!(image::image_dimensions(&output).unwrap(), (3, 320));
+        }
 
-        let new_width = new_width.max(1);
-        let new_height = new_height.max(1);
+        let reduced = decode_reduced(&png, (240, 20_000), 10).unwrap();
+        assert_eq!((reduced.width(), reduced.height()), (24, 2000));
+        // Five rows of white, five of black: each block averages to the stripe
+        assert_eq!(reduced.to_rgba8().get_pixel(0, 0).0[0], 255);
+        assert_eq!(reduced.to_rgba8().get_pixel(0, 5).0[0], 0);
 
-        // Create source image
-        let src_image = Image::from_vec_u8(
-            width,
-            height,
-            rgba.into_raw(),
-            fast_image_resize::PixelType::U8x4,
-        )
-        .expect("Failed to create source image");
+        // Small enough to keep its size, cut into strips
+        assert_eq!(preview_size(240, 20_000), (240, 20_000));
+        let modified = SystemTime::now();
+        let previews = dir.join("previews");
+        let strips = write_preview_strips(&png, modified, &previews).unwrap();
+        assert_eq!(strips.len(), 5);
+        assert_eq!(strips.iter().map(|s| s.height).sum::<u32>(), 20_000);
+        for strip in &strips {
+            assert_eq!(
+                image::image_dimensions(&strip.path).unwrap(),
+                (strip.width, strip.height)
+            );
+        }
+        assert_eq!(write_preview_strips(&png, modified, &previews).unwrap(), strips);
 
-        // Create destination image
-        let mut dst_image = Image::new(new_width, new_height, fast_image_resize::PixelType::U8x4);
+        // A big panorama is capped
+        let (width, height) = preview_size(40_000, 3000);
+        assert!(height <= PREVIEW_MAX_SHORT_EDGE);
+        assert!(width as u64 * height as u64 <= PREVIEW_MAX_PIXELS);
+        assert_eq!(width / height, 40_000 / 3000);
 
-        // Resize
-        let mut resizer = Resizer::new();
-        let options = ResizeOptions::new().resize_alg(ResizeAlg::Convolution(
-            fast_image_resize::FilterType::Lanczos3,
-        ));
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_grid_edge() {
+        assert_eq!(grid_edge(150), 320);
+        assert_eq!(grid_edge(160), 320);
+        assert_eq!(grid_edge(80), 192);
+    }
+
+    #[test]
+    fn test_grid_thumbnails_decode_each_original_once() {
+        let dir = scratch_dir("grid");
+        let shots = dir.join("shots");
+        std::fs::create_dir_all(&shots).unwrap();
+        let modified = SystemTime::now();
+        let files: Vec<PathBuf> = (0..30)
+            .map(|i| {
+                let file = shots.join(format!("{i}.png"));
+                RgbaImage::new(600, 400).save(&file).unwrap();
+                file
+            })
+            .collect();
+
+        // Fewer in-memory entries than files, so lookups fall back to disk
+        let (tx, rx) = unbounded();
+        let cache = ThumbnailCache::new(8, dir.join("thumbs"), tx);
+
+        // Scroll past every tile a few times, as renders would
+        for _ in 0..3 {
+            for file in &files {
+                cache.grid_thumbnail(file, modified, 150);
+            }
+        }
+        for _ in &files {
+            rx.recv_timeout(std::time::Duration::from_secs(30)).unwrap();
+        }
+        for file in &files {
+            let GridThumbnail::Ready(thumbnail) = cache.grid_thumbnail(file, modified, 150) else {
+                panic!("no thumbnail for {:?}", file);
+            };
+            assert_eq!(image::image_dimensions(&thumbnail).unwrap(), (320, 213));
+        }
+        assert_eq!(cache.originals_decoded(), files.len());
 
-        resizer
-            .resize(&src_image, &mut dst_image, &options)
-            .expect("Failed to resize image");
+        // A new modified time means the file changed
+        assert_eq!(
+            cache.grid_thumbnail(&files[0], modified + std::time::Duration::from_secs(1), 150),
+            GridThumbnail::Pending
+        );
 
-        RgbaImage::from_raw(new_width, new_height, dst_image.into_vec())
-            .unwrap_or_else(|| RgbaImage::new(new_width, new_height))
+        let _ = std::fs::remove_dir_all(&dir);
     }
-}
 
-impl Default for ThumbnailCache {
-    fn default() -> Self {
-        Self::new(500)
+    #[test]
+    fn test_grid_worker_reports_undecodable() {
+        let dir = scratch_dir("grid-undecodable");
+        let broken = dir.join("broken.png");
+        std::fs::write(&broken, b"not an image").unwrap();
+        let modified = SystemTime::now();
+
+        let (tx, rx) = unbounded();
+        let cache = ThumbnailCache::new(8, dir.join("thumbs"), tx);
+        assert_eq!(cache.grid_thumbnail(&broken, modified, 150), GridThumbnail::Pending);
+        rx.recv_timeout(std::time::Duration::from_secs(30)).unwrap();
+
+        // Found by the worker, not at render time
+        let GridThumbnail::Failed(undecodable) = cache.grid_thumbnail(&broken, modified, 150) else {
+            panic!("broken file wasn't reported");
+        };
+        assert!(undecodable.error.is_some());
+        assert_eq!(decode_error(&broken, modified), undecodable.error);
+        assert_eq!(cache.originals_decoded(), 0);
+
+        let _ = std::fs::remove_dir_all(&dir);
     }
 }
@@ -0,0 +1,252 @@
+//! Per-file processing timeline
+//!
+//! Pipeline components (watcher, converter, organizer, indexer, thumbnails)
+//! record one event per step so the details pane can explain what happened
+//! to a screenshot. Kept in memory in a small ring buffer per path; every
+//! event is also written to the debug log.
+
+use log::debug;
+use parking_lot::Mutex;
+use std::collections::{HashMap, VecDeque};
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use crate::paths;
+
+/// Events kept per file (oldest dropped first)
+const MAX_EVENTS_PER_FILE: usize = 32;
+
+/// Files tracked at once (least recently updated dropped first)
+const MAX_FILES: usize = 2000;
+
+/// A pipeline step
+#[derive(Debug, Clone, PartialEq)]
+pub enum EventKind {
+    /// New file seen by the watcher
+    Detected,
+    /// Converted to another format (new path)
+    Converted(PathBuf),
+    /// Conversion failed (reason)
+    ConvertFailed(String),
+    /// Moved into a date folder (new path)
+    Organized(PathBuf),
+    /// Renamed or moved outside the app, e.g. in Explorer (new path)
+    Renamed(PathBuf),
+    /// Organizing failed (reason)
+    OrganizeFailed(String),
+    /// Embedding stored in the search index
+    Indexed,
+    /// Embedding failed (reason)
+    IndexFailed(String),
+    /// Left out of the search index for its size (width, height)
+    IndexSkipped(u32, u32),
+    /// Thumbnail rendered (e.g. by the shell fallback)
+    ThumbnailGenerated,
+    /// Image couldn't be decoded (reason)
+    DecodeFailed(String),
+}
+
+impl EventKind {
+    /// Whether this step failed
+    pub fn is_error(&self) -> bool {
+        matches!(
+            self,
+            EventKind::ConvertFailed(_)
+                | EventKind::OrganizeFailed(_)
+                | EventKind::IndexFailed(_)
+                | EventKind::DecodeFailed(_)
+        )
+    }
+}
+
+/// A recorded step with its time
+#[derive(Debug, Clone, PartialEq)]
+pub struct Event {
+    pub at: SystemTime,
+    pub kind: EventKind,
+}
+
+#[derive(Default)]
+struct Timeline {
+    files: HashMap<PathBuf, VecDeque<Event>>,
+}
+
+impl Timeline {
+    fn push(&mut self, path: PathBuf, event: Event) {
+        if !self.files.contains_key(&path) && self.files.len() >= MAX_FILES {
+            self.evict_oldest();
+        }
+
+        let events = self.files.entry(path).or_default();
+        if events.len() >= MAX_EVENTS_PER_FILE {
+            events.pop_front();
+        }
+        events.push_back(event);
+    }
+
+    /// Move history from one path to another, keeping it in time order
+    fn carry_over(&mut self, from: &Path, to: PathBuf) {
+        let Some(old) = self.files.remove(from) else {
+            return;
+        };
+
+        let events = self.files.entry(to).or_default();
+        let mut merged: Vec<Event> = old.into_iter().chain(events.drain(..)).collect();
+        merged.sort_by_key(|e| e.at);
+        let skip = merged.len().saturating_sub(MAX_EVENTS_PER_FILE);
+        events.extend(merged.into_iter().skip(skip));
+    }
+
+    /// Whether `path`'s last step is the one that put it there, i.e. a move
+    /// already recorded when the watcher reports it
+    fn arrived_by_move(&self, path: &Path) -> bool {
+        self.files
+            .get(path)
+            .and_then(|events| events.back())
+            .is_some_and(|event| match &event.kind {
+                EventKind::Converted(to) | EventKind::Organized(to) | EventKind::Renamed(to) => key(to) == path,
+                _ => false,
+            })
+    }
+
+    fn evict_oldest(&mut self) {
+        let oldest = self
+            .files
+            .iter()
+            .min_by_key(|(_, events)| events.back().map(|e| e.at))
+            .map(|(path, _)| path.clone());
+        if let Some(path) = oldest {
+            self.files.remove(&path);
+        }
+    }
+}
+
+static TIMELINE: Mutex<Option<Timeline>> = Mutex::new(None);
+
+/// Normalize paths so `\\?\` and plain forms share one history
+fn key(path: &Path) -> PathBuf {
+    paths::display_path(path)
+}
+
+/// Record a step for a file
+pub fn record(path: &Path, kind: EventKind) {
+    debug!("Timeline {:?}: {:?}", path, kind);
+    let event = Event {
+        at: SystemTime::now(),
+        kind,
+    };
+    TIMELINE
+        .lock()
+        .get_or_insert_with(Timeline::default)
+        .push(key(path), event);
+}
+
+/// Record a step that moved the file (convert/organize); its history follows it
+pub fn record_moved(from: &Path, to: &Path, kind: EventKind) {
+    record(from, kind);
+    TIMELINE
+        .lock()
+        .get_or_insert_with(Timeline::default)
+        .carry_over(&key(from), key(to));
+}
+
+/// Record a rename the watcher saw; its history follows it. Moves the app
+/// made itself (organizing, converting) were recorded already and are skipped.
+pub fn record_renamed(from: &Path, to: &Path) {
+    let (from_key, to_key) = (key(from), key(to));
+    let mut timeline = TIMELINE.lock();
+    let timeline = timeline.get_or_insert_with(Timeline::default);
+    if !timeline.files.contains_key(&from_key) && timeline.arrived_by_move(&to_key) {
+        return;
+    }
+    debug!("Timeline {:?}: renamed to {:?}", from, to);
+    timeline.push(
+        from_key.clone(),
+        Event {
+            at: SystemTime::now(),
+            kind: EventKind::Renamed(to.to_path_buf()),
+        },
+    );
+    timeline.carry_over(&from_key, to_key);
+}
+
+/// Recorded steps for a file, oldest first
+pub fn events(path: &Path) -> Vec<Event> {
+    TIMELINE
+        .lock()
+        .as_ref()
+        .and_then(|t| t.files.get(&key(path)))
+        .map(|events| events.iter().cloned().collect())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn event_at(secs: u64, kind: EventKind) -> Event {
+        Event {
+            at: SystemTime::UNIX_EPOCH + Duration::from_secs(secs),
+            kind,
+        }
+    }
+
+    #[test]
+    fn test_ring_buffer_drops_oldest() {
+        let mut timeline = Timeline::default();
+        let path = PathBuf::from("a.png");
+        for i in 0..(MAX_EVENTS_PER_FILE as u64 + 5) {
+            timeline.push(path.clone(), event_at(i, EventKind::Indexed));
+        }
+
+        let events = &timeline.files[&path];
+        assert_eq!(events.len(), MAX_EVENTS_PER_FILE);
+        assert_eq!(events.front().unwrap().at, SystemTime::UNIX_EPOCH + Duration::from_secs(5));
+    }
+
+    #[test]
+    fn test_carry_over_merges_in_order() {
+        let mut timeline = Timeline::default();
+        let png = PathBuf::from("a.png");
+        let webp = PathBuf::from("a.webp");
+
+        timeline.push(png.clone(), event_at(1, EventKind::Detected));
+        timeline.push(webp.clone(), event_at(3, EventKind::Indexed));
+        timeline.push(png.clone(), event_at(2, EventKind::Converted(webp.clone())));
+        timeline.carry_over(&png, webp.clone());
+
+        assert!(!timeline.files.contains_key(&png));
+        let kinds: Vec<_> = timeline.files[&webp].iter().map(|e| e.kind.clone()).collect();
+        assert_eq!(
+            kinds,
+            vec![EventKind::Detected, EventKind::Converted(webp), EventKind::Indexed]
+        );
+    }
+
+    #[test]
+    fn test_own_moves_are_not_recorded_as_renames() {
+        let mut timeline = Timeline::default();
+        let shot = PathBuf::from("shot.png");
+        let organized = PathBuf::from("2024-01-15").join("shot.png");
+        timeline.push(shot.clone(), event_at(1, EventKind::Detected));
+        timeline.push(shot.clone(), event_at(2, EventKind::Organized(organized.clone())));
+        timeline.carry_over(&shot, organized.clone());
+
+        assert!(timeline.arrived_by_move(&organized));
+        assert!(!timeline.arrived_by_move(&shot));
+
+        // Renamed afterwards in Explorer: the last step no longer points here
+        let renamed = PathBuf::from("2024-01-15").join("bug.png");
+        timeline.push(organized.clone(), event_at(3, EventKind::Renamed(renamed.clone())));
+        timeline.carry_over(&organized, renamed.clone());
+        assert!(timeline.arrived_by_move(&renamed));
+        assert!(!timeline.files.contains_key(&organized));
+    }
+
+    #[test]
+    fn test_is_error() {
+        assert!(EventKind::ConvertFailed("x".into()).is_error());
+        assert!(!EventKind::Organized(PathBuf::from("b")).is_error());
+    }
+}
@@ -5,12 +5,18 @@ use crossbeam_channel::Sender;
 use log::{debug, info};
 use rust_i18n::t;
 use parking_lot::Mutex;
+use std::path::PathBuf;
 use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant, SystemTime};
 use tray_icon::{
-    menu::{Menu, MenuEvent, MenuItem, PredefinedMenuItem},
+    menu::{CheckMenuItem, Menu, MenuEvent, MenuId, MenuItem, PredefinedMenuItem, Submenu},
     Icon, TrayIcon, TrayIconBuilder, TrayIconEvent,
 };
 
+use crate::i18n_helpers::plural_key;
+use crate::number_format::format_count;
+use crate::pipeline::{self, RecentEntry};
+use crate::settings::WindowLayout;
 use crate::AppMessage;
 
 #[cfg(windows)]
@@ -32,9 +38,20 @@ const DRAG_THRESHOLD: f64 = 5.0;
 /// Shared state for window handle
 pub static WINDOW_HWND: Mutex<Option<isize>> = Mutex::new(None);
 
+/// Menu ids of the Recent submenu entries and the files they open
+static RECENT_ITEMS: Mutex<Vec<(MenuId, PathBuf)>> = Mutex::new(Vec::new());
+
 /// Track window visibility
 static WINDOW_VISIBLE: AtomicBool = AtomicBool::new(true);
 
+/// Layout to show the window in (None = centered on the cursor's monitor)
+static ACTIVE_LAYOUT: Mutex<Option<WindowLayout>> = Mutex::new(None);
+
+/// Set the layout the window is shown in from the tray or hotkey
+pub fn set_active_layout(layout: Option<WindowLayout>) {
+    *ACTIVE_LAYOUT.lock() = layout;
+}
+
 /// Set the window handle for tray operations
 pub fn set_window_hwnd(hwnd: isize) {
     *WINDOW_HWND.lock() = Some(hwnd);
@@ -90,11 +107,9 @@ pub fn hide_window() {
 #[cfg(windows)]
 fn move_window_to_cursor_monitor() {
     use windows::Win32::Foundation::{HWND, POINT, RECT};
-    use windows::Win32::Graphics::Gdi::{
-        GetMonitorInfoW, MonitorFromPoint, MONITORINFO, MONITOR_DEFAULTTONEAREST,
-    };
+    use windows::Win32::Graphics::Gdi::{MonitorFromPoint, MONITOR_DEFAULTTONEAREST};
     use windows::Win32::UI::WindowsAndMessaging::{
-        GetCursorPos, GetWindowRect, SetWindowPos, HWND_TOP, SWP_NOSIZE, SWP_NOZORDER,
+        GetCursorPos, GetWindowRect, SetWindowPos, HWND_TOP, SWP_NOZORDER,
     };
 
     if let Some(hwnd) = *WINDOW_HWND.lock() {
@@ -107,17 +122,11 @@ fn move_window_to_cursor_monitor() {
                 return;
             }
 
-            // Get monitor at cursor position
+            // Get monitor at cursor position, and its work area right now
             let monitor = MonitorFromPoint(cursor_pos, MONITOR_DEFAULTTONEAREST);
-
-            // Get monitor info
-            let mut monitor_info = MONITORINFO {
-                cbSize: std::mem::size_of::<MONITORINFO>() as u32,
-                ..Default::default()
-            };
-            if !GetMonitorInfoW(monitor, &mut monitor_info).as_bool() {
+            let Some(work) = usable_work_area_now(monitor) else {
                 return;
-            }
+            };
 
             // Get current window rect
             let mut window_rect = RECT::default();
@@ -129,23 +138,10 @@ fn move_window_to_cursor_monitor() {
             let window_height = window_rect.bottom - window_rect.top;
 
             // Calculate centered position on the monitor
-            let monitor_work = monitor_info.rcWork;
-            let monitor_width = monitor_work.right - monitor_work.left;
-            let monitor_height = monitor_work.bottom - monitor_work.top;
-
-            let new_x = monitor_work.left + (monitor_width - window_width) / 2;
-            let new_y = monitor_work.top + (monitor_height - window_height) / 2;
+            let (new_x, new_y, width, height) = center_in_area(window_width, window_height, work);
 
             // Move window to new position
-            let _ = SetWindowPos(
-                hwnd,
-                HWND_TOP,
-                new_x,
-                new_y,
-                0,
-                0,
-                SWP_NOSIZE | SWP_NOZORDER,
-            );
+            let _ = SetWindowPos(hwnd, HWND_TOP, new_x, new_y, width, height, SWP_NOZORDER);
             debug!(
                 "Moved window to monitor at cursor position ({}, {})",
                 new_x, new_y
@@ -154,6 +150,347 @@ fn move_window_to_cursor_monitor() {
     }
 }
 
+/// Move the window to another monitor, cycling in `EnumDisplayMonitors` order.
+///
+/// The window keeps its relative position inside the work area and its size
+/// is scaled by the DPI ratio between the two monitors. Only the open window
+/// moves: the next [`show_window`] places it by its layout or the cursor.
+#[cfg(windows)]
+pub fn move_window_to_monitor(delta: i32) {
+    use windows::Win32::Foundation::{BOOL, HWND, LPARAM, RECT};
+    use windows::Win32::Graphics::Gdi::{
+        EnumDisplayMonitors, GetMonitorInfoW, MonitorFromWindow, HDC, HMONITOR, MONITORINFO,
+        MONITORINFOEXW, MONITOR_DEFAULTTONEAREST,
+    };
+    use windows::Win32::UI::HiDpi::{GetDpiForMonitor, MDT_EFFECTIVE_DPI};
+    use windows::Win32::UI::WindowsAndMessaging::{
+        GetWindowRect, SetWindowPos, HWND_TOP, SWP_NOACTIVATE, SWP_NOZORDER,
+    };
+
+    unsafe extern "system" fn collect_monitor(
+        monitor: HMONITOR,
+        _hdc: HDC,
+        _rect: *mut RECT,
+        data: LPARAM,
+    ) -> BOOL {
+        let monitors = unsafe { &mut *(data.0 as *mut Vec<HMONITOR>) };
+        monitors.push(monitor);
+        BOOL(1)
+    }
+
+    unsafe fn monitor_info(monitor: HMONITOR) -> Option<MONITORINFOEXW> {
+        let mut info = MONITORINFOEXW {
+            monitorInfo: MONITORINFO {
+                cbSize: std::mem::size_of::<MONITORINFOEXW>() as u32,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let ok = unsafe {
+            GetMonitorInfoW(monitor, &mut info as *mut MONITORINFOEXW as *mut MONITORINFO)
+        };
+        ok.as_bool().then_some(info)
+    }
+
+    unsafe fn monitor_dpi(monitor: HMONITOR) -> u32 {
+        let (mut dpi_x, mut dpi_y) = (96, 96);
+        if unsafe { GetDpiForMonitor(monitor, MDT_EFFECTIVE_DPI, &mut dpi_x, &mut dpi_y) }.is_err() {
+            return 96;
+        }
+        dpi_x
+    }
+
+    let Some(hwnd) = *WINDOW_HWND.lock() else {
+        return;
+    };
+    let hwnd = HWND(hwnd as *mut std::ffi::c_void);
+
+    unsafe {
+        let mut monitors: Vec<HMONITOR> = Vec::new();
+        let _ = EnumDisplayMonitors(
+            HDC::default(),
+            None,
+            Some(collect_monitor),
+            LPARAM(&mut monitors as *mut Vec<HMONITOR> as isize),
+        );
+        if monitors.len() < 2 {
+            debug!("Only {} monitor(s), not moving window", monitors.len());
+            return;
+        }
+
+        let current = MonitorFromWindow(hwnd, MONITOR_DEFAULTTONEAREST);
+        let current_index = monitors.iter().position(|m| *m == current).unwrap_or(0);
+        let target = monitors[cycle_index(current_index, delta, monitors.len())];
+
+        let (Some(from), Some(to)) = (monitor_info(current), monitor_info(target)) else {
+            log::warn!("Failed to read monitor info, not moving window");
+            return;
+        };
+
+        let mut window_rect = RECT::default();
+        if let Err(e) = GetWindowRect(hwnd, &mut window_rect) {
+            log::warn!("Failed to get window rect: {}", e);
+            return;
+        }
+
+        let scale = monitor_dpi(target) as f32 / monitor_dpi(current) as f32;
+        let rect_tuple = |r: RECT| (r.left, r.top, r.right, r.bottom);
+        let (x, y, width, height) = place_in_work_area(
+            rect_tuple(window_rect),
+            rect_tuple(from.monitorInfo.rcWork),
+            rect_tuple(to.monitorInfo.rcWork),
+            scale,
+        );
+
+        let device_len = to.szDevice.iter().position(|c| *c == 0).unwrap_or(to.szDevice.len());
+        let device = String::from_utf16_lossy(&to.szDevice[..device_len]);
+        if let Err(e) = SetWindowPos(
+            hwnd,
+            HWND_TOP,
+            x,
+            y,
+            width,
+            height,
+            SWP_NOZORDER | SWP_NOACTIVATE,
+        ) {
+            log::warn!("Failed to move window to monitor {}: {}", device, e);
+            return;
+        }
+        info!("Moved window to monitor {} ({}, {} {}x{})", device, x, y, width, height);
+    }
+}
+
+#[cfg(not(windows))]
+pub fn move_window_to_monitor(_delta: i32) {
+    // Not implemented for non-Windows
+}
+
+/// Step `delta` monitors from `current`, wrapping around
+#[cfg_attr(not(windows), allow(dead_code))]
+fn cycle_index(current: usize, delta: i32, count: usize) -> usize {
+    (current as i64 + delta as i64).rem_euclid(count as i64) as usize
+}
+
+/// Place a window (left, top, right, bottom) from one work area into another.
+/// The size is scaled and clamped to the target, and the window keeps its
+/// relative position in the free space. Returns (x, y, width, height).
+#[cfg_attr(not(windows), allow(dead_code))]
+fn place_in_work_area(
+    window: (i32, i32, i32, i32),
+    from: (i32, i32, i32, i32),
+    to: (i32, i32, i32, i32),
+    scale: f32,
+) -> (i32, i32, i32, i32) {
+    let (win_w, win_h) = (window.2 - window.0, window.3 - window.1);
+    let (from_w, from_h) = (from.2 - from.0, from.3 - from.1);
+    let (to_w, to_h) = (to.2 - to.0, to.3 - to.1);
+
+    let new_w = ((win_w as f32 * scale).round() as i32).min(to_w);
+    let new_h = ((win_h as f32 * scale).round() as i32).min(to_h);
+
+    // Fraction of the free space to the left/top of the window
+    let relative = |offset: i32, free: i32| {
+        if free <= 0 {
+            0.0
+        } else {
+            (offset as f32 / free as f32).clamp(0.0, 1.0)
+        }
+    };
+    let rel_x = relative(window.0 - from.0, from_w - win_w);
+    let rel_y = relative(window.1 - from.1, from_h - win_h);
+
+    let x = to.0 + (rel_x * (to_w - new_w) as f32).round() as i32;
+    let y = to.1 + (rel_y * (to_h - new_h) as f32).round() as i32;
+
+    (x, y, new_w, new_h)
+}
+
+/// Position and size of the window, with the DPI of its monitor
+#[cfg(windows)]
+pub fn current_window_layout() -> Option<WindowLayout> {
+    use windows::Win32::Foundation::{HWND, RECT};
+    use windows::Win32::UI::HiDpi::GetDpiForWindow;
+    use windows::Win32::UI::WindowsAndMessaging::GetWindowRect;
+
+    let hwnd = HWND((*WINDOW_HWND.lock())? as *mut std::ffi::c_void);
+    unsafe {
+        let mut rect = RECT::default();
+        GetWindowRect(hwnd, &mut rect).ok()?;
+        Some(WindowLayout {
+            x: rect.left,
+            y: rect.top,
+            width: rect.right - rect.left,
+            height: rect.bottom - rect.top,
+            dpi: GetDpiForWindow(hwnd).max(96),
+        })
+    }
+}
+
+#[cfg(not(windows))]
+pub fn current_window_layout() -> Option<WindowLayout> {
+    // Not implemented for non-Windows
+    None
+}
+
+/// Move and resize the window to a saved layout. The layout goes on the
+/// connected monitor nearest to where it was saved, scaled if that
+/// monitor's DPI changed and clamped to its work area as it is now.
+#[cfg(windows)]
+pub fn apply_window_layout(layout: WindowLayout) -> bool {
+    use windows::Win32::Foundation::{HWND, RECT};
+    use windows::Win32::Graphics::Gdi::{MonitorFromRect, MONITOR_DEFAULTTONEAREST};
+    use windows::Win32::UI::HiDpi::{GetDpiForMonitor, MDT_EFFECTIVE_DPI};
+    use windows::Win32::UI::WindowsAndMessaging::{SetWindowPos, HWND_TOP, SWP_NOZORDER};
+
+    let Some(hwnd) = *WINDOW_HWND.lock() else {
+        return false;
+    };
+    unsafe {
+        let hwnd = HWND(hwnd as *mut std::ffi::c_void);
+        let saved = RECT {
+            left: layout.x,
+            top: layout.y,
+            right: layout.x + layout.width,
+            bottom: layout.y + layout.height,
+        };
+        let monitor = MonitorFromRect(&saved, MONITOR_DEFAULTTONEAREST);
+        let Some(work) = usable_work_area_now(monitor) else {
+            return false;
+        };
+        let (mut dpi, mut dpi_y) = (96, 96);
+        if GetDpiForMonitor(monitor, MDT_EFFECTIVE_DPI, &mut dpi, &mut dpi_y).is_err() {
+            dpi = layout.dpi;
+        }
+
+        let (x, y, width, height) = fit_layout(layout, dpi, work);
+        if SetWindowPos(hwnd, HWND_TOP, x, y, width, height, SWP_NOZORDER).is_err() {
+            return false;
+        }
+        debug!("Applied window layout ({}, {} {}x{})", x, y, width, height);
+        true
+    }
+}
+
+#[cfg(not(windows))]
+pub fn apply_window_layout(_layout: WindowLayout) -> bool {
+    // Not implemented for non-Windows
+    false
+}
+
+/// Fit a saved layout into a work area (left, top, right, bottom) whose
+/// monitor is at `dpi`. Returns (x, y, width, height).
+#[cfg_attr(not(windows), allow(dead_code))]
+fn fit_layout(layout: WindowLayout, dpi: u32, work: (i32, i32, i32, i32)) -> (i32, i32, i32, i32) {
+    let scale = dpi as f32 / layout.dpi.max(1) as f32;
+    let (work_w, work_h) = (work.2 - work.0, work.3 - work.1);
+    let width = ((layout.width as f32 * scale).round() as i32).min(work_w);
+    let height = ((layout.height as f32 * scale).round() as i32).min(work_h);
+    let x = layout.x.clamp(work.0, work.2 - width);
+    let y = layout.y.clamp(work.1, work.3 - height);
+    (x, y, width, height)
+}
+
+/// Edge of a monitor a taskbar is docked to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(not(windows), allow(dead_code))]
+enum TaskbarEdge {
+    Left,
+    Top,
+    Right,
+    Bottom,
+}
+
+/// Work area of `monitor` (left, top, right, bottom), read when the window
+/// is shown rather than when a layout was saved, less the auto-hide taskbars
+#[cfg(windows)]
+unsafe fn usable_work_area_now(
+    monitor: windows::Win32::Graphics::Gdi::HMONITOR,
+) -> Option<(i32, i32, i32, i32)> {
+    use windows::Win32::Graphics::Gdi::{GetMonitorInfoW, MONITORINFO};
+    use windows::Win32::UI::Shell::{
+        SHAppBarMessage, ABE_BOTTOM, ABE_LEFT, ABE_RIGHT, ABE_TOP, ABM_GETAUTOHIDEBAREX,
+        APPBARDATA,
+    };
+
+    let mut monitor_info = MONITORINFO {
+        cbSize: std::mem::size_of::<MONITORINFO>() as u32,
+        ..Default::default()
+    };
+    if !unsafe { GetMonitorInfoW(monitor, &mut monitor_info) }.as_bool() {
+        return None;
+    }
+    let rect_tuple = |r: RECT| (r.left, r.top, r.right, r.bottom);
+
+    let edges = [
+        (ABE_LEFT, TaskbarEdge::Left),
+        (ABE_TOP, TaskbarEdge::Top),
+        (ABE_RIGHT, TaskbarEdge::Right),
+        (ABE_BOTTOM, TaskbarEdge::Bottom),
+    ];
+    let bars: Vec<(TaskbarEdge, i32)> = edges
+        .into_iter()
+        .filter_map(|(abe, edge)| {
+            let mut data = APPBARDATA {
+                cbSize: std::mem::size_of::<APPBARDATA>() as u32,
+                uEdge: abe,
+                rc: monitor_info.rcMonitor,
+                ..Default::default()
+            };
+            let bar = unsafe { SHAppBarMessage(ABM_GETAUTOHIDEBAREX, &mut data) };
+            if bar == 0 {
+                return None;
+            }
+            // Slid out or not, the bar keeps its size
+            let mut bar_rect = RECT::default();
+            unsafe { GetWindowRect(HWND(bar as *mut std::ffi::c_void), &mut bar_rect) }.ok()?;
+            let thickness = match edge {
+                TaskbarEdge::Left | TaskbarEdge::Right => bar_rect.right - bar_rect.left,
+                TaskbarEdge::Top | TaskbarEdge::Bottom => bar_rect.bottom - bar_rect.top,
+            };
+            debug!("Auto-hide taskbar on the {:?} edge ({} px)", edge, thickness);
+            Some((edge, thickness))
+        })
+        .collect();
+
+    Some(usable_work_area(
+        rect_tuple(monitor_info.rcWork),
+        rect_tuple(monitor_info.rcMonitor),
+        &bars,
+    ))
+}
+
+/// Work area (left, top, right, bottom) with room kept for auto-hide
+/// taskbars, given as the edge they're on and their thickness. Such a bar
+/// reserves no space, so the work area reaches under it and the edge of a
+/// window placed there is covered whenever the bar slides out.
+#[cfg_attr(not(windows), allow(dead_code))]
+fn usable_work_area(
+    work: (i32, i32, i32, i32),
+    monitor: (i32, i32, i32, i32),
+    auto_hide_bars: &[(TaskbarEdge, i32)],
+) -> (i32, i32, i32, i32) {
+    let (mut left, mut top, mut right, mut bottom) = work;
+    for &(edge, thickness) in auto_hide_bars {
+        match edge {
+            TaskbarEdge::Left => left = left.max(monitor.0 + thickness),
+            TaskbarEdge::Top => top = top.max(monitor.1 + thickness),
+            TaskbarEdge::Right => right = right.min(monitor.2 - thickness),
+            TaskbarEdge::Bottom => bottom = bottom.min(monitor.3 - thickness),
+        }
+    }
+    // A bar can't leave less than nothing
+    (left, top, right.max(left), bottom.max(top))
+}
+
+/// Center a window of this size in a work area (left, top, right, bottom),
+/// shrinking it to fit. Returns (x, y, width, height).
+#[cfg_attr(not(windows), allow(dead_code))]
+fn center_in_area(width: i32, height: i32, area: (i32, i32, i32, i32)) -> (i32, i32, i32, i32) {
+    let (area_w, area_h) = (area.2 - area.0, area.3 - area.1);
+    let (width, height) = (width.min(area_w), height.min(area_h));
+    (area.0 + (area_w - width) / 2, area.1 + (area_h - height) / 2, width, height)
+}
+
 /// Show and activate the window using Windows API
 #[cfg(windows)]
 pub fn show_window() {
@@ -162,8 +499,11 @@ pub fn show_window() {
         SetForegroundWindow, ShowWindow, SW_RESTORE, SW_SHOW,
     };
 
-    // First move window to cursor's monitor
-    move_window_to_cursor_monitor();
+    // First put the window in its layout, or on the cursor's monitor
+    let layout = *ACTIVE_LAYOUT.lock();
+    if !layout.is_some_and(apply_window_layout) {
+        move_window_to_cursor_monitor();
+    }
 
     if let Some(hwnd) = *WINDOW_HWND.lock() {
         unsafe {
@@ -285,7 +625,9 @@ pub fn show_tray_notification(title: &str, message: &str) {
 
             // Show window with fade-in animation
             let _ = ShowWindow(hwnd, SW_SHOWNOACTIVATE);
-            let _ = AnimateWindow(hwnd, 200, AW_BLEND);
+            if crate::motion::animations_enabled() {
+                let _ = AnimateWindow(hwnd, 200, AW_BLEND);
+            }
 
             // Store title, message, and scale in window data
             let title_wide: Vec<u16> = title.encode_utf16().chain(std::iter::once(0)).collect();
@@ -418,14 +760,18 @@ unsafe extern "system" fn notification_wndproc(
         WM_TIMER => {
             // Timer fired - close the window with animation
             let _ = KillTimer(hwnd, 1);
-            let _ = AnimateWindow(hwnd, 300, AW_HIDE | AW_BLEND);
+            if crate::motion::animations_enabled() {
+                let _ = AnimateWindow(hwnd, 300, AW_HIDE | AW_BLEND);
+            }
             let _ = DestroyWindow(hwnd);
             LRESULT(0)
         }
         WM_LBUTTONDOWN | WM_RBUTTONDOWN => {
             // Close on click with faster animation
             let _ = KillTimer(hwnd, 1);
-            let _ = AnimateWindow(hwnd, 150, AW_HIDE | AW_BLEND);
+            if crate::motion::animations_enabled() {
+                let _ = AnimateWindow(hwnd, 150, AW_HIDE | AW_BLEND);
+            }
             let _ = DestroyWindow(hwnd);
             LRESULT(0)
         }
@@ -438,8 +784,118 @@ pub fn show_tray_notification(_title: &str, _message: &str) {
     // Not implemented for non-Windows
 }
 
+/// Start a drag of the newest screenshot from the tray icon, unless it's
+/// still being converted (the converter deletes the source when it's done)
+fn start_tray_drag() {
+    if pipeline::latest_converting() {
+        info!("Tray drag refused: latest screenshot is still converting");
+        show_tray_notification(
+            &t!("tray.notification.converting_title"),
+            &t!("tray.notification.converting_message"),
+        );
+        return;
+    }
+    crate::drag_drop::start_latest_drag();
+}
+
+/// Open a file picked from the Recent submenu
+fn open_recent(path: &PathBuf) {
+    if pipeline::recent().iter().any(|e| e.path == *path && e.converting.is_some()) {
+        return;
+    }
+    info!("Opening recent screenshot from tray: {:?}", path);
+    crate::usage::mark_used(std::slice::from_ref(path));
+    if let Err(e) = open::that(path) {
+        log::warn!("Failed to open {:?}: {}", path, e);
+    }
+}
+
+/// Columns of file name in a Recent menu item before it's shortened
+const RECENT_NAME_WIDTH: usize = 40;
+
+/// Menu label for a Recent entry
+fn recent_label(entry: &RecentEntry) -> String {
+    let name = entry
+        .path
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_else(|| entry.path.display().to_string());
+    let name = crate::ui::text::middle_ellipsize(&name, RECENT_NAME_WIDTH);
+    match entry.converting {
+        Some(format) => t!(
+            "tray.menu.recent_converting",
+            name = name,
+            format = format.display_name()
+        )
+        .to_string(),
+        None => name,
+    }
+}
+
+/// Shortest time between tooltip changes; the shell flickers when it's
+/// updated faster
+pub const TOOLTIP_INTERVAL: Duration = Duration::from_secs(1);
+
+/// What the tray tooltip summarizes
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TrayStatus {
+    /// Screenshots in the library
+    pub screenshots: usize,
+    /// Running jobs as (done, total)
+    pub indexing: Option<(usize, usize)>,
+    pub converting: Option<(usize, usize)>,
+    pub organizing: Option<(usize, usize)>,
+    /// Newest screenshot, for the idle tooltip
+    pub last_capture: Option<SystemTime>,
+    /// New files are held until processing is resumed from the tray menu
+    pub paused: bool,
+}
+
+impl TrayStatus {
+    pub fn is_busy(&self) -> bool {
+        self.indexing.is_some() || self.converting.is_some() || self.organizing.is_some()
+    }
+}
+
+/// Tooltip text: the library size, then running jobs, or how long ago the
+/// last screenshot was taken when nothing is running
+pub fn status_tooltip(status: &TrayStatus, now: SystemTime) -> String {
+    let mut parts = vec![t!(
+        &plural_key("tray.status.library", status.screenshots),
+        count = format_count(status.screenshots)
+    )
+    .to_string()];
+    if status.paused {
+        parts.push(t!("tray.status.paused").to_string());
+    }
+    if let Some((current, total)) = status.indexing {
+        parts.push(t!("tray.status.indexing", current = current, total = total).to_string());
+    }
+    if let Some((current, total)) = status.converting {
+        parts.push(t!("tray.status.converting", count = total.saturating_sub(current)).to_string());
+    }
+    if let Some((current, total)) = status.organizing {
+        parts.push(t!("tray.status.organizing", count = total.saturating_sub(current)).to_string());
+    }
+    if !status.is_busy() {
+        if let Some(at) = status.last_capture {
+            let when = crate::app::format_relative_time(at, now);
+            parts.push(t!("tray.status.last_capture", when = when).to_string());
+        }
+    }
+    parts.join(" · ")
+}
+
 pub struct TrayManager {
     _tray_icon: TrayIcon,
+    recent_menu: Submenu,
+    pause_item: CheckMenuItem,
+    /// Kept to build the icon again after an Explorer restart
+    menu: Menu,
+    icon: Icon,
+    tooltip: String,
+    /// When the tooltip last changed (see [`TOOLTIP_INTERVAL`])
+    tooltip_changed: Option<Instant>,
 }
 
 impl TrayManager {
@@ -447,11 +903,19 @@ impl TrayManager {
         info!("Creating tray icon...");
 
         let menu = Menu::new();
+        let recent_menu = Submenu::new(&t!("tray.menu.recent"), true);
+        let edit_latest_item = MenuItem::new(&t!("tray.menu.edit_latest"), true, None);
+        let pause_item = CheckMenuItem::new(&t!("tray.menu.pause"), true, crate::watcher::is_paused(), None);
         let settings_item = MenuItem::new(&t!("tray.menu.settings"), true, None);
         let check_updates_item = MenuItem::new(&t!("tray.menu.check_for_updates"), true, None);
         let quit_item = MenuItem::new(&t!("tray.menu.quit"), true, None);
 
         menu.append_items(&[
+            &recent_menu,
+            &edit_latest_item,
+            &PredefinedMenuItem::separator(),
+            &pause_item,
+            &PredefinedMenuItem::separator(),
             &settings_item,
             &check_updates_item,
             &PredefinedMenuItem::separator(),
@@ -459,15 +923,12 @@ impl TrayManager {
         ])?;
 
         let icon = Self::generate_camera_icon()?;
-
-        let tray_icon = TrayIconBuilder::new()
-            .with_menu(Box::new(menu))
-            .with_tooltip(&t!("tray.tooltip"))
-            .with_icon(icon)
-            .with_menu_on_left_click(false)
-            .build()?;
+        let tooltip = t!("tray.tooltip").to_string();
+        let tray_icon = Self::build_icon(&menu, &icon, &tooltip)?;
 
         let menu_tx = message_tx.clone();
+        let edit_latest_id = edit_latest_item.id().clone();
+        let pause_id = pause_item.id().clone();
         let settings_id = settings_item.id().clone();
         let check_updates_id = check_updates_item.id().clone();
         let quit_id = quit_item.id().clone();
@@ -476,7 +937,22 @@ impl TrayManager {
             let menu_receiver = MenuEvent::receiver();
             loop {
                 if let Ok(event) = menu_receiver.recv() {
-                    if event.id == settings_id {
+                    let recent_path = RECENT_ITEMS
+                        .lock()
+                        .iter()
+                        .find(|(id, _)| *id == event.id)
+                        .map(|(_, path)| path.clone());
+                    if let Some(path) = recent_path {
+                        open_recent(&path);
+                    } else if event.id == edit_latest_id {
+                        info!("Edit latest screenshot requested from tray menu");
+                        crate::editor::edit_latest();
+                    } else if event.id == pause_id {
+                        let paused = !crate::watcher::is_paused();
+                        info!("Processing {} from tray menu", if paused { "paused" } else { "resumed" });
+                        crate::watcher::set_paused(paused);
+                        let _ = menu_tx.send(AppMessage::ProcessingPaused(paused));
+                    } else if event.id == settings_id {
                         show_window();
                         let _ = menu_tx.send(AppMessage::OpenSettings);
                     } else if event.id == check_updates_id {
@@ -502,6 +978,8 @@ impl TrayManager {
                         });
                     } else if event.id == quit_id {
                         info!("Quit requested from tray menu");
+                        crate::server::stop();
+                        crate::usage::flush();
                         std::process::exit(0);
                     }
                 }
@@ -549,12 +1027,7 @@ impl TrayManager {
                                         TRAY_MOUSE_DOWN.store(false, Ordering::SeqCst);
                                         *TRAY_DRAG_START.lock() = None;
 
-                                        if let Some(latest_path) = crate::get_latest_screenshot() {
-                                            info!("Starting tray drag with: {:?}", latest_path);
-                                            crate::drag_drop::start_drag(&[latest_path]);
-                                        } else {
-                                            debug!("No screenshots available for tray drag");
-                                        }
+                                        start_tray_drag();
                                     }
                                 }
                             }
@@ -564,10 +1037,7 @@ impl TrayManager {
                                 TRAY_MOUSE_DOWN.store(false, Ordering::SeqCst);
                                 *TRAY_DRAG_START.lock() = None;
 
-                                if let Some(latest_path) = crate::get_latest_screenshot() {
-                                    info!("Starting tray drag (leave) with: {:?}", latest_path);
-                                    crate::drag_drop::start_drag(&[latest_path]);
-                                }
+                                start_tray_drag();
                             }
                         }
                         TrayIconEvent::DoubleClick {
@@ -586,9 +1056,40 @@ impl TrayManager {
         });
 
         info!("Tray icon created successfully");
-        Ok(Self {
+        let mut manager = Self {
             _tray_icon: tray_icon,
-        })
+            recent_menu,
+            pause_item,
+            menu,
+            icon,
+            tooltip,
+            tooltip_changed: None,
+        };
+        manager.update_recent(&pipeline::recent());
+        Ok(manager)
+    }
+
+    /// Rebuild the Recent submenu. Entries still being converted are shown
+    /// disabled so they can't be opened before the converted file exists.
+    pub fn update_recent(&mut self, entries: &[RecentEntry]) {
+        for item in self.recent_menu.items() {
+            let _ = self.recent_menu.remove(item.as_ref());
+        }
+
+        let mut ids = Vec::new();
+        if entries.is_empty() {
+            let empty = MenuItem::new(&t!("tray.menu.recent_empty"), false, None);
+            let _ = self.recent_menu.append(&empty);
+        }
+        for entry in entries {
+            let item = MenuItem::new(recent_label(entry), entry.converting.is_none(), None);
+            if let Err(e) = self.recent_menu.append(&item) {
+                log::warn!("Failed to add recent tray entry: {}", e);
+                continue;
+            }
+            ids.push((item.id().clone(), entry.path.clone()));
+        }
+        *RECENT_ITEMS.lock() = ids;
     }
 
     fn generate_camera_icon() -> Result<Icon> {
@@ -638,10 +1139,195 @@ impl TrayManager {
             .map_err(|e| anyhow::anyhow!("Failed to create generated icon: {}", e))
     }
 
+    fn build_icon(menu: &Menu, icon: &Icon, tooltip: &str) -> Result<TrayIcon> {
+        Ok(TrayIconBuilder::new()
+            .with_menu(Box::new(menu.clone()))
+            .with_tooltip(tooltip)
+            .with_icon(icon.clone())
+            .with_menu_on_left_click(false)
+            .build()?)
+    }
+
+    /// Keep the "Pause processing" check mark in step with the paused state
+    pub fn set_paused(&mut self, paused: bool) {
+        self.pause_item.set_checked(paused);
+    }
+
+    /// Put the icon back in the notification area after an Explorer restart
+    /// forgot it. The new icon gets the same menu (Recent entries included)
+    /// and tooltip; click and menu events keep flowing through the same
+    /// global receivers, so the event threads don't need restarting.
+    pub fn rebuild_icon(&mut self) -> Result<()> {
+        let tray_icon = Self::build_icon(&self.menu, &self.icon, &self.tooltip)
+            .map_err(|e| anyhow::anyhow!("Failed to re-add tray icon: {}", e))?;
+        // Dropping the old icon removes whatever is left of it
+        self._tray_icon = tray_icon;
+        info!("Tray icon re-added");
+        Ok(())
+    }
+
+    /// Show `text` as the tooltip unless it changed less than
+    /// [`TOOLTIP_INTERVAL`] ago; returns how long to wait before trying again
+    pub fn set_tooltip(&mut self, text: &str, now: Instant) -> Option<Duration> {
+        if text == self.tooltip {
+            return None;
+        }
+        if let Some(changed) = self.tooltip_changed {
+            let since = now.saturating_duration_since(changed);
+            if since < TOOLTIP_INTERVAL {
+                return Some(TOOLTIP_INTERVAL - since);
+            }
+        }
+        self.update_tooltip(text);
+        self.tooltip_changed = Some(now);
+        None
+    }
+
     /// Update tray icon tooltip
     pub fn update_tooltip(&mut self, text: &str) {
+        self.tooltip = text.to_string();
         if let Err(e) = self._tray_icon.set_tooltip(Some(text)) {
             log::warn!("Failed to update tray tooltip: {}", e);
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_status_tooltip() {
+        let now = SystemTime::UNIX_EPOCH + Duration::from_secs(1_000_000);
+        let mut status = TrayStatus {
+            screenshots: 1248,
+            last_capture: Some(now - Duration::from_secs(4 * 60)),
+            ..Default::default()
+        };
+        assert_eq!(
+            status_tooltip(&status, now),
+            "Sukusho — 1,248 screenshots · last capture 4m ago"
+        );
+
+        status.indexing = Some((320, 900));
+        status.converting = Some((3, 5));
+        assert_eq!(
+            status_tooltip(&status, now),
+            "Sukusho — 1,248 screenshots · indexing 320/900 · converting 2"
+        );
+
+        status.paused = true;
+        status.indexing = None;
+        status.converting = None;
+        assert_eq!(
+            status_tooltip(&status, now),
+            "Sukusho — 1,248 screenshots · paused · last capture 4m ago"
+        );
+    }
+
+    #[test]
+    fn test_cycle_index_wraps() {
+        assert_eq!(cycle_index(0, 1, 3), 1);
+        assert_eq!(cycle_index(2, 1, 3), 0);
+        assert_eq!(cycle_index(0, -1, 3), 2);
+        assert_eq!(cycle_index(1, -4, 3), 0);
+    }
+
+    #[test]
+    fn test_place_in_work_area_keeps_relative_position() {
+        // Centered 800x600 window on a 1920x1040 work area
+        let window = (560, 220, 1360, 820);
+        let from = (0, 0, 1920, 1040);
+        let to = (1920, 0, 3840, 1040);
+
+        assert_eq!(place_in_work_area(window, from, to, 1.0), (2480, 220, 800, 600));
+    }
+
+    #[test]
+    fn test_fit_layout() {
+        let layout = WindowLayout {
+            x: 1500,
+            y: 700,
+            width: 400,
+            height: 300,
+            dpi: 96,
+        };
+        let work = (0, 0, 1920, 1040);
+        assert_eq!(fit_layout(layout, 96, work), (1500, 700, 400, 300));
+
+        // Scaling went to 150%: bigger, pushed back inside the work area
+        assert_eq!(fit_layout(layout, 144, work), (1320, 590, 600, 450));
+
+        // Saved on a monitor that's gone: lands on the nearest one
+        let off_screen = WindowLayout { x: 2500, y: -200, ..layout };
+        assert_eq!(fit_layout(off_screen, 96, work), (1520, 0, 400, 300));
+
+        // Bigger than the work area
+        let huge = WindowLayout { width: 4000, height: 3000, ..layout };
+        assert_eq!(fit_layout(huge, 96, work), (0, 0, 1920, 1040));
+    }
+
+    #[test]
+    fn test_usable_work_area_keeps_clear_of_auto_hide_taskbars() {
+        let monitor = (0, 0, 1920, 1080);
+        // Auto-hide bars reserve nothing, so the work area is the whole monitor
+        let layout = WindowLayout {
+            x: 0,
+            y: 0,
+            width: 800,
+            height: 600,
+            dpi: 96,
+        };
+        // (edge, bar thickness, usable area, saved position touching that edge)
+        let cases = [
+            (TaskbarEdge::Left, 62, (62, 0, 1920, 1080), (10, 200)),
+            (TaskbarEdge::Top, 48, (0, 48, 1920, 1080), (500, 0)),
+            (TaskbarEdge::Right, 62, (0, 0, 1858, 1080), (1100, 200)),
+            (TaskbarEdge::Bottom, 48, (0, 0, 1920, 1032), (500, 480)),
+        ];
+        for (edge, thickness, expected, (x, y)) in cases {
+            let area = usable_work_area(monitor, monitor, &[(edge, thickness)]);
+            assert_eq!(area, expected, "{:?}", edge);
+            let saved = WindowLayout { x, y, ..layout };
+
+            // A restored window that reached under the bar is pushed out of it
+            let (x, y, w, h) = fit_layout(saved, 96, area);
+            assert_eq!((w, h), (800, 600), "{:?}", edge);
+            assert!(x >= area.0 && y >= area.1, "{:?}", edge);
+            assert!(x + w <= area.2 && y + h <= area.3, "{:?}", edge);
+        }
+
+        // A regular taskbar already took its space out of the work area
+        let work = (0, 0, 1920, 1032);
+        assert_eq!(usable_work_area(work, monitor, &[]), work);
+        assert_eq!(usable_work_area(work, monitor, &[(TaskbarEdge::Bottom, 40)]), work);
+
+        // Auto-hide bars on two edges
+        let both = [(TaskbarEdge::Top, 48), (TaskbarEdge::Right, 62)];
+        assert_eq!(usable_work_area(monitor, monitor, &both), (0, 48, 1858, 1080));
+    }
+
+    #[test]
+    fn test_center_in_area() {
+        assert_eq!(center_in_area(800, 600, (0, 0, 1920, 1032)), (560, 216, 800, 600));
+
+        // Too tall for the space left by a taskbar on the left
+        assert_eq!(center_in_area(800, 1200, (62, 0, 1920, 1080)), (591, 0, 800, 1080));
+    }
+
+    #[test]
+    fn test_place_in_work_area_scales_and_clamps() {
+        // Window pinned to the bottom-right corner, moving to a 150% monitor
+        let window = (1120, 440, 1920, 1040);
+        let from = (0, 0, 1920, 1040);
+        let to = (-1280, 0, 0, 984);
+
+        let (x, y, w, h) = place_in_work_area(window, from, to, 1.5);
+        assert_eq!((w, h), (1200, 900));
+        assert_eq!((x + w, y + h), (0, 984));
+
+        // Larger than the target work area -> clamped to it
+        let (x, y, w, h) = place_in_work_area(window, from, to, 3.0);
+        assert_eq!((x, y, w, h), (-1280, 0, 1280, 984));
+    }
+}
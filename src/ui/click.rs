@@ -0,0 +1,216 @@
+//! Click, double-click and drag detection for gallery tiles
+//!
+//! Kept per view instead of in statics, and driven by plain mouse events: a
+//! press arms the tile, moving past the threshold starts a drag, releasing
+//! without moving selects. Nothing blocks while the button is held.
+
+use gpui::{Modifiers, Pixels, Point};
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+/// Two presses on the same tile within this count as a double-click
+pub const DOUBLE_CLICK_TIME: Duration = Duration::from_millis(500);
+
+/// Distance in pixels the pointer must travel while pressed to start a drag
+pub const DRAG_THRESHOLD: f32 = 5.0;
+
+/// What a pointer event turned out to mean
+#[derive(Debug, Clone, PartialEq)]
+pub enum PointerAction {
+    /// Plain click on a tile
+    Select { path: PathBuf, modifiers: Modifiers },
+    /// Second click on the same tile in time
+    Open(PathBuf),
+    /// Pressed tile moved past the threshold
+    Drag(Vec<PathBuf>),
+}
+
+/// A tile press that hasn't become a click or drag yet
+#[derive(Debug, Clone)]
+struct Press {
+    path: PathBuf,
+    drag_paths: Vec<PathBuf>,
+    origin: Point<Pixels>,
+    modifiers: Modifiers,
+}
+
+/// Pointer state for one gallery view
+#[derive(Debug, Default)]
+pub struct ClickTracker {
+    press: Option<Press>,
+    last_click: Option<(Instant, PathBuf)>,
+    /// A tile or header took this mouse-down (the background shouldn't clear the selection)
+    item_hit: bool,
+}
+
+impl ClickTracker {
+    /// Left button went down on a tile. Returns `Open` for a double-click.
+    pub fn press(
+        &mut self,
+        path: PathBuf,
+        drag_paths: Vec<PathBuf>,
+        origin: Point<Pixels>,
+        modifiers: Modifiers,
+        now: Instant,
+    ) -> Option<PointerAction> {
+        self.item_hit = true;
+
+        let is_double_click = self.last_click.as_ref().is_some_and(|(at, last_path)| {
+            now.duration_since(*at) < DOUBLE_CLICK_TIME && *last_path == path
+        });
+        if is_double_click {
+            self.last_click = None;
+            self.press = None;
+            return Some(PointerAction::Open(path));
+        }
+
+        self.last_click = Some((now, path.clone()));
+        self.press = Some(Press {
+            path,
+            drag_paths,
+            origin,
+            modifiers,
+        });
+        None
+    }
+
+    /// Pointer moved. Returns `Drag` once a pressed tile moves past the threshold.
+    pub fn moved(&mut self, position: Point<Pixels>, button_down: bool) -> Option<PointerAction> {
+        if !button_down {
+            // Released somewhere we didn't see; forget the press
+            self.press = None;
+            return None;
+        }
+
+        let press = self.press.as_ref()?;
+        let dx: f32 = (position.x - press.origin.x).into();
+        let dy: f32 = (position.y - press.origin.y).into();
+        if (dx * dx + dy * dy).sqrt() <= DRAG_THRESHOLD {
+            return None;
+        }
+
+        let press = self.press.take()?;
+        // A drag isn't the first half of a double-click
+        self.last_click = None;
+        (!press.drag_paths.is_empty()).then_some(PointerAction::Drag(press.drag_paths))
+    }
+
+    /// Left button released. Returns `Select` if the press never became a drag.
+    pub fn release(&mut self) -> Option<PointerAction> {
+        let press = self.press.take()?;
+        Some(PointerAction::Select {
+            path: press.path,
+            modifiers: press.modifiers,
+        })
+    }
+
+    /// Note that a tile or header handled the current mouse-down
+    pub fn mark_item_hit(&mut self) {
+        self.item_hit = true;
+    }
+
+    /// Whether a tile or header handled the current mouse-down; resets the flag
+    pub fn take_item_hit(&mut self) -> bool {
+        std::mem::take(&mut self.item_hit)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gpui::{point, px};
+
+    fn at(x: f32, y: f32) -> Point<Pixels> {
+        point(px(x), px(y))
+    }
+
+    fn select(path: &PathBuf) -> Option<PointerAction> {
+        Some(PointerAction::Select {
+            path: path.clone(),
+            modifiers: Modifiers::default(),
+        })
+    }
+
+    #[test]
+    fn test_click() {
+        let mut tracker = ClickTracker::default();
+        let a = PathBuf::from("a.png");
+        let start = Instant::now();
+
+        assert_eq!(tracker.press(a.clone(), vec![a.clone()], at(10.0, 10.0), Modifiers::default(), start), None);
+        assert_eq!(tracker.moved(at(12.0, 11.0), true), None);
+        assert_eq!(tracker.release(), select(&a));
+        assert!(tracker.take_item_hit());
+        assert!(!tracker.take_item_hit());
+    }
+
+    #[test]
+    fn test_fast_clicks_on_different_tiles() {
+        let mut tracker = ClickTracker::default();
+        let a = PathBuf::from("a.png");
+        let b = PathBuf::from("b.png");
+        let start = Instant::now();
+
+        tracker.press(a.clone(), vec![a.clone()], at(10.0, 10.0), Modifiers::default(), start);
+        assert_eq!(tracker.release(), select(&a));
+
+        // 50 ms later on another tile: a new click, not a double-click
+        let later = start + Duration::from_millis(50);
+        assert_eq!(tracker.press(b.clone(), vec![b.clone()], at(200.0, 10.0), Modifiers::default(), later), None);
+        assert_eq!(tracker.release(), select(&b));
+    }
+
+    #[test]
+    fn test_double_click() {
+        let mut tracker = ClickTracker::default();
+        let a = PathBuf::from("a.png");
+        let start = Instant::now();
+
+        tracker.press(a.clone(), vec![a.clone()], at(10.0, 10.0), Modifiers::default(), start);
+        tracker.release();
+        assert_eq!(
+            tracker.press(a.clone(), vec![a.clone()], at(10.0, 10.0), Modifiers::default(), start + Duration::from_millis(200)),
+            Some(PointerAction::Open(a.clone()))
+        );
+        // The second release doesn't select again
+        assert_eq!(tracker.release(), None);
+
+        // Too slow for a double-click
+        tracker.press(a.clone(), vec![a.clone()], at(10.0, 10.0), Modifiers::default(), start + Duration::from_secs(1));
+        tracker.release();
+        assert_eq!(
+            tracker.press(a.clone(), vec![a.clone()], at(10.0, 10.0), Modifiers::default(), start + Duration::from_secs(2)),
+            None
+        );
+    }
+
+    #[test]
+    fn test_click_drag() {
+        let mut tracker = ClickTracker::default();
+        let a = PathBuf::from("a.png");
+        let b = PathBuf::from("b.png");
+        let start = Instant::now();
+
+        tracker.press(a.clone(), vec![a.clone(), b.clone()], at(10.0, 10.0), Modifiers::default(), start);
+        assert_eq!(tracker.moved(at(20.0, 10.0), true), Some(PointerAction::Drag(vec![a.clone(), b])));
+        // Nothing left to select after the drag, and further moves are ignored
+        assert_eq!(tracker.moved(at(40.0, 10.0), true), None);
+        assert_eq!(tracker.release(), None);
+
+        // A drag doesn't arm a double-click
+        assert_eq!(
+            tracker.press(a.clone(), vec![a.clone()], at(10.0, 10.0), Modifiers::default(), start + Duration::from_millis(100)),
+            None
+        );
+    }
+
+    #[test]
+    fn test_release_outside_forgets_press() {
+        let mut tracker = ClickTracker::default();
+        let a = PathBuf::from("a.png");
+
+        tracker.press(a.clone(), vec![a], at(10.0, 10.0), Modifiers::default(), Instant::now());
+        assert_eq!(tracker.moved(at(30.0, 10.0), false), None);
+        assert_eq!(tracker.release(), None);
+    }
+}
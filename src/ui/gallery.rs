@@ -1,29 +1,26 @@
-//! Image gallery grid with drag-drop support and date grouping
+//! Image gallery grid with drag-drop support and date or folder grouping
 
 use chrono::{DateTime, Datelike, Local, NaiveDate};
 use gpui::prelude::FluentBuilder;
 use gpui::*;
 use rust_i18n::t;
 use gpui_component::scroll::ScrollableElement;
-use gpui_component::ActiveTheme;
-use std::collections::HashSet;
-use std::path::PathBuf;
-use std::sync::atomic::{AtomicBool, Ordering};
-use std::sync::{Arc, Mutex as StdMutex};
+use gpui_component::spinner::Spinner;
+use gpui_component::tooltip::Tooltip;
+use gpui_component::{h_flex, v_flex, ActiveTheme, Sizable};
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
 use std::time::{Instant, SystemTime};
 
-use crate::app::{format_file_size, GalleryAction, ScreenshotInfo, Sukusho};
+use crate::app::{GalleryAction, ScreenshotInfo, Sukusho};
 use crate::drag_drop;
-use crate::thumbnail::ThumbnailCache;
-
-/// Flag to track if a gallery item was clicked (to prevent background deselection)
-static ITEM_CLICKED: AtomicBool = AtomicBool::new(false);
-
-/// Track last click for double-click detection (time, path)
-static LAST_CLICK: StdMutex<Option<(Instant, PathBuf)>> = StdMutex::new(None);
-
-/// Double-click time threshold in milliseconds
-const DOUBLE_CLICK_TIME_MS: u128 = 500;
+use crate::number_format::format_file_size;
+use crate::settings::{BadgeContent, GalleryGrouping, SortMode};
+use crate::thumbnail::{GridThumbnail, ThumbnailCache, Undecodable};
+use crate::ui::click::PointerAction;
+use crate::ui::keynav;
+use crate::ui::text;
 
 /// Date group category
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
@@ -79,14 +76,17 @@ fn is_same_week(date1: NaiveDate, date2: NaiveDate) -> bool {
     date1.iso_week() == date2.iso_week() && date1.year() == date2.year()
 }
 
-/// Group screenshots by date
-fn group_by_date(screenshots: &[ScreenshotInfo]) -> Vec<(DateGroup, Vec<&ScreenshotInfo>)> {
+/// Group screenshots by date, oldest group first when they're sorted oldest first
+fn group_by_date(
+    screenshots: &[ScreenshotInfo],
+    oldest_first: bool,
+) -> Vec<(DateGroup, Vec<&ScreenshotInfo>)> {
     use std::collections::BTreeMap;
 
     let mut groups: BTreeMap<(u32, String), (DateGroup, Vec<&ScreenshotInfo>)> = BTreeMap::new();
 
     for info in screenshots {
-        let group = DateGroup::from_system_time(info.modified);
+        let group = DateGroup::from_system_time(info.captured);
         let key = (group.order(), group.label());
 
         groups
@@ -96,45 +96,225 @@ fn group_by_date(screenshots: &[ScreenshotInfo]) -> Vec<(DateGroup, Vec<&Screens
             .push(info);
     }
 
-    groups.into_values().collect()
+    let mut groups: Vec<_> = groups.into_values().collect();
+    if oldest_first {
+        groups.reverse();
+    }
+    groups
+}
+
+/// Which way an image is longer, for the orientation filter
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Orientation {
+    Landscape,
+    Portrait,
+    Square,
+}
+
+impl Orientation {
+    pub const ALL: [Orientation; 3] = [Orientation::Landscape, Orientation::Portrait, Orientation::Square];
+
+    /// Within 2% of square counts as square (crops rarely come out exact)
+    pub fn of(width: u32, height: u32) -> Self {
+        let (width, height) = (u64::from(width), u64::from(height));
+        if width.abs_diff(height) * 50 <= width.max(height) {
+            Orientation::Square
+        } else if width > height {
+            Orientation::Landscape
+        } else {
+            Orientation::Portrait
+        }
+    }
+
+    /// From dimensions already probed; `None` until then, without touching the file
+    pub fn cached(info: &ScreenshotInfo) -> Option<Self> {
+        crate::thumbnail::cached_dimensions(&info.path, info.modified)
+            .flatten()
+            .map(|(width, height)| Self::of(width, height))
+    }
+
+    pub fn label(self) -> String {
+        match self {
+            Orientation::Landscape => t!("app.header.orientation.landscape").to_string(),
+            Orientation::Portrait => t!("app.header.orientation.portrait").to_string(),
+            Orientation::Square => t!("app.header.orientation.square").to_string(),
+        }
+    }
+}
+
+/// Header for a folder group: the folder relative to `root`
+pub fn folder_label(root: &Path, folder: &Path) -> String {
+    let relative = folder.strip_prefix(root).unwrap_or(folder);
+    if relative.as_os_str().is_empty() {
+        return t!("gallery.folder_group.root").to_string();
+    }
+    relative
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join(" / ")
+}
+
+/// Group screenshots by parent folder, keeping their order; a folder's group
+/// goes where its first screenshot is
+fn group_by_folder<'a>(
+    screenshots: &'a [ScreenshotInfo],
+    root: &Path,
+) -> Vec<(String, Vec<&'a ScreenshotInfo>)> {
+    let mut positions: HashMap<&Path, usize> = HashMap::new();
+    let mut groups: Vec<(String, Vec<&ScreenshotInfo>)> = Vec::new();
+    for info in screenshots {
+        let folder = info.path.parent().unwrap_or(root);
+        let index = *positions.entry(folder).or_insert_with(|| {
+            groups.push((folder_label(root, folder), Vec::new()));
+            groups.len() - 1
+        });
+        groups[index].1.push(info);
+    }
+    groups
+}
+
+/// The screenshots in `ranking`, in its order (search results, most relevant
+/// first)
+fn rank<'a>(screenshots: &'a [ScreenshotInfo], ranking: &[PathBuf]) -> Vec<&'a ScreenshotInfo> {
+    let positions: HashMap<&Path, usize> = ranking
+        .iter()
+        .enumerate()
+        .map(|(position, path)| (path.as_path(), position))
+        .collect();
+    let mut ranked: Vec<(usize, &ScreenshotInfo)> = screenshots
+        .iter()
+        .filter_map(|info| positions.get(info.path.as_path()).map(|&position| (position, info)))
+        .collect();
+    ranked.sort_by_key(|&(position, _)| position);
+    ranked.into_iter().map(|(_, info)| info).collect()
+}
+
+/// Screenshots in gallery groups with their header (a single group without
+/// one when nothing splits them). Search results (`ranking`) are a single
+/// group in relevance order: a date header would break up the ranking.
+fn group<'a>(
+    screenshots: &'a [ScreenshotInfo],
+    ranking: Option<&[PathBuf]>,
+    sort: SortMode,
+    grouping: GalleryGrouping,
+    root: &Path,
+) -> Vec<(Option<String>, Vec<&'a ScreenshotInfo>)> {
+    if let Some(ranking) = ranking {
+        return vec![(None, rank(screenshots, ranking))];
+    }
+    if !grouping.has_headers(sort) {
+        return vec![(None, screenshots.iter().collect())];
+    }
+    match grouping {
+        GalleryGrouping::Date => group_by_date(screenshots, sort == SortMode::Oldest)
+            .into_iter()
+            .map(|(group, items)| (Some(group.label()), items))
+            .collect(),
+        GalleryGrouping::Folder => group_by_folder(screenshots, root)
+            .into_iter()
+            .map(|(label, items)| (Some(label), items))
+            .collect(),
+    }
+}
+
+/// Paths in the order the gallery shows them, one list per group
+pub fn display_groups(
+    screenshots: &[ScreenshotInfo],
+    ranking: Option<&[PathBuf]>,
+    sort: SortMode,
+    grouping: GalleryGrouping,
+    root: &Path,
+) -> Vec<Vec<PathBuf>> {
+    group(screenshots, ranking, sort, grouping, root)
+        .into_iter()
+        .map(|(_, items)| items.into_iter().map(|s| s.path.clone()).collect())
+        .collect()
+}
+
+/// Approximate width of one Latin badge character (text_xs) in pixels
+const BADGE_CHAR_WIDTH: f32 = 7.0;
+
+/// Horizontal space a badge can't use: its padding plus the tile inset
+const BADGE_INSET: f32 = 28.0;
+
+/// How many badge columns fit on a tile of `tile_size` pixels (a CJK
+/// character or emoji takes two, see [`text::display_width`])
+fn badge_max_chars(tile_size: u32) -> usize {
+    ((tile_size as f32 - BADGE_INSET) / BADGE_CHAR_WIDTH).max(4.0) as usize
+}
+
+/// Badge text for a tile, or None when no badge should be drawn
+fn badge_text(
+    content: BadgeContent,
+    file_name: &str,
+    extension: &str,
+    file_size: u64,
+    max_chars: usize,
+) -> Option<String> {
+    match content {
+        BadgeContent::ExtensionSize => Some(format!("{} | {}", extension, format_file_size(file_size))),
+        BadgeContent::SizeOnly => Some(format_file_size(file_size)),
+        BadgeContent::FileName => Some(text::middle_ellipsize(file_name, max_chars)),
+        BadgeContent::None => None,
+    }
 }
 
 /// Item data for gallery rendering
 struct GalleryItemData {
     path: PathBuf,
     is_selected: bool,
+    /// Keyboard focus is on this item
+    is_focused: bool,
+    /// Briefly highlighted (where a cleared search's result is)
+    is_flashing: bool,
+    /// Auto-convert is still working on it
+    is_converting: bool,
     selected_paths: Vec<PathBuf>,
     size: u32,
     index: usize,
     file_size: u64,
     extension: String,
+    /// Downscaled copy to show, or what to show for an undecodable file
+    thumbnail: GridThumbnail,
+    /// Corner badge text (None = no badge)
+    badge: Option<String>,
+    /// Taller than wide (as far as known), for the corner marker
+    portrait: bool,
 }
 
-/// Build a gallery grid component with date grouping
+/// Build a gallery grid component with date or folder grouping, or only the
+/// search results (`ranking`) in relevance order
 pub fn gallery(
     screenshots: Vec<ScreenshotInfo>,
-    filtered_paths: Option<Vec<PathBuf>>,
+    ranking: Option<Vec<PathBuf>>,
     selected: HashSet<PathBuf>,
-    _thumbnail_cache: Arc<ThumbnailCache>,
+    focused: Option<PathBuf>,
+    flashing: Option<PathBuf>,
+    converting: HashSet<PathBuf>,
+    thumbnail_cache: Arc<ThumbnailCache>,
     _columns: u32,
     thumbnail_size: u32,
-    has_more: bool,
+    badge_content: BadgeContent,
+    sort: SortMode,
+    grouping: GalleryGrouping,
+    root: PathBuf,
+    scroll_handle: &ScrollHandle,
+    loading_more: bool,
     cx: &mut Context<Sukusho>,
 ) -> impl IntoElement {
-    let spacing = 8.0;
+    let spacing = keynav::TILE_GAP;
 
-    // Filter screenshots if search is active
-    let visible_screenshots = if let Some(filter) = filtered_paths {
-        let filter_set: HashSet<_> = filter.into_iter().collect();
-        screenshots
-            .into_iter()
-            .filter(|s| filter_set.contains(&s.path))
-            .collect()
-    } else {
-        screenshots
-    };
+    // Group by date or folder; Today/Yesterday mean nothing in size or name order
+    let groups = crate::perf::time(crate::perf::Phase::Grouping, || {
+        group(&screenshots, ranking.as_deref(), sort, grouping, &root)
+    });
 
-    if visible_screenshots.is_empty() {
+    // A search without good matches says so above the grid
+    if ranking.is_some() && groups.iter().all(|(_, items)| items.is_empty()) {
+        return div().size_full().into_any_element();
+    }
+    if screenshots.is_empty() {
         return div()
             .size_full()
             .flex()
@@ -148,36 +328,35 @@ pub fn gallery(
             .into_any_element();
     }
 
-    // Group screenshots by date
-    let groups = group_by_date(&visible_screenshots);
-
     // Build grouped content
     let mut content_children: Vec<AnyElement> = Vec::new();
     let mut global_index = 0usize;
 
     for (group, items) in groups {
         // Add group header
-        content_children.push(
-            div()
-                .w_full()
-                .pt_4()
-                .pb_2()
-                .on_mouse_down(
-                    MouseButton::Left,
-                    cx.listener(|_, _, _, _| {
-                        // Mark that a header was clicked (prevent background deselection)
-                        ITEM_CLICKED.store(true, Ordering::SeqCst);
-                    }),
-                )
-                .child(
-                    div()
-                        .text_sm()
-                        .font_weight(FontWeight::SEMIBOLD)
-                        .text_color(cx.theme().muted_foreground)
-                        .child(group.label()),
-                )
-                .into_any_element(),
-        );
+        if let Some(group) = group {
+            content_children.push(
+                div()
+                    .w_full()
+                    .pt_4()
+                    .pb_2()
+                    .on_mouse_down(
+                        MouseButton::Left,
+                        cx.listener(|this, _, _, _| {
+                            // Mark that a header was clicked (prevent background deselection)
+                            this.click_tracker().mark_item_hit();
+                        }),
+                    )
+                    .child(
+                        div()
+                            .text_sm()
+                            .font_weight(FontWeight::SEMIBOLD)
+                            .text_color(cx.theme().muted_foreground)
+                            .child(group),
+                    )
+                    .into_any_element(),
+            );
+        }
 
         // Build items for this group
         let mut group_items: Vec<AnyElement> = Vec::new();
@@ -189,14 +368,36 @@ pub fn gallery(
                 vec![info.path.clone()]
             };
 
+            // Undecodable files are found by the thumbnail workers too
+            let thumbnail = thumbnail_cache.grid_thumbnail(&info.path, info.modified, thumbnail_size);
+
+            let file_name = info
+                .path
+                .file_name()
+                .map(|n| n.to_string_lossy().to_string())
+                .unwrap_or_default();
+            let badge = badge_text(
+                badge_content,
+                &file_name,
+                &info.extension,
+                info.file_size,
+                badge_max_chars(thumbnail_size),
+            );
+
             let data = GalleryItemData {
                 path: info.path.clone(),
                 is_selected,
+                is_focused: focused.as_ref() == Some(&info.path),
+                is_flashing: flashing.as_ref() == Some(&info.path),
+                is_converting: converting.contains(&info.path),
                 selected_paths,
                 size: thumbnail_size,
                 index: global_index,
                 file_size: info.file_size,
                 extension: info.extension.clone(),
+                thumbnail,
+                badge,
+                portrait: Orientation::cached(info) == Some(Orientation::Portrait),
             };
             group_items.push(gallery_item(data, cx).into_any_element());
             global_index += 1;
@@ -214,27 +415,17 @@ pub fn gallery(
         );
     }
 
-    // Add infinite scroll trigger at the bottom
-    if has_more {
+    // Spinner while the next page is being appended (loading is driven by scroll offset in Sukusho)
+    if loading_more {
         content_children.push(
             div()
-                .id("infinite-scroll-trigger")
+                .id("infinite-scroll-spinner")
                 .w_full()
-                .h(px(50.0))
+                .h(px(32.0))
                 .flex()
                 .items_center()
                 .justify_center()
-                .text_sm()
-                .text_color(cx.theme().muted_foreground)
-                .child(t!("gallery.loading_more").to_string())
-                .on_mouse_down(
-                    MouseButton::Left,
-                    cx.listener(|this, _, _, cx| {
-                        // Mark that an item was clicked (prevent background deselection)
-                        ITEM_CLICKED.store(true, Ordering::SeqCst);
-                        this.handle_action(GalleryAction::LoadMore, cx);
-                    }),
-                )
+                .child(Spinner::new().small())
                 .into_any_element(),
         );
     }
@@ -242,18 +433,9 @@ pub fn gallery(
     div()
         .id("gallery-scroll-container")
         .size_full()
-        .overflow_y_scrollbar()
-        // Trigger load more when scrolling near bottom
-        .on_scroll_wheel(cx.listener(move |this, event: &ScrollWheelEvent, _, cx| {
-            // Load more when scrolling down (negative delta means scrolling down)
-            let is_scrolling_down = match event.delta {
-                ScrollDelta::Lines(delta) => delta.y < 0.0,
-                ScrollDelta::Pixels(delta) => delta.y < px(0.0),
-            };
-            if is_scrolling_down && has_more {
-                this.handle_action(GalleryAction::LoadMore, cx);
-            }
-        }))
+        .track_scroll(scroll_handle)
+        .overflow_y_scroll()
+        .vertical_scrollbar(scroll_handle)
         .child(
             div()
                 .id("gallery-content")
@@ -264,13 +446,29 @@ pub fn gallery(
                     MouseButton::Left,
                     cx.listener(|this, _event: &MouseDownEvent, _, cx| {
                         // Check if an item was clicked (item handlers set this flag)
-                        if !ITEM_CLICKED.swap(false, Ordering::SeqCst) {
+                        if !this.click_tracker().take_item_hit() {
                             // No item was clicked, so this is a background click
                             // Clear selection
                             this.handle_action(GalleryAction::ClearSelection, cx);
                         }
                     }),
                 )
+                // A pressed tile becomes a drag once the pointer moves far enough
+                .on_mouse_move(cx.listener(|this, event: &MouseMoveEvent, _, cx| {
+                    let button_down = event.pressed_button == Some(MouseButton::Left);
+                    if let Some(action) = this.click_tracker().moved(event.position, button_down) {
+                        run_pointer_action(this, action, cx);
+                    }
+                }))
+                // ...or a click if it's released first
+                .on_mouse_up(
+                    MouseButton::Left,
+                    cx.listener(|this, _event: &MouseUpEvent, _, cx| {
+                        if let Some(action) = this.click_tracker().release() {
+                            run_pointer_action(this, action, cx);
+                        }
+                    }),
+                )
                 .children(content_children),
         )
         .into_any_element()
@@ -287,13 +485,19 @@ fn gallery_item(data: GalleryItemData, cx: &mut Context<Sukusho>) -> impl IntoEl
     let is_selected = data.is_selected;
 
     // Enhanced color scheme
-    let bg_color = if is_selected {
+    let bg_color = if data.is_flashing {
+        cx.theme().primary.opacity(0.35)
+    } else if is_selected {
         cx.theme().accent
     } else {
         cx.theme().secondary
     };
 
-    let border_color = if is_selected {
+    let border_color = if data.is_flashing {
+        cx.theme().primary
+    } else if data.is_focused {
+        cx.theme().ring
+    } else if is_selected {
         cx.theme().primary
     } else {
         cx.theme().border
@@ -302,7 +506,10 @@ fn gallery_item(data: GalleryItemData, cx: &mut Context<Sukusho>) -> impl IntoEl
     let hover_border = cx.theme().primary;
     let hover_bg = cx.theme().muted;
 
-    let file_badge = format!("{} | {}", data.extension, format_file_size(data.file_size));
+    let file_badge = data.badge.clone();
+    let extension = data.extension.clone();
+    let thumbnail = data.thumbnail.clone();
+    let placeholder_color = cx.theme().muted_foreground;
 
     // Badge colors - semi-transparent black with white text for good contrast
     let badge_bg = gpui::hsla(0.0, 0.0, 0.0, 0.75);
@@ -349,12 +556,63 @@ fn gallery_item(data: GalleryItemData, cx: &mut Context<Sukusho>) -> impl IntoEl
                         .flex()
                         .items_center()
                         .justify_center()
-                        .child(
-                            img(path.clone())
-                                .max_w_full()
-                                .max_h_full()
-                                .object_fit(ObjectFit::Contain),
-                        ),
+                        .map(|this| match thumbnail {
+                            // Undecodable here, but the shell could render it
+                            GridThumbnail::Failed(Undecodable {
+                                error: Some(error),
+                                fallback: Some(fallback),
+                                ..
+                            }) => this.child(
+                                div()
+                                    .id(ElementId::Name(format!("undecodable-{}", data.index).into()))
+                                    .size_full()
+                                    .flex()
+                                    .items_center()
+                                    .justify_center()
+                                    .tooltip(move |window, cx| {
+                                        Tooltip::new(error.clone()).build(window, cx)
+                                    })
+                                    .child(
+                                        img(fallback)
+                                            .max_w_full()
+                                            .max_h_full()
+                                            .object_fit(ObjectFit::Contain),
+                                    ),
+                            ),
+                            // Known undecodable file - skip the image loader entirely
+                            GridThumbnail::Failed(Undecodable {
+                                error: Some(error),
+                                fallback: None,
+                                icon,
+                            }) => this.child(
+                                undecodable_placeholder(&extension, icon, placeholder_color)
+                                    .id(ElementId::Name(format!("undecodable-{}", data.index).into()))
+                                    .tooltip(move |window, cx| {
+                                        Tooltip::new(error.clone()).build(window, cx)
+                                    }),
+                            ),
+                            GridThumbnail::Ready(thumbnail) => this.child(
+                                img(thumbnail)
+                                    .max_w_full()
+                                    .max_h_full()
+                                    .object_fit(ObjectFit::Contain)
+                                    .with_fallback(move || {
+                                        undecodable_placeholder(&extension, None, placeholder_color)
+                                            .into_any_element()
+                                    }),
+                            ),
+                            // Still being generated
+                            GridThumbnail::Pending => this.child(
+                                div()
+                                    .size_full()
+                                    .rounded(px(8.0))
+                                    .bg(placeholder_color.opacity(0.12)),
+                            ),
+                            // The header was fine but the image wasn't (e.g. truncated)
+                            GridThumbnail::Failed(Undecodable { error: None, icon, .. }) => {
+                                this.child(undecodable_placeholder(&extension, icon, placeholder_color))
+                            }
+                        }),
                 )
                 // Selection checkbox - always visible (circular design)
                 .child(
@@ -390,7 +648,7 @@ fn gallery_item(data: GalleryItemData, cx: &mut Context<Sukusho>) -> impl IntoEl
                             MouseButton::Left,
                             cx.listener(move |this, _event: &MouseDownEvent, _, cx| {
                                 // Mark that an item was clicked (prevent background deselection)
-                                ITEM_CLICKED.store(true, Ordering::SeqCst);
+                                this.click_tracker().mark_item_hit();
                                 // Checkbox click = toggle selection (append/remove like Ctrl+click)
                                 this.handle_action(
                                     GalleryAction::Select {
@@ -405,32 +663,75 @@ fn gallery_item(data: GalleryItemData, cx: &mut Context<Sukusho>) -> impl IntoEl
                             }),
                         ),
                 )
-                .child(
-                    // File format and size badge - enhanced styling
-                    div()
-                        .absolute()
-                        .bottom(px(6.0))
-                        .right(px(6.0))
-                        .px(px(8.0))
-                        .py(px(3.0))
-                        .rounded(px(6.0))
-                        .bg(badge_bg)
-                        .text_color(gpui::rgb(0xFFFFFF))
-                        .text_xs()
-                        .font_weight(FontWeight::MEDIUM)
-                        .child(file_badge),
-                ),
+                // Portrait marker: a small upright frame in the corner
+                .when(data.portrait, |this| {
+                    this.child(
+                        div()
+                            .absolute()
+                            .top(px(8.0))
+                            .right(px(8.0))
+                            .w(px(8.0))
+                            .h(px(13.0))
+                            .rounded(px(2.0))
+                            .border_1()
+                            .border_color(gpui::hsla(0.0, 0.0, 1.0, 0.7))
+                            .bg(badge_bg),
+                    )
+                })
+                // Auto-convert hasn't swapped in the converted file yet
+                .when(data.is_converting, |this| {
+                    this.child(
+                        h_flex()
+                            .absolute()
+                            .bottom(px(6.0))
+                            .left(px(6.0))
+                            .items_center()
+                            .gap_1()
+                            .px(px(6.0))
+                            .py(px(3.0))
+                            .rounded(px(6.0))
+                            .bg(badge_bg)
+                            .text_color(gpui::rgb(0xFFFFFF))
+                            .text_xs()
+                            .whitespace_nowrap()
+                            .child(Spinner::new().xsmall().color(gpui::white()))
+                            .child(t!("app.converting_badge").to_string()),
+                    )
+                })
+                .when_some(file_badge, |this, file_badge| {
+                    // File format/size/name badge - enhanced styling
+                    this.child(
+                        div()
+                            .absolute()
+                            .bottom(px(6.0))
+                            .right(px(6.0))
+                            .px(px(8.0))
+                            .py(px(3.0))
+                            .rounded(px(6.0))
+                            .bg(badge_bg)
+                            .text_color(gpui::rgb(0xFFFFFF))
+                            .text_xs()
+                            .font_weight(FontWeight::MEDIUM)
+                            .whitespace_nowrap()
+                            .child(file_badge),
+                    )
+                }),
         )
         // Right click - context menu (for selected items or just clicked item)
         .on_mouse_down(
             MouseButton::Right,
             cx.listener(move |this, event: &MouseDownEvent, _, cx| {
                 // Mark that an item was clicked (prevent background deselection)
-                ITEM_CLICKED.store(true, Ordering::SeqCst);
+                this.click_tracker().mark_item_hit();
                 // If the clicked item is selected, show context menu for all selected
+                // (the clicked one first, for actions on a single item)
                 // Otherwise, show context menu for just the clicked item
                 let paths = if this.is_path_selected(&path_for_ctx) && this.has_selection() {
-                    this.get_selected_paths()
+                    let mut paths = this.get_selected_paths();
+                    if let Some(index) = paths.iter().position(|p| *p == path_for_ctx) {
+                        paths.swap(0, index);
+                    }
+                    paths
                 } else {
                     vec![path_for_ctx.clone()]
                 };
@@ -443,101 +744,142 @@ fn gallery_item(data: GalleryItemData, cx: &mut Context<Sukusho>) -> impl IntoEl
                 );
             }),
         )
-        // Drag operation - use Windows DragDetect for threshold detection
+        // Press arms click/double-click/drag detection (resolved on move or release)
         .on_mouse_down(
             MouseButton::Left,
             cx.listener({
                 let drag_paths = drag_paths.clone();
-                let path_for_select = path.clone();
-                let path_for_dblclick = path_for_dbl.clone();
+                let path_for_press = path_for_dbl.clone();
                 move |this, event: &MouseDownEvent, _, cx| {
-                    // Mark that an item was clicked (prevent background deselection)
-                    ITEM_CLICKED.store(true, Ordering::SeqCst);
-
-                    if drag_paths.is_empty() {
-                        return;
-                    }
-
-                    // Check for double-click BEFORE drag detection
-                    let now = Instant::now();
-                    let is_double_click = {
-                        let mut last_click = LAST_CLICK.lock().unwrap();
-                        let is_dbl = if let Some((last_time, last_path)) = last_click.as_ref() {
-                            let elapsed = now.duration_since(*last_time).as_millis();
-                            elapsed < DOUBLE_CLICK_TIME_MS && last_path == &path_for_dblclick
-                        } else {
-                            false
-                        };
-
-                        if is_dbl {
-                            // Clear last click on double-click
-                            *last_click = None;
-                        } else {
-                            // Record this click
-                            *last_click = Some((now, path_for_dblclick.clone()));
-                        }
-                        is_dbl
-                    };
-
-                    // If double-click detected, open file immediately and skip drag detection
-                    if is_double_click {
-                        log::info!("Double-click detected via timer, opening file: {:?}", path_for_dblclick);
-                        this.handle_action(GalleryAction::Open(path_for_dblclick.clone()), cx);
-                        return;
-                    }
-
-                    // NOT a double-click - proceed with normal drag detection (UNCHANGED)
-                    // Use Windows DragDetect for threshold detection
-                    // This is a modal function that returns true if user dragged past threshold
-                    let should_drag = drag_drop::check_drag_threshold();
-
-                    if should_drag {
-                        log::info!(
-                            "DragDetect returned true, starting native OLE drag with {} files",
-                            drag_paths.len()
-                        );
-                        drag_drop::start_drag(&drag_paths);
-                    } else {
-                        // User just clicked without dragging - treat as selection
-                        log::debug!("DragDetect returned false, treating as click");
-                        this.handle_action(
-                            GalleryAction::Select {
-                                path: path_for_select.clone(),
-                                modifiers: Modifiers {
-                                    control: event.modifiers.control,
-                                    alt: event.modifiers.alt,
-                                    shift: event.modifiers.shift,
-                                    platform: event.modifiers.platform,
-                                    function: event.modifiers.function,
-                                },
-                            },
-                            cx,
-                        );
+                    let action = this.click_tracker().press(
+                        path_for_press.clone(),
+                        drag_paths.clone(),
+                        event.position,
+                        event.modifiers,
+                        Instant::now(),
+                    );
+                    if let Some(action) = action {
+                        run_pointer_action(this, action, cx);
                     }
                 }
             }),
         )
 }
 
-/// Show Windows shell context menu for multiple files
+/// Carry out what the click tracker decided
+fn run_pointer_action(this: &mut Sukusho, action: PointerAction, cx: &mut Context<Sukusho>) {
+    match action {
+        PointerAction::Select { path, modifiers } => {
+            this.handle_action(GalleryAction::Select { path, modifiers }, cx);
+        }
+        PointerAction::Open(path) => {
+            log::info!("Double-click detected, opening file: {:?}", path);
+            this.handle_action(GalleryAction::Open(path), cx);
+        }
+        PointerAction::Drag(paths) => {
+            log::info!("Drag threshold passed, starting native OLE drag with {} files", paths.len());
+            drag_drop::start_drag(&paths);
+        }
+    }
+}
+
+/// Placeholder for files that can't be decoded: shell file icon (looked up
+/// by the thumbnail workers) plus extension. Selection, drag, open and the
+/// context menu still work through the tile.
+fn undecodable_placeholder(extension: &str, icon: Option<PathBuf>, color: Hsla) -> Div {
+    v_flex()
+        .size_full()
+        .items_center()
+        .justify_center()
+        .gap_2()
+        .when_some(icon, |this, icon| {
+            this.child(img(icon).size(px(32.0)))
+        })
+        .child(
+            div()
+                .text_sm()
+                .font_weight(FontWeight::SEMIBOLD)
+                .text_color(color)
+                .child(extension.to_uppercase()),
+        )
+}
+
+/// Our own items in the shell context menu
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ContextMenuCommand {
+    /// Show the processing timeline
+    Details,
+    /// Open in the configured editor
+    Edit,
+    /// Add screenshots that look like this one to the selection
+    SelectSimilar,
+    /// Set modified times back to the date inside the files
+    FixTimestamps,
+    /// Copy a one-line summary (dimensions, size, dates, path) as text
+    CopyInfo,
+    /// Show the screenshot full-size in the window
+    Preview,
+    /// Move into the organizer's date folders, even with the organizer off
+    Organize,
+    /// Show screenshots that look like this one as search results
+    FindSimilar,
+}
+
+/// Menu command id for our "Details" item (above the shell's 1..=0x7FFF range)
+#[cfg(windows)]
+const DETAILS_COMMAND_ID: usize = 0x8000;
+
+/// Menu command id for our "Edit" item
+#[cfg(windows)]
+const EDIT_COMMAND_ID: usize = 0x8001;
+
+/// Menu command id for our "Select Similar" item
+#[cfg(windows)]
+const SELECT_SIMILAR_COMMAND_ID: usize = 0x8002;
+
+/// Menu command id for our "Fix Timestamp" item
 #[cfg(windows)]
-pub fn show_shell_context_menu(paths: &[PathBuf]) {
+const FIX_TIMESTAMPS_COMMAND_ID: usize = 0x8003;
+
+/// Menu command id for our "Copy Info" item
+#[cfg(windows)]
+const COPY_INFO_COMMAND_ID: usize = 0x8004;
+
+/// Menu command id for our "Preview" item
+#[cfg(windows)]
+const PREVIEW_COMMAND_ID: usize = 0x8005;
+
+/// Menu command id for our "Organize into Date Folders" item
+#[cfg(windows)]
+const ORGANIZE_COMMAND_ID: usize = 0x8006;
+
+/// Menu command id for our "Find Similar" item
+#[cfg(windows)]
+const FIND_SIMILAR_COMMAND_ID: usize = 0x8007;
+
+/// Show Windows shell context menu for multiple files.
+/// Returns which of our own items the user picked, if any.
+#[cfg(windows)]
+pub fn show_shell_context_menu(paths: &[PathBuf]) -> Option<ContextMenuCommand> {
     use crate::tray::WINDOW_HWND;
     use log::{debug, error, info};
     use std::ffi::OsStr;
     use std::os::windows::ffi::OsStrExt;
     use windows::core::PCWSTR;
     use windows::Win32::Foundation::{HWND, POINT};
+    use windows::Win32::UI::Shell::Common::ITEMIDLIST;
     use windows::Win32::UI::Shell::{
-        BHID_SFUIObject, IContextMenu, IShellItem, SHCreateItemFromParsingName, CMINVOKECOMMANDINFO,
+        BHID_SFUIObject, IContextMenu, ILFree, SHCreateShellItemArrayFromIDLists, SHParseDisplayName,
+        CMINVOKECOMMANDINFO,
     };
     use windows::Win32::UI::WindowsAndMessaging::{
-        CreatePopupMenu, DestroyMenu, GetCursorPos, PostMessageW, SetForegroundWindow,
-        TrackPopupMenu, TPM_LEFTALIGN, TPM_RETURNCMD, TPM_RIGHTBUTTON, WM_NULL,
+        CreatePopupMenu, DestroyMenu, GetCursorPos, InsertMenuW, PostMessageW,
+        SetForegroundWindow, TrackPopupMenu, MF_BYPOSITION, MF_SEPARATOR, MF_STRING,
+        TPM_LEFTALIGN, TPM_RETURNCMD, TPM_RIGHTBUTTON, WM_NULL,
     };
 
     if paths.is_empty() {
-        return;
+        return None;
     }
 
     info!("Opening context menu for {} files", paths.len());
@@ -546,7 +888,7 @@ pub fn show_shell_context_menu(paths: &[PathBuf]) {
     let valid_paths: Vec<_> = paths.iter().filter(|p| p.exists()).collect();
     if valid_paths.is_empty() {
         error!("No valid paths for context menu");
-        return;
+        return None;
     }
 
     // Get window handle
@@ -554,7 +896,7 @@ pub fn show_shell_context_menu(paths: &[PathBuf]) {
         Some(h) => HWND(h as *mut std::ffi::c_void),
         None => {
             error!("No window handle available for context menu");
-            return;
+            return None;
         }
     };
 
@@ -562,39 +904,44 @@ pub fn show_shell_context_menu(paths: &[PathBuf]) {
         // Set foreground window to ensure menu shows
         let _ = SetForegroundWindow(hwnd);
 
-        // Create shell items for all paths
-        let mut shell_items: Vec<IShellItem> = Vec::new();
-        for path in &valid_paths {
+        // The shell builds one menu for items in one folder, so a selection
+        // spanning date folders gets the menu of those next to the first
+        let folder = valid_paths[0].parent();
+        let mut pidls: Vec<*mut ITEMIDLIST> = Vec::new();
+        for path in valid_paths.iter().filter(|p| p.parent() == folder) {
             let wide_path: Vec<u16> = OsStr::new(path)
                 .encode_wide()
                 .chain(std::iter::once(0))
                 .collect();
 
-            match SHCreateItemFromParsingName(PCWSTR(wide_path.as_ptr()), None) {
-                Ok(item) => shell_items.push(item),
+            let mut pidl: *mut ITEMIDLIST = std::ptr::null_mut();
+            match SHParseDisplayName(PCWSTR(wide_path.as_ptr()), None, &mut pidl, 0, None) {
+                Ok(()) => pidls.push(pidl),
                 Err(e) => {
-                    debug!("Failed to create shell item for {:?}: {:?}", path, e);
+                    debug!("Failed to parse {:?} for the shell: {:?}", path, e);
                 }
             }
         }
 
-        if shell_items.is_empty() {
+        if pidls.is_empty() {
             error!("No shell items created");
-            return;
+            return None;
         }
 
-        info!("Created {} shell items for context menu", shell_items.len());
-
-        // Get context menu - use first item for now (multi-file support is complex)
-        // TODO: Implement full multi-file context menu using IShellFolder::GetUIObjectOf
-        let context_menu: IContextMenu = match shell_items[0].BindToHandler(None, &BHID_SFUIObject)
-        {
-            Ok(cm) => cm,
-            Err(e) => {
-                error!("Failed to get context menu: {:?}", e);
-                return;
-            }
-        };
+        let item_ids: Vec<*const ITEMIDLIST> = pidls.iter().map(|&pidl| pidl as *const _).collect();
+        let item_array = SHCreateShellItemArrayFromIDLists(&item_ids);
+        for pidl in pidls.drain(..) {
+            ILFree(Some(pidl));
+        }
+        let item_count = item_ids.len();
+        let context_menu: IContextMenu =
+            match item_array.and_then(|items| items.BindToHandler(None, &BHID_SFUIObject)) {
+                Ok(cm) => cm,
+                Err(e) => {
+                    error!("Failed to get context menu: {:?}", e);
+                    return None;
+                }
+            };
 
         debug!("Got IContextMenu successfully");
 
@@ -603,21 +950,134 @@ pub fn show_shell_context_menu(paths: &[PathBuf]) {
             Ok(m) => m,
             Err(e) => {
                 error!("Failed to create popup menu: {:?}", e);
-                return;
+                return None;
             }
         };
 
-        // Query context menu items
-        if let Err(e) = context_menu.QueryContextMenu(
+        // Query context menu items. Shell extensions run here, over every
+        // item; timed so the confirmation threshold can be tuned against
+        // real selections.
+        let query_start = std::time::Instant::now();
+        let query_result = context_menu.QueryContextMenu(
             hmenu,
             0,
             1,
             0x7FFF,
             windows::Win32::UI::Shell::CMF_NORMAL,
-        ) {
+        );
+        info!(
+            "QueryContextMenu took {:?} for {} files",
+            query_start.elapsed(),
+            item_count
+        );
+        if let Err(e) = query_result {
             error!("Failed to query context menu: {:?}", e);
             let _ = DestroyMenu(hmenu);
-            return;
+            return None;
+        }
+
+        // Our own "Details" item at the top
+        let details_label: Vec<u16> = t!("gallery.context_menu.details")
+            .encode_utf16()
+            .chain(std::iter::once(0))
+            .collect();
+        let _ = InsertMenuW(
+            hmenu,
+            0,
+            MF_BYPOSITION | MF_STRING,
+            DETAILS_COMMAND_ID,
+            PCWSTR(details_label.as_ptr()),
+        );
+        let edit_label: Vec<u16> = match crate::editor::preferred_editor() {
+            Some(editor) => t!(
+                "gallery.context_menu.edit_in",
+                editor = crate::editor::editor_name(&editor)
+            )
+            .to_string(),
+            None => t!("gallery.context_menu.edit").to_string(),
+        }
+        .encode_utf16()
+        .chain(std::iter::once(0))
+        .collect();
+        let _ = InsertMenuW(
+            hmenu,
+            1,
+            MF_BYPOSITION | MF_STRING,
+            EDIT_COMMAND_ID,
+            PCWSTR(edit_label.as_ptr()),
+        );
+        let similar_label: Vec<u16> = t!("gallery.context_menu.select_similar")
+            .encode_utf16()
+            .chain(std::iter::once(0))
+            .collect();
+        let _ = InsertMenuW(
+            hmenu,
+            2,
+            MF_BYPOSITION | MF_STRING,
+            SELECT_SIMILAR_COMMAND_ID,
+            PCWSTR(similar_label.as_ptr()),
+        );
+        let fix_label: Vec<u16> = t!("gallery.context_menu.fix_timestamps")
+            .encode_utf16()
+            .chain(std::iter::once(0))
+            .collect();
+        let _ = InsertMenuW(
+            hmenu,
+            3,
+            MF_BYPOSITION | MF_STRING,
+            FIX_TIMESTAMPS_COMMAND_ID,
+            PCWSTR(fix_label.as_ptr()),
+        );
+        let copy_info_label: Vec<u16> = t!("gallery.context_menu.copy_info")
+            .encode_utf16()
+            .chain(std::iter::once(0))
+            .collect();
+        let _ = InsertMenuW(
+            hmenu,
+            4,
+            MF_BYPOSITION | MF_STRING,
+            COPY_INFO_COMMAND_ID,
+            PCWSTR(copy_info_label.as_ptr()),
+        );
+        let organize_label: Vec<u16> = t!("gallery.context_menu.organize")
+            .encode_utf16()
+            .chain(std::iter::once(0))
+            .collect();
+        let _ = InsertMenuW(
+            hmenu,
+            5,
+            MF_BYPOSITION | MF_STRING,
+            ORGANIZE_COMMAND_ID,
+            PCWSTR(organize_label.as_ptr()),
+        );
+        let _ = InsertMenuW(hmenu, 6, MF_BYPOSITION | MF_SEPARATOR, 0, PCWSTR::null());
+        // "Find Similar" next to "Select Similar", for a single screenshot
+        let find_similar_label: Vec<u16> = t!("gallery.context_menu.find_similar")
+            .encode_utf16()
+            .chain(std::iter::once(0))
+            .collect();
+        if valid_paths.len() == 1 {
+            let _ = InsertMenuW(
+                hmenu,
+                3,
+                MF_BYPOSITION | MF_STRING,
+                FIND_SIMILAR_COMMAND_ID,
+                PCWSTR(find_similar_label.as_ptr()),
+            );
+        }
+        // "Preview" above everything, for a single screenshot
+        let preview_label: Vec<u16> = t!("gallery.context_menu.preview")
+            .encode_utf16()
+            .chain(std::iter::once(0))
+            .collect();
+        if valid_paths.len() == 1 {
+            let _ = InsertMenuW(
+                hmenu,
+                0,
+                MF_BYPOSITION | MF_STRING,
+                PREVIEW_COMMAND_ID,
+                PCWSTR(preview_label.as_ptr()),
+            );
         }
 
         // Get cursor position
@@ -639,6 +1099,22 @@ pub fn show_shell_context_menu(paths: &[PathBuf]) {
         // Post WM_NULL to clear menu state
         let _ = PostMessageW(hwnd, WM_NULL, None, None);
 
+        let own_command = match cmd.0 as usize {
+            DETAILS_COMMAND_ID => Some(ContextMenuCommand::Details),
+            EDIT_COMMAND_ID => Some(ContextMenuCommand::Edit),
+            SELECT_SIMILAR_COMMAND_ID => Some(ContextMenuCommand::SelectSimilar),
+            FIX_TIMESTAMPS_COMMAND_ID => Some(ContextMenuCommand::FixTimestamps),
+            COPY_INFO_COMMAND_ID => Some(ContextMenuCommand::CopyInfo),
+            PREVIEW_COMMAND_ID => Some(ContextMenuCommand::Preview),
+            ORGANIZE_COMMAND_ID => Some(ContextMenuCommand::Organize),
+            FIND_SIMILAR_COMMAND_ID => Some(ContextMenuCommand::FindSimilar),
+            _ => None,
+        };
+        if own_command.is_some() {
+            let _ = DestroyMenu(hmenu);
+            return own_command;
+        }
+
         if cmd.0 != 0 {
             let mut invoke_info = CMINVOKECOMMANDINFO {
                 cbSize: std::mem::size_of::<CMINVOKECOMMANDINFO>() as u32,
@@ -656,9 +1132,119 @@ pub fn show_shell_context_menu(paths: &[PathBuf]) {
 
         let _ = DestroyMenu(hmenu);
     }
+
+    None
 }
 
 #[cfg(not(windows))]
-pub fn show_shell_context_menu(_paths: &[PathBuf]) {
+pub fn show_shell_context_menu(_paths: &[PathBuf]) -> Option<ContextMenuCommand> {
     // Not implemented for non-Windows
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_badge_text_permutations() {
+        let name = "Screenshot 2024-01-15 103000.png";
+        assert_eq!(
+            badge_text(BadgeContent::ExtensionSize, name, "PNG", 2048, 15),
+            Some(format!("PNG | {}", format_file_size(2048)))
+        );
+        assert_eq!(
+            badge_text(BadgeContent::SizeOnly, name, "PNG", 2048, 15),
+            Some(format_file_size(2048))
+        );
+        assert_eq!(
+            badge_text(BadgeContent::FileName, name, "PNG", 2048, 15),
+            Some("Screens…000.png".to_string())
+        );
+        assert_eq!(badge_text(BadgeContent::None, name, "PNG", 2048, 15), None);
+    }
+
+    #[test]
+    fn test_group_by_folder() {
+        let root = Path::new("shots");
+        let info = |path: &str| ScreenshotInfo {
+            path: root.join(path),
+            filename: String::new(),
+            modified: SystemTime::UNIX_EPOCH,
+            captured: SystemTime::UNIX_EPOCH,
+            file_size: 0,
+            extension: "PNG".to_string(),
+        };
+        // Gallery order: groups follow it, and so do the tiles inside them
+        let screenshots = vec![
+            info("2024-05-12/c.png"),
+            info("top.png"),
+            info("2024-05-11/b.png"),
+            info("2024-05-12/a.png"),
+            info("2024/05/d.png"),
+        ];
+        let groups: Vec<(String, Vec<&str>)> = group_by_folder(&screenshots, root)
+            .into_iter()
+            .map(|(label, items)| {
+                let names = items.iter().map(|s| s.path.file_name().unwrap().to_str().unwrap()).collect();
+                (label, names)
+            })
+            .collect();
+        assert_eq!(
+            groups,
+            vec![
+                ("2024-05-12".to_string(), vec!["c.png", "a.png"]),
+                (folder_label(root, root), vec!["top.png"]),
+                ("2024-05-11".to_string(), vec!["b.png"]),
+                ("2024 / 05".to_string(), vec!["d.png"]),
+            ]
+        );
+
+        // Folder headers in every order; date headers only by date
+        assert_eq!(group(&screenshots, None, SortMode::Name, GalleryGrouping::Folder, root).len(), 4);
+        assert_eq!(group(&screenshots, None, SortMode::Name, GalleryGrouping::Date, root)[0].0, None);
+    }
+
+    #[test]
+    fn test_search_results_in_relevance_order() {
+        let root = Path::new("shots");
+        let info = |path: &str| ScreenshotInfo {
+            path: root.join(path),
+            filename: String::new(),
+            modified: SystemTime::UNIX_EPOCH,
+            captured: SystemTime::UNIX_EPOCH,
+            file_size: 0,
+            extension: "PNG".to_string(),
+        };
+        let screenshots = vec![info("2024-05-12/c.png"), info("top.png"), info("2024-05-11/b.png")];
+        // Not loaded (or filtered out): skipped
+        let ranking = vec![root.join("2024-05-11/b.png"), root.join("gone.png"), root.join("2024-05-12/c.png")];
+
+        // One group without a header, whatever the grouping
+        for grouping in [GalleryGrouping::Date, GalleryGrouping::Folder] {
+            let groups = display_groups(&screenshots, Some(ranking.as_slice()), SortMode::Newest, grouping, root);
+            assert_eq!(groups, vec![vec![root.join("2024-05-11/b.png"), root.join("2024-05-12/c.png")]]);
+            assert_eq!(group(&screenshots, Some(ranking.as_slice()), SortMode::Newest, grouping, root)[0].0, None);
+        }
+        // Nothing close enough: an empty group, not the whole gallery
+        let groups = display_groups(&screenshots, Some(&[]), SortMode::Newest, GalleryGrouping::Date, root);
+        assert_eq!(groups, vec![Vec::<PathBuf>::new()]);
+    }
+
+    #[test]
+    fn test_orientation() {
+        assert_eq!(Orientation::of(1920, 1080), Orientation::Landscape);
+        assert_eq!(Orientation::of(1170, 2532), Orientation::Portrait);
+        assert_eq!(Orientation::of(512, 512), Orientation::Square);
+        assert_eq!(Orientation::of(1000, 990), Orientation::Square);
+        assert_eq!(Orientation::of(1000, 970), Orientation::Landscape);
+        assert_eq!(Orientation::of(0, 0), Orientation::Square);
+    }
+
+    #[test]
+    fn test_badge_max_chars() {
+        assert_eq!(badge_max_chars(150), 17);
+        assert_eq!(badge_max_chars(80), 7);
+        assert_eq!(badge_max_chars(10), 4);
+    }
 }
@@ -0,0 +1,185 @@
+//! Arrow-key movement through the gallery grid
+//!
+//! Tiles wrap into rows inside each date group, so a row never spans two
+//! groups. Positions are in display order; the group sizes and the column
+//! count are all it takes to know where rows break and how far down a tile
+//! sits.
+
+/// Gap between tiles and between rows (the grid's `gap`)
+pub const TILE_GAP: f32 = 8.0;
+
+/// Horizontal padding of the gallery content on each side (`px_4`)
+const CONTENT_PADDING: f32 = 16.0;
+
+/// Height of a date header: `pt_4` + `pb_2` + one line of `text_sm`
+const GROUP_HEADER_HEIGHT: f32 = 44.0;
+
+/// Where a key moves the focus
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GridMove {
+    Left,
+    Right,
+    Up,
+    Down,
+    First,
+    Last,
+}
+
+impl GridMove {
+    /// The move for a GPUI key name, if it is one
+    pub fn from_key(key: &str) -> Option<Self> {
+        match key {
+            "left" => Some(GridMove::Left),
+            "right" => Some(GridMove::Right),
+            "up" => Some(GridMove::Up),
+            "down" => Some(GridMove::Down),
+            "home" => Some(GridMove::First),
+            "end" => Some(GridMove::Last),
+            _ => None,
+        }
+    }
+}
+
+/// How many tiles fit on a row of a gallery `width` pixels wide
+pub fn columns_for_width(width: f32, tile_size: u32) -> usize {
+    let usable = width - 2.0 * CONTENT_PADDING;
+    (((usable + TILE_GAP) / (tile_size as f32 + TILE_GAP)).floor() as usize).max(1)
+}
+
+/// Every row as (first position, length)
+fn rows(group_sizes: &[usize], columns: usize) -> Vec<(usize, usize)> {
+    let mut rows = Vec::new();
+    let mut start = 0;
+    for &size in group_sizes {
+        let mut offset = 0;
+        while offset < size {
+            let len = columns.min(size - offset);
+            rows.push((start + offset, len));
+            offset += len;
+        }
+        start += size;
+    }
+    rows
+}
+
+/// Position after moving from `from`. With nothing focused yet, any move
+/// starts at the first tile (End at the last). `None` if the grid is empty.
+pub fn step(
+    group_sizes: &[usize],
+    columns: usize,
+    from: Option<usize>,
+    direction: GridMove,
+) -> Option<usize> {
+    let total: usize = group_sizes.iter().sum();
+    if total == 0 {
+        return None;
+    }
+    let last = total - 1;
+    let Some(from) = from.filter(|&f| f < total) else {
+        return Some(if direction == GridMove::Last { last } else { 0 });
+    };
+
+    Some(match direction {
+        GridMove::Left => from.saturating_sub(1),
+        GridMove::Right => (from + 1).min(last),
+        GridMove::First => 0,
+        GridMove::Last => last,
+        GridMove::Up | GridMove::Down => {
+            let rows = rows(group_sizes, columns.max(1));
+            let row = rows
+                .iter()
+                .position(|&(start, len)| from < start + len)
+                .unwrap_or(0);
+            let column = from - rows[row].0;
+            let target = match direction {
+                GridMove::Up => row.checked_sub(1),
+                _ => Some(row + 1).filter(|&r| r < rows.len()),
+            };
+            // Shorter rows (the end of a group) take the last tile instead
+            match target {
+                Some(target) => rows[target].0 + column.min(rows[target].1 - 1),
+                None => from,
+            }
+        }
+    })
+}
+
+/// Top of the tile at `position`, in pixels from the top of the gallery
+/// content. `headers` is whether each group has a date header above it.
+pub fn tile_top(
+    group_sizes: &[usize],
+    columns: usize,
+    tile_size: u32,
+    headers: bool,
+    position: usize,
+) -> f32 {
+    let columns = columns.max(1);
+    let row_height = tile_size as f32 + TILE_GAP;
+    let mut top = 0.0;
+    let mut start = 0;
+    for &size in group_sizes {
+        if headers {
+            top += GROUP_HEADER_HEIGHT;
+        }
+        if position < start + size {
+            return top + ((position - start) / columns) as f32 * row_height;
+        }
+        let group_rows = size.div_ceil(columns);
+        top += group_rows as f32 * row_height - TILE_GAP;
+        start += size;
+    }
+    top
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_columns_for_width() {
+        // 32px padding, then 150px tiles with 8px gaps
+        assert_eq!(columns_for_width(32.0 + 150.0 * 3.0 + 8.0 * 2.0, 150), 3);
+        assert_eq!(columns_for_width(32.0 + 150.0 * 3.0 + 8.0, 150), 2);
+        assert_eq!(columns_for_width(100.0, 150), 1);
+    }
+
+    #[test]
+    fn test_step_within_and_across_groups() {
+        // Groups of 5 and 3 in 3 columns:
+        //   0 1 2
+        //   3 4
+        //   5 6 7
+        let groups = [5, 3];
+
+        assert_eq!(step(&groups, 3, None, GridMove::Down), Some(0));
+        assert_eq!(step(&groups, 3, None, GridMove::Last), Some(7));
+        assert_eq!(step(&groups, 3, Some(0), GridMove::Left), Some(0));
+        assert_eq!(step(&groups, 3, Some(2), GridMove::Right), Some(3));
+        assert_eq!(step(&groups, 3, Some(7), GridMove::Right), Some(7));
+
+        assert_eq!(step(&groups, 3, Some(1), GridMove::Down), Some(4));
+        // Column 2 doesn't exist on the short row
+        assert_eq!(step(&groups, 3, Some(2), GridMove::Down), Some(4));
+        assert_eq!(step(&groups, 3, Some(4), GridMove::Down), Some(6));
+        assert_eq!(step(&groups, 3, Some(7), GridMove::Up), Some(4));
+        assert_eq!(step(&groups, 3, Some(1), GridMove::Up), Some(1));
+        assert_eq!(step(&groups, 3, Some(6), GridMove::Down), Some(6));
+
+        assert_eq!(step(&groups, 3, Some(6), GridMove::First), Some(0));
+        assert_eq!(step(&[], 3, Some(0), GridMove::Down), None);
+    }
+
+    #[test]
+    fn test_tile_top() {
+        let groups = [5, 3];
+        let row = 150.0 + TILE_GAP;
+
+        assert_eq!(tile_top(&groups, 3, 150, false, 4), row);
+        assert_eq!(tile_top(&groups, 3, 150, true, 0), GROUP_HEADER_HEIGHT);
+        // Second group: its header follows two rows of the first
+        assert_eq!(
+            tile_top(&groups, 3, 150, true, 5),
+            GROUP_HEADER_HEIGHT * 2.0 + 2.0 * row - TILE_GAP
+        );
+    }
+}
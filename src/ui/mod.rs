@@ -1,9 +1,14 @@
 //! UI components
 
+pub mod click;
 mod gallery;
+pub mod keynav;
+pub mod modal;
+pub mod progress;
+pub mod settings_focus;
+pub mod text;
 pub mod toast;
 
-pub use gallery::gallery;
-#[cfg(windows)]
-pub use gallery::show_shell_context_menu;
+pub use gallery::{display_groups, folder_label, gallery, Orientation};
+pub use gallery::{show_shell_context_menu, ContextMenuCommand};
 pub use toast::ToastManager;
@@ -0,0 +1,301 @@
+//! Host for modal dialogs over the main window
+//!
+//! `Sukusho` keeps each dialog's state and draws its card, but stacking,
+//! focus and the keyboard go through [`ModalHost`]: Enter runs the focused
+//! button (the dialog's default one at first), Escape cancels, Tab and
+//! Shift+Tab cycle the dialog's buttons without reaching the gallery, and
+//! gallery shortcuts are off while any dialog is open. The backdrop dims
+//! the window and swallows clicks meant for what's underneath.
+
+use gpui::*;
+use gpui_component::ActiveTheme;
+
+/// Dialogs that can be open over the main window
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ModalId {
+    ShortcutHelp,
+    /// "Open the shell menu for this many files?"
+    ContextMenuConfirm,
+    /// Copy/Details/Edit for a large selection
+    ContextMenuAppMenu,
+    ReadOnlyPrompt,
+    /// Old and new modified times before "Fix Timestamp" applies them
+    FixTimestamps,
+    /// Which files had metadata stripped, which had a location, which failed
+    StripReport,
+    /// The vector DB won't open: wait, or set it aside and start over
+    IndexRecovery,
+    /// One screenshot scaled to fit the window
+    Preview,
+}
+
+impl ModalId {
+    fn element_id(self) -> &'static str {
+        match self {
+            ModalId::ShortcutHelp => "shortcut-help",
+            ModalId::ContextMenuConfirm => "context-menu-confirm",
+            ModalId::ContextMenuAppMenu => "context-menu-app-menu",
+            ModalId::ReadOnlyPrompt => "read-only-prompt",
+            ModalId::FixTimestamps => "fix-timestamps",
+            ModalId::StripReport => "strip-report",
+            ModalId::IndexRecovery => "index-recovery",
+            ModalId::Preview => "preview",
+        }
+    }
+
+    /// Whether clicking the backdrop cancels the dialog. Prompts that hold up
+    /// a file operation need an explicit answer.
+    pub fn cancel_on_backdrop(self) -> bool {
+        !matches!(self, ModalId::ReadOnlyPrompt)
+    }
+}
+
+/// An open dialog, as described by its owner on every render
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ModalSpec {
+    pub id: ModalId,
+    /// Buttons Tab cycles through, in order
+    pub actions: usize,
+    /// Button Enter runs until Tab moves focus
+    pub default_action: usize,
+}
+
+/// What a key press in the top dialog asks for
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ModalKey {
+    /// Run this button
+    Confirm(usize),
+    Cancel,
+    /// Focus moved; repaint
+    Moved,
+    /// Not a dialog key (still not passed on to the gallery)
+    Ignored,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct ActiveModal {
+    spec: ModalSpec,
+    focused: usize,
+}
+
+/// Open dialogs, bottom to top
+#[derive(Debug, Default)]
+pub struct ModalStack {
+    stack: Vec<ActiveModal>,
+}
+
+impl ModalStack {
+    /// Match the stack to the dialogs open now. Dialogs still open keep their
+    /// place and focus (unless their buttons changed); new ones go on top.
+    pub fn sync(&mut self, open: &[ModalSpec]) {
+        self.stack
+            .retain(|active| open.iter().any(|spec| spec.id == active.spec.id));
+        for spec in open {
+            match self.stack.iter_mut().find(|active| active.spec.id == spec.id) {
+                Some(active) if active.spec == *spec => {}
+                Some(active) => {
+                    active.spec = *spec;
+                    active.focused = spec.default_action;
+                }
+                None => self.stack.push(ActiveModal {
+                    spec: *spec,
+                    focused: spec.default_action,
+                }),
+            }
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.stack.is_empty()
+    }
+
+    /// Dialog receiving keys
+    pub fn top(&self) -> Option<ModalId> {
+        self.stack.last().map(|active| active.spec.id)
+    }
+
+    /// Open dialogs, bottom to top
+    pub fn ids(&self) -> impl Iterator<Item = ModalId> + '_ {
+        self.stack.iter().map(|active| active.spec.id)
+    }
+
+    /// Whether `action` of dialog `id` has keyboard focus
+    pub fn is_focused(&self, id: ModalId, action: usize) -> bool {
+        self.stack
+            .iter()
+            .any(|active| active.spec.id == id && active.focused == action)
+    }
+
+    /// Focus back on the default button (after it ran, for queued prompts)
+    pub fn reset_focus(&mut self, id: ModalId) {
+        if let Some(active) = self.stack.iter_mut().find(|active| active.spec.id == id) {
+            active.focused = active.spec.default_action;
+        }
+    }
+
+    /// Handle a key (gpui key name) for the top dialog
+    pub fn handle_key(&mut self, key: &str, shift: bool) -> ModalKey {
+        let Some(active) = self.stack.last_mut() else {
+            return ModalKey::Ignored;
+        };
+        let actions = active.spec.actions;
+        match key {
+            "escape" => ModalKey::Cancel,
+            // Nothing to confirm in an informational dialog: Enter closes it
+            "enter" if actions == 0 => ModalKey::Cancel,
+            "enter" => ModalKey::Confirm(active.focused),
+            "tab" if actions > 0 => {
+                active.focused = if shift {
+                    (active.focused + actions - 1) % actions
+                } else {
+                    (active.focused + 1) % actions
+                };
+                ModalKey::Moved
+            }
+            _ => ModalKey::Ignored,
+        }
+    }
+}
+
+/// [`ModalStack`] plus the focus handling that needs a window
+pub struct ModalHost {
+    stack: ModalStack,
+    focus_handle: FocusHandle,
+    /// Focus from before the first dialog opened, restored when the last closes
+    restore_focus: Option<FocusHandle>,
+}
+
+impl ModalHost {
+    pub fn new(cx: &mut App) -> Self {
+        Self {
+            stack: ModalStack::default(),
+            focus_handle: cx.focus_handle(),
+            restore_focus: None,
+        }
+    }
+
+    /// Sync with the dialogs open now (see [`ModalStack::sync`]), moving
+    /// focus into the dialogs when the first opens and back when the last closes
+    pub fn sync(&mut self, open: &[ModalSpec], window: &mut Window, cx: &App) {
+        let was_empty = self.stack.is_empty();
+        self.stack.sync(open);
+        match (was_empty, self.stack.is_empty()) {
+            (true, false) => {
+                self.restore_focus = window.focused(cx);
+                window.focus(&self.focus_handle);
+            }
+            (false, true) => {
+                if let Some(handle) = self.restore_focus.take() {
+                    window.focus(&handle);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    pub fn stack(&self) -> &ModalStack {
+        &self.stack
+    }
+
+    pub fn stack_mut(&mut self) -> &mut ModalStack {
+        &mut self.stack
+    }
+
+    /// Dimmed full-window backdrop for dialog `id`; the caller adds the card
+    /// and, for [`ModalId::cancel_on_backdrop`] dialogs, a click handler
+    pub fn backdrop(&self, id: ModalId) -> Stateful<Div> {
+        let backdrop = div()
+            .id(id.element_id())
+            .absolute()
+            .top_0()
+            .left_0()
+            .size_full()
+            .flex()
+            .items_center()
+            .justify_center()
+            .bg(gpui::rgba(0x00000080))
+            // Nothing underneath gets clicks or scrolls
+            .occlude();
+        if self.stack.top() == Some(id) {
+            backdrop.track_focus(&self.focus_handle)
+        } else {
+            backdrop
+        }
+    }
+}
+
+/// Ring around a dialog button or settings control that has keyboard focus
+pub fn focus_ring(focused: bool, button: impl IntoElement, cx: &App) -> Div {
+    div()
+        .rounded(px(6.0))
+        .border_2()
+        .border_color(ring_color(focused, cx))
+        .child(button)
+}
+
+/// Border color for an element that may have keyboard focus (the border
+/// stays, transparent, so focus doesn't shift the layout)
+pub fn ring_color(focused: bool, cx: &App) -> Hsla {
+    if focused {
+        cx.theme().ring
+    } else {
+        gpui::transparent_black()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn spec(id: ModalId, actions: usize, default_action: usize) -> ModalSpec {
+        ModalSpec {
+            id,
+            actions,
+            default_action,
+        }
+    }
+
+    #[test]
+    fn test_stack_order() {
+        let mut stack = ModalStack::default();
+        stack.sync(&[spec(ModalId::ReadOnlyPrompt, 3, 2)]);
+        stack.sync(&[
+            spec(ModalId::ShortcutHelp, 0, 0),
+            spec(ModalId::ReadOnlyPrompt, 3, 2),
+        ]);
+        // Newly opened dialogs go on top regardless of the order given
+        assert_eq!(stack.top(), Some(ModalId::ShortcutHelp));
+
+        stack.sync(&[spec(ModalId::ReadOnlyPrompt, 3, 2)]);
+        assert_eq!(stack.top(), Some(ModalId::ReadOnlyPrompt));
+        stack.sync(&[]);
+        assert!(stack.is_empty());
+    }
+
+    #[test]
+    fn test_keys() {
+        let mut stack = ModalStack::default();
+        assert_eq!(stack.handle_key("enter", false), ModalKey::Ignored);
+
+        stack.sync(&[spec(ModalId::ContextMenuConfirm, 3, 2)]);
+        assert_eq!(stack.handle_key("enter", false), ModalKey::Confirm(2));
+        assert_eq!(stack.handle_key("escape", false), ModalKey::Cancel);
+
+        // Tab wraps around within the dialog
+        assert_eq!(stack.handle_key("tab", false), ModalKey::Moved);
+        assert!(stack.is_focused(ModalId::ContextMenuConfirm, 0));
+        assert_eq!(stack.handle_key("tab", true), ModalKey::Moved);
+        assert_eq!(stack.handle_key("enter", false), ModalKey::Confirm(2));
+        assert_eq!(stack.handle_key("a", false), ModalKey::Ignored);
+
+        // Another dialog in its place starts on its own default
+        stack.handle_key("tab", false);
+        stack.sync(&[spec(ModalId::ContextMenuAppMenu, 3, 0)]);
+        assert_eq!(stack.handle_key("enter", false), ModalKey::Confirm(0));
+
+        // Informational dialogs close on Enter
+        stack.sync(&[spec(ModalId::ShortcutHelp, 0, 0)]);
+        assert_eq!(stack.handle_key("enter", false), ModalKey::Cancel);
+        assert_eq!(stack.handle_key("tab", false), ModalKey::Ignored);
+    }
+}
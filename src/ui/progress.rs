@@ -0,0 +1,166 @@
+//! The file shown under the organize, convert and index progress bars
+//!
+//! Hovering shows the full path, clicking reveals the file in Explorer. A file
+//! that failed is shown in red with its error for [`FAILED_HOLD`], so it can
+//! be read before the next file replaces it.
+
+use gpui::*;
+use gpui_component::tooltip::Tooltip;
+use gpui_component::ActiveTheme;
+use log::warn;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use crate::paths;
+use crate::ui::text;
+
+/// How long a failed file stays shown before later files replace it
+pub const FAILED_HOLD: Duration = Duration::from_secs(3);
+
+/// Columns of file name that fit the 200px label (text_xs)
+const NAME_WIDTH: usize = 30;
+
+/// File a batch operation is on
+#[derive(Debug, Clone, Default)]
+pub struct CurrentFile {
+    path: Option<PathBuf>,
+    /// Error for `path`, and when it was reported
+    error: Option<(String, Instant)>,
+}
+
+impl CurrentFile {
+    /// Batch started or finished
+    pub fn clear(&mut self) {
+        *self = Self::default();
+    }
+
+    /// Moved on to `path`; ignored while a failure is still being held
+    pub fn advance(&mut self, path: PathBuf) {
+        if self
+            .error
+            .as_ref()
+            .is_some_and(|(_, at)| at.elapsed() < FAILED_HOLD)
+        {
+            return;
+        }
+        self.path = Some(path);
+        self.error = None;
+    }
+
+    /// `path` failed with `error`
+    pub fn fail(&mut self, path: PathBuf, error: String) {
+        self.path = Some(path);
+        self.error = Some((error, Instant::now()));
+    }
+
+    pub fn path(&self) -> Option<&Path> {
+        self.path.as_deref()
+    }
+
+    pub fn error(&self) -> Option<&str> {
+        self.error.as_ref().map(|(error, _)| error.as_str())
+    }
+}
+
+/// Label for the current file, or `placeholder` before the first one.
+/// Shortened in the middle to fit 200px; the tooltip has the full path and
+/// any error.
+pub fn render_current_file(
+    id: impl Into<ElementId>,
+    file: &CurrentFile,
+    placeholder: String,
+    cx: &App,
+) -> Stateful<Div> {
+    let label = div()
+        .id(id)
+        .text_xs()
+        .max_w(px(200.0))
+        .overflow_x_hidden()
+        .whitespace_nowrap()
+        .text_color(if file.error().is_some() {
+            cx.theme().danger
+        } else {
+            cx.theme().muted_foreground
+        });
+
+    let Some(path) = file.path().map(Path::to_path_buf) else {
+        return label.child(placeholder);
+    };
+
+    let full_path = paths::display_path(&path).to_string_lossy().to_string();
+    let name = path
+        .file_name()
+        .map(|n| text::middle_ellipsize(&n.to_string_lossy(), NAME_WIDTH))
+        .unwrap_or_else(|| full_path.clone());
+    let tooltip = match file.error() {
+        Some(error) => format!("{}\n{}", full_path, error),
+        None => full_path,
+    };
+
+    label
+        .cursor_pointer()
+        .hover(|s| s.underline())
+        .tooltip(move |window, cx| Tooltip::new(tooltip.clone()).build(window, cx))
+        .on_click(move |_, _, _| reveal_in_explorer(&path))
+        .child(name)
+}
+
+/// Open the file's folder with the file selected (just the folder if it has
+/// since been moved or deleted)
+pub fn reveal_in_explorer(path: &Path) {
+    let result = if paths::long_path(path).exists() {
+        select_in_folder(path)
+    } else {
+        match path.parent() {
+            Some(dir) => open::that(dir),
+            None => return,
+        }
+    };
+    if let Err(e) = result {
+        warn!("Failed to reveal {:?}: {}", path, e);
+    }
+}
+
+#[cfg(windows)]
+fn select_in_folder(path: &Path) -> std::io::Result<()> {
+    use std::os::windows::process::CommandExt;
+    // Explorer wants the path quoted inside a single argument
+    std::process::Command::new("explorer")
+        .raw_arg(format!("/select,\"{}\"", paths::display_path(path).display()))
+        .spawn()
+        .map(|_| ())
+}
+
+#[cfg(not(windows))]
+fn select_in_folder(path: &Path) -> std::io::Result<()> {
+    match path.parent() {
+        Some(dir) => open::that(dir),
+        None => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_failed_file_is_held() {
+        let mut file = CurrentFile::default();
+        file.advance("a.png".into());
+        assert_eq!(file.path(), Some(Path::new("a.png")));
+
+        file.fail("b.png".into(), "locked".to_string());
+        file.advance("c.png".into());
+        assert_eq!(file.path(), Some(Path::new("b.png")));
+        assert_eq!(file.error(), Some("locked"));
+
+        // Once the hold is over the next file replaces it
+        file.error.as_mut().unwrap().1 = Instant::now() - FAILED_HOLD;
+        file.advance("d.png".into());
+        assert_eq!(file.path(), Some(Path::new("d.png")));
+        assert_eq!(file.error(), None);
+
+        file.clear();
+        assert_eq!(file.path(), None);
+    }
+}
@@ -0,0 +1,205 @@
+//! Keyboard focus for the settings pages
+//!
+//! Each render lists the controls it drew, in the order they appear (sidebar
+//! first, then the page top to bottom); Tab and Shift+Tab walk that list.
+//! Focus is kept by element id rather than position, so a control appearing
+//! or disappearing above the focused one doesn't move the focus.
+
+use gpui::ElementId;
+
+/// How a control reacts to keys once focused
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ControlKind {
+    /// Switch: Space or Enter flips it
+    Toggle,
+    /// Button or sidebar tab: Space or Enter presses it
+    Button,
+    /// -/+ pair: arrow keys step the value
+    Stepper,
+}
+
+/// Input for the focused control
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ControlInput {
+    Activate,
+    /// -1 (Left/Down) or +1 (Right/Up)
+    Step(i32),
+}
+
+/// A control drawn this frame; `action` is whatever runs it
+pub struct Control<T> {
+    pub id: ElementId,
+    pub kind: ControlKind,
+    pub action: T,
+}
+
+/// Result of a key press on a settings page
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SettingsKey {
+    Ignored,
+    Moved,
+    /// Run the control at this index with this input
+    Input(usize, ControlInput),
+}
+
+#[derive(Debug, Default)]
+pub struct SettingsFocus {
+    focused: Option<ElementId>,
+}
+
+impl SettingsFocus {
+    pub fn is_focused(&self, id: &ElementId) -> bool {
+        self.focused.as_ref() == Some(id)
+    }
+
+    pub fn clear(&mut self) {
+        self.focused = None;
+    }
+
+    /// Handle a key (gpui key name) for `controls`, in tab order
+    pub fn handle_key<T>(
+        &mut self,
+        controls: &[Control<T>],
+        key: &str,
+        shift: bool,
+    ) -> SettingsKey {
+        if controls.is_empty() {
+            return SettingsKey::Ignored;
+        }
+        let current = self
+            .focused
+            .as_ref()
+            .and_then(|id| controls.iter().position(|control| control.id == *id));
+        if key == "tab" {
+            let next = match (current, shift) {
+                (Some(index), false) => (index + 1) % controls.len(),
+                (Some(index), true) => (index + controls.len() - 1) % controls.len(),
+                (None, false) => 0,
+                (None, true) => controls.len() - 1,
+            };
+            self.focused = Some(controls[next].id.clone());
+            return SettingsKey::Moved;
+        }
+        let Some(index) = current else {
+            return SettingsKey::Ignored;
+        };
+        let input = match (controls[index].kind, key) {
+            (ControlKind::Toggle | ControlKind::Button, "space" | "enter") => {
+                ControlInput::Activate
+            }
+            (ControlKind::Stepper, "left" | "down") => ControlInput::Step(-1),
+            (ControlKind::Stepper, "right" | "up") => ControlInput::Step(1),
+            _ => return SettingsKey::Ignored,
+        };
+        SettingsKey::Input(index, input)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn controls(kinds: &[(&'static str, ControlKind)]) -> Vec<Control<()>> {
+        kinds
+            .iter()
+            .map(|&(id, kind)| Control {
+                id: id.into(),
+                kind,
+                action: (),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_tab_order_wraps() {
+        let page = controls(&[
+            ("a", ControlKind::Toggle),
+            ("b", ControlKind::Button),
+            ("c", ControlKind::Stepper),
+        ]);
+        let mut focus = SettingsFocus::default();
+        assert_eq!(
+            focus.handle_key(&page, "space", false),
+            SettingsKey::Ignored
+        );
+
+        assert_eq!(focus.handle_key(&page, "tab", false), SettingsKey::Moved);
+        assert!(focus.is_focused(&"a".into()));
+        focus.handle_key(&page, "tab", true);
+        assert!(focus.is_focused(&"c".into()));
+        focus.handle_key(&page, "tab", false);
+        assert!(focus.is_focused(&"a".into()));
+
+        let mut focus = SettingsFocus::default();
+        focus.handle_key(&page, "tab", true);
+        assert!(focus.is_focused(&"c".into()));
+    }
+
+    #[test]
+    fn test_focus_follows_id() {
+        let mut focus = SettingsFocus::default();
+        let page = controls(&[("a", ControlKind::Toggle), ("b", ControlKind::Button)]);
+        focus.handle_key(&page, "tab", false);
+        focus.handle_key(&page, "tab", false);
+        assert!(focus.is_focused(&"b".into()));
+
+        // A control appeared above: still on "b", Tab goes on from there
+        let page = controls(&[
+            ("new", ControlKind::Button),
+            ("a", ControlKind::Toggle),
+            ("b", ControlKind::Button),
+            ("c", ControlKind::Button),
+        ]);
+        assert_eq!(
+            focus.handle_key(&page, "enter", false),
+            SettingsKey::Input(2, ControlInput::Activate)
+        );
+        focus.handle_key(&page, "tab", false);
+        assert!(focus.is_focused(&"c".into()));
+
+        // The focused control went away: Tab starts over
+        let page = controls(&[("a", ControlKind::Toggle)]);
+        assert_eq!(
+            focus.handle_key(&page, "space", false),
+            SettingsKey::Ignored
+        );
+        focus.handle_key(&page, "tab", false);
+        assert!(focus.is_focused(&"a".into()));
+    }
+
+    #[test]
+    fn test_keys_by_kind() {
+        let page = controls(&[
+            ("toggle", ControlKind::Toggle),
+            ("button", ControlKind::Button),
+            ("stepper", ControlKind::Stepper),
+        ]);
+        let mut focus = SettingsFocus::default();
+        focus.handle_key(&page, "tab", false);
+        assert_eq!(
+            focus.handle_key(&page, "space", false),
+            SettingsKey::Input(0, ControlInput::Activate)
+        );
+        assert_eq!(focus.handle_key(&page, "left", false), SettingsKey::Ignored);
+
+        focus.handle_key(&page, "tab", false);
+        assert_eq!(
+            focus.handle_key(&page, "enter", false),
+            SettingsKey::Input(1, ControlInput::Activate)
+        );
+
+        focus.handle_key(&page, "tab", false);
+        assert_eq!(
+            focus.handle_key(&page, "space", false),
+            SettingsKey::Ignored
+        );
+        assert_eq!(
+            focus.handle_key(&page, "left", false),
+            SettingsKey::Input(2, ControlInput::Step(-1))
+        );
+        assert_eq!(
+            focus.handle_key(&page, "up", false),
+            SettingsKey::Input(2, ControlInput::Step(1))
+        );
+    }
+}
@@ -0,0 +1,138 @@
+//! Shortening file names for narrow places
+//!
+//! Names are cut between grapheme clusters, so a Hangul syllable, an emoji
+//! sequence or a letter with combining marks is never split into pieces the
+//! font can't draw. Widths are estimated per cluster: CJK, Hangul, fullwidth
+//! forms and emoji take two columns, everything else one.
+
+use unicode_segmentation::UnicodeSegmentation;
+
+const ELLIPSIS: &str = "…";
+
+/// Estimated columns a string takes
+pub fn display_width(text: &str) -> usize {
+    text.graphemes(true).map(grapheme_width).sum()
+}
+
+fn grapheme_width(grapheme: &str) -> usize {
+    match grapheme.chars().next() {
+        Some(c) if is_wide(c) => 2,
+        Some(_) => 1,
+        None => 0,
+    }
+}
+
+/// Characters drawn about twice as wide as Latin letters
+fn is_wide(c: char) -> bool {
+    matches!(
+        c as u32,
+        0x1100..=0x115F       // Hangul Jamo
+            | 0x2E80..=0x303E // CJK radicals, punctuation
+            | 0x3041..=0x33FF // Kana, CJK compatibility
+            | 0x3400..=0x4DBF // CJK extension A
+            | 0x4E00..=0x9FFF // CJK unified ideographs
+            | 0xA000..=0xA4CF // Yi
+            | 0xAC00..=0xD7A3 // Hangul syllables
+            | 0xF900..=0xFAFF // CJK compatibility ideographs
+            | 0xFE30..=0xFE4F // CJK compatibility forms
+            | 0xFF00..=0xFF60 // Fullwidth forms
+            | 0xFFE0..=0xFFE6
+            | 0x1F1E6..=0x1F1FF // Regional indicators (flags)
+            | 0x1F300..=0x1F64F // Symbols and emoticons
+            | 0x1F680..=0x1F6FF // Transport and map symbols
+            | 0x1F900..=0x1F9FF // Supplemental symbols
+            | 0x20000..=0x3FFFD // CJK extensions B and later
+    )
+}
+
+/// Shorten `name` to about `max_width` columns by replacing its middle with
+/// an ellipsis, keeping the start and the extension-bearing end
+pub fn middle_ellipsize(name: &str, max_width: usize) -> String {
+    if display_width(name) <= max_width {
+        return name.to_string();
+    }
+    if max_width <= 1 {
+        return ELLIPSIS.to_string();
+    }
+
+    let graphemes: Vec<&str> = name.graphemes(true).collect();
+    let budget = max_width - 1;
+
+    let mut head_width = 0;
+    let head = graphemes
+        .iter()
+        .take_while(|g| {
+            head_width += grapheme_width(g);
+            head_width <= budget - budget / 2
+        })
+        .count();
+    let head_width: usize = graphemes[..head].iter().map(|g| grapheme_width(g)).sum();
+
+    // Whatever the head couldn't use (a wide character that didn't fit) goes to the tail
+    let mut tail_width = 0;
+    let tail = graphemes[head..]
+        .iter()
+        .rev()
+        .take_while(|g| {
+            tail_width += grapheme_width(g);
+            tail_width <= budget - head_width
+        })
+        .count();
+
+    let mut shortened = graphemes[..head].concat();
+    shortened.push_str(ELLIPSIS);
+    shortened.push_str(&graphemes[graphemes.len() - tail..].concat());
+    shortened
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_latin() {
+        assert_eq!(middle_ellipsize("short.png", 20), "short.png");
+        assert_eq!(middle_ellipsize("Screenshot 2024-01-15 103000.png", 15), "Screens…000.png");
+        assert_eq!(middle_ellipsize("abcdef", 5), "ab…ef");
+        assert_eq!(middle_ellipsize("abc", 1), "…");
+    }
+
+    #[test]
+    fn test_cjk_counts_double() {
+        assert_eq!(display_width("스크린샷.png"), 12);
+        assert_eq!(middle_ellipsize("スクリーンショット一覧.png", 8), "スク…png");
+        assert_eq!(middle_ellipsize("스크린샷 2024-01-15 103000.png", 16), "스크린샷…000.png");
+        // A wide character that doesn't fit the head leaves room for the tail
+        assert_eq!(middle_ellipsize("가나다라마바사.png", 7), "가….png");
+        for name in ["スクリーンショット一覧.png", "스크린샷 2024-01-15 103000.png"] {
+            for width in 2..30 {
+                assert!(display_width(&middle_ellipsize(name, width)) <= width, "{name} at {width}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_emoji_sequences_stay_whole() {
+        // Family (joined with ZWJ) and a flag: each one cluster, two columns
+        let name = "👨‍👩‍👧 trip 🇰🇷 day.png";
+        assert_eq!(display_width("👨‍👩‍👧"), 2);
+        assert_eq!(middle_ellipsize(name, 10), "👨‍👩‍👧 tr….png");
+        assert_eq!(middle_ellipsize(name, 13), "👨‍👩‍👧 tri…ay.png");
+        // Never half a flag
+        assert_eq!(middle_ellipsize("🇰🇷🇯🇵🇺🇸🇫🇷.png", 9), "🇰🇷🇯🇵….png");
+    }
+
+    #[test]
+    fn test_combining_marks_stay_attached() {
+        // "é" as e + U+0301, and Hangul written as conjoining jamo
+        let name = "cafe\u{301}-cafe\u{301}-cafe\u{301}.png";
+        assert_eq!(display_width(name), 18);
+        let short = middle_ellipsize(name, 11);
+        assert_eq!(short, "cafe\u{301}-…e\u{301}.png");
+        assert!(!short.contains("…\u{301}"));
+
+        let jamo = "\u{1112}\u{1161}\u{11AB}\u{1100}\u{1173}\u{11AF}.png";
+        assert_eq!(display_width(jamo), 8);
+        assert_eq!(middle_ellipsize(jamo, 7), "\u{1112}\u{1161}\u{11AB}….png");
+    }
+}
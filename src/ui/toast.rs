@@ -3,12 +3,17 @@
 use gpui::*;
 use std::time::{Duration, Instant};
 
+/// How long a toast takes to fade out at the end of its duration
+pub const TOAST_FADE: Duration = Duration::from_millis(300);
+
 #[derive(Clone)]
 pub struct Toast {
     pub id: usize,
     pub message: String,
     pub created_at: Instant,
     pub duration: Duration,
+    /// [`TOAST_FADE`], or zero when animations are off
+    pub fade: Duration,
 }
 
 impl Toast {
@@ -18,12 +23,34 @@ impl Toast {
             message,
             created_at: Instant::now(),
             duration: Duration::from_secs(3),
+            fade: if crate::motion::animations_enabled() {
+                TOAST_FADE
+            } else {
+                Duration::ZERO
+            },
         }
     }
 
     pub fn is_expired(&self) -> bool {
         self.created_at.elapsed() > self.duration
     }
+
+    /// In the last `fade` of its duration
+    pub fn is_fading(&self) -> bool {
+        !self.fade.is_zero() && self.created_at.elapsed() >= self.duration.saturating_sub(self.fade)
+    }
+
+    /// Time until the toast next changes (starts fading, or expires)
+    fn next_change(&self) -> Duration {
+        let elapsed = self.created_at.elapsed();
+        let fade_start = self.duration.saturating_sub(self.fade);
+        if elapsed < fade_start {
+            fade_start - elapsed
+        } else {
+            // Just past the end, so `is_expired` holds when it fires
+            (self.duration + Duration::from_millis(1)).saturating_sub(elapsed)
+        }
+    }
 }
 
 pub struct ToastManager {
@@ -55,6 +82,13 @@ impl ToastManager {
         self.toasts.retain(|t| !t.is_expired());
     }
 
+    /// When the window next needs a repaint for toasts (a fade starting or a
+    /// toast expiring), or `None` when there are none. Lets the owner drive
+    /// expiry with a timer instead of waiting for some other repaint.
+    pub fn next_tick(&self) -> Option<Duration> {
+        self.toasts.iter().map(Toast::next_change).min()
+    }
+
     pub fn render(&self) -> impl IntoElement {
         let toasts = self.toasts.clone();
 
@@ -74,10 +108,12 @@ impl ToastManager {
     }
 }
 
-fn render_toast(toast: Toast) -> impl IntoElement {
+fn render_toast(toast: Toast) -> AnyElement {
     let toast_id = toast.id;
+    let fading = toast.is_fading();
+    let fade = toast.fade;
 
-    div()
+    let toast = div()
         .id(("toast", toast_id))
         .flex()
         .flex_row()
@@ -111,5 +147,67 @@ fn render_toast(toast: Toast) -> impl IntoElement {
                     s.bg(gpui::rgba(0xFFFFFF22))
                 })
                 .child("✕")
-        )
+        );
+
+    // The animation starts the first frame the toast is rendered as fading.
+    // Without animations it never fades and is simply gone once expired.
+    if fading {
+        toast
+            .with_animation(
+                ("toast-fade", toast_id),
+                Animation::new(fade),
+                |toast, delta| toast.opacity(1.0 - delta),
+            )
+            .into_any_element()
+    } else {
+        toast.into_any_element()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn toast_aged(age: Duration) -> Toast {
+        let mut toast = Toast::new(0, "Copied".to_string());
+        toast.created_at = Instant::now() - age;
+        toast
+    }
+
+    #[test]
+    fn test_next_tick() {
+        let mut manager = ToastManager::new();
+        assert_eq!(manager.next_tick(), None);
+
+        // A fresh toast needs a repaint when its fade starts
+        manager.toasts.push(toast_aged(Duration::ZERO));
+        let tick = manager.next_tick().unwrap();
+        assert!(tick <= Duration::from_secs(3) - TOAST_FADE);
+        assert!(tick > Duration::from_secs(2));
+
+        // A fading toast needs one when it expires
+        manager.toasts = vec![toast_aged(Duration::from_secs(3) - TOAST_FADE / 2)];
+        assert!(manager.toasts[0].is_fading());
+        assert!(manager.next_tick().unwrap() <= TOAST_FADE / 2 + Duration::from_millis(1));
+
+        // Expired toasts are gone after an update
+        manager.toasts = vec![toast_aged(Duration::from_secs(4))];
+        manager.update();
+        assert_eq!(manager.next_tick(), None);
+    }
+
+    #[test]
+    fn test_without_animations() {
+        let mut manager = ToastManager::new();
+        let mut toast = toast_aged(Duration::from_secs(3) - TOAST_FADE / 2);
+        toast.fade = Duration::ZERO;
+        manager.toasts.push(toast);
+
+        // Shown in full until it expires, then removed
+        assert!(!manager.toasts[0].is_fading());
+        assert!(manager.next_tick().unwrap() <= TOAST_FADE / 2 + Duration::from_millis(1));
+        manager.toasts[0].created_at = Instant::now() - Duration::from_secs(4);
+        manager.update();
+        assert_eq!(manager.next_tick(), None);
+    }
 }
@@ -0,0 +1,185 @@
+//! Last-used tracking for screenshots
+//!
+//! Records when a screenshot was last copied, dragged out or opened in a
+//! small sidecar file next to settings.json. The cleanup page uses it to find
+//! old screenshots that were never used.
+
+use log::debug;
+use parking_lot::Mutex;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::sidecar;
+
+/// Path -> last used time (seconds since UNIX epoch), loaded lazily
+static USAGE: Mutex<Option<HashMap<PathBuf, u64>>> = Mutex::new(None);
+
+/// Bumped on every change, so callers can tell when results derived from
+/// the store are stale
+static REVISION: AtomicU64 = AtomicU64::new(0);
+
+/// Sidecar file holding [`USAGE`]
+const STORE: &str = "usage.json";
+
+fn load() -> HashMap<PathBuf, u64> {
+    sidecar::load(STORE)
+}
+
+/// How long a change waits before it's written, so a burst of copies or a
+/// batch of moves is saved once
+const SAVE_DELAY: Duration = Duration::from_secs(2);
+
+/// A write is already scheduled
+static SAVE_SCHEDULED: AtomicBool = AtomicBool::new(false);
+
+/// Write the store after [`SAVE_DELAY`], on a background thread; changes made
+/// meanwhile go out with it
+fn save_soon() {
+    if SAVE_SCHEDULED.swap(true, Ordering::SeqCst) {
+        return;
+    }
+    std::thread::spawn(|| {
+        std::thread::sleep(SAVE_DELAY);
+        flush();
+    });
+}
+
+/// Held while the store is written, so quitting waits for a write under way
+static WRITING: Mutex<()> = Mutex::new(());
+
+/// Write any scheduled change now (on quit)
+pub fn flush() {
+    let _writing = WRITING.lock();
+    if !SAVE_SCHEDULED.swap(false, Ordering::SeqCst) {
+        return;
+    }
+    let usage = USAGE.lock().clone();
+    if let Some(usage) = usage {
+        sidecar::save(STORE, &usage);
+    }
+}
+
+/// Record that the given screenshots were just used (copied, dragged out, opened)
+pub fn mark_used(paths: &[PathBuf]) {
+    if paths.is_empty() {
+        return;
+    }
+
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+
+    let mut guard = USAGE.lock();
+    let usage = guard.get_or_insert_with(load);
+    for path in paths {
+        usage.insert(path.clone(), now);
+    }
+    REVISION.fetch_add(1, Ordering::SeqCst);
+    save_soon();
+
+    debug!("Marked {} screenshots as used", paths.len());
+}
+
+/// Drop the records of screenshots that are gone for good
+pub fn forget(paths: &[PathBuf]) {
+    let mut guard = USAGE.lock();
+    let usage = guard.get_or_insert_with(load);
+    let before = usage.len();
+    for path in paths {
+        usage.remove(path);
+    }
+    if usage.len() != before {
+        REVISION.fetch_add(1, Ordering::SeqCst);
+        save_soon();
+    }
+}
+
+/// Keep a screenshot's record when it moves (organizer, conversion)
+pub fn follow_move(from: &Path, to: &Path) {
+    let mut guard = USAGE.lock();
+    let usage = guard.get_or_insert_with(load);
+    if let Some(secs) = usage.remove(from) {
+        usage.insert(to.to_path_buf(), secs);
+        REVISION.fetch_add(1, Ordering::SeqCst);
+        save_soon();
+    }
+}
+
+/// Changes whenever any screenshot's record does
+pub fn revision() -> u64 {
+    REVISION.load(Ordering::SeqCst)
+}
+
+/// Every screenshot that has been used, taken in one go for checking many
+/// files (see [`unused_candidates`])
+pub fn used_paths() -> HashSet<PathBuf> {
+    let mut guard = USAGE.lock();
+    let usage = guard.get_or_insert_with(load);
+    usage.keys().cloned().collect()
+}
+
+/// Pick screenshots that are older than `min_age` and were never used,
+/// largest first. Items are `(path, modified, file_size)`.
+pub fn unused_candidates<'a>(
+    items: impl IntoIterator<Item = (&'a PathBuf, SystemTime, u64)>,
+    min_age: Duration,
+    now: SystemTime,
+    was_used: impl Fn(&Path) -> bool,
+) -> Vec<(PathBuf, SystemTime, u64)> {
+    let cutoff = now.checked_sub(min_age).unwrap_or(UNIX_EPOCH);
+
+    let mut candidates: Vec<_> = items
+        .into_iter()
+        .filter(|(path, modified, _)| *modified < cutoff && !was_used(path))
+        .map(|(path, modified, size)| (path.clone(), modified, size))
+        .collect();
+
+    candidates.sort_by(|a, b| b.2.cmp(&a.2));
+    candidates
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DAY: u64 = 24 * 60 * 60;
+
+    #[test]
+    fn test_unused_candidates_filters_and_sorts() {
+        let now = UNIX_EPOCH + Duration::from_secs(100 * DAY);
+        let old_small = PathBuf::from("old_small.png");
+        let old_large = PathBuf::from("old_large.png");
+        let old_used = PathBuf::from("old_used.png");
+        let recent = PathBuf::from("recent.png");
+
+        let items = vec![
+            (&old_small, now - Duration::from_secs(40 * DAY), 10),
+            (&old_large, now - Duration::from_secs(60 * DAY), 500),
+            (&old_used, now - Duration::from_secs(90 * DAY), 900),
+            (&recent, now - Duration::from_secs(DAY), 1000),
+        ];
+
+        let candidates = unused_candidates(
+            items,
+            Duration::from_secs(30 * DAY),
+            now,
+            |p| p == old_used.as_path(),
+        );
+
+        let paths: Vec<_> = candidates.iter().map(|(p, _, _)| p.clone()).collect();
+        assert_eq!(paths, vec![old_large, old_small]);
+    }
+
+    #[test]
+    fn test_unused_candidates_zero_age_includes_everything_unused() {
+        let now = SystemTime::now();
+        let path = PathBuf::from("a.png");
+        let items = vec![(&path, now - Duration::from_secs(1), 1)];
+
+        let candidates = unused_candidates(items, Duration::ZERO, now, |_| false);
+        assert_eq!(candidates.len(), 1);
+    }
+}
@@ -1,23 +1,90 @@
 //! File system watcher for screenshot directory
 
 use anyhow::Result;
-use crossbeam_channel::Sender;
+use crossbeam_channel::{Receiver, RecvTimeoutError, Sender};
 use log::{debug, error, info, warn};
-use notify::RecursiveMode;
-use notify_debouncer_full::{new_debouncer, DebounceEventResult};
+use notify::event::{CreateKind, RemoveKind, RenameMode};
+use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode};
+use notify_debouncer_full::{
+    new_debouncer, DebounceEventResult, DebouncedEvent, Debouncer, RecommendedCache,
+};
 use parking_lot::Mutex;
 use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
-use std::time::Duration;
+use std::time::{Duration, Instant, SystemTime};
 
-use crate::convert;
+use crate::convert::{self, ConvertOptions};
+use crate::convert_pool::{self, Priority};
+use crate::exclude;
+use crate::folder_snapshot::FolderSnapshot;
+use crate::kept_pngs;
+use crate::naming;
 use crate::organizer;
+use crate::paths;
+use crate::pipeline::{self, Transition};
+use crate::removals::{self, RecycleBin, Removal};
 use crate::settings::Settings;
+use crate::timeline;
+use crate::app::ScreenshotInfo;
 use crate::AppMessage;
 
 /// Image extensions we care about
 const IMAGE_EXTENSIONS: &[&str] = &["png", "jpg", "jpeg", "gif", "bmp", "webp", "avif"];
 
+/// Threads converting and organizing new screenshots
+const PIPELINE_WORKERS: usize = 4;
+
+/// Screenshots per [`AppMessage::ScreenshotsScanned`] from a folder scan
+const SCAN_BATCH: usize = 500;
+
+/// How often the watcher checks whether its settings changed
+const SETTINGS_POLL: Duration = Duration::from_secs(1);
+
+/// Poll interval when the folder can't be watched natively and none is set
+const FALLBACK_POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Processing paused from the tray: events are held instead of handled
+static PAUSED: AtomicBool = AtomicBool::new(false);
+
+/// Events that arrived while paused, oldest first; handled in order by the
+/// watcher loop once processing resumes
+static HELD: Mutex<Vec<DebouncedEvent>> = Mutex::new(Vec::new());
+
+/// Pause or resume handling new files (conversion, organizing, auto-index)
+pub fn set_paused(paused: bool) {
+    PAUSED.store(paused, Ordering::SeqCst);
+}
+
+/// Whether processing is paused
+pub fn is_paused() -> bool {
+    PAUSED.load(Ordering::SeqCst)
+}
+
+/// Sent to a running watcher from the UI
+#[derive(Debug, Clone)]
+pub enum WatcherCommand {
+    /// Watch a different screenshot directory
+    ChangeDirectory(PathBuf),
+}
+
+/// A new screenshot waiting for a pipeline worker
+struct PipelineJob {
+    path: PathBuf,
+}
+
+/// How the watcher learns about changes
+enum Source {
+    /// The file system's change notifications, debounced
+    Native(Debouncer<RecommendedWatcher, RecommendedCache>),
+    /// Comparing the folder with a snapshot every `interval`
+    Polling {
+        snapshot: FolderSnapshot,
+        interval: Duration,
+        next: Instant,
+    },
+}
+
 pub struct ScreenshotWatcher {
     directory: PathBuf,
     message_tx: Sender<AppMessage>,
@@ -37,98 +104,428 @@ impl ScreenshotWatcher {
         }
     }
 
-    /// Run the watcher (blocking)
-    pub fn run(self) -> Result<()> {
+    /// Run the watcher (blocking) until `control` is dropped
+    pub fn run(mut self, control: Receiver<WatcherCommand>) -> Result<()> {
         info!("Starting file watcher for: {:?}", self.directory);
 
-        // Ensure directory exists
-        if !self.directory.exists() {
-            warn!(
-                "Screenshot directory does not exist, creating: {:?}",
-                self.directory
-            );
-            std::fs::create_dir_all(&self.directory)?;
-        }
+        Self::ensure_directory(&self.directory)?;
 
         // Scan existing files first (includes subdirectories for organized files)
         self.scan_existing_files()?;
 
-        // Create debounced watcher
+        let mut jobs = self.start_workers();
+
+        let (mut debounce_ms, mut poll_secs) = self.watch_settings();
+        let mut source = self.start_source(debounce_ms, poll_secs, &jobs);
+
+        // Keep the thread alive, rebuilding the watcher when its settings or
+        // the directory change
+        loop {
+            match control.recv_timeout(SETTINGS_POLL) {
+                Ok(WatcherCommand::ChangeDirectory(directory)) => {
+                    if directory == self.directory {
+                        debug!("Already watching {:?}", directory);
+                        continue;
+                    }
+                    if let Err(e) = Self::ensure_directory(&directory) {
+                        error!(
+                            "Can't watch {:?}, still watching {:?}: {}",
+                            directory, self.directory, e
+                        );
+                        continue;
+                    }
+                    info!(
+                        "Watcher directory changed: {:?} -> {:?}",
+                        self.directory, directory
+                    );
+                    // Stop the old watcher first so nothing from the old folder is
+                    // queued; its workers finish what they have and exit
+                    drop(source);
+                    let held = std::mem::take(&mut *HELD.lock()).len();
+                    if held > 0 {
+                        info!("Dropped {} held events from the old directory", held);
+                    }
+                    self.directory = directory;
+                    self.scan_existing_files()?;
+                    jobs = self.start_workers();
+                    source = self.start_source(debounce_ms, poll_secs, &jobs);
+                }
+                Err(RecvTimeoutError::Timeout) => {}
+                Err(RecvTimeoutError::Disconnected) => {
+                    info!("File watcher stopped");
+                    return Ok(());
+                }
+            }
+
+            self.release_held(&jobs);
+
+            let (wanted_debounce, wanted_poll) = self.watch_settings();
+            if wanted_poll != poll_secs {
+                info!("Watcher poll interval changed: {} s -> {} s", poll_secs, wanted_poll);
+                source = match source {
+                    // Keep the snapshot so nothing in between is missed
+                    Source::Polling { snapshot, .. } if wanted_poll > 0 => {
+                        Self::polling(snapshot, Duration::from_secs(wanted_poll))
+                    }
+                    old => {
+                        drop(old);
+                        self.start_source(wanted_debounce, wanted_poll, &jobs)
+                    }
+                };
+            } else if wanted_debounce != debounce_ms && matches!(source, Source::Native(_)) {
+                info!("Watcher debounce changed: {} ms -> {} ms", debounce_ms, wanted_debounce);
+                // Stop the old watcher first so no event is handled twice
+                drop(source);
+                source = self.start_source(wanted_debounce, wanted_poll, &jobs);
+            }
+            debounce_ms = wanted_debounce;
+            poll_secs = wanted_poll;
+
+            if let Source::Polling { snapshot, interval, next } = &mut source {
+                if Instant::now() >= *next {
+                    self.poll(snapshot, &jobs);
+                    *next = Instant::now() + *interval;
+                }
+            }
+        }
+    }
+
+    /// Debounce and poll interval from the settings
+    fn watch_settings(&self) -> (u64, u64) {
+        let settings = self.settings.lock();
+        (settings.watcher_debounce_ms, settings.watcher_poll_interval_secs)
+    }
+
+    /// Watch natively, or poll every `poll_secs` when set. A folder that
+    /// can't be watched natively (some network shares) is polled instead.
+    fn start_source(&self, debounce_ms: u64, poll_secs: u64, jobs: &Sender<PipelineJob>) -> Source {
+        let interval = if poll_secs > 0 {
+            Duration::from_secs(poll_secs)
+        } else {
+            match self.watch(debounce_ms, jobs) {
+                Ok(debouncer) => {
+                    info!("File watcher started successfully ({} ms debounce)", debounce_ms);
+                    return Source::Native(debouncer);
+                }
+                Err(e) => {
+                    warn!(
+                        "Can't watch {:?} for changes ({}), polling every {:?} instead",
+                        self.directory, e, FALLBACK_POLL_INTERVAL
+                    );
+                    FALLBACK_POLL_INTERVAL
+                }
+            }
+        };
+        let snapshot = FolderSnapshot::new(&self.directory, Self::has_image_extension);
+        info!(
+            "Polling {:?} every {:?} ({} files)",
+            self.directory,
+            interval,
+            snapshot.file_count()
+        );
+        Self::polling(snapshot, interval)
+    }
+
+    fn polling(snapshot: FolderSnapshot, interval: Duration) -> Source {
+        Source::Polling {
+            snapshot,
+            interval,
+            next: Instant::now() + interval,
+        }
+    }
+
+    /// Refresh the snapshot and handle what changed as if the file system had
+    /// reported it, so pausing and bulk copies work the same as when watching
+    fn poll(&self, snapshot: &mut FolderSnapshot, jobs: &Sender<PipelineJob>) {
+        let changes = snapshot.refresh();
+        if changes.is_empty() {
+            return;
+        }
+        debug!(
+            "Poll found {} new and {} removed files",
+            changes.created.len(),
+            changes.removed.len()
+        );
+        let now = Instant::now();
+        let created = changes.created.into_iter().map(|path| {
+            Event::new(EventKind::Create(CreateKind::File)).add_path(path)
+        });
+        let removed = changes.removed.into_iter().map(|path| {
+            Event::new(EventKind::Remove(RemoveKind::File)).add_path(path)
+        });
+        let events = removed
+            .chain(created)
+            .map(|event| DebouncedEvent::new(event, now))
+            .collect();
+        Self::handle_debounced_events(Ok(events), &self.message_tx, jobs, &self.directory, &self.settings);
+    }
+
+    /// Create the screenshot directory if it doesn't exist yet
+    fn ensure_directory(directory: &Path) -> Result<()> {
+        if !directory.exists() {
+            warn!("Screenshot directory does not exist, creating: {:?}", directory);
+            std::fs::create_dir_all(directory)?;
+        }
+        Ok(())
+    }
+
+    /// Watch the directory recursively (to detect deletions in subdirectories)
+    fn watch(
+        &self,
+        debounce_ms: u64,
+        jobs: &Sender<PipelineJob>,
+    ) -> Result<Debouncer<RecommendedWatcher, RecommendedCache>> {
         let tx = self.message_tx.clone();
         let base_dir = self.directory.clone();
         let settings = Arc::clone(&self.settings);
+        let jobs = jobs.clone();
         let mut debouncer = new_debouncer(
-            Duration::from_millis(200),
+            Duration::from_millis(debounce_ms),
             None,
             move |result: DebounceEventResult| {
-                Self::handle_debounced_events(result, &tx, &base_dir, &settings);
+                Self::handle_debounced_events(result, &tx, &jobs, &base_dir, &settings);
             },
         )?;
-
-        // Watch the directory recursively to detect deletions in subdirectories
         debouncer.watch(&self.directory, RecursiveMode::Recursive)?;
+        Ok(debouncer)
+    }
 
-        info!("File watcher started successfully");
-
-        // Keep the thread alive
-        loop {
-            std::thread::sleep(Duration::from_secs(60));
+    /// Start the pipeline workers; new screenshots are queued on the returned sender
+    fn start_workers(&self) -> Sender<PipelineJob> {
+        let (jobs_tx, jobs_rx) = crossbeam_channel::unbounded::<PipelineJob>();
+        for i in 0..PIPELINE_WORKERS {
+            let jobs_rx = jobs_rx.clone();
+            let tx = self.message_tx.clone();
+            let base_dir = self.directory.clone();
+            let settings = Arc::clone(&self.settings);
+            std::thread::Builder::new()
+                .name(format!("pipeline-{}", i))
+                .spawn(move || {
+                    for job in jobs_rx {
+                        Self::run_pipeline(job.path, &tx, &base_dir, &settings);
+                    }
+                })
+                .expect("failed to spawn pipeline worker");
         }
+        jobs_tx
     }
 
     /// Scan existing files in the directory (recursive to include organized subdirectories)
     fn scan_existing_files(&self) -> Result<()> {
         info!("Scanning existing screenshots...");
-        let mut count = 0;
-        let mut files = Vec::new();
+        let files = Self::scan_images(&self.directory, &self.message_tx, &self.settings);
+
+        // Seed the tray's Recent list, oldest first so the newest ends up on top
+        for path in files.iter().take(pipeline::RECENT_LIMIT).rev() {
+            pipeline::report(
+                Transition::Finished {
+                    from: path.clone(),
+                    to: path.clone(),
+                },
+                &self.message_tx,
+            );
+        }
+
+        // Not auto-indexed: that's what manual indexing is for
+        let count = Self::send_scanned(files, &self.message_tx, &self.settings);
+        info!("Found {} existing screenshots", count);
+        Ok(())
+    }
+
+    /// Hand scanned files to the UI in batches, so it sorts once per batch
+    /// instead of inserting them one message at a time. Their details are
+    /// read here, off the UI thread. With auto-convert on, PNGs are converted
+    /// first and show up when done; ones under the minimum size are sent as
+    /// they are. Returns how many were found.
+    fn send_scanned(
+        files: Vec<PathBuf>,
+        tx: &Sender<AppMessage>,
+        settings: &Arc<Mutex<Settings>>,
+    ) -> usize {
+        let (auto_convert, options, min_size_kb) = {
+            let s = settings.lock();
+            (s.auto_convert_webp, ConvertOptions::from_settings(&s), s.convert_min_size_kb)
+        };
+        // PNGs that didn't shrink last time are listed as they are
+        let (to_convert, files): (Vec<_>, Vec<_>) = files.into_iter().partition(|path| {
+            auto_convert
+                && convert::should_auto_convert(path, min_size_kb)
+                && kept_pngs::lookup(path, options).is_none()
+        });
+        let mut count = to_convert.len();
+        for path in to_convert {
+            Self::convert_scanned(path, options, tx);
+        }
+
+        for chunk in files.chunks(SCAN_BATCH) {
+            let infos: Vec<ScreenshotInfo> =
+                chunk.iter().filter_map(|path| ScreenshotInfo::from_path_ref(path)).collect();
+            count += infos.len();
+            if !infos.is_empty() {
+                let _ = tx.send(AppMessage::ScreenshotsScanned(infos));
+            }
+        }
+        count
+    }
+
+    /// Convert a PNG found by a scan in the background, then hand the result
+    /// (or the PNG, if that failed) to the UI. Only converted, not organized.
+    fn convert_scanned(path: PathBuf, options: ConvertOptions, tx: &Sender<AppMessage>) {
+        info!("Auto-converting existing screenshot to {:?}: {:?}", options.format, path);
+        pipeline::report(Transition::Converting(path.clone(), options.format), tx);
+        let tx = tx.clone();
+        let source = path.clone();
+        convert_pool::submit(source, options, Priority::Background, move |result| {
+            let to = match result {
+                Ok(output_path) => {
+                    info!("Converted: {:?} -> {:?}", path, output_path);
+                    // In case the PNG was listed already: it's gone now
+                    let _ = tx.send(AppMessage::ScreenshotRemoved(path.clone()));
+                    output_path
+                }
+                Err(e) => {
+                    if !e.is::<convert::NotSmaller>() {
+                        error!("Failed to convert {:?}: {}", path, e);
+                    }
+                    path.clone()
+                }
+            };
+            pipeline::report(
+                Transition::Finished {
+                    from: path,
+                    to: to.clone(),
+                },
+                &tx,
+            );
+            let _ = tx.send(AppMessage::NewScreenshot(to, false));
+        });
+    }
 
-        // Recursive scan function
-        fn scan_dir(dir: &Path, files: &mut Vec<PathBuf>) {
-            if let Ok(entries) = std::fs::read_dir(dir) {
-                for entry in entries.flatten() {
+    /// Every image under `dir`, newest first
+    pub fn collect_images(dir: &Path) -> Vec<PathBuf> {
+        Self::collect_newest(dir, usize::MAX).0
+    }
+
+    /// Images under `dir`, newest first, and whether the walk stopped after
+    /// finding more than `limit` of them. Each folder's entries are visited
+    /// newest first, so a capped walk of dated subfolders still keeps the
+    /// recent ones; only those found are stat'ed and sorted.
+    fn collect_newest(dir: &Path, limit: usize) -> (Vec<PathBuf>, bool) {
+        type Found = Vec<(Option<SystemTime>, PathBuf)>;
+
+        /// Walk `dir` into `files`; true once there are more than `limit`
+        fn scan_dir(dir: &Path, limit: usize, files: &mut Found) -> bool {
+            let Ok(entries) = std::fs::read_dir(dir) else {
+                return false;
+            };
+            // Listing metadata comes with the entry; links are followed
+            let mut entries: Vec<(std::fs::Metadata, PathBuf)> = entries
+                .flatten()
+                .filter_map(|entry| {
                     let path = entry.path();
-                    if path.is_dir() {
-                        // Recurse into subdirectories
-                        scan_dir(&path, files);
-                    } else if ScreenshotWatcher::is_image_file(&path) {
-                        files.push(path);
+                    let metadata = match entry.file_type() {
+                        Ok(kind) if kind.is_symlink() => std::fs::metadata(paths::long_path(&path)),
+                        _ => entry.metadata(),
+                    };
+                    metadata.ok().map(|metadata| (metadata, path))
+                })
+                .collect();
+            entries.sort_by_cached_key(|(metadata, _)| std::cmp::Reverse(metadata.modified().ok()));
+
+            for (metadata, path) in entries {
+                if metadata.is_dir() {
+                    if scan_dir(&path, limit, files) {
+                        return true;
+                    }
+                } else if metadata.is_file() && ScreenshotWatcher::has_image_extension(&path) {
+                    files.push((metadata.modified().ok(), path));
+                    if files.len() > limit {
+                        return true;
                     }
                 }
             }
+            false
         }
 
-        scan_dir(&self.directory, &mut files);
+        let mut files = Vec::new();
+        let more = scan_dir(dir, limit, &mut files);
+        files.sort_by(|a, b| b.0.cmp(&a.0));
+        files.truncate(limit);
+        (files.into_iter().map(|(_, path)| path).collect(), more)
+    }
 
-        // Sort by modified time (newest first)
-        files.sort_by(|a, b| {
-            let a_time = std::fs::metadata(a).and_then(|m| m.modified()).ok();
-            let b_time = std::fs::metadata(b).and_then(|m| m.modified()).ok();
-            b_time.cmp(&a_time)
-        });
+    /// Images created in a batch of events
+    fn created_images(events: &[DebouncedEvent]) -> usize {
+        events
+            .iter()
+            .filter(|event| matches!(event.kind, EventKind::Create(_)))
+            .flat_map(|event| &event.paths)
+            .filter(|path| Self::is_image_file(path))
+            .count()
+    }
 
-        for path in files {
-            debug!("Found existing screenshot: {:?}", path);
-            // Don't auto-index during initial scan (false)
-            let _ = self.message_tx.send(AppMessage::NewScreenshot(path, false));
-            count += 1;
+    /// Whether a batch with `created` new images is a bulk copy rather than
+    /// screenshots (0 = never)
+    fn is_burst(created: usize, threshold: usize) -> bool {
+        threshold > 0 && created > threshold
+    }
+
+    /// Pick up a bulk copy with one walk of the folder, like the startup scan:
+    /// the files are shown, but not converted, organized or indexed one by one
+    fn rescan(base_dir: &Path, tx: &Sender<AppMessage>, settings: &Arc<Mutex<Settings>>) {
+        let files = Self::scan_images(base_dir, tx, settings);
+        let count = Self::send_scanned(files, tx, settings);
+        info!("Rescanned {} screenshots after a burst of new files", count);
+    }
+
+    /// Images under `dir` for a scan, up to `large_directory_files`,
+    /// telling the UI if that left some out. A folder that big is rarely
+    /// only screenshots, so the walk stops there and the rest aren't loaded
+    /// or auto-converted.
+    fn scan_images(dir: &Path, tx: &Sender<AppMessage>, settings: &Arc<Mutex<Settings>>) -> Vec<PathBuf> {
+        let limit = settings.lock().large_directory_files;
+        let (files, more) = Self::collect_newest(dir, limit);
+        if more {
+            warn!("Scan found more than {} images, showing the newest found", limit);
+            let _ = tx.send(AppMessage::ScanCapped(limit));
         }
+        files
+    }
 
-        info!("Found {} existing screenshots", count);
-        Ok(())
+    /// Handle the events held while paused, once processing has resumed.
+    /// The lock is kept throughout so newer events wait their turn.
+    fn release_held(&self, jobs: &Sender<PipelineJob>) {
+        if is_paused() {
+            return;
+        }
+        let mut held = HELD.lock();
+        if held.is_empty() {
+            return;
+        }
+        let events = std::mem::take(&mut *held);
+        info!("Processing resumed, handling {} held events", events.len());
+        Self::handle_events(events, &self.message_tx, jobs, &self.directory, &self.settings);
     }
 
-    /// Handle debounced file system events
+    /// Handle debounced file system events, or hold them while paused (or
+    /// while older held events are still waiting)
     fn handle_debounced_events(
         result: DebounceEventResult,
         tx: &Sender<AppMessage>,
+        jobs: &Sender<PipelineJob>,
         base_dir: &Path,
         settings: &Arc<Mutex<Settings>>,
     ) {
         match result {
             Ok(events) => {
-                for event in events {
-                    Self::process_event(&event, tx, base_dir, settings);
+                let mut held = HELD.lock();
+                if is_paused() || !held.is_empty() {
+                    debug!("Holding {} events while paused", events.len());
+                    held.extend(events);
+                    return;
                 }
+                drop(held);
+                Self::handle_events(events, tx, jobs, base_dir, settings);
             }
             Err(errors) => {
                 for e in errors {
@@ -138,14 +535,48 @@ impl ScreenshotWatcher {
         }
     }
 
-    /// Process a single debounced event
-    fn process_event(
-        event: &notify_debouncer_full::DebouncedEvent,
+    /// Handle a batch of events: a bulk copy is rescanned, everything else
+    /// goes through [`Self::process_event`]
+    fn handle_events(
+        events: Vec<DebouncedEvent>,
         tx: &Sender<AppMessage>,
+        jobs: &Sender<PipelineJob>,
         base_dir: &Path,
         settings: &Arc<Mutex<Settings>>,
     ) {
-        use notify::EventKind;
+        let created = Self::created_images(&events);
+        let threshold = settings.lock().watcher_burst_threshold;
+        let burst = Self::is_burst(created, threshold);
+        if burst {
+            info!(
+                "{} new images in one batch (threshold {}), rescanning instead",
+                created, threshold
+            );
+            Self::rescan(base_dir, tx, settings);
+        }
+        // Deleting many files at once reads the Recycle Bin once
+        let mut bin = RecycleBin::default();
+        for event in events {
+            Self::process_event(&event, tx, jobs, burst, &mut bin);
+        }
+    }
+
+    /// Process a single debounced event. New files are queued for the
+    /// pipeline workers, unless `burst` says a rescan already picked them up.
+    /// `bin` is the Recycle Bin as read for this batch.
+    fn process_event(
+        event: &DebouncedEvent,
+        tx: &Sender<AppMessage>,
+        jobs: &Sender<PipelineJob>,
+        burst: bool,
+        bin: &mut RecycleBin,
+    ) {
+        use notify::event::ModifyKind;
+
+        if let EventKind::Modify(ModifyKind::Name(mode)) = &event.kind {
+            Self::process_rename(*mode, &event.paths, tx, jobs, burst, bin);
+            return;
+        }
 
         for path in &event.paths {
             // For Remove events, file no longer exists so we only check extension
@@ -160,74 +591,8 @@ impl ScreenshotWatcher {
             }
 
             match &event.kind {
-                EventKind::Create(_) => {
-                    info!("New screenshot detected: {:?}", path);
-
-                    // Check if organizer and/or auto-convert is enabled
-                    let (organizer_enabled, organizer_format, auto_convert, conversion_format, quality) = {
-                        let s = settings.lock();
-                        (
-                            s.organizer_enabled,
-                            s.organizer_format.clone(),
-                            s.auto_convert_webp,
-                            s.conversion_format,
-                            s.webp_quality,
-                        )
-                    };
-
-                    // Process in background thread
-                    let path_clone = path.clone();
-                    let base_dir = base_dir.to_path_buf();
-                    let tx = tx.clone();
-
-                    std::thread::spawn(move || {
-                        // Small delay to ensure file is fully written
-                        std::thread::sleep(Duration::from_millis(500));
-
-                        let mut current_path = path_clone.clone();
-
-                        // Step 1: Auto-convert if enabled (PNG -> WebP/JPEG)
-                        if auto_convert && convert::is_convertible(&current_path) {
-                            info!("Auto-converting screenshot: {:?}", current_path);
-                            match convert::convert_image(&current_path, conversion_format, quality) {
-                                Ok(new_path) => {
-                                    info!("Converted: {:?} -> {:?}", current_path, new_path);
-                                    current_path = new_path;
-                                }
-                                Err(e) => {
-                                    error!("Failed to convert screenshot: {}", e);
-                                }
-                            }
-                        }
-
-                        // Step 2: Organize if enabled (move to date-based subdirectory)
-                        if organizer_enabled {
-                            match organizer::organize_file(
-                                &current_path,
-                                &base_dir,
-                                &organizer_format,
-                            ) {
-                                Ok(Some(new_path)) => {
-                                    info!("Organized: {:?} -> {:?}", current_path, new_path);
-                                    current_path = new_path;
-                                }
-                                Ok(None) => {
-                                    // Already organized or in subdirectory
-                                }
-                                Err(e) => {
-                                    error!("Failed to organize screenshot: {}", e);
-                                }
-                            }
-                        }
-
-                        // Send final path to UI with auto-index flag (true for new screenshots)
-                        let _ = tx.send(AppMessage::NewScreenshot(current_path, true));
-                    });
-                }
-                EventKind::Remove(_) => {
-                    info!("Screenshot removed: {:?}", path);
-                    let _ = tx.send(AppMessage::ScreenshotRemoved(path.clone()));
-                }
+                EventKind::Create(_) => Self::report_created(path, tx, jobs, burst),
+                EventKind::Remove(_) => Self::report_removed(path, tx, bin),
                 EventKind::Modify(_) => {
                     // Modification might mean the file is fully written
                     debug!("Screenshot modified: {:?}", path);
@@ -237,16 +602,248 @@ impl ScreenshotWatcher {
         }
     }
 
+    /// Process a rename. Both names inside the tree make a rename the UI
+    /// applies in place (list, selection, thumbnail, index row), without
+    /// running the pipeline again; with only one of them inside, it's a
+    /// removal or a new file.
+    fn process_rename(
+        mode: RenameMode,
+        event_paths: &[PathBuf],
+        tx: &Sender<AppMessage>,
+        jobs: &Sender<PipelineJob>,
+        burst: bool,
+        bin: &mut RecycleBin,
+    ) {
+        match (mode, event_paths) {
+            (RenameMode::Both, [from, to]) if paths::long_path(to).is_dir() => {
+                // A folder (say a date folder): everything in it moved along
+                for new_path in Self::collect_images(to) {
+                    if let Ok(relative) = new_path.strip_prefix(to) {
+                        Self::report_renamed(&from.join(relative), &new_path, tx);
+                    }
+                }
+            }
+            (RenameMode::Both, [from, to]) => {
+                match (Self::has_image_extension(from), Self::is_image_file(to)) {
+                    (true, true) => Self::report_renamed(from, to, tx),
+                    // Renamed to something we don't show
+                    (true, false) => Self::report_removed(from, tx, bin),
+                    // Written under a temporary name and then given its real
+                    // one (metadata stripping does this too): listed, but not
+                    // put through the pipeline, which may be what wrote it
+                    (false, true) => {
+                        debug!("Image renamed into place: {:?} -> {:?}", from, to);
+                        let _ = tx.send(AppMessage::NewScreenshot(to.clone(), false));
+                    }
+                    (false, false) => {}
+                }
+            }
+            // Moved out of the watched tree
+            (RenameMode::From, [from]) if Self::has_image_extension(from) => {
+                Self::report_removed(from, tx, bin)
+            }
+            // Moved in from outside it
+            (RenameMode::To, [to]) if Self::is_image_file(to) => {
+                Self::report_created(to, tx, jobs, burst)
+            }
+            _ => debug!("Unpaired rename ignored: {:?} {:?}", mode, event_paths),
+        }
+    }
+
+    /// A new image in the tree: queued for the pipeline
+    fn report_created(path: &Path, tx: &Sender<AppMessage>, jobs: &Sender<PipelineJob>, burst: bool) {
+        if burst {
+            return;
+        }
+        // Back from the Recycle Bin: not a new screenshot, skip the pipeline
+        if removals::is_pending(path) {
+            info!("Screenshot restored: {:?}", path);
+            let _ = tx.send(AppMessage::NewScreenshot(path.to_path_buf(), false));
+            return;
+        }
+        info!("New screenshot detected: {:?}", path);
+        timeline::record(path, timeline::EventKind::Detected);
+        let _ = jobs.send(PipelineJob {
+            path: path.to_path_buf(),
+        });
+    }
+
+    /// An image gone from the tree
+    fn report_removed(path: &Path, tx: &Sender<AppMessage>, bin: &mut RecycleBin) {
+        // Before the UI hears of it, so it knows whether to keep metadata
+        let removal = removals::classify(path, bin);
+        info!(
+            "Screenshot removed: {:?} ({})",
+            path,
+            match removal {
+                Removal::Recycled => "recycled",
+                Removal::Gone => "gone",
+            }
+        );
+        pipeline::report(Transition::Removed(path.to_path_buf()), tx);
+        let _ = tx.send(AppMessage::ScreenshotRemoved(path.to_path_buf()));
+    }
+
+    /// An image renamed or moved within the tree, by us (the organizer) or
+    /// by someone else (Explorer, another instance)
+    fn report_renamed(from: &Path, to: &Path, tx: &Sender<AppMessage>) {
+        info!("Screenshot renamed: {:?} -> {:?}", from, to);
+        timeline::record_renamed(from, to);
+        pipeline::report(
+            Transition::Moved {
+                from: from.to_path_buf(),
+                to: to.to_path_buf(),
+            },
+            tx,
+        );
+        let _ = tx.send(AppMessage::ScreenshotRenamed(from.to_path_buf(), to.to_path_buf()));
+    }
+
+    /// Rename, convert and organize a new screenshot (on a pipeline worker),
+    /// then hand the final path to the UI
+    fn run_pipeline(
+        path: PathBuf,
+        tx: &Sender<AppMessage>,
+        base_dir: &Path,
+        settings: &Arc<Mutex<Settings>>,
+    ) {
+        // Let whoever is writing it finish first, or conversion reads half a file
+        if let Err(e) = convert::wait_for_file_ready(&path) {
+            if !paths::long_path(&path).exists() {
+                debug!("{:?} went away before it was processed", path);
+                return;
+            }
+            // Listed as it is; converting or moving a file still being
+            // written would break it
+            warn!("Not processing {:?}: {:#}", path, e);
+            pipeline::report(
+                Transition::Finished {
+                    from: path.clone(),
+                    to: path.clone(),
+                },
+                tx,
+            );
+            let _ = tx.send(AppMessage::NewScreenshot(path, false));
+            return;
+        }
+
+        // Check if renaming, organizer and/or auto-convert is enabled
+        let (
+            name_pattern,
+            organizer_enabled,
+            organizer_format,
+            auto_convert,
+            convert_options,
+            min_size_kb,
+        ) = {
+            let s = settings.lock();
+            (
+                s.rename_new_screenshots.then(|| s.file_name_pattern.clone()),
+                s.organizer_enabled,
+                s.organizer_format.clone(),
+                s.auto_convert_webp,
+                ConvertOptions::from_settings(&s),
+                s.convert_min_size_kb,
+            )
+        };
+
+        let mut current_path = path.clone();
+
+        // Step 0: Rename after the file name pattern if enabled
+        if let Some(pattern) = name_pattern {
+            match naming::rename_to_pattern(&current_path, &pattern) {
+                Ok(Some(new_path)) => {
+                    info!("Renamed: {:?} -> {:?}", current_path, new_path);
+                    current_path = new_path;
+                }
+                Ok(None) => {}
+                Err(e) => {
+                    error!("Failed to rename screenshot: {}", e);
+                }
+            }
+        }
+
+        // Step 1: Auto-convert if enabled (PNG -> WebP/JPEG/AVIF), unless it's too small to bother
+        if auto_convert && convert::should_auto_convert(&current_path, min_size_kb) {
+            info!("Auto-converting screenshot: {:?}", current_path);
+            pipeline::report(Transition::Converting(current_path.clone(), convert_options.format), tx);
+            let converted = convert_pool::convert(current_path.clone(), convert_options, Priority::New);
+            match converted {
+                Some(Ok(new_path)) => {
+                    info!("Converted: {:?} -> {:?}", current_path, new_path);
+                    current_path = new_path;
+                }
+                // Kept as a PNG; convert_image logged why
+                Some(Err(e)) if e.is::<convert::NotSmaller>() => {}
+                Some(Err(e)) => {
+                    error!("Failed to convert screenshot: {}", e);
+                }
+                // Reported twice; the conversion already under way finishes it
+                None => {
+                    debug!("Already converting {:?}", current_path);
+                    return;
+                }
+            }
+        }
+
+        // Step 2: Organize if enabled (move to date-based subdirectory)
+        if organizer_enabled {
+            match organizer::organize_file(&current_path, base_dir, &organizer_format) {
+                Ok(Some(new_path)) => {
+                    info!("Organized: {:?} -> {:?}", current_path, new_path);
+                    current_path = new_path;
+                }
+                Ok(None) => {
+                    // Already organized or in subdirectory
+                }
+                Err(e) => {
+                    error!("Failed to organize screenshot: {}", e);
+                }
+            }
+        }
+
+        pipeline::report(
+            Transition::Finished {
+                from: path,
+                to: current_path.clone(),
+            },
+            tx,
+        );
+
+        // Send final path to UI with auto-index flag (true for new screenshots)
+        let _ = tx.send(AppMessage::NewScreenshot(current_path, true));
+    }
+
+    /// Count images in a directory tree (used to confirm a directory switch)
+    pub fn count_images(dir: &Path) -> usize {
+        let Ok(entries) = std::fs::read_dir(paths::long_path(dir)) else {
+            return 0;
+        };
+        entries
+            .flatten()
+            .map(|entry| {
+                let path = dir.join(entry.file_name());
+                if entry.file_type().is_ok_and(|t| t.is_dir()) {
+                    Self::count_images(&path)
+                } else if Self::has_image_extension(&path) {
+                    1
+                } else {
+                    0
+                }
+            })
+            .sum()
+    }
+
     /// Check if a path is an image file we care about (file must exist)
     fn is_image_file(path: &Path) -> bool {
-        if !path.is_file() {
+        if !paths::long_path(path).is_file() {
             return false;
         }
         Self::has_image_extension(path)
     }
 
-    /// Check if a path has an image extension (doesn't check if file exists)
-    /// Used for Remove events where the file no longer exists
+    /// Check if a path has an image extension and isn't excluded (doesn't
+    /// check if file exists). Used for Remove events where the file no longer exists
     fn has_image_extension(path: &Path) -> bool {
         path.extension()
             .and_then(|ext| ext.to_str())
@@ -255,5 +852,199 @@ impl ScreenshotWatcher {
                     .iter()
                     .any(|&e| e.eq_ignore_ascii_case(ext))
             })
+            && !exclude::is_excluded(path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::scratch_dir;
+    use notify::event::ModifyKind;
+
+    fn event(kind: EventKind, path: &Path) -> DebouncedEvent {
+        DebouncedEvent::new(Event::new(kind).add_path(path.to_path_buf()), Instant::now())
+    }
+
+    #[test]
+    fn test_burst_detection() {
+        let dir = scratch_dir("watcher");
+
+        let mut events = Vec::new();
+        for i in 0..5 {
+            let path = dir.join(format!("{i}.png"));
+            std::fs::write(&path, b"png").unwrap();
+            events.push(event(EventKind::Create(CreateKind::File), &path));
+        }
+        // Removals and non-images don't count
+        std::fs::write(dir.join("notes.txt"), b"txt").unwrap();
+        events.push(event(EventKind::Create(CreateKind::File), &dir.join("notes.txt")));
+        events.push(event(EventKind::Remove(RemoveKind::File), &dir.join("gone.png")));
+
+        let created = ScreenshotWatcher::created_images(&events);
+        assert_eq!(created, 5);
+        assert!(!ScreenshotWatcher::is_burst(created, 5));
+        assert!(ScreenshotWatcher::is_burst(created, 4));
+        assert!(!ScreenshotWatcher::is_burst(created, 0));
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_collect_newest_stops_at_limit() {
+        let dir = scratch_dir("watcher-collect");
+        let at = |secs: i64| filetime::FileTime::from_unix_time(1_700_000_000 + secs, 0);
+
+        // Two dated folders; the newer one's files are the newest overall
+        let mut expected = Vec::new();
+        for (folder, base) in [("2024-01", 0), ("2024-02", 100)] {
+            let folder = dir.join(folder);
+            std::fs::create_dir_all(&folder).unwrap();
+            for i in 0..3 {
+                let path = folder.join(format!("{i}.png"));
+                std::fs::write(&path, b"png").unwrap();
+                filetime::set_file_mtime(&path, at(base + i)).unwrap();
+                expected.push(path);
+            }
+            filetime::set_file_mtime(&folder, at(base + 10)).unwrap();
+        }
+        std::fs::write(dir.join("notes.txt"), b"txt").unwrap();
+        expected.reverse();
+
+        let (files, more) = ScreenshotWatcher::collect_newest(&dir, 10);
+        assert_eq!(files, expected);
+        assert!(!more);
+
+        // Stops in the older folder, after its newest file
+        let (files, more) = ScreenshotWatcher::collect_newest(&dir, 4);
+        assert_eq!(files, expected[..4]);
+        assert!(more);
+
+        assert_eq!(ScreenshotWatcher::collect_images(&dir), expected);
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    fn rename(mode: RenameMode, paths: &[&Path]) -> DebouncedEvent {
+        let mut event = Event::new(EventKind::Modify(ModifyKind::Name(mode)));
+        for path in paths {
+            event = event.add_path(path.to_path_buf());
+        }
+        DebouncedEvent::new(event, Instant::now())
+    }
+
+    #[test]
+    fn test_renames() {
+        let dir = scratch_dir("watcher-renames");
+        std::fs::create_dir_all(dir.join("2024-01-15")).unwrap();
+        let (tx, rx) = crossbeam_channel::unbounded();
+        let (jobs_tx, jobs_rx) = crossbeam_channel::unbounded();
+        let mut bin = RecycleBin::default();
+        let messages = || rx.try_iter().filter(|m| !matches!(m, AppMessage::PipelineTransition(_))).collect::<Vec<_>>();
+
+        // Renamed in place: not new, so nothing is queued for the pipeline
+        let (old, new) = (dir.join("shot.png"), dir.join("bug.png"));
+        std::fs::write(&new, b"png").unwrap();
+        ScreenshotWatcher::process_event(&rename(RenameMode::Both, &[&old, &new]), &tx, &jobs_tx, false, &mut bin);
+        assert!(matches!(
+            messages().as_slice(),
+            [AppMessage::ScreenshotRenamed(from, to)] if *from == old && *to == new
+        ));
+        assert!(jobs_rx.try_recv().is_err());
+
+        // A renamed folder carries its images along
+        let folder = dir.join("2024-01-16");
+        std::fs::write(dir.join("2024-01-15").join("a.png"), b"png").unwrap();
+        std::fs::rename(dir.join("2024-01-15"), &folder).unwrap();
+        ScreenshotWatcher::process_event(
+            &rename(RenameMode::Both, &[&dir.join("2024-01-15"), &folder]),
+            &tx,
+            &jobs_tx,
+            false,
+            &mut bin,
+        );
+        assert!(matches!(
+            messages().as_slice(),
+            [AppMessage::ScreenshotRenamed(from, to)]
+                if *from == dir.join("2024-01-15").join("a.png") && *to == folder.join("a.png")
+        ));
+
+        // Given its real name after being written: listed, not processed
+        let temp = dir.join("capture.png.tmp");
+        let done = dir.join("capture.png");
+        std::fs::write(&done, b"png").unwrap();
+        ScreenshotWatcher::process_event(&rename(RenameMode::Both, &[&temp, &done]), &tx, &jobs_tx, false, &mut bin);
+        assert!(matches!(
+            messages().as_slice(),
+            [AppMessage::NewScreenshot(path, false)] if *path == done
+        ));
+        assert!(jobs_rx.try_recv().is_err());
+
+        // Renamed to something we don't show, or moved out of the folder
+        let notes = dir.join("notes.txt");
+        std::fs::write(&notes, b"txt").unwrap();
+        ScreenshotWatcher::process_event(&rename(RenameMode::Both, &[&new, &notes]), &tx, &jobs_tx, false, &mut bin);
+        ScreenshotWatcher::process_event(&rename(RenameMode::From, &[&done]), &tx, &jobs_tx, false, &mut bin);
+        let removed: Vec<PathBuf> = messages()
+            .into_iter()
+            .filter_map(|m| match m {
+                AppMessage::ScreenshotRemoved(path) => Some(path),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(removed, vec![new.clone(), done.clone()]);
+
+        // Moved in from outside: a new screenshot
+        ScreenshotWatcher::process_event(&rename(RenameMode::To, &[&done]), &tx, &jobs_tx, false, &mut bin);
+        assert_eq!(jobs_rx.try_recv().map(|job| job.path).ok(), Some(done));
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    /// Paths of the screenshots sent within `timeout`
+    fn screenshots(rx: &Receiver<AppMessage>, timeout: Duration) -> Vec<PathBuf> {
+        let mut paths = Vec::new();
+        while let Ok(msg) = rx.recv_timeout(timeout) {
+            match msg {
+                AppMessage::NewScreenshot(path, _) => paths.push(path),
+                AppMessage::ScreenshotsScanned(infos) => {
+                    paths.extend(infos.into_iter().map(|info| info.path))
+                }
+                _ => {}
+            }
+        }
+        paths
+    }
+
+    #[test]
+    fn test_change_directory() {
+        let root = scratch_dir("watcher-dirs");
+        let (old_dir, new_dir) = (root.join("old"), root.join("new"));
+        std::fs::create_dir_all(&old_dir).unwrap();
+        std::fs::write(old_dir.join("old.png"), b"png").unwrap();
+
+        let (tx, rx) = crossbeam_channel::unbounded();
+        let (control_tx, control_rx) = crossbeam_channel::unbounded();
+        let settings = Arc::new(Mutex::new(Settings::default()));
+        let watcher = ScreenshotWatcher::new(old_dir.clone(), tx, settings);
+        let handle = std::thread::spawn(move || watcher.run(control_rx));
+        let wait = Duration::from_millis(500);
+        assert_eq!(screenshots(&rx, wait), vec![old_dir.join("old.png")]);
+
+        // Same directory: nothing rescanned
+        control_tx.send(WatcherCommand::ChangeDirectory(old_dir.clone())).unwrap();
+        assert!(screenshots(&rx, wait).is_empty());
+
+        // A directory that doesn't exist yet is created and watched
+        control_tx.send(WatcherCommand::ChangeDirectory(new_dir.clone())).unwrap();
+        std::thread::sleep(wait);
+        assert!(new_dir.is_dir());
+        std::fs::write(old_dir.join("ignored.png"), b"png").unwrap();
+        std::fs::write(new_dir.join("new.jpg"), b"jpg").unwrap();
+        assert_eq!(screenshots(&rx, Duration::from_secs(3)), vec![new_dir.join("new.jpg")]);
+
+        drop(control_tx);
+        handle.join().unwrap().unwrap();
+        let _ = std::fs::remove_dir_all(&root);
     }
 }